@@ -17,6 +17,7 @@
   mesa,
   libdisplay-info,
   lua,
+  pipewire,
   gitRev ? null,
 }:
 rustPlatform.buildRustPackage (finalAttrs: {
@@ -30,7 +31,7 @@ rustPlatform.buildRustPackage (finalAttrs: {
     allowBuiltinFetchGit = true;
   };
 
-  nativeBuildInputs = [pkg-config];
+  nativeBuildInputs = [pkg-config rustPlatform.bindgenHook];
 
   buildInputs = [
     wayland
@@ -48,8 +49,13 @@ rustPlatform.buildRustPackage (finalAttrs: {
     mesa
     libdisplay-info
     lua
+    pipewire
   ];
 
+  postInstall = ''
+    install -Dm644 resources/raven.portal -t $out/share/xdg-desktop-portal/portals
+  '';
+
   doCheck = false;
 
   meta = {
@@ -1,26 +1,153 @@
-use crate::Raven;
+//! Action execution shared by every trigger source.
+
+use crate::{Raven, config::KeybindAction};
 use smithay::{desktop::Window, utils::SERIAL_COUNTER};
 
-pub enum Action {
-    FocusNext,
-    FocusPrevious,
-}
+/// An action as named in keybinds and listed by `raven actions`.
+pub type Action = KeybindAction;
+
+// How much `resize_left`/`resize_right` change `master_factor`.
+const MASTER_FACTOR_STEP: f32 = 0.05;
+// How much the `resize_grow_*`/`resize_shrink_*` actions change a tile's weight.
+const TILE_WEIGHT_STEP: f32 = 0.1;
 
 enum Direction {
     Next,
     Previous,
 }
 
-impl Action {
-    pub fn execute(self, raven: &mut Raven) {
-        match self {
-            Action::FocusNext => {
-                change_focus(Direction::Next, raven);
-            }
-            Action::FocusPrevious => {
-                change_focus(Direction::Previous, raven);
-            }
-        };
+impl Raven {
+    /// Run an action outside of key handling (e.g. from IPC).
+    pub fn dispatch_action(&mut self, action: Action) {
+        dispatch(self, action);
+    }
+}
+
+/// Run `action`. Keybinds, sway IPC commands and every other trigger source go
+/// through here, so an action added to `KeybindAction` works from all of them.
+pub fn dispatch(state: &mut Raven, action: Action) {
+    match action {
+        Action::Exec(command) => state.spawn_keybind_command(&command),
+        Action::Terminal => state.spawn_terminal(),
+        Action::Launcher => state.spawn_launcher(),
+        Action::CloseFocused => close_focused_window(state, false),
+        Action::ForceCloseFocused => close_focused_window(state, true),
+        Action::ToggleFullscreen => {
+            state
+                .toggle_fullscreen_focused_window()
+                .map_err(|err| tracing::warn!("failed to toggle fullscreen: {err}"))
+                .ok();
+        }
+        Action::ToggleFloating => {
+            state
+                .toggle_floating_focused_window()
+                .map_err(|err| tracing::warn!("failed to toggle floating: {err}"))
+                .ok();
+        }
+        Action::TogglePin => {
+            state
+                .toggle_pin_focused_window()
+                .map_err(|err| tracing::warn!("failed to toggle pin: {err}"))
+                .ok();
+        }
+        Action::Quit => state.loop_signal.stop(),
+        Action::FocusNext => change_focus(Direction::Next, state),
+        Action::FocusPrevious => change_focus(Direction::Previous, state),
+        Action::ReloadConfig => {
+            state
+                .reload_config()
+                .map_err(|err| tracing::warn!("failed to reload config: {err}"))
+                .ok();
+        }
+        Action::SwitchWorkspace(workspace_index) => {
+            state
+                .switch_workspace(workspace_index)
+                .map_err(|err| tracing::warn!("failed to switch workspace: {err}"))
+                .ok();
+        }
+        Action::SwitchToEmptyWorkspace => {
+            state
+                .switch_to_empty_workspace()
+                .map_err(|err| tracing::warn!("failed to switch workspace: {err}"))
+                .ok();
+        }
+        Action::MoveFocusedToWorkspace(workspace_index) => {
+            state
+                .move_focused_window_to_workspace(workspace_index)
+                .map_err(|err| tracing::warn!("failed to move window to workspace: {err}"))
+                .ok();
+        }
+        Action::SwapWorkspaces(first, second) => {
+            state
+                .swap_workspaces(first, second)
+                .map_err(|err| tracing::warn!("failed to swap workspaces: {err}"))
+                .ok();
+        }
+        Action::ReorderWorkspace { from, to } => {
+            state
+                .reorder_workspace(from, to)
+                .map_err(|err| tracing::warn!("failed to reorder workspaces: {err}"))
+                .ok();
+        }
+        Action::PreviousWorkspace => {
+            state
+                .switch_workspace_relative(false)
+                .map_err(|err| tracing::warn!("failed to switch workspace: {err}"))
+                .ok();
+        }
+        Action::NextWorkspace => {
+            state
+                .switch_workspace_relative(true)
+                .map_err(|err| tracing::warn!("failed to switch workspace: {err}"))
+                .ok();
+        }
+        Action::WorkspaceBackAndForth => {
+            state
+                .workspace_back_and_forth()
+                .map_err(|err| tracing::warn!("failed to switch workspace: {err}"))
+                .ok();
+        }
+        Action::SetLayout(layout_type) => state.set_layout(layout_type),
+        Action::CycleLayout => state.set_layout(state.layout_type().next()),
+        Action::AdjustSplitRatio(delta) => state.adjust_focused_split(delta),
+        Action::FocusColumnLeft => state.focus_adjacent_tiled(false),
+        Action::FocusColumnRight => state.focus_adjacent_tiled(true),
+        Action::ShrinkMaster => state.adjust_master_factor(-MASTER_FACTOR_STEP),
+        Action::GrowMaster => state.adjust_master_factor(MASTER_FACTOR_STEP),
+        Action::SwapMaster => state.swap_focused_with_master(),
+        Action::SwapDirection(direction) => state.swap_focused_in_direction(direction),
+        Action::CycleWindows => state.cycle_windows(true),
+        Action::CycleWindowsBack => state.cycle_windows(false),
+        Action::GrowTileWidth => state.resize_focused_tile(TILE_WEIGHT_STEP, 0.0),
+        Action::ShrinkTileWidth => state.resize_focused_tile(-TILE_WEIGHT_STEP, 0.0),
+        Action::GrowTileHeight => state.resize_focused_tile(0.0, TILE_WEIGHT_STEP),
+        Action::ShrinkTileHeight => state.resize_focused_tile(0.0, -TILE_WEIGHT_STEP),
+        Action::TogglePerformanceMode => {
+            let enabled = !state.performance_mode();
+            state
+                .set_performance_mode(enabled)
+                .map_err(|err| tracing::warn!("failed to toggle performance mode: {err}"))
+                .ok();
+        }
+        Action::FindCursor => state.find_cursor(),
+        Action::Dpms(mode) => {
+            state
+                .set_dpms(None, mode)
+                .map_err(|err| tracing::warn!("failed to set output power: {err}"))
+                .ok();
+        }
+        Action::FocusUrgent => state.focus_urgent(),
+        Action::MinimizeFocused => state.minimize_focused_window(),
+    }
+}
+
+fn close_focused_window(state: &mut Raven, force: bool) {
+    let keyboard = state.seat.get_keyboard().unwrap();
+    if let Some(focused_surface) = keyboard.current_focus()
+        && let Some(window) = state.window_for_surface(&focused_surface)
+        && let Err(err) = state.close_window(&window, force)
+    {
+        tracing::warn!("failed to close focused window: {err}");
     }
 }
 
@@ -0,0 +1,187 @@
+//! Output capture helpers for `raven screenshot`: stitching per-output captures into
+//! one image and encoding it as PNG, and copying captures into clients' shm buffers.
+
+use image::{ExtendedColorType, ImageEncoder, codecs::png::PngEncoder};
+use smithay::reexports::wayland_server::protocol::{wl_buffer::WlBuffer, wl_shm::Format};
+use smithay::utils::{Logical, Physical, Rectangle, Size};
+use smithay::wayland::shm;
+
+use crate::CompositorError;
+
+/// Pixels read back from one output, with where that output sits in the layout.
+#[derive(Clone, Debug)]
+pub struct OutputCapture {
+    pub name: String,
+    /// Output geometry in the global logical space.
+    pub logical: Rectangle<i32, Logical>,
+    /// Size of `pixels` in physical pixels.
+    pub size: Size<i32, Physical>,
+    /// Tightly packed RGBA8 rows, top to bottom.
+    pub pixels: Vec<u8>,
+}
+
+impl OutputCapture {
+    fn scale(&self) -> f64 {
+        if self.logical.size.w <= 0 {
+            return 1.0;
+        }
+        f64::from(self.size.w) / f64::from(self.logical.size.w)
+    }
+}
+
+/// An RGBA8 image.
+#[derive(Debug)]
+pub struct StitchedImage {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<u8>,
+}
+
+/// Composite `captures` into one canvas laid out like the outputs.
+///
+/// The canvas uses the largest output scale so HiDPI outputs keep full detail;
+/// lower-scale outputs are upscaled (nearest neighbour) into their logical area.
+/// Gaps between outputs stay transparent.
+pub fn stitch_captures(captures: &[OutputCapture]) -> Option<StitchedImage> {
+    let (bounds, canvas_scale) = canvas_layout(captures)?;
+
+    let width = (f64::from(bounds.size.w) * canvas_scale).ceil() as usize;
+    let height = (f64::from(bounds.size.h) * canvas_scale).ceil() as usize;
+    let mut pixels = vec![0u8; width * height * 4];
+
+    for capture in captures {
+        let src_w = capture.size.w.max(0) as usize;
+        let src_h = capture.size.h.max(0) as usize;
+        if src_w == 0 || src_h == 0 || capture.pixels.len() < src_w * src_h * 4 {
+            tracing::warn!(output = %capture.name, "skipping malformed capture while stitching");
+            continue;
+        }
+
+        let offset = capture.logical.loc - bounds.loc;
+        let dst_x = (f64::from(offset.x) * canvas_scale).round() as usize;
+        let dst_y = (f64::from(offset.y) * canvas_scale).round() as usize;
+        let dst_w = ((f64::from(capture.logical.size.w) * canvas_scale).round() as usize)
+            .min(width.saturating_sub(dst_x));
+        let dst_h = ((f64::from(capture.logical.size.h) * canvas_scale).round() as usize)
+            .min(height.saturating_sub(dst_y));
+
+        for y in 0..dst_h {
+            let src_y = (y * src_h / dst_h.max(1)).min(src_h - 1);
+            let dst_row = ((dst_y + y) * width + dst_x) * 4;
+            if dst_w == src_w {
+                let src_row = src_y * src_w * 4;
+                pixels[dst_row..dst_row + dst_w * 4]
+                    .copy_from_slice(&capture.pixels[src_row..src_row + src_w * 4]);
+                continue;
+            }
+            for x in 0..dst_w {
+                let src_x = (x * src_w / dst_w.max(1)).min(src_w - 1);
+                let src = (src_y * src_w + src_x) * 4;
+                let dst = dst_row + x * 4;
+                pixels[dst..dst + 4].copy_from_slice(&capture.pixels[src..src + 4]);
+            }
+        }
+    }
+
+    Some(StitchedImage {
+        width: width as u32,
+        height: height as u32,
+        pixels,
+    })
+}
+
+/// Logical bounds of `captures` and the scale the stitched canvas uses.
+fn canvas_layout(captures: &[OutputCapture]) -> Option<(Rectangle<i32, Logical>, f64)> {
+    let bounds = captures
+        .iter()
+        .map(|capture| capture.logical)
+        .reduce(|acc, rect| acc.merge(rect))?;
+    if bounds.size.w <= 0 || bounds.size.h <= 0 {
+        return None;
+    }
+    let canvas_scale = captures
+        .iter()
+        .map(OutputCapture::scale)
+        .fold(1.0_f64, f64::max);
+    Some((bounds, canvas_scale))
+}
+
+/// Stitch `captures` and cut out `region`, given in the global logical space.
+/// Parts of the region outside every output are dropped.
+pub fn stitch_region(
+    captures: &[OutputCapture],
+    region: Rectangle<i32, Logical>,
+) -> Option<StitchedImage> {
+    let (bounds, canvas_scale) = canvas_layout(captures)?;
+    let region = region.intersection(bounds)?;
+    let image = stitch_captures(captures)?;
+
+    let offset = region.loc - bounds.loc;
+    let x = ((f64::from(offset.x) * canvas_scale).round() as u32).min(image.width);
+    let y = ((f64::from(offset.y) * canvas_scale).round() as u32).min(image.height);
+    let width = ((f64::from(region.size.w) * canvas_scale).round() as u32).min(image.width - x);
+    let height = ((f64::from(region.size.h) * canvas_scale).round() as u32).min(image.height - y);
+    if width == 0 || height == 0 {
+        return None;
+    }
+
+    let row_len = width as usize * 4;
+    let mut pixels = Vec::with_capacity(row_len * height as usize);
+    for row in y..y + height {
+        let start = (row as usize * image.width as usize + x as usize) * 4;
+        pixels.extend_from_slice(&image.pixels[start..start + row_len]);
+    }
+    Some(StitchedImage {
+        width,
+        height,
+        pixels,
+    })
+}
+
+/// Encode an RGBA8 image as PNG.
+///
+/// Compression is not cheap on large captures; call this off the event loop.
+pub fn encode_png(image: &StitchedImage) -> Result<Vec<u8>, CompositorError> {
+    let mut png = Vec::new();
+    PngEncoder::new(&mut png)
+        .write_image(
+            &image.pixels,
+            image.width,
+            image.height,
+            ExtendedColorType::Rgba8,
+        )
+        .map_err(|err| CompositorError::Screencopy(format!("failed to encode png: {err}")))?;
+    Ok(png)
+}
+
+/// Copy a tightly packed RGBA capture of `size` into an XRGB8888 shm buffer,
+/// as handed out by screencopy clients.
+pub fn copy_rgba_to_shm(
+    buffer: &WlBuffer,
+    size: Size<i32, Physical>,
+    rgba: &[u8],
+) -> Result<(), CompositorError> {
+    let expected = size.w as usize * size.h as usize * 4;
+    if rgba.len() < expected {
+        return Err(CompositorError::Screencopy(format!(
+            "capture has {} bytes, expected {expected}",
+            rgba.len()
+        )));
+    }
+    shm::with_buffer_contents_mut(buffer, |shm_buffer, shm_len, buffer_data| {
+        if buffer_data.format != Format::Xrgb8888 || shm_len < expected {
+            return Err(CompositorError::Screencopy(
+                "buffer does not fit the capture".to_owned(),
+            ));
+        }
+        let dst = unsafe { std::slice::from_raw_parts_mut(shm_buffer.cast::<u8>(), shm_len) };
+        for (dst, src) in dst
+            .chunks_exact_mut(4)
+            .zip(rgba[..expected].chunks_exact(4))
+        {
+            dst.copy_from_slice(&[src[2], src[1], src[0], 0xff]);
+        }
+        Ok(())
+    })
+    .map_err(|err| CompositorError::Screencopy(format!("shm buffer: {err:?}")))?
+}
@@ -0,0 +1,98 @@
+//! Commit-to-present latency per window, for `raven stats --clients`.
+//!
+//! The last commit of each window is stamped when it arrives, moved to the frame
+//! that is queued with it, and turned into a latency sample when that frame's
+//! vblank comes back. Steady high numbers for one window next to low numbers for
+//! the rest point at the compositor holding frames back, not at a slow client.
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use smithay::reexports::wayland_server::{Resource, protocol::wl_surface::WlSurface};
+
+/// Upper bounds of the histogram buckets; one more bucket catches the rest.
+pub const BUCKET_BOUNDS: [Duration; 5] = [
+    Duration::from_millis(8),
+    Duration::from_millis(16),
+    Duration::from_millis(33),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+];
+
+#[derive(Clone, Debug, Default)]
+pub struct LatencyHistogram {
+    pub buckets: [u64; BUCKET_BOUNDS.len() + 1],
+    pub count: u64,
+    pub total: Duration,
+    pub max: Duration,
+}
+
+impl LatencyHistogram {
+    fn record(&mut self, latency: Duration) {
+        let bucket = BUCKET_BOUNDS
+            .iter()
+            .position(|bound| latency <= *bound)
+            .unwrap_or(BUCKET_BOUNDS.len());
+        self.buckets[bucket] += 1;
+        self.count += 1;
+        self.total += latency;
+        self.max = self.max.max(latency);
+    }
+
+    pub fn average(&self) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+        self.total / u32::try_from(self.count).unwrap_or(u32::MAX)
+    }
+}
+
+#[derive(Default)]
+pub struct CommitTiming {
+    // Newest commit of each window not yet handed to a frame.
+    pending: HashMap<WlSurface, Instant>,
+    histograms: HashMap<WlSurface, LatencyHistogram>,
+}
+
+impl CommitTiming {
+    pub fn note_commit(&mut self, surface: &WlSurface) {
+        self.pending.insert(surface.clone(), Instant::now());
+    }
+
+    /// Take the pending commits of `surfaces`, which are about to be shown.
+    pub fn take_pending<'a>(
+        &mut self,
+        surfaces: impl IntoIterator<Item = &'a WlSurface>,
+    ) -> Vec<(WlSurface, Instant)> {
+        surfaces
+            .into_iter()
+            .filter_map(|surface| {
+                self.pending
+                    .remove(surface)
+                    .map(|committed_at| (surface.clone(), committed_at))
+            })
+            .collect()
+    }
+
+    pub fn record_presented(&mut self, commits: Vec<(WlSurface, Instant)>, presented_at: Instant) {
+        if commits.is_empty() {
+            return;
+        }
+        for (surface, committed_at) in commits {
+            self.histograms
+                .entry(surface)
+                .or_default()
+                .record(presented_at.saturating_duration_since(committed_at));
+        }
+        self.histograms.retain(|surface, _| surface.is_alive());
+        self.pending.retain(|surface, _| surface.is_alive());
+    }
+
+    pub fn histograms(&self) -> impl Iterator<Item = (&WlSurface, &LatencyHistogram)> {
+        self.histograms
+            .iter()
+            .filter(|(surface, _)| surface.is_alive())
+    }
+}
@@ -0,0 +1,229 @@
+//! Headless rendering for integration tests (`test-capture` feature).
+//!
+//! Outputs are virtual and frames are rendered into offscreen textures on a
+//! surfaceless EGL context, then read back as RGBA. Nothing is presented, so
+//! tests can compare captures against golden images without a session or a
+//! DRM master. `raven --backend headless` runs the compositor the same way, with
+//! one virtual output and no rendering at all.
+
+use std::time::Duration;
+
+use smithay::{
+    backend::{
+        allocator::Fourcc,
+        egl::{EGLContext, EGLDevice, EGLDisplay},
+        renderer::{Bind, ExportMem, damage::OutputDamageTracker, gles::GlesRenderer},
+    },
+    desktop::layer_map_for_output,
+    output::{Mode, Output, PhysicalProperties, Scale as OutputScale, Subpixel},
+    reexports::calloop::{
+        EventLoop,
+        timer::{TimeoutAction, Timer},
+    },
+    utils::{Logical, Physical, Point, Rectangle, Size, Transform},
+};
+
+use crate::{
+    CompositorError, Raven,
+    backend::{BackendKind, capture::OutputCapture, udev::OutputFrame},
+    render_helpers::{OffscreenPool, SolidColorBuffer},
+};
+
+// Frame callback pacing for `--backend headless`, roughly 60 Hz.
+const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+pub struct HeadlessBackend {
+    renderer: GlesRenderer,
+    offscreen: OffscreenPool,
+}
+
+impl HeadlessBackend {
+    /// Create a renderer on the first EGL device, preferring a software rasterizer
+    /// so captures are identical across CI machines.
+    pub fn new() -> Result<Self, CompositorError> {
+        let mut devices: Vec<EGLDevice> = EGLDevice::enumerate()
+            .map_err(|err| CompositorError::Backend(format!("failed to list EGL devices: {err}")))?
+            .collect();
+        devices.sort_by_key(|device| !device.is_software());
+        let device = devices
+            .into_iter()
+            .next()
+            .ok_or_else(|| CompositorError::Backend("no EGL device available".to_owned()))?;
+
+        let display = unsafe { EGLDisplay::new(device) }.map_err(|err| {
+            CompositorError::Backend(format!("failed to create EGL display: {err}"))
+        })?;
+        let context = EGLContext::new(&display).map_err(|err| {
+            CompositorError::Backend(format!("failed to create EGL context: {err}"))
+        })?;
+        let renderer = unsafe { GlesRenderer::new(context) }
+            .map_err(|err| CompositorError::Backend(format!("failed to create renderer: {err}")))?;
+
+        Ok(Self {
+            renderer,
+            offscreen: OffscreenPool::default(),
+        })
+    }
+
+    /// Add a virtual output at `location` and relayout.
+    pub fn add_output(
+        &mut self,
+        state: &mut Raven,
+        name: &str,
+        size: impl Into<Size<i32, Physical>>,
+        scale: f64,
+        location: impl Into<Point<i32, Logical>>,
+    ) -> Result<Output, CompositorError> {
+        add_virtual_output(state, name, size, scale, location)
+    }
+
+    /// Render `output` as it would be presented right now and read it back.
+    ///
+    /// Pending interactive layout updates are flushed first, and frame callbacks
+    /// are sent afterwards so clients can commit their next frame before the
+    /// following capture.
+    pub fn render_output(
+        &mut self,
+        state: &mut Raven,
+        output: &Output,
+    ) -> Result<OutputCapture, CompositorError> {
+        state.flush_interactive_frame_updates();
+
+        let logical = state.space.output_geometry(output).ok_or_else(|| {
+            CompositorError::Screencopy(format!("output {} is not mapped", output.name()))
+        })?;
+        let mode = output.current_mode().ok_or_else(|| {
+            CompositorError::Screencopy(format!("output {} has no mode", output.name()))
+        })?;
+        let size = output.current_transform().transform_size(mode.size);
+        let buffer_size = size.to_logical(1).to_buffer(1, Transform::Normal);
+
+        let mut target = self
+            .offscreen
+            .acquire(&mut self.renderer, Fourcc::Abgr8888, buffer_size)
+            .map_err(|err| CompositorError::Screencopy(format!("create_buffer: {err:?}")))?;
+        let mut framebuffer = self
+            .renderer
+            .bind(target.texture())
+            .map_err(|err| CompositorError::Screencopy(format!("bind: {err:?}")))?;
+
+        // The same elements the udev backend presents, so goldens match real runs.
+        let frame = OutputFrame::new(state, output);
+        let backdrop = SolidColorBuffer::new(logical.size.to_f64(), state.config.background_color);
+        let elements = frame.elements(&mut self.renderer, &state.space, &backdrop);
+
+        // A fresh tracker every time: age 0 forces a full redraw of the frame.
+        let mut damage_tracker = OutputDamageTracker::from_output(output);
+        let result = damage_tracker
+            .render_output(
+                &mut self.renderer,
+                &mut framebuffer,
+                0,
+                &elements,
+                state.config.background_color,
+            )
+            .map_err(|err| CompositorError::Screencopy(format!("render_output: {err:?}")))?;
+        if let Err(err) = result.sync.wait() {
+            tracing::warn!("error waiting for headless render: {err:?}");
+        }
+
+        let mapping = self
+            .renderer
+            .copy_framebuffer(
+                &framebuffer,
+                Rectangle::from_size(buffer_size),
+                Fourcc::Abgr8888,
+            )
+            .map_err(|err| CompositorError::Screencopy(format!("copy_framebuffer: {err:?}")))?;
+        let pixels = self
+            .renderer
+            .map_texture(&mapping)
+            .map_err(|err| CompositorError::Screencopy(format!("map_texture: {err:?}")))?
+            .to_vec();
+
+        send_frame_callbacks(state, output);
+
+        Ok(OutputCapture {
+            name: output.name(),
+            logical,
+            size,
+            pixels,
+        })
+    }
+}
+
+fn add_virtual_output(
+    state: &mut Raven,
+    name: &str,
+    size: impl Into<Size<i32, Physical>>,
+    scale: f64,
+    location: impl Into<Point<i32, Logical>>,
+) -> Result<Output, CompositorError> {
+    let mode = Mode {
+        size: size.into(),
+        refresh: 60_000,
+    };
+    let output = Output::new(
+        name.to_owned(),
+        PhysicalProperties {
+            size: (0, 0).into(),
+            subpixel: Subpixel::Unknown,
+            make: "raven".into(),
+            model: "headless".into(),
+            serial_number: "Unknown".into(),
+        },
+    );
+    output.create_global::<Raven>(&state.display_handle);
+    output.change_current_state(
+        Some(mode),
+        Some(Transform::Normal),
+        Some(OutputScale::Fractional(scale)),
+        None,
+    );
+    output.set_preferred(mode);
+
+    state.space.map_output(&output, location);
+    state.apply_layout()?;
+    state.backend_kind = BackendKind::Headless;
+    Ok(output)
+}
+
+/// Start `--backend headless`: one 1920x1080 virtual output that is never
+/// rendered, with frame callbacks sent on a timer so clients keep drawing.
+pub fn init_headless(
+    event_loop: &mut EventLoop<Raven>,
+    state: &mut Raven,
+) -> Result<(), CompositorError> {
+    let output = add_virtual_output(state, "HEADLESS-1", (1920, 1080), 1.0, (0, 0))?;
+    event_loop
+        .handle()
+        .insert_source(Timer::from_duration(FRAME_INTERVAL), move |_, _, state| {
+            send_frame_callbacks(state, &output);
+            TimeoutAction::ToDuration(FRAME_INTERVAL)
+        })
+        .map_err(|err| {
+            CompositorError::EventLoop(format!("failed to schedule headless frames: {err}"))
+        })?;
+    Ok(())
+}
+
+fn send_frame_callbacks(state: &mut Raven, output: &Output) {
+    let now = state.start_time.elapsed();
+    for window in state.space.elements() {
+        if !crate::frame_limit::frame_due(state, window, now) {
+            continue;
+        }
+        window.send_frame(output, now, Some(Duration::ZERO), |_, _| {
+            Some(output.clone())
+        });
+    }
+    for layer in layer_map_for_output(output).layers() {
+        layer.send_frame(output, now, Some(Duration::ZERO), |_, _| {
+            Some(output.clone())
+        });
+    }
+    state.space.refresh();
+    if let Err(err) = state.display_handle.flush_clients() {
+        tracing::warn!("failed to flush clients after headless render: {err}");
+    }
+}
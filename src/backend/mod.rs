@@ -1,2 +1,28 @@
+pub mod capture;
+pub mod commit_timing;
+#[cfg(feature = "test-capture")]
+pub mod headless;
 pub mod udev;
 pub mod winit;
+
+/// The backend driving the outputs, for reports.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BackendKind {
+    /// No backend has started yet.
+    #[default]
+    None,
+    Drm,
+    Winit,
+    Headless,
+}
+
+impl BackendKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::None => "none",
+            Self::Drm => "drm",
+            Self::Winit => "winit",
+            Self::Headless => "headless",
+        }
+    }
+}
@@ -2,10 +2,11 @@ use std::cell::RefCell;
 use std::collections::{HashMap, HashSet};
 use std::fs::OpenOptions;
 use std::io::Write;
+use std::panic::{self, AssertUnwindSafe};
 use std::path::Path;
 use std::sync::Mutex;
 use std::sync::OnceLock;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use smithay::{
     backend::{
@@ -15,14 +16,17 @@ use smithay::{
         },
         drm::{
             DrmDevice, DrmDeviceFd, DrmEvent, DrmEventMetadata, DrmEventTime, DrmNode, NodeType,
+            VrrSupport,
             compositor::FrameFlags,
             exporter::gbm::GbmFramebufferExporter,
             output::{DrmOutput, DrmOutputManager, DrmOutputRenderElements},
         },
         egl::{EGLDevice, EGLDisplay},
+        input::InputEvent,
         libinput::{LibinputInputBackend, LibinputSessionInterface},
         renderer::{
-            ImportAll, ImportDma, ImportMem, ImportMemWl, Renderer, RendererSuper,
+            Bind, ExportMem, ImportAll, ImportDma, ImportMem, ImportMemWl, Renderer, Texture,
+            damage::OutputDamageTracker,
             element::{
                 AsRenderElements, Element, Id, Kind, RenderElement, UnderlyingStorage,
                 default_primary_scanout_output_compare,
@@ -34,30 +38,37 @@ use smithay::{
                     RelocateRenderElement, RescaleRenderElement, constrain_as_render_elements,
                 },
             },
-            gles::GlesRenderer,
-            multigpu::{GpuManager, MultiRenderer, gbm::GbmGlesBackend},
-            utils::{CommitCounter, DamageSet, OpaqueRegions, with_renderer_surface_state},
+            gles::{GlesError, GlesFrame, GlesRenderer},
+            multigpu::{Error as MultiError, GpuManager, MultiRenderer, gbm::GbmGlesBackend},
+            utils::{
+                CommitCounter, DamageSet, OpaqueRegions, RendererSurfaceStateUserData,
+                with_renderer_surface_state,
+            },
         },
         session::{Event as SessionEvent, Session, libseat::LibSeatSession},
         udev::{UdevBackend, UdevEvent, all_gpus, primary_gpu},
     },
+    delegate_drm_lease,
     desktop::{
-        Window, layer_map_for_output,
+        Space, Window, layer_map_for_output,
         space::{SpaceRenderElements, space_render_elements},
         utils::{
             OutputPresentationFeedback, surface_presentation_feedback_flags_from_states,
             surface_primary_scanout_output, update_surface_primary_scanout_output,
         },
     },
-    input::pointer::{CursorImageAttributes, CursorImageStatus},
+    input::pointer::{CursorIcon, CursorImageAttributes, CursorImageStatus},
     output::{Mode as WlMode, Output, PhysicalProperties, Scale as OutputScale, Subpixel},
     reexports::{
         calloop::{
             EventLoop, RegistrationToken,
             timer::{TimeoutAction, Timer},
         },
-        drm::control::{Mode, ModeTypeFlags, connector, crtc},
-        input::Libinput,
+        drm::{
+            Device as BasicDevice,
+            control::{Device as ControlDevice, Mode, ModeTypeFlags, connector, crtc},
+        },
+        input::{Device as LibinputDevice, Libinput},
         rustix::fs::OFlags,
         wayland_protocols::wp::presentation_time::server::wp_presentation_feedback,
         wayland_server::{backend::GlobalId, protocol::wl_surface::WlSurface},
@@ -68,7 +79,12 @@ use smithay::{
     wayland::{
         compositor,
         dmabuf::{DmabufFeedbackBuilder, DmabufState},
+        drm_lease::{
+            DrmLease, DrmLeaseBuilder, DrmLeaseHandler, DrmLeaseRequest, DrmLeaseState,
+            LeaseRejected,
+        },
         drm_syncobj::{DrmSyncobjState, supports_syncobj_eventfd},
+        fractional_scale::with_fractional_scale,
         presentation::Refresh,
         shell::wlr_layer::Layer as WlrLayer,
     },
@@ -80,16 +96,27 @@ use smithay_drm_extras::{
 
 use crate::{
     CompositorError, Raven,
-    config::MonitorConfig,
+    backend::{
+        BackendKind,
+        capture::{OutputCapture, copy_rgba_to_shm},
+        commit_timing::{CommitTiming, LatencyHistogram},
+    },
+    config::{InputConfig, MonitorConfig, MonitorMode},
     cursor::{CursorThemeManager, PointerElement, PointerRenderElement},
-    render_helpers::{SolidColorBuffer, SolidColorRenderElement},
+    protocols::wlr_screencopy::Screencopy,
+    render_helpers::{
+        OffscreenPool, RoundedCornerElement, RoundedPrograms, RoundedRectBuffer,
+        RoundedRectElement, RoundedRenderer, SolidColorBuffer, SolidColorRenderElement,
+    },
+    state::{OutputWallpaper, ProgressStatus, WorkspaceTransitionFrame},
     vblank_throttle::VBlankThrottle,
 };
 
 // Supported color formats for DRM output
 const SUPPORTED_FORMATS: &[Fourcc] = &[Fourcc::Abgr8888, Fourcc::Argb8888];
 
-// Background clear color (same as winit backend)
+// Clear color of captures and of a new output's backdrop until its first frame
+// picks up `background_color`.
 const CLEAR_COLOR: [f32; 4] = [150.0 / 255.0, 154.0 / 255.0, 171.0 / 255.0, 1.0];
 
 fn env_truthy(name: &str) -> Option<bool> {
@@ -110,12 +137,29 @@ fn scanout_enabled() -> bool {
     })
 }
 
-fn frame_flags() -> FrameFlags {
-    if scanout_enabled() {
-        FrameFlags::DEFAULT
-    } else {
+fn hardware_cursor_enabled() -> bool {
+    static ENABLE_HARDWARE_CURSOR: OnceLock<bool> = OnceLock::new();
+    *ENABLE_HARDWARE_CURSOR
+        .get_or_init(|| !env_truthy("RAVEN_DISABLE_HARDWARE_CURSOR").unwrap_or(false))
+}
+
+/// With `video_or_game`, any fullscreen buffer may go straight to the primary
+/// plane, not only the ones smithay marks as scanout candidates. With
+/// `cursor_plane`, the pointer goes on the cursor plane when it fits there, so
+/// moving it leaves the primary plane alone; otherwise it is composited.
+fn frame_flags(video_or_game: bool, cursor_plane: bool) -> FrameFlags {
+    let mut flags = if !scanout_enabled() {
         FrameFlags::empty()
-    }
+    } else if video_or_game {
+        FrameFlags::DEFAULT | FrameFlags::ALLOW_PRIMARY_PLANE_SCANOUT_ANY
+    } else {
+        FrameFlags::DEFAULT
+    };
+    flags.set(
+        FrameFlags::ALLOW_CURSOR_PLANE_SCANOUT,
+        cursor_plane && hardware_cursor_enabled(),
+    );
+    flags
 }
 
 fn force_full_redraw() -> bool {
@@ -206,7 +250,8 @@ fn scanout_rejection_reason(
     };
 
     let cursor_visible_on_output = output_geo.to_f64().contains(state.pointer_location)
-        && !matches!(state.cursor_status, CursorImageStatus::Hidden);
+        && !matches!(state.cursor_status, CursorImageStatus::Hidden)
+        && !state.cursor_hidden_by_device;
     if cursor_visible_on_output {
         return Some("cursor-visible");
     }
@@ -231,6 +276,26 @@ type UdevRenderer<'a> = MultiRenderer<
 
 type GbmFbExporter = GbmFramebufferExporter<DrmDeviceFd>;
 
+impl RoundedRenderer for UdevRenderer<'_> {
+    fn gles_renderer(&mut self) -> &mut GlesRenderer {
+        self.as_mut()
+    }
+
+    fn gles_error(err: GlesError) -> Self::Error {
+        MultiError::Render(err)
+    }
+
+    fn gles_frame<'a, 'frame, 'buffer>(
+        frame: &'a mut Self::Frame<'frame, 'buffer>,
+    ) -> &'a mut GlesFrame<'frame, 'buffer>
+    where
+        Self: 'frame,
+        'buffer: 'frame,
+    {
+        frame.as_mut()
+    }
+}
+
 smithay::backend::renderer::element::render_elements! {
     pub UdevRenderElement<R, E> where R: ImportAll + ImportMem;
     Backdrop=SolidColorRenderElement,
@@ -240,11 +305,14 @@ smithay::backend::renderer::element::render_elements! {
 }
 
 smithay::backend::renderer::element::render_elements! {
-    pub UdevCompositeRenderElement<R, E> where R: ImportAll + ImportMem + Renderer;
+    pub UdevCompositeRenderElement<R, E> where R: ImportAll + ImportMem + RoundedRenderer;
     Base=UdevRenderElement<R, E>,
     CorrectedBase=CorrectedWaylandSurfaceRenderElement<R>,
     CorrectedTexture=TextureRenderElement<R::TextureId>,
     ConstrainedWindow=CropRenderElement<RelocateRenderElement<RescaleRenderElement<WaylandSurfaceRenderElement<R>>>>,
+    RoundedBase=RoundedCornerElement<CorrectedWaylandSurfaceRenderElement<R>>,
+    RoundedTexture=RoundedCornerElement<TextureRenderElement<R::TextureId>>,
+    RoundedRect=RoundedRectElement,
 }
 
 #[allow(dead_code)]
@@ -262,6 +330,11 @@ struct AssignedWindowRect {
     raw_root_physical: Rectangle<i32, smithay::utils::Physical>,
     render_origin_logical: Point<i32, smithay::utils::Logical>,
     render_origin_physical: Point<i32, smithay::utils::Physical>,
+    // From the window's `opacity` rule and `dim_inactive`, and its border color
+    // and logical corner radius unless fullscreen.
+    alpha: f32,
+    border_color: Option<[f32; 4]>,
+    corner_radius: f64,
 }
 
 impl AssignedWindowRect {
@@ -592,11 +665,15 @@ fn output_scale_from_logical_rects(
     Scale::from((scale_x, scale_y))
 }
 
-fn corrected_root_texture_element<'render, 'frame>(
-    renderer: &'render mut UdevRenderer<'frame>,
+fn corrected_root_texture_element<R>(
+    renderer: &mut R,
     assignment: &AssignedWindowRect,
-    root_element: &WaylandSurfaceRenderElement<UdevRenderer<'frame>>,
-) -> Option<TextureRenderElement<<UdevRenderer<'frame> as RendererSuper>::TextureId>> {
+    root_element: &WaylandSurfaceRenderElement<R>,
+) -> Option<TextureRenderElement<R::TextureId>>
+where
+    R: RoundedRenderer + ImportAll + ImportMem,
+    R::TextureId: Texture + Clone + Send + 'static,
+{
     let target_logical = corrected_root_target_rect_logical(assignment);
     let projection_expected_size =
         if assignment.raw_root_logical.size != assignment.reported_logical_size {
@@ -657,6 +734,8 @@ fn corrected_root_texture_element<'render, 'frame>(
                     .collect::<Vec<_>>(),
             )
         });
+        // A translucent window hides nothing beneath it.
+        let opaque_regions = opaque_regions.filter(|_| root_element.alpha() >= 1.0);
 
         render_trace_line(format!(
             "root_path=custom {} {} {} {} expected={}x{} src={:.2}x{:.2}@{:.2},{:.2} opaque={}",
@@ -732,10 +811,16 @@ fn window_assignment_for_output(
     output_geo: Rectangle<i32, smithay::utils::Logical>,
     output_scale: Scale<f64>,
     window: &Window,
+    transition: Option<&WorkspaceTransitionFrame>,
 ) -> Option<AssignedWindowRect> {
     let toplevel = window.toplevel()?;
     let surface_id = toplevel.wl_surface().clone();
-    let assigned_logical = state.window_visual_or_assigned_rect(window)?;
+    let mut assigned_logical = state.window_visual_or_assigned_rect(window)?;
+    // The incoming workspace slides in while switching; sticky windows stay put.
+    let transition = transition.filter(|_| !state.is_window_sticky(window));
+    if let Some(transition) = transition {
+        assigned_logical.loc += transition.incoming_offset;
+    }
     let render_origin_logical = assigned_logical.loc - window.geometry().loc;
     let raw_root_size =
         with_renderer_surface_state(&surface_id, |renderer_state| renderer_state.surface_size())
@@ -781,12 +866,22 @@ fn window_assignment_for_output(
             .to_i32_round(),
     );
     let render_origin_physical = raw_root_physical.loc;
+    // Fullscreen windows are drawn opaque and without a border.
+    let is_fullscreen = state.window_effective_fullscreen_state(window);
+    let alpha =
+        state.window_alpha(window) * transition.map_or(1.0, |transition| transition.incoming_alpha);
+    let border_color = (!is_fullscreen).then(|| state.window_border_color(&surface_id));
+    let corner_radius = if is_fullscreen {
+        0.0
+    } else {
+        f64::from(state.window_corner_radius(&surface_id))
+    };
 
     Some(AssignedWindowRect {
         window: window.clone(),
         surface_id,
         surface_ids,
-        is_fullscreen: state.window_effective_fullscreen_state(window),
+        is_fullscreen,
         assigned_logical,
         assigned_physical,
         reported_logical_size,
@@ -795,19 +890,21 @@ fn window_assignment_for_output(
         raw_root_physical,
         render_origin_logical,
         render_origin_physical,
+        alpha,
+        border_color,
+        corner_radius,
     })
 }
 
-fn assigned_window_render_elements<'render, 'frame>(
-    renderer: &'render mut UdevRenderer<'frame>,
+fn assigned_window_render_elements<R>(
+    renderer: &mut R,
     assignment: &AssignedWindowRect,
     output_scale: Scale<f64>,
-) -> Vec<
-    UdevCompositeRenderElement<
-        UdevRenderer<'frame>,
-        WaylandSurfaceRenderElement<UdevRenderer<'frame>>,
-    >,
-> {
+) -> Vec<UdevCompositeRenderElement<R, WaylandSurfaceRenderElement<R>>>
+where
+    R: RoundedRenderer + ImportAll + ImportMem,
+    R::TextureId: Texture + Clone + Send + 'static,
+{
     let Some(toplevel) = assignment.window.toplevel() else {
         return Vec::new();
     };
@@ -824,15 +921,12 @@ fn assigned_window_render_elements<'render, 'frame>(
             assignment.reported_logical_size.w,
             assignment.reported_logical_size.h,
         ));
-        return render_elements_from_surface_tree::<
-            _,
-            WaylandSurfaceRenderElement<UdevRenderer<'frame>>,
-        >(
+        return render_elements_from_surface_tree::<_, WaylandSurfaceRenderElement<R>>(
             renderer,
             root_surface,
             assignment.render_origin_physical,
             output_scale,
-            1.0,
+            assignment.alpha,
             Kind::Unspecified,
         )
         .into_iter()
@@ -848,17 +942,14 @@ fn assigned_window_render_elements<'render, 'frame>(
 
     if assignment.is_fullscreen {
         return constrain_as_render_elements::<
-            UdevRenderer<'frame>,
+            R,
             Window,
-            UdevCompositeRenderElement<
-                UdevRenderer<'frame>,
-                WaylandSurfaceRenderElement<UdevRenderer<'frame>>,
-            >,
+            UdevCompositeRenderElement<R, WaylandSurfaceRenderElement<R>>,
         >(
             &assignment.window,
             renderer,
             assignment.render_origin_physical,
-            1.0,
+            assignment.alpha,
             assignment.assigned_physical,
             assignment.raw_root_physical,
             ConstrainScaleBehavior::Stretch,
@@ -884,12 +975,12 @@ fn assigned_window_render_elements<'render, 'frame>(
         } else {
             assignment.reported_physical_size
         };
-    render_elements_from_surface_tree::<_, WaylandSurfaceRenderElement<UdevRenderer<'frame>>>(
+    render_elements_from_surface_tree::<_, WaylandSurfaceRenderElement<R>>(
         renderer,
         root_surface,
         assignment.render_origin_physical,
         output_scale,
-        1.0,
+        assignment.alpha,
         Kind::Unspecified,
     )
     .into_iter()
@@ -957,6 +1048,606 @@ fn assigned_window_render_elements<'render, 'frame>(
     .collect()
 }
 
+/// Dim veils for the unfocused windows among `window_assignments`, keyed by
+/// assignment index.
+fn unfocused_dim_elements(
+    state: &mut Raven,
+    output_geo: Option<Rectangle<i32, smithay::utils::Logical>>,
+    window_assignments: &[AssignedWindowRect],
+) -> HashMap<usize, WindowDecoration> {
+    let Some(output_geo) = output_geo else {
+        return HashMap::new();
+    };
+    let alphas: Vec<(usize, f32)> = window_assignments
+        .iter()
+        .enumerate()
+        .filter(|(_, assignment)| !assignment.is_fullscreen)
+        .filter_map(|(index, assignment)| {
+            state
+                .unfocused_dim_alpha(&assignment.window)
+                .map(|alpha| (index, alpha))
+        })
+        .collect();
+    let buffers = &mut state.decoration_buffers;
+    buffers.dim.retain(|surface, _| surface.is_alive());
+    buffers.rounded_dim.retain(|surface, _| surface.is_alive());
+
+    alphas
+        .into_iter()
+        .map(|(index, alpha)| {
+            let assignment = &window_assignments[index];
+            let location = (assignment.assigned_logical.loc - output_geo.loc).to_f64();
+            if assignment.corner_radius > 0.0 {
+                let buffer = buffers
+                    .rounded_dim
+                    .entry(assignment.surface_id.clone())
+                    .or_default();
+                buffer.update(
+                    assignment.assigned_logical.size.to_f64(),
+                    [0.0, 0.0, 0.0, alpha],
+                    assignment.corner_radius as f32,
+                    0.0,
+                );
+                let decoration = WindowDecoration::Rounded {
+                    buffer: buffer.clone(),
+                    location,
+                    alpha: 1.0,
+                };
+                return (index, decoration);
+            }
+            let buffer = buffers
+                .dim
+                .entry(assignment.surface_id.clone())
+                .or_default();
+            buffer.update(
+                assignment.assigned_logical.size.to_f64(),
+                [0.0, 0.0, 0.0, alpha],
+            );
+            let element =
+                SolidColorRenderElement::from_buffer(buffer, location, 1.0, Kind::Unspecified);
+            (index, WindowDecoration::Solid(vec![element]))
+        })
+        .collect()
+}
+
+/// Borders for the windows among `window_assignments` that are not fullscreen,
+/// keyed by assignment index. They sit `border_size` wide outside the window's
+/// rect, in the room its tile leaves for them; around a window with rounded
+/// corners the border is rounded to match.
+fn window_border_elements(
+    state: &mut Raven,
+    output_geo: Option<Rectangle<i32, smithay::utils::Logical>>,
+    window_assignments: &[AssignedWindowRect],
+) -> HashMap<usize, WindowDecoration> {
+    let Some(output_geo) = output_geo else {
+        return HashMap::new();
+    };
+    let width = state.config.border_size.min(i32::MAX as u32) as i32;
+    let buffers = &mut state.decoration_buffers;
+    buffers.border.retain(|surface, _| surface.is_alive());
+    buffers
+        .rounded_border
+        .retain(|surface, _| surface.is_alive());
+    if width == 0 {
+        return HashMap::new();
+    }
+
+    let side =
+        |x, y, w, h| Rectangle::<i32, smithay::utils::Logical>::new((x, y).into(), (w, h).into());
+    window_assignments
+        .iter()
+        .enumerate()
+        .filter_map(|(index, assignment)| {
+            let [red, green, blue, alpha] = assignment.border_color?;
+            let color = [red * alpha, green * alpha, blue * alpha, alpha];
+            let rect = assignment.assigned_logical;
+            let (left, top) = (rect.loc.x - width, rect.loc.y - width);
+            let full_width = rect.size.w + 2 * width;
+            if assignment.corner_radius > 0.0 {
+                let buffer = buffers
+                    .rounded_border
+                    .entry(assignment.surface_id.clone())
+                    .or_default();
+                buffer.update(
+                    (full_width, rect.size.h + 2 * width),
+                    color,
+                    assignment.corner_radius as f32,
+                    width as f32,
+                );
+                let decoration = WindowDecoration::Rounded {
+                    buffer: buffer.clone(),
+                    location: (Point::from((left, top)) - output_geo.loc).to_f64(),
+                    alpha: assignment.alpha,
+                };
+                return Some((index, decoration));
+            }
+            let sides = [
+                side(left, top, full_width, width),
+                side(left, rect.loc.y + rect.size.h, full_width, width),
+                side(left, rect.loc.y, width, rect.size.h),
+                side(rect.loc.x + rect.size.w, rect.loc.y, width, rect.size.h),
+            ];
+            let edges = buffers
+                .border
+                .entry(assignment.surface_id.clone())
+                .or_default();
+            let elements = edges
+                .iter_mut()
+                .zip(sides)
+                .map(|(buffer, side)| {
+                    buffer.update(side.size.to_f64(), color);
+                    SolidColorRenderElement::from_buffer(
+                        buffer,
+                        (side.loc - output_geo.loc).to_f64(),
+                        assignment.alpha,
+                        Kind::Unspecified,
+                    )
+                })
+                .collect();
+            Some((index, WindowDecoration::Solid(elements)))
+        })
+        .collect()
+}
+
+/// The outgoing windows of a workspace switch animation that reach onto the
+/// output, front to back.
+fn outgoing_window_elements<R>(
+    renderer: &mut R,
+    transition: &WorkspaceTransitionFrame,
+    output_geo: Rectangle<i32, smithay::utils::Logical>,
+    output_scale: Scale<f64>,
+) -> Vec<UdevCompositeRenderElement<R, WaylandSurfaceRenderElement<R>>>
+where
+    R: RoundedRenderer + ImportAll + ImportMem,
+    R::TextureId: Texture + Clone + Send + 'static,
+{
+    transition
+        .outgoing
+        .iter()
+        .rev()
+        .filter(|(window, location)| {
+            let mut bbox = window.bbox();
+            bbox.loc += *location - window.geometry().loc;
+            output_geo.overlaps(bbox)
+        })
+        .flat_map(|(window, location)| {
+            let render_location = (*location - window.geometry().loc - output_geo.loc)
+                .to_physical_precise_round(output_scale);
+            AsRenderElements::<R>::render_elements::<WaylandSurfaceRenderElement<R>>(
+                window,
+                renderer,
+                render_location,
+                output_scale,
+                transition.outgoing_alpha,
+            )
+        })
+        .map(|element| {
+            UdevCompositeRenderElement::from(CorrectedWaylandSurfaceRenderElement {
+                inner: element,
+                src_override: None,
+                geometry_override: None,
+            })
+        })
+        .collect()
+}
+
+/// Border and veil buffers of windows, shared by every backend's frames.
+#[derive(Default)]
+pub struct DecorationBuffers {
+    // Veil buffers for unfocused-window dimming; stable ids keep damage minimal.
+    dim: HashMap<WlSurface, SolidColorBuffer>,
+    // Top, bottom, left and right edges of `border_color` rule borders.
+    border: HashMap<WlSurface, [SolidColorBuffer; 4]>,
+    // Veils and borders of windows with rounded corners.
+    rounded_dim: HashMap<WlSurface, RoundedRectBuffer>,
+    rounded_border: HashMap<WlSurface, RoundedRectBuffer>,
+}
+
+/// A window's border or dim veil: solid rects, or for a window with rounded
+/// corners one rounded rect, drawn by a shader.
+enum WindowDecoration {
+    Solid(Vec<SolidColorRenderElement>),
+    Rounded {
+        buffer: RoundedRectBuffer,
+        location: Point<f64, smithay::utils::Logical>,
+        alpha: f32,
+    },
+}
+
+impl WindowDecoration {
+    fn into_elements<R>(
+        self,
+        programs: Option<&RoundedPrograms>,
+    ) -> Vec<UdevCompositeRenderElement<R, WaylandSurfaceRenderElement<R>>>
+    where
+        R: RoundedRenderer + ImportAll + ImportMem,
+        R::TextureId: Texture + Clone + Send + 'static,
+    {
+        match self {
+            WindowDecoration::Solid(elements) => elements
+                .into_iter()
+                .map(|element| UdevCompositeRenderElement::from(UdevRenderElement::from(element)))
+                .collect(),
+            // Without the shaders there is nothing to draw it with; the
+            // window's corners stay square too.
+            WindowDecoration::Rounded {
+                buffer,
+                location,
+                alpha,
+            } => programs
+                .map(|programs| {
+                    UdevCompositeRenderElement::RoundedRect(RoundedRectElement::from_buffer(
+                        &buffer,
+                        programs.rect.clone(),
+                        location,
+                        alpha,
+                        Kind::Unspecified,
+                    ))
+                })
+                .into_iter()
+                .collect(),
+        }
+    }
+}
+
+/// Clip the elements of a window with a corner radius to its rounded rect.
+/// Surfaces with a buffer transform keep square corners.
+fn round_window_corners<R>(
+    programs: Option<&RoundedPrograms>,
+    assignment: &AssignedWindowRect,
+    output_scale: Scale<f64>,
+    elements: Vec<UdevCompositeRenderElement<R, WaylandSurfaceRenderElement<R>>>,
+) -> Vec<UdevCompositeRenderElement<R, WaylandSurfaceRenderElement<R>>>
+where
+    R: RoundedRenderer + ImportAll + ImportMem,
+    R::TextureId: Texture + Clone + Send + 'static,
+{
+    let Some(programs) = programs.filter(|_| assignment.corner_radius > 0.0) else {
+        return elements;
+    };
+    let texture_sizes = surface_texture_sizes(&assignment.window);
+    let radius = (assignment.corner_radius * output_scale.x) as f32;
+    let window = assignment.assigned_physical;
+    elements
+        .into_iter()
+        .map(|element| {
+            let Some(texture_size) = texture_sizes.get(element.id()).copied() else {
+                return element;
+            };
+            match element {
+                UdevCompositeRenderElement::CorrectedBase(inner) => {
+                    UdevCompositeRenderElement::RoundedBase(RoundedCornerElement::new(
+                        inner,
+                        programs.corners.clone(),
+                        window,
+                        radius,
+                        texture_size,
+                    ))
+                }
+                UdevCompositeRenderElement::CorrectedTexture(inner) => {
+                    UdevCompositeRenderElement::RoundedTexture(RoundedCornerElement::new(
+                        inner,
+                        programs.corners.clone(),
+                        window,
+                        radius,
+                        texture_size,
+                    ))
+                }
+                other => other,
+            }
+        })
+        .collect()
+}
+
+/// Buffer size of each surface of `window` without a buffer transform, keyed
+/// by render element id.
+fn surface_texture_sizes(window: &Window) -> HashMap<Id, smithay::utils::Size<i32, BufferCoords>> {
+    let mut sizes = HashMap::new();
+    window.with_surfaces(|surface, states| {
+        let Some(data) = states.data_map.get::<RendererSurfaceStateUserData>() else {
+            return;
+        };
+        let Ok(data) = data.lock() else {
+            return;
+        };
+        if data.buffer_transform() != Transform::Normal {
+            return;
+        }
+        if let Some(size) = data.buffer_size() {
+            sizes.insert(
+                Id::from_wayland_resource(surface),
+                size.to_buffer(data.buffer_scale(), Transform::Normal),
+            );
+        }
+    });
+    sizes
+}
+
+/// What an output's frame is made of, gathered from the compositor state
+/// before any renderer is borrowed. Every backend composes its frames from
+/// this, so windows get the same borders, corners, veils and wallpaper
+/// wherever they are drawn.
+pub(crate) struct OutputFrame {
+    output: Output,
+    output_geo: Option<Rectangle<i32, smithay::utils::Logical>>,
+    output_scale: Scale<f64>,
+    fullscreen: bool,
+    transition: Option<WorkspaceTransitionFrame>,
+    window_assignments: Vec<AssignedWindowRect>,
+    window_assignment_indices: HashMap<Id, usize>,
+    // Columns of a neighbouring output's scrolled strip that reach onto this one.
+    scrolled_out_ids: HashSet<Id>,
+    dim_elements: HashMap<usize, WindowDecoration>,
+    border_elements: HashMap<usize, WindowDecoration>,
+    overlay_elements: Vec<SolidColorRenderElement>,
+    wallpaper: Option<OutputWallpaper>,
+}
+
+impl OutputFrame {
+    pub(crate) fn new(state: &mut Raven, output: &Output) -> Self {
+        let output_scale = Scale::from(output.current_scale().fractional_scale());
+        let output_geo = state.space.output_geometry(output);
+        let transition = state.workspace_transition_frame(output);
+        let window_assignments: Vec<AssignedWindowRect> = output_geo
+            .map(|output_geo| {
+                state
+                    .space
+                    .elements_for_output(output)
+                    .filter(|window| !state.window_scrolled_out(window, output))
+                    .filter_map(|window| {
+                        window_assignment_for_output(
+                            state,
+                            output_geo,
+                            output_scale,
+                            window,
+                            transition.as_ref(),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut scrolled_out_ids = HashSet::new();
+        for window in state.space.elements_for_output(output) {
+            if state.window_scrolled_out(window, output) {
+                window.with_surfaces(|surface, _| {
+                    scrolled_out_ids.insert(Id::from_wayland_resource(surface));
+                });
+            }
+        }
+        let mut window_assignment_indices = HashMap::new();
+        for (index, assignment) in window_assignments.iter().enumerate() {
+            for id in &assignment.surface_ids {
+                window_assignment_indices.insert(id.clone(), index);
+            }
+        }
+        let dim_elements = unfocused_dim_elements(state, output_geo, &window_assignments);
+        let border_elements = window_border_elements(state, output_geo, &window_assignments);
+
+        Self {
+            output: output.clone(),
+            output_geo,
+            output_scale,
+            fullscreen: state.output_has_fullscreen_window(output),
+            transition,
+            window_assignments,
+            window_assignment_indices,
+            scrolled_out_ids,
+            dim_elements,
+            border_elements,
+            overlay_elements: state.overlay_elements(output),
+            wallpaper: state.output_wallpaper(output),
+        }
+    }
+
+    /// The frame's elements front to back: overlays, windows and layer
+    /// surfaces with each window's border and veil, the built-in wallpaper
+    /// and finally `backdrop`.
+    pub(crate) fn elements<R>(
+        self,
+        renderer: &mut R,
+        space: &Space<Window>,
+        backdrop: &SolidColorBuffer,
+    ) -> Vec<UdevCompositeRenderElement<R, WaylandSurfaceRenderElement<R>>>
+    where
+        R: RoundedRenderer + ImportAll + ImportMem,
+        R::TextureId: Texture + Clone + Send + 'static,
+    {
+        let Self {
+            output,
+            output_geo,
+            output_scale,
+            fullscreen,
+            transition,
+            window_assignments,
+            window_assignment_indices,
+            scrolled_out_ids,
+            dim_elements,
+            border_elements,
+            overlay_elements,
+            wallpaper,
+        } = self;
+
+        // Fullscreen ownership only affects ordering. Every mapped window is rendered through
+        // the same compositor-assigned rect pipeline below, regardless of whether it is tiled,
+        // floating, fullscreen, or in a fullscreen visual handoff.
+        let mut space_elements = match space_render_elements(renderer, [space], &output, 1.0) {
+            Ok(elements) => elements,
+            Err(e) => {
+                tracing::warn!("Failed to collect render elements: {e:?}");
+                Vec::new()
+            }
+        };
+        if fullscreen {
+            let mut window_elements = Vec::new();
+            let mut lower_layer_elements = Vec::new();
+            let mut saw_window_element = false;
+            for element in space_elements {
+                match element {
+                    SpaceRenderElements::Element(_) => {
+                        saw_window_element = true;
+                        window_elements.push(element);
+                    }
+                    SpaceRenderElements::Surface(_) if saw_window_element => {
+                        lower_layer_elements.push(element);
+                    }
+                    SpaceRenderElements::Surface(_) => {}
+                    SpaceRenderElements::_GenericCatcher(_) => {}
+                }
+            }
+
+            let layer_map = layer_map_for_output(&output);
+            let mut collect_layer_elements = |layer: WlrLayer| {
+                let mut out = Vec::new();
+                for layer_surface in layer_map.layers_on(layer).rev() {
+                    let Some(layer_geo) = layer_map.layer_geometry(layer_surface) else {
+                        continue;
+                    };
+                    out.extend(
+                        AsRenderElements::<R>::render_elements::<WaylandSurfaceRenderElement<R>>(
+                            layer_surface,
+                            renderer,
+                            layer_geo.loc.to_physical_precise_round(output_scale),
+                            output_scale,
+                            1.0,
+                        )
+                        .into_iter()
+                        .map(SpaceRenderElements::Surface),
+                    );
+                }
+                out
+            };
+            let overlay_layer_elements = collect_layer_elements(WlrLayer::Overlay);
+            let top_layer_elements = collect_layer_elements(WlrLayer::Top);
+
+            let mut reordered = Vec::new();
+            reordered.extend(overlay_layer_elements);
+            reordered.extend(window_elements);
+            reordered.extend(top_layer_elements);
+            reordered.extend(lower_layer_elements);
+            space_elements = reordered;
+        }
+
+        let mut converted: Vec<UdevCompositeRenderElement<R, WaylandSurfaceRenderElement<R>>> =
+            Vec::new();
+        let mut rendered_assigned_windows = HashSet::new();
+        let rounded_programs = window_assignments
+            .iter()
+            .any(|assignment| assignment.corner_radius > 0.0)
+            .then(|| RoundedPrograms::get(renderer.gles_renderer()))
+            .flatten();
+
+        for element in space_elements {
+            let base = UdevRenderElement::from(element);
+            if scrolled_out_ids.contains(base.id()) {
+                continue;
+            }
+
+            if let Some(assignment_index) = window_assignment_indices.get(base.id()).copied() {
+                let assignment = &window_assignments[assignment_index];
+                // Only the assigned path draws with the window's alpha.
+                // Rounded corners and workspace switch animations too.
+                let needs_assigned_render_path = assignment.is_fullscreen
+                    || assignment.needs_correction()
+                    || assignment.alpha < 1.0
+                    || assignment.corner_radius > 0.0
+                    || transition.is_some();
+                if !needs_assigned_render_path {
+                    converted.push(UdevCompositeRenderElement::from(base));
+                    continue;
+                }
+                if rendered_assigned_windows.insert(assignment_index) {
+                    converted.retain(|element| !assignment.surface_ids.contains(element.id()));
+                    let window_elements =
+                        assigned_window_render_elements(renderer, assignment, output_scale);
+                    converted.extend(round_window_corners(
+                        rounded_programs.as_ref(),
+                        assignment,
+                        output_scale,
+                        window_elements,
+                    ));
+                }
+                continue;
+            }
+
+            converted.push(UdevCompositeRenderElement::from(base));
+        }
+
+        // The workspace switched away from goes behind the new one, in front of
+        // the bottom and background layers.
+        if let (Some(transition), Some(output_geo)) = (&transition, output_geo) {
+            let outgoing = outgoing_window_elements(renderer, transition, output_geo, output_scale);
+            let layer_map = layer_map_for_output(&output);
+            let below_ids: HashSet<Id> = [WlrLayer::Bottom, WlrLayer::Background]
+                .into_iter()
+                .flat_map(|layer| layer_map.layers_on(layer))
+                .map(|layer_surface| Id::from_wayland_resource(layer_surface.wl_surface()))
+                .collect();
+            let position = converted
+                .iter()
+                .position(|element| below_ids.contains(element.id()))
+                .unwrap_or(converted.len());
+            converted.splice(position..position, outgoing);
+        }
+
+        // Each border goes right behind its window's bottommost element.
+        for (assignment_index, borders) in border_elements {
+            let surface_ids = &window_assignments[assignment_index].surface_ids;
+            if let Some(position) = converted
+                .iter()
+                .rposition(|element| surface_ids.contains(element.id()))
+            {
+                converted.splice(
+                    position + 1..position + 1,
+                    borders.into_elements(rounded_programs.as_ref()),
+                );
+            }
+        }
+
+        // Each veil goes right in front of its window's topmost element.
+        for (assignment_index, veil) in dim_elements {
+            let surface_ids = &window_assignments[assignment_index].surface_ids;
+            if let Some(position) = converted
+                .iter()
+                .position(|element| surface_ids.contains(element.id()))
+            {
+                converted.splice(
+                    position..position,
+                    veil.into_elements(rounded_programs.as_ref()),
+                );
+            }
+        }
+
+        let mut elements: Vec<UdevCompositeRenderElement<R, WaylandSurfaceRenderElement<R>>> =
+            overlay_elements
+                .into_iter()
+                .map(UdevRenderElement::from)
+                .map(UdevCompositeRenderElement::from)
+                .collect();
+        elements.extend(converted);
+        // The built-in wallpaper goes beneath background layers, over the backdrop.
+        if let Some(wallpaper) = &wallpaper {
+            match MemoryRenderBufferRenderElement::from_buffer(
+                renderer,
+                (0.0, 0.0),
+                &wallpaper.buffer,
+                None,
+                Some(wallpaper.src),
+                Some(wallpaper.size),
+                Kind::Unspecified,
+            ) {
+                Ok(element) => elements.push(UdevCompositeRenderElement::from(
+                    UdevRenderElement::from(element),
+                )),
+                Err(err) => tracing::warn!("failed to upload wallpaper: {err:?}"),
+            }
+        }
+        elements.push(UdevCompositeRenderElement::from(UdevRenderElement::from(
+            SolidColorRenderElement::from_buffer(backdrop, (0.0, 0.0), 1.0, Kind::Unspecified),
+        )));
+        elements
+    }
+}
+
 /// Per-GPU device state
 struct BackendData {
     surfaces: HashMap<crtc::Handle, SurfaceData>,
@@ -969,6 +1660,14 @@ struct BackendData {
     drm_scanner: DrmScanner,
     render_node: Option<DrmNode>,
     registration_token: RegistrationToken,
+    // `wp_drm_lease_device_v1` of this device; `None` when it could not be created.
+    lease_global: Option<DrmLeaseState>,
+    // Non-desktop connectors (VR headsets) offered for leasing instead of mapped,
+    // with the CRTC the scanner picked for them.
+    non_desktop_connectors: Vec<(connector::Handle, crtc::Handle)>,
+    active_leases: Vec<DrmLease>,
+    #[cfg_attr(not(feature = "screencast"), allow(dead_code))]
+    gbm: GbmDevice<DrmDeviceFd>,
 }
 
 #[derive(Debug, Default)]
@@ -1014,6 +1713,89 @@ struct SurfaceData {
     redraw_state: RedrawState,
     frame_callback_sequence: u32,
     vblank_throttle: VBlankThrottle,
+    // Set while the CRTC is released via `raven output <name> pause`; holds the
+    // layout position to restore on resume.
+    paused_location: Option<Point<i32, smithay::utils::Logical>>,
+    // Set while the CRTC is blanked by DPMS; unlike a pause the output stays in
+    // the layout.
+    powered_off: bool,
+    // Gamma ramps overriding the CRTC's own: a gamma control client's or the night light.
+    gamma: Option<GammaOverride>,
+    // Window commits shown by the frame waiting for vblank, with their commit times.
+    frame_commits: Vec<(WlSurface, Instant)>,
+    // Set once frames start failing; see `render_surface`.
+    render_failure: Option<RenderFailure>,
+    connector: connector::Handle,
+    // Set while a fullscreen window tagged video or game (content-type-v1) is
+    // shown; it gets VRR, direct scanout and unthrottled vblanks.
+    video_or_game: bool,
+    // Adaptive sync is on; only while `video_or_game` and the connector can.
+    vrr: bool,
+    // Damage since the last `copy_with_damage` screencopy served from this
+    // output, at the size it was created for.
+    screencopy_damage: Option<(smithay::utils::Size<i32, Physical>, OutputDamageTracker)>,
+    // What the last frame handed to the DRM compositor showed; a frame that
+    // would look the same is skipped before it gets there.
+    last_frame: Option<FrameSnapshot>,
+}
+
+/// Every element of a frame with its commit, place and opacity, top to bottom.
+/// Two equal snapshots draw the same pixels.
+#[derive(PartialEq)]
+struct FrameSnapshot(Vec<(Id, CommitCounter, Rectangle<i32, Physical>, f32)>);
+
+impl FrameSnapshot {
+    fn new<E: Element>(elements: &[E], scale: Scale<f64>) -> Self {
+        Self(
+            elements
+                .iter()
+                .map(|element| {
+                    (
+                        element.id().clone(),
+                        element.current_commit(),
+                        element.geometry(scale),
+                        element.alpha(),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+/// What overrides the gamma ramp of a CRTC. Ramps hold the red, green and blue
+/// channels back to back.
+struct GammaOverride {
+    // The CRTC's own ramp, put back once nothing overrides it.
+    original: Vec<u16>,
+    // Loaded by a `zwlr_gamma_control_v1` client; wins over the night light.
+    client: Option<Vec<u16>>,
+    // Red, green and blue multipliers the night light applies to `original`.
+    night_light: Option<[f64; 3]>,
+}
+
+impl GammaOverride {
+    /// The ramp to load; `None` when nothing overrides `original`.
+    fn ramp(&self) -> Option<Vec<u16>> {
+        if let Some(client) = &self.client {
+            return Some(client.clone());
+        }
+        let whitepoint = self.night_light?;
+        let size = self.original.len() / 3;
+        Some(
+            self.original
+                .iter()
+                .enumerate()
+                .map(|(index, entry)| (f64::from(*entry) * whitepoint[index / size]).round() as u16)
+                .collect(),
+        )
+    }
+}
+
+impl SurfaceData {
+    /// Whether the CRTC is off, paused or blanked, so nothing is rendered to it.
+    fn crtc_released(&self) -> bool {
+        self.paused_location.is_some() || self.powered_off
+    }
 }
 
 impl Drop for SurfaceData {
@@ -1027,15 +1809,59 @@ struct SurfaceFrameThrottlingState {
     last_sent_at: RefCell<Option<(Output, u32)>>,
 }
 
+/// Why a redraw was queued; aggregated per output for `raven stats`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum RedrawCause {
+    Pointer,
+    Keyboard,
+    Commit,
+    LayerShell,
+    WindowClosed,
+    Workspace,
+    Startup,
+    Ipc,
+    Config,
+    Input,
+    // Timer ticks of overlays and animations, which wake the GPU on their own.
+    Animation,
+}
+
+impl RedrawCause {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            RedrawCause::Pointer => "pointer",
+            RedrawCause::Keyboard => "keyboard",
+            RedrawCause::Commit => "commit",
+            RedrawCause::LayerShell => "layer-shell",
+            RedrawCause::WindowClosed => "window-closed",
+            RedrawCause::Workspace => "workspace",
+            RedrawCause::Startup => "startup",
+            RedrawCause::Ipc => "ipc",
+            RedrawCause::Config => "config",
+            RedrawCause::Input => "input",
+            RedrawCause::Animation => "animation",
+        }
+    }
+}
+
 /// DRM/udev backend data stored alongside the compositor state
 pub struct UdevData {
     pub session: LibSeatSession,
     pub primary_gpu: DrmNode,
     pub gpus: GpuManager<GbmGlesBackend<GlesRenderer, DrmDeviceFd>>,
     cursor_theme: CursorThemeManager,
-    pointer_images: Vec<(xcursor::parser::Image, MemoryRenderBuffer)>,
+    // Uploaded cursor frames with the buffer scale they were loaded at.
+    pointer_images: Vec<(xcursor::parser::Image, i32, MemoryRenderBuffer)>,
     backends: HashMap<DrmNode, BackendData>,
     queued_redraws: HashSet<(DrmNode, crtc::Handle)>,
+    redraw_cause_counts: HashMap<(String, RedrawCause), u64>,
+    // libinput devices currently present, so config reloads can reapply settings.
+    input_devices: Vec<LibinputDevice>,
+    // Offscreen textures for captures, one pool per render node's context.
+    offscreen_pools: HashMap<DrmNode, OffscreenPool>,
+    commit_timing: CommitTiming,
+    // Pointer location and cursor image saved on session pause, restored on resume.
+    paused_pointer: Option<(Point<f64, smithay::utils::Logical>, CursorImageStatus)>,
 }
 
 /// Initialize the DRM/KMS backend
@@ -1064,6 +1890,11 @@ pub fn init_udev(event_loop: &mut EventLoop<Raven>, state: &mut Raven) -> crate:
         pointer_images: Vec::new(),
         backends: HashMap::new(),
         queued_redraws: HashSet::new(),
+        redraw_cause_counts: HashMap::new(),
+        input_devices: Vec::new(),
+        offscreen_pools: HashMap::new(),
+        commit_timing: CommitTiming::default(),
+        paused_pointer: None,
     });
 
     // 5. Create UdevBackend for device enumeration
@@ -1095,7 +1926,12 @@ pub fn init_udev(event_loop: &mut EventLoop<Raven>, state: &mut Raven) -> crate:
     // 9. Register libinput event source
     event_loop
         .handle()
-        .insert_source(libinput_backend, |event, _, state| {
+        .insert_source(libinput_backend, |mut event, _, state| {
+            match &mut event {
+                InputEvent::DeviceAdded { device } => input_device_added(state, device),
+                InputEvent::DeviceRemoved { device } => input_device_removed(state, device),
+                _ => {}
+            }
             state.handle_input_event(event);
         })
         .map_err(|e| CompositorError::Backend(format!("failed to insert libinput source: {e}")))?;
@@ -1140,6 +1976,10 @@ pub fn init_udev(event_loop: &mut EventLoop<Raven>, state: &mut Raven) -> crate:
     // Refresh activation environment after backend globals are known.
     state.sync_activation_environment();
 
+    #[cfg(feature = "screencast")]
+    state.init_screencast();
+
+    state.backend_kind = BackendKind::Drm;
     tracing::info!(
         socket = ?state.socket_name,
         "DRM/KMS backend initialized"
@@ -1158,42 +1998,521 @@ pub fn reload_cursor_theme(state: &mut Raven) {
     tracing::info!("reloaded cursor theme");
 }
 
-/// Import buffers for a committed surface early, before the next render pass.
-pub fn early_import(state: &mut Raven, surface: &WlSurface) {
+fn configure_input_device(config: &InputConfig, device: &mut LibinputDevice) {
+    let left_handed = config.left_handed_for(device.name());
+    // Keyboards and touchscreens report Unsupported; only worth a note when asked for.
+    if let Err(err) = device.config_left_handed_set(left_handed)
+        && left_handed
+    {
+        tracing::debug!(
+            device = device.name(),
+            "left-handed mode not applied: {err:?}"
+        );
+    }
+}
+
+fn input_device_added(state: &mut Raven, device: &mut LibinputDevice) {
+    configure_input_device(&state.config.input, device);
+    if let Some(udev) = state.udev_data.as_mut() {
+        udev.input_devices.push(device.clone());
+    }
+}
+
+fn input_device_removed(state: &mut Raven, device: &LibinputDevice) {
+    if let Some(udev) = state.udev_data.as_mut() {
+        udev.input_devices.retain(|known| known != device);
+    }
+}
+
+/// Reapply the `input` config section to every connected libinput device.
+pub fn apply_input_config(state: &mut Raven) {
     let Some(udev) = state.udev_data.as_mut() else {
         return;
     };
 
-    if let Err(err) = udev.gpus.early_import(udev.primary_gpu, surface) {
-        tracing::debug!("early import failed: {err:?}");
+    for device in &mut udev.input_devices {
+        configure_input_device(&state.config.input, device);
     }
+    tracing::info!(
+        devices = udev.input_devices.len(),
+        "reapplied input device config"
+    );
 }
 
-/// Queue all outputs for redraw on the next drain cycle.
-pub fn queue_redraw_all(state: &mut Raven) {
-    let Some(udev) = state.udev_data.as_mut() else {
+/// Reapply `scale` and `transform` from the monitor config to connected
+/// outputs and lay them out again, since their logical sizes change with it.
+/// Outputs without an explicit position line up left to right as on connect.
+/// Mode changes still take a reconnect.
+pub fn apply_monitor_config(state: &mut Raven) {
+    let Some(udev) = state.udev_data.as_ref() else {
         return;
     };
+    let mut outputs: Vec<(Output, Option<MonitorConfig>)> = udev
+        .backends
+        .values()
+        .flat_map(|backend| backend.surfaces.values())
+        .filter(|surface| surface.paused_location.is_none())
+        .map(|surface| {
+            let output = surface.output.clone();
+            let monitor = select_monitor_config(&state.config.monitors, &output.name());
+            (output, monitor)
+        })
+        .collect();
 
-    let mut to_queue: Vec<(DrmNode, crtc::Handle)> = Vec::new();
+    let mut changed = false;
+    for (output, monitor) in &outputs {
+        let output_name = output.name();
+        let scale = monitor
+            .as_ref()
+            .and_then(|monitor| monitor.scale)
+            .map(output_scale_from_config)
+            .unwrap_or(OutputScale::Integer(1));
+        let transform = monitor
+            .as_ref()
+            .map(|monitor| {
+                monitor_transform_from_config(monitor.transform.as_deref(), &output_name)
+            })
+            .unwrap_or(Transform::Normal);
+        if output.current_scale().fractional_scale() == scale.fractional_scale()
+            && output.current_transform() == transform
+        {
+            continue;
+        }
+        tracing::info!(
+            output = %output_name,
+            scale = scale.fractional_scale(),
+            ?transform,
+            "applying monitor scale"
+        );
+        output.change_current_state(None, Some(transform), Some(scale), None);
+        changed = true;
+    }
+
+    // Explicit positions win; the rest follow the outputs left of them with
+    // their new logical widths.
+    outputs.sort_by_key(|(output, _)| {
+        state
+            .space
+            .output_geometry(output)
+            .map(|geo| geo.loc.x)
+            .unwrap_or_default()
+    });
+    let mut auto_x = 0;
+    for (output, monitor) in &outputs {
+        let x = monitor
+            .as_ref()
+            .and_then(|monitor| monitor.x)
+            .unwrap_or(auto_x);
+        let y = monitor.as_ref().and_then(|monitor| monitor.y).unwrap_or(0);
+        let location = Point::from((x, y));
+        if state
+            .space
+            .output_geometry(output)
+            .is_none_or(|geo| geo.loc != location)
+        {
+            output.change_current_state(None, None, None, Some(location));
+            state.space.map_output(output, location);
+            changed = true;
+        }
+        auto_x += state
+            .space
+            .output_geometry(output)
+            .map(|geo| geo.size.w)
+            .unwrap_or(0);
+    }
+
+    if changed {
+        // Windows and layer surfaces are laid out again by the caller's
+        // `apply_layout`; the next frame on each output sends its surfaces the
+        // new scale.
+        queue_redraw_all(state, RedrawCause::Ipc);
+    }
+}
+
+/// Import buffers for a committed surface early, before the next render pass.
+pub fn early_import(state: &mut Raven, surface: &WlSurface) {
+    let Some(udev) = state.udev_data.as_mut() else {
+        return;
+    };
+
+    if let Err(err) = udev.gpus.early_import(udev.primary_gpu, surface) {
+        tracing::debug!("early import failed: {err:?}");
+    }
+}
+
+/// Stamp a window commit for the `raven stats --clients` latency histograms.
+pub fn note_window_commit(state: &mut Raven, surface: &WlSurface) {
+    if let Some(udev) = state.udev_data.as_mut() {
+        udev.commit_timing.note_commit(surface);
+    }
+}
+
+/// Commit-to-present latency of every live window seen so far.
+pub fn commit_latency_histograms(state: &Raven) -> Vec<(WlSurface, LatencyHistogram)> {
+    let Some(udev) = state.udev_data.as_ref() else {
+        return Vec::new();
+    };
+
+    udev.commit_timing
+        .histograms()
+        .map(|(surface, histogram)| (surface.clone(), histogram.clone()))
+        .collect()
+}
+
+/// Device path of the primary GPU, with its kernel driver when sysfs names one.
+pub fn primary_gpu_description(state: &Raven) -> Option<String> {
+    let udev = state.udev_data.as_ref()?;
+    let Some(path) = udev.primary_gpu.dev_path() else {
+        return Some(format!("{:?}", udev.primary_gpu));
+    };
+    let driver = path
+        .file_name()
+        .and_then(|name| {
+            std::fs::read_link(Path::new("/sys/class/drm").join(name).join("device/driver")).ok()
+        })
+        .and_then(|driver| {
+            driver
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+        });
+    Some(match driver {
+        Some(driver) => format!("{} ({driver})", path.display()),
+        None => path.display().to_string(),
+    })
+}
+
+/// Render `output` offscreen on the primary GPU and read it back as RGBA.
+///
+/// Only windows and layer surfaces are captured; the cursor is left out.
+pub fn capture_output(
+    state: &mut Raven,
+    output: &Output,
+) -> Result<OutputCapture, CompositorError> {
+    let logical = state.space.output_geometry(output).ok_or_else(|| {
+        CompositorError::Screencopy(format!("output {} is not mapped", output.name()))
+    })?;
+
+    let udev = state.udev_data.as_mut().ok_or_else(|| {
+        CompositorError::Screencopy("screenshots need the DRM backend".to_owned())
+    })?;
+    let primary_gpu = udev.primary_gpu;
+    let mut renderer = udev
+        .gpus
+        .single_renderer(&primary_gpu)
+        .map_err(|err| CompositorError::Screencopy(format!("renderer: {err}")))?;
+
+    let elements = space_render_elements(&mut renderer, [&state.space], output, 1.0)
+        .map_err(|err| CompositorError::Screencopy(format!("render elements: {err:?}")))?;
+    let pool = udev.offscreen_pools.entry(primary_gpu).or_default();
+    render_capture(&mut renderer, pool, output, logical, &elements)
+}
+
+/// Render `elements` offscreen at the output's mode size and read the pixels back.
+fn render_capture<'a, E: RenderElement<UdevRenderer<'a>>>(
+    renderer: &mut UdevRenderer<'a>,
+    pool: &mut OffscreenPool,
+    output: &Output,
+    logical: Rectangle<i32, smithay::utils::Logical>,
+    elements: &[E],
+) -> Result<OutputCapture, CompositorError> {
+    let mode = output.current_mode().ok_or_else(|| {
+        CompositorError::Screencopy(format!("output {} has no mode", output.name()))
+    })?;
+    let size = output.current_transform().transform_size(mode.size);
+    let scale = output.current_scale().fractional_scale();
+    let pixels = render_offscreen(renderer, pool, size, scale, elements)?;
+
+    Ok(OutputCapture {
+        name: output.name(),
+        logical,
+        size,
+        pixels,
+    })
+}
+
+/// Render `window` alone offscreen on the primary GPU and read it back as
+/// RGBA, at the scale of the output it is on. Works for windows that are not
+/// currently shown, as long as their buffers are still attached.
+pub fn capture_window(
+    state: &mut Raven,
+    window: &Window,
+) -> Result<(smithay::utils::Size<i32, Physical>, Vec<u8>), CompositorError> {
+    let scale = state
+        .toplevel_output(window)
+        .map_or(1.0, |output| output.current_scale().fractional_scale());
+    let geometry = window.geometry();
+    let size = geometry.size.to_physical_precise_round(scale);
+    if size.w <= 0 || size.h <= 0 {
+        return Err(CompositorError::Screencopy(
+            "window has no size yet".to_owned(),
+        ));
+    }
+
+    let udev = state.udev_data.as_mut().ok_or_else(|| {
+        CompositorError::Screencopy("window capture needs the DRM backend".to_owned())
+    })?;
+    let primary_gpu = udev.primary_gpu;
+    let mut renderer = udev
+        .gpus
+        .single_renderer(&primary_gpu)
+        .map_err(|err| CompositorError::Screencopy(format!("renderer: {err}")))?;
+
+    // Place the window geometry, not its shadows, at the buffer origin.
+    let location = Point::from((-geometry.loc.x, -geometry.loc.y)).to_physical_precise_round(scale);
+    let elements: Vec<WaylandSurfaceRenderElement<UdevRenderer<'_>>> =
+        window.render_elements(&mut renderer, location, Scale::from(scale), 1.0);
+    let pool = udev.offscreen_pools.entry(primary_gpu).or_default();
+    let pixels = render_offscreen(&mut renderer, pool, size, scale, &elements)?;
+    Ok((size, pixels))
+}
+
+/// Render `elements` into a pooled texture of `size` and read back its RGBA pixels.
+fn render_offscreen<'a, E: RenderElement<UdevRenderer<'a>>>(
+    renderer: &mut UdevRenderer<'a>,
+    pool: &mut OffscreenPool,
+    size: smithay::utils::Size<i32, Physical>,
+    scale: f64,
+    elements: &[E],
+) -> Result<Vec<u8>, CompositorError> {
+    let buffer_size = size.to_logical(1).to_buffer(1, Transform::Normal);
+    let mut target = pool
+        .acquire(renderer, Fourcc::Abgr8888, buffer_size)
+        .map_err(|err| CompositorError::Screencopy(format!("create_buffer: {err:?}")))?;
+    let mut framebuffer = renderer
+        .bind(target.texture())
+        .map_err(|err| CompositorError::Screencopy(format!("bind: {err:?}")))?;
+
+    // Render untransformed so the capture matches the logical layout.
+    let mut damage_tracker = OutputDamageTracker::new(size, scale, Transform::Normal);
+    let result = damage_tracker
+        .render_output(renderer, &mut framebuffer, 0, elements, CLEAR_COLOR)
+        .map_err(|err| CompositorError::Screencopy(format!("render_output: {err:?}")))?;
+    if let Err(err) = result.sync.wait() {
+        tracing::warn!("error waiting for capture render: {err:?}");
+    }
+
+    let mapping = renderer
+        .copy_framebuffer(
+            &framebuffer,
+            Rectangle::from_size(buffer_size),
+            Fourcc::Abgr8888,
+        )
+        .map_err(|err| CompositorError::Screencopy(format!("copy_framebuffer: {err:?}")))?;
+    let pixels = renderer
+        .map_texture(&mapping)
+        .map_err(|err| CompositorError::Screencopy(format!("map_texture: {err:?}")))?;
+    Ok(pixels.to_vec())
+}
+
+/// Serve the wlr-screencopy frames waiting on `output` from this frame's
+/// elements. `copy_with_damage` frames stay queued until something changed
+/// since the last one was served, then report what did.
+fn serve_screencopies<'a, E: RenderElement<UdevRenderer<'a>>>(
+    renderer: &mut UdevRenderer<'a>,
+    pool: &mut OffscreenPool,
+    damage_tracker: &mut Option<(smithay::utils::Size<i32, Physical>, OutputDamageTracker)>,
+    pending: &mut Vec<Screencopy>,
+    output: &Output,
+    elements: &[E],
+    timestamp: Duration,
+) {
+    let (waiting, others): (Vec<_>, Vec<_>) = std::mem::take(pending)
+        .into_iter()
+        .partition(|screencopy| screencopy.output() == output);
+    *pending = others;
+    // Frames dropped from here on fail on the client's side.
+    let Some(mode) = output.current_mode() else {
+        return;
+    };
+    let size = output.current_transform().transform_size(mode.size);
+    let scale = output.current_scale().fractional_scale();
+
+    let damage = if waiting.iter().any(Screencopy::with_damage) {
+        if damage_tracker
+            .as_ref()
+            .is_none_or(|(tracked, _)| *tracked != size)
+        {
+            *damage_tracker = Some((
+                size,
+                OutputDamageTracker::new(size, scale, Transform::Normal),
+            ));
+        }
+        let (_, tracker) = damage_tracker.as_mut().unwrap();
+        match tracker.damage_output(1, elements) {
+            Ok((damage, _)) => damage.map(|damage| damage.to_vec()).unwrap_or_default(),
+            Err(err) => {
+                tracing::warn!("screencopy damage failed: {err:?}");
+                vec![Rectangle::from_size(size)]
+            }
+        }
+    } else {
+        Vec::new()
+    };
+
+    let (ready, held): (Vec<_>, Vec<_>) = waiting
+        .into_iter()
+        .partition(|screencopy| !screencopy.with_damage() || !damage.is_empty());
+    pending.extend(held);
+    if ready.is_empty() {
+        return;
+    }
+
+    let pixels = match render_offscreen(renderer, pool, size, scale, elements) {
+        Ok(pixels) => pixels,
+        Err(err) => {
+            tracing::warn!("screencopy failed: {err}");
+            return;
+        }
+    };
+    for screencopy in ready {
+        if screencopy.buffer_size() != size {
+            // The output changed mode since the client allocated its buffer.
+            continue;
+        }
+        if screencopy.with_damage() {
+            screencopy.damage(&damage);
+        }
+        match copy_rgba_to_shm(&screencopy.buffer, size, &pixels) {
+            Ok(()) => screencopy.submit(timestamp),
+            Err(err) => tracing::warn!("screencopy failed: {err}"),
+        }
+    }
+}
+
+/// GBM device of the primary GPU, for buffers shared with other processes.
+#[cfg(feature = "screencast")]
+pub fn primary_gbm(state: &Raven) -> Option<GbmDevice<DrmDeviceFd>> {
+    let udev = state.udev_data.as_ref()?;
+    udev.backends
+        .iter()
+        .find(|(node, device)| {
+            **node == udev.primary_gpu || device.render_node == Some(udev.primary_gpu)
+        })
+        .map(|(_, device)| device.gbm.clone())
+}
+
+/// Feed this frame of `output` to casts of it and of the windows shown on it.
+#[cfg(feature = "screencast")]
+fn render_casts<'a, E: RenderElement<UdevRenderer<'a>>>(
+    renderer: &mut UdevRenderer<'a>,
+    casts: &mut [crate::screencast::Cast],
+    space: &smithay::desktop::Space<Window>,
+    output: &Output,
+    elements: &[E],
+) {
+    use crate::screencast::CastTarget;
+
+    let Some(mode) = output.current_mode() else {
+        return;
+    };
+    let output_size = output.current_transform().transform_size(mode.size);
+    let scale = output.current_scale().fractional_scale();
+
+    for cast in casts {
+        let window = match &cast.target {
+            CastTarget::Output(target) => {
+                if target.upgrade().as_ref() != Some(output) {
+                    continue;
+                }
+                None
+            }
+            CastTarget::Window(window) => {
+                // A window spanning outputs is cast from the first one.
+                if space.outputs_for_element(window).first() != Some(output) {
+                    continue;
+                }
+                Some(window.clone())
+            }
+        };
+
+        let result = match window {
+            None => cast.render_frame(output_size, |dmabuf| {
+                render_to_dmabuf(renderer, dmabuf, output_size, scale, elements)
+            }),
+            Some(window) => {
+                let geometry = window.geometry();
+                let size = geometry.size.to_physical_precise_round(scale);
+                let location = Point::from((-geometry.loc.x, -geometry.loc.y))
+                    .to_physical_precise_round(scale);
+                let window_elements: Vec<WaylandSurfaceRenderElement<UdevRenderer<'a>>> =
+                    window.render_elements(renderer, location, Scale::from(scale), 1.0);
+                cast.render_frame(size, |dmabuf| {
+                    render_to_dmabuf(renderer, dmabuf, size, scale, &window_elements)
+                })
+            }
+        };
+        if let Err(err) = result {
+            tracing::warn!(
+                session = cast.session,
+                "failed to render screencast frame: {err}"
+            );
+        }
+    }
+}
+
+/// Render `elements` into a cast's dmabuf, untransformed like captures.
+#[cfg(feature = "screencast")]
+fn render_to_dmabuf<'a, E: RenderElement<UdevRenderer<'a>>>(
+    renderer: &mut UdevRenderer<'a>,
+    dmabuf: &mut smithay::backend::allocator::dmabuf::Dmabuf,
+    size: smithay::utils::Size<i32, Physical>,
+    scale: f64,
+    elements: &[E],
+) -> Result<(), CompositorError> {
+    let mut framebuffer = renderer
+        .bind(dmabuf)
+        .map_err(|err| CompositorError::Screencopy(format!("bind: {err:?}")))?;
+    let mut damage_tracker = OutputDamageTracker::new(size, scale, Transform::Normal);
+    let result = damage_tracker
+        .render_output(renderer, &mut framebuffer, 0, elements, CLEAR_COLOR)
+        .map_err(|err| CompositorError::Screencopy(format!("render_output: {err:?}")))?;
+    if let Err(err) = result.sync.wait() {
+        tracing::warn!("error waiting for screencast render: {err:?}");
+    }
+    Ok(())
+}
+
+fn record_redraw_cause(udev: &mut UdevData, output: &Output, cause: RedrawCause) {
+    *udev
+        .redraw_cause_counts
+        .entry((output.name(), cause))
+        .or_insert(0) += 1;
+    render_trace_line(format!(
+        "queue_redraw output={} cause={}",
+        output.name(),
+        cause.as_str()
+    ));
+}
+
+/// Queue all outputs for redraw on the next drain cycle.
+pub fn queue_redraw_all(state: &mut Raven, cause: RedrawCause) {
+    let Some(udev) = state.udev_data.as_mut() else {
+        return;
+    };
+
+    let mut to_queue: Vec<(DrmNode, crtc::Handle, Output)> = Vec::new();
     for (node, backend) in &mut udev.backends {
         for (crtc, surface) in &mut backend.surfaces {
+            if surface.crtc_released() {
+                continue;
+            }
             // Ensure queued redraws always produce at least one damaged region.
             // Without this, remapped workspaces can occasionally hit an empty-damage frame
             // and stay visually stale until a later input/commit triggers new damage.
             surface.backdrop.touch();
             surface.redraw_state = std::mem::take(&mut surface.redraw_state).queue_redraw();
-            to_queue.push((*node, *crtc));
+            to_queue.push((*node, *crtc, surface.output.clone()));
         }
     }
 
-    for entry in to_queue {
-        udev.queued_redraws.insert(entry);
+    for (node, crtc, output) in to_queue {
+        record_redraw_cause(udev, &output, cause);
+        udev.queued_redraws.insert((node, crtc));
     }
 }
 
 /// Queue a specific output for redraw on the next drain cycle.
-pub fn queue_redraw_for_output(state: &mut Raven, output: &Output) {
+pub fn queue_redraw_for_output(state: &mut Raven, output: &Output, cause: RedrawCause) {
     let Some(udev) = state.udev_data.as_mut() else {
         return;
     };
@@ -1201,7 +2520,7 @@ pub fn queue_redraw_for_output(state: &mut Raven, output: &Output) {
     let mut to_queue: Vec<(DrmNode, crtc::Handle)> = Vec::new();
     for (node, backend) in &mut udev.backends {
         for (crtc, surface) in &mut backend.surfaces {
-            if surface.output == *output {
+            if surface.output == *output && !surface.crtc_released() {
                 // Force damage for explicit output redraw requests too.
                 surface.backdrop.touch();
                 surface.redraw_state = std::mem::take(&mut surface.redraw_state).queue_redraw();
@@ -1214,11 +2533,27 @@ pub fn queue_redraw_for_output(state: &mut Raven, output: &Output) {
         return;
     }
 
+    record_redraw_cause(udev, output, cause);
     for entry in to_queue {
         udev.queued_redraws.insert(entry);
     }
 }
 
+/// Per-output redraw request counts grouped by cause, sorted by output name.
+pub fn redraw_cause_counts(state: &Raven) -> Vec<(String, RedrawCause, u64)> {
+    let Some(udev) = state.udev_data.as_ref() else {
+        return Vec::new();
+    };
+
+    let mut counts = udev
+        .redraw_cause_counts
+        .iter()
+        .map(|((output, cause), count)| (output.clone(), *cause, *count))
+        .collect::<Vec<_>>();
+    counts.sort();
+    counts
+}
+
 /// Drain queued redraw requests and render each targeted output once.
 pub fn drain_queued_redraws(state: &mut Raven) {
     let queued = {
@@ -1233,6 +2568,347 @@ pub fn drain_queued_redraws(state: &mut Raven) {
     }
 }
 
+/// Stop driving the CRTC behind `output_name` so another DRM master (a VM with
+/// GPU passthrough, a nested compositor) can take it over, or reclaim it.
+///
+/// A paused output is removed from the layout; resuming maps it back at its
+/// previous position and forces a full modeset on the next frame.
+pub fn set_output_paused(
+    state: &mut Raven,
+    output_name: &str,
+    paused: bool,
+) -> Result<(), CompositorError> {
+    let loop_handle = state.loop_handle.clone();
+    let udev = state.udev_data.as_mut().ok_or_else(|| {
+        CompositorError::Backend("pausing outputs needs the DRM backend".to_owned())
+    })?;
+    let Some((node, crtc, surface)) = udev.backends.iter_mut().find_map(|(node, backend)| {
+        backend
+            .surfaces
+            .iter_mut()
+            .find(|(_, surface)| surface.output.name() == output_name)
+            .map(|(crtc, surface)| (*node, *crtc, surface))
+    }) else {
+        return Err(CompositorError::Backend(format!(
+            "unknown output `{output_name}`"
+        )));
+    };
+    if surface.paused_location.is_some() == paused {
+        return Ok(());
+    }
+    let output = surface.output.clone();
+
+    if paused {
+        match std::mem::take(&mut surface.redraw_state) {
+            RedrawState::WaitingForEstimatedVBlank(token)
+            | RedrawState::WaitingForEstimatedVBlankAndQueued(token) => loop_handle.remove(token),
+            _ => {}
+        }
+        surface
+            .drm_output
+            .with_compositor(|compositor| compositor.clear())
+            .map_err(|err| {
+                CompositorError::Backend(format!("failed to disable {output_name}: {err:?}"))
+            })?;
+        let location = state
+            .space
+            .output_geometry(&output)
+            .map(|geo| geo.loc)
+            .unwrap_or_default();
+        surface.paused_location = Some(location);
+        udev.queued_redraws.remove(&(node, crtc));
+        state.space.unmap_output(&output);
+        tracing::info!(output = %output_name, ?crtc, "output paused, crtc released");
+    } else {
+        if let Err(err) = surface
+            .drm_output
+            .with_compositor(|compositor| compositor.reset_state())
+        {
+            tracing::warn!(output = %output_name, "failed to reset drm state on resume: {err:?}");
+        }
+        let location = surface.paused_location.take().unwrap_or_default();
+        state.space.map_output(&output, location);
+        tracing::info!(output = %output_name, ?crtc, "output resumed");
+        reapply_gamma(state, &output);
+    }
+
+    if let Err(err) = state.apply_layout() {
+        tracing::warn!("failed to apply layout after output pause change: {err}");
+    }
+    queue_redraw_all(state, RedrawCause::Ipc);
+    Ok(())
+}
+
+/// Blank `output` with DPMS, or light it up again. The output keeps its place in
+/// the layout and its DRM state, so powering on needs no modeset from scratch.
+pub fn set_output_power(
+    state: &mut Raven,
+    output: &Output,
+    on: bool,
+) -> Result<(), CompositorError> {
+    let loop_handle = state.loop_handle.clone();
+    let output_name = output.name();
+    let udev = state.udev_data.as_mut().ok_or_else(|| {
+        CompositorError::Backend("switching output power needs the DRM backend".to_owned())
+    })?;
+    let Some((node, crtc, surface)) = udev.backends.iter_mut().find_map(|(node, backend)| {
+        backend
+            .surfaces
+            .iter_mut()
+            .find(|(_, surface)| surface.output == *output)
+            .map(|(crtc, surface)| (*node, *crtc, surface))
+    }) else {
+        return Err(CompositorError::Backend(format!(
+            "unknown output `{output_name}`"
+        )));
+    };
+    if surface.paused_location.is_some() {
+        return Err(CompositorError::Backend(format!(
+            "output `{output_name}` is paused"
+        )));
+    }
+    if surface.powered_off != on {
+        return Ok(());
+    }
+
+    if on {
+        if let Err(err) = surface
+            .drm_output
+            .with_compositor(|compositor| compositor.reset_state())
+        {
+            tracing::warn!(output = %output_name, "failed to reset drm state on power on: {err:?}");
+        }
+        surface.powered_off = false;
+        tracing::info!(output = %output_name, ?crtc, "output powered on");
+        reapply_gamma(state, output);
+        queue_redraw_for_output(state, output, RedrawCause::Ipc);
+    } else {
+        match std::mem::take(&mut surface.redraw_state) {
+            RedrawState::WaitingForEstimatedVBlank(token)
+            | RedrawState::WaitingForEstimatedVBlankAndQueued(token) => loop_handle.remove(token),
+            _ => {}
+        }
+        surface
+            .drm_output
+            .with_compositor(|compositor| compositor.clear())
+            .map_err(|err| {
+                CompositorError::Backend(format!("failed to power off {output_name}: {err:?}"))
+            })?;
+        surface.powered_off = true;
+        udev.queued_redraws.remove(&(node, crtc));
+        tracing::info!(output = %output_name, ?crtc, "output powered off");
+    }
+    state.output_power_changed(output, on);
+    Ok(())
+}
+
+/// Whether `output` is lit: not blanked by DPMS. Always true off the DRM backend.
+pub fn output_powered(state: &Raven, output: &Output) -> bool {
+    let Some(udev) = state.udev_data.as_ref() else {
+        return true;
+    };
+    !udev
+        .backends
+        .values()
+        .flat_map(|backend| backend.surfaces.values())
+        .any(|surface| surface.output == *output && surface.powered_off)
+}
+
+/// Turn adaptive sync on or off where the connector supports it without a modeset.
+fn set_vrr(surface: &mut SurfaceData, on: bool) {
+    if surface.vrr == on {
+        return;
+    }
+    let connector = surface.connector;
+    let result = surface.drm_output.with_compositor(|compositor| {
+        if on
+            && !matches!(
+                compositor.vrr_supported(connector),
+                Ok(VrrSupport::Supported)
+            )
+        {
+            return Ok(false);
+        }
+        compositor.use_vrr(on).map(|()| true)
+    });
+    match result {
+        Ok(true) => {
+            tracing::info!(output = %surface.output.name(), vrr = on, "adaptive sync changed");
+            surface.vrr = on;
+        }
+        Ok(false) => {}
+        Err(err) => {
+            tracing::warn!(output = %surface.output.name(), "failed to set adaptive sync: {err:?}");
+        }
+    }
+}
+
+/// Whether adaptive sync is on for `output`.
+pub fn output_vrr(state: &Raven, output: &Output) -> bool {
+    state.udev_data.as_ref().is_some_and(|udev| {
+        udev.backends
+            .values()
+            .flat_map(|backend| backend.surfaces.values())
+            .any(|surface| surface.output == *output && surface.vrr)
+    })
+}
+
+fn load_gamma(fd: &DrmDeviceFd, crtc: crtc::Handle, ramp: &[u16]) -> std::io::Result<()> {
+    let size = ramp.len() / 3;
+    let (red, rest) = ramp.split_at(size);
+    let (green, blue) = rest.split_at(size);
+    fd.set_gamma(crtc, red, green, blue)
+}
+
+/// Gamma ramp entries per channel of the CRTC behind `output`, if it has a ramp.
+pub fn gamma_size(state: &Raven, output: &Output) -> Option<u32> {
+    let udev = state.udev_data.as_ref()?;
+    udev.backends.values().find_map(|backend| {
+        let (crtc, _) = backend
+            .surfaces
+            .iter()
+            .find(|(_, surface)| surface.output == *output)?;
+        let info = backend.drm_output_manager.device().get_crtc(*crtc).ok()?;
+        Some(info.gamma_length()).filter(|size| *size > 0)
+    })
+}
+
+fn read_gamma(fd: &DrmDeviceFd, crtc: crtc::Handle) -> std::io::Result<Vec<u16>> {
+    let size = fd.get_crtc(crtc)?.gamma_length() as usize;
+    if size == 0 {
+        return Err(std::io::Error::other("crtc has no gamma ramp"));
+    }
+    let mut ramp = vec![0; size * 3];
+    let (red, rest) = ramp.split_at_mut(size);
+    let (green, blue) = rest.split_at_mut(size);
+    fd.get_gamma(crtc, red, green, blue)?;
+    Ok(ramp)
+}
+
+/// Change what overrides the gamma ramp of the CRTC behind `output`, and load
+/// the result. A released CRTC gets it once it is lit again.
+fn update_gamma(
+    state: &mut Raven,
+    output: &Output,
+    update: impl FnOnce(&mut GammaOverride),
+) -> Result<(), CompositorError> {
+    let udev = state.udev_data.as_mut().ok_or_else(|| {
+        CompositorError::Backend("gamma control needs the DRM backend".to_owned())
+    })?;
+    let Some((fd, crtc, surface)) = udev.backends.values_mut().find_map(|backend| {
+        let fd = backend.drm_output_manager.device().device_fd().clone();
+        backend
+            .surfaces
+            .iter_mut()
+            .find(|(_, surface)| surface.output == *output)
+            .map(|(crtc, surface)| (fd, *crtc, surface))
+    }) else {
+        return Err(CompositorError::Backend(format!(
+            "unknown output `{}`",
+            output.name()
+        )));
+    };
+
+    let mut gamma = surface.gamma.take().unwrap_or(GammaOverride {
+        original: Vec::new(),
+        client: None,
+        night_light: None,
+    });
+    update(&mut gamma);
+    if gamma.original.is_empty() {
+        if gamma.client.is_none() && gamma.night_light.is_none() {
+            return Ok(());
+        }
+        gamma.original = read_gamma(&fd, crtc)
+            .map_err(|err| CompositorError::Backend(format!("failed to read gamma: {err}")))?;
+    }
+
+    let ramp = gamma.ramp();
+    let result = match &ramp {
+        _ if surface.crtc_released() => Ok(()),
+        Some(ramp) => load_gamma(&fd, crtc, ramp),
+        None => load_gamma(&fd, crtc, &gamma.original),
+    };
+    // Keep the original around for a released CRTC; it is loaded when it comes back.
+    if ramp.is_some() || surface.crtc_released() {
+        surface.gamma = Some(gamma);
+    }
+    result.map_err(|err| CompositorError::Backend(format!("failed to set gamma: {err}")))
+}
+
+/// Load `ramp`, the red, green and blue channels back to back, onto the CRTC
+/// behind `output` for a gamma control client, or drop the client's ramp with `None`.
+pub fn set_gamma(
+    state: &mut Raven,
+    output: &Output,
+    ramp: Option<Vec<u16>>,
+) -> Result<(), CompositorError> {
+    update_gamma(state, output, |gamma| gamma.client = ramp)
+}
+
+/// Scale the gamma ramp of `output` by the night light's red, green and blue
+/// multipliers, or stop with `None`. A gamma control client's ramp wins.
+pub fn set_night_light(
+    state: &mut Raven,
+    output: &Output,
+    whitepoint: Option<[f64; 3]>,
+) -> Result<(), CompositorError> {
+    update_gamma(state, output, |gamma| gamma.night_light = whitepoint)
+}
+
+/// Load the gamma ramp of `output` again after its CRTC was released or another
+/// DRM master held it.
+fn reapply_gamma(state: &Raven, output: &Output) {
+    let Some(udev) = state.udev_data.as_ref() else {
+        return;
+    };
+    for backend in udev.backends.values() {
+        let fd = backend.drm_output_manager.device().device_fd();
+        for (crtc, surface) in &backend.surfaces {
+            if surface.output != *output || surface.crtc_released() {
+                continue;
+            }
+            let Some(gamma) = &surface.gamma else {
+                continue;
+            };
+            let ramp = gamma.ramp();
+            if let Err(err) = load_gamma(fd, *crtc, ramp.as_deref().unwrap_or(&gamma.original)) {
+                tracing::warn!(output = %output.name(), "failed to reapply gamma: {err}");
+            }
+        }
+    }
+}
+
+/// Outputs whose CRTC is released by `output <name> pause`. They are off the
+/// layout, so `space.outputs()` does not list them.
+pub fn paused_outputs(state: &Raven) -> Vec<Output> {
+    let Some(udev) = state.udev_data.as_ref() else {
+        return Vec::new();
+    };
+    udev.backends
+        .values()
+        .flat_map(|backend| backend.surfaces.values())
+        .filter(|surface| surface.paused_location.is_some())
+        .map(|surface| surface.output.clone())
+        .collect()
+}
+
+/// Whether frames on `output` currently go straight to the plane or through
+/// the compositor, with the reason when they do not. `None` off the DRM backend.
+pub fn scanout_status(state: &Raven, output: &Output) -> Option<String> {
+    state.udev_data.as_ref()?;
+    if !scanout_enabled() {
+        return Some("disabled".to_owned());
+    }
+    let fullscreen_on_output = state.output_has_fullscreen_window(output);
+    Some(
+        match scanout_rejection_reason(state, output, fullscreen_on_output) {
+            Some(reason) => format!("composited ({reason})"),
+            None => "direct".to_owned(),
+        },
+    )
+}
+
 /// Find the primary GPU node
 fn find_primary_gpu(session: &LibSeatSession) -> Option<DrmNode> {
     primary_gpu(session.seat())
@@ -1404,11 +3080,15 @@ fn device_added(
         drm,
         allocator,
         framebuffer_exporter,
-        Some(gbm),
+        Some(gbm.clone()),
         SUPPORTED_FORMATS.iter().copied(),
         render_formats,
     );
 
+    let lease_global = DrmLeaseState::new::<Raven>(&state.display_handle, &node)
+        .map_err(|err| tracing::warn!(?node, "failed to create drm lease global: {err}"))
+        .ok();
+
     udev.backends.insert(
         node,
         BackendData {
@@ -1417,6 +3097,10 @@ fn device_added(
             drm_scanner: DrmScanner::new(),
             render_node,
             surfaces: HashMap::new(),
+            lease_global,
+            non_desktop_connectors: Vec::new(),
+            active_leases: Vec::new(),
+            gbm,
         },
     );
 
@@ -1456,10 +3140,12 @@ fn device_changed(state: &mut Raven, node: DrmNode) {
                 connector_connected(state, node, connector, crtc);
             }
             DrmScanEvent::Disconnected {
-                connector: _,
+                connector,
                 crtc: Some(crtc),
             } => {
-                connector_disconnected(state, node, crtc);
+                if !withdraw_leasable_connector(state, node, connector.handle()) {
+                    connector_disconnected(state, node, crtc);
+                }
             }
             _ => {}
         }
@@ -1505,6 +3191,23 @@ fn connector_connected(
         connector.interface().as_str(),
         connector.interface_id()
     );
+
+    // VR headsets are left to whoever leases them.
+    if is_non_desktop(device.drm_output_manager.device(), connector.handle()) {
+        tracing::info!(output = %output_name, "non-desktop connector; offering it for lease");
+        device
+            .non_desktop_connectors
+            .push((connector.handle(), crtc));
+        if let Some(lease_global) = device.lease_global.as_mut() {
+            lease_global.add_connector::<Raven>(
+                connector.handle(),
+                output_name,
+                format!("{make} {model}"),
+            );
+        }
+        return;
+    }
+
     let monitor_config = select_monitor_config(&state.config.monitors, &output_name);
 
     if let Some(monitor) = monitor_config.as_ref()
@@ -1532,9 +3235,9 @@ fn connector_connected(
         );
     }
 
-    // Select mode from config if present, otherwise preferred mode.
-    let mode_idx = select_mode_index(&output_name, connector.modes(), monitor_config.as_ref());
-    let drm_mode = connector.modes()[mode_idx];
+    // Select mode from config (or its fallbacks) if present, otherwise preferred mode.
+    let mode_choice = select_mode(&output_name, connector.modes(), monitor_config.as_ref());
+    let drm_mode = connector.modes()[mode_choice.index];
     let wl_mode = WlMode::from(drm_mode);
 
     // Create Wayland output
@@ -1591,6 +3294,7 @@ fn connector_connected(
         Ok(r) => r,
         Err(e) => {
             tracing::error!("Failed to get renderer for connector: {e}");
+            report_output_failure(&loop_handle, &output_name, format!("renderer: {e}"));
             return;
         }
     };
@@ -1611,6 +3315,11 @@ fn connector_connected(
         Ok(output) => output,
         Err(e) => {
             tracing::error!("Failed to initialize DRM output: {e:?}");
+            report_output_failure(
+                &loop_handle,
+                &output_name,
+                format!("failed to initialize DRM output: {e:?}"),
+            );
             return;
         }
     };
@@ -1628,6 +3337,16 @@ fn connector_connected(
             redraw_state: RedrawState::Queued,
             frame_callback_sequence: 0,
             vblank_throttle: VBlankThrottle::new(loop_handle, output_name.clone()),
+            paused_location: None,
+            powered_off: false,
+            gamma: None,
+            frame_commits: Vec::new(),
+            render_failure: None,
+            connector: connector.handle(),
+            video_or_game: false,
+            vrr: false,
+            screencopy_damage: None,
+            last_frame: None,
         },
     );
 
@@ -1639,32 +3358,53 @@ fn connector_connected(
         scale = output.current_scale().fractional_scale(),
         position_x = x,
         position_y = y,
+        reason = %mode_choice.reason,
         "Output initialized"
     );
+    state.broadcast_output_mode_event(&output_name, wl_mode, &mode_choice.reason);
+    state.report_progress("output", Some(&output_name), ProgressStatus::Succeeded);
+    state.output_added_event(&output_name);
+    state.outputs_changed();
 
     // Schedule initial render
     let handle = state.loop_handle.clone();
     handle.insert_idle(move |state| {
         render_surface(state, node, crtc);
     });
-}
-
-fn select_mode_index(output_name: &str, modes: &[Mode], monitor: Option<&MonitorConfig>) -> usize {
-    let preferred_idx = modes
-        .iter()
-        .position(|mode| mode.mode_type().contains(ModeTypeFlags::PREFERRED))
-        .unwrap_or(0);
-    let Some(monitor) = monitor else {
-        return preferred_idx;
-    };
-    let requested_size = monitor.width.zip(monitor.height);
-    let requested_refresh = monitor.refresh_hz;
+}
+
+/// The mode picked for a connector and why, reported to IPC `output` subscribers.
+struct ModeChoice {
+    index: usize,
+    reason: String,
+}
+
+/// Report a connector that failed to come up once `state` is no longer borrowed.
+fn report_output_failure(
+    loop_handle: &smithay::reexports::calloop::LoopHandle<'static, Raven>,
+    output_name: &str,
+    error: String,
+) {
+    let output_name = output_name.to_owned();
+    loop_handle.insert_idle(move |state| {
+        state.report_progress("output", Some(&output_name), ProgressStatus::Failed(error));
+    });
+}
 
-    if requested_size.is_none() && requested_refresh.is_none() {
-        return preferred_idx;
+fn describe_monitor_mode(mode: &MonitorMode) -> String {
+    match mode.refresh_hz {
+        Some(refresh_hz) => format!("{}x{}@{refresh_hz}", mode.width, mode.height),
+        None => format!("{}x{}", mode.width, mode.height),
     }
+}
 
-    let candidate = modes
+/// Closest connector mode to the requested size and refresh rate, if the size is offered.
+fn find_mode_index(
+    modes: &[Mode],
+    requested_size: Option<(u16, u16)>,
+    requested_refresh: Option<f64>,
+) -> Option<usize> {
+    modes
         .iter()
         .enumerate()
         .filter(|(_, mode)| {
@@ -1689,9 +3429,34 @@ fn select_mode_index(output_name: &str, modes: &[Mode], monitor: Option<&Monitor
                     right_preferred.cmp(&left_preferred)
                 })
                 .then_with(|| right.vrefresh().cmp(&left.vrefresh()))
-        });
+        })
+        .map(|(index, _)| index)
+}
+
+fn select_mode(output_name: &str, modes: &[Mode], monitor: Option<&MonitorConfig>) -> ModeChoice {
+    let preferred_idx = modes
+        .iter()
+        .position(|mode| mode.mode_type().contains(ModeTypeFlags::PREFERRED))
+        .unwrap_or(0);
+    let Some(monitor) = monitor else {
+        return ModeChoice {
+            index: preferred_idx,
+            reason: "preferred mode (no monitor config)".to_owned(),
+        };
+    };
+    let requested_size = monitor.width.zip(monitor.height);
+    let requested_refresh = monitor.refresh_hz;
+    let has_request = requested_size.is_some() || requested_refresh.is_some();
+
+    if !has_request && monitor.fallback_modes.is_empty() {
+        return ModeChoice {
+            index: preferred_idx,
+            reason: "preferred mode (no mode configured)".to_owned(),
+        };
+    }
 
-    if let Some((index, selected_mode)) = candidate {
+    if has_request && let Some(index) = find_mode_index(modes, requested_size, requested_refresh) {
+        let selected_mode = modes[index];
         tracing::info!(
             output = %output_name,
             requested_width = requested_size.map(|(w, _)| w),
@@ -1702,7 +3467,35 @@ fn select_mode_index(output_name: &str, modes: &[Mode], monitor: Option<&Monitor
             selected_refresh_hz = selected_mode.vrefresh(),
             "selected monitor mode from config"
         );
-        return index;
+        return ModeChoice {
+            index,
+            reason: "configured mode".to_owned(),
+        };
+    }
+
+    for (position, fallback) in monitor.fallback_modes.iter().enumerate() {
+        let fallback_size = Some((fallback.width, fallback.height));
+        let Some(index) = find_mode_index(modes, fallback_size, fallback.refresh_hz) else {
+            continue;
+        };
+        let fallback_desc = describe_monitor_mode(fallback);
+        tracing::warn!(
+            output = %output_name,
+            requested_width = requested_size.map(|(w, _)| w),
+            requested_height = requested_size.map(|(_, h)| h),
+            requested_refresh_hz = requested_refresh,
+            fallback = %fallback_desc,
+            "configured monitor mode unavailable; using fallback mode"
+        );
+        let reason = if has_request {
+            format!(
+                "fallback mode {} ({fallback_desc}): configured mode unavailable",
+                position + 1
+            )
+        } else {
+            format!("fallback mode {} ({fallback_desc})", position + 1)
+        };
+        return ModeChoice { index, reason };
     }
 
     tracing::warn!(
@@ -1710,9 +3503,13 @@ fn select_mode_index(output_name: &str, modes: &[Mode], monitor: Option<&Monitor
         requested_width = requested_size.map(|(w, _)| w),
         requested_height = requested_size.map(|(_, h)| h),
         requested_refresh_hz = requested_refresh,
+        fallback_modes = monitor.fallback_modes.len(),
         "no mode matched monitor config; falling back to preferred mode"
     );
-    preferred_idx
+    ModeChoice {
+        index: preferred_idx,
+        reason: "preferred mode: no configured or fallback mode is available".to_owned(),
+    }
 }
 
 fn monitor_transform_from_config(raw: Option<&str>, output_name: &str) -> Transform {
@@ -1790,81 +3587,391 @@ fn connector_disconnected(state: &mut Raven, node: DrmNode, crtc: crtc::Handle)
     };
 
     if let Some(mut surface_data) = device.surfaces.remove(&crtc) {
+        // Leave the CRTC with its own ramp for whichever output gets it next.
+        if let Some(gamma) = surface_data.gamma.take()
+            && let Err(err) = load_gamma(
+                device.drm_output_manager.device().device_fd(),
+                crtc,
+                &gamma.original,
+            )
+        {
+            tracing::debug!(?crtc, "failed to restore gamma: {err}");
+        }
         state.space.unmap_output(&surface_data.output);
         if let Some(global) = surface_data.global.take() {
             state.display_handle.remove_global::<Raven>(global);
         }
         tracing::info!(?crtc, "Connector disconnected, output removed");
+        state.output_power_removed(&surface_data.output);
+        state.output_removed_event(&surface_data.output.name());
+        state.outputs_changed();
+    }
+}
+
+/// Whether `connector` drives a head-mounted display rather than a desktop
+/// monitor, going by its `non-desktop` property.
+fn is_non_desktop(device: &DrmDevice, connector: connector::Handle) -> bool {
+    let Ok(properties) = device.get_properties(connector) else {
+        return false;
+    };
+    properties.into_iter().any(|(property, value)| {
+        device.get_property(property).is_ok_and(|info| {
+            info.name().to_str() == Ok("non-desktop")
+                && info.value_type().convert_value(value).as_boolean() == Some(true)
+        })
+    })
+}
+
+/// Stop offering a non-desktop connector that went away; `false` when
+/// `connector` was a regular output.
+fn withdraw_leasable_connector(
+    state: &mut Raven,
+    node: DrmNode,
+    connector: connector::Handle,
+) -> bool {
+    let Some(device) = state
+        .udev_data
+        .as_mut()
+        .and_then(|udev| udev.backends.get_mut(&node))
+    else {
+        return false;
+    };
+    let Some(index) = device
+        .non_desktop_connectors
+        .iter()
+        .position(|(handle, _)| *handle == connector)
+    else {
+        return false;
+    };
+    device.non_desktop_connectors.remove(index);
+    if let Some(lease_global) = device.lease_global.as_mut() {
+        lease_global.withdraw_connector(connector);
+    }
+    tracing::info!(?connector, "non-desktop connector disconnected");
+    true
+}
+
+impl DrmLeaseHandler for Raven {
+    fn drm_lease_state(&mut self, node: DrmNode) -> &mut DrmLeaseState {
+        self.udev_data
+            .as_mut()
+            .and_then(|udev| udev.backends.get_mut(&node))
+            .and_then(|device| device.lease_global.as_mut())
+            .expect("drm lease request for a device without a lease global")
+    }
+
+    fn lease_request(
+        &mut self,
+        node: DrmNode,
+        request: DrmLeaseRequest,
+    ) -> Result<DrmLeaseBuilder, LeaseRejected> {
+        let device = self
+            .udev_data
+            .as_ref()
+            .and_then(|udev| udev.backends.get(&node))
+            .ok_or_else(LeaseRejected::default)?;
+        let drm = device.drm_output_manager.device();
+
+        let mut builder = DrmLeaseBuilder::new(drm);
+        for connector in request.connectors {
+            // Desktop outputs stay Raven's.
+            let Some((_, crtc)) = device
+                .non_desktop_connectors
+                .iter()
+                .find(|(handle, _)| *handle == connector)
+            else {
+                tracing::warn!(
+                    ?connector,
+                    "lease requested for a desktop connector; denied"
+                );
+                return Err(LeaseRejected::default());
+            };
+            builder.add_connector(connector);
+            builder.add_crtc(*crtc);
+
+            let planes = drm.planes(crtc).map_err(LeaseRejected::with_cause)?;
+            let (primary, primary_claim) = planes
+                .primary
+                .iter()
+                .find_map(|plane| {
+                    drm.claim_plane(plane.handle, *crtc)
+                        .map(|claim| (plane, claim))
+                })
+                .ok_or_else(LeaseRejected::default)?;
+            builder.add_plane(primary.handle, primary_claim);
+            if let Some((cursor, cursor_claim)) = planes.cursor.iter().find_map(|plane| {
+                drm.claim_plane(plane.handle, *crtc)
+                    .map(|claim| (plane, claim))
+            }) {
+                builder.add_plane(cursor.handle, cursor_claim);
+            }
+        }
+        Ok(builder)
+    }
+
+    fn new_active_lease(&mut self, node: DrmNode, lease: DrmLease) {
+        tracing::info!(?node, lease = lease.id(), "drm lease granted");
+        if let Some(device) = self
+            .udev_data
+            .as_mut()
+            .and_then(|udev| udev.backends.get_mut(&node))
+        {
+            device.active_leases.push(lease);
+        }
+    }
+
+    fn lease_destroyed(&mut self, node: DrmNode, lease: u32) {
+        tracing::info!(?node, lease, "drm lease ended");
+        if let Some(device) = self
+            .udev_data
+            .as_mut()
+            .and_then(|udev| udev.backends.get_mut(&node))
+        {
+            device.active_leases.retain(|active| active.id() != lease);
+        }
     }
 }
 
+delegate_drm_lease!(Raven);
+
 /// Handle a DRM device being removed
 fn device_removed(state: &mut Raven, node: DrmNode) {
     let udev = state.udev_data.as_mut().unwrap();
-    if let Some(device) = udev.backends.remove(&node) {
+    // The pool's textures belong to this device's renderer.
+    udev.offscreen_pools.remove(&node);
+    if let Some(mut device) = udev.backends.remove(&node) {
+        if let Some(mut lease_global) = device.lease_global.take() {
+            lease_global.disable_global::<Raven>();
+        }
         for (_crtc, mut surface_data) in device.surfaces {
             state.space.unmap_output(&surface_data.output);
             if let Some(global) = surface_data.global.take() {
                 state.display_handle.remove_global::<Raven>(global);
             }
+            state.output_power_removed(&surface_data.output);
+            state.output_removed_event(&surface_data.output.name());
         }
+        state.outputs_changed();
         state.loop_handle.remove(device.registration_token);
         tracing::info!(?node, "DRM device removed");
     }
 }
 
-/// Render a surface for the given device and CRTC
+/// Consecutive failed frames after which an output is disabled.
+const MAX_CONSECUTIVE_RENDER_ERRORS: u32 = 5;
+const RENDER_RETRY_MIN: Duration = Duration::from_secs(1);
+const RENDER_RETRY_MAX: Duration = Duration::from_secs(30);
+
+/// Why frames on an output keep failing. While `retry` is set the output is
+/// disabled and left alone until the timer fires.
+struct RenderFailure {
+    error: String,
+    consecutive: u32,
+    backoff: Duration,
+    retry: Option<RegistrationToken>,
+}
+
+/// Render a surface for the given device and CRTC.
+///
+/// A panic or a run of failed frames disables just this output; it is retried
+/// with exponential backoff while the other outputs keep rendering.
 fn render_surface(state: &mut Raven, node: DrmNode, crtc: crtc::Handle) {
+    let disabled = state
+        .udev_data
+        .as_ref()
+        .and_then(|udev| udev.backends.get(&node))
+        .and_then(|device| device.surfaces.get(&crtc))
+        .and_then(|surface| surface.render_failure.as_ref())
+        .is_some_and(|failure| failure.retry.is_some());
+    if disabled {
+        return;
+    }
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| render_surface_frame(state, node, crtc)));
+    match result {
+        Ok(Ok(())) => render_recovered(state, node, crtc),
+        Ok(Err(error)) => render_failed(state, node, crtc, error, false),
+        Err(payload) => {
+            let message = payload
+                .downcast_ref::<&str>()
+                .map(|message| message.to_string())
+                .or_else(|| payload.downcast_ref::<String>().cloned())
+                .unwrap_or_else(|| "unknown panic".to_owned());
+            render_failed(state, node, crtc, format!("panic: {message}"), true);
+        }
+    }
+}
+
+fn render_recovered(state: &mut Raven, node: DrmNode, crtc: crtc::Handle) {
+    let Some(surface) = state
+        .udev_data
+        .as_mut()
+        .and_then(|udev| udev.backends.get_mut(&node))
+        .and_then(|device| device.surfaces.get_mut(&crtc))
+    else {
+        return;
+    };
+    let Some(failure) = surface.render_failure.take() else {
+        return;
+    };
+    let output_name = surface.output.name();
+    // The backoff only grows once the output has been disabled, and only then
+    // was the failure reported.
+    if failure.backoff > RENDER_RETRY_MIN {
+        tracing::info!(output = %output_name, "output rendering recovered");
+        state.report_progress("render", Some(&output_name), ProgressStatus::Succeeded);
+    }
+}
+
+fn render_failed(
+    state: &mut Raven,
+    node: DrmNode,
+    crtc: crtc::Handle,
+    error: String,
+    panicked: bool,
+) {
+    let loop_handle = state.loop_handle.clone();
+    let Some(udev) = state.udev_data.as_mut() else {
+        return;
+    };
+    // Frames fail while another session holds the devices; that says nothing
+    // about the output.
+    if !udev.session.is_active() {
+        return;
+    }
+    let Some(surface) = udev
+        .backends
+        .get_mut(&node)
+        .and_then(|device| device.surfaces.get_mut(&crtc))
+    else {
+        return;
+    };
+    let failure = surface.render_failure.get_or_insert(RenderFailure {
+        error: String::new(),
+        consecutive: 0,
+        backoff: RENDER_RETRY_MIN,
+        retry: None,
+    });
+    failure.error = error.clone();
+    failure.consecutive += 1;
+    if !panicked && failure.consecutive < MAX_CONSECUTIVE_RENDER_ERRORS {
+        return;
+    }
+
+    let backoff = failure.backoff;
+    failure.backoff = (backoff * 2).min(RENDER_RETRY_MAX);
+    let retry = loop_handle
+        .insert_source(Timer::from_duration(backoff), move |_, _, state| {
+            retry_render(state, node, crtc);
+            TimeoutAction::Drop
+        })
+        .ok();
+    if retry.is_none() {
+        // Without a timer the output would never come back; keep trying frames.
+        tracing::error!("failed to schedule render retry");
+        return;
+    }
+    failure.retry = retry;
+    match std::mem::take(&mut surface.redraw_state) {
+        RedrawState::WaitingForEstimatedVBlank(token)
+        | RedrawState::WaitingForEstimatedVBlankAndQueued(token) => loop_handle.remove(token),
+        _ => {}
+    }
+    udev.queued_redraws.remove(&(node, crtc));
+
+    let output_name = surface.output.name();
+    tracing::error!(
+        output = %output_name,
+        retry_in = ?backoff,
+        "disabling output after render failure: {error}"
+    );
+    state.report_progress("render", Some(&output_name), ProgressStatus::Failed(error));
+}
+
+fn retry_render(state: &mut Raven, node: DrmNode, crtc: crtc::Handle) {
+    let Some(surface) = state
+        .udev_data
+        .as_mut()
+        .and_then(|udev| udev.backends.get_mut(&node))
+        .and_then(|device| device.surfaces.get_mut(&crtc))
+    else {
+        return;
+    };
+    let Some(failure) = surface.render_failure.as_mut() else {
+        return;
+    };
+    failure.retry = None;
+    // One more failure disables the output again, with a longer backoff.
+    failure.consecutive = MAX_CONSECUTIVE_RENDER_ERRORS - 1;
+    tracing::info!(output = %surface.output.name(), "retrying disabled output");
+    surface.backdrop.touch();
+    surface.redraw_state = RedrawState::Queued;
+    render_surface(state, node, crtc);
+}
+
+/// Outputs whose rendering is currently disabled after repeated failures, with
+/// the last error.
+pub fn render_failures(state: &Raven) -> Vec<(Output, String)> {
+    let Some(udev) = state.udev_data.as_ref() else {
+        return Vec::new();
+    };
+    udev.backends
+        .values()
+        .flat_map(|backend| backend.surfaces.values())
+        .filter_map(|surface| {
+            let failure = surface.render_failure.as_ref()?;
+            failure
+                .retry
+                .is_some()
+                .then(|| (surface.output.clone(), failure.error.clone()))
+        })
+        .collect()
+}
+
+fn render_surface_frame(
+    state: &mut Raven,
+    node: DrmNode,
+    crtc: crtc::Handle,
+) -> Result<(), String> {
     state.flush_interactive_frame_updates();
     let loop_handle = state.loop_handle.clone();
     let output = {
         let udev = state.udev_data.as_ref().unwrap();
         let Some(device) = udev.backends.get(&node) else {
-            return;
+            return Ok(());
         };
         let Some(surface_data) = device.surfaces.get(&crtc) else {
-            return;
+            return Ok(());
         };
+        if surface_data.crtc_released() {
+            return Ok(());
+        }
         surface_data.output.clone()
     };
-    let fullscreen_requested_on_output = state.output_has_fullscreen_window(&output);
+    let frame = OutputFrame::new(state, &output);
 
-    if fullscreen_requested_on_output {
+    if frame.fullscreen {
         if !scanout_enabled() {
             state.record_scanout_rejection(&output, "scanout-disabled");
-        } else if let Some(reason) =
-            scanout_rejection_reason(state, &output, fullscreen_requested_on_output)
-        {
+        } else if let Some(reason) = scanout_rejection_reason(state, &output, frame.fullscreen) {
             state.record_scanout_rejection(&output, reason);
         }
     }
 
-    let output_scale = Scale::from(output.current_scale().fractional_scale());
-    let output_geo = state.space.output_geometry(&output);
-    let window_assignments: Vec<AssignedWindowRect> = output_geo
-        .map(|output_geo| {
-            state
-                .space
-                .elements_for_output(&output)
-                .filter_map(|window| {
-                    window_assignment_for_output(state, output_geo, output_scale, window)
-                })
-                .collect()
-        })
-        .unwrap_or_default();
-    let mut window_assignment_indices = HashMap::new();
-    for (index, assignment) in window_assignments.iter().enumerate() {
-        for id in &assignment.surface_ids {
-            window_assignment_indices.insert(id.clone(), index);
-        }
-    }
+    let output_scale = frame.output_scale;
+    let output_geo = frame.output_geo;
+    let background_color = state.config.background_color;
+    let capture_next_frame = state.next_frame_screenshot_pending(&output);
+    let video_or_game = frame.window_assignments.iter().any(|assignment| {
+        assignment.is_fullscreen && Raven::window_shows_video_or_game(&assignment.window)
+    });
 
     let udev = state.udev_data.as_mut().unwrap();
     let Some(device) = udev.backends.get_mut(&node) else {
-        return;
+        return Ok(());
     };
     let Some(surface_data) = device.surfaces.get_mut(&crtc) else {
-        return;
+        return Ok(());
     };
     match std::mem::take(&mut surface_data.redraw_state) {
         RedrawState::Queued => {}
@@ -1873,26 +3980,43 @@ fn render_surface(state: &mut Raven, node: DrmNode, crtc: crtc::Handle) {
         }
         other => {
             surface_data.redraw_state = other;
-            return;
+            return Ok(());
         }
     }
+    if video_or_game != surface_data.video_or_game {
+        surface_data.video_or_game = video_or_game;
+        set_vrr(surface_data, video_or_game);
+    }
 
     if let Some(output_geo) = state.space.output_geometry(&output) {
         surface_data.backdrop.update(
             (output_geo.size.w as f64, output_geo.size.h as f64),
-            CLEAR_COLOR,
+            background_color,
         );
     }
     if force_full_redraw() {
         surface_data.backdrop.touch();
     }
     let render_node = device.render_node.unwrap_or(udev.primary_gpu);
-    let cursor_frame = udev.cursor_theme.image(1, state.clock.now().into());
+    let cursor_icon = match state.cursor_status {
+        CursorImageStatus::Named(icon) => icon,
+        _ => CursorIcon::Default,
+    };
+    // Load the cursor at the output scale rounded up, so fractional scales
+    // shrink a larger image instead of blowing up a smaller one.
+    let cursor_scale = output.current_scale().integer_scale().max(1);
+    let cursor_frame =
+        udev.cursor_theme
+            .image(cursor_icon, cursor_scale as u32, state.clock.now().into());
+    let named_cursor_hotspot = Point::from((
+        cursor_frame.xhot as i32 / cursor_scale,
+        cursor_frame.yhot as i32 / cursor_scale,
+    ));
     let pointer_image = udev
         .pointer_images
         .iter()
-        .find_map(|(image, buffer)| {
-            if image == &cursor_frame {
+        .find_map(|(image, scale, buffer)| {
+            if image == &cursor_frame && *scale == cursor_scale {
                 Some(buffer.clone())
             } else {
                 None
@@ -1903,11 +4027,12 @@ fn render_surface(state: &mut Raven, node: DrmNode, crtc: crtc::Handle) {
                 &cursor_frame.pixels_rgba,
                 Fourcc::Argb8888,
                 (cursor_frame.width as i32, cursor_frame.height as i32),
-                1,
+                cursor_scale,
                 Transform::Normal,
                 None,
             );
-            udev.pointer_images.push((cursor_frame, buffer.clone()));
+            udev.pointer_images
+                .push((cursor_frame, cursor_scale, buffer.clone()));
             buffer
         });
 
@@ -1916,110 +4041,8 @@ fn render_surface(state: &mut Raven, node: DrmNode, crtc: crtc::Handle) {
         Ok(r) => r,
         Err(e) => {
             tracing::error!("Failed to get renderer: {e}");
-            return;
-        }
-    };
-    // Collect render elements from the space (windows + layer surfaces).
-    //
-    // Fullscreen ownership only affects ordering. Every mapped window is rendered through the
-    // same compositor-assigned rect pipeline below, regardless of whether it is tiled, floating,
-    // fullscreen, or in a fullscreen visual handoff.
-    let mut space_elements =
-        match space_render_elements(&mut renderer, [&state.space], &output, 1.0) {
-            Ok(elements) => elements,
-            Err(e) => {
-                tracing::warn!("Failed to collect render elements: {e:?}");
-                Vec::new()
-            }
-        };
-    if fullscreen_requested_on_output {
-        let mut window_elements = Vec::new();
-        let mut lower_layer_elements = Vec::new();
-        let mut saw_window_element = false;
-        for element in space_elements {
-            match element {
-                SpaceRenderElements::Element(_) => {
-                    saw_window_element = true;
-                    window_elements.push(element);
-                }
-                SpaceRenderElements::Surface(_) if saw_window_element => {
-                    lower_layer_elements.push(element);
-                }
-                SpaceRenderElements::Surface(_) => {}
-                SpaceRenderElements::_GenericCatcher(_) => {}
-            }
-        }
-
-        let layer_map = layer_map_for_output(&output);
-        let mut collect_layer_elements = |layer: WlrLayer| {
-            let mut out = Vec::new();
-            for layer_surface in layer_map.layers_on(layer).rev() {
-                let Some(layer_geo) = layer_map.layer_geometry(layer_surface) else {
-                    continue;
-                };
-                out.extend(
-                    AsRenderElements::<UdevRenderer<'_>>::render_elements::<
-                        WaylandSurfaceRenderElement<UdevRenderer<'_>>,
-                    >(
-                        layer_surface,
-                        &mut renderer,
-                        layer_geo.loc.to_physical_precise_round(1.0),
-                        Scale::from(1.0),
-                        1.0,
-                    )
-                    .into_iter()
-                    .map(SpaceRenderElements::Surface),
-                );
-            }
-            out
-        };
-        let overlay_layer_elements = collect_layer_elements(WlrLayer::Overlay);
-        let top_layer_elements = collect_layer_elements(WlrLayer::Top);
-
-        let mut reordered = Vec::new();
-        reordered.extend(overlay_layer_elements);
-        reordered.extend(window_elements);
-        reordered.extend(top_layer_elements);
-        reordered.extend(lower_layer_elements);
-        space_elements = reordered;
-    }
-    let space_elements_converted: Vec<
-        UdevCompositeRenderElement<UdevRenderer<'_>, WaylandSurfaceRenderElement<UdevRenderer<'_>>>,
-    > = {
-        let mut converted: Vec<
-            UdevCompositeRenderElement<
-                UdevRenderer<'_>,
-                WaylandSurfaceRenderElement<UdevRenderer<'_>>,
-            >,
-        > = Vec::new();
-        let mut rendered_assigned_windows = HashSet::new();
-
-        for element in space_elements {
-            let base = UdevRenderElement::from(element);
-
-            if let Some(assignment_index) = window_assignment_indices.get(base.id()).copied() {
-                let assignment = &window_assignments[assignment_index];
-                let needs_assigned_render_path =
-                    assignment.is_fullscreen || assignment.needs_correction();
-                if !needs_assigned_render_path {
-                    converted.push(UdevCompositeRenderElement::from(base));
-                    continue;
-                }
-                if rendered_assigned_windows.insert(assignment_index) {
-                    converted.retain(|element| !assignment.surface_ids.contains(element.id()));
-                    converted.extend(assigned_window_render_elements(
-                        &mut renderer,
-                        assignment,
-                        output_scale,
-                    ));
-                }
-                continue;
-            }
-
-            converted.push(UdevCompositeRenderElement::from(base));
+            return Err(format!("failed to get renderer: {e}"));
         }
-
-        converted
     };
 
     // Render order is front-to-back, so cursor elements must come first.
@@ -2030,6 +4053,7 @@ fn render_surface(state: &mut Raven, node: DrmNode, crtc: crtc::Handle) {
     // Render the cursor on outputs where the pointer currently is.
     if let Some(output_geo) = state.space.output_geometry(&output)
         && output_geo.to_f64().contains(state.pointer_location)
+        && !state.cursor_hidden_by_device
     {
         if let CursorImageStatus::Surface(ref surface) = state.cursor_status
             && !surface.alive()
@@ -2046,7 +4070,7 @@ fn render_surface(state: &mut Raven, node: DrmNode, crtc: crtc::Handle) {
                     .unwrap_or((0, 0).into())
             })
         } else {
-            (0, 0).into()
+            named_cursor_hotspot
         };
 
         let scale = Scale::from(output.current_scale().fractional_scale());
@@ -2073,21 +4097,50 @@ fn render_surface(state: &mut Raven, node: DrmNode, crtc: crtc::Handle) {
         );
     }
 
-    elements.extend(space_elements_converted);
-    elements.push(UdevCompositeRenderElement::from(UdevRenderElement::from(
-        SolidColorRenderElement::from_buffer(
-            &surface_data.backdrop,
-            (0.0, 0.0),
-            1.0,
-            Kind::Unspecified,
-        ),
-    )));
+    elements.extend(frame.elements(&mut renderer, &state.space, &surface_data.backdrop));
+
+    // While a capture waits on this output the cursor is composited like
+    // everything else, so the captured frame and the one on screen match.
+    let screencopy_pending = state
+        .pending_screencopies
+        .iter()
+        .any(|screencopy| screencopy.output() == &output);
+    let cursor_plane = !screencopy_pending && !capture_next_frame;
+
+    // Screencopy clients get these elements whether or not KMS needs a new frame.
+    if screencopy_pending {
+        let pool = udev.offscreen_pools.entry(render_node).or_default();
+        serve_screencopies(
+            &mut renderer,
+            pool,
+            &mut surface_data.screencopy_damage,
+            &mut state.pending_screencopies,
+            &output,
+            &elements,
+            state.start_time.elapsed(),
+        );
+    }
+
+    // Nothing changed since the last frame: skip plane assignment and
+    // rendering altogether. Otherwise the DRM compositor repaints only the
+    // damaged regions and hands them to KMS as damage clips.
+    let snapshot = FrameSnapshot::new(&elements, output_scale);
+    if !force_full_redraw()
+        && !capture_next_frame
+        && surface_data.last_frame.as_ref() == Some(&snapshot)
+    {
+        schedule_estimated_vblank(&loop_handle, surface_data, node, crtc);
+        return Ok(());
+    }
+    surface_data.last_frame = Some(snapshot);
 
     // Render frame with collected elements
-    let render_result =
-        surface_data
-            .drm_output
-            .render_frame(&mut renderer, &elements, CLEAR_COLOR, frame_flags());
+    let render_result = surface_data.drm_output.render_frame(
+        &mut renderer,
+        &elements,
+        background_color,
+        frame_flags(video_or_game, cursor_plane),
+    );
 
     match render_result {
         Ok(result) => {
@@ -2102,10 +4155,30 @@ fn render_surface(state: &mut Raven, node: DrmNode, crtc: crtc::Handle) {
 
             let rendered = !result.is_empty;
 
-            if rendered {
+            let submitted = if rendered {
                 let render_element_states = result.states.clone();
                 drop(result);
 
+                // Read back the very elements of this frame for `screenshot --next-frame`.
+                let next_frame_capture = output_geo.filter(|_| capture_next_frame).map(|logical| {
+                    let pool = udev.offscreen_pools.entry(render_node).or_default();
+                    render_capture(&mut renderer, pool, &output, logical, &elements)
+                });
+
+                // Screencasts share the frame too; their buffers live on the primary GPU.
+                #[cfg(feature = "screencast")]
+                if render_node == udev.primary_gpu
+                    && let Some(screencast) = state.screencast.as_mut()
+                {
+                    render_casts(
+                        &mut renderer,
+                        &mut screencast.casts,
+                        &state.space,
+                        &output,
+                        &elements,
+                    );
+                }
+
                 let _ = surface_data;
                 let _ = device;
                 let _ = udev;
@@ -2113,14 +4186,15 @@ fn render_surface(state: &mut Raven, node: DrmNode, crtc: crtc::Handle) {
                 update_primary_scanout_output_for_output(state, &output, &render_element_states);
                 let output_presentation_feedback =
                     take_presentation_feedback_for_output(state, &output, &render_element_states);
+                let frame_commits = take_frame_commits_for_output(state, &output);
 
                 let queue_result = {
                     let udev = state.udev_data.as_mut().unwrap();
                     let Some(device) = udev.backends.get_mut(&node) else {
-                        return;
+                        return Ok(());
                     };
                     let Some(surface_data) = device.surfaces.get_mut(&crtc) else {
-                        return;
+                        return Ok(());
                     };
 
                     match surface_data
@@ -2131,55 +4205,75 @@ fn render_surface(state: &mut Raven, node: DrmNode, crtc: crtc::Handle) {
                             surface_data.redraw_state = RedrawState::WaitingForVBlank {
                                 redraw_needed: false,
                             };
+                            surface_data.frame_commits = frame_commits;
                             surface_data.frame_callback_sequence =
                                 surface_data.frame_callback_sequence.wrapping_add(1);
                             Ok(surface_data.frame_callback_sequence)
                         }
                         Err(err) => {
                             tracing::error!("Failed to queue frame: {err:?}");
+                            // The frame never made it out; the next one has to.
+                            surface_data.last_frame = None;
                             surface_data.redraw_state = RedrawState::Queued;
-                            Err(())
+                            Err(format!("failed to queue frame: {err:?}"))
                         }
                     }
                 };
 
                 if let Ok(frame_callback_sequence) = queue_result {
                     send_frame_callbacks_for_output(state, &output, frame_callback_sequence);
+                    if let Some(capture) = next_frame_capture {
+                        state.next_frame_screenshot_captured(&output, capture);
+                    }
                 }
+                queue_result.map(|_| ())
             } else {
                 // No frame was submitted to KMS; emulate vblank timing for callbacks.
-                let refresh_rate = output
-                    .current_mode()
-                    .map(|mode| mode.refresh as u64)
-                    .unwrap_or(60_000);
-                let frame_duration = Duration::from_micros(1_000_000u64 / (refresh_rate / 1_000));
-                let timer = Timer::from_duration(frame_duration);
-                let token = loop_handle
-                    .insert_source(timer, move |_, _, state| {
-                        on_estimated_vblank_timer(state, node, crtc);
-                        TimeoutAction::Drop
-                    })
-                    .ok();
-                surface_data.redraw_state = if let Some(token) = token {
-                    RedrawState::WaitingForEstimatedVBlank(token)
-                } else {
-                    RedrawState::Idle
-                };
-            }
+                schedule_estimated_vblank(&loop_handle, surface_data, node, crtc);
+                Ok(())
+            };
 
             state.space.refresh();
             state.display_handle.flush_clients().unwrap();
+            submitted
         }
         Err(e) => {
             tracing::error!("Failed to render frame: {e:?}");
+            surface_data.last_frame = None;
             surface_data.redraw_state = RedrawState::Queued;
-            let _ = surface_data;
-            let _ = device;
-            let _ = udev;
+            Err(format!("failed to render frame: {e:?}"))
         }
     }
 }
 
+/// Stand in for the vblank of a frame that never went to KMS, so frame
+/// callbacks keep their pace.
+fn schedule_estimated_vblank(
+    loop_handle: &smithay::reexports::calloop::LoopHandle<'static, Raven>,
+    surface_data: &mut SurfaceData,
+    node: DrmNode,
+    crtc: crtc::Handle,
+) {
+    let refresh_rate = surface_data
+        .output
+        .current_mode()
+        .map(|mode| mode.refresh as u64)
+        .unwrap_or(60_000);
+    let frame_duration = Duration::from_micros(1_000_000u64 / (refresh_rate / 1_000));
+    let timer = Timer::from_duration(frame_duration);
+    let token = loop_handle
+        .insert_source(timer, move |_, _, state| {
+            on_estimated_vblank_timer(state, node, crtc);
+            TimeoutAction::Drop
+        })
+        .ok();
+    surface_data.redraw_state = if let Some(token) = token {
+        RedrawState::WaitingForEstimatedVBlank(token)
+    } else {
+        RedrawState::Idle
+    };
+}
+
 fn send_frame_callbacks_for_output(
     state: &mut Raven,
     output: &Output,
@@ -2206,12 +4300,14 @@ fn send_frame_callbacks_for_output(
         Some(output.clone())
     };
 
+    let now = state.start_time.elapsed();
     state.space.elements().for_each(|window| {
         if state
             .space
             .outputs_for_element(window)
             .iter()
             .any(|candidate| candidate == output)
+            && crate::frame_limit::frame_due_or_schedule(state, window, output, now)
         {
             window.send_frame(
                 output,
@@ -2271,6 +4367,23 @@ fn take_presentation_feedback_for_output(
     output_presentation_feedback
 }
 
+fn take_frame_commits_for_output(state: &mut Raven, output: &Output) -> Vec<(WlSurface, Instant)> {
+    let surfaces: Vec<WlSurface> = state
+        .space
+        .elements()
+        .filter(|window| state.space.outputs_for_element(window).contains(output))
+        .filter_map(|window| {
+            window
+                .toplevel()
+                .map(|toplevel| toplevel.wl_surface().clone())
+        })
+        .collect();
+    let Some(udev) = state.udev_data.as_mut() else {
+        return Vec::new();
+    };
+    udev.commit_timing.take_pending(&surfaces)
+}
+
 fn update_primary_scanout_output_for_output(
     state: &mut Raven,
     output: &Output,
@@ -2278,30 +4391,54 @@ fn update_primary_scanout_output_for_output(
 ) {
     state.space.elements().for_each(|window| {
         window.with_surfaces(|surface, states| {
-            update_surface_primary_scanout_output(
+            let primary = update_surface_primary_scanout_output(
                 surface,
                 output,
                 states,
                 render_element_states,
                 default_primary_scanout_output_compare,
             );
+            send_preferred_scale(surface, states, primary);
         });
     });
 
     let layer_map = layer_map_for_output(output);
     for layer in layer_map.layers() {
         layer.with_surfaces(|surface, states| {
-            update_surface_primary_scanout_output(
+            let primary = update_surface_primary_scanout_output(
                 surface,
                 output,
                 states,
                 render_element_states,
                 default_primary_scanout_output_compare,
             );
+            send_preferred_scale(surface, states, primary);
         });
     }
 }
 
+/// Ask a surface for buffers at the scale and transform of the output it is
+/// mostly shown on, which changes as its window moves between outputs or the
+/// output's scale is changed.
+fn send_preferred_scale(
+    surface: &WlSurface,
+    states: &compositor::SurfaceData,
+    primary: Option<Output>,
+) {
+    let Some(output) = primary else {
+        return;
+    };
+    compositor::send_surface_state(
+        surface,
+        states,
+        output.current_scale().integer_scale(),
+        output.current_transform(),
+    );
+    with_fractional_scale(states, |fractional_scale| {
+        fractional_scale.set_preferred_scale(output.current_scale().fractional_scale());
+    });
+}
+
 fn on_estimated_vblank_timer(state: &mut Raven, node: DrmNode, crtc: crtc::Handle) {
     let (output, frame_callback_sequence) = {
         let udev = state.udev_data.as_mut().unwrap();
@@ -2365,7 +4502,8 @@ fn frame_finish(
         });
         let sequence = metadata.as_ref().map(|meta| meta.sequence).unwrap_or(0);
 
-        if let Some(timestamp) = timestamp {
+        // Video and games want every vblank as soon as it comes.
+        if let Some(timestamp) = timestamp.filter(|_| !surface.video_or_game) {
             surface
                 .vblank_throttle
                 .throttle(refresh_interval, timestamp, move |state| {
@@ -2417,12 +4555,16 @@ fn frame_finish(
         (state.clock.now(), wp_presentation_feedback::Kind::Vsync)
     };
 
+    let frame_commits = std::mem::take(&mut surface.frame_commits);
+
     // Notify that the frame was submitted
     match surface.drm_output.frame_submitted() {
         Ok(user_data) => {
             if let Some(mut output_feedback) = user_data.flatten() {
                 output_feedback.presented(clock, Refresh::fixed(frame_duration), seq, flags);
             }
+            udev.commit_timing
+                .record_presented(frame_commits, Instant::now());
         }
         Err(e) => {
             tracing::error!("frame_submitted error: {e:?}");
@@ -2455,9 +4597,15 @@ fn handle_session_event(state: &mut Raven, event: SessionEvent, libinput_context
             tracing::info!("Session paused (TTY switch away)");
             libinput_context.suspend();
 
+            let location = state.pointer().current_location();
+            let cursor_status = state.cursor_status.clone();
             let udev = state.udev_data.as_mut().unwrap();
+            udev.paused_pointer = Some((location, cursor_status));
             for (_node, backend) in udev.backends.iter_mut() {
                 backend.drm_output_manager.pause();
+                if let Some(lease_global) = backend.lease_global.as_mut() {
+                    lease_global.suspend();
+                }
             }
         }
         SessionEvent::ActivateSession => {
@@ -2466,6 +4614,7 @@ fn handle_session_event(state: &mut Raven, event: SessionEvent, libinput_context
                 tracing::error!("Failed to resume libinput: {e:?}");
             }
 
+            let handle = state.loop_handle.clone();
             let udev = state.udev_data.as_mut().unwrap();
             let nodes: Vec<DrmNode> = udev.backends.keys().cloned().collect();
             for node in &nodes {
@@ -2473,15 +4622,49 @@ fn handle_session_event(state: &mut Raven, event: SessionEvent, libinput_context
                     if let Err(e) = backend.drm_output_manager.lock().activate(false) {
                         tracing::error!(?node, "Failed to activate DRM backend: {e}");
                     }
+                    if let Some(lease_global) = backend.lease_global.as_mut() {
+                        lease_global.resume::<Raven>();
+                    }
+                    for surface in backend.surfaces.values_mut() {
+                        // Outputs start over with a clean record on the new session.
+                        if let Some(retry) = surface
+                            .render_failure
+                            .take()
+                            .and_then(|failure| failure.retry)
+                        {
+                            handle.remove(retry);
+                        }
+                        // Activation restores every CRTC; keep paused and blanked ones released.
+                        if surface.crtc_released()
+                            && let Err(e) = surface
+                                .drm_output
+                                .with_compositor(|compositor| compositor.clear())
+                        {
+                            tracing::error!(
+                                output = %surface.output.name(),
+                                "Failed to keep output paused: {e:?}"
+                            );
+                        }
+                    }
                 }
             }
 
+            // Whoever held the session may have left its own gamma behind.
+            let outputs: Vec<Output> = state.space.outputs().cloned().collect();
+            for output in &outputs {
+                reapply_gamma(state, output);
+            }
+
             // Schedule re-render for all outputs
-            let handle = state.loop_handle.clone();
             for node in nodes {
                 let udev = state.udev_data.as_ref().unwrap();
                 if let Some(backend) = udev.backends.get(&node) {
-                    let crtcs: Vec<_> = backend.surfaces.keys().cloned().collect();
+                    let crtcs: Vec<_> = backend
+                        .surfaces
+                        .iter()
+                        .filter(|(_, surface)| !surface.crtc_released())
+                        .map(|(crtc, _)| *crtc)
+                        .collect();
                     for crtc in crtcs {
                         handle.insert_idle(move |state| {
                             render_surface(state, node, crtc);
@@ -2489,6 +4672,15 @@ fn handle_session_event(state: &mut Raven, event: SessionEvent, libinput_context
                     }
                 }
             }
+
+            if let Some((location, cursor_status)) = state
+                .udev_data
+                .as_mut()
+                .and_then(|udev| udev.paused_pointer.take())
+            {
+                tracing::debug!(?location, "Restoring pointer after session resume");
+                state.restore_pointer(location, cursor_status);
+            }
         }
     }
 }
@@ -19,7 +19,15 @@ use smithay::{
     wayland::shm,
 };
 
-use crate::{CompositorError, Raven, Result, protocols::wlr_screencopy::Screencopy};
+use crate::{
+    CompositorError, Raven, Result,
+    backend::{
+        BackendKind,
+        udev::{OutputFrame, UdevCompositeRenderElement},
+    },
+    protocols::wlr_screencopy::Screencopy,
+    render_helpers::SolidColorBuffer,
+};
 
 pub fn init_winit(event_loop: &mut EventLoop<Raven>, state: &mut Raven) -> Result<()> {
     let (mut winit_backend, winit) =
@@ -51,6 +59,11 @@ pub fn init_winit(event_loop: &mut EventLoop<Raven>, state: &mut Raven) -> Resul
     state.space.map_output(&output, (0, 0));
 
     let mut damage_tracker = OutputDamageTracker::from_output(&output);
+    // Only advanced when a `copy_with_damage` frame is served, so it accumulates
+    // everything that changed since the client's previous frame.
+    let mut screencopy_damage_tracker = OutputDamageTracker::from_output(&output);
+    // Kept across frames so an unchanged background is not redrawn.
+    let mut backdrop = SolidColorBuffer::default();
 
     // Set WAYLAND_DISPLAY for child processes
     unsafe { std::env::set_var("WAYLAND_DISPLAY", &state.socket_name) };
@@ -76,37 +89,59 @@ pub fn init_winit(event_loop: &mut EventLoop<Raven>, state: &mut Raven) -> Resul
                 let damage = Rectangle::from_size(size);
 
                 state.flush_interactive_frame_updates();
-                let pending_screencopy = state.pending_screencopy.take();
+                let pending_screencopies = std::mem::take(&mut state.pending_screencopies);
+                // The same elements the udev backend presents, borders and all.
+                let frame = OutputFrame::new(state, &output);
+                backdrop.update(size.to_logical(1).to_f64(), state.config.background_color);
+                let elements = frame.elements(winit_backend.renderer(), &state.space, &backdrop);
 
                 {
                     let (renderer, mut framebuffer) =
                         winit_backend.bind().expect("failed to bind winit window");
-                    smithay::desktop::space::render_output::<
-                        _,
-                        WaylandSurfaceRenderElement<GlesRenderer>,
-                        _,
-                        _,
-                    >(
-                        &output,
-                        renderer,
-                        &mut framebuffer,
-                        1.0,
-                        0,
-                        [&state.space],
-                        &[],
-                        &mut damage_tracker,
-                        make_rgb(150., 154., 171., 1.0),
-                    )
-                    .unwrap();
+                    damage_tracker
+                        .render_output(
+                            renderer,
+                            &mut framebuffer,
+                            0,
+                            &elements,
+                            state.config.background_color,
+                        )
+                        .unwrap();
                 }
 
                 winit_backend
                     .submit(Some(&[damage]))
                     .expect("failed to submit damage");
 
-                if let Some(screencopy) = pending_screencopy
-                    && screencopy.output() == &output
-                {
+                // Damage is computed once per redraw and shared by every
+                // `copy_with_damage` frame waiting on it.
+                let damage = pending_screencopies
+                    .iter()
+                    .any(|screencopy| screencopy.with_damage())
+                    .then(|| {
+                        screencopy_damage(&mut screencopy_damage_tracker, &elements).unwrap_or_else(
+                            |err| {
+                                tracing::warn!("screencopy damage failed: {err:?}");
+                                vec![Rectangle::from_size(size)]
+                            },
+                        )
+                    });
+                for screencopy in pending_screencopies {
+                    if screencopy.output() != &output {
+                        continue;
+                    }
+                    if screencopy.with_damage() {
+                        match &damage {
+                            // Nothing changed since the client's last frame; hold
+                            // the copy until something does.
+                            Some(damage) if damage.is_empty() => {
+                                state.pending_screencopies.push(screencopy);
+                                continue;
+                            }
+                            Some(damage) => screencopy.damage(damage),
+                            None => {}
+                        }
+                    }
                     let (renderer, framebuffer) =
                         winit_backend.bind().expect("failed to bind for screencopy");
                     if let Err(err) = render_screencopy(
@@ -120,14 +155,20 @@ pub fn init_winit(event_loop: &mut EventLoop<Raven>, state: &mut Raven) -> Resul
                     }
                 }
 
-                state.space.elements().for_each(|window| {
-                    window.send_frame(
-                        &output,
-                        state.start_time.elapsed(),
-                        Some(Duration::ZERO),
-                        |_, _| Some(output.clone()),
-                    );
-                });
+                // Winit redraws continuously, so throttled windows just skip this frame.
+                let now = state.start_time.elapsed();
+                state
+                    .space
+                    .elements()
+                    .filter(|window| crate::frame_limit::frame_due(state, window, now))
+                    .for_each(|window| {
+                        window.send_frame(
+                            &output,
+                            state.start_time.elapsed(),
+                            Some(Duration::ZERO),
+                            |_, _| Some(output.clone()),
+                        );
+                    });
                 let layer_map = layer_map_for_output(&output);
                 layer_map.layers().for_each(|layer| {
                     layer.send_frame(
@@ -149,9 +190,24 @@ pub fn init_winit(event_loop: &mut EventLoop<Raven>, state: &mut Raven) -> Resul
         })
         .map_err(|e| CompositorError::Backend(format!("{:?}", e)))?;
 
+    state.backend_kind = BackendKind::Winit;
     Ok(())
 }
 
+/// The regions of the output that changed since the last `copy_with_damage` frame.
+fn screencopy_damage(
+    damage_tracker: &mut OutputDamageTracker,
+    elements: &[UdevCompositeRenderElement<
+        GlesRenderer,
+        WaylandSurfaceRenderElement<GlesRenderer>,
+    >],
+) -> Result<Vec<Rectangle<i32, Physical>>> {
+    let (damage, _) = damage_tracker
+        .damage_output(1, elements)
+        .map_err(|e| CompositorError::Screencopy(format!("damage_output: {e:?}")))?;
+    Ok(damage.map(|damage| damage.to_vec()).unwrap_or_default())
+}
+
 fn render_screencopy(
     renderer: &mut GlesRenderer,
     target: &GlesTarget<'_>,
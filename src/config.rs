@@ -1,70 +1,478 @@
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::{BTreeMap, HashMap, HashSet},
     fs,
     path::{Path, PathBuf},
     process::Command,
 };
 
+use regex::{Regex, RegexBuilder};
 use smithay::input::keyboard::{Keysym, ModifiersState};
 
-use crate::CompositorError;
+use crate::{CompositorError, layout::LayoutType};
+
+/// Most workspaces Raven keeps, configured or created on demand.
+pub const MAX_WORKSPACES: usize = 32;
 
 #[derive(Clone, Debug)]
 pub struct RuntimeConfig {
     pub main_key: MainKey,
     pub keybinds: Vec<Keybind>,
-    pub autostart: Vec<String>,
+    pub autostart: Vec<AutostartEntry>,
+    pub session: Vec<SessionEntry>,
     pub terminal: String,
     pub launcher: String,
     pub focus_follow_mouse: bool,
+    pub new_window_output_focus: NewWindowOutputFocus,
+    pub workspace_animation: WorkspaceAnimation,
+    pub warp_pointer: bool,
+    /// Dwell time at a screen edge during drag-and-drop before switching workspace; 0 is off.
+    pub dnd_edge_switch_delay_ms: u32,
+    /// How long a reload that moves tiled windows outlines their new slots first; 0 is off.
+    pub reload_layout_preview_ms: u32,
+    /// Send keys to a focused fullscreen window instead of matching keybinds,
+    /// except binds whose action is in `fullscreen_exclusive_allow`.
+    pub fullscreen_exclusive_input: bool,
+    /// Canonical action names, as listed by `raven actions`.
+    pub fullscreen_exclusive_allow: Vec<String>,
+    /// Workspaces per row in the grid ext-workspace pagers draw; 1 keeps one column.
+    pub workspace_grid_columns: u32,
+    /// Workspaces that always exist. Switching past them, or to `next_empty`,
+    /// creates more, which go away again once they are empty and unfocused.
+    pub workspace_count: usize,
+    /// How far `swap_*` actions move a floating window, in logical pixels.
+    pub floating_move_step: u32,
     pub no_csd: bool,
+    pub strict_protocol: bool,
+    /// Buffer memory one client may hold attached, in MiB; 0 disables the limit.
+    pub client_buffer_budget_mb: u32,
+    /// Buffer memory across all clients, in MiB; 0 disables the limit.
+    pub buffer_budget_mb: u32,
+    /// Width of the border drawn around every window; tiles shrink to make room.
     pub border_size: u32,
+    /// Border color of the window with keyboard focus.
+    pub border_active_color: [f32; 4],
+    /// Border color of the other windows.
+    pub border_inactive_color: [f32; 4],
+    /// Radius of the rounded window corners in logical pixels; 0 keeps them square.
+    pub corner_radius: u32,
+    /// Border color of windows that want attention; `None` leaves their border alone.
+    pub urgent_border_color: Option<[f32; 4]>,
+    /// Drawn behind everything, where no wallpaper covers the output.
+    pub background_color: [f32; 4],
     pub gaps_outer_horizontal: u32,
     pub gaps_outer_vertical: u32,
     pub gaps_inner_horizontal: u32,
     pub gaps_inner_vertical: u32,
+    /// Tiling layout every workspace starts in; `layout` and `cycle_layout` change
+    /// it for the current workspace only.
+    pub layout_type: LayoutType,
     pub master_factor: f32,
     pub num_master: i32,
     pub smart_gaps: bool,
+    /// How much to darken unfocused windows, from 0.0 (off) to 1.0 (black).
+    pub dim_unfocused: f32,
+    /// How much to fade unfocused windows, from 0.0 (off) to 1.0 (invisible).
+    pub dim_inactive: f32,
+    /// Command that picks what a screencast shares: it reads one
+    /// `output:<name> ...` or `window:<id> ...` line per source on stdin and
+    /// prints the chosen one. Casts are refused without it, so nothing is shared
+    /// that the user did not pick.
+    pub screencast_chooser: Option<String>,
     pub cursor_theme: String,
     pub cursor_size: u32,
     pub monitors: Vec<MonitorConfig>,
     pub window_rules: Vec<WindowRule>,
+    pub workspace_hooks: WorkspaceHooks,
+    pub workspace_hook_overrides: BTreeMap<usize, WorkspaceHooks>,
+    /// Names from `workspaces`, by 0-based workspace. Unnamed workspaces go by
+    /// their number.
+    pub workspace_names: BTreeMap<usize, String>,
+    pub events: EventHooks,
+    pub idle: IdleConfig,
+    pub night_light: NightLightConfig,
+    pub last_window_closed: LastWindowClosed,
     pub wallpaper: WallpaperConfig,
     pub xwayland: XwaylandConfig,
+    pub input: InputConfig,
+}
+
+/// A command run once at startup. With `workspace` set (0-based), the first window
+/// the command opens is placed on that workspace instead of the focused one.
+#[derive(Clone, Debug)]
+pub struct AutostartEntry {
+    pub command: String,
+    pub workspace: Option<usize>,
+}
+
+/// One command of the startup session. Its first window opens on `workspace`
+/// (0-based); tiled entries fill that workspace in the order they are listed.
+/// Position and size apply to floating entries; `x`/`y` are relative to the
+/// output the window opens on.
+#[derive(Clone, Debug)]
+pub struct SessionEntry {
+    pub command: String,
+    pub workspace: usize,
+    pub floating: bool,
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+}
+
+/// Shell commands run when a workspace changes between empty and occupied.
+#[derive(Clone, Debug, Default)]
+pub struct WorkspaceHooks {
+    pub on_workspace_empty: Option<String>,
+    pub on_first_window: Option<String>,
+}
+
+/// Shell commands from the `events` table, run with the event's details in
+/// `RAVEN_*` environment variables.
+#[derive(Clone, Debug, Default)]
+pub struct EventHooks {
+    pub output_added: Option<String>,
+    pub output_removed: Option<String>,
+    pub workspace_switched: Option<String>,
+    pub window_opened: Option<String>,
+}
+
+/// The `idle` table: after `timeout_secs` without input Raven runs `on_idle`, and
+/// `on_resume` on the next input. Visible idle inhibitors hold the timer.
+#[derive(Clone, Debug, Default)]
+pub struct IdleConfig {
+    /// Seconds without input before the session counts as idle; 0 is off.
+    pub timeout_secs: u32,
+    pub on_idle: Option<String>,
+    pub on_resume: Option<String>,
+}
+
+/// The `night_light` table: warm every output to `temperature` kelvin, all the
+/// time or only inside `schedule`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NightLightConfig {
+    pub enabled: bool,
+    pub temperature: u32,
+    /// Start and end in minutes after local midnight; the span may wrap past it.
+    pub schedule: Option<(u32, u32)>,
+}
+
+impl Default for NightLightConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            temperature: 4000,
+            schedule: None,
+        }
+    }
+}
+
+/// Whether a new window that opens on another output than the focused one takes
+/// keyboard focus there.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NewWindowOutputFocus {
+    #[default]
+    Always,
+    /// Only when the launch came from a Raven keybind, as proven by the
+    /// activation token the keybind handed to the spawned command.
+    Keybind,
+}
+
+/// How switching workspaces is animated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WorkspaceAnimation {
+    /// Switch at once.
+    None,
+    /// The new workspace pushes the old one off the output sideways.
+    #[default]
+    Slide,
+    Crossfade,
+}
+
+/// What to do when the last window on the focused workspace closes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum LastWindowClosed {
+    #[default]
+    Stay,
+    PreviousNonEmpty,
+    Command(String),
+}
+
+/// How a window rule compares its `class`/`app_id`/`title` patterns, and their
+/// `not_` forms, against a window. Matching ignores case in every mode.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MatchMode {
+    /// App ids match whole, titles by substring.
+    #[default]
+    Default,
+    Exact,
+    Contains,
+    Regex,
+}
+
+impl MatchMode {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "default" => Some(Self::Default),
+            "exact" => Some(Self::Exact),
+            "contains" | "substring" => Some(Self::Contains),
+            "regex" => Some(Self::Regex),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::Exact => "exact",
+            Self::Contains => "contains",
+            Self::Regex => "regex",
+        }
+    }
+}
+
+/// A window rule pattern, compiled when the config is loaded.
+#[derive(Clone, Debug)]
+pub enum RulePattern {
+    Exact(String),
+    Contains(String),
+    Regex(Regex),
+}
+
+impl RulePattern {
+    fn matches(&self, actual: Option<&str>) -> bool {
+        match self {
+            Self::Exact(expected) => matches_ci_exact(actual, expected),
+            Self::Contains(expected) => matches_ci_contains(actual, expected),
+            Self::Regex(regex) => actual.is_some_and(|value| regex.is_match(value)),
+        }
+    }
+
+    /// The pattern as written in the config.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Exact(pattern) | Self::Contains(pattern) => pattern,
+            Self::Regex(regex) => regex.as_str(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Default)]
 pub struct WindowRule {
-    pub class: Option<String>,
-    pub app_id: Option<String>,
-    pub title: Option<String>,
+    pub class: Option<RulePattern>,
+    pub app_id: Option<RulePattern>,
+    pub title: Option<RulePattern>,
+    /// The rule skips windows these match.
+    pub not_class: Option<RulePattern>,
+    pub not_app_id: Option<RulePattern>,
+    pub not_title: Option<RulePattern>,
+    pub match_mode: MatchMode,
+    pub window_type: Option<WindowType>,
     pub workspace: Option<usize>,
     pub floating: Option<bool>,
+    /// Shown on every workspace, floating.
+    pub sticky: Option<bool>,
     pub fullscreen: Option<bool>,
     pub focus: Option<bool>,
-    pub width: Option<u32>,
-    pub height: Option<u32>,
+    pub no_focus_follows_mouse: Option<bool>,
+    /// Overrides `fullscreen_exclusive_input` while this window is fullscreen.
+    pub exclusive_input: Option<bool>,
+    /// Closing the window moves it to this workspace instead; `force_close` still
+    /// closes it for real.
+    pub close_to_workspace: Option<usize>,
+    /// Like `close_to_workspace`, parking the window on the last of the
+    /// `workspace_count` workspaces when no workspace is given.
+    pub hide_on_close: Option<bool>,
+    /// Command run when a matching window opens, like `events.window_opened`.
+    pub on_open: Option<String>,
+    /// Output floating windows open on, by connector name.
+    pub output: Option<String>,
+    pub width: Option<RuleSize>,
+    pub height: Option<RuleSize>,
+    /// Where a floating window opens on its output.
+    pub position: Option<RulePosition>,
+    pub max_fps: Option<u32>,
+    /// Alpha the window is drawn with, from 0.0 to 1.0.
+    pub opacity: Option<f32>,
+    /// RGBA border drawn `border_size` pixels wide around the window.
+    pub border_color: Option<[f32; 4]>,
+    /// The window's tile reaches the output edges and its neighbours, ignoring
+    /// the gaps.
+    pub no_gaps: Option<bool>,
+    /// Overrides `corner_radius` for the window.
+    pub corner_radius: Option<u32>,
+    /// Space left free around the window while it is maximized.
+    pub maximize_margins: Option<Margins>,
 }
 
-impl WindowRule {
-    pub fn matches(&self, app_id: Option<&str>, title: Option<&str>) -> bool {
-        if let Some(expected) = &self.class
-            && !matches_ci_exact(app_id, expected)
-        {
-            return false;
+/// Per-side margins in logical pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Margins {
+    pub top: u32,
+    pub bottom: u32,
+    pub left: u32,
+    pub right: u32,
+}
+
+/// A window rule size: logical pixels, or a percentage of the output's work area.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RuleSize {
+    Pixels(u32),
+    Percent(f32),
+}
+
+impl RuleSize {
+    pub fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        match raw.strip_suffix('%') {
+            Some(percent) => {
+                let percent = percent.trim().parse::<f32>().ok()?;
+                (percent > 0.0 && percent <= 100.0).then_some(Self::Percent(percent))
+            }
+            None => raw.parse().ok().map(Self::Pixels),
         }
-        if let Some(expected) = &self.app_id
-            && !matches_ci_exact(app_id, expected)
-        {
-            return false;
+    }
+
+    /// Logical pixels, with percentages taken of `available`. A percentage has
+    /// no size while the work area is unknown.
+    pub fn resolve(self, available: Option<i32>) -> Option<u32> {
+        match self {
+            Self::Pixels(pixels) => Some(pixels),
+            Self::Percent(percent) => available
+                .map(|available| (available.max(0) as f32 * percent / 100.0).round() as u32),
+        }
+    }
+}
+
+/// Where a floating window goes along one axis of its output's work area.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Align {
+    Start,
+    Center,
+    End,
+    /// Logical pixels from the start of the work area.
+    Offset(i32),
+}
+
+impl Align {
+    /// Offset from the start of `available` of a window `size` long.
+    pub fn offset(self, available: i32, size: i32) -> i32 {
+        match self {
+            Self::Start => 0,
+            Self::Center => (available - size) / 2,
+            Self::End => available - size,
+            Self::Offset(offset) => offset,
+        }
+    }
+}
+
+/// Placement of a floating window, from the `position`, `center`, `x` and `y`
+/// fields of a window rule.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RulePosition {
+    pub x: Align,
+    pub y: Align,
+}
+
+impl RulePosition {
+    pub const CENTER: Self = Self {
+        x: Align::Center,
+        y: Align::Center,
+    };
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        let (y, x) = match raw.trim().to_ascii_lowercase().replace('_', "-").as_str() {
+            "top-left" => (Align::Start, Align::Start),
+            "top" => (Align::Start, Align::Center),
+            "top-right" => (Align::Start, Align::End),
+            "left" => (Align::Center, Align::Start),
+            "center" => (Align::Center, Align::Center),
+            "right" => (Align::Center, Align::End),
+            "bottom-left" => (Align::End, Align::Start),
+            "bottom" => (Align::End, Align::Center),
+            "bottom-right" => (Align::End, Align::End),
+            _ => return None,
+        };
+        Some(Self { x, y })
+    }
+}
+
+/// What kind of toplevel a window is, as far as Raven can tell from its role hints.
+///
+/// Toplevels with a parent are dialogs; parentless fixed-size toplevels are treated
+/// as splash screens. Modal comes from the `xdg_dialog_v1` modal hint. X11 windows
+/// take their type from `_NET_WM_WINDOW_TYPE`, where utility windows (palettes,
+/// toolboxes, torn-off menus) come from; without one, a window transient for
+/// another is a dialog. Through xwayland-satellite, transient windows arrive as
+/// parented toplevels and land in the same buckets.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WindowType {
+    Normal,
+    Dialog,
+    Modal,
+    Utility,
+    Splash,
+}
+
+impl WindowType {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "normal" => Some(Self::Normal),
+            "dialog" => Some(Self::Dialog),
+            "modal" => Some(Self::Modal),
+            "utility" => Some(Self::Utility),
+            "splash" => Some(Self::Splash),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Normal => "normal",
+            Self::Dialog => "dialog",
+            Self::Modal => "modal",
+            Self::Utility => "utility",
+            Self::Splash => "splash",
         }
-        if let Some(expected) = &self.title
-            && !matches_ci_contains(title, expected)
+    }
+
+    /// Whether a window of this type satisfies a rule asking for `expected`.
+    /// Modal dialogs are dialogs too.
+    fn satisfies(self, expected: WindowType) -> bool {
+        self == expected || (expected == Self::Dialog && self == Self::Modal)
+    }
+}
+
+impl WindowRule {
+    pub fn matches(
+        &self,
+        app_id: Option<&str>,
+        title: Option<&str>,
+        window_type: WindowType,
+    ) -> bool {
+        if let Some(expected) = self.window_type
+            && !window_type.satisfies(expected)
         {
             return false;
         }
-        true
+        let required = [
+            (&self.class, app_id),
+            (&self.app_id, app_id),
+            (&self.title, title),
+        ];
+        let excluded = [
+            (&self.not_class, app_id),
+            (&self.not_app_id, app_id),
+            (&self.not_title, title),
+        ];
+        let matched = |pattern: &Option<RulePattern>, actual: Option<&str>| {
+            pattern.as_ref().map(|pattern| pattern.matches(actual))
+        };
+        required
+            .iter()
+            .all(|&(pattern, actual)| matched(pattern, actual) != Some(false))
+            && excluded
+                .iter()
+                .all(|&(pattern, actual)| matched(pattern, actual) != Some(true))
     }
 }
 
@@ -76,6 +484,11 @@ pub struct WallpaperConfig {
     pub resize: String,
     pub transition_type: String,
     pub transition_duration: f32,
+    /// Cycle through the images in this directory instead of showing `image`.
+    pub slideshow_directory: String,
+    pub slideshow_interval_secs: u32,
+    /// Draw the images beneath the windows instead of handing them to swww.
+    pub builtin: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -85,6 +498,31 @@ pub struct XwaylandConfig {
     pub display: String,
 }
 
+/// libinput device settings, applied when a device is added and on reload.
+#[derive(Clone, Debug)]
+pub struct InputConfig {
+    pub left_handed: bool,
+    /// Hide the cursor while a touchscreen is driving the pointer.
+    pub hide_cursor_on_touch: bool,
+    /// Milliseconds a touchscreen finger must rest in place to right-click; 0 turns it off.
+    pub touch_hold_right_click_ms: u32,
+    /// Tapping a modifier alone keeps it held for the next key; tapping it twice locks it.
+    pub sticky_keys: bool,
+    /// Milliseconds a key must be held before the press counts; 0 turns it off.
+    pub slow_keys_ms: u32,
+    /// Milliseconds after a release during which the same key is ignored; 0 turns it off.
+    pub bounce_keys_ms: u32,
+    pub devices: Vec<InputDeviceConfig>,
+}
+
+/// Overrides for one device, matched case-insensitively against its libinput name.
+#[derive(Clone, Debug, Default)]
+pub struct InputDeviceConfig {
+    pub name: String,
+    pub left_handed: Option<bool>,
+    pub hide_cursor: Option<bool>,
+}
+
 #[derive(Clone, Debug)]
 pub struct MonitorConfig {
     pub name: String,
@@ -96,6 +534,17 @@ pub struct MonitorConfig {
     pub y: Option<i32>,
     pub scale: Option<f64>,
     pub transform: Option<String>,
+    /// Tried in order when the configured mode isn't offered by the connector.
+    pub fallback_modes: Vec<MonitorMode>,
+    /// Built-in wallpaper image for this output instead of `wallpaper.image`.
+    pub wallpaper: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MonitorMode {
+    pub width: u16,
+    pub height: u16,
+    pub refresh_hz: Option<f64>,
 }
 
 impl Default for MonitorConfig {
@@ -110,6 +559,8 @@ impl Default for MonitorConfig {
             y: None,
             scale: None,
             transform: None,
+            fallback_modes: Vec::new(),
+            wallpaper: None,
         }
     }
 }
@@ -123,10 +574,48 @@ impl Default for WallpaperConfig {
             resize: "crop".to_owned(),
             transition_type: "simple".to_owned(),
             transition_duration: 0.7,
+            slideshow_directory: String::new(),
+            slideshow_interval_secs: 300,
+            builtin: false,
+        }
+    }
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            left_handed: false,
+            hide_cursor_on_touch: true,
+            touch_hold_right_click_ms: 0,
+            sticky_keys: false,
+            slow_keys_ms: 0,
+            bounce_keys_ms: 0,
+            devices: Vec::new(),
         }
     }
 }
 
+impl InputConfig {
+    fn device(&self, name: &str) -> Option<&InputDeviceConfig> {
+        self.devices
+            .iter()
+            .find(|device| device.name.eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn left_handed_for(&self, name: &str) -> bool {
+        self.device(name)
+            .and_then(|device| device.left_handed)
+            .unwrap_or(self.left_handed)
+    }
+
+    /// Whether pointer input from this device should hide the cursor.
+    pub fn hides_cursor(&self, name: &str, is_touch: bool) -> bool {
+        self.device(name)
+            .and_then(|device| device.hide_cursor)
+            .unwrap_or(is_touch && self.hide_cursor_on_touch)
+    }
+}
+
 impl Default for XwaylandConfig {
     fn default() -> Self {
         Self {
@@ -149,6 +638,51 @@ impl RuntimeConfig {
             .find(|bind| bind.matches(modifiers, keysym))
             .map(|bind| bind.action.clone())
     }
+
+    /// This config with cosmetic extras switched off, for performance mode.
+    ///
+    /// Raven draws no blur, so gaps, borders, rounded corners, unfocused dimming,
+    /// the workspace animation and the wallpaper transition are everything there
+    /// is to turn off.
+    pub fn with_performance_overrides(&self) -> Self {
+        let mut config = self.clone();
+        config.border_size = 0;
+        config.corner_radius = 0;
+        config.workspace_animation = WorkspaceAnimation::None;
+        config.dim_unfocused = 0.0;
+        config.dim_inactive = 0.0;
+        config.gaps_outer_horizontal = 0;
+        config.gaps_outer_vertical = 0;
+        config.gaps_inner_horizontal = 0;
+        config.gaps_inner_vertical = 0;
+        config.wallpaper.transition_type = "none".to_owned();
+        config.wallpaper.transition_duration = 0.0;
+        config
+    }
+
+    /// Hook for a workspace (0-based), preferring the per-workspace override.
+    pub fn on_workspace_empty_command(&self, workspace_index: usize) -> Option<&str> {
+        self.workspace_hook_overrides
+            .get(&workspace_index)
+            .and_then(|hooks| hooks.on_workspace_empty.as_deref())
+            .or(self.workspace_hooks.on_workspace_empty.as_deref())
+    }
+
+    pub fn on_first_window_command(&self, workspace_index: usize) -> Option<&str> {
+        self.workspace_hook_overrides
+            .get(&workspace_index)
+            .and_then(|hooks| hooks.on_first_window.as_deref())
+            .or(self.workspace_hooks.on_first_window.as_deref())
+    }
+
+    /// What bars and reports call a workspace (0-based): its configured name,
+    /// else its number.
+    pub fn workspace_label(&self, workspace_index: usize) -> String {
+        self.workspace_names
+            .get(&workspace_index)
+            .cloned()
+            .unwrap_or_else(|| (workspace_index + 1).to_string())
+    }
 }
 
 impl Default for RuntimeConfig {
@@ -160,24 +694,55 @@ impl Default for RuntimeConfig {
             main_key,
             keybinds,
             autostart: Vec::new(),
+            session: Vec::new(),
             terminal: "weston-terminal".to_owned(),
             launcher: "rofi -show drun".to_owned(),
             focus_follow_mouse: true,
+            new_window_output_focus: NewWindowOutputFocus::default(),
+            workspace_animation: WorkspaceAnimation::default(),
+            warp_pointer: false,
+            dnd_edge_switch_delay_ms: 0,
+            reload_layout_preview_ms: 0,
+            fullscreen_exclusive_input: false,
+            fullscreen_exclusive_allow: vec!["fullscreen".to_owned()],
+            workspace_grid_columns: 1,
+            workspace_count: 10,
+            floating_move_step: 40,
             no_csd: true,
+            strict_protocol: false,
+            client_buffer_budget_mb: 2048,
+            buffer_budget_mb: 0,
             border_size: 2,
+            border_active_color: [0.35, 0.6, 0.95, 1.0],
+            border_inactive_color: [0.27, 0.28, 0.33, 1.0],
+            corner_radius: 0,
+            urgent_border_color: None,
+            background_color: [150.0 / 255.0, 154.0 / 255.0, 171.0 / 255.0, 1.0],
             gaps_outer_horizontal: 20,
             gaps_outer_vertical: 20,
             gaps_inner_horizontal: 10,
             gaps_inner_vertical: 10,
+            layout_type: LayoutType::default(),
             master_factor: 0.55,
             num_master: 1,
             smart_gaps: true,
+            dim_unfocused: 0.0,
+            dim_inactive: 0.0,
+            screencast_chooser: None,
             cursor_theme: "default".to_owned(),
             cursor_size: 24,
             monitors: Vec::new(),
             window_rules: Vec::new(),
+            workspace_hooks: WorkspaceHooks::default(),
+            workspace_hook_overrides: BTreeMap::new(),
+            workspace_names: BTreeMap::new(),
+            events: EventHooks::default(),
+            idle: IdleConfig::default(),
+            night_light: NightLightConfig::default(),
+            last_window_closed: LastWindowClosed::default(),
             wallpaper: WallpaperConfig::default(),
             xwayland: XwaylandConfig::default(),
+            input: InputConfig::default(),
         }
     }
 }
@@ -237,21 +802,135 @@ impl KeybindModifiers {
     }
 }
 
+/// Direction argument of the `swap_*` actions.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// What the `dpms` action does to the outputs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DpmsMode {
+    On,
+    Off,
+    /// Off while any output is lit, on otherwise.
+    Toggle,
+}
+
+impl DpmsMode {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::On => "on",
+            Self::Off => "off",
+            Self::Toggle => "toggle",
+        }
+    }
+
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "on" => Some(Self::On),
+            "off" => Some(Self::Off),
+            "toggle" => Some(Self::Toggle),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub enum KeybindAction {
     Exec(String),
     Terminal,
     Launcher,
     CloseFocused,
+    ForceCloseFocused,
     ToggleFullscreen,
     ToggleFloating,
+    TogglePin,
     Quit,
     FocusNext,
     FocusPrevious,
     ReloadConfig,
     SwitchWorkspace(usize),
+    SwitchToEmptyWorkspace,
     MoveFocusedToWorkspace(usize),
-    Unsupported(String),
+    SwapWorkspaces(usize, usize),
+    ReorderWorkspace { from: usize, to: usize },
+    PreviousWorkspace,
+    NextWorkspace,
+    WorkspaceBackAndForth,
+    SetLayout(LayoutType),
+    CycleLayout,
+    AdjustSplitRatio(f32),
+    FocusColumnLeft,
+    FocusColumnRight,
+    ShrinkMaster,
+    GrowMaster,
+    SwapMaster,
+    SwapDirection(Direction),
+    CycleWindows,
+    CycleWindowsBack,
+    GrowTileWidth,
+    ShrinkTileWidth,
+    GrowTileHeight,
+    ShrinkTileHeight,
+    TogglePerformanceMode,
+    FindCursor,
+    Dpms(DpmsMode),
+    FocusUrgent,
+    MinimizeFocused,
+}
+
+impl KeybindAction {
+    /// Canonical config name of the action, as listed by `raven actions`.
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Exec(_) => "exec",
+            Self::Terminal => "terminal",
+            Self::Launcher => "launcher",
+            Self::CloseFocused => "close",
+            Self::ForceCloseFocused => "force_close",
+            Self::ToggleFullscreen => "fullscreen",
+            Self::ToggleFloating => "toggle_floating",
+            Self::TogglePin => "pin",
+            Self::Quit => "quit",
+            Self::FocusNext => "focus_next",
+            Self::FocusPrevious => "focus_prev",
+            Self::ReloadConfig => "reload",
+            Self::SwitchWorkspace(_) | Self::SwitchToEmptyWorkspace => "workspace",
+            Self::MoveFocusedToWorkspace(_) => "movetoworkspace",
+            Self::SwapWorkspaces(..) => "workspace_swap",
+            Self::ReorderWorkspace { .. } => "workspace_reorder",
+            Self::PreviousWorkspace => "workspace_prev",
+            Self::NextWorkspace => "workspace_next",
+            Self::WorkspaceBackAndForth => "workspace_back_and_forth",
+            Self::SetLayout(_) => "layout",
+            Self::CycleLayout => "cycle_layout",
+            Self::AdjustSplitRatio(_) => "split_ratio",
+            Self::FocusColumnLeft => "focus_left",
+            Self::FocusColumnRight => "focus_right",
+            Self::ShrinkMaster => "resize_left",
+            Self::GrowMaster => "resize_right",
+            Self::SwapMaster => "swap_master",
+            Self::SwapDirection(Direction::Left) => "swap_left",
+            Self::SwapDirection(Direction::Right) => "swap_right",
+            Self::SwapDirection(Direction::Up) => "swap_up",
+            Self::SwapDirection(Direction::Down) => "swap_down",
+            Self::CycleWindows => "cycle_windows",
+            Self::CycleWindowsBack => "cycle_windows_back",
+            Self::GrowTileWidth => "resize_grow_width",
+            Self::ShrinkTileWidth => "resize_shrink_width",
+            Self::GrowTileHeight => "resize_grow_height",
+            Self::ShrinkTileHeight => "resize_shrink_height",
+            Self::TogglePerformanceMode => "performance_mode",
+            Self::FindCursor => "find_cursor",
+            Self::Dpms(_) => "dpms",
+            Self::FocusUrgent => "focus_urgent",
+            Self::MinimizeFocused => "minimize_focused",
+        }
+    }
 }
 
 pub struct LoadedConfig {
@@ -323,8 +1002,78 @@ pub fn load_from_path(path: &Path) -> Result<RuntimeConfig, CompositorError> {
     }
     config.focus_follow_mouse =
         parse_bool_flexible(&values, "focus_follow_mouse", config.focus_follow_mouse)?;
+    config.warp_pointer = parse_bool_flexible(&values, "warp_pointer", config.warp_pointer)?;
+    config.dnd_edge_switch_delay_ms = parse_u32(
+        &values,
+        "dnd_edge_switch_delay",
+        config.dnd_edge_switch_delay_ms,
+    )?;
+    config.reload_layout_preview_ms = parse_u32(
+        &values,
+        "reload_layout_preview",
+        config.reload_layout_preview_ms,
+    )?;
+    config.fullscreen_exclusive_input = parse_bool_flexible(
+        &values,
+        "fullscreen_exclusive_input",
+        config.fullscreen_exclusive_input,
+    )?;
+    if let Some(allow) = values.get("fullscreen_exclusive_allow") {
+        config.fullscreen_exclusive_allow =
+            parse_action_names(allow, "fullscreen_exclusive_allow")?;
+    }
+    config.workspace_grid_columns = parse_u32(
+        &values,
+        "workspace_grid_columns",
+        config.workspace_grid_columns,
+    )?;
+    if config.workspace_grid_columns == 0 {
+        return Err(CompositorError::Backend(
+            "workspace_grid_columns must be greater than 0".to_owned(),
+        ));
+    }
+    config.workspace_count =
+        parse_u32(&values, "workspace_count", config.workspace_count as u32)? as usize;
+    if !(1..=MAX_WORKSPACES).contains(&config.workspace_count) {
+        return Err(CompositorError::Backend(format!(
+            "workspace_count must be between 1 and {MAX_WORKSPACES}"
+        )));
+    }
+    config.floating_move_step =
+        parse_u32(&values, "floating_move_step", config.floating_move_step)?;
+    if config.floating_move_step == 0 {
+        return Err(CompositorError::Backend(
+            "floating_move_step must be greater than 0".to_owned(),
+        ));
+    }
     config.no_csd = parse_bool_flexible(&values, "no_csd", config.no_csd)?;
+    config.strict_protocol =
+        parse_bool_flexible(&values, "strict_protocol", config.strict_protocol)?;
+    config.client_buffer_budget_mb = parse_u32(
+        &values,
+        "client_buffer_budget_mb",
+        config.client_buffer_budget_mb,
+    )?;
+    config.buffer_budget_mb = parse_u32(&values, "buffer_budget_mb", config.buffer_budget_mb)?;
     config.border_size = parse_u32(&values, "border_size", config.border_size)?;
+    config.border_active_color =
+        parse_color(&values, "border_active_color", config.border_active_color)?;
+    config.border_inactive_color = parse_color(
+        &values,
+        "border_inactive_color",
+        config.border_inactive_color,
+    )?;
+    config.corner_radius = parse_u32(&values, "corner_radius", config.corner_radius)?;
+    config.background_color = parse_color(&values, "background_color", config.background_color)?;
+    config.urgent_border_color = normalize_non_empty_field(&values, "urgent_border_color")
+        .map(|raw| {
+            parse_hex_color(&raw).ok_or_else(|| {
+                CompositorError::Backend(format!(
+                    "invalid value for urgent_border_color: {raw} (expected #rrggbb or #rrggbbaa)"
+                ))
+            })
+        })
+        .transpose()?;
 
     if let Some(gap_size) = parse_optional_u32(&values, "gap_size")? {
         config.gaps_outer_horizontal = gap_size;
@@ -348,6 +1097,9 @@ pub fn load_from_path(path: &Path) -> Result<RuntimeConfig, CompositorError> {
     config.gaps_inner_vertical =
         parse_u32(&values, "gaps.inner_vertical", config.gaps_inner_vertical)?;
 
+    if let Some(raw) = values.get("layout_type") {
+        config.layout_type = parse_layout_type(raw, "layout.type")?;
+    }
     config.master_factor = parse_f32(&values, "master_factor", config.master_factor)?;
     if !(0.1..=0.9).contains(&config.master_factor) {
         return Err(CompositorError::Backend(
@@ -363,6 +1115,19 @@ pub fn load_from_path(path: &Path) -> Result<RuntimeConfig, CompositorError> {
     }
 
     config.smart_gaps = parse_bool(&values, "smart_gaps", config.smart_gaps)?;
+    config.dim_unfocused = parse_f32(&values, "dim_unfocused", config.dim_unfocused)?;
+    if !(0.0..=1.0).contains(&config.dim_unfocused) {
+        return Err(CompositorError::Backend(
+            "dim_unfocused must be between 0.0 and 1.0".to_owned(),
+        ));
+    }
+    config.dim_inactive = parse_f32(&values, "dim_inactive", config.dim_inactive)?;
+    if !(0.0..=1.0).contains(&config.dim_inactive) {
+        return Err(CompositorError::Backend(
+            "dim_inactive must be between 0.0 and 1.0".to_owned(),
+        ));
+    }
+    config.screencast_chooser = normalize_non_empty_field(&values, "screencast_chooser");
 
     if let Some(value) = values.get("cursor_theme") {
         config.cursor_theme = value.clone();
@@ -374,7 +1139,9 @@ pub fn load_from_path(path: &Path) -> Result<RuntimeConfig, CompositorError> {
         ));
     }
 
-    config.autostart = collect_indexed_values(&values, "autostart.")?;
+    config.workspace_names = parse_workspace_names(&values)?;
+    config.autostart = parse_autostart(&values, &config.workspace_names)?;
+    config.session = parse_session(&values)?;
 
     config.wallpaper.enabled =
         parse_bool_flexible(&values, "wallpaper.enabled", config.wallpaper.enabled)?;
@@ -405,12 +1172,30 @@ pub fn load_from_path(path: &Path) -> Result<RuntimeConfig, CompositorError> {
             "wallpaper.transition_duration must be >= 0".to_owned(),
         ));
     }
+    if let Some(value) = values.get("wallpaper.slideshow_directory") {
+        config.wallpaper.slideshow_directory = value.clone();
+    }
+    config.wallpaper.slideshow_interval_secs = parse_u32(
+        &values,
+        "wallpaper.slideshow_interval",
+        config.wallpaper.slideshow_interval_secs,
+    )?;
+    if config.wallpaper.slideshow_interval_secs == 0 {
+        return Err(CompositorError::Backend(
+            "wallpaper.slideshow_interval must be greater than 0".to_owned(),
+        ));
+    }
+    config.wallpaper.builtin =
+        parse_bool_flexible(&values, "wallpaper.builtin", config.wallpaper.builtin)?;
+    // The built-in renderer falls back to `background_color` without an image.
     if config.wallpaper.enabled
+        && !config.wallpaper.builtin
         && config.wallpaper.restore_command.trim().is_empty()
         && config.wallpaper.image.trim().is_empty()
+        && config.wallpaper.slideshow_directory.trim().is_empty()
     {
         return Err(CompositorError::Backend(
-            "wallpaper.enabled is true but wallpaper.restore_command and wallpaper.image are empty"
+            "wallpaper.enabled is true but wallpaper.restore_command, wallpaper.image and wallpaper.slideshow_directory are empty"
                 .to_owned(),
         ));
     }
@@ -437,8 +1222,47 @@ pub fn load_from_path(path: &Path) -> Result<RuntimeConfig, CompositorError> {
         }
     }
 
+    config.input.left_handed =
+        parse_bool_flexible(&values, "input.left_handed", config.input.left_handed)?;
+    config.input.hide_cursor_on_touch = parse_bool_flexible(
+        &values,
+        "input.hide_cursor_on_touch",
+        config.input.hide_cursor_on_touch,
+    )?;
+    config.input.touch_hold_right_click_ms = parse_u32(
+        &values,
+        "input.touch_hold_right_click",
+        config.input.touch_hold_right_click_ms,
+    )?;
+    config.input.sticky_keys =
+        parse_bool_flexible(&values, "input.sticky_keys", config.input.sticky_keys)?;
+    config.input.slow_keys_ms = parse_u32(&values, "input.slow_keys", config.input.slow_keys_ms)?;
+    config.input.bounce_keys_ms =
+        parse_u32(&values, "input.bounce_keys", config.input.bounce_keys_ms)?;
+    config.input.devices = parse_input_devices(&values)?;
+
     config.monitors = parse_monitor_configs(&values)?;
-    config.window_rules = parse_window_rules(&values)?;
+    config.window_rules = parse_window_rules(&values, &config.workspace_names)?;
+    config.workspace_hooks = WorkspaceHooks {
+        on_workspace_empty: normalize_non_empty_field(&values, "on_workspace_empty"),
+        on_first_window: normalize_non_empty_field(&values, "on_first_window"),
+    };
+    config.workspace_hook_overrides = parse_workspace_hook_overrides(&values)?;
+    config.events = EventHooks {
+        output_added: normalize_non_empty_field(&values, "events.output_added"),
+        output_removed: normalize_non_empty_field(&values, "events.output_removed"),
+        workspace_switched: normalize_non_empty_field(&values, "events.workspace_switched"),
+        window_opened: normalize_non_empty_field(&values, "events.window_opened"),
+    };
+    config.idle = IdleConfig {
+        timeout_secs: parse_u32(&values, "idle.timeout", 0)?,
+        on_idle: normalize_non_empty_field(&values, "idle.on_idle"),
+        on_resume: normalize_non_empty_field(&values, "idle.on_resume"),
+    };
+    config.night_light = parse_night_light(&values)?;
+    config.last_window_closed = parse_last_window_closed(&values)?;
+    config.new_window_output_focus = parse_new_window_output_focus(&values)?;
+    config.workspace_animation = parse_workspace_animation(&values)?;
 
     let keybind_lines = collect_indexed_values(&values, "keybind.")?;
     config.keybinds = if keybind_lines.is_empty() {
@@ -446,7 +1270,7 @@ pub fn load_from_path(path: &Path) -> Result<RuntimeConfig, CompositorError> {
     } else {
         keybind_lines
             .iter()
-            .map(|line| parse_keybind_line(line, config.main_key))
+            .map(|line| parse_keybind_line(line, config.main_key, &config.workspace_names))
             .collect::<Result<Vec<_>, _>>()?
     };
 
@@ -473,7 +1297,7 @@ pub fn apply_environment(config: &RuntimeConfig) {
     }
 }
 
-fn config_path() -> Result<PathBuf, CompositorError> {
+pub fn config_path() -> Result<PathBuf, CompositorError> {
     if let Some(xdg) = std::env::var_os("XDG_CONFIG_HOME")
         && !xdg.is_empty()
     {
@@ -559,6 +1383,121 @@ fn collect_indexed_values(
     Ok(indexed.into_iter().map(|(_, value)| value).collect())
 }
 
+fn parse_autostart(
+    values: &HashMap<String, String>,
+    workspace_names: &BTreeMap<usize, String>,
+) -> Result<Vec<AutostartEntry>, CompositorError> {
+    let mut entries = BTreeMap::<usize, AutostartEntry>::new();
+    for (key, value) in values {
+        let Some(raw_index) = key.strip_prefix("autostart.") else {
+            continue;
+        };
+        let index = raw_index.parse::<usize>().map_err(|err| {
+            CompositorError::Backend(format!(
+                "invalid indexed key `{key}`: index is not a number ({err})"
+            ))
+        })?;
+        entries.insert(
+            index,
+            AutostartEntry {
+                command: value.clone(),
+                workspace: None,
+            },
+        );
+    }
+
+    for (key, value) in values {
+        let Some(raw_index) = key.strip_prefix("autostart_workspace.") else {
+            continue;
+        };
+        let entry = raw_index
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| entries.get_mut(&index))
+            .ok_or_else(|| {
+                CompositorError::Backend(format!(
+                    "invalid autostart key `{key}`: no autostart command at that index"
+                ))
+            })?;
+        let trimmed = value.trim();
+        let workspace = resolve_workspace(trimmed, workspace_names).map_err(|err| {
+            CompositorError::Backend(format!("invalid value for {key}: {trimmed} ({err})"))
+        })?;
+        entry.workspace = Some(workspace);
+    }
+
+    Ok(entries.into_values().collect())
+}
+
+fn parse_session(values: &HashMap<String, String>) -> Result<Vec<SessionEntry>, CompositorError> {
+    let mut grouped = BTreeMap::<usize, HashMap<String, String>>::new();
+
+    for (key, value) in values {
+        let Some(rest) = key.strip_prefix("session.") else {
+            continue;
+        };
+        let Some((raw_index, field)) = rest.split_once('.') else {
+            return Err(CompositorError::Backend(format!(
+                "invalid session key `{key}`: expected format session.<index>.<field>"
+            )));
+        };
+        let index = raw_index.parse::<usize>().map_err(|err| {
+            CompositorError::Backend(format!(
+                "invalid session key `{key}`: index is not a number ({err})"
+            ))
+        })?;
+        grouped
+            .entry(index)
+            .or_default()
+            .insert(field.trim().to_owned(), value.clone());
+    }
+
+    let mut entries = Vec::with_capacity(grouped.len());
+    for (index, fields) in grouped {
+        let Some(command) = normalize_non_empty_field(&fields, "cmd") else {
+            return Err(CompositorError::Backend(format!(
+                "session.{index} is missing a command"
+            )));
+        };
+        let Some(raw_workspace) = fields.get("workspace").map(|raw| raw.trim()) else {
+            return Err(CompositorError::Backend(format!(
+                "session.{index} is missing a workspace"
+            )));
+        };
+        let workspace = raw_workspace.parse::<usize>().map_err(|err| {
+            CompositorError::Backend(format!(
+                "invalid value for session.{index}.workspace: {raw_workspace} ({err})"
+            ))
+        })?;
+        if !(1..=MAX_WORKSPACES).contains(&workspace) {
+            return Err(CompositorError::Backend(format!(
+                "invalid value for session.{index}.workspace: {raw_workspace} (expected 1..{MAX_WORKSPACES})"
+            )));
+        }
+
+        entries.push(SessionEntry {
+            command,
+            workspace: workspace - 1,
+            floating: parse_optional_bool_flexible_in_map(
+                &fields,
+                "floating",
+                &format!("session.{index}.floating"),
+            )?
+            .unwrap_or(false),
+            x: parse_optional_i32_flexible_in_map(&fields, "x", &format!("session.{index}.x"))?,
+            y: parse_optional_i32_flexible_in_map(&fields, "y", &format!("session.{index}.y"))?,
+            width: parse_optional_u32_in_map(&fields, "width", &format!("session.{index}.width"))?,
+            height: parse_optional_u32_in_map(
+                &fields,
+                "height",
+                &format!("session.{index}.height"),
+            )?,
+        });
+    }
+
+    Ok(entries)
+}
+
 fn parse_monitor_configs(
     values: &HashMap<String, String>,
 ) -> Result<Vec<MonitorConfig>, CompositorError> {
@@ -716,48 +1655,333 @@ fn parse_monitor_configs(
             &format!("monitor.{monitor_name}.scale", monitor_name = monitor.name),
         )?;
 
-        if let Some(scale) = monitor.scale
-            && scale <= 0.0
+        if let Some(scale) = monitor.scale
+            && scale <= 0.0
+        {
+            return Err(CompositorError::Backend(format!(
+                "monitor `{}`: scale must be greater than 0",
+                monitor.name
+            )));
+        }
+
+        monitor.transform = fields
+            .get("transform")
+            .map(|value| value.trim().to_owned())
+            .filter(|value| !value.is_empty());
+        monitor.fallback_modes = parse_monitor_fallback_modes(&fields, &monitor.name)?;
+        monitor.wallpaper = fields
+            .get("wallpaper")
+            .map(|value| value.trim().to_owned())
+            .filter(|value| !value.is_empty());
+
+        monitors.push(monitor);
+    }
+
+    Ok(monitors)
+}
+
+fn parse_monitor_fallback_modes(
+    fields: &HashMap<String, String>,
+    monitor_name: &str,
+) -> Result<Vec<MonitorMode>, CompositorError> {
+    let mut ordered = BTreeMap::<usize, &str>::new();
+    for (field, value) in fields {
+        let Some(raw_index) = field.strip_prefix("fallback_mode.") else {
+            continue;
+        };
+        let index = raw_index.parse::<usize>().map_err(|err| {
+            CompositorError::Backend(format!(
+                "invalid monitor key `monitor.{monitor_name}.{field}`: index is not a number ({err})"
+            ))
+        })?;
+        ordered.insert(index, value);
+    }
+
+    ordered
+        .into_iter()
+        .map(|(index, raw)| {
+            let (width, height, refresh_hz) = parse_monitor_mode(
+                raw,
+                &format!("monitor.{monitor_name}.fallback_modes[{index}]"),
+            )?;
+            Ok(MonitorMode {
+                width,
+                height,
+                refresh_hz,
+            })
+        })
+        .collect()
+}
+
+fn parse_window_rules(
+    values: &HashMap<String, String>,
+    workspace_names: &BTreeMap<usize, String>,
+) -> Result<Vec<WindowRule>, CompositorError> {
+    let mut grouped = BTreeMap::<usize, HashMap<String, String>>::new();
+
+    for (key, value) in values {
+        let Some(rest) = key.strip_prefix("window_rule.") else {
+            continue;
+        };
+        let Some((raw_index, field)) = rest.split_once('.') else {
+            return Err(CompositorError::Backend(format!(
+                "invalid window rule key `{key}`: expected format window_rule.<index>.<field>"
+            )));
+        };
+        if field.trim().is_empty() {
+            return Err(CompositorError::Backend(format!(
+                "invalid window rule key `{key}`: missing field"
+            )));
+        }
+        let index = raw_index.parse::<usize>().map_err(|err| {
+            CompositorError::Backend(format!(
+                "invalid window rule key `{key}`: index is not a number ({err})"
+            ))
+        })?;
+        grouped
+            .entry(index)
+            .or_default()
+            .insert(field.trim().to_owned(), value.clone());
+    }
+
+    let mut rules = Vec::with_capacity(grouped.len());
+    for (index, fields) in grouped {
+        let mut rule = WindowRule::default();
+        if let Some(raw) = normalize_non_empty_field(&fields, "match_mode") {
+            rule.match_mode = MatchMode::parse(&raw).ok_or_else(|| {
+                CompositorError::Backend(format!(
+                    "invalid value for window_rule.{index}.match_mode: {raw} (expected default, exact, contains or regex)"
+                ))
+            })?;
+        }
+        let pattern = |field: &str, substring_by_default: bool| {
+            parse_rule_pattern(&fields, field, index, rule.match_mode, substring_by_default)
+        };
+        rule.class = pattern("class", false)?;
+        rule.app_id = match pattern("app_id", false)? {
+            Some(app_id) => Some(app_id),
+            None => pattern("appid", false)?,
+        };
+        rule.title = pattern("title", true)?;
+        rule.not_class = pattern("not_class", false)?;
+        rule.not_app_id = pattern("not_app_id", false)?;
+        rule.not_title = pattern("not_title", true)?;
+        rule.window_type = normalize_non_empty_field(&fields, "type")
+            .map(|raw| {
+                WindowType::parse(&raw).ok_or_else(|| {
+                    CompositorError::Backend(format!(
+                        "invalid value for window_rule.{index}.type: {raw} (expected normal, dialog, modal, utility or splash)"
+                    ))
+                })
+            })
+            .transpose()?;
+        rule.workspace = parse_window_rule_workspace(&fields, "workspace", index, workspace_names)?;
+        rule.floating = parse_optional_bool_flexible_in_map(
+            &fields,
+            "floating",
+            &format!("window_rule.{index}.floating"),
+        )?;
+        rule.sticky = parse_optional_bool_flexible_in_map(
+            &fields,
+            "sticky",
+            &format!("window_rule.{index}.sticky"),
+        )?;
+        rule.fullscreen = parse_optional_bool_flexible_in_map(
+            &fields,
+            "fullscreen",
+            &format!("window_rule.{index}.fullscreen"),
+        )?;
+        rule.focus = parse_optional_bool_flexible_in_map(
+            &fields,
+            "focus",
+            &format!("window_rule.{index}.focus"),
+        )?;
+        rule.no_focus_follows_mouse = parse_optional_bool_flexible_in_map(
+            &fields,
+            "no_focus_follows_mouse",
+            &format!("window_rule.{index}.no_focus_follows_mouse"),
+        )?;
+        rule.exclusive_input = parse_optional_bool_flexible_in_map(
+            &fields,
+            "exclusive_input",
+            &format!("window_rule.{index}.exclusive_input"),
+        )?;
+        rule.close_to_workspace =
+            parse_window_rule_workspace(&fields, "close_to_workspace", index, workspace_names)?;
+        rule.hide_on_close = parse_optional_bool_flexible_in_map(
+            &fields,
+            "hide_on_close",
+            &format!("window_rule.{index}.hide_on_close"),
+        )?;
+        rule.on_open = normalize_non_empty_field(&fields, "on_open");
+        rule.output = normalize_non_empty_field(&fields, "output");
+        rule.width = parse_window_rule_size(&fields, "width", index)?;
+        rule.height = parse_window_rule_size(&fields, "height", index)?;
+        rule.position = parse_window_rule_position(&fields, index)?;
+        rule.max_fps =
+            parse_optional_u32_in_map(&fields, "max_fps", &format!("window_rule.{index}.max_fps"))?;
+        if rule.max_fps == Some(0) {
+            return Err(CompositorError::Backend(format!(
+                "invalid value for window_rule.{index}.max_fps: must be greater than 0"
+            )));
+        }
+        rule.maximize_margins = parse_window_rule_margins(&fields, index)?;
+        rule.opacity =
+            parse_optional_f64_in_map(&fields, "opacity", &format!("window_rule.{index}.opacity"))?
+                .map(|opacity| opacity as f32);
+        if rule
+            .opacity
+            .is_some_and(|opacity| !(0.0..=1.0).contains(&opacity))
+        {
+            return Err(CompositorError::Backend(format!(
+                "invalid value for window_rule.{index}.opacity: must be between 0.0 and 1.0"
+            )));
+        }
+        rule.border_color = normalize_non_empty_field(&fields, "border_color")
+            .map(|raw| {
+                parse_hex_color(&raw).ok_or_else(|| {
+                    CompositorError::Backend(format!(
+                        "invalid value for window_rule.{index}.border_color: {raw} (expected #rrggbb or #rrggbbaa)"
+                    ))
+                })
+            })
+            .transpose()?;
+        rule.no_gaps = parse_optional_bool_flexible_in_map(
+            &fields,
+            "no_gaps",
+            &format!("window_rule.{index}.no_gaps"),
+        )?;
+        rule.corner_radius = parse_optional_u32_in_map(
+            &fields,
+            "corner_radius",
+            &format!("window_rule.{index}.corner_radius"),
+        )?;
+
+        rules.push(rule);
+    }
+
+    Ok(rules)
+}
+
+fn parse_window_rule_size(
+    fields: &HashMap<String, String>,
+    field: &str,
+    index: usize,
+) -> Result<Option<RuleSize>, CompositorError> {
+    normalize_non_empty_field(fields, field)
+        .map(|raw| {
+            RuleSize::parse(&raw).ok_or_else(|| {
+                CompositorError::Backend(format!(
+                    "invalid value for window_rule.{index}.{field}: {raw} (expected pixels or a percentage like \"50%\")"
+                ))
+            })
+        })
+        .transpose()
+}
+
+/// `position` or `center` pick an anchor, then `x`/`y` pin either axis to an
+/// offset; an axis left alone is centered.
+fn parse_window_rule_position(
+    fields: &HashMap<String, String>,
+    index: usize,
+) -> Result<Option<RulePosition>, CompositorError> {
+    let mut position = normalize_non_empty_field(fields, "position")
+        .map(|raw| {
+            RulePosition::parse(&raw).ok_or_else(|| {
+                CompositorError::Backend(format!(
+                    "invalid value for window_rule.{index}.position: {raw} (expected center, top, bottom, left, right, top-left, top-right, bottom-left or bottom-right)"
+                ))
+            })
+        })
+        .transpose()?;
+    if parse_optional_bool_flexible_in_map(
+        fields,
+        "center",
+        &format!("window_rule.{index}.center"),
+    )? == Some(true)
+    {
+        position = Some(RulePosition::CENTER);
+    }
+    let x = parse_optional_i32_flexible_in_map(fields, "x", &format!("window_rule.{index}.x"))?;
+    let y = parse_optional_i32_flexible_in_map(fields, "y", &format!("window_rule.{index}.y"))?;
+    if x.is_some() || y.is_some() {
+        let anchor = position.unwrap_or(RulePosition::CENTER);
+        position = Some(RulePosition {
+            x: x.map_or(anchor.x, Align::Offset),
+            y: y.map_or(anchor.y, Align::Offset),
+        });
+    }
+    Ok(position)
+}
+
+/// `#rrggbb` or `#rrggbbaa` as RGBA components from 0.0 to 1.0.
+fn parse_hex_color(raw: &str) -> Option<[f32; 4]> {
+    let hex = raw.strip_prefix('#').unwrap_or(raw);
+    if !matches!(hex.len(), 6 | 8) || !hex.is_ascii() {
+        return None;
+    }
+    let mut color = [1.0; 4];
+    for (slot, start) in color.iter_mut().zip((0..hex.len()).step_by(2)) {
+        *slot = u8::from_str_radix(&hex[start..start + 2], 16).ok()? as f32 / 255.0;
+    }
+    Some(color)
+}
+
+fn parse_color(
+    values: &HashMap<String, String>,
+    key: &str,
+    default: [f32; 4],
+) -> Result<[f32; 4], CompositorError> {
+    match normalize_non_empty_field(values, key) {
+        Some(raw) => parse_hex_color(&raw).ok_or_else(|| {
+            CompositorError::Backend(format!(
+                "invalid value for {key}: {raw} (expected #rrggbb or #rrggbbaa)"
+            ))
+        }),
+        None => Ok(default),
+    }
+}
+
+fn parse_window_rule_margins(
+    fields: &HashMap<String, String>,
+    index: usize,
+) -> Result<Option<Margins>, CompositorError> {
+    let mut margins = Margins::default();
+    let mut any = false;
+    for (side, slot) in [
+        ("top", &mut margins.top),
+        ("bottom", &mut margins.bottom),
+        ("left", &mut margins.left),
+        ("right", &mut margins.right),
+    ] {
+        let key = format!("maximize_margins.{side}");
+        if let Some(value) =
+            parse_optional_u32_in_map(fields, &key, &format!("window_rule.{index}.{key}"))?
         {
-            return Err(CompositorError::Backend(format!(
-                "monitor `{}`: scale must be greater than 0",
-                monitor.name
-            )));
+            *slot = value;
+            any = true;
         }
-
-        monitor.transform = fields
-            .get("transform")
-            .map(|value| value.trim().to_owned())
-            .filter(|value| !value.is_empty());
-
-        monitors.push(monitor);
     }
-
-    Ok(monitors)
+    Ok(any.then_some(margins))
 }
 
-fn parse_window_rules(
+fn parse_input_devices(
     values: &HashMap<String, String>,
-) -> Result<Vec<WindowRule>, CompositorError> {
+) -> Result<Vec<InputDeviceConfig>, CompositorError> {
     let mut grouped = BTreeMap::<usize, HashMap<String, String>>::new();
 
     for (key, value) in values {
-        let Some(rest) = key.strip_prefix("window_rule.") else {
+        let Some(rest) = key.strip_prefix("input.device.") else {
             continue;
         };
         let Some((raw_index, field)) = rest.split_once('.') else {
             return Err(CompositorError::Backend(format!(
-                "invalid window rule key `{key}`: expected format window_rule.<index>.<field>"
+                "invalid input device key `{key}`: expected format input.device.<index>.<field>"
             )));
         };
-        if field.trim().is_empty() {
-            return Err(CompositorError::Backend(format!(
-                "invalid window rule key `{key}`: missing field"
-            )));
-        }
         let index = raw_index.parse::<usize>().map_err(|err| {
             CompositorError::Backend(format!(
-                "invalid window rule key `{key}`: index is not a number ({err})"
+                "invalid input device key `{key}`: index is not a number ({err})"
             ))
         })?;
         grouped
@@ -766,38 +1990,116 @@ fn parse_window_rules(
             .insert(field.trim().to_owned(), value.clone());
     }
 
-    let mut rules = Vec::with_capacity(grouped.len());
+    let mut devices = Vec::with_capacity(grouped.len());
     for (index, fields) in grouped {
-        let mut rule = WindowRule::default();
-        rule.class = normalize_non_empty_field(&fields, "class");
-        rule.app_id = normalize_non_empty_field(&fields, "app_id")
-            .or_else(|| normalize_non_empty_field(&fields, "appid"));
-        rule.title = normalize_non_empty_field(&fields, "title");
-        rule.workspace = parse_window_rule_workspace(&fields, index)?;
-        rule.floating = parse_optional_bool_flexible_in_map(
-            &fields,
-            "floating",
-            &format!("window_rule.{index}.floating"),
-        )?;
-        rule.fullscreen = parse_optional_bool_flexible_in_map(
-            &fields,
-            "fullscreen",
-            &format!("window_rule.{index}.fullscreen"),
-        )?;
-        rule.focus = parse_optional_bool_flexible_in_map(
-            &fields,
-            "focus",
-            &format!("window_rule.{index}.focus"),
-        )?;
-        rule.width =
-            parse_optional_u32_in_map(&fields, "width", &format!("window_rule.{index}.width"))?;
-        rule.height =
-            parse_optional_u32_in_map(&fields, "height", &format!("window_rule.{index}.height"))?;
+        let Some(name) = normalize_non_empty_field(&fields, "name") else {
+            return Err(CompositorError::Backend(format!(
+                "input.device.{index} is missing a device name"
+            )));
+        };
+        devices.push(InputDeviceConfig {
+            name,
+            left_handed: parse_optional_bool_flexible_in_map(
+                &fields,
+                "left_handed",
+                &format!("input.device.{index}.left_handed"),
+            )?,
+            hide_cursor: parse_optional_bool_flexible_in_map(
+                &fields,
+                "hide_cursor",
+                &format!("input.device.{index}.hide_cursor"),
+            )?,
+        });
+    }
 
-        rules.push(rule);
+    Ok(devices)
+}
+
+fn parse_workspace_hook_overrides(
+    values: &HashMap<String, String>,
+) -> Result<BTreeMap<usize, WorkspaceHooks>, CompositorError> {
+    let mut overrides = BTreeMap::<usize, WorkspaceHooks>::new();
+
+    for (key, value) in values {
+        let Some(rest) = key.strip_prefix("workspace.") else {
+            continue;
+        };
+        let Some((raw_index, field)) = rest.split_once('.') else {
+            return Err(CompositorError::Backend(format!(
+                "invalid workspace key `{key}`: expected format workspace.<number>.<field>"
+            )));
+        };
+        let number = raw_index.trim().parse::<usize>().map_err(|err| {
+            CompositorError::Backend(format!(
+                "invalid workspace key `{key}`: workspace is not a number ({err})"
+            ))
+        })?;
+        if !(1..=MAX_WORKSPACES).contains(&number) {
+            return Err(CompositorError::Backend(format!(
+                "invalid workspace key `{key}`: workspace must be between 1 and {MAX_WORKSPACES}"
+            )));
+        }
+
+        let command = Some(value.trim().to_owned()).filter(|command| !command.is_empty());
+        let hooks = overrides.entry(number - 1).or_default();
+        match field.trim() {
+            "on_workspace_empty" => hooks.on_workspace_empty = command,
+            "on_first_window" => hooks.on_first_window = command,
+            "name" => {}
+            other => {
+                return Err(CompositorError::Backend(format!(
+                    "invalid workspace key `{key}`: unknown field `{other}`"
+                )));
+            }
+        }
     }
 
-    Ok(rules)
+    Ok(overrides)
+}
+
+fn parse_workspace_names(
+    values: &HashMap<String, String>,
+) -> Result<BTreeMap<usize, String>, CompositorError> {
+    let mut names = BTreeMap::<usize, String>::new();
+
+    for (key, value) in values {
+        let Some(raw_index) = key
+            .strip_prefix("workspace.")
+            .and_then(|rest| rest.strip_suffix(".name"))
+        else {
+            continue;
+        };
+        // parse_workspace_hook_overrides reports malformed workspace numbers.
+        let Some(number) = raw_index
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .filter(|number| (1..=MAX_WORKSPACES).contains(number))
+        else {
+            continue;
+        };
+        let name = value.trim();
+        if name.is_empty() {
+            continue;
+        }
+        if name.parse::<usize>().is_ok() || name.contains(char::is_whitespace) {
+            return Err(CompositorError::Backend(format!(
+                "invalid value for {key}: `{name}` (workspace names must not be numbers or contain spaces)"
+            )));
+        }
+        names.insert(number - 1, name.to_owned());
+    }
+
+    let mut seen = HashSet::new();
+    for name in names.values() {
+        if !seen.insert(name.as_str()) {
+            return Err(CompositorError::Backend(format!(
+                "workspace name `{name}` is used by more than one workspace"
+            )));
+        }
+    }
+
+    Ok(names)
 }
 
 fn normalize_non_empty_field(fields: &HashMap<String, String>, field: &str) -> Option<String> {
@@ -808,11 +2110,41 @@ fn normalize_non_empty_field(fields: &HashMap<String, String>, field: &str) -> O
         .map(|raw| raw.to_owned())
 }
 
+fn parse_rule_pattern(
+    fields: &HashMap<String, String>,
+    field: &str,
+    index: usize,
+    mode: MatchMode,
+    substring_by_default: bool,
+) -> Result<Option<RulePattern>, CompositorError> {
+    let Some(raw) = normalize_non_empty_field(fields, field) else {
+        return Ok(None);
+    };
+    let pattern = match mode {
+        MatchMode::Default if substring_by_default => RulePattern::Contains(raw),
+        MatchMode::Default | MatchMode::Exact => RulePattern::Exact(raw),
+        MatchMode::Contains => RulePattern::Contains(raw),
+        MatchMode::Regex => RulePattern::Regex(
+            RegexBuilder::new(&raw)
+                .case_insensitive(true)
+                .build()
+                .map_err(|err| {
+                    CompositorError::Backend(format!(
+                        "invalid value for window_rule.{index}.{field}: {raw} ({err})"
+                    ))
+                })?,
+        ),
+    };
+    Ok(Some(pattern))
+}
+
 fn parse_window_rule_workspace(
     fields: &HashMap<String, String>,
+    field: &str,
     index: usize,
+    workspace_names: &BTreeMap<usize, String>,
 ) -> Result<Option<usize>, CompositorError> {
-    let Some(raw) = fields.get("workspace").map(String::as_str) else {
+    let Some(raw) = fields.get(field).map(String::as_str) else {
         return Ok(None);
     };
     let trimmed = raw.trim();
@@ -820,19 +2152,39 @@ fn parse_window_rule_workspace(
         return Ok(None);
     }
 
-    let number = trimmed.parse::<usize>().map_err(|err| {
-        CompositorError::Backend(format!(
-            "invalid value for window_rule.{index}.workspace: {trimmed} ({err})"
-        ))
-    })?;
+    resolve_workspace(trimmed, workspace_names)
+        .map(Some)
+        .map_err(|err| {
+            CompositorError::Backend(format!(
+                "invalid value for window_rule.{index}.{field}: {trimmed} ({err})"
+            ))
+        })
+}
 
-    if !(1..=10).contains(&number) {
-        return Err(CompositorError::Backend(format!(
-            "invalid value for window_rule.{index}.workspace: {trimmed} (expected 1..10)"
-        )));
+/// Resolve a workspace given by its number or its configured name into
+/// a 0-based index. The error describes what was wrong, for the caller to wrap.
+fn resolve_workspace(
+    raw: &str,
+    workspace_names: &BTreeMap<usize, String>,
+) -> Result<usize, String> {
+    if let Some((index, _)) = workspace_names
+        .iter()
+        .find(|(_, name)| name.as_str() == raw)
+    {
+        return Ok(*index);
     }
-
-    Ok(Some(number - 1))
+    let number = raw.parse::<usize>().map_err(|_| {
+        if workspace_names.is_empty() {
+            "expected a workspace number".to_owned()
+        } else {
+            let names: Vec<&str> = workspace_names.values().map(String::as_str).collect();
+            format!("expected a workspace number or one of {}", names.join(", "))
+        }
+    })?;
+    if !(1..=MAX_WORKSPACES).contains(&number) {
+        return Err(format!("expected 1..{MAX_WORKSPACES}"));
+    }
+    Ok(number - 1)
 }
 
 fn parse_optional_bool_flexible_in_map(
@@ -1010,6 +2362,99 @@ fn parse_main_key(raw: &str) -> Result<MainKey, CompositorError> {
     }
 }
 
+fn parse_night_light(
+    values: &HashMap<String, String>,
+) -> Result<NightLightConfig, CompositorError> {
+    let default = NightLightConfig::default();
+    let temperature = parse_u32(values, "night_light.temperature", default.temperature)?;
+    if !(1000..=10000).contains(&temperature) {
+        return Err(CompositorError::Backend(format!(
+            "invalid value for night_light.temperature: {temperature} (expected 1000 to 10000 kelvin)"
+        )));
+    }
+    let schedule = match normalize_non_empty_field(values, "night_light.schedule") {
+        Some(raw) => Some(parse_night_light_schedule(&raw).ok_or_else(|| {
+            CompositorError::Backend(format!(
+                "invalid value for night_light.schedule: {raw} (expected HH:MM-HH:MM)"
+            ))
+        })?),
+        None => None,
+    };
+    Ok(NightLightConfig {
+        enabled: parse_bool_flexible(values, "night_light.enabled", default.enabled)?,
+        temperature,
+        schedule,
+    })
+}
+
+/// `"21:00-07:00"` as minutes after midnight.
+fn parse_night_light_schedule(raw: &str) -> Option<(u32, u32)> {
+    let minutes = |time: &str| {
+        let (hours, minutes) = time.trim().split_once(':')?;
+        let hours = hours.parse::<u32>().ok().filter(|hours| *hours < 24)?;
+        let minutes = minutes.parse::<u32>().ok().filter(|mins| *mins < 60)?;
+        Some(hours * 60 + minutes)
+    };
+    let (start, end) = raw.split_once('-')?;
+    Some((minutes(start)?, minutes(end)?))
+}
+
+fn parse_last_window_closed(
+    values: &HashMap<String, String>,
+) -> Result<LastWindowClosed, CompositorError> {
+    let command = normalize_non_empty_field(values, "last_window_closed_command");
+    let Some(raw) = values.get("last_window_closed") else {
+        return Ok(command.map_or(LastWindowClosed::Stay, LastWindowClosed::Command));
+    };
+
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "stay" | "none" => Ok(LastWindowClosed::Stay),
+        "previous" | "previous_non_empty" | "back" => Ok(LastWindowClosed::PreviousNonEmpty),
+        "command" | "exec" => command.map(LastWindowClosed::Command).ok_or_else(|| {
+            CompositorError::Backend(
+                "last_window_closed is `command` but last_window_closed_command is empty"
+                    .to_owned(),
+            )
+        }),
+        _ => Err(CompositorError::Backend(format!(
+            "invalid last_window_closed `{raw}` (expected stay, previous, or command)"
+        ))),
+    }
+}
+
+fn parse_new_window_output_focus(
+    values: &HashMap<String, String>,
+) -> Result<NewWindowOutputFocus, CompositorError> {
+    let Some(raw) = values.get("new_window_output_focus") else {
+        return Ok(NewWindowOutputFocus::default());
+    };
+
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "always" => Ok(NewWindowOutputFocus::Always),
+        "keybind" => Ok(NewWindowOutputFocus::Keybind),
+        _ => Err(CompositorError::Backend(format!(
+            "invalid new_window_output_focus `{raw}` (expected always or keybind)"
+        ))),
+    }
+}
+
+fn parse_workspace_animation(
+    values: &HashMap<String, String>,
+) -> Result<WorkspaceAnimation, CompositorError> {
+    let Some(raw) = values.get("workspace_animation") else {
+        return Ok(WorkspaceAnimation::default());
+    };
+
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "none" | "off" => Ok(WorkspaceAnimation::None),
+        "slide" => Ok(WorkspaceAnimation::Slide),
+        "crossfade" | "fade" => Ok(WorkspaceAnimation::Crossfade),
+        _ => Err(CompositorError::Backend(format!(
+            "invalid workspace_animation `{raw}` (expected slide, crossfade, or none)"
+        ))),
+    }
+}
+
 fn default_keybinds(main_key: MainKey) -> Result<Vec<Keybind>, CompositorError> {
     const DEFAULT_BINDS: &[&str] = &[
         "Main+Return terminal",
@@ -1018,17 +2463,23 @@ fn default_keybinds(main_key: MainKey) -> Result<Vec<Keybind>, CompositorError>
         "Main+V toggle_floating",
         "Main+J focus_next",
         "Main+K focus_previous",
+        "Main+Tab cycle_windows",
+        "Main+Shift+Tab cycle_windows_back",
         "Main+Shift+R reload_config",
         "Main+Escape quit",
     ];
 
     DEFAULT_BINDS
         .iter()
-        .map(|line| parse_keybind_line(line, main_key))
+        .map(|line| parse_keybind_line(line, main_key, &BTreeMap::new()))
         .collect()
 }
 
-fn parse_keybind_line(line: &str, main_key: MainKey) -> Result<Keybind, CompositorError> {
+fn parse_keybind_line(
+    line: &str,
+    main_key: MainKey,
+    workspace_names: &BTreeMap<usize, String>,
+) -> Result<Keybind, CompositorError> {
     let trimmed = line.trim();
     if trimmed.is_empty() {
         return Err(CompositorError::Backend(
@@ -1046,7 +2497,7 @@ fn parse_keybind_line(line: &str, main_key: MainKey) -> Result<Keybind, Composit
     let action_args = parts.collect::<Vec<_>>().join(" ");
 
     let (modifiers, key) = parse_combo(combo, main_key)?;
-    let action = parse_keybind_action(action_name, action_args.as_str(), trimmed)?;
+    let action = parse_keybind_action(action_name, action_args.as_str(), trimmed, workspace_names)?;
 
     Ok(Keybind {
         modifiers,
@@ -1113,12 +2564,139 @@ fn normalize_key_token(raw: &str) -> String {
     }
 }
 
-fn parse_keybind_action(
+/// A keybind action as written in the config, for `raven actions` and for
+/// resolving aliases before `parse_keybind_action` builds the `KeybindAction`.
+pub struct KeybindActionSignature {
+    pub name: &'static str,
+    pub aliases: &'static [&'static str],
+    pub args: &'static str,
+}
+
+const fn signature(
+    name: &'static str,
+    aliases: &'static [&'static str],
+    args: &'static str,
+) -> KeybindActionSignature {
+    KeybindActionSignature {
+        name,
+        aliases,
+        args,
+    }
+}
+
+/// One entry per `KeybindAction` variant that a keybind can name.
+pub const KEYBIND_ACTIONS: &[KeybindActionSignature] = &[
+    signature("exec", &[], "<command>"),
+    signature("terminal", &[], ""),
+    signature("launcher", &[], ""),
+    signature("close", &["close_focused", "close_window"], ""),
+    signature("force_close", &["kill"], ""),
+    signature("fullscreen", &["togglefullscreen"], ""),
+    signature("toggle_floating", &["togglefloating", "floating"], ""),
+    signature("pin", &["sticky", "toggle_sticky"], ""),
+    signature("quit", &[], ""),
+    signature("focus_next", &["next"], ""),
+    signature("focus_prev", &["focus_previous", "prev"], ""),
+    signature("reload", &["reload_config"], ""),
+    signature("performance_mode", &["toggle_performance_mode"], ""),
+    signature("find_cursor", &["locate_pointer"], ""),
+    signature("dpms", &["power"], "<on|off|toggle>"),
+    signature("focus_urgent", &["urgent"], ""),
+    signature("minimize_focused", &["minimize"], ""),
+    signature("workspace", &[], "<number|name|next_empty>"),
+    signature("movetoworkspace", &[], "<number|name>"),
+    signature("workspace_swap", &["swapworkspaces"], "<a> <b>"),
+    signature("workspace_reorder", &["moveworkspace"], "<from> <to>"),
+    signature("workspace_prev", &["prev_workspace"], ""),
+    signature("workspace_next", &["next_workspace"], ""),
+    signature("workspace_back_and_forth", &["back_and_forth"], ""),
+    signature("layout", &["setlayout"], "<tiling|bsp|scrolling|monocle>"),
+    signature("cycle_layout", &["nextlayout"], ""),
+    signature("split_ratio", &["splitratio"], "<+-delta>"),
+    signature("focus_left", &["focus_column_left"], ""),
+    signature("focus_right", &["focus_column_right"], ""),
+    signature("resize_left", &["shrink_master"], ""),
+    signature("resize_right", &["grow_master"], ""),
+    signature("swap_master", &["zoom"], ""),
+    signature("swap_left", &["move_left"], ""),
+    signature("swap_right", &["move_right"], ""),
+    signature("swap_up", &["move_up"], ""),
+    signature("swap_down", &["move_down"], ""),
+    signature("cycle_windows", &["alt_tab"], ""),
+    signature("cycle_windows_back", &["alt_shift_tab"], ""),
+    signature("resize_grow_width", &[], ""),
+    signature("resize_shrink_width", &[], ""),
+    signature("resize_grow_height", &[], ""),
+    signature("resize_shrink_height", &[], ""),
+];
+
+fn edit_distance(lhs: &str, rhs: &str) -> usize {
+    let rhs: Vec<char> = rhs.chars().collect();
+    let mut previous: Vec<usize> = (0..=rhs.len()).collect();
+    for (i, lhs_ch) in lhs.chars().enumerate() {
+        let mut current = vec![i + 1; rhs.len() + 1];
+        for (j, rhs_ch) in rhs.iter().enumerate() {
+            let substitution = previous[j] + usize::from(lhs_ch != *rhs_ch);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[rhs.len()]
+}
+
+/// Closest action name or alias to a misspelled `name`, reported by its
+/// canonical name.
+fn suggest_keybind_action(name: &str) -> Option<&'static str> {
+    let limit = (name.chars().count() / 3).max(2);
+    KEYBIND_ACTIONS
+        .iter()
+        .flat_map(|action| {
+            std::iter::once(action.name)
+                .chain(action.aliases.iter().copied())
+                .map(move |candidate| (edit_distance(name, candidate), action.name))
+        })
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, name)| name)
+}
+
+/// Canonical name of the keybind action called `name` or one of its aliases.
+fn canonical_keybind_action(name: &str) -> Option<&'static str> {
+    let lowered = name.to_ascii_lowercase();
+    KEYBIND_ACTIONS
+        .iter()
+        .find(|action| action.name == lowered || action.aliases.contains(&lowered.as_str()))
+        .map(|action| action.name)
+}
+
+/// Comma-separated action names, resolved to their canonical names.
+fn parse_action_names(raw: &str, key: &str) -> Result<Vec<String>, CompositorError> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(|name| {
+            canonical_keybind_action(name)
+                .map(str::to_owned)
+                .ok_or_else(|| {
+                    let hint = suggest_keybind_action(&name.to_ascii_lowercase())
+                        .map(|suggestion| format!(" (did you mean `{suggestion}`?)"))
+                        .unwrap_or_default();
+                    CompositorError::Backend(format!(
+                        "invalid value for {key}: unknown action `{name}`{hint}"
+                    ))
+                })
+        })
+        .collect()
+}
+
+pub(crate) fn parse_keybind_action(
     action_name: &str,
     action_args: &str,
     full_line: &str,
+    workspace_names: &BTreeMap<usize, String>,
 ) -> Result<KeybindAction, CompositorError> {
-    let action = match action_name.to_ascii_lowercase().as_str() {
+    let lowered = action_name.to_ascii_lowercase();
+    let action = match canonical_keybind_action(&lowered).unwrap_or(lowered.as_str()) {
         "exec" => {
             if action_args.trim().is_empty() {
                 return Err(CompositorError::Backend(format!(
@@ -1129,30 +2707,91 @@ fn parse_keybind_action(
         }
         "terminal" => KeybindAction::Terminal,
         "launcher" => KeybindAction::Launcher,
-        "close" | "close_focused" => KeybindAction::CloseFocused,
-        "close_window" => KeybindAction::CloseFocused,
-        "fullscreen" | "togglefullscreen" => KeybindAction::ToggleFullscreen,
-        "toggle_floating" | "togglefloating" | "floating" => KeybindAction::ToggleFloating,
+        "close" => KeybindAction::CloseFocused,
+        "force_close" => KeybindAction::ForceCloseFocused,
+        "fullscreen" => KeybindAction::ToggleFullscreen,
+        "toggle_floating" => KeybindAction::ToggleFloating,
+        "pin" => KeybindAction::TogglePin,
         "quit" => KeybindAction::Quit,
-        "focus_next" | "next" => KeybindAction::FocusNext,
-        "focus_prev" | "focus_previous" | "prev" => KeybindAction::FocusPrevious,
-        "reload" | "reload_config" => KeybindAction::ReloadConfig,
+        "focus_next" => KeybindAction::FocusNext,
+        "focus_prev" => KeybindAction::FocusPrevious,
+        "reload" => KeybindAction::ReloadConfig,
+        "performance_mode" => KeybindAction::TogglePerformanceMode,
+        "find_cursor" => KeybindAction::FindCursor,
+        "dpms" => KeybindAction::Dpms(DpmsMode::parse(action_args).ok_or_else(|| {
+            CompositorError::Backend(format!(
+                "invalid keybind `{full_line}`: `dpms` expects on, off or toggle"
+            ))
+        })?),
+        "focus_urgent" => KeybindAction::FocusUrgent,
+        "minimize_focused" => KeybindAction::MinimizeFocused,
+        "workspace" if action_args.trim() == "next_empty" => KeybindAction::SwitchToEmptyWorkspace,
         "workspace" => KeybindAction::SwitchWorkspace(parse_workspace_index(
             action_args,
             full_line,
             "workspace",
+            workspace_names,
         )?),
         "movetoworkspace" => KeybindAction::MoveFocusedToWorkspace(parse_workspace_index(
             action_args,
             full_line,
             "movetoworkspace",
+            workspace_names,
+        )?),
+        "workspace_swap" => {
+            let (first, second) =
+                parse_workspace_pair(action_args, full_line, "workspace_swap", workspace_names)?;
+            KeybindAction::SwapWorkspaces(first, second)
+        }
+        "workspace_reorder" => {
+            let (from, to) =
+                parse_workspace_pair(action_args, full_line, "workspace_reorder", workspace_names)?;
+            KeybindAction::ReorderWorkspace { from, to }
+        }
+        "workspace_prev" => KeybindAction::PreviousWorkspace,
+        "workspace_next" => KeybindAction::NextWorkspace,
+        "workspace_back_and_forth" => KeybindAction::WorkspaceBackAndForth,
+        "layout" => KeybindAction::SetLayout(parse_layout_type(
+            action_args.trim(),
+            &format!("keybind `{full_line}`"),
         )?),
-        "resize_left" | "resize_right" | "swap_master" => {
-            KeybindAction::Unsupported(action_name.to_owned())
+        "cycle_layout" => KeybindAction::CycleLayout,
+        "split_ratio" => {
+            let raw = action_args.trim();
+            let delta = raw
+                .trim_start_matches('+')
+                .parse::<f32>()
+                .ok()
+                .filter(|delta| delta.is_finite() && delta.abs() < 1.0)
+                .ok_or_else(|| {
+                    CompositorError::Backend(format!(
+                        "invalid keybind `{full_line}`: `split_ratio` expects a change such as +0.05 or -0.05, got `{raw}`"
+                    ))
+                })?;
+            KeybindAction::AdjustSplitRatio(delta)
         }
+        "focus_left" => KeybindAction::FocusColumnLeft,
+        "focus_right" => KeybindAction::FocusColumnRight,
+        "resize_left" => KeybindAction::ShrinkMaster,
+        "resize_right" => KeybindAction::GrowMaster,
+        "swap_master" => KeybindAction::SwapMaster,
+        "swap_left" => KeybindAction::SwapDirection(Direction::Left),
+        "swap_right" => KeybindAction::SwapDirection(Direction::Right),
+        "swap_up" => KeybindAction::SwapDirection(Direction::Up),
+        "swap_down" => KeybindAction::SwapDirection(Direction::Down),
+        "cycle_windows" => KeybindAction::CycleWindows,
+        "cycle_windows_back" => KeybindAction::CycleWindowsBack,
+        "resize_grow_width" => KeybindAction::GrowTileWidth,
+        "resize_shrink_width" => KeybindAction::ShrinkTileWidth,
+        "resize_grow_height" => KeybindAction::GrowTileHeight,
+        "resize_shrink_height" => KeybindAction::ShrinkTileHeight,
         _ => {
+            let hint = match suggest_keybind_action(&lowered) {
+                Some(suggestion) => format!("did you mean `{suggestion}`?"),
+                None => "run `raven actions` for the list".to_owned(),
+            };
             return Err(CompositorError::Backend(format!(
-                "invalid keybind `{full_line}`: unknown action `{action_name}`"
+                "invalid keybind `{full_line}`: unknown action `{action_name}` ({hint})"
             )));
         }
     };
@@ -1161,7 +2800,12 @@ fn parse_keybind_action(
         action,
         KeybindAction::Exec(_)
             | KeybindAction::SwitchWorkspace(_)
+            | KeybindAction::SwitchToEmptyWorkspace
             | KeybindAction::MoveFocusedToWorkspace(_)
+            | KeybindAction::SwapWorkspaces(..)
+            | KeybindAction::ReorderWorkspace { .. }
+            | KeybindAction::SetLayout(_)
+            | KeybindAction::AdjustSplitRatio(_)
     ) && !action_args.trim().is_empty()
     {
         return Err(CompositorError::Backend(format!(
@@ -1176,32 +2820,55 @@ fn parse_workspace_index(
     action_args: &str,
     full_line: &str,
     action_name: &str,
+    workspace_names: &BTreeMap<usize, String>,
 ) -> Result<usize, CompositorError> {
     let raw = action_args.trim();
     if raw.is_empty() {
         return Err(CompositorError::Backend(format!(
-            "invalid keybind `{full_line}`: action `{action_name}` requires workspace number"
+            "invalid keybind `{full_line}`: action `{action_name}` requires a workspace number or name"
         )));
     }
     if raw.contains(char::is_whitespace) {
         return Err(CompositorError::Backend(format!(
-            "invalid keybind `{full_line}`: action `{action_name}` expects a single number"
+            "invalid keybind `{full_line}`: action `{action_name}` expects a single workspace"
         )));
     }
 
-    let number = raw.parse::<usize>().map_err(|err| {
+    resolve_workspace(raw, workspace_names).map_err(|err| {
         CompositorError::Backend(format!(
-            "invalid keybind `{full_line}`: invalid workspace number `{raw}` ({err})"
+            "invalid keybind `{full_line}`: invalid workspace `{raw}` ({err})"
         ))
-    })?;
+    })
+}
+
+fn parse_layout_type(raw: &str, context: &str) -> Result<LayoutType, CompositorError> {
+    LayoutType::from_str(raw.trim()).map_err(|_| {
+        let expected: Vec<&str> = LayoutType::ALL.iter().map(LayoutType::name).collect();
+        CompositorError::Backend(format!(
+            "invalid {context}: unknown layout `{raw}` (expected {})",
+            expected.join(" or ")
+        ))
+    })
+}
 
-    if !(1..=10).contains(&number) {
+/// Parse two workspaces, by 1-based number or name (`"2 web"`), into 0-based
+/// indices.
+pub(crate) fn parse_workspace_pair(
+    action_args: &str,
+    full_line: &str,
+    action_name: &str,
+    workspace_names: &BTreeMap<usize, String>,
+) -> Result<(usize, usize), CompositorError> {
+    let mut numbers = action_args.split_whitespace();
+    let (Some(first), Some(second), None) = (numbers.next(), numbers.next(), numbers.next()) else {
         return Err(CompositorError::Backend(format!(
-            "invalid keybind `{full_line}`: workspace must be between 1 and 10"
+            "invalid keybind `{full_line}`: action `{action_name}` expects two workspaces"
         )));
-    }
-
-    Ok(number - 1)
+    };
+    Ok((
+        parse_workspace_index(first, full_line, action_name, workspace_names)?,
+        parse_workspace_index(second, full_line, action_name, workspace_names)?,
+    ))
 }
 
 fn matches_ci_exact(actual: Option<&str>, expected: &str) -> bool {
@@ -1346,7 +3013,7 @@ fn parse_bool_flexible(
 }
 
 fn default_config_template() -> &'static str {
-    r#"-- Raven config
+    r##"-- Raven config
 -- File: ~/.config/raven/config.lua (or $XDG_CONFIG_HOME/raven/config.lua)
 return {
   general = {
@@ -1357,8 +3024,40 @@ return {
     no_csd = true,
     gap_size = 8,
     border_size = 0,
+    -- border_active_color = "#5999f2",  -- border of the focused window (needs border_size)
+    -- border_inactive_color = "#454754",  -- border of the other windows
+    -- corner_radius = 8,  -- round window corners and their borders (0 = square)
+    -- background_color = "#969aab",  -- behind everything where no wallpaper is drawn
+    -- urgent_border_color = "#ff5555",  -- border of windows that asked for attention (needs border_size)
+    -- last_window_closed = "previous",  -- "stay", "previous" (last non-empty workspace), or "command"
+    -- last_window_closed_command = "fuzzel",
+    -- strict_protocol = true,  -- reject client spec violations (for client developers)
+    -- client_buffer_budget_mb = 1024,  -- disconnect a client holding more buffer memory (default 2048, 0 = off)
+    -- buffer_budget_mb = 4096,  -- refuse new dmabufs past this much across all clients (default 0 = off)
+    -- dim_unfocused = 0.2,  -- darken unfocused windows (0.0 = off); skipped for fullscreen and video
+    -- dim_inactive = 0.1,  -- fade unfocused windows so the desktop shows through (0.0 = off)
+    -- screencast_chooser = "fuzzel --dmenu",  -- pick the output or window to share (required: casts are refused without it)
+    -- new_window_output_focus = "keybind",  -- only keybind launches pull focus to another output
+    -- workspace_animation = "crossfade",  -- "slide" (default), "crossfade", or "none"
+    -- warp_pointer = true,  -- move the pointer along when focus jumps to a new window's output
+    -- dnd_edge_switch_delay = 600,  -- while dragging, rest at the left/right edge this many ms to change workspace
+    -- reload_layout_preview = 800,  -- on reload, outline the new tiled slots this many ms before windows move
+    -- fullscreen_exclusive_input = true,  -- keys go to a focused fullscreen window, not keybinds
+    -- fullscreen_exclusive_allow = { "fullscreen", "workspace" },  -- binds that still work (default: fullscreen)
+    -- workspace_grid_columns = 5,  -- pagers draw workspaces as a 5x2 grid instead of one column
+    -- workspace_count = 10,  -- workspaces that always exist; extra ones come and go as needed
+    -- floating_move_step = 40,  -- pixels swap_left/right/up/down move a floating window
   },
 
+  -- layout = {
+  --   type = "bsp",  -- "tiling" (master and stack, the default), "bsp" (each window splits the space left)
+  --                  -- "scrolling" (a strip of columns, master_factor wide, that scrolls to the focus)
+  --                  -- or "monocle" (every window fills the screen)
+  --   master_factor = 0.55,
+  --   gaps = { outer_horizontal = 20, outer_vertical = 20, inner_horizontal = 10, inner_vertical = 10 },
+  -- },
+
+  -- `raven actions` lists every action with its arguments.
   keybindings = {
     { combo = "Main+Q", action = "exec", command = "foot" },
     { combo = "Main+X", action = "exec", command = "firefox" },
@@ -1366,10 +3065,32 @@ return {
     { combo = "Main+C", action = "close_window" },
     { combo = "Main+F", action = "fullscreen" },
     { combo = "Main+V", action = "toggle_floating" },
+    -- { combo = "Main+P", action = "pin" },  -- keep the window on every workspace
     { combo = "Main+J", action = "focus_next" },
     { combo = "Main+K", action = "focus_prev" },
+    { combo = "Main+Tab", action = "cycle_windows" },  -- most recently used first; focus lands on release
+    { combo = "Main+Shift+Tab", action = "cycle_windows_back" },
     { combo = "Main+Shift+R", action = "reload_config" },
     { combo = "Main+Shift+Q", action = "quit" },
+    -- { combo = "Main+Shift+P", action = "find_cursor" },  -- flash a ring around the pointer
+    -- { combo = "Main+Shift+D", action = "dpms", arg = "toggle" },  -- blank or wake the screens
+    -- { combo = "Main+U", action = "focus_urgent" },  -- jump to the window that last asked for attention
+    -- { combo = "Main+M", action = "minimize_focused" },  -- a taskbar brings it back
+    -- { combo = "Main+Shift+C", action = "force_close" },  -- closes even hide_on_close windows
+    -- { combo = "Main+Space", action = "cycle_layout" },  -- or action = "layout", arg = "bsp"; per workspace
+    -- { combo = "Main+L", action = "split_ratio", arg = "+0.05" },  -- grow the focused bsp split
+    -- { combo = "Main+H", action = "split_ratio", arg = "-0.05" },
+    -- { combo = "Main+Left", action = "focus_left" },  -- previous column in the scrolling layout
+    -- { combo = "Main+Right", action = "focus_right" },
+    -- { combo = "Main+Shift+H", action = "resize_left" },  -- master_factor -0.05 until reload
+    -- { combo = "Main+Shift+L", action = "resize_right" },
+    -- { combo = "Main+Return", action = "swap_master" },
+    -- { combo = "Main+Shift+Left", action = "swap_left" },  -- trade places with the tile to the left
+    -- { combo = "Main+Shift+Right", action = "swap_right" },
+    -- { combo = "Main+Ctrl+L", action = "resize_grow_width" },  -- size just the focused tile (tiling, scrolling)
+    -- { combo = "Main+Ctrl+H", action = "resize_shrink_width" },
+    -- { combo = "Main+Ctrl+K", action = "resize_grow_height" },
+    -- { combo = "Main+Ctrl+J", action = "resize_shrink_height" },
 
     { combo = "Main+1", action = "workspace", arg = "1" },
     { combo = "Main+2", action = "workspace", arg = "2" },
@@ -1381,6 +3102,10 @@ return {
     { combo = "Main+8", action = "workspace", arg = "8" },
     { combo = "Main+9", action = "workspace", arg = "9" },
     { combo = "Main+0", action = "workspace", arg = "10" },
+    -- { combo = "Main+N", action = "workspace", arg = "next_empty" },  -- adds a workspace when all are taken
+    -- { combo = "Main+grave", action = "workspace_back_and_forth" },  -- toggle with the last workspace
+    -- { combo = "Main+bracketleft", action = "workspace_prev" },  -- wraps around
+    -- { combo = "Main+bracketright", action = "workspace_next" },
 
     { combo = "Main+Shift+1", action = "movetoworkspace", arg = "1" },
     { combo = "Main+Shift+2", action = "movetoworkspace", arg = "2" },
@@ -1408,6 +3133,8 @@ return {
     --   scale = 1.0,                     -- integer/fractional, must be > 0
     --   transform = "normal",            -- normal/90/180/270/flipped/flipped-90/flipped-180/flipped-270
     --   position = { x = 0, y = 0 },     -- or x = 0, y = 0
+    --   fallback_modes = { "1920x1080@60", "1280x720" },  -- tried in order if `mode` is unavailable
+    --   wallpaper = "~/Pictures/left.jpg",  -- with wallpaper.builtin, in place of wallpaper.image
     -- },
     --
     -- Disable an output:
@@ -1420,13 +3147,91 @@ return {
   window_rules = {
     { class = "Firefox", workspace = "2" },
     -- { class = "mpv", floating = true, width = 1280, height = 720 },
+    -- { app_id = "pavucontrol", floating = true, width = "40%", height = "50%", position = "top-right" },
+    -- { app_id = "qalculate-gtk", floating = true, x = 40, y = 40 },  -- or center = true; x/y are from the work area's corner
+    -- { app_id = "wvkbd", no_focus_follows_mouse = true },  -- hover never steals focus
+    -- { class = "cs2", exclusive_input = true },  -- overrides fullscreen_exclusive_input
+    -- { app_id = "spotify", close_to_workspace = 10 },  -- close hides it; force_close quits
+    -- { app_id = "discord", on_open = "notify-send 'Discord is up'" },
+    -- { app_id = "pavucontrol", floating = true, output = "HDMI-A-1" },
+    -- { title = "Picture-in-Picture", sticky = true },  -- floats on every workspace
+    -- { class = "Slack", max_fps = 30 },  -- withhold frame callbacks beyond 30 per second
+    -- { app_id = "kitty", opacity = 0.9 },  -- draw the window partly transparent
+    -- { class = "firefox", border_color = "#ff0000" },  -- border_size wide; also #rrggbbaa
+    -- { app_id = "mpv", no_gaps = true },  -- tile reaches the output edges and neighbours
+    -- { app_id = "mpv", corner_radius = 0 },  -- overrides corner_radius for the window
+    -- { app_id = "foot", maximize_margins = { top = 40, right = 300 } },  -- keep room for conky when maximized
+    -- { type = "splash", floating = false },  -- type = normal/dialog/modal/utility/splash
+    -- { class = "^steam_app_.*", match_mode = "regex", workspace = "5" },  -- match_mode = exact/contains/regex
+    -- { class = "chromium", not_title = "YouTube", floating = true },  -- not_class/not_app_id/not_title exclude
   },
 
+  -- Input devices. Names come from libinput (`libinput list-devices`).
+  -- input = {
+  --   left_handed = false,               -- swap left/right buttons on every pointer
+  --   hide_cursor_on_touch = true,       -- hide the cursor while a touchscreen is used
+  --   touch_hold_right_click = 500,      -- hold a finger still this many ms to right-click
+  --   sticky_keys = true,                -- tap a modifier to apply it to the next key, twice to lock
+  --   slow_keys = 300,                   -- accept a key only after it is held this many ms
+  --   bounce_keys = 200,                 -- ignore repeated presses of a key within this many ms
+  --   devices = {
+  --     ["Logitech MX Master 3"] = { left_handed = true },
+  --     ["Wacom Intuos S Pen"] = { hide_cursor = true },
+  --   },
+  -- },
+
   autostart = {
     "waybar",
     "mako",
+    -- { cmd = "firefox", workspace = 2 },  -- first window opens on workspace 2
   },
 
+  -- Initial layout, launched once at startup (`raven session` shows progress).
+  -- Tiled commands fill their workspace in the listed order; floating ones take
+  -- x/y (relative to the output) and width/height.
+  -- session = {
+  --   [1] = { "kitty", "kitty -e htop" },
+  --   [3] = {
+  --     "firefox",
+  --     { cmd = "pavucontrol", floating = true, x = 100, y = 80, width = 900, height = 600 },
+  --   },
+  -- },
+
+  -- Workspace names, shown by bars and usable wherever a workspace number is
+  -- (keybinds, window rules, autostart). Entries can also be tables with commands
+  -- run when the workspace loses its last window or gets its first one;
+  -- $RAVEN_WORKSPACE holds the workspace number. Per-workspace entries win over general.
+  -- workspaces = {
+  --   "web",
+  --   "code",
+  --   { name = "chat", on_first_window = "pkill -USR1 waybar", on_workspace_empty = "pkill -USR1 waybar" },
+  -- },
+
+  -- Commands run on compositor events. $RAVEN_OUTPUT names the output; workspace
+  -- switches get $RAVEN_WORKSPACE and $RAVEN_PREVIOUS_WORKSPACE; opened windows get
+  -- $RAVEN_APP_ID, $RAVEN_TITLE, $RAVEN_WINDOW_ID and $RAVEN_WORKSPACE. A window rule's
+  -- on_open runs for the windows it matches.
+  -- events = {
+  --   output_added = "kanshictl reload",
+  --   output_removed = "kanshictl reload",
+  --   workspace_switched = "pkill -RTMIN+8 waybar",
+  --   window_opened = "notify-send \"opened $RAVEN_APP_ID\"",
+  -- },
+
+  -- Run commands after `timeout` seconds without input, and on the next input.
+  -- Video players holding an idle inhibitor keep the timer from firing. Idle
+  -- daemons such as swayidle or hypridle work too, through ext-idle-notify.
+  -- idle = {
+  --   timeout = 300,
+  --   on_idle = "swaylock -f",
+  --   on_resume = "notify-send \"welcome back\"",
+  -- },
+
+  -- Warm the screens to `temperature` kelvin (1000-10000; 6500 is neutral),
+  -- always or inside `schedule`. `raven night-light on|off|toggle` overrides it
+  -- until the schedule next flips. Gamma tools like gammastep take precedence.
+  -- night_light = { enabled = true, temperature = 4000, schedule = "21:00-07:00" },
+
   wallpaper = {
     enabled = false,
     restore_command = "waypaper --restore",
@@ -1439,9 +3244,18 @@ return {
     -- resize = "crop",
     -- transition_type = "simple",
     -- transition_duration = 0.7,
+
+    -- Slideshow (swww mode, takes the place of image): a new picture from the
+    -- directory every interval seconds, held while a window is fullscreen.
+    -- slideshow_directory = "~/Pictures/wallpapers",
+    -- slideshow_interval = 300,
+
+    -- Built-in mode: Raven draws image (or the slideshow, or a monitor's own
+    -- wallpaper) itself, cropped to fill each output; no swww needed.
+    -- builtin = true,
   },
 }
-"#
+"##
 }
 
 fn lua_loader_script() -> &'static str {
@@ -1480,6 +3294,7 @@ focus_next = "focus_next"
 focus_prev = "focus_prev"
 focus_previous = "focus_previous"
 close_window = "close_window"
+force_close = "force_close"
 quit = "quit"
 reload_config = "reload_config"
 terminal_action = "terminal"
@@ -1576,6 +3391,17 @@ local function emit_number(name, value)
   emit(name, value)
 end
 
+local function emit_number_or_string(name, value)
+  if value == nil then
+    return
+  end
+  if type(value) ~= "number" and type(value) ~= "string" then
+    io.stderr:write(name .. " must be a number or string\n")
+    os.exit(1)
+  end
+  emit(name, value)
+end
+
 local function emit_boolean(name, value)
   if value == nil then
     return
@@ -1660,8 +3486,46 @@ emit_string("terminal", pick(general.terminal, pick(cfg.terminal, _G.terminal)))
 emit_string("launcher", pick(general.launcher, pick(cfg.launcher, _G.launcher)))
 emit_bool_like("focus_follow_mouse", pick(general.focus_follow_mouse, pick(cfg.focus_follow_mouse, _G.focus_follow_mouse)))
 emit_bool_like("no_csd", pick(general.no_csd, pick(cfg.no_csd, _G.no_csd)))
+emit_bool_like("strict_protocol", pick(general.strict_protocol, cfg.strict_protocol))
+emit_number("client_buffer_budget_mb", pick(general.client_buffer_budget_mb, cfg.client_buffer_budget_mb))
+emit_number("buffer_budget_mb", pick(general.buffer_budget_mb, cfg.buffer_budget_mb))
+emit_string("new_window_output_focus", pick(general.new_window_output_focus, cfg.new_window_output_focus))
+emit_string("workspace_animation", pick(general.workspace_animation, cfg.workspace_animation))
+emit_bool_like("warp_pointer", pick(general.warp_pointer, cfg.warp_pointer))
+emit_number("dnd_edge_switch_delay", pick(general.dnd_edge_switch_delay, cfg.dnd_edge_switch_delay))
+emit_number("reload_layout_preview", pick(general.reload_layout_preview, cfg.reload_layout_preview))
+emit_bool_like("fullscreen_exclusive_input", pick(general.fullscreen_exclusive_input, cfg.fullscreen_exclusive_input))
+local exclusive_allow = pick(general.fullscreen_exclusive_allow, cfg.fullscreen_exclusive_allow)
+if type(exclusive_allow) == "string" then
+  exclusive_allow = { exclusive_allow }
+end
+expect_table("fullscreen_exclusive_allow", exclusive_allow)
+if exclusive_allow then
+  for _, action in ipairs(exclusive_allow) do
+    if type(action) ~= "string" then
+      io.stderr:write("fullscreen_exclusive_allow entries must be action names\n")
+      os.exit(1)
+    end
+  end
+  emit("fullscreen_exclusive_allow", table.concat(exclusive_allow, ","))
+end
+emit_number("workspace_grid_columns", pick(general.workspace_grid_columns, cfg.workspace_grid_columns))
+emit_number("workspace_count", pick(general.workspace_count, cfg.workspace_count))
+emit_number("floating_move_step", pick(general.floating_move_step, cfg.floating_move_step))
+emit_number("dim_unfocused", pick(general.dim_unfocused, cfg.dim_unfocused))
+emit_number("dim_inactive", pick(general.dim_inactive, cfg.dim_inactive))
+emit_string("screencast_chooser", pick(general.screencast_chooser, cfg.screencast_chooser))
 emit_number("border_size", pick(general.border_size, pick(cfg.border_size, _G.border_size)))
+emit_string("border_active_color", pick(general.border_active_color, cfg.border_active_color))
+emit_string("border_inactive_color", pick(general.border_inactive_color, cfg.border_inactive_color))
+emit_number("corner_radius", pick(general.corner_radius, cfg.corner_radius))
+emit_string("urgent_border_color", pick(general.urgent_border_color, cfg.urgent_border_color))
+emit_string("background_color", pick(general.background_color, cfg.background_color))
 emit_number("gap_size", pick(general.gap_size, pick(cfg.gap_size, _G.gap_size)))
+emit_string("on_workspace_empty", pick(general.on_workspace_empty, cfg.on_workspace_empty))
+emit_string("last_window_closed", pick(general.last_window_closed, cfg.last_window_closed))
+emit_string("last_window_closed_command", pick(general.last_window_closed_command, cfg.last_window_closed_command))
+emit_string("on_first_window", pick(general.on_first_window, cfg.on_first_window))
 
 local keybinds_table = pick(cfg.keybindings, pick(cfg.keybinds, pick(_G.keybindings, _G.keybinds)))
 expect_table("keybindings", keybinds_table)
@@ -1678,15 +3542,68 @@ end
 local autostart = pick(cfg.autostart, _G.autostart)
 expect_table("autostart", autostart)
 if autostart then
-  for index, command in ipairs(autostart) do
+  for index, entry in ipairs(autostart) do
+    local command = entry
+    if type(entry) == "table" then
+      command = pick(entry.cmd, entry.command)
+      local workspace = entry.workspace
+      if workspace ~= nil and type(workspace) ~= "number" and type(workspace) ~= "string" then
+        io.stderr:write("autostart[" .. tostring(index) .. "].workspace must be a number or string\n")
+        os.exit(1)
+      end
+      if workspace ~= nil then
+        emit("autostart_workspace." .. tostring(index), workspace)
+      end
+    end
     if type(command) ~= "string" then
-      io.stderr:write("autostart[" .. tostring(index) .. "] must be a string\n")
+      io.stderr:write("autostart[" .. tostring(index) .. "] must be a string or { cmd = ..., workspace = ... }\n")
       os.exit(1)
     end
     emit("autostart." .. tostring(index), command)
   end
 end
 
+local session = pick(cfg.session, _G.session)
+expect_table("session", session)
+if session then
+  local workspace_keys = {}
+  for workspace in pairs(session) do
+    if tonumber(workspace) == nil then
+      io.stderr:write("session keys must be workspace numbers\n")
+      os.exit(1)
+    end
+    table.insert(workspace_keys, workspace)
+  end
+  table.sort(workspace_keys, function(a, b) return tonumber(a) < tonumber(b) end)
+
+  local session_index = 1
+  for _, workspace in ipairs(workspace_keys) do
+    local commands = session[workspace]
+    if type(commands) == "string" then
+      commands = { commands }
+    end
+    expect_table("session[" .. tostring(workspace) .. "]", commands)
+    for _, entry in ipairs(commands) do
+      if type(entry) == "string" then
+        entry = { cmd = entry }
+      end
+      if type(entry) ~= "table" then
+        io.stderr:write("session[" .. tostring(workspace) .. "] entries must be strings or { cmd = ... } tables\n")
+        os.exit(1)
+      end
+      local prefix = "session." .. tostring(session_index) .. "."
+      emit(prefix .. "workspace", workspace)
+      emit_string(prefix .. "cmd", pick(entry.cmd, entry.command))
+      emit_bool_like(prefix .. "floating", entry.floating)
+      emit_number(prefix .. "x", entry.x)
+      emit_number(prefix .. "y", entry.y)
+      emit_number(prefix .. "width", entry.width)
+      emit_number(prefix .. "height", entry.height)
+      session_index = session_index + 1
+    end
+  end
+end
+
 local window_rules = pick(cfg.window_rules, pick(cfg.rules, pick(_G.window_rules, _G.rules)))
 expect_table("window_rules", window_rules)
 if window_rules then
@@ -1702,12 +3619,39 @@ if window_rules then
     emit_string(prefix .. "class", pick(rule.class, key_name))
     emit_string(prefix .. "app_id", pick(rule.app_id, rule.appid))
     emit_string(prefix .. "title", rule.title)
-    emit_string(prefix .. "workspace", pick(rule.workspace, rule.ws))
+    emit_string(prefix .. "not_class", rule.not_class)
+    emit_string(prefix .. "not_app_id", rule.not_app_id)
+    emit_string(prefix .. "not_title", rule.not_title)
+    emit_string(prefix .. "match_mode", rule.match_mode)
+    emit_string(prefix .. "type", rule.type)
+    emit_number_or_string(prefix .. "workspace", pick(rule.workspace, rule.ws))
     emit_bool_like(prefix .. "floating", rule.floating)
+    emit_bool_like(prefix .. "sticky", pick(rule.sticky, rule.pin))
     emit_bool_like(prefix .. "fullscreen", rule.fullscreen)
     emit_bool_like(prefix .. "focus", rule.focus)
-    emit_number(prefix .. "width", rule.width)
-    emit_number(prefix .. "height", rule.height)
+    emit_bool_like(prefix .. "no_focus_follows_mouse", pick(rule.no_focus_follows_mouse, rule.no_focus_follow_mouse))
+    emit_bool_like(prefix .. "exclusive_input", rule.exclusive_input)
+    emit_number_or_string(prefix .. "close_to_workspace", rule.close_to_workspace)
+    emit_bool_like(prefix .. "hide_on_close", rule.hide_on_close)
+    emit_string(prefix .. "on_open", rule.on_open)
+    emit_string(prefix .. "output", rule.output)
+    emit_number_or_string(prefix .. "width", rule.width)
+    emit_number_or_string(prefix .. "height", rule.height)
+    emit_number(prefix .. "x", rule.x)
+    emit_number(prefix .. "y", rule.y)
+    emit_bool_like(prefix .. "center", rule.center)
+    emit_string(prefix .. "position", rule.position)
+    emit_number(prefix .. "max_fps", rule.max_fps)
+    emit_number(prefix .. "opacity", pick(rule.opacity, rule.alpha))
+    emit_string(prefix .. "border_color", rule.border_color)
+    emit_bool_like(prefix .. "no_gaps", rule.no_gaps)
+    emit_number(prefix .. "corner_radius", rule.corner_radius)
+    expect_table(prefix .. "maximize_margins", rule.maximize_margins)
+    if rule.maximize_margins then
+      for _, side in ipairs({ "top", "bottom", "left", "right" }) do
+        emit_number(prefix .. "maximize_margins." .. side, rule.maximize_margins[side])
+      end
+    end
     rule_index = rule_index + 1
   end
 
@@ -1722,6 +3666,25 @@ if window_rules then
   end
 end
 
+local workspaces = pick(cfg.workspaces, _G.workspaces)
+expect_table("workspaces", workspaces)
+if workspaces then
+  for key, workspace in pairs(workspaces) do
+    if type(workspace) == "string" then
+      workspace = { name = workspace }
+    end
+    if type(workspace) ~= "table" then
+      io.stderr:write("workspaces[" .. tostring(key) .. "] must be a name or a table\n")
+      os.exit(1)
+    end
+
+    local prefix = "workspace." .. tostring(key) .. "."
+    emit_string(prefix .. "name", workspace.name)
+    emit_string(prefix .. "on_workspace_empty", workspace.on_workspace_empty)
+    emit_string(prefix .. "on_first_window", workspace.on_first_window)
+  end
+end
+
 local monitors = pick(cfg.monitors, _G.monitors)
 expect_table("monitors", monitors)
 if monitors then
@@ -1758,6 +3721,16 @@ if monitors then
     emit_number(prefix .. "y", pick(monitor.y, position and position.y or nil))
     emit_number(prefix .. "scale", monitor.scale)
     emit_string(prefix .. "transform", monitor.transform)
+    emit_string(prefix .. "wallpaper", monitor.wallpaper)
+
+    local fallback_modes = monitor.fallback_modes
+    if fallback_modes ~= nil and type(fallback_modes) ~= "table" then
+      io.stderr:write("monitors[" .. tostring(index) .. "].fallback_modes must be a list of modes\n")
+      os.exit(1)
+    end
+    for fallback_index, fallback_mode in ipairs(fallback_modes or {}) do
+      emit_string(prefix .. "fallback_mode." .. tostring(fallback_index), fallback_mode)
+    end
   end
 
   local monitor_index = 1
@@ -1779,12 +3752,17 @@ expect_table("gaps", cfg.gaps)
 expect_table("cursor", cfg.cursor)
 expect_table("wallpaper", cfg.wallpaper)
 expect_table("xwayland", cfg.xwayland)
+expect_table("input", cfg.input)
+expect_table("events", cfg.events)
+expect_table("idle", cfg.idle)
+expect_table("night_light", cfg.night_light)
 
 local layout = cfg.layout or {}
 local gaps = pick(layout.gaps, cfg.gaps)
 expect_table("layout.gaps", gaps)
 gaps = gaps or {}
 
+emit_string("layout_type", pick(layout.type, cfg.layout_type))
 emit_number("master_factor", pick(layout.master_factor, cfg.master_factor))
 emit_number("num_master", pick(layout.num_master, cfg.num_master))
 emit_boolean("smart_gaps", pick(layout.smart_gaps, cfg.smart_gaps))
@@ -1805,6 +3783,25 @@ emit_string("wallpaper.image", pick(wallpaper.image, pick(wallpaper.path, cfg.wa
 emit_string("wallpaper.resize", pick(wallpaper.resize, cfg.wallpaper_resize))
 emit_string("wallpaper.transition_type", pick(wallpaper.transition_type, cfg.wallpaper_transition_type))
 emit_number("wallpaper.transition_duration", pick(wallpaper.transition_duration, cfg.wallpaper_transition_duration))
+emit_string("wallpaper.slideshow_directory", pick(wallpaper.slideshow_directory, wallpaper.slideshow_dir))
+emit_number("wallpaper.slideshow_interval", wallpaper.slideshow_interval)
+emit_bool_like("wallpaper.builtin", wallpaper.builtin)
+
+local events = cfg.events or {}
+emit_string("events.output_added", events.output_added)
+emit_string("events.output_removed", events.output_removed)
+emit_string("events.workspace_switched", events.workspace_switched)
+emit_string("events.window_opened", events.window_opened)
+
+local idle = cfg.idle or {}
+emit_number("idle.timeout", idle.timeout)
+emit_string("idle.on_idle", idle.on_idle)
+emit_string("idle.on_resume", idle.on_resume)
+
+local night_light = cfg.night_light or {}
+emit_bool_like("night_light.enabled", night_light.enabled)
+emit_number("night_light.temperature", night_light.temperature)
+emit_string("night_light.schedule", night_light.schedule)
 
 local xwayland = cfg.xwayland or {}
 local xwayland_enabled = pick(xwayland.enabled, pick(cfg.xwayland_enabled, _G.xwayland_enabled))
@@ -1818,5 +3815,34 @@ end
 emit_bool_like("xwayland.enabled", xwayland_enabled)
 emit_string("xwayland.path", pick(xwayland.path, pick(cfg.xwayland_path, _G.xwayland_path)))
 emit_string("xwayland.display", pick(xwayland.display, pick(cfg.xwayland_display, _G.xwayland_display)))
+
+local input = cfg.input or {}
+expect_table("input.devices", input.devices)
+emit_bool_like("input.left_handed", pick(input.left_handed, cfg.left_handed))
+emit_bool_like("input.hide_cursor_on_touch", input.hide_cursor_on_touch)
+emit_number("input.touch_hold_right_click", input.touch_hold_right_click)
+emit_bool_like("input.sticky_keys", input.sticky_keys)
+emit_number("input.slow_keys", input.slow_keys)
+emit_number("input.bounce_keys", input.bounce_keys)
+
+local device_index = 1
+local function emit_device(device, key_name)
+  expect_table("input.devices[" .. tostring(key_name or device_index) .. "]", device)
+  local prefix = "input.device." .. tostring(device_index) .. "."
+  emit_string(prefix .. "name", pick(device.name, key_name))
+  emit_bool_like(prefix .. "left_handed", device.left_handed)
+  emit_bool_like(prefix .. "hide_cursor", device.hide_cursor)
+  device_index = device_index + 1
+end
+
+local devices = input.devices or {}
+for _, device in ipairs(devices) do
+  emit_device(device, nil)
+end
+for key, device in pairs(devices) do
+  if type(key) == "string" then
+    emit_device(device, key)
+  end
+end
 "#
 }
@@ -0,0 +1,996 @@
+//! Converters from other compositor configs (sway, Hyprland) into a Raven `config.lua`.
+//!
+//! Only the directives with a direct Raven equivalent are translated: key bindings, outputs,
+//! window rules, autostart commands, gaps/borders and the cursor theme. Everything else is
+//! listed at the end of the generated file so nothing is dropped silently.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Write as _,
+    fs,
+    path::Path,
+};
+
+use crate::{
+    config::{KeybindAction, MAX_WORKSPACES},
+    errors::CompositorError,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportSource {
+    Sway,
+    Hyprland,
+}
+
+impl ImportSource {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "sway" | "i3" => Some(Self::Sway),
+            "hyprland" | "hypr" => Some(Self::Hyprland),
+            _ => None,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Sway => "sway",
+            Self::Hyprland => "hyprland",
+        }
+    }
+}
+
+/// Result of converting a foreign config file.
+pub struct ImportOutcome {
+    pub lua: String,
+    pub skipped: Vec<String>,
+}
+
+pub fn import_config(source: ImportSource, path: &Path) -> Result<ImportOutcome, CompositorError> {
+    let contents = fs::read_to_string(path).map_err(|err| {
+        CompositorError::Backend(format!(
+            "failed to read {} config {}: {err}",
+            source.name(),
+            path.display()
+        ))
+    })?;
+
+    let imported = match source {
+        ImportSource::Sway => parse_sway(&contents),
+        ImportSource::Hyprland => parse_hyprland(&contents),
+    };
+
+    Ok(ImportOutcome {
+        lua: imported.render_lua(source, path),
+        skipped: imported.skipped,
+    })
+}
+
+#[derive(Debug, Default)]
+struct ImportedConfig {
+    modkey: Option<String>,
+    terminal: Option<String>,
+    launcher: Option<String>,
+    focus_follow_mouse: Option<bool>,
+    border_size: Option<u32>,
+    corner_radius: Option<u32>,
+    dim_inactive: Option<f32>,
+    gaps_inner: Option<u32>,
+    gaps_outer: Option<u32>,
+    cursor_theme: Option<String>,
+    cursor_size: Option<u32>,
+    keybinds: Vec<ImportedKeybind>,
+    autostart: Vec<String>,
+    monitors: Vec<ImportedMonitor>,
+    window_rules: Vec<ImportedRule>,
+    skipped: Vec<String>,
+}
+
+#[derive(Debug)]
+struct ImportedKeybind {
+    combo: String,
+    action: &'static str,
+    arg: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct ImportedMonitor {
+    name: String,
+    enabled: bool,
+    mode: Option<String>,
+    x: Option<i32>,
+    y: Option<i32>,
+    scale: Option<f64>,
+    transform: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct ImportedRule {
+    app_id: Option<String>,
+    class: Option<String>,
+    title: Option<String>,
+    workspace: Option<usize>,
+    floating: Option<bool>,
+    fullscreen: Option<bool>,
+    width: Option<u32>,
+    height: Option<u32>,
+}
+
+impl ImportedRule {
+    fn has_matcher(&self) -> bool {
+        self.app_id.is_some() || self.class.is_some() || self.title.is_some()
+    }
+}
+
+impl ImportedConfig {
+    fn skip(&mut self, line_number: usize, line: &str) {
+        self.skipped
+            .push(format!("line {line_number}: {}", line.trim()));
+    }
+
+    /// Rewrite the configured mod key to `Main` so the binding follows `general.modkey`.
+    fn normalize_combo(&self, parts: &[String]) -> String {
+        parts
+            .iter()
+            .map(|part| {
+                let modifier = modifier_name(part);
+                match (&self.modkey, modifier) {
+                    (Some(modkey), Some(name)) if name == modkey.as_str() => "Main".to_owned(),
+                    (_, Some(name)) => name.to_owned(),
+                    _ => part.clone(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+
+    /// Merge rule effects into an existing entry with identical matchers.
+    fn push_rule(&mut self, rule: ImportedRule) {
+        if let Some(existing) = self.window_rules.iter_mut().find(|existing| {
+            existing.app_id == rule.app_id
+                && existing.class == rule.class
+                && existing.title == rule.title
+        }) {
+            existing.workspace = rule.workspace.or(existing.workspace);
+            existing.floating = rule.floating.or(existing.floating);
+            existing.fullscreen = rule.fullscreen.or(existing.fullscreen);
+            existing.width = rule.width.or(existing.width);
+            existing.height = rule.height.or(existing.height);
+            return;
+        }
+        self.window_rules.push(rule);
+    }
+
+    fn render_lua(&self, source: ImportSource, path: &Path) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "-- Raven config");
+        let _ = writeln!(
+            out,
+            "-- Imported from {} config: {}",
+            source.name(),
+            path.display()
+        );
+        let _ = writeln!(out, "return {{");
+
+        let _ = writeln!(out, "  general = {{");
+        if let Some(modkey) = &self.modkey {
+            let _ = writeln!(out, "    modkey = {},", lua_string(modkey));
+        }
+        if let Some(terminal) = &self.terminal {
+            let _ = writeln!(out, "    terminal = {},", lua_string(terminal));
+        }
+        if let Some(launcher) = &self.launcher {
+            let _ = writeln!(out, "    launcher = {},", lua_string(launcher));
+        }
+        if let Some(focus_follow_mouse) = self.focus_follow_mouse {
+            let _ = writeln!(out, "    focus_follow_mouse = {focus_follow_mouse},");
+        }
+        if let Some(border_size) = self.border_size {
+            let _ = writeln!(out, "    border_size = {border_size},");
+        }
+        if let Some(corner_radius) = self.corner_radius {
+            let _ = writeln!(out, "    corner_radius = {corner_radius},");
+        }
+        if let Some(dim_inactive) = self.dim_inactive {
+            let _ = writeln!(out, "    dim_inactive = {dim_inactive},");
+        }
+        let _ = writeln!(out, "  }},");
+
+        let _ = writeln!(out);
+        let _ = writeln!(out, "  keybindings = {{");
+        for bind in &self.keybinds {
+            let _ = write!(
+                out,
+                "    {{ combo = {}, action = {}",
+                lua_string(&bind.combo),
+                lua_string(bind.action)
+            );
+            match (&bind.arg, bind.action) {
+                (Some(arg), "exec") => {
+                    let _ = write!(out, ", command = {}", lua_string(arg));
+                }
+                (Some(arg), _) => {
+                    let _ = write!(out, ", arg = {}", lua_string(arg));
+                }
+                (None, _) => {}
+            }
+            let _ = writeln!(out, " }},");
+        }
+        let _ = writeln!(out, "  }},");
+
+        let _ = writeln!(out);
+        let _ = writeln!(out, "  monitors = {{");
+        for monitor in &self.monitors {
+            let _ = write!(out, "    {{ name = {}", lua_string(&monitor.name));
+            if !monitor.enabled {
+                let _ = write!(out, ", enabled = false");
+            }
+            if let Some(mode) = &monitor.mode {
+                let _ = write!(out, ", mode = {}", lua_string(mode));
+            }
+            if let Some(x) = monitor.x {
+                let _ = write!(out, ", x = {x}");
+            }
+            if let Some(y) = monitor.y {
+                let _ = write!(out, ", y = {y}");
+            }
+            if let Some(scale) = monitor.scale {
+                let _ = write!(out, ", scale = {scale}");
+            }
+            if let Some(transform) = &monitor.transform {
+                let _ = write!(out, ", transform = {}", lua_string(transform));
+            }
+            let _ = writeln!(out, " }},");
+        }
+        let _ = writeln!(out, "  }},");
+
+        let _ = writeln!(out);
+        let _ = writeln!(out, "  window_rules = {{");
+        for rule in &self.window_rules {
+            let mut fields = Vec::new();
+            if let Some(class) = &rule.class {
+                fields.push(format!("class = {}", lua_string(class)));
+            }
+            if let Some(app_id) = &rule.app_id {
+                fields.push(format!("app_id = {}", lua_string(app_id)));
+            }
+            if let Some(title) = &rule.title {
+                fields.push(format!("title = {}", lua_string(title)));
+            }
+            if let Some(workspace) = rule.workspace {
+                fields.push(format!("workspace = \"{workspace}\""));
+            }
+            if let Some(floating) = rule.floating {
+                fields.push(format!("floating = {floating}"));
+            }
+            if let Some(fullscreen) = rule.fullscreen {
+                fields.push(format!("fullscreen = {fullscreen}"));
+            }
+            if let (Some(width), Some(height)) = (rule.width, rule.height) {
+                fields.push(format!("width = {width}, height = {height}"));
+            }
+            let _ = writeln!(out, "    {{ {} }},", fields.join(", "));
+        }
+        let _ = writeln!(out, "  }},");
+
+        let _ = writeln!(out);
+        let _ = writeln!(out, "  autostart = {{");
+        for command in &self.autostart {
+            let _ = writeln!(out, "    {},", lua_string(command));
+        }
+        let _ = writeln!(out, "  }},");
+
+        if self.gaps_inner.is_some() || self.gaps_outer.is_some() {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "  layout = {{");
+            let _ = writeln!(out, "    gaps = {{");
+            if let Some(inner) = self.gaps_inner {
+                let _ = writeln!(out, "      inner_horizontal = {inner},");
+                let _ = writeln!(out, "      inner_vertical = {inner},");
+            }
+            if let Some(outer) = self.gaps_outer {
+                let _ = writeln!(out, "      outer_horizontal = {outer},");
+                let _ = writeln!(out, "      outer_vertical = {outer},");
+            }
+            let _ = writeln!(out, "    }},");
+            let _ = writeln!(out, "  }},");
+        }
+
+        if self.cursor_theme.is_some() || self.cursor_size.is_some() {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "  cursor = {{");
+            if let Some(theme) = &self.cursor_theme {
+                let _ = writeln!(out, "    theme = {},", lua_string(theme));
+            }
+            if let Some(size) = self.cursor_size {
+                let _ = writeln!(out, "    size = {size},");
+            }
+            let _ = writeln!(out, "  }},");
+        }
+
+        let _ = writeln!(out, "}}");
+
+        if !self.skipped.is_empty() {
+            let _ = writeln!(out);
+            let _ = writeln!(out, "-- Not imported (no Raven equivalent yet):");
+            for entry in &self.skipped {
+                let _ = writeln!(out, "--   {entry}");
+            }
+        }
+
+        out
+    }
+}
+
+fn parse_sway(contents: &str) -> ImportedConfig {
+    let mut config = ImportedConfig::default();
+    let mut variables = HashMap::<String, String>::new();
+    let mut block_depth = 0usize;
+
+    for (line_number, line) in logical_lines(contents, '#') {
+        if block_depth > 0 {
+            block_depth += line.matches('{').count();
+            block_depth = block_depth.saturating_sub(line.matches('}').count());
+            continue;
+        }
+        if line.ends_with('{') {
+            // bar/input/mode blocks have no Raven counterpart; skip them as a whole.
+            config.skip(line_number, &line);
+            block_depth = 1;
+            continue;
+        }
+
+        let words = split_words(&line);
+        let Some(directive) = words.first().map(String::as_str) else {
+            continue;
+        };
+
+        if directive == "set" {
+            if let (Some(name), Some(_)) = (words.get(1), words.get(2)) {
+                let value = substitute_variables(&words[2..].join(" "), &variables);
+                match name.as_str() {
+                    "$mod" => config.modkey = modifier_name(&value).map(str::to_owned),
+                    "$term" => config.terminal = Some(value.clone()),
+                    "$menu" => config.launcher = Some(value.clone()),
+                    _ => {}
+                }
+                variables.insert(name.clone(), value);
+            }
+            continue;
+        }
+
+        let line = substitute_variables(&line, &variables);
+        let words = split_words(&line);
+        let args = &words[1..];
+
+        let handled = match directive {
+            "bindsym" => parse_sway_bindsym(&mut config, args),
+            "exec" | "exec_always" => {
+                let command = strip_exec_flags(args);
+                if !command.is_empty() {
+                    config.autostart.push(command);
+                }
+                !args.is_empty()
+            }
+            "output" => parse_sway_output(&mut config, args),
+            "for_window" => parse_sway_for_window(&mut config, &line),
+            "assign" => parse_sway_assign(&mut config, &line),
+            "gaps" => match (args.first().map(String::as_str), args.get(1)) {
+                (Some("inner"), Some(value)) => {
+                    config.gaps_inner = value.parse().ok();
+                    config.gaps_inner.is_some()
+                }
+                (Some("outer"), Some(value)) => {
+                    config.gaps_outer = value.parse().ok();
+                    config.gaps_outer.is_some()
+                }
+                _ => false,
+            },
+            "default_border" => match (args.first().map(String::as_str), args.get(1)) {
+                (Some("pixel" | "normal"), Some(value)) => {
+                    config.border_size = value.parse().ok();
+                    config.border_size.is_some()
+                }
+                (Some("none"), _) => {
+                    config.border_size = Some(0);
+                    true
+                }
+                _ => false,
+            },
+            "focus_follows_mouse" => match args.first().map(String::as_str) {
+                Some("yes" | "always") => {
+                    config.focus_follow_mouse = Some(true);
+                    true
+                }
+                Some("no") => {
+                    config.focus_follow_mouse = Some(false);
+                    true
+                }
+                _ => false,
+            },
+            "seat" => match (args.get(1).map(String::as_str), args.get(2)) {
+                (Some("xcursor_theme"), Some(theme)) => {
+                    config.cursor_theme = Some(theme.clone());
+                    config.cursor_size = args.get(3).and_then(|size| size.parse().ok());
+                    true
+                }
+                _ => false,
+            },
+            _ => false,
+        };
+
+        if !handled {
+            config.skip(line_number, &line);
+        }
+    }
+
+    config
+}
+
+fn parse_sway_bindsym(config: &mut ImportedConfig, args: &[String]) -> bool {
+    let mut args = args.iter().skip_while(|arg| arg.starts_with("--"));
+    let Some(combo) = args.next() else {
+        return false;
+    };
+    let command = args.cloned().collect::<Vec<_>>();
+    if command.iter().any(|word| word.contains([';', ','])) {
+        return false;
+    }
+
+    let Some((action, arg)) = translate_sway_command(&command) else {
+        return false;
+    };
+    let parts = combo.split('+').map(str::to_owned).collect::<Vec<_>>();
+    config.keybinds.push(ImportedKeybind {
+        combo: config.normalize_combo(&parts),
+        action,
+        arg,
+    });
+    true
+}
+
+/// Translate one runtime sway/i3 command (as sent over IPC) into a Raven action.
+/// Unlike imported binds, `workspace <name>` may name a configured workspace.
+pub(crate) fn sway_command_action(
+    command: &str,
+    workspace_names: &BTreeMap<usize, String>,
+) -> Option<KeybindAction> {
+    let words = split_words(command);
+    let (action, arg) = translate_sway_command(&words).or_else(|| match words.as_slice() {
+        [verb, name] if verb == "workspace" => Some(("workspace", Some(name.clone()))),
+        _ => None,
+    })?;
+    crate::config::parse_keybind_action(
+        action,
+        arg.as_deref().unwrap_or(""),
+        command,
+        workspace_names,
+    )
+    .ok()
+}
+
+fn translate_sway_command(command: &[String]) -> Option<(&'static str, Option<String>)> {
+    let words = command.iter().map(String::as_str).collect::<Vec<_>>();
+    match words.as_slice() {
+        ["exec", ..] => {
+            let command = strip_exec_flags(&command[1..]);
+            (!command.is_empty()).then_some(("exec", Some(command)))
+        }
+        ["kill"] => Some(("close_window", None)),
+        ["fullscreen"] | ["fullscreen", "toggle"] => Some(("fullscreen", None)),
+        ["floating", "toggle"] => Some(("toggle_floating", None)),
+        ["exit"] => Some(("quit", None)),
+        ["reload"] => Some(("reload_config", None)),
+        ["focus", "right" | "down" | "next"] => Some(("focus_next", None)),
+        ["focus", "left" | "up" | "prev"] => Some(("focus_prev", None)),
+        ["workspace", "prev" | "prev_on_output"] => Some(("workspace_prev", None)),
+        ["workspace", "next" | "next_on_output"] => Some(("workspace_next", None)),
+        ["workspace", "back_and_forth"] => Some(("workspace_back_and_forth", None)),
+        ["workspace", "number", target] | ["workspace", target] => {
+            workspace_number(target).map(|index| ("workspace", Some(index.to_string())))
+        }
+        ["move", .., "workspace", "number", target] | ["move", .., "workspace", target]
+            if words.contains(&"to") =>
+        {
+            workspace_number(target).map(|index| ("movetoworkspace", Some(index.to_string())))
+        }
+        _ => None,
+    }
+}
+
+fn parse_sway_output(config: &mut ImportedConfig, args: &[String]) -> bool {
+    let Some(name) = args.first() else {
+        return false;
+    };
+    if name == "*" {
+        return false;
+    }
+
+    let mut monitor = ImportedMonitor {
+        name: name.trim_matches('"').to_owned(),
+        enabled: true,
+        ..ImportedMonitor::default()
+    };
+    let mut index = 1;
+    while index < args.len() {
+        match args[index].as_str() {
+            "mode" | "res" | "resolution" => {
+                let mut value = args.get(index + 1).map(String::as_str);
+                if value == Some("--custom") {
+                    index += 1;
+                    value = args.get(index + 1).map(String::as_str);
+                }
+                monitor.mode = value.map(|mode| mode.trim_end_matches("Hz").to_owned());
+                index += 2;
+            }
+            "pos" | "position" => {
+                monitor.x = args.get(index + 1).and_then(|x| x.parse().ok());
+                monitor.y = args.get(index + 2).and_then(|y| y.parse().ok());
+                index += 3;
+            }
+            "scale" => {
+                monitor.scale = args.get(index + 1).and_then(|scale| scale.parse().ok());
+                index += 2;
+            }
+            "transform" => {
+                monitor.transform = args
+                    .get(index + 1)
+                    .map(|transform| transform.replace('-', ""));
+                index += 2;
+            }
+            "disable" => {
+                monitor.enabled = false;
+                index += 1;
+            }
+            "enable" => index += 1,
+            _ => return false,
+        }
+    }
+
+    config.monitors.push(monitor);
+    true
+}
+
+fn parse_sway_for_window(config: &mut ImportedConfig, line: &str) -> bool {
+    let Some((mut rule, command)) = parse_sway_criteria(line) else {
+        return false;
+    };
+
+    let words = split_words(command);
+    let words = words.iter().map(String::as_str).collect::<Vec<_>>();
+    match words.as_slice() {
+        ["floating", "enable"] => rule.floating = Some(true),
+        ["floating", "disable"] => rule.floating = Some(false),
+        ["fullscreen", "enable"] | ["fullscreen"] => rule.fullscreen = Some(true),
+        ["move", .., "workspace", "number", target] | ["move", .., "workspace", target] => {
+            let Some(index) = workspace_number(target) else {
+                return false;
+            };
+            rule.workspace = Some(index);
+        }
+        _ => return false,
+    }
+
+    config.push_rule(rule);
+    true
+}
+
+fn parse_sway_assign(config: &mut ImportedConfig, line: &str) -> bool {
+    let Some((mut rule, target)) = parse_sway_criteria(line) else {
+        return false;
+    };
+
+    let words = split_words(target);
+    let Some(index) = words
+        .iter()
+        .rev()
+        .find(|word| !matches!(word.as_str(), "→" | "workspace" | "number"))
+        .and_then(|word| workspace_number(word))
+    else {
+        return false;
+    };
+    rule.workspace = Some(index);
+
+    config.push_rule(rule);
+    true
+}
+
+/// Split `for_window [app_id="x" title="y"] rest` into matchers and the trailing command.
+fn parse_sway_criteria(line: &str) -> Option<(ImportedRule, &str)> {
+    let open = line.find('[')?;
+    let close = open + line[open..].find(']')?;
+    let criteria = &line[open + 1..close];
+
+    let mut rule = ImportedRule::default();
+    for pair in split_words(criteria) {
+        let (key, value) = pair.split_once('=')?;
+        let value = plain_pattern(value.trim_matches('"'))?;
+        match key {
+            "app_id" => rule.app_id = Some(value),
+            "class" | "instance" => rule.class = Some(value),
+            "title" => rule.title = Some(value),
+            _ => return None,
+        }
+    }
+
+    rule.has_matcher()
+        .then_some((rule, line[close + 1..].trim()))
+}
+
+fn parse_hyprland(contents: &str) -> ImportedConfig {
+    let mut config = ImportedConfig::default();
+    let mut variables = HashMap::<String, String>::new();
+    let mut sections = Vec::<String>::new();
+
+    for (line_number, line) in logical_lines(contents, '#') {
+        if let Some(section) = line.strip_suffix('{') {
+            sections.push(section.trim().to_owned());
+            continue;
+        }
+        if line == "}" {
+            sections.pop();
+            continue;
+        }
+
+        let Some((raw_key, raw_value)) = line.split_once('=') else {
+            config.skip(line_number, &line);
+            continue;
+        };
+        let raw_key = raw_key.trim();
+        let value = raw_value.trim();
+
+        if raw_key.starts_with('$') {
+            let value = substitute_variables(value, &variables);
+            match raw_key {
+                "$mainMod" | "$mod" => config.modkey = modifier_name(&value).map(str::to_owned),
+                "$terminal" => config.terminal = Some(value.clone()),
+                "$menu" => config.launcher = Some(value.clone()),
+                _ => {}
+            }
+            variables.insert(raw_key.to_owned(), value);
+            continue;
+        }
+
+        let key = sections
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(raw_key))
+            .collect::<Vec<_>>()
+            .join(":");
+        let value = substitute_variables(value, &variables);
+
+        let handled = match key.as_str() {
+            "bind" | "binde" | "bindl" | "bindr" | "bindel" | "bindle" => {
+                parse_hyprland_bind(&mut config, &value)
+            }
+            "exec-once" | "exec" => {
+                config.autostart.push(value.clone());
+                true
+            }
+            "monitor" => parse_hyprland_monitor(&mut config, &value),
+            "windowrule" => parse_hyprland_window_rule(&mut config, &value, false),
+            "windowrulev2" => parse_hyprland_window_rule(&mut config, &value, true),
+            "general:gaps_in" => {
+                config.gaps_inner = value.parse().ok();
+                config.gaps_inner.is_some()
+            }
+            "general:gaps_out" => {
+                config.gaps_outer = value.parse().ok();
+                config.gaps_outer.is_some()
+            }
+            "general:border_size" => {
+                config.border_size = value.parse().ok();
+                config.border_size.is_some()
+            }
+            "decoration:rounding" => {
+                config.corner_radius = value.parse().ok();
+                config.corner_radius.is_some()
+            }
+            "decoration:inactive_opacity" => {
+                config.dim_inactive = value
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|opacity| (0.0..=1.0).contains(opacity))
+                    // Rounded so 0.9 comes out as 0.1, not 0.100000024.
+                    .map(|opacity| ((1.0 - opacity) * 100.0).round() / 100.0);
+                config.dim_inactive.is_some()
+            }
+            "input:follow_mouse" => {
+                config.focus_follow_mouse = value.parse::<u32>().ok().map(|mode| mode != 0);
+                config.focus_follow_mouse.is_some()
+            }
+            "env" => match value.split_once(',') {
+                Some(("XCURSOR_THEME", theme)) => {
+                    config.cursor_theme = Some(theme.trim().to_owned());
+                    true
+                }
+                Some(("XCURSOR_SIZE", size)) => {
+                    config.cursor_size = size.trim().parse().ok();
+                    config.cursor_size.is_some()
+                }
+                _ => false,
+            },
+            _ => false,
+        };
+
+        if !handled {
+            config.skip(line_number, &format!("{key} = {value}"));
+        }
+    }
+
+    config
+}
+
+fn parse_hyprland_bind(config: &mut ImportedConfig, value: &str) -> bool {
+    let fields = value.splitn(4, ',').map(str::trim).collect::<Vec<_>>();
+    let [mods, key, dispatcher, rest @ ..] = fields.as_slice() else {
+        return false;
+    };
+    let params = rest.first().copied().unwrap_or("");
+
+    let (action, arg) = match (dispatcher.to_ascii_lowercase().as_str(), params) {
+        ("exec", command) if !command.is_empty() => ("exec", Some(command.to_owned())),
+        ("killactive", _) => ("close_window", None),
+        ("fullscreen", _) => ("fullscreen", None),
+        ("togglefloating", _) => ("toggle_floating", None),
+        ("exit", _) => ("quit", None),
+        ("cyclenext", "") | ("movefocus", "r" | "d") => ("focus_next", None),
+        ("cyclenext", "prev") | ("movefocus", "l" | "u") => ("focus_prev", None),
+        ("workspace", "previous") => ("workspace_back_and_forth", None),
+        ("workspace", "e-1" | "m-1" | "r-1") => ("workspace_prev", None),
+        ("workspace", "e+1" | "m+1" | "r+1") => ("workspace_next", None),
+        ("workspace", target) => match workspace_number(target) {
+            Some(index) => ("workspace", Some(index.to_string())),
+            None => return false,
+        },
+        ("movetoworkspace" | "movetoworkspacesilent", target) => match workspace_number(target) {
+            Some(index) => ("movetoworkspace", Some(index.to_string())),
+            None => return false,
+        },
+        _ => return false,
+    };
+
+    let mut parts = mods
+        .split(|ch: char| ch.is_whitespace() || ch == '_')
+        .filter(|part| !part.is_empty())
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+    parts.push((*key).to_owned());
+    config.keybinds.push(ImportedKeybind {
+        combo: config.normalize_combo(&parts),
+        action,
+        arg,
+    });
+    true
+}
+
+fn parse_hyprland_monitor(config: &mut ImportedConfig, value: &str) -> bool {
+    let fields = value.split(',').map(str::trim).collect::<Vec<_>>();
+    let Some(name) = fields.first().filter(|name| !name.is_empty()) else {
+        return false;
+    };
+
+    let mut monitor = ImportedMonitor {
+        name: (*name).to_owned(),
+        enabled: true,
+        ..ImportedMonitor::default()
+    };
+    if fields.get(1) == Some(&"disable") {
+        monitor.enabled = false;
+        config.monitors.push(monitor);
+        return true;
+    }
+
+    if let Some(mode) = fields.get(1)
+        && mode.contains('x')
+    {
+        monitor.mode = Some((*mode).to_owned());
+    }
+    if let Some((x, y)) = fields.get(2).and_then(|position| position.split_once('x')) {
+        monitor.x = x.parse().ok();
+        monitor.y = y.parse().ok();
+    }
+    monitor.scale = fields
+        .get(3)
+        .filter(|scale| **scale != "auto")
+        .and_then(|scale| scale.parse().ok());
+
+    let mut index = 4;
+    while index + 1 < fields.len() {
+        if fields[index] == "transform" {
+            monitor.transform = match fields[index + 1] {
+                "0" => Some("normal"),
+                "1" => Some("90"),
+                "2" => Some("180"),
+                "3" => Some("270"),
+                "4" => Some("flipped"),
+                "5" => Some("flipped90"),
+                "6" => Some("flipped180"),
+                "7" => Some("flipped270"),
+                _ => None,
+            }
+            .map(str::to_owned);
+        }
+        index += 2;
+    }
+
+    config.monitors.push(monitor);
+    true
+}
+
+fn parse_hyprland_window_rule(config: &mut ImportedConfig, value: &str, v2: bool) -> bool {
+    let Some((effect, matchers)) = value.split_once(',') else {
+        return false;
+    };
+
+    let mut rule = ImportedRule::default();
+    for matcher in matchers.split(',').map(str::trim) {
+        let (field, pattern) = if v2 {
+            let Some((field, pattern)) = matcher.split_once(':') else {
+                return false;
+            };
+            (field.trim(), pattern.trim())
+        } else {
+            ("class", matcher)
+        };
+        let Some(pattern) = plain_pattern(pattern) else {
+            return false;
+        };
+        match field {
+            "class" => rule.app_id = Some(pattern),
+            "title" => rule.title = Some(pattern),
+            _ => return false,
+        }
+    }
+    if !rule.has_matcher() {
+        return false;
+    }
+
+    let effect = effect.split_whitespace().collect::<Vec<_>>();
+    match effect.as_slice() {
+        ["float"] => rule.floating = Some(true),
+        ["tile"] => rule.floating = Some(false),
+        ["fullscreen"] => rule.fullscreen = Some(true),
+        ["workspace", target, ..] => {
+            let Some(index) = workspace_number(target) else {
+                return false;
+            };
+            rule.workspace = Some(index);
+        }
+        ["size", width, height] => {
+            let (Ok(width), Ok(height)) = (width.parse(), height.parse()) else {
+                return false;
+            };
+            rule.width = Some(width);
+            rule.height = Some(height);
+        }
+        _ => return false,
+    }
+
+    config.push_rule(rule);
+    true
+}
+
+/// Yield trimmed, comment-free lines, joining trailing-backslash continuations.
+fn logical_lines(contents: &str, comment: char) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut pending = String::new();
+    let mut pending_start = 0;
+
+    for (index, raw_line) in contents.lines().enumerate() {
+        let line = raw_line.trim();
+        if pending.is_empty() {
+            pending_start = index + 1;
+        }
+        if let Some(continued) = line.strip_suffix('\\') {
+            pending.push_str(continued.trim());
+            pending.push(' ');
+            continue;
+        }
+        pending.push_str(line);
+
+        let joined = std::mem::take(&mut pending);
+        let joined = joined.trim();
+        if joined.is_empty() || joined.starts_with(comment) {
+            continue;
+        }
+        lines.push((pending_start, joined.to_owned()));
+    }
+
+    lines
+}
+
+/// Whitespace split that keeps single- and double-quoted runs together.
+fn split_words(line: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+
+    for ch in line.chars() {
+        match (quote, ch) {
+            (Some(open), ch) if ch == open => quote = None,
+            (Some(_), ch) => current.push(ch),
+            (None, '"' | '\'') => quote = Some(ch),
+            (None, ch) if ch.is_whitespace() => {
+                if !current.is_empty() {
+                    words.push(std::mem::take(&mut current));
+                }
+            }
+            (None, ch) => current.push(ch),
+        }
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+
+    words
+}
+
+fn substitute_variables(line: &str, variables: &HashMap<String, String>) -> String {
+    // Longest names first so `$mod` does not clobber `$modshift`.
+    let mut names = variables.keys().collect::<Vec<_>>();
+    names.sort_by_key(|name| std::cmp::Reverse(name.len()));
+
+    let mut out = line.to_owned();
+    for name in names {
+        out = out.replace(name.as_str(), &variables[name]);
+    }
+    out
+}
+
+fn strip_exec_flags(args: &[String]) -> String {
+    args.iter()
+        .skip_while(|arg| arg.as_str() == "--no-startup-id")
+        .cloned()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn modifier_name(raw: &str) -> Option<&'static str> {
+    match raw.trim().to_ascii_lowercase().as_str() {
+        "mod4" | "super" | "logo" | "win" => Some("Super"),
+        "mod1" | "alt" => Some("Alt"),
+        "ctrl" | "control" => Some("Ctrl"),
+        "shift" => Some("Shift"),
+        _ => None,
+    }
+}
+
+fn workspace_number(raw: &str) -> Option<usize> {
+    raw.trim()
+        .parse::<usize>()
+        .ok()
+        .filter(|index| (1..=MAX_WORKSPACES).contains(index))
+}
+
+/// Reduce an anchored pattern such as `^(firefox)$` to its literal; regexes are rejected.
+fn plain_pattern(raw: &str) -> Option<String> {
+    let trimmed = raw.trim();
+    let trimmed = trimmed.strip_prefix('^').unwrap_or(trimmed);
+    let trimmed = trimmed.strip_suffix('$').unwrap_or(trimmed);
+    let trimmed = trimmed
+        .strip_prefix('(')
+        .and_then(|inner| inner.strip_suffix(')'))
+        .unwrap_or(trimmed);
+
+    let is_literal = !trimmed.is_empty()
+        && !trimmed.contains([
+            '*', '+', '?', '|', '[', ']', '(', ')', '{', '}', '\\', '^', '$',
+        ])
+        && !trimmed.contains(".*");
+    is_literal.then(|| trimmed.to_owned())
+}
+
+fn lua_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            _ => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
@@ -1,4 +1,4 @@
-use std::{fs::File, io::Read, time::Duration};
+use std::{collections::HashMap, fs::File, io::Read, time::Duration};
 
 use smithay::{
     backend::renderer::{
@@ -9,11 +9,11 @@ use smithay::{
             surface::{WaylandSurfaceRenderElement, render_elements_from_surface_tree},
         },
     },
-    input::pointer::CursorImageStatus,
+    input::pointer::{CursorIcon, CursorImageStatus},
     render_elements,
     utils::{Physical, Point, Scale},
 };
-use tracing::warn;
+use tracing::{debug, warn};
 use xcursor::{
     CursorTheme,
     parser::{Image, parse_xcursor},
@@ -101,6 +101,10 @@ where
 }
 
 pub struct CursorThemeManager {
+    theme: CursorTheme,
+    // Shapes clients asked for by name (cursor-shape-v1, or smithay's default),
+    // loaded on first use; `None` when the theme has none of the shape's names.
+    shapes: HashMap<CursorIcon, Option<Vec<Image>>>,
     icons: Vec<Image>,
     size: u32,
 }
@@ -114,27 +118,52 @@ impl CursorThemeManager {
             .unwrap_or(24);
 
         let theme = CursorTheme::load(&name);
-        let icons = load_default_cursor(&theme).unwrap_or_else(|err| {
+        let icons = load_cursor(&theme, "default").unwrap_or_else(|err| {
             warn!("Unable to load xcursor theme ({err}), using fallback cursor");
             vec![fallback_cursor_image()]
         });
 
-        Self { icons, size }
+        Self {
+            theme,
+            shapes: HashMap::new(),
+            icons,
+            size,
+        }
     }
 
-    pub fn image(&self, scale: u32, time: Duration) -> Image {
+    /// Frame of `icon` due at `time`. Shapes the theme lacks get the default
+    /// arrow.
+    pub fn image(&mut self, icon: CursorIcon, scale: u32, time: Duration) -> Image {
+        let theme = &self.theme;
+        let images = self
+            .shapes
+            .entry(icon)
+            .or_insert_with(|| {
+                let images = std::iter::once(icon.name())
+                    .chain(icon.alt_names().iter().copied())
+                    .find_map(|name| load_cursor(theme, name).ok());
+                if images.is_none() {
+                    debug!(
+                        "cursor theme has no `{}` cursor, using default",
+                        icon.name()
+                    );
+                }
+                images
+            })
+            .as_ref()
+            .unwrap_or(&self.icons);
         frame(
             time.as_millis() as u32,
             self.size.saturating_mul(scale),
-            &self.icons,
+            images,
         )
     }
 }
 
-fn load_default_cursor(theme: &CursorTheme) -> Result<Vec<Image>, String> {
+fn load_cursor(theme: &CursorTheme, name: &str) -> Result<Vec<Image>, String> {
     let path = theme
-        .load_icon("default")
-        .ok_or_else(|| "theme has no `default` cursor".to_owned())?;
+        .load_icon(name)
+        .ok_or_else(|| format!("theme has no `{name}` cursor"))?;
 
     let mut file = File::open(path).map_err(|err| format!("failed to open cursor file: {err}"))?;
     let mut data = Vec::new();
@@ -0,0 +1,107 @@
+//! Frame rate limiting for the `max_fps` window rule.
+//!
+//! Throttling works by withholding `wl_surface.frame` callbacks: a well-behaved
+//! client only draws after its callback fires. Buffers a client commits anyway
+//! are still composited as usual and get regular presentation-time feedback.
+
+use std::{cell::Cell, time::Duration};
+
+use smithay::{
+    desktop::Window,
+    output::Output,
+    reexports::{
+        calloop::timer::{TimeoutAction, Timer},
+        wayland_server::Resource,
+    },
+    wayland::compositor::with_states,
+};
+
+use crate::{Raven, backend::udev::RedrawCause};
+
+#[derive(Default)]
+struct FrameLimitState {
+    last_frame_at: Cell<Option<Duration>>,
+    wakeup_armed: Cell<bool>,
+}
+
+/// Time until `window` may get its next frame callbacks, or `None` if they are due.
+///
+/// `now` is measured from compositor start. Returning `None` records `now` as the
+/// window's latest frame.
+fn frame_delay(state: &Raven, window: &Window, now: Duration) -> Option<Duration> {
+    let surface = window.toplevel()?.wl_surface().clone();
+    let max_fps = state.surface_max_fps(&surface)?;
+    let interval = Duration::from_secs_f64(1.0 / f64::from(max_fps));
+
+    with_states(&surface, |states| {
+        let limit = states.data_map.get_or_insert(FrameLimitState::default);
+        if let Some(last) = limit.last_frame_at.get()
+            && now < last + interval
+        {
+            return Some(last + interval - now);
+        }
+        limit.last_frame_at.set(Some(now));
+        None
+    })
+}
+
+/// Whether `window` should receive frame callbacks for a frame at `now`.
+pub fn frame_due(state: &Raven, window: &Window, now: Duration) -> bool {
+    frame_delay(state, window, now).is_none()
+}
+
+/// Like [`frame_due`], but a throttled window also gets a redraw of `output`
+/// scheduled for when its next frame is due, so the withheld callback is sent
+/// even if nothing else on the output changes in the meantime.
+pub fn frame_due_or_schedule(
+    state: &Raven,
+    window: &Window,
+    output: &Output,
+    now: Duration,
+) -> bool {
+    let Some(delay) = frame_delay(state, window, now) else {
+        return true;
+    };
+    let Some(surface) = window
+        .toplevel()
+        .map(|toplevel| toplevel.wl_surface().clone())
+    else {
+        return false;
+    };
+
+    let newly_armed = with_states(&surface, |states| {
+        let limit = states.data_map.get_or_insert(FrameLimitState::default);
+        !limit.wakeup_armed.replace(true)
+    });
+    if !newly_armed {
+        return false;
+    }
+
+    let output = output.clone();
+    let timer_surface = surface.clone();
+    let scheduled =
+        state
+            .loop_handle
+            .insert_source(Timer::from_duration(delay), move |_, _, state| {
+                if timer_surface.is_alive() {
+                    with_states(&timer_surface, |states| {
+                        let limit = states.data_map.get_or_insert(FrameLimitState::default);
+                        limit.wakeup_armed.set(false);
+                    });
+                }
+                crate::backend::udev::queue_redraw_for_output(
+                    state,
+                    &output,
+                    RedrawCause::Animation,
+                );
+                TimeoutAction::Drop
+            });
+    if let Err(err) = scheduled {
+        tracing::warn!("failed to schedule frame limit wakeup: {err}");
+        with_states(&surface, |states| {
+            let limit = states.data_map.get_or_insert(FrameLimitState::default);
+            limit.wakeup_armed.set(false);
+        });
+    }
+    false
+}
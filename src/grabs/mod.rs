@@ -1,2 +1,3 @@
 pub mod move_grab;
+pub mod region_select_grab;
 pub mod resize_grab;
@@ -0,0 +1,153 @@
+use smithay::{
+    backend::input::ButtonState,
+    input::pointer::{
+        AxisFrame, ButtonEvent, GestureHoldBeginEvent, GestureHoldEndEvent, GesturePinchBeginEvent,
+        GesturePinchEndEvent, GesturePinchUpdateEvent, GestureSwipeBeginEvent,
+        GestureSwipeEndEvent, GestureSwipeUpdateEvent, GrabStartData as PointerGrabStartData,
+        MotionEvent, PointerGrab, PointerInnerHandle, RelativeMotionEvent,
+    },
+    reexports::wayland_server::protocol::wl_surface::WlSurface,
+    utils::{Logical, Point},
+};
+
+use crate::Raven;
+
+// Button codes from linux/input-event-codes.h.
+const BTN_LEFT: u32 = 0x110;
+const BTN_RIGHT: u32 = 0x111;
+
+/// Pointer grab for `raven screenshot --region`: drag with the left button to
+/// select, right button cancels. Clients get no pointer events meanwhile.
+pub struct RegionSelectGrab {
+    pub start_data: PointerGrabStartData<Raven>,
+}
+
+impl PointerGrab<Raven> for RegionSelectGrab {
+    fn motion(
+        &mut self,
+        data: &mut Raven,
+        handle: &mut PointerInnerHandle<'_, Raven>,
+        _focus: Option<(WlSurface, Point<f64, Logical>)>,
+        event: &MotionEvent,
+    ) {
+        handle.motion(data, None, event);
+        data.region_selection_moved(event.location);
+    }
+
+    fn relative_motion(
+        &mut self,
+        data: &mut Raven,
+        handle: &mut PointerInnerHandle<'_, Raven>,
+        _focus: Option<(WlSurface, Point<f64, Logical>)>,
+        event: &RelativeMotionEvent,
+    ) {
+        handle.relative_motion(data, None, event);
+    }
+
+    fn button(
+        &mut self,
+        data: &mut Raven,
+        handle: &mut PointerInnerHandle<'_, Raven>,
+        event: &ButtonEvent,
+    ) {
+        match (event.button, event.state) {
+            (BTN_LEFT, ButtonState::Pressed) => {
+                data.region_selection_pressed(data.pointer_location);
+            }
+            (BTN_LEFT, ButtonState::Released) => {
+                data.finish_region_selection();
+                handle.unset_grab(self, data, event.serial, event.time, true);
+            }
+            (BTN_RIGHT, ButtonState::Pressed) => {
+                handle.unset_grab(self, data, event.serial, event.time, true);
+            }
+            _ => {}
+        }
+    }
+
+    fn axis(
+        &mut self,
+        _data: &mut Raven,
+        _handle: &mut PointerInnerHandle<'_, Raven>,
+        _details: AxisFrame,
+    ) {
+    }
+
+    fn frame(&mut self, data: &mut Raven, handle: &mut PointerInnerHandle<'_, Raven>) {
+        handle.frame(data);
+    }
+
+    fn gesture_swipe_begin(
+        &mut self,
+        _data: &mut Raven,
+        _handle: &mut PointerInnerHandle<'_, Raven>,
+        _event: &GestureSwipeBeginEvent,
+    ) {
+    }
+
+    fn gesture_swipe_update(
+        &mut self,
+        _data: &mut Raven,
+        _handle: &mut PointerInnerHandle<'_, Raven>,
+        _event: &GestureSwipeUpdateEvent,
+    ) {
+    }
+
+    fn gesture_swipe_end(
+        &mut self,
+        _data: &mut Raven,
+        _handle: &mut PointerInnerHandle<'_, Raven>,
+        _event: &GestureSwipeEndEvent,
+    ) {
+    }
+
+    fn gesture_pinch_begin(
+        &mut self,
+        _data: &mut Raven,
+        _handle: &mut PointerInnerHandle<'_, Raven>,
+        _event: &GesturePinchBeginEvent,
+    ) {
+    }
+
+    fn gesture_pinch_update(
+        &mut self,
+        _data: &mut Raven,
+        _handle: &mut PointerInnerHandle<'_, Raven>,
+        _event: &GesturePinchUpdateEvent,
+    ) {
+    }
+
+    fn gesture_pinch_end(
+        &mut self,
+        _data: &mut Raven,
+        _handle: &mut PointerInnerHandle<'_, Raven>,
+        _event: &GesturePinchEndEvent,
+    ) {
+    }
+
+    fn gesture_hold_begin(
+        &mut self,
+        _data: &mut Raven,
+        _handle: &mut PointerInnerHandle<'_, Raven>,
+        _event: &GestureHoldBeginEvent,
+    ) {
+    }
+
+    fn gesture_hold_end(
+        &mut self,
+        _data: &mut Raven,
+        _handle: &mut PointerInnerHandle<'_, Raven>,
+        _event: &GestureHoldEndEvent,
+    ) {
+    }
+
+    fn start_data(&self) -> &PointerGrabStartData<Raven> {
+        &self.start_data
+    }
+
+    fn unset(&mut self, data: &mut Raven) {
+        // A finished selection was already taken; anything still pending was
+        // cancelled or replaced by another grab.
+        data.cancel_region_selection();
+    }
+}
@@ -27,6 +27,7 @@ use smithay::{
 
 use crate::{
     Raven,
+    backend::udev::RedrawCause,
     grabs::resize_grab,
     handlers::{layer_shell, xdg_shell},
     state::ClientState,
@@ -105,6 +106,9 @@ impl CompositorHandler for Raven {
 
     fn commit(&mut self, surface: &WlSurface) {
         on_commit_buffer_handler::<Self>(surface);
+        if !self.check_surface_conformance(surface) || !self.account_buffer_commit(surface) {
+            return;
+        }
 
         // Pre-import the buffer on the primary GPU right away.  Without this,
         // the import happens synchronously inside render_frame() which blocks
@@ -120,6 +124,8 @@ impl CompositorHandler for Raven {
         if !is_sync_subsurface(surface) {
             if let Some(window) = self.window_for_surface(&commit_root_surface) {
                 window.on_commit();
+                crate::backend::udev::note_window_commit(self, &commit_root_surface);
+                self.note_sway_ipc_window_change();
             }
             lifecycle_root_surface = Some(commit_root_surface.clone());
         }
@@ -144,12 +150,16 @@ impl CompositorHandler for Raven {
                 let tracked_unmapped = self.is_surface_unmapped_toplevel(root_surface);
 
                 if tracked_unmapped {
-                    if root_is_mapped && root_has_buffer {
+                    if root_is_mapped
+                        && root_has_buffer
+                        && !self.hold_floating_first_map(root_surface, &window)
+                    {
                         // niri-style transition: an explicitly tracked unmapped toplevel now has
                         // a real root buffer, so it may enter the mapped layout path.
                         self.clear_surface_unmapped_toplevel(root_surface);
                         self.clear_initial_configure_for_surface(root_surface);
                         self.promote_window_to_mapped_workspace(&window);
+                        self.window_opened_event(root_surface);
                         lifecycle_transition = true;
                     } else {
                         if self.pending_initial_configure_ids.contains(root_surface) {
@@ -182,10 +192,14 @@ impl CompositorHandler for Raven {
                     if defer_pending {
                         self.maybe_apply_deferred_window_rules(root_surface);
                     }
-                    let on_current_workspace =
-                        self.workspace_contains_window(self.current_workspace, &window);
+                    let on_current_workspace = self
+                        .workspace_contains_window(self.current_workspace, &window)
+                        && !self.is_window_minimized(&window);
                     if on_current_workspace {
                         self.map_window_to_initial_location(&window, false);
+                    }
+                    self.order_session_windows(&window);
+                    if on_current_workspace {
                         if let Err(err) = self.apply_layout() {
                             tracing::warn!("failed to apply layout after root remap: {err}");
                         }
@@ -240,14 +254,14 @@ impl CompositorHandler for Raven {
         // heavy clients like Brave/Steam.
         if let Some(window) = self.window_for_surface(&commit_root_surface) {
             if let Some(output) = self.space.outputs_for_element(&window).into_iter().next() {
-                crate::backend::udev::queue_redraw_for_output(self, &output);
+                crate::backend::udev::queue_redraw_for_output(self, &output, RedrawCause::Commit);
             } else {
                 // Window not on any output yet, queue all
-                crate::backend::udev::queue_redraw_all(self);
+                crate::backend::udev::queue_redraw_all(self, RedrawCause::Commit);
             }
         } else {
             // No window found for the root surface, queue all outputs.
-            crate::backend::udev::queue_redraw_all(self);
+            crate::backend::udev::queue_redraw_all(self, RedrawCause::Commit);
         }
     }
 
@@ -259,7 +273,9 @@ impl CompositorHandler for Raven {
 }
 
 impl BufferHandler for Raven {
-    fn buffer_destroyed(&mut self, _buffer: &wl_buffer::WlBuffer) {}
+    fn buffer_destroyed(&mut self, buffer: &wl_buffer::WlBuffer) {
+        Raven::buffer_destroyed(self, buffer);
+    }
 }
 
 impl ShmHandler for Raven {
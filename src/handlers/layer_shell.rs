@@ -1,3 +1,4 @@
+use crate::backend::udev::RedrawCause;
 use crate::state::Raven;
 use smithay::delegate_layer_shell;
 use smithay::desktop::{LayerSurface, Space, Window, WindowSurfaceType, layer_map_for_output};
@@ -47,7 +48,7 @@ impl WlrLayerShellHandler for Raven {
             tracing::warn!("failed to map layer surface: {err:?}");
         }
         // Only redraw the affected output, not all outputs
-        crate::backend::udev::queue_redraw_for_output(self, &output);
+        crate::backend::udev::queue_redraw_for_output(self, &output, RedrawCause::LayerShell);
     }
 
     fn layer_destroyed(&mut self, surface: WlrLayerSurface) {
@@ -66,7 +67,7 @@ impl WlrLayerShellHandler for Raven {
         }
         if let Some(output) = output_to_redraw {
             // Only redraw the affected output
-            crate::backend::udev::queue_redraw_for_output(self, &output);
+            crate::backend::udev::queue_redraw_for_output(self, &output, RedrawCause::LayerShell);
         }
     }
 
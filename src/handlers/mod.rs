@@ -4,10 +4,12 @@ mod xdg_shell;
 
 use smithay::{
     backend::renderer::ImportDma,
-    delegate_data_device, delegate_dmabuf, delegate_drm_syncobj, delegate_fractional_scale,
-    delegate_output, delegate_pointer_constraints, delegate_pointer_gestures,
+    delegate_content_type, delegate_cursor_shape, delegate_data_device, delegate_dmabuf,
+    delegate_drm_syncobj, delegate_foreign_toplevel_list, delegate_fractional_scale,
+    delegate_idle_notify, delegate_output, delegate_pointer_constraints, delegate_pointer_gestures,
     delegate_presentation, delegate_primary_selection, delegate_relative_pointer, delegate_seat,
-    delegate_viewporter,
+    delegate_viewporter, delegate_xdg_activation,
+    desktop::utils::surface_primary_scanout_output,
     input::{
         Seat, SeatHandler, SeatState,
         dnd::{DnDGrab, DndGrabHandler, GrabType},
@@ -15,12 +17,14 @@ use smithay::{
     },
     output::Output,
     reexports::wayland_server::{Resource, protocol::wl_surface::WlSurface},
-    utils::{Logical, Point, SERIAL_COUNTER},
+    utils::{Logical, Physical, Point, SERIAL_COUNTER, Size},
     wayland::{
-        compositor::with_states,
+        compositor::{get_parent, with_states},
         dmabuf::{DmabufGlobal, DmabufHandler, DmabufState, ImportNotifier},
         drm_syncobj::{DrmSyncobjHandler, DrmSyncobjState},
+        foreign_toplevel_list::{ForeignToplevelListHandler, ForeignToplevelListState},
         fractional_scale::{FractionalScaleHandler, with_fractional_scale},
+        idle_notify::{IdleNotifierHandler, IdleNotifierState},
         output::OutputHandler,
         pointer_constraints::{PointerConstraintsHandler, with_pointer_constraint},
         selection::{
@@ -32,14 +36,27 @@ use smithay::{
                 PrimarySelectionHandler, PrimarySelectionState, set_primary_focus,
             },
         },
+        tablet_manager::TabletSeatHandler,
+        xdg_activation::{
+            XdgActivationHandler, XdgActivationState, XdgActivationToken, XdgActivationTokenData,
+        },
     },
 };
 
 use crate::{
-    Raven, delegate_ext_workspace, delegate_foreign_toplevel, delegate_screencopy,
+    Raven, delegate_ext_workspace, delegate_foreign_toplevel, delegate_gamma_control,
+    delegate_idle_inhibit, delegate_image_copy_capture, delegate_output_power, delegate_screencopy,
+    delegate_tearing_control,
     protocols::{
         ext_workspace::{self, ExtWorkspaceHandler, ExtWorkspaceManagerState},
         foreign_toplevel::{self, ForeignToplevelHandler, ForeignToplevelManagerState},
+        gamma_control::{GammaControlHandler, GammaControlManagerState},
+        idle_inhibit::{IdleInhibitHandler, IdleInhibitManagerState},
+        image_copy_capture::{
+            CaptureFrame, CaptureSource, ImageCopyCaptureHandler, ImageCopyCaptureState,
+        },
+        output_power::{OutputPowerHandler, OutputPowerManagerState},
+        tearing_control::{TearingControlHandler, TearingControlManagerState},
         wlr_screencopy::{Screencopy, ScreencopyHandler, ScreencopyManagerState},
     },
 };
@@ -61,6 +78,7 @@ impl SeatHandler for Raven {
 
         let focused_window = focused.and_then(|surface| self.window_for_surface(surface));
         self.sync_window_activation(focused_window.as_ref());
+        self.clear_window_urgency(focused);
     }
 
     fn cursor_image(&mut self, _seat: &Seat<Self>, image: CursorImageStatus) {
@@ -100,6 +118,7 @@ impl WaylandDndGrabHandler for Raven {
 
                 let grab = DnDGrab::new_pointer(&self.display_handle, start_data, source, seat);
                 ptr.set_grab(self, grab, serial, Focus::Keep);
+                self.dnd_drag_started();
             }
             // TODO: handle touch grab
             GrabType::Touch => {}
@@ -130,12 +149,24 @@ delegate_output!(Raven);
 
 impl FractionalScaleHandler for Raven {
     fn new_fractional_scale(&mut self, surface: WlSurface) {
-        let preferred_scale = self
-            .space
-            .outputs()
-            .next()
-            .map(|output| output.current_scale().fractional_scale())
-            .unwrap_or(1.0);
+        // Surfaces already on screen take the scale of the output they are
+        // mostly shown on; the udev backend keeps it current after each frame.
+        // Others guess their window's output, or the one under the pointer,
+        // where new windows open.
+        let mut root = surface.clone();
+        while let Some(parent) = get_parent(&root) {
+            root = parent;
+        }
+        let preferred_scale = with_states(&surface, |states| {
+            surface_primary_scanout_output(&surface, states)
+        })
+        .or_else(|| {
+            self.window_for_surface(&root)
+                .and_then(|window| self.window_output(&window))
+        })
+        .or_else(|| self.active_output_for_pointer())
+        .map(|output| output.current_scale().fractional_scale())
+        .unwrap_or(1.0);
 
         with_states(&surface, |states| {
             with_fractional_scale(states, |fractional_scale| {
@@ -147,6 +178,28 @@ impl FractionalScaleHandler for Raven {
 
 delegate_fractional_scale!(Raven);
 delegate_viewporter!(Raven);
+delegate_content_type!(Raven);
+
+// Shapes set through cursor-shape-v1 reach `SeatHandler::cursor_image` as named
+// cursors; tablet tools use the default no-op hooks.
+impl TabletSeatHandler for Raven {}
+delegate_cursor_shape!(Raven);
+
+impl XdgActivationHandler for Raven {
+    fn activation_state(&mut self) -> &mut XdgActivationState {
+        &mut self.xdg_activation_state
+    }
+
+    fn request_activation(
+        &mut self,
+        _token: XdgActivationToken,
+        token_data: XdgActivationTokenData,
+        surface: WlSurface,
+    ) {
+        self.handle_activation_request(&token_data, &surface);
+    }
+}
+delegate_xdg_activation!(Raven);
 
 impl PrimarySelectionHandler for Raven {
     fn primary_selection_state(&mut self) -> &mut PrimarySelectionState {
@@ -161,12 +214,102 @@ impl ScreencopyHandler for Raven {
     }
 
     fn frame(&mut self, screencopy: Screencopy) {
-        self.pending_screencopy = Some(screencopy);
+        // A plain copy wants a frame now; `copy_with_damage` waits for the
+        // output to redraw on its own.
+        let output = (!screencopy.with_damage()).then(|| screencopy.output().clone());
+        self.pending_screencopies.push(screencopy);
+        if let Some(output) = output {
+            crate::backend::udev::queue_redraw_for_output(
+                self,
+                &output,
+                crate::backend::udev::RedrawCause::Ipc,
+            );
+        }
     }
 }
 
 delegate_screencopy!(Raven);
 
+impl ImageCopyCaptureHandler for Raven {
+    fn image_copy_capture_state(&mut self) -> &mut ImageCopyCaptureState {
+        &mut self.image_copy_capture_state
+    }
+
+    fn capture_source_size(&mut self, source: &CaptureSource) -> Option<Size<i32, Physical>> {
+        self.image_capture_source_size(source)
+    }
+
+    fn frame(&mut self, frame: CaptureFrame) {
+        self.serve_capture_frame(frame);
+    }
+}
+
+delegate_image_copy_capture!(Raven);
+
+impl IdleInhibitHandler for Raven {
+    fn idle_inhibit_state(&mut self) -> &mut IdleInhibitManagerState {
+        &mut self.idle_inhibit_state
+    }
+
+    fn inhibitors_changed(&mut self) {
+        self.idle_inhibitors_changed();
+    }
+}
+
+delegate_idle_inhibit!(Raven);
+
+impl IdleNotifierHandler for Raven {
+    fn idle_notifier_state(&mut self) -> &mut IdleNotifierState<Self> {
+        &mut self.idle_notifier_state
+    }
+}
+
+delegate_idle_notify!(Raven);
+
+impl OutputPowerHandler for Raven {
+    fn output_power_state(&mut self) -> &mut OutputPowerManagerState {
+        &mut self.output_power_state
+    }
+
+    fn output_powered(&self, output: &Output) -> bool {
+        crate::backend::udev::output_powered(self, output)
+    }
+
+    fn set_output_power(&mut self, output: &Output, on: bool) -> bool {
+        crate::backend::udev::set_output_power(self, output, on)
+            .map_err(|err| tracing::warn!("failed to set output power: {err}"))
+            .is_ok()
+    }
+}
+
+delegate_output_power!(Raven);
+
+impl GammaControlHandler for Raven {
+    fn gamma_control_state(&mut self) -> &mut GammaControlManagerState {
+        &mut self.gamma_control_state
+    }
+
+    fn gamma_size(&self, output: &Output) -> Option<u32> {
+        crate::backend::udev::gamma_size(self, output)
+    }
+
+    fn set_gamma(&mut self, output: &Output, ramp: Option<Vec<u16>>) -> bool {
+        crate::backend::udev::set_gamma(self, output, ramp)
+            .map_err(|err| tracing::warn!("failed to set gamma: {err}"))
+            .is_ok()
+    }
+}
+
+delegate_gamma_control!(Raven);
+
+impl TearingControlHandler for Raven {
+    fn tearing_control_state(&mut self) -> &mut TearingControlManagerState {
+        &mut self.tearing_control_state
+    }
+}
+
+delegate_tearing_control!(Raven);
+
 impl ExtWorkspaceHandler for Raven {
     fn ext_workspace_manager_state(&mut self) -> &mut ExtWorkspaceManagerState {
         &mut self.ext_workspace_manager_state
@@ -177,6 +320,10 @@ impl ExtWorkspaceHandler for Raven {
             tracing::warn!("failed to activate ext-workspace index {workspace_index}: {err}");
         }
     }
+
+    fn refresh_workspaces(&mut self) {
+        self.refresh_ext_workspace();
+    }
 }
 
 delegate_ext_workspace!(Raven);
@@ -200,15 +347,16 @@ impl ForeignToplevelHandler for Raven {
             return;
         }
 
+        self.unminimize_window(&window);
         self.raise_window_preserving_layer(&window);
         self.set_keyboard_focus(Some(wl_surface), SERIAL_COUNTER.next_serial());
     }
 
     fn close(&mut self, wl_surface: WlSurface) {
         if let Some(window) = self.window_for_surface(&wl_surface)
-            && let Some(toplevel) = window.toplevel()
+            && let Err(err) = self.close_window(&window, false)
         {
-            toplevel.send_close();
+            tracing::warn!("failed to close foreign toplevel: {err}");
         }
     }
 
@@ -230,12 +378,7 @@ impl ForeignToplevelHandler for Raven {
         }
         self.clear_pending_unmapped_fullscreen_for_surface(&wl_surface);
 
-        if let Some(target_workspace) =
-            (0..self.workspaces.len()).find(|index| self.workspace_contains_window(*index, &window))
-            && target_workspace != self.current_workspace
-            && let Err(err) = self.switch_workspace(target_workspace)
-        {
-            tracing::warn!("failed to switch workspace for foreign toplevel fullscreen: {err}");
+        if !self.reveal_workspace_for_fullscreen_request(&window) {
             return;
         }
 
@@ -298,10 +441,34 @@ impl ForeignToplevelHandler for Raven {
             tracing::warn!("failed to apply layout after foreign toplevel unmaximize: {err}");
         }
     }
+
+    fn set_minimized(&mut self, wl_surface: WlSurface) {
+        if let Some(window) = self.window_for_surface(&wl_surface) {
+            self.minimize_window(&window);
+        }
+    }
+
+    fn unset_minimized(&mut self, wl_surface: WlSurface) {
+        if let Some(window) = self.window_for_surface(&wl_surface) {
+            self.unminimize_window(&window);
+        }
+    }
+
+    fn refresh_toplevels(&mut self) {
+        self.refresh_foreign_toplevel();
+    }
 }
 
 delegate_foreign_toplevel!(Raven);
 
+impl ForeignToplevelListHandler for Raven {
+    fn foreign_toplevel_list_state(&mut self) -> &mut ForeignToplevelListState {
+        &mut self.foreign_toplevel_list_state
+    }
+}
+
+delegate_foreign_toplevel_list!(Raven);
+
 impl DmabufHandler for Raven {
     fn dmabuf_state(&mut self) -> &mut DmabufState {
         self.dmabuf_state
@@ -315,6 +482,10 @@ impl DmabufHandler for Raven {
         dmabuf: smithay::backend::allocator::dmabuf::Dmabuf,
         notifier: ImportNotifier,
     ) {
+        if !self.buffer_import_allowed(&dmabuf) {
+            notifier.failed();
+            return;
+        }
         if let Some(ref mut udev_data) = self.udev_data {
             if udev_data
                 .gpus
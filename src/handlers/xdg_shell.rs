@@ -1,5 +1,5 @@
 use smithay::{
-    delegate_kde_decoration, delegate_xdg_decoration, delegate_xdg_shell,
+    delegate_kde_decoration, delegate_xdg_decoration, delegate_xdg_dialog, delegate_xdg_shell,
     desktop::{
         PopupKind, PopupManager, Space, Window, find_popup_root_surface, get_popup_toplevel_coords,
     },
@@ -26,7 +26,7 @@ use smithay::{
             kde::decoration::{KdeDecorationHandler, KdeDecorationState},
             xdg::{
                 PopupSurface, PositionerState, ToplevelSurface, XdgShellHandler, XdgShellState,
-                XdgToplevelSurfaceData, decoration::XdgDecorationHandler,
+                XdgToplevelSurfaceData, decoration::XdgDecorationHandler, dialog::XdgDialogHandler,
             },
         },
     },
@@ -34,6 +34,7 @@ use smithay::{
 
 use crate::{
     Raven,
+    backend::udev::RedrawCause,
     grabs::{move_grab::MoveGrab, resize_grab::ResizeSurfaceGrab},
 };
 
@@ -52,6 +53,8 @@ impl XdgShellHandler for Raven {
             return;
         }
         let window = Window::new_wayland_window(surface.clone());
+        self.assign_window_id(surface.wl_surface());
+        self.claim_autostart_workspace(surface.wl_surface());
         let rules = self.resolve_window_rules_for_surface(surface.wl_surface());
         let (effective_floating, _, _, _) = self.resolve_effective_floating_for_surface(
             surface.wl_surface(),
@@ -97,7 +100,7 @@ impl XdgShellHandler for Raven {
             tracing::debug!("new_toplevel: step=apply_layout:start");
             self.apply_layout().ok();
             tracing::debug!("new_toplevel: step=apply_layout:done");
-            if visible_on_current_workspace && rules.focus {
+            if visible_on_current_workspace && rules.focus && self.may_focus_new_window(&window) {
                 tracing::debug!("new_toplevel: step=set_keyboard_focus:start");
                 self.set_keyboard_focus(
                     Some(surface.wl_surface().clone()),
@@ -256,6 +259,12 @@ impl XdgShellHandler for Raven {
         }
         self.clear_pending_unmapped_fullscreen_for_surface(surface.wl_surface());
 
+        // An explicit output means the client wants the window visible there now; bring its
+        // workspace forward rather than fullscreening it while hidden.
+        if requested_output.is_some() && !self.reveal_workspace_for_fullscreen_request(&window) {
+            return;
+        }
+
         if self.request_fullscreen_enter_on_output(&window, requested_output) {
             self.space.raise_element(&window, true);
             if let Err(err) = self.apply_layout() {
@@ -293,6 +302,11 @@ impl XdgShellHandler for Raven {
         self.clear_pending_unmapped_state_for_surface(wl_surface);
         self.clear_window_rule_recheck_for_surface(wl_surface);
         self.clear_floating_recenter_for_surface(wl_surface);
+        self.clear_surface_modal(wl_surface);
+        self.clear_autostart_workspace(wl_surface);
+        self.release_window_id(wl_surface);
+        self.forget_window_output(wl_surface);
+        self.note_sway_ipc_window_change();
 
         let Some(window) = window else {
             self.debug_assert_state_invariants("xdg_toplevel_destroyed_no_window");
@@ -312,7 +326,7 @@ impl XdgShellHandler for Raven {
                     );
                 }
                 self.refocus_visible_window();
-                crate::backend::udev::queue_redraw_all(self);
+                crate::backend::udev::queue_redraw_all(self, RedrawCause::WindowClosed);
             }
             self.debug_assert_state_invariants("xdg_toplevel_destroyed_unmapped");
             return;
@@ -325,8 +339,9 @@ impl XdgShellHandler for Raven {
         if let Err(err) = self.apply_layout() {
             tracing::warn!("failed to apply layout after xdg toplevel destroy: {err}");
         }
+        self.handle_last_window_closed();
         self.refocus_visible_window();
-        self.queue_redraw_for_outputs_or_all(outputs);
+        self.queue_redraw_for_outputs_or_all(outputs, RedrawCause::WindowClosed);
         self.debug_assert_state_invariants("xdg_toplevel_destroyed_mapped");
     }
 }
@@ -420,6 +435,16 @@ impl KdeDecorationHandler for Raven {
 
 delegate_kde_decoration!(Raven);
 
+impl XdgDialogHandler for Raven {
+    fn modal_changed(&mut self, toplevel: ToplevelSurface, is_modal: bool) {
+        let surface = toplevel.wl_surface();
+        tracing::debug!(surface = ?surface.id(), is_modal, "xdg_dialog: modal hint changed");
+        self.set_surface_modal(surface, is_modal);
+    }
+}
+
+delegate_xdg_dialog!(Raven);
+
 fn set_tiled_state(state: &mut smithay::wayland::shell::xdg::ToplevelState, tiled: bool) {
     if tiled {
         state.states.set(xdg_toplevel::State::TiledLeft);
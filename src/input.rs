@@ -1,6 +1,6 @@
 use crate::{
     action::Action,
-    config::KeybindAction,
+    backend::udev::RedrawCause,
     grabs::{
         move_grab::MoveGrab,
         resize_grab::{ResizeEdge, ResizeSurfaceGrab},
@@ -9,23 +9,23 @@ use crate::{
 };
 use smithay::{
     backend::input::{
-        AbsolutePositionEvent, Axis, AxisSource, ButtonState, Event, InputBackend, InputEvent,
-        KeyState, KeyboardKeyEvent, MouseButton, PointerAxisEvent, PointerButtonEvent,
-        PointerMotionEvent,
+        AbsolutePositionEvent, Axis, AxisSource, ButtonState, Device, DeviceCapability, Event,
+        InputBackend, InputEvent, KeyState, KeyboardKeyEvent, MouseButton, PointerAxisEvent,
+        PointerButtonEvent, PointerMotionEvent, TouchEvent,
     },
-    desktop::{Window, WindowSurfaceType, layer_map_for_output},
+    desktop::{Window, WindowSurfaceType, layer_map_for_output, space::SpaceElement},
     input::{
-        keyboard::{FilterResult, Keysym, ModifiersState},
+        keyboard::{FilterResult, Keycode, Keysym, ModifiersState},
         pointer::{
-            AxisFrame, ButtonEvent, Focus, GrabStartData as PointerGrabStartData, MotionEvent,
-            PointerHandle, RelativeMotionEvent,
+            AxisFrame, ButtonEvent, CursorImageStatus, Focus,
+            GrabStartData as PointerGrabStartData, MotionEvent, PointerHandle, RelativeMotionEvent,
         },
     },
     reexports::{
         wayland_protocols::xdg::shell::server::xdg_toplevel,
         wayland_server::protocol::wl_surface::WlSurface,
     },
-    utils::{Logical, Point, Rectangle, SERIAL_COUNTER, Serial},
+    utils::{IsAlive, Logical, Point, Rectangle, SERIAL_COUNTER, Serial},
     wayland::{
         input_method::InputMethodSeat,
         pointer_constraints::{PointerConstraint, with_pointer_constraint},
@@ -54,10 +54,27 @@ impl Raven {
             })
     }
 
+    /// The window the pointer is over, unless a layer surface or popup covers it
+    /// there, with its location.
     pub fn window_under_pointer(&self) -> Option<(Window, Point<i32, Logical>)> {
-        self.space
-            .element_under(self.pointer_location)
-            .map(|(window, point)| (window.clone(), point))
+        let window = self.contents_under(self.pointer_location).window?;
+        let location = self.space.element_location(&window)?;
+        Some((window, location))
+    }
+
+    /// Whether Main+drag may move or resize `window`. Fullscreen and maximized
+    /// windows stay put; a tiled window is floated first, where it is.
+    fn prepare_window_for_pointer_grab(&mut self, window: &Window) -> bool {
+        if self.window_has_exclusive_layout_state(window) {
+            return false;
+        }
+        if !self.is_window_floating(window) {
+            self.set_window_floating(window, true);
+            if let Err(err) = self.apply_layout() {
+                tracing::warn!("failed to apply layout after floating a dragged window: {err}");
+            }
+        }
+        true
     }
 
     pub fn contents_under(&self, position: Point<f64, Logical>) -> PointContents {
@@ -98,20 +115,35 @@ impl Raven {
             })
         };
 
-        let window_under = || -> Option<PointContents> {
+        let element_under = || {
+            if !self.layout().scrolls() {
+                return self.space.element_under(position);
+            }
+            // Skip columns of a neighbouring output's strip that overhang this one.
             self.space
-                .element_under(position)
-                .and_then(|(window, render_location)| {
+                .elements()
+                .rev()
+                .filter(|window| !self.window_scrolled_out(window, output))
+                .find_map(|window| {
+                    let render_location =
+                        self.space.element_location(window)? - window.geometry().loc;
                     window
-                        .surface_under(position - render_location.to_f64(), WindowSurfaceType::ALL)
-                        .map(|(surface, local_pos)| PointContents {
-                            output: Some(output.clone()),
-                            surface: Some((surface, (local_pos + render_location).to_f64())),
-                            window: Some(window.clone()),
-                            layer: None,
-                        })
+                        .is_in_input_region(&(position - render_location.to_f64()))
+                        .then_some((window, render_location))
                 })
         };
+        let window_under = || -> Option<PointContents> {
+            element_under().and_then(|(window, render_location)| {
+                window
+                    .surface_under(position - render_location.to_f64(), WindowSurfaceType::ALL)
+                    .map(|(surface, local_pos)| PointContents {
+                        output: Some(output.clone()),
+                        surface: Some((surface, (local_pos + render_location).to_f64())),
+                        window: Some(window.clone()),
+                        layer: None,
+                    })
+            })
+        };
 
         if fullscreen_on_output {
             layer_surface_under(WlrLayer::Overlay, true)
@@ -184,6 +216,55 @@ impl Raven {
         true
     }
 
+    /// Put the pointer back where it was when the session was paused. Outputs can
+    /// go away while we are switched out, so a location no output covers falls
+    /// back to the center of the first one. Motion is sent even when the contents
+    /// under the pointer are unchanged, so the focused client sees the position
+    /// again and a lock/confine constraint on it can re-activate.
+    pub fn restore_pointer(
+        &mut self,
+        location: Point<f64, Logical>,
+        cursor_status: CursorImageStatus,
+    ) {
+        let location = if self.space.output_under(location).next().is_some() {
+            location
+        } else {
+            self.space
+                .outputs()
+                .next()
+                .and_then(|output| self.space.output_geometry(output))
+                .map_or(location, |geometry| {
+                    Point::from((
+                        geometry.loc.x as f64 + geometry.size.w as f64 / 2.0,
+                        geometry.loc.y as f64 + geometry.size.h as f64 / 2.0,
+                    ))
+                })
+        };
+        self.cursor_status = match cursor_status {
+            CursorImageStatus::Surface(surface) if !surface.alive() => {
+                CursorImageStatus::default_named()
+            }
+            status => status,
+        };
+
+        self.pointer_location = location;
+        let under = self.contents_under(location);
+        self.pointer_contents.clone_from(&under);
+        let pointer = self.pointer();
+        pointer.motion(
+            self,
+            under.surface,
+            &MotionEvent {
+                location,
+                serial: SERIAL_COUNTER.next_serial(),
+                time: self.start_time.elapsed().as_millis() as u32,
+            },
+        );
+        pointer.frame(self);
+        self.maybe_activate_pointer_constraint();
+        self.queue_redraw_for_pointer_output();
+    }
+
     pub fn queue_redraw_for_pointer_output(&mut self) {
         let output = self.pointer_contents.output.clone().or_else(|| {
             self.space
@@ -193,9 +274,9 @@ impl Raven {
         });
 
         if let Some(output) = output {
-            crate::backend::udev::queue_redraw_for_output(self, &output);
+            crate::backend::udev::queue_redraw_for_output(self, &output, RedrawCause::Pointer);
         } else {
-            crate::backend::udev::queue_redraw_all(self);
+            crate::backend::udev::queue_redraw_all(self, RedrawCause::Pointer);
         }
     }
 
@@ -277,6 +358,30 @@ impl Raven {
         if let Some(keyboard) = self.seat.get_keyboard() {
             keyboard.set_focus(self, target, serial);
         }
+        if let Some(surface) = focused_window
+            .as_ref()
+            .and_then(|window| window.toplevel())
+            .map(|toplevel| toplevel.wl_surface().clone())
+        {
+            self.record_window_focus(&surface);
+        }
+        // Scroll the column strip so the newly focused window is in view.
+        if self.layout().scrolls()
+            && focused_window
+                .as_ref()
+                .is_some_and(|window| !self.is_window_floating(window))
+            && let Err(err) = self.apply_layout()
+        {
+            tracing::warn!("failed to scroll to focused window: {err}");
+        }
+        // Dim veils, faded windows and border colors follow focus even when the
+        // clients do not redraw on activation.
+        if self.config.dim_unfocused > 0.0
+            || self.config.dim_inactive > 0.0
+            || self.config.border_size > 0
+        {
+            crate::backend::udev::queue_redraw_all(self, RedrawCause::Keyboard);
+        }
     }
 
     pub fn refocus_visible_window(&mut self) {
@@ -323,7 +428,44 @@ impl Raven {
         }
     }
 
+    /// Hide the cursor while a touchscreen (or a device configured with
+    /// `hide_cursor`) drives input, and bring it back on the next regular pointer event.
+    fn update_cursor_visibility_for_device<D: Device>(&mut self, device: &D) {
+        let hidden = self.config.input.hides_cursor(
+            &device.name(),
+            device.has_capability(DeviceCapability::Touch),
+        );
+        if hidden == self.cursor_hidden_by_device {
+            return;
+        }
+        self.cursor_hidden_by_device = hidden;
+        self.queue_redraw_for_pointer_output();
+    }
+
     pub fn handle_input_event<B: InputBackend>(&mut self, event: InputEvent<B>) {
+        if !matches!(
+            event,
+            InputEvent::DeviceAdded { .. } | InputEvent::DeviceRemoved { .. }
+        ) {
+            self.notify_activity();
+        }
+
+        let pointer_device = match &event {
+            InputEvent::PointerMotion { event } => Some(event.device()),
+            InputEvent::PointerMotionAbsolute { event } => Some(event.device()),
+            InputEvent::PointerButton { event } => Some(event.device()),
+            InputEvent::PointerAxis { event } => Some(event.device()),
+            InputEvent::TouchDown { event } => Some(event.device()),
+            InputEvent::TouchMotion { event } => Some(event.device()),
+            InputEvent::TabletToolAxis { event } => Some(event.device()),
+            InputEvent::TabletToolProximity { event } => Some(event.device()),
+            InputEvent::TabletToolTip { event } => Some(event.device()),
+            _ => None,
+        };
+        if let Some(device) = pointer_device {
+            self.update_cursor_visibility_for_device(&device);
+        }
+
         match event {
             InputEvent::Keyboard { event } => self.handle_keyboard_event::<B>(event),
             InputEvent::PointerMotion { event } => self.handle_pointer_motion::<B>(event),
@@ -332,16 +474,44 @@ impl Raven {
             }
             InputEvent::PointerButton { event } => self.handle_pointer_button::<B>(event),
             InputEvent::PointerAxis { event } => self.handle_pointer_axis::<B>(event),
+            InputEvent::TouchDown { event } => {
+                if let Some(location) = self.touch_only_location::<B>(&event) {
+                    self.touch_hold_down(event.slot(), location);
+                }
+            }
+            InputEvent::TouchMotion { event } => {
+                if let Some(location) = self.touch_only_location::<B>(&event) {
+                    self.touch_hold_motion(event.slot(), location);
+                }
+            }
+            InputEvent::TouchUp { event } => self.touch_hold_up(event.slot()),
+            InputEvent::TouchCancel { .. } => self.touch_hold_cancel(),
             _ => {}
         }
     }
 
+    /// Where a touch lands in the global space, mapped onto the first output.
+    /// `None` for devices that also drive a pointer; their touches are not holds.
+    fn touch_only_location<B: InputBackend>(
+        &self,
+        event: &(impl AbsolutePositionEvent<B> + Event<B>),
+    ) -> Option<Point<f64, Logical>> {
+        if event.device().has_capability(DeviceCapability::Pointer) {
+            return None;
+        }
+        let output = self.space.outputs().next()?;
+        let output_geo = self.space.output_geometry(output)?;
+        Some(event.position_transformed(output_geo.size) + output_geo.loc.to_f64())
+    }
+
     fn handle_keyboard_event<B: InputBackend>(&mut self, event: B::KeyboardKeyEvent) {
-        let serial = SERIAL_COUNTER.next_serial();
-        let time_msec = Event::time_msec(&event);
-        let key_code = event.key_code();
-        let key_state = event.state();
+        self.accessx_key(event.key_code(), event.state(), Event::time_msec(&event));
+    }
 
+    /// Hand a key to the exclusive layer surface, the keybinds or the focused client,
+    /// once the accessibility filters have let it through.
+    pub(crate) fn deliver_key(&mut self, key_code: Keycode, key_state: KeyState, time_msec: u32) {
+        let serial = SERIAL_COUNTER.next_serial();
         let keyboard = self.seat.get_keyboard().expect("keyboard not initialized");
 
         let output = self.space.outputs().next().cloned();
@@ -367,7 +537,7 @@ impl Raven {
                 keyboard.input::<(), _>(self, key_code, key_state, serial, time_msec, |_, _, _| {
                     FilterResult::Forward
                 });
-                crate::backend::udev::queue_redraw_all(self);
+                crate::backend::udev::queue_redraw_all(self, RedrawCause::Keyboard);
                 return;
             }
         }
@@ -381,6 +551,11 @@ impl Raven {
             |state, modifiers, keysym_handle| {
                 if key_state == KeyState::Pressed {
                     let keysym = keysym_handle.modified_sym();
+                    if keysym == Keysym::Escape && state.region_selection_active() {
+                        // Ending the grab cancels the selection.
+                        state.pointer().unset_grab(state, serial, time_msec);
+                        return FilterResult::Intercept(());
+                    }
                     if handle_keybinding(state, modifiers, keysym) {
                         return FilterResult::Intercept(());
                     }
@@ -388,8 +563,11 @@ impl Raven {
                 FilterResult::Forward
             },
         );
+        if key_state == KeyState::Released {
+            self.window_switcher_key_released();
+        }
 
-        self.queue_pointer_redraw_throttled(event.time_msec());
+        self.queue_pointer_redraw_throttled(time_msec);
     }
 
     fn handle_pointer_motion<B: InputBackend>(&mut self, event: B::PointerMotionEvent) {
@@ -515,6 +693,7 @@ impl Raven {
         if self.config.focus_follow_mouse {
             self.update_keyboard_focus(self.pointer_location, serial, false);
         }
+        self.update_dnd_edge_switch();
 
         self.queue_pointer_redraw_throttled(event.time_msec());
     }
@@ -559,6 +738,7 @@ impl Raven {
         if self.config.focus_follow_mouse {
             self.update_keyboard_focus(self.pointer_location, serial, false);
         }
+        self.update_dnd_edge_switch();
 
         self.queue_redraw_for_pointer_output();
     }
@@ -572,6 +752,13 @@ impl Raven {
 
         self.update_pointer_contents(event.time_msec());
 
+        if ButtonState::Pressed == button_state
+            && !pointer.is_grabbed()
+            && self.dismiss_config_error_at(self.pointer_location)
+        {
+            return;
+        }
+
         let keyboard = self.seat.get_keyboard().expect("keyboard not initialized");
         let modifiers = keyboard.modifier_state();
         let main_key_held = self.config.main_key.matches(&modifiers);
@@ -580,8 +767,9 @@ impl Raven {
         if ButtonState::Pressed == button_state
             && button == Some(MouseButton::Left)
             && main_key_held
-            && let Some((window, _)) = self.window_under_pointer()
             && !pointer.is_grabbed()
+            && let Some((window, _)) = self.window_under_pointer()
+            && self.prepare_window_for_pointer_grab(&window)
         {
             let location = self.pointer_location;
 
@@ -604,8 +792,9 @@ impl Raven {
         if ButtonState::Pressed == button_state
             && button == Some(MouseButton::Right)
             && resize_modifier_held
-            && let Some((window, window_location)) = self.window_under_pointer()
             && !pointer.is_grabbed()
+            && let Some((window, window_location)) = self.window_under_pointer()
+            && self.prepare_window_for_pointer_grab(&window)
         {
             let location = self.pointer_location;
             let window_size = window.geometry().size;
@@ -710,6 +899,12 @@ impl Raven {
                 .element_under(location)
                 .map(|(w, p)| (w.clone(), p))
             {
+                if !raise
+                    && let Some(toplevel) = window.toplevel()
+                    && self.surface_ignores_focus_follows_mouse(toplevel.wl_surface())
+                {
+                    return;
+                }
                 tracing::trace!("Setting focus of surface under pointer");
                 if raise {
                     self.raise_window_preserving_layer(&window);
@@ -814,96 +1009,43 @@ impl Raven {
 }
 
 fn handle_keybinding(state: &mut Raven, modifiers: &ModifiersState, keysym: Keysym) -> bool {
+    let exclusive = state.fullscreen_input_is_exclusive();
     if let Some(action) = state.config.keybind_action_for(modifiers, keysym) {
-        execute_keybind_action(state, action);
+        let allowed = !exclusive
+            || state
+                .config
+                .fullscreen_exclusive_allow
+                .iter()
+                .any(|name| name == action.name());
+        if !allowed {
+            tracing::trace!(
+                action = action.name(),
+                "keybind passed to fullscreen window"
+            );
+            return false;
+        }
+        crate::action::dispatch(state, action);
         return true;
     }
 
     let main_key_held = state.config.main_key.matches(modifiers);
-    if !main_key_held {
+    if !main_key_held || exclusive {
         return false;
     }
 
     if let Some(workspace_index) = workspace_from_keysym(keysym) {
-        if modifiers.shift {
-            state
-                .move_focused_window_to_workspace(workspace_index)
-                .map_err(|err| tracing::warn!("failed to move window to workspace: {err}"))
-                .ok();
+        let action = if modifiers.shift {
+            Action::MoveFocusedToWorkspace(workspace_index)
         } else {
-            state
-                .switch_workspace(workspace_index)
-                .map_err(|err| tracing::warn!("failed to switch workspace: {err}"))
-                .ok();
-        }
+            Action::SwitchWorkspace(workspace_index)
+        };
+        crate::action::dispatch(state, action);
         return true;
     }
 
     false
 }
 
-fn execute_keybind_action(state: &mut Raven, action: KeybindAction) {
-    match action {
-        KeybindAction::Exec(command) => state.spawn_command(&command),
-        KeybindAction::Terminal => state.spawn_terminal(),
-        KeybindAction::Launcher => state.spawn_launcher(),
-        KeybindAction::CloseFocused => close_focused_window(state),
-        KeybindAction::ToggleFullscreen => {
-            state
-                .toggle_fullscreen_focused_window()
-                .map_err(|err| tracing::warn!("failed to toggle fullscreen: {err}"))
-                .ok();
-        }
-        KeybindAction::ToggleFloating => {
-            state
-                .toggle_floating_focused_window()
-                .map_err(|err| tracing::warn!("failed to toggle floating: {err}"))
-                .ok();
-        }
-        KeybindAction::Quit => state.loop_signal.stop(),
-        KeybindAction::FocusNext => Action::FocusNext.execute(state),
-        KeybindAction::FocusPrevious => Action::FocusPrevious.execute(state),
-        KeybindAction::ReloadConfig => {
-            state
-                .reload_config()
-                .map_err(|err| tracing::warn!("failed to reload config: {err}"))
-                .ok();
-        }
-        KeybindAction::SwitchWorkspace(workspace_index) => {
-            state
-                .switch_workspace(workspace_index)
-                .map_err(|err| tracing::warn!("failed to switch workspace: {err}"))
-                .ok();
-        }
-        KeybindAction::MoveFocusedToWorkspace(workspace_index) => {
-            state
-                .move_focused_window_to_workspace(workspace_index)
-                .map_err(|err| tracing::warn!("failed to move window to workspace: {err}"))
-                .ok();
-        }
-        KeybindAction::Unsupported(name) => {
-            tracing::warn!("action `{name}` is not implemented yet");
-        }
-    }
-}
-
-fn close_focused_window(state: &mut Raven) {
-    let keyboard = state.seat.get_keyboard().unwrap();
-    if let Some(focused_surface) = keyboard.current_focus()
-        && let Some(window) = state.window_for_surface(&focused_surface)
-    {
-        tracing::info!("Closing focused window");
-        if let Some(toplevel) = window.toplevel() {
-            toplevel.send_close();
-        } else {
-            #[cfg(feature = "xwayland")]
-            if let Some(x11) = window.x11_surface() {
-                let _ = x11.close();
-            }
-        }
-    }
-}
-
 fn workspace_from_keysym(keysym: Keysym) -> Option<usize> {
     match keysym {
         Keysym::_1 | Keysym::exclam => Some(0),
@@ -928,6 +1070,24 @@ fn resize_edges_from_local_point(
     let width = width.max(1) as f64;
     let height = height.max(1) as f64;
 
+    // The outer thirds of each axis pick that side, so a grab near the middle of
+    // an edge resizes along one axis only; the center falls back to the nearest
+    // corner.
+    let side = |position: f64, length: f64, low: ResizeEdge, high: ResizeEdge| {
+        if position < length / 3.0 {
+            low
+        } else if position > length * 2.0 / 3.0 {
+            high
+        } else {
+            ResizeEdge::empty()
+        }
+    };
+    let edges = side(local.x, width, ResizeEdge::LEFT, ResizeEdge::RIGHT)
+        | side(local.y, height, ResizeEdge::TOP, ResizeEdge::BOTTOM);
+    if !edges.is_empty() {
+        return edges;
+    }
+
     let horizontal = if local.x < width / 2.0 {
         ResizeEdge::LEFT
     } else {
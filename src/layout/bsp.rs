@@ -0,0 +1,128 @@
+use std::collections::HashMap;
+
+use smithay::desktop::Window;
+use smithay::reexports::wayland_server::{Resource, protocol::wl_surface::WlSurface};
+
+use super::{GapConfig, Layout, WindowGeometry};
+
+const DEFAULT_RATIO: f32 = 0.5;
+const MIN_RATIO: f32 = 0.1;
+const MAX_RATIO: f32 = 0.9;
+
+/// Binary space partitioning: every window but the last splits the space left
+/// over by the ones before it, along its longer side, and hands the rest on.
+///
+/// Each split belongs to the window that takes its first half and keeps its
+/// ratio while that window lives.
+#[derive(Default)]
+pub struct Bsp {
+    ratios: HashMap<WlSurface, f32>,
+}
+
+fn surface(window: &Window) -> Option<&WlSurface> {
+    window.toplevel().map(|toplevel| toplevel.wl_surface())
+}
+
+impl Bsp {
+    fn ratio(&self, window: &Window) -> f32 {
+        surface(window)
+            .and_then(|surface| self.ratios.get(surface))
+            .copied()
+            .unwrap_or(DEFAULT_RATIO)
+    }
+}
+
+impl Layout for Bsp {
+    fn arrange(
+        &self,
+        windows: &[Window],
+        screen_width: u32,
+        screen_height: u32,
+        gaps: &GapConfig,
+        _master_factor: f32,
+        _num_master: i32,
+        smartgaps_enabled: bool,
+    ) -> Vec<WindowGeometry> {
+        let window_count = windows.len();
+        if window_count == 0 {
+            return Vec::new();
+        }
+
+        let outer_enabled = !(smartgaps_enabled && window_count == 1);
+        let (outer_horizontal, outer_vertical) = if outer_enabled {
+            (gaps.outer_horizontal as i32, gaps.outer_vertical as i32)
+        } else {
+            (0, 0)
+        };
+        let inner_horizontal = gaps.inner_horizontal as i32;
+        let inner_vertical = gaps.inner_vertical as i32;
+
+        let mut x = outer_vertical;
+        let mut y = outer_horizontal;
+        let mut width = (screen_width as i32 - 2 * outer_vertical).max(1);
+        let mut height = (screen_height as i32 - 2 * outer_horizontal).max(1);
+
+        let mut geometries = Vec::with_capacity(window_count);
+        for (index, window) in windows.iter().enumerate() {
+            if index + 1 == window_count {
+                geometries.push(WindowGeometry {
+                    x_coordinate: x,
+                    y_coordinate: y,
+                    width: width as u32,
+                    height: height as u32,
+                });
+                break;
+            }
+
+            let ratio = self.ratio(window);
+            if width >= height {
+                let first = (((width - inner_vertical) as f32 * ratio) as i32).max(1);
+                geometries.push(WindowGeometry {
+                    x_coordinate: x,
+                    y_coordinate: y,
+                    width: first as u32,
+                    height: height as u32,
+                });
+                x += first + inner_vertical;
+                width = (width - first - inner_vertical).max(1);
+            } else {
+                let first = (((height - inner_horizontal) as f32 * ratio) as i32).max(1);
+                geometries.push(WindowGeometry {
+                    x_coordinate: x,
+                    y_coordinate: y,
+                    width: width as u32,
+                    height: first as u32,
+                });
+                y += first + inner_horizontal;
+                height = (height - first - inner_horizontal).max(1);
+            }
+        }
+
+        geometries
+    }
+
+    fn adjust_split(&mut self, windows: &[Window], index: usize, delta: f32) -> bool {
+        if windows.len() < 2 || index >= windows.len() {
+            return false;
+        }
+        self.ratios.retain(|surface, _| surface.is_alive());
+
+        // The last window has no split of its own; it takes the second half of
+        // the one before it.
+        let (owner, delta) = if index + 1 == windows.len() {
+            (&windows[index - 1], -delta)
+        } else {
+            (&windows[index], delta)
+        };
+        let Some(surface) = surface(owner) else {
+            return false;
+        };
+        let current = self.ratio(owner);
+        let ratio = (current + delta).clamp(MIN_RATIO, MAX_RATIO);
+        if ratio == current {
+            return false;
+        }
+        self.ratios.insert(surface.clone(), ratio);
+        true
+    }
+}
@@ -1,4 +1,8 @@
+pub mod bsp;
+pub mod monocle;
+pub mod scrolling;
 pub mod tiling;
+pub mod weights;
 
 use smithay::desktop::Window;
 
@@ -11,22 +15,78 @@ pub struct GapConfig {
     pub outer_vertical: u32,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
 pub enum LayoutType {
+    #[default]
     Tiling,
+    Bsp,
+    Scrolling,
+    Monocle,
 }
 
 impl LayoutType {
+    pub const ALL: [LayoutType; 4] = [
+        LayoutType::Tiling,
+        LayoutType::Bsp,
+        LayoutType::Scrolling,
+        LayoutType::Monocle,
+    ];
+
     pub fn new(&self) -> LayoutBox {
         match self {
-            LayoutType::Tiling => Box::new(tiling::Tiling),
+            LayoutType::Tiling => Box::new(tiling::Tiling::default()),
+            LayoutType::Bsp => Box::new(bsp::Bsp::default()),
+            LayoutType::Scrolling => Box::new(scrolling::Scrolling::default()),
+            LayoutType::Monocle => Box::new(monocle::Monocle),
         }
     }
     pub fn from_str(s: &str) -> Result<Self, String> {
         match s.to_lowercase().as_str() {
             "tiling" => Ok(Self::Tiling),
+            "bsp" => Ok(Self::Bsp),
+            "scrolling" => Ok(Self::Scrolling),
+            "monocle" => Ok(Self::Monocle),
             _ => Err(format!("Invalid Layout Type: {}", s)),
         }
     }
+    pub fn name(&self) -> &'static str {
+        match self {
+            LayoutType::Tiling => "tiling",
+            LayoutType::Bsp => "bsp",
+            LayoutType::Scrolling => "scrolling",
+            LayoutType::Monocle => "monocle",
+        }
+    }
+    /// The layout after this one, for `cycle_layout`.
+    pub fn next(&self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|layout| layout == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// The layout of one workspace. Each workspace keeps its own, split ratios
+/// included, while others are shown.
+pub struct WorkspaceLayout {
+    pub layout_type: LayoutType,
+    pub layout: LayoutBox,
+}
+
+impl WorkspaceLayout {
+    pub fn new(layout_type: LayoutType) -> Self {
+        Self {
+            layout_type,
+            layout: layout_type.new(),
+        }
+    }
+}
+
+impl Default for WorkspaceLayout {
+    fn default() -> Self {
+        Self::new(LayoutType::default())
+    }
 }
 
 pub trait Layout {
@@ -40,6 +100,31 @@ pub trait Layout {
         num_master: i32,
         smartgaps_enabled: bool,
     ) -> Vec<WindowGeometry>;
+
+    /// Grow the tile of `windows[index]` by `delta` of its split, for layouts
+    /// with adjustable splits. Returns whether anything changed.
+    fn adjust_split(&mut self, _windows: &[Window], _index: usize, _delta: f32) -> bool {
+        false
+    }
+
+    /// Grow the tile of `windows[index]` by `width` and `height` of its size
+    /// weights, for layouts that size tiles per window. Returns whether anything
+    /// changed.
+    fn resize_tile(
+        &mut self,
+        _windows: &[Window],
+        _index: usize,
+        _width: f32,
+        _height: f32,
+    ) -> bool {
+        false
+    }
+
+    /// Whether the arranged geometries may run past the right edge of the screen,
+    /// to be scrolled into view around the focused window.
+    fn scrolls(&self) -> bool {
+        false
+    }
 }
 
 #[derive(Clone)]
@@ -49,3 +134,74 @@ pub struct WindowGeometry {
     pub width: u32,
     pub height: u32,
 }
+
+impl WindowGeometry {
+    fn right(&self) -> i32 {
+        self.x_coordinate + self.width as i32
+    }
+
+    fn bottom(&self) -> i32 {
+        self.y_coordinate + self.height as i32
+    }
+}
+
+/// `geometries[index]` grown over the gaps around it: each side reaches the
+/// nearest tile beside it, or the screen edge when there is none. Tiles past
+/// the screen, as in scrolling layouts, keep their outer sides.
+pub fn without_gaps(
+    geometries: &[WindowGeometry],
+    index: usize,
+    screen_width: u32,
+    screen_height: u32,
+) -> WindowGeometry {
+    let tile = &geometries[index];
+    let others = || {
+        geometries
+            .iter()
+            .enumerate()
+            .filter(move |(other_index, _)| *other_index != index)
+            .map(|(_, other)| other)
+    };
+    let beside_vertically = |other: &&WindowGeometry| {
+        other.y_coordinate < tile.bottom() && tile.y_coordinate < other.bottom()
+    };
+    let beside_horizontally = |other: &&WindowGeometry| {
+        other.x_coordinate < tile.right() && tile.x_coordinate < other.right()
+    };
+
+    let left = others()
+        .filter(beside_vertically)
+        .map(WindowGeometry::right)
+        .filter(|right| *right <= tile.x_coordinate)
+        .max()
+        .unwrap_or(0)
+        .min(tile.x_coordinate);
+    let right = others()
+        .filter(beside_vertically)
+        .map(|other| other.x_coordinate)
+        .filter(|x| *x >= tile.right())
+        .min()
+        .unwrap_or(screen_width as i32)
+        .max(tile.right());
+    let top = others()
+        .filter(beside_horizontally)
+        .map(WindowGeometry::bottom)
+        .filter(|bottom| *bottom <= tile.y_coordinate)
+        .max()
+        .unwrap_or(0)
+        .min(tile.y_coordinate);
+    let bottom = others()
+        .filter(beside_horizontally)
+        .map(|other| other.y_coordinate)
+        .filter(|y| *y >= tile.bottom())
+        .min()
+        .unwrap_or(screen_height as i32)
+        .max(tile.bottom());
+
+    WindowGeometry {
+        x_coordinate: left,
+        y_coordinate: top,
+        width: (right - left) as u32,
+        height: (bottom - top) as u32,
+    }
+}
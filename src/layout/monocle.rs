@@ -0,0 +1,33 @@
+use smithay::desktop::Window;
+
+use super::{GapConfig, Layout, WindowGeometry};
+
+/// Every window fills the screen; the focused one is raised above the rest.
+/// Only one window is ever visible, so smart gaps always drop the outer gaps.
+pub struct Monocle;
+
+impl Layout for Monocle {
+    fn arrange(
+        &self,
+        windows: &[Window],
+        screen_width: u32,
+        screen_height: u32,
+        gaps: &GapConfig,
+        _master_factor: f32,
+        _num_master: i32,
+        smartgaps_enabled: bool,
+    ) -> Vec<WindowGeometry> {
+        let (outer_horizontal, outer_vertical) = if smartgaps_enabled {
+            (0, 0)
+        } else {
+            (gaps.outer_horizontal, gaps.outer_vertical)
+        };
+        let geometry = WindowGeometry {
+            x_coordinate: outer_vertical as i32,
+            y_coordinate: outer_horizontal as i32,
+            width: screen_width.saturating_sub(2 * outer_vertical).max(1),
+            height: screen_height.saturating_sub(2 * outer_horizontal).max(1),
+        };
+        vec![geometry; windows.len()]
+    }
+}
@@ -0,0 +1,71 @@
+use smithay::desktop::Window;
+
+use super::{GapConfig, Layout, WindowGeometry, weights::TileWeights};
+
+/// Columns in a horizontal strip, one window each, in workspace order. The strip
+/// grows past the right edge of the screen; Raven scrolls it so the focused
+/// column stays in view.
+///
+/// `master_factor` is the width of a column as a fraction of the screen, scaled
+/// by the window's width weight. Columns are always full height.
+#[derive(Default)]
+pub struct Scrolling {
+    weights: TileWeights,
+}
+
+impl Layout for Scrolling {
+    fn arrange(
+        &self,
+        windows: &[Window],
+        screen_width: u32,
+        screen_height: u32,
+        gaps: &GapConfig,
+        master_factor: f32,
+        _num_master: i32,
+        smartgaps_enabled: bool,
+    ) -> Vec<WindowGeometry> {
+        let window_count = windows.len();
+        if window_count == 0 {
+            return Vec::new();
+        }
+
+        let outer_enabled = !(smartgaps_enabled && window_count == 1);
+        let (outer_horizontal, outer_vertical) = if outer_enabled {
+            (gaps.outer_horizontal as i32, gaps.outer_vertical as i32)
+        } else {
+            (0, 0)
+        };
+        let inner_vertical = gaps.inner_vertical as i32;
+
+        let usable_width = (screen_width as i32 - 2 * outer_vertical).max(1);
+        let height = (screen_height as i32 - 2 * outer_horizontal).max(1);
+        let column_width = usable_width as f32 * master_factor;
+
+        let mut x = outer_vertical;
+        windows
+            .iter()
+            .map(|window| {
+                let width = ((column_width * self.weights.width(window)) as i32).max(1);
+                let geometry = WindowGeometry {
+                    x_coordinate: x,
+                    y_coordinate: outer_horizontal,
+                    width: width as u32,
+                    height: height as u32,
+                };
+                x += width + inner_vertical;
+                geometry
+            })
+            .collect()
+    }
+
+    fn resize_tile(&mut self, windows: &[Window], index: usize, width: f32, _height: f32) -> bool {
+        width != 0.0
+            && windows
+                .get(index)
+                .is_some_and(|window| self.weights.adjust(window, width, 0.0))
+    }
+
+    fn scrolls(&self) -> bool {
+        true
+    }
+}
@@ -1,6 +1,13 @@
-use super::{GapConfig, Layout, WindowGeometry};
+use super::{GapConfig, Layout, WindowGeometry, weights::TileWeights};
 use smithay::desktop::Window;
-pub struct Tiling;
+
+/// Master and stack columns. A window's height weight sets its share of its
+/// column; the width weights of each column's windows, averaged, scale
+/// `master_factor` between the two.
+#[derive(Default)]
+pub struct Tiling {
+    weights: TileWeights,
+}
 
 struct GapValues {
     outer_horizontal: u32,
@@ -34,29 +41,24 @@ impl Tiling {
     }
 
     fn getfacts(
-        window_count: usize,
+        height_weights: &[f32],
         num_master: i32,
         master_size: i32,
         stack_size: i32,
     ) -> FactValues {
-        let num_master = num_master.max(0) as usize;
-        let master_facts = window_count.min(num_master) as f32;
-        let stack_facts = if window_count > num_master {
-            (window_count - num_master) as f32
-        } else {
-            0.0
-        };
-
-        let mut master_total = 0;
-        let mut stack_total = 0;
-
-        for i in 0..window_count {
-            if i < num_master {
-                master_total += (master_size as f32 / master_facts) as i32;
-            } else if stack_facts > 0.0 {
-                stack_total += (stack_size as f32 / stack_facts) as i32;
-            }
-        }
+        let num_master = (num_master.max(0) as usize).min(height_weights.len());
+        let (master_weights, stack_weights) = height_weights.split_at(num_master);
+        let master_facts: f32 = master_weights.iter().sum();
+        let stack_facts: f32 = stack_weights.iter().sum();
+
+        let master_total: i32 = master_weights
+            .iter()
+            .map(|weight| (master_size as f32 * weight / master_facts) as i32)
+            .sum();
+        let stack_total: i32 = stack_weights
+            .iter()
+            .map(|weight| (stack_size as f32 * weight / stack_facts) as i32)
+            .sum();
 
         FactValues {
             master_facts,
@@ -109,19 +111,32 @@ impl Layout for Tiling {
         let mut master_width = stack_width;
 
         if num_master > 0 && window_count > num_master_usize {
-            stack_width =
-                ((master_width as f32 - inner_gap_vertical as f32) * (1.0 - master_factor)) as i32;
+            let mean_width = |windows: &[Window]| {
+                windows
+                    .iter()
+                    .map(|window| self.weights.width(window))
+                    .sum::<f32>()
+                    / windows.len() as f32
+            };
+            let master_weight = master_factor * mean_width(&windows[..master_count]);
+            let stack_weight = (1.0 - master_factor) * mean_width(&windows[master_count..]);
+            let stack_factor = stack_weight / (master_weight + stack_weight);
+            stack_width = ((master_width as f32 - inner_gap_vertical as f32) * stack_factor) as i32;
             master_width = master_width - inner_gap_vertical as i32 - stack_width;
             stack_x = master_x + master_width + inner_gap_vertical as i32;
         }
 
-        let facts = Self::getfacts(window_count, num_master, master_height, stack_height);
+        let height_weights: Vec<f32> = windows
+            .iter()
+            .map(|window| self.weights.height(window))
+            .collect();
+        let facts = Self::getfacts(&height_weights, num_master, master_height, stack_height);
 
         let mut geometries = Vec::new();
 
-        for (i, _window) in windows.iter().enumerate() {
+        for (i, weight) in height_weights.iter().enumerate() {
             if i < num_master_usize {
-                let window_height = (master_height as f32 / facts.master_facts) as i32
+                let window_height = (master_height as f32 * weight / facts.master_facts) as i32
                     + if (i as i32) < facts.master_remainder {
                         1
                     } else {
@@ -138,7 +153,7 @@ impl Layout for Tiling {
                 master_y += window_height + inner_gap_horizontal as i32;
             } else {
                 let window_height = if facts.stack_facts > 0.0 {
-                    (stack_height as f32 / facts.stack_facts) as i32
+                    (stack_height as f32 * weight / facts.stack_facts) as i32
                         + if ((i - num_master_usize) as i32) < facts.stack_remainder {
                             1
                         } else {
@@ -161,4 +176,10 @@ impl Layout for Tiling {
 
         geometries
     }
+
+    fn resize_tile(&mut self, windows: &[Window], index: usize, width: f32, height: f32) -> bool {
+        windows
+            .get(index)
+            .is_some_and(|window| self.weights.adjust(window, width, height))
+    }
 }
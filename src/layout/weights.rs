@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use smithay::desktop::Window;
+use smithay::reexports::wayland_server::{Resource, protocol::wl_surface::WlSurface};
+
+const DEFAULT_WEIGHT: f32 = 1.0;
+const MIN_WEIGHT: f32 = 0.2;
+const MAX_WEIGHT: f32 = 5.0;
+
+#[derive(Clone, Copy)]
+struct Weight {
+    width: f32,
+    height: f32,
+}
+
+impl Default for Weight {
+    fn default() -> Self {
+        Self {
+            width: DEFAULT_WEIGHT,
+            height: DEFAULT_WEIGHT,
+        }
+    }
+}
+
+/// Per-window size weights set by the `resize_*` actions. A tile's share of the
+/// space it splits with its neighbours is its weight over theirs; windows never
+/// resized weigh 1.
+#[derive(Default)]
+pub struct TileWeights {
+    weights: HashMap<WlSurface, Weight>,
+}
+
+fn surface(window: &Window) -> Option<&WlSurface> {
+    window.toplevel().map(|toplevel| toplevel.wl_surface())
+}
+
+impl TileWeights {
+    fn weight(&self, window: &Window) -> Weight {
+        surface(window)
+            .and_then(|surface| self.weights.get(surface))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    pub fn width(&self, window: &Window) -> f32 {
+        self.weight(window).width
+    }
+
+    pub fn height(&self, window: &Window) -> f32 {
+        self.weight(window).height
+    }
+
+    /// Add `width` and `height` to `window`'s weights. Returns whether either
+    /// changed.
+    pub fn adjust(&mut self, window: &Window, width: f32, height: f32) -> bool {
+        let Some(surface) = surface(window) else {
+            return false;
+        };
+        self.weights.retain(|surface, _| surface.is_alive());
+
+        let current = self.weight(window);
+        let weight = Weight {
+            width: (current.width + width).clamp(MIN_WEIGHT, MAX_WEIGHT),
+            height: (current.height + height).clamp(MIN_WEIGHT, MAX_WEIGHT),
+        };
+        if weight.width == current.width && weight.height == current.height {
+            return false;
+        }
+        self.weights.insert(surface.clone(), weight);
+        true
+    }
+}
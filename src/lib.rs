@@ -1,14 +1,18 @@
 pub mod action;
 pub mod backend;
 pub mod config;
+pub mod config_import;
 pub mod cursor;
 pub mod errors;
+pub mod frame_limit;
 pub mod grabs;
 mod handlers;
 pub mod input;
 pub mod layout;
 pub mod protocols;
 pub mod render_helpers;
+#[cfg(feature = "screencast")]
+pub mod screencast;
 pub mod state;
 pub mod vblank_throttle;
 
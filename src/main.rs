@@ -1,4 +1,8 @@
-use raven::{CompositorError, Result, state::Raven};
+use raven::{
+    CompositorError, Result,
+    config_import::{self, ImportSource},
+    state::Raven,
+};
 use smithay::reexports::{
     calloop::{
         EventLoop,
@@ -9,9 +13,9 @@ use smithay::reexports::{
 use std::{
     backtrace::Backtrace,
     fs,
-    io::{Read, Write},
+    io::{BufRead, BufReader, Read, Write},
     os::unix::net::UnixStream,
-    path::PathBuf,
+    path::{Path, PathBuf},
 };
 use tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -25,13 +29,61 @@ fn main() -> Result<()> {
     }));
 
     let args: Vec<String> = std::env::args().collect();
+    if args.get(1).map(String::as_str) == Some("import-config") {
+        return run_import_config(&args[2..]);
+    }
+    if args.get(1).map(String::as_str) == Some("clients")
+        && args
+            .iter()
+            .skip(2)
+            .any(|arg| arg == "--watch" || arg == "-w")
+    {
+        return run_clients_watch();
+    }
     if let Some(command) = args.get(1).map(String::as_str)
-        && matches!(command, "clients" | "reload" | "monitors")
+        && matches!(
+            command,
+            "reload" | "resync" | "session" | "version" | "actions"
+        )
     {
         let output = run_ipc_command(command)?;
         print!("{output}");
         return Ok(());
     }
+    if let Some(command) = args.get(1).map(String::as_str)
+        && matches!(
+            command,
+            "clients"
+                | "monitors"
+                | "workspaces"
+                | "focused"
+                | "pager"
+                | "stats"
+                | "idle"
+                | "night-light"
+                | "get"
+                | "layout-overlay"
+                | "performance-mode"
+                | "keymap"
+                | "output"
+                | "dpms"
+                | "reserve"
+                | "unreserve"
+                | "workspace"
+                | "workspace-swap"
+                | "workspace-reorder"
+                | "dispatch"
+        )
+    {
+        let output = run_ipc_command(&args[1..].join(" "))?;
+        print!("{output}");
+        return Ok(());
+    }
+    if args.get(1).map(String::as_str) == Some("screenshot") {
+        return screenshot(&args[2..]);
+    }
+
+    let (backend, spawn_cmd) = launch_args(&args)?;
 
     let mut event_loop: EventLoop<Raven> =
         EventLoop::try_new().map_err(|e| CompositorError::EventLoop(e.to_string()))?;
@@ -39,21 +91,13 @@ fn main() -> Result<()> {
     let display = Display::new().map_err(|e| CompositorError::Backend(e.to_string()))?;
     let mut state = Raven::new(display, event_loop.handle(), event_loop.get_signal())?;
 
-    let force_winit = args.iter().any(|a| a == "--winit");
-    let force_drm = args.iter().any(|a| a == "--drm" || a == "--tty");
-
-    if force_winit && force_drm {
-        return Err(CompositorError::Backend(
-            "cannot pass both --winit and --drm/--tty".to_owned(),
-        ));
+    if let Err(err) = start_backend(&mut event_loop, &mut state, backend) {
+        tracing::error!("{err}");
+        eprintln!("raven: {err}");
+        std::process::exit(1);
     }
-
-    if force_winit || (!force_drm && is_nested()) {
-        tracing::info!("Starting with Winit backend");
-        raven::backend::winit::init_winit(&mut event_loop, &mut state)?;
-    } else {
-        tracing::info!("Starting with DRM/KMS backend");
-        raven::backend::udev::init_udev(&mut event_loop, &mut state)?;
+    for line in state.version_report().lines() {
+        tracing::info!("{line}");
     }
 
     event_loop
@@ -69,8 +113,7 @@ fn main() -> Result<()> {
             CompositorError::EventLoop(format!("failed to schedule startup tasks: {err}"))
         })?;
 
-    // Spawn a command if provided (skip --winit flag)
-    let spawn_cmd = args.iter().skip(1).find(|a| !a.starts_with("--"));
+    // Spawn a command if provided
     if let Some(cmd) = spawn_cmd {
         state.spawn_command(cmd);
     }
@@ -88,7 +131,13 @@ fn main() -> Result<()> {
             // stale popup grabs, etc.
             state.space.refresh();
             state.refresh_ext_workspace();
+            state.run_workspace_hooks();
+            state.collect_empty_workspaces();
+            state.broadcast_sway_ipc_events();
+            state.refresh_clients_watchers();
             state.refresh_foreign_toplevel();
+            state.refresh_idle_inhibit();
+            state.refresh_screencasts();
             state.popups.cleanup();
 
             // Keep pointer focus in sync even when client surface trees change without
@@ -109,7 +158,150 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn run_ipc_command(command: &str) -> Result<String> {
+fn run_import_config(args: &[String]) -> Result<()> {
+    const USAGE: &str =
+        "usage: raven import-config <sway|hyprland> <path> [--output <path>|-] [--force]";
+
+    let mut positional = Vec::new();
+    let mut output: Option<&str> = None;
+    let mut force = false;
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        match arg.as_str() {
+            "--force" => force = true,
+            "--output" | "-o" => {
+                output = Some(iter.next().map(String::as_str).ok_or_else(|| {
+                    CompositorError::Backend(format!("--output requires a path\n{USAGE}"))
+                })?);
+            }
+            other => positional.push(other),
+        }
+    }
+
+    let [source, input] = positional.as_slice() else {
+        return Err(CompositorError::Backend(USAGE.to_owned()));
+    };
+    let source = ImportSource::parse(source).ok_or_else(|| {
+        CompositorError::Backend(format!("unknown config source `{source}`\n{USAGE}"))
+    })?;
+
+    let outcome = config_import::import_config(source, &expand_home(input))?;
+
+    if output == Some("-") {
+        print!("{}", outcome.lua);
+        return Ok(());
+    }
+
+    let output_path = match output {
+        Some(path) => expand_home(path),
+        None => raven::config::config_path()?,
+    };
+    if output_path.exists() && !force {
+        return Err(CompositorError::Backend(format!(
+            "refusing to overwrite {}; pass --force or --output <path>",
+            output_path.display()
+        )));
+    }
+    if let Some(parent) = output_path.parent() {
+        fs::create_dir_all(parent).map_err(|err| {
+            CompositorError::Backend(format!("failed to create {}: {err}", parent.display()))
+        })?;
+    }
+    fs::write(&output_path, &outcome.lua).map_err(|err| {
+        CompositorError::Backend(format!("failed to write {}: {err}", output_path.display()))
+    })?;
+
+    println!(
+        "imported {} config into {} ({} directive(s) not imported, listed at the end of the file)",
+        source.name(),
+        output_path.display(),
+        outcome.skipped.len()
+    );
+    Ok(())
+}
+
+fn expand_home(path: &str) -> PathBuf {
+    if let Some(rest) = path.strip_prefix("~/")
+        && let Some(home) = std::env::var_os("HOME")
+    {
+        return PathBuf::from(home).join(rest);
+    }
+    PathBuf::from(path)
+}
+
+/// `raven screenshot [--next-frame] [--output NAME | --region | --window | all | NAME] [path|-]`.
+///
+/// With `-` the PNG goes to stdout: the compositor writes it to a file in the
+/// runtime dir, which is copied out and removed.
+fn screenshot(args: &[String]) -> Result<()> {
+    let to_stdout = args.last().map(String::as_str) == Some("-");
+    if !to_stdout {
+        let output = run_ipc_command(&screenshot_request(args, None)?)?;
+        print!("{output}");
+        return Ok(());
+    }
+
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+    let path = runtime_dir.join(format!("raven-screenshot-{}.png", std::process::id()));
+    let output = run_ipc_command(&screenshot_request(&args[..args.len() - 1], Some(&path))?)?;
+    if output.starts_with("error:") {
+        print!("{output}");
+        return Ok(());
+    }
+    let png = fs::read(&path).map_err(|err| {
+        CompositorError::Backend(format!("failed to read {}: {err}", path.display()))
+    });
+    let _ = fs::remove_file(&path);
+    std::io::stdout()
+        .write_all(&png?)
+        .map_err(|err| CompositorError::Backend(format!("failed to write screenshot: {err}")))
+}
+
+/// Build the ipc request for `raven screenshot`. The compositor resolves paths
+/// against its own working directory, so an explicit path is made absolute.
+fn screenshot_request(args: &[String], path: Option<&Path>) -> Result<String> {
+    let mut command = "screenshot".to_owned();
+    let mut target = None;
+    let mut rest = Vec::new();
+    let mut args = args.iter().map(String::as_str);
+    while let Some(arg) = args.next() {
+        match arg {
+            "--next-frame" => command = "screenshot --next-frame".to_owned(),
+            "--output" => {
+                let name = args.next().ok_or_else(|| {
+                    CompositorError::Backend("--output needs an output name".to_owned())
+                })?;
+                target = Some(name);
+            }
+            "--region" | "--window" => target = Some(arg),
+            _ => rest.push(arg),
+        }
+    }
+    // Without a flag, the first word is the target as before.
+    let target = match target {
+        Some(target) => target,
+        None if rest.is_empty() => "all",
+        None => rest.remove(0),
+    };
+    let path = match path {
+        Some(path) => Some(path.to_path_buf()),
+        None if rest.is_empty() => None,
+        None => Some(PathBuf::from(rest.join(" "))),
+    };
+    let Some(mut path) = path else {
+        return Ok(format!("{command} {target}"));
+    };
+    if path.is_relative() {
+        let cwd = std::env::current_dir()
+            .map_err(|err| CompositorError::Backend(format!("failed to read cwd: {err}")))?;
+        path = cwd.join(path);
+    }
+    Ok(format!("{command} {target} {}", path.display()))
+}
+
+fn connect_ipc(command: &str) -> Result<UnixStream> {
     let socket_path = ipc_socket_path_from_env()?;
     let mut stream = UnixStream::connect(&socket_path).map_err(|err| {
         CompositorError::Backend(format!(
@@ -124,6 +316,33 @@ fn run_ipc_command(command: &str) -> Result<String> {
     stream.shutdown(std::net::Shutdown::Write).map_err(|err| {
         CompositorError::Backend(format!("failed to finalize ipc command write: {err}"))
     })?;
+    Ok(stream)
+}
+
+/// Redraw the clients report every time the compositor pushes a new one, until
+/// the compositor closes the connection or the user interrupts.
+fn run_clients_watch() -> Result<()> {
+    let mut reader = BufReader::new(connect_ipc("clients --watch")?);
+    let mut stdout = std::io::stdout();
+    let mut frame = Vec::new();
+    loop {
+        frame.clear();
+        let read = reader.read_until(0, &mut frame).map_err(|err| {
+            CompositorError::Backend(format!("failed to read ipc response: {err}"))
+        })?;
+        if read == 0 {
+            return Ok(());
+        }
+        if frame.last() == Some(&0) {
+            frame.pop();
+        }
+        let _ = write!(stdout, "\x1b[H\x1b[2J{}", String::from_utf8_lossy(&frame));
+        let _ = stdout.flush();
+    }
+}
+
+fn run_ipc_command(command: &str) -> Result<String> {
+    let mut stream = connect_ipc(command)?;
 
     let mut response = String::new();
     stream
@@ -197,6 +416,150 @@ fn is_nested() -> bool {
     std::env::var("WAYLAND_DISPLAY").is_ok() || std::env::var("DISPLAY").is_ok()
 }
 
+/// Backend from `--backend`, or from the older `--winit` / `--drm` / `--tty` flags.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum BackendChoice {
+    /// Winit when nested, DRM otherwise; if that backend fails to start, the other
+    /// one is tried in case the nesting guess was wrong.
+    Auto,
+    Drm,
+    Winit,
+    Headless,
+}
+
+impl BackendChoice {
+    fn parse(raw: &str) -> Result<Self> {
+        match raw {
+            "auto" => Ok(Self::Auto),
+            "drm" | "tty" => Ok(Self::Drm),
+            "winit" => Ok(Self::Winit),
+            "headless" => Ok(Self::Headless),
+            other => Err(CompositorError::Backend(format!(
+                "unknown backend `{other}` (expected auto, drm, winit or headless)"
+            ))),
+        }
+    }
+}
+
+/// The backend to start and the command to spawn once it runs, if any.
+fn launch_args(args: &[String]) -> Result<(BackendChoice, Option<&str>)> {
+    let mut backend: Option<(BackendChoice, &str)> = None;
+    let mut spawn_cmd = None;
+    let mut iter = args.iter().skip(1);
+    while let Some(arg) = iter.next() {
+        let (choice, flag) = match arg.as_str() {
+            "--winit" => (BackendChoice::Winit, arg.as_str()),
+            "--drm" | "--tty" => (BackendChoice::Drm, arg.as_str()),
+            "--backend" => {
+                let raw = iter.next().ok_or_else(|| {
+                    CompositorError::Backend(
+                        "--backend requires auto, drm, winit or headless".to_owned(),
+                    )
+                })?;
+                (BackendChoice::parse(raw)?, "--backend")
+            }
+            other => match other.strip_prefix("--backend=") {
+                Some(raw) => (BackendChoice::parse(raw)?, "--backend"),
+                None => {
+                    if !other.starts_with("--") && spawn_cmd.is_none() {
+                        spawn_cmd = Some(other);
+                    }
+                    continue;
+                }
+            },
+        };
+        match backend {
+            Some((previous, previous_flag)) if previous != choice => {
+                return Err(CompositorError::Backend(format!(
+                    "conflicting backend flags {previous_flag} and {flag}"
+                )));
+            }
+            _ => backend = Some((choice, flag)),
+        }
+    }
+    Ok((
+        backend.map_or(BackendChoice::Auto, |(choice, _)| choice),
+        spawn_cmd,
+    ))
+}
+
+/// Wrap a backend's startup error with what the user can do about it.
+fn backend_failure(backend: &str, err: CompositorError, hint: &str) -> CompositorError {
+    let reason = match err {
+        CompositorError::Backend(msg) => msg,
+        other => other.to_string(),
+    };
+    CompositorError::Backend(format!("{backend} backend: {reason} ({hint})"))
+}
+
+fn start_drm(event_loop: &mut EventLoop<Raven>, state: &mut Raven) -> Result<()> {
+    tracing::info!("Starting with DRM/KMS backend");
+    raven::backend::udev::init_udev(event_loop, state).map_err(|err| {
+        // A half-initialized session must not make a fallback backend look like DRM.
+        state.udev_data = None;
+        backend_failure(
+            "DRM",
+            err,
+            "run raven from a TTY with seatd or logind and a GPU, or use --backend winit inside another session",
+        )
+    })
+}
+
+fn start_winit(event_loop: &mut EventLoop<Raven>, state: &mut Raven) -> Result<()> {
+    tracing::info!("Starting with Winit backend");
+    raven::backend::winit::init_winit(event_loop, state).map_err(|err| {
+        backend_failure(
+            "winit",
+            err,
+            "it needs WAYLAND_DISPLAY or DISPLAY to point at a running session",
+        )
+    })
+}
+
+#[cfg(feature = "test-capture")]
+fn start_headless(event_loop: &mut EventLoop<Raven>, state: &mut Raven) -> Result<()> {
+    tracing::info!("Starting with headless backend");
+    raven::backend::headless::init_headless(event_loop, state)
+}
+
+#[cfg(not(feature = "test-capture"))]
+fn start_headless(_event_loop: &mut EventLoop<Raven>, _state: &mut Raven) -> Result<()> {
+    Err(CompositorError::Backend(
+        "the headless backend needs raven built with the `test-capture` feature".to_owned(),
+    ))
+}
+
+type StartBackend = fn(&mut EventLoop<Raven>, &mut Raven) -> Result<()>;
+
+fn start_backend(
+    event_loop: &mut EventLoop<Raven>,
+    state: &mut Raven,
+    backend: BackendChoice,
+) -> Result<()> {
+    match backend {
+        BackendChoice::Drm => start_drm(event_loop, state),
+        BackendChoice::Winit => start_winit(event_loop, state),
+        BackendChoice::Headless => start_headless(event_loop, state),
+        BackendChoice::Auto => {
+            let nested = is_nested();
+            let (detected, fallback): (StartBackend, StartBackend) = if nested {
+                (start_winit, start_drm)
+            } else {
+                (start_drm, start_winit)
+            };
+            let Err(err) = detected(event_loop, state) else {
+                return Ok(());
+            };
+            tracing::warn!(nested, "{err}; trying the other backend");
+            fallback(event_loop, state).map_err(|fallback_err| {
+                CompositorError::Backend(format!(
+                    "no backend could start:\n  {err}\n  {fallback_err}"
+                ))
+            })
+        }
+    }
+}
+
 const DEFAULT_LOG_FILTER: &str = concat!(
     // Keep default logs useful but quiet in daily-driver runs.
     "raven=info,",
@@ -13,13 +13,15 @@ use smithay::reexports::wayland_server::{
     Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
 };
 
-use crate::state::{Raven, WORKSPACE_COUNT};
+use crate::state::Raven;
 
 const VERSION: u32 = 1;
 
 pub trait ExtWorkspaceHandler {
     fn ext_workspace_manager_state(&mut self) -> &mut ExtWorkspaceManagerState;
     fn activate_workspace(&mut self, workspace_index: usize);
+    /// Bring the protocol state up to date before a new manager gets its snapshot.
+    fn refresh_workspaces(&mut self);
 }
 
 enum Action {
@@ -53,6 +55,9 @@ pub struct ExtWorkspaceGlobalData {
 pub fn refresh(state: &mut Raven) {
     let outputs: Vec<Output> = state.space.outputs().cloned().collect();
     let primary_output = outputs.first().cloned();
+    let urgent: Vec<bool> = (0..state.workspaces.len())
+        .map(|index| state.is_workspace_urgent(index))
+        .collect();
 
     let protocol_state = &mut state.ext_workspace_manager_state;
     let mut changed = false;
@@ -76,11 +81,18 @@ pub fn refresh(state: &mut Raven) {
         changed |= refresh_workspace_group(protocol_state, output);
     }
 
-    for index in 0..WORKSPACE_COUNT {
+    // Workspaces come and go with the dynamic count; removed ones are sent
+    // `removed` below.
+    let columns = state.config.workspace_grid_columns;
+    let workspace_count = state.workspaces.len();
+    for index in 0..workspace_count {
         changed |= refresh_workspace(
             protocol_state,
             index,
+            state.config.workspace_label(index),
+            workspace_coordinates(index, columns),
             index == state.current_workspace,
+            urgent[index],
             primary_output.as_ref(),
         );
     }
@@ -89,7 +101,7 @@ pub fn refresh(state: &mut Raven) {
         .workspaces
         .keys()
         .copied()
-        .filter(|index| *index >= WORKSPACE_COUNT)
+        .filter(|index| *index >= workspace_count)
         .collect();
     for index in stale_workspaces {
         if let Some(workspace) = protocol_state.workspaces.remove(&index) {
@@ -105,6 +117,25 @@ pub fn refresh(state: &mut Raven) {
     }
 }
 
+/// Re-send the current name, coordinates and state of every workspace to every
+/// bound manager, for bars that got out of sync. Ids and capabilities are only
+/// sent once per handle, so they are left alone.
+pub fn resync(state: &mut Raven) {
+    refresh(state);
+
+    let protocol_state = &state.ext_workspace_manager_state;
+    for workspace in protocol_state.workspaces.values() {
+        for handle in &workspace.instances {
+            handle.name(workspace.name.clone());
+            handle.coordinates(coordinates_bytes(workspace.coordinates));
+            handle.state(workspace.state);
+        }
+    }
+    for manager in protocol_state.instances.keys() {
+        manager.done();
+    }
+}
+
 pub fn on_output_bound(state: &mut Raven, output: &Output, wl_output: &WlOutput) {
     let Some(client) = wl_output.client() else {
         return;
@@ -170,14 +201,31 @@ fn refresh_workspace_group(protocol_state: &mut ExtWorkspaceManagerState, output
     true
 }
 
-fn build_workspace_name(index: usize) -> String {
+fn build_workspace_id(index: usize) -> String {
     (index + 1).to_string()
 }
 
+/// Grid position pagers draw workspace `index` at: `[column, row]` with `columns`
+/// workspaces per row. `raven pager` reports the same coordinates.
+pub fn workspace_coordinates(index: usize, columns: u32) -> [u32; 2] {
+    let columns = columns.max(1) as usize;
+    [(index % columns) as u32, (index / columns) as u32]
+}
+
+fn coordinates_bytes(coordinates: [u32; 2]) -> Vec<u8> {
+    coordinates
+        .iter()
+        .flat_map(|value| value.to_ne_bytes())
+        .collect()
+}
+
 fn refresh_workspace(
     protocol_state: &mut ExtWorkspaceManagerState,
     workspace_index: usize,
+    name: String,
+    coordinates: [u32; 2],
     active: bool,
+    urgent: bool,
     output: Option<&Output>,
 ) -> bool {
     let workspace_groups = &protocol_state.workspace_groups;
@@ -185,6 +233,9 @@ fn refresh_workspace(
     if active {
         state |= ext_workspace_handle_v1::State::Active;
     }
+    if urgent {
+        state |= ext_workspace_handle_v1::State::Urgent;
+    }
 
     match protocol_state.workspaces.entry(workspace_index) {
         Entry::Occupied(entry) => {
@@ -196,6 +247,23 @@ fn refresh_workspace(
                 state_changed = true;
             }
 
+            // Names change when the config is reloaded; ids stay the number.
+            let name_changed = workspace.name != name;
+            if name_changed {
+                for handle in &workspace.instances {
+                    handle.name(name.clone());
+                }
+                workspace.name = name;
+            }
+
+            let coordinates_changed = workspace.coordinates != coordinates;
+            if coordinates_changed {
+                workspace.coordinates = coordinates;
+                for handle in &workspace.instances {
+                    handle.coordinates(coordinates_bytes(coordinates));
+                }
+            }
+
             let mut output_changed = false;
             if workspace.output.as_ref() != output {
                 send_workspace_enter_leave(workspace_groups, workspace, false);
@@ -213,13 +281,13 @@ fn refresh_workspace(
                 }
             }
 
-            output_changed || state_changed
+            output_changed || state_changed || name_changed || coordinates_changed
         }
         Entry::Vacant(entry) => {
             let mut workspace = WorkspaceData {
-                id: build_workspace_name(workspace_index),
-                name: build_workspace_name(workspace_index),
-                coordinates: [0, workspace_index as u32],
+                id: build_workspace_id(workspace_index),
+                name,
+                coordinates,
                 state,
                 output: output.cloned(),
                 instances: Vec::new(),
@@ -326,12 +394,7 @@ impl WorkspaceData {
         manager.workspace(&workspace);
         workspace.id(self.id.clone());
         workspace.name(self.name.clone());
-        workspace.coordinates(
-            self.coordinates
-                .iter()
-                .flat_map(|value| value.to_ne_bytes())
-                .collect(),
-        );
+        workspace.coordinates(coordinates_bytes(self.coordinates));
         workspace.state(self.state);
         workspace.capabilities(ext_workspace_handle_v1::WorkspaceCapabilities::Activate);
 
@@ -379,6 +442,9 @@ where
         data_init: &mut DataInit<'_, D>,
     ) {
         let manager = data_init.init(resource, ());
+        // A restarted bar binds a fresh manager; make sure it starts from the
+        // current workspaces rather than whatever the last refresh saw.
+        state.refresh_workspaces();
         let protocol_state = state.ext_workspace_manager_state();
 
         for (output, group_data) in &mut protocol_state.workspace_groups {
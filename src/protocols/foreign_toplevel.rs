@@ -12,6 +12,7 @@ use smithay::reexports::wayland_server::{
     Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
 };
 use smithay::wayland::compositor::with_states;
+use smithay::wayland::foreign_toplevel_list::{ForeignToplevelHandle, ForeignToplevelListState};
 use smithay::wayland::shell::xdg::{ToplevelCachedState, ToplevelStateSet, XdgToplevelSurfaceData};
 
 use crate::Raven;
@@ -35,6 +36,10 @@ pub trait ForeignToplevelHandler {
     fn unset_fullscreen(&mut self, wl_surface: WlSurface);
     fn set_maximized(&mut self, wl_surface: WlSurface);
     fn unset_maximized(&mut self, wl_surface: WlSurface);
+    fn set_minimized(&mut self, wl_surface: WlSurface);
+    fn unset_minimized(&mut self, wl_surface: WlSurface);
+    /// Bring the protocol state up to date before a new manager gets its snapshot.
+    fn refresh_toplevels(&mut self);
 }
 
 struct ToplevelData {
@@ -43,6 +48,8 @@ struct ToplevelData {
     states: Vec<u32>,
     output: Option<Output>,
     instances: HashMap<ZwlrForeignToplevelHandleV1, Vec<WlOutput>>,
+    // The same toplevel as listed by ext-foreign-toplevel-list.
+    list_handle: ForeignToplevelHandle,
 }
 
 pub struct ForeignToplevelGlobalData {
@@ -69,6 +76,16 @@ impl ForeignToplevelManagerState {
     }
 }
 
+impl ForeignToplevelManagerState {
+    /// Surface of the toplevel that `handle` lists over ext-foreign-toplevel-list.
+    pub fn surface_for_list_handle(&self, handle: &ForeignToplevelHandle) -> Option<WlSurface> {
+        self.toplevels
+            .iter()
+            .find(|(_, data)| data.list_handle.identifier() == handle.identifier())
+            .map(|(surface, _)| surface.clone())
+    }
+}
+
 pub fn refresh(state: &mut Raven) {
     let focused_surface = state
         .seat
@@ -84,12 +101,17 @@ pub fn refresh(state: &mut Raven) {
         };
         let surface = toplevel.wl_surface();
         if seen_windows.insert(surface.clone()) {
-            windows.push(window.clone());
+            windows.push((
+                window.clone(),
+                state.is_window_minimized(window),
+                state.toplevel_output(window),
+            ));
         }
     }
 
     let live_surfaces = seen_windows;
     let protocol_state = &mut state.foreign_toplevel_manager_state;
+    let list_state = &mut state.foreign_toplevel_list_state;
 
     protocol_state.toplevels.retain(|surface, data| {
         if live_surfaces.contains(surface) {
@@ -99,21 +121,16 @@ pub fn refresh(state: &mut Raven) {
         for instance in data.instances.keys() {
             instance.closed();
         }
+        list_state.remove_toplevel(&data.list_handle);
 
         false
     });
 
-    for window in windows {
+    for (window, minimized, output) in windows {
         let Some(toplevel) = window.toplevel() else {
             continue;
         };
         let wl_surface = toplevel.wl_surface().clone();
-        let mapped = state.space.elements().any(|candidate| candidate == &window);
-        let output = if mapped {
-            state.space.outputs().next().cloned()
-        } else {
-            None
-        };
         let has_focus = focused_surface.as_ref() == Some(&wl_surface);
 
         let (title, app_id, xdg_states) = with_states(&wl_surface, |states| {
@@ -132,8 +149,39 @@ pub fn refresh(state: &mut Raven) {
             (role.title.clone(), role.app_id.clone(), current_state)
         });
 
-        let states = to_state_vec(xdg_states.as_ref().map(|state| &state.states), has_focus);
-        refresh_toplevel(protocol_state, &wl_surface, title, app_id, states, output);
+        let states = to_state_vec(
+            xdg_states.as_ref().map(|state| &state.states),
+            has_focus,
+            minimized,
+        );
+        refresh_toplevel(
+            protocol_state,
+            list_state,
+            &wl_surface,
+            title,
+            app_id,
+            states,
+            output,
+        );
+    }
+}
+
+/// Re-send the title, app id and state of every toplevel to every bound manager,
+/// for bars whose task list got out of sync.
+pub fn resync(state: &mut Raven) {
+    refresh(state);
+
+    for data in state.foreign_toplevel_manager_state.toplevels.values() {
+        for instance in data.instances.keys() {
+            if let Some(title) = &data.title {
+                instance.title(title.clone());
+            }
+            if let Some(app_id) = &data.app_id {
+                instance.app_id(app_id.clone());
+            }
+            instance.state(data.states.iter().flat_map(|x| x.to_ne_bytes()).collect());
+            instance.done();
+        }
     }
 }
 
@@ -162,6 +210,7 @@ pub fn on_output_bound(state: &mut Raven, output: &Output, wl_output: &WlOutput)
 
 fn refresh_toplevel(
     protocol_state: &mut ForeignToplevelManagerState,
+    list_state: &mut ForeignToplevelListState,
     wl_surface: &WlSurface,
     title: Option<String>,
     app_id: Option<String>,
@@ -196,6 +245,18 @@ fn refresh_toplevel(
                 output_changed = true;
             }
 
+            if title_changed || app_id_changed {
+                if title_changed {
+                    data.list_handle
+                        .send_title(data.title.as_deref().unwrap_or_default());
+                }
+                if app_id_changed {
+                    data.list_handle
+                        .send_app_id(data.app_id.as_deref().unwrap_or_default());
+                }
+                data.list_handle.send_done();
+            }
+
             if title_changed || app_id_changed || states_changed || output_changed {
                 for (instance, outputs) in &mut data.instances {
                     if title_changed && let Some(title) = &data.title {
@@ -229,12 +290,17 @@ fn refresh_toplevel(
             }
         }
         Entry::Vacant(entry) => {
+            let list_handle = list_state.new_toplevel::<Raven>(
+                title.clone().unwrap_or_default(),
+                app_id.clone().unwrap_or_default(),
+            );
             let mut data = ToplevelData {
                 title,
                 app_id,
                 states,
                 output,
                 instances: HashMap::new(),
+                list_handle,
             };
 
             for manager in &protocol_state.instances {
@@ -303,6 +369,9 @@ where
     ) {
         let manager = data_init.init(resource, ());
 
+        // Refresh first so the new manager sees current titles and states, not
+        // whatever the last refresh saw.
+        state.refresh_toplevels();
         let protocol_state = state.foreign_toplevel_manager_state();
         for data in protocol_state.toplevels.values_mut() {
             data.add_instance::<D>(handle, client, &manager);
@@ -384,8 +453,10 @@ where
             zwlr_foreign_toplevel_handle_v1::Request::UnsetMaximized => {
                 state.unset_maximized(surface)
             }
-            zwlr_foreign_toplevel_handle_v1::Request::SetMinimized => {}
-            zwlr_foreign_toplevel_handle_v1::Request::UnsetMinimized => {}
+            zwlr_foreign_toplevel_handle_v1::Request::SetMinimized => state.set_minimized(surface),
+            zwlr_foreign_toplevel_handle_v1::Request::UnsetMinimized => {
+                state.unset_minimized(surface)
+            }
             zwlr_foreign_toplevel_handle_v1::Request::Activate { .. } => state.activate(surface),
             zwlr_foreign_toplevel_handle_v1::Request::Close => state.close(surface),
             zwlr_foreign_toplevel_handle_v1::Request::SetRectangle { .. } => {}
@@ -413,14 +484,17 @@ where
     }
 }
 
-fn to_state_vec(states: Option<&ToplevelStateSet>, has_focus: bool) -> Vec<u32> {
-    let mut result = Vec::with_capacity(3);
+fn to_state_vec(states: Option<&ToplevelStateSet>, has_focus: bool, minimized: bool) -> Vec<u32> {
+    let mut result = Vec::with_capacity(4);
     if states.is_some_and(|s| s.contains(xdg_toplevel::State::Maximized)) {
         result.push(zwlr_foreign_toplevel_handle_v1::State::Maximized as u32);
     }
     if states.is_some_and(|s| s.contains(xdg_toplevel::State::Fullscreen)) {
         result.push(zwlr_foreign_toplevel_handle_v1::State::Fullscreen as u32);
     }
+    if minimized {
+        result.push(zwlr_foreign_toplevel_handle_v1::State::Minimized as u32);
+    }
     if has_focus {
         result.push(zwlr_foreign_toplevel_handle_v1::State::Activated as u32);
     }
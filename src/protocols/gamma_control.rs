@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::os::unix::fs::FileExt;
+
+use smithay::output::Output;
+use smithay::reexports::wayland_protocols_wlr::gamma_control::v1::server::{
+    zwlr_gamma_control_manager_v1::{self, ZwlrGammaControlManagerV1},
+    zwlr_gamma_control_v1::{self, ZwlrGammaControlV1},
+};
+use smithay::reexports::wayland_server::backend::ClientId;
+use smithay::reexports::wayland_server::{
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+};
+
+const VERSION: u32 = 1;
+
+pub trait GammaControlHandler {
+    fn gamma_control_state(&mut self) -> &mut GammaControlManagerState;
+    /// Gamma ramp entries per channel of `output`; `None` when it has no ramp.
+    fn gamma_size(&self, output: &Output) -> Option<u32>;
+    /// Load `ramp`, the red, green and blue channels back to back, onto
+    /// `output`, or restore its own ramp with `None`. `false` fails the control.
+    fn set_gamma(&mut self, output: &Output, ramp: Option<Vec<u16>>) -> bool;
+}
+
+/// Gamma controls in effect; an output takes one client at a time.
+#[derive(Default)]
+pub struct GammaControlManagerState {
+    controls: HashMap<ZwlrGammaControlV1, Output>,
+}
+
+pub struct GammaControlGlobalData {
+    filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+impl GammaControlManagerState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<ZwlrGammaControlManagerV1, GammaControlGlobalData>,
+        D: Dispatch<ZwlrGammaControlManagerV1, ()>,
+        D: 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+    {
+        let global_data = GammaControlGlobalData {
+            filter: Box::new(filter),
+        };
+        display.create_global::<D, ZwlrGammaControlManagerV1, _>(VERSION, global_data);
+
+        Self::default()
+    }
+
+    /// Fail the control of an output that went away; it is inert from now on.
+    pub fn output_removed(&mut self, output: &Output) {
+        self.controls.retain(|control, controlled| {
+            if controlled != output {
+                return true;
+            }
+            control.failed();
+            false
+        });
+    }
+}
+
+impl<D> GlobalDispatch<ZwlrGammaControlManagerV1, GammaControlGlobalData, D>
+    for GammaControlManagerState
+where
+    D: GlobalDispatch<ZwlrGammaControlManagerV1, GammaControlGlobalData>,
+    D: Dispatch<ZwlrGammaControlManagerV1, ()>,
+    D: 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<ZwlrGammaControlManagerV1>,
+        _global_data: &GammaControlGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+
+    fn can_view(client: Client, global_data: &GammaControlGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<ZwlrGammaControlManagerV1, (), D> for GammaControlManagerState
+where
+    D: Dispatch<ZwlrGammaControlManagerV1, ()>,
+    D: Dispatch<ZwlrGammaControlV1, ()>,
+    D: GammaControlHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        _resource: &ZwlrGammaControlManagerV1,
+        request: <ZwlrGammaControlManagerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwlr_gamma_control_manager_v1::Request::GetGammaControl { id, output } => {
+                let control = data_init.init(id, ());
+                let Some(output) = Output::from_resource(&output) else {
+                    control.failed();
+                    return;
+                };
+                let taken = state
+                    .gamma_control_state()
+                    .controls
+                    .values()
+                    .any(|controlled| *controlled == output);
+                let Some(size) = state.gamma_size(&output).filter(|_| !taken) else {
+                    control.failed();
+                    return;
+                };
+                control.gamma_size(size);
+                state.gamma_control_state().controls.insert(control, output);
+            }
+            zwlr_gamma_control_manager_v1::Request::Destroy => {}
+            _ => {}
+        }
+    }
+}
+
+impl<D> Dispatch<ZwlrGammaControlV1, (), D> for GammaControlManagerState
+where
+    D: Dispatch<ZwlrGammaControlV1, ()>,
+    D: GammaControlHandler,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &ZwlrGammaControlV1,
+        request: <ZwlrGammaControlV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwlr_gamma_control_v1::Request::SetGamma { fd } => {
+                let Some(output) = state.gamma_control_state().controls.get(resource).cloned()
+                else {
+                    return;
+                };
+                let Some(size) = state.gamma_size(&output) else {
+                    fail(state, resource, &output);
+                    return;
+                };
+                // Red, green and blue ramps of `size` native-endian u16 each.
+                let mut bytes = vec![0u8; size as usize * 3 * 2];
+                if let Err(err) = File::from(fd).read_exact_at(&mut bytes, 0) {
+                    tracing::debug!("failed to read gamma ramp: {err}");
+                    resource.post_error(
+                        zwlr_gamma_control_v1::Error::InvalidGamma,
+                        "gamma ramp does not match gamma_size",
+                    );
+                    return;
+                }
+                let ramp = bytes
+                    .chunks_exact(2)
+                    .map(|entry| u16::from_ne_bytes([entry[0], entry[1]]))
+                    .collect();
+                if !state.set_gamma(&output, Some(ramp)) {
+                    fail(state, resource, &output);
+                }
+            }
+            zwlr_gamma_control_v1::Request::Destroy => {}
+            _ => {}
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, resource: &ZwlrGammaControlV1, _data: &()) {
+        if let Some(output) = state.gamma_control_state().controls.remove(resource) {
+            state.set_gamma(&output, None);
+        }
+    }
+}
+
+/// Drop a control whose ramp could not be applied, restoring the output's own.
+fn fail<D: GammaControlHandler>(state: &mut D, control: &ZwlrGammaControlV1, output: &Output) {
+    state.gamma_control_state().controls.remove(control);
+    state.set_gamma(output, None);
+    control.failed();
+}
+
+#[macro_export]
+macro_rules! delegate_gamma_control {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols_wlr::gamma_control::v1::server::zwlr_gamma_control_manager_v1::ZwlrGammaControlManagerV1: $crate::protocols::gamma_control::GammaControlGlobalData
+        ] => $crate::protocols::gamma_control::GammaControlManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols_wlr::gamma_control::v1::server::zwlr_gamma_control_manager_v1::ZwlrGammaControlManagerV1: ()
+        ] => $crate::protocols::gamma_control::GammaControlManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols_wlr::gamma_control::v1::server::zwlr_gamma_control_v1::ZwlrGammaControlV1: ()
+        ] => $crate::protocols::gamma_control::GammaControlManagerState);
+    };
+}
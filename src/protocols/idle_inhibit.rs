@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+
+use smithay::reexports::wayland_protocols::wp::idle_inhibit::zv1::server::{
+    zwp_idle_inhibit_manager_v1::{self, ZwpIdleInhibitManagerV1},
+    zwp_idle_inhibitor_v1::{self, ZwpIdleInhibitorV1},
+};
+use smithay::reexports::wayland_server::backend::ClientId;
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::reexports::wayland_server::{
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+};
+
+const VERSION: u32 = 1;
+
+pub trait IdleInhibitHandler {
+    fn idle_inhibit_state(&mut self) -> &mut IdleInhibitManagerState;
+    /// An inhibitor was created or destroyed.
+    fn inhibitors_changed(&mut self);
+}
+
+#[derive(Default)]
+pub struct IdleInhibitManagerState {
+    inhibitors: HashMap<ZwpIdleInhibitorV1, WlSurface>,
+}
+
+pub struct IdleInhibitGlobalData {
+    filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+impl IdleInhibitManagerState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<ZwpIdleInhibitManagerV1, IdleInhibitGlobalData>,
+        D: Dispatch<ZwpIdleInhibitManagerV1, ()>,
+        D: 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+    {
+        let global_data = IdleInhibitGlobalData {
+            filter: Box::new(filter),
+        };
+        display.create_global::<D, ZwpIdleInhibitManagerV1, _>(VERSION, global_data);
+
+        Self::default()
+    }
+
+    /// Surfaces with a live inhibitor, once each.
+    pub fn surfaces(&self) -> Vec<WlSurface> {
+        let mut surfaces: Vec<WlSurface> = Vec::new();
+        for surface in self.inhibitors.values() {
+            if surface.is_alive() && !surfaces.contains(surface) {
+                surfaces.push(surface.clone());
+            }
+        }
+        surfaces
+    }
+}
+
+impl<D> GlobalDispatch<ZwpIdleInhibitManagerV1, IdleInhibitGlobalData, D>
+    for IdleInhibitManagerState
+where
+    D: GlobalDispatch<ZwpIdleInhibitManagerV1, IdleInhibitGlobalData>,
+    D: Dispatch<ZwpIdleInhibitManagerV1, ()>,
+    D: 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<ZwpIdleInhibitManagerV1>,
+        _global_data: &IdleInhibitGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+
+    fn can_view(client: Client, global_data: &IdleInhibitGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<ZwpIdleInhibitManagerV1, (), D> for IdleInhibitManagerState
+where
+    D: Dispatch<ZwpIdleInhibitManagerV1, ()>,
+    D: Dispatch<ZwpIdleInhibitorV1, ()>,
+    D: IdleInhibitHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        _resource: &ZwpIdleInhibitManagerV1,
+        request: <ZwpIdleInhibitManagerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_idle_inhibit_manager_v1::Request::CreateInhibitor { id, surface } => {
+                let inhibitor = data_init.init(id, ());
+                state
+                    .idle_inhibit_state()
+                    .inhibitors
+                    .insert(inhibitor, surface);
+                state.inhibitors_changed();
+            }
+            zwp_idle_inhibit_manager_v1::Request::Destroy => {}
+            _ => {}
+        }
+    }
+}
+
+impl<D> Dispatch<ZwpIdleInhibitorV1, (), D> for IdleInhibitManagerState
+where
+    D: Dispatch<ZwpIdleInhibitorV1, ()>,
+    D: IdleInhibitHandler,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _resource: &ZwpIdleInhibitorV1,
+        request: <ZwpIdleInhibitorV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwp_idle_inhibitor_v1::Request::Destroy => {}
+            _ => {}
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, resource: &ZwpIdleInhibitorV1, _data: &()) {
+        if state
+            .idle_inhibit_state()
+            .inhibitors
+            .remove(resource)
+            .is_some()
+        {
+            state.inhibitors_changed();
+        }
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_idle_inhibit {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::idle_inhibit::zv1::server::zwp_idle_inhibit_manager_v1::ZwpIdleInhibitManagerV1: $crate::protocols::idle_inhibit::IdleInhibitGlobalData
+        ] => $crate::protocols::idle_inhibit::IdleInhibitManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::idle_inhibit::zv1::server::zwp_idle_inhibit_manager_v1::ZwpIdleInhibitManagerV1: ()
+        ] => $crate::protocols::idle_inhibit::IdleInhibitManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::idle_inhibit::zv1::server::zwp_idle_inhibitor_v1::ZwpIdleInhibitorV1: ()
+        ] => $crate::protocols::idle_inhibit::IdleInhibitManagerState);
+    };
+}
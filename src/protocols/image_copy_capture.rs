@@ -0,0 +1,503 @@
+//! ext-image-copy-capture-v1 with the output and foreign-toplevel capture
+//! sources of ext-image-capture-source-v1, so a client can capture a single
+//! window rather than a whole output. Frames are copied into shm buffers and
+//! leave the pointer out; cursor sessions stop as soon as they are created.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use smithay::output::{Output, WeakOutput};
+use smithay::reexports::wayland_protocols::ext::image_capture_source::v1::server::{
+    ext_foreign_toplevel_image_capture_source_manager_v1::{
+        self, ExtForeignToplevelImageCaptureSourceManagerV1,
+    },
+    ext_image_capture_source_v1::ExtImageCaptureSourceV1,
+    ext_output_image_capture_source_manager_v1::{self, ExtOutputImageCaptureSourceManagerV1},
+};
+use smithay::reexports::wayland_protocols::ext::image_copy_capture::v1::server::{
+    ext_image_copy_capture_cursor_session_v1::{self, ExtImageCopyCaptureCursorSessionV1},
+    ext_image_copy_capture_frame_v1::{self, ExtImageCopyCaptureFrameV1, FailureReason},
+    ext_image_copy_capture_manager_v1::{self, ExtImageCopyCaptureManagerV1},
+    ext_image_copy_capture_session_v1::{self, ExtImageCopyCaptureSessionV1},
+};
+use smithay::reexports::wayland_server::protocol::wl_buffer::WlBuffer;
+use smithay::reexports::wayland_server::protocol::wl_output::Transform;
+use smithay::reexports::wayland_server::protocol::wl_shm::Format;
+use smithay::reexports::wayland_server::{
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource,
+};
+use smithay::utils::{Physical, Size};
+use smithay::wayland::foreign_toplevel_list::ForeignToplevelHandle;
+use smithay::wayland::shm;
+
+const VERSION: u32 = 1;
+
+/// What a capture session shows.
+#[derive(Clone)]
+pub enum CaptureSource {
+    Output(WeakOutput),
+    /// A toplevel, as listed over ext-foreign-toplevel-list.
+    Toplevel(ForeignToplevelHandle),
+}
+
+pub trait ImageCopyCaptureHandler {
+    fn image_copy_capture_state(&mut self) -> &mut ImageCopyCaptureState;
+    /// Size frames of `source` are captured at, or `None` once it is gone.
+    fn capture_source_size(&mut self, source: &CaptureSource) -> Option<Size<i32, Physical>>;
+    fn frame(&mut self, frame: CaptureFrame);
+}
+
+#[derive(Default)]
+pub struct ImageCopyCaptureState;
+
+/// Shared by the three globals, which are offered to the same clients.
+pub struct ImageCopyCaptureGlobalData {
+    filter: Arc<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+impl ImageCopyCaptureState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<ExtImageCopyCaptureManagerV1, ImageCopyCaptureGlobalData>,
+        D: GlobalDispatch<ExtOutputImageCaptureSourceManagerV1, ImageCopyCaptureGlobalData>,
+        D: GlobalDispatch<
+                ExtForeignToplevelImageCaptureSourceManagerV1,
+                ImageCopyCaptureGlobalData,
+            >,
+        D: 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+    {
+        let filter: Arc<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync> = Arc::new(filter);
+        let global_data = || ImageCopyCaptureGlobalData {
+            filter: filter.clone(),
+        };
+        display.create_global::<D, ExtImageCopyCaptureManagerV1, _>(VERSION, global_data());
+        display.create_global::<D, ExtOutputImageCaptureSourceManagerV1, _>(VERSION, global_data());
+        display.create_global::<D, ExtForeignToplevelImageCaptureSourceManagerV1, _>(
+            VERSION,
+            global_data(),
+        );
+
+        Self
+    }
+}
+
+pub struct SessionData {
+    // `None` for sources that were already gone, and for cursor sessions.
+    source: Option<CaptureSource>,
+    buffer_size: Mutex<Size<i32, Physical>>,
+    frame: Mutex<Option<ExtImageCopyCaptureFrameV1>>,
+}
+
+pub struct FrameData {
+    session: ExtImageCopyCaptureSessionV1,
+    buffer: Mutex<Option<WlBuffer>>,
+    captured: AtomicBool,
+}
+
+fn send_constraints(session: &ExtImageCopyCaptureSessionV1, size: Size<i32, Physical>) {
+    session.buffer_size(size.w as u32, size.h as u32);
+    session.shm_format(Format::Xrgb8888);
+    session.done();
+}
+
+macro_rules! impl_manager_global {
+    ($manager:ty) => {
+        impl<D> GlobalDispatch<$manager, ImageCopyCaptureGlobalData, D> for ImageCopyCaptureState
+        where
+            D: Dispatch<$manager, ()>,
+            D: 'static,
+        {
+            fn bind(
+                _state: &mut D,
+                _display: &DisplayHandle,
+                _client: &Client,
+                manager: New<$manager>,
+                _global_data: &ImageCopyCaptureGlobalData,
+                data_init: &mut DataInit<'_, D>,
+            ) {
+                data_init.init(manager, ());
+            }
+
+            fn can_view(client: Client, global_data: &ImageCopyCaptureGlobalData) -> bool {
+                (global_data.filter)(&client)
+            }
+        }
+    };
+}
+
+impl_manager_global!(ExtImageCopyCaptureManagerV1);
+impl_manager_global!(ExtOutputImageCaptureSourceManagerV1);
+impl_manager_global!(ExtForeignToplevelImageCaptureSourceManagerV1);
+
+impl<D> Dispatch<ExtOutputImageCaptureSourceManagerV1, (), D> for ImageCopyCaptureState
+where
+    D: Dispatch<ExtImageCaptureSourceV1, Option<CaptureSource>>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _manager: &ExtOutputImageCaptureSourceManagerV1,
+        request: ext_output_image_capture_source_manager_v1::Request,
+        _data: &(),
+        _display: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_output_image_capture_source_manager_v1::Request::CreateSource {
+                source,
+                output,
+            } => {
+                let output = Output::from_resource(&output).map(|output| output.downgrade());
+                data_init.init(source, output.map(CaptureSource::Output));
+            }
+            ext_output_image_capture_source_manager_v1::Request::Destroy => {}
+            _ => {}
+        }
+    }
+}
+
+impl<D> Dispatch<ExtForeignToplevelImageCaptureSourceManagerV1, (), D> for ImageCopyCaptureState
+where
+    D: Dispatch<ExtImageCaptureSourceV1, Option<CaptureSource>>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _manager: &ExtForeignToplevelImageCaptureSourceManagerV1,
+        request: ext_foreign_toplevel_image_capture_source_manager_v1::Request,
+        _data: &(),
+        _display: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_foreign_toplevel_image_capture_source_manager_v1::Request::CreateSource {
+                source,
+                toplevel_handle,
+            } => {
+                let toplevel = ForeignToplevelHandle::from_resource(&toplevel_handle);
+                data_init.init(source, toplevel.map(CaptureSource::Toplevel));
+            }
+            ext_foreign_toplevel_image_capture_source_manager_v1::Request::Destroy => {}
+            _ => {}
+        }
+    }
+}
+
+impl<D> Dispatch<ExtImageCaptureSourceV1, Option<CaptureSource>, D> for ImageCopyCaptureState
+where
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _source: &ExtImageCaptureSourceV1,
+        _request: <ExtImageCaptureSourceV1 as Resource>::Request,
+        _data: &Option<CaptureSource>,
+        _display: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+    }
+}
+
+impl<D> Dispatch<ExtImageCopyCaptureManagerV1, (), D> for ImageCopyCaptureState
+where
+    D: Dispatch<ExtImageCopyCaptureSessionV1, SessionData>,
+    D: Dispatch<ExtImageCopyCaptureCursorSessionV1, ()>,
+    D: ImageCopyCaptureHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        _manager: &ExtImageCopyCaptureManagerV1,
+        request: ext_image_copy_capture_manager_v1::Request,
+        _data: &(),
+        _display: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_image_copy_capture_manager_v1::Request::CreateSession {
+                session, source, ..
+            } => {
+                // Frames never include the pointer, so `paint_cursors` is ignored.
+                let source = source.data::<Option<CaptureSource>>().cloned().flatten();
+                let size = source
+                    .as_ref()
+                    .and_then(|source| state.capture_source_size(source));
+                let session = data_init.init(
+                    session,
+                    SessionData {
+                        source: source.filter(|_| size.is_some()),
+                        buffer_size: Mutex::new(size.unwrap_or_default()),
+                        frame: Mutex::new(None),
+                    },
+                );
+                match size {
+                    Some(size) => send_constraints(&session, size),
+                    None => {
+                        tracing::trace!("image copy capture: source is gone");
+                        session.stopped();
+                    }
+                }
+            }
+            ext_image_copy_capture_manager_v1::Request::CreatePointerCursorSession {
+                session,
+                ..
+            } => {
+                data_init.init(session, ());
+            }
+            ext_image_copy_capture_manager_v1::Request::Destroy => {}
+            _ => {}
+        }
+    }
+}
+
+impl<D> Dispatch<ExtImageCopyCaptureCursorSessionV1, (), D> for ImageCopyCaptureState
+where
+    D: Dispatch<ExtImageCopyCaptureSessionV1, SessionData>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        _cursor_session: &ExtImageCopyCaptureCursorSessionV1,
+        request: ext_image_copy_capture_cursor_session_v1::Request,
+        _data: &(),
+        _display: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            // Cursor images are not offered; the session stops right away.
+            ext_image_copy_capture_cursor_session_v1::Request::GetCaptureSession { session } => {
+                let session = data_init.init(
+                    session,
+                    SessionData {
+                        source: None,
+                        buffer_size: Mutex::new(Size::default()),
+                        frame: Mutex::new(None),
+                    },
+                );
+                session.stopped();
+            }
+            ext_image_copy_capture_cursor_session_v1::Request::Destroy => {}
+            _ => {}
+        }
+    }
+}
+
+impl<D> Dispatch<ExtImageCopyCaptureSessionV1, SessionData, D> for ImageCopyCaptureState
+where
+    D: Dispatch<ExtImageCopyCaptureFrameV1, FrameData>,
+    D: 'static,
+{
+    fn request(
+        _state: &mut D,
+        _client: &Client,
+        session: &ExtImageCopyCaptureSessionV1,
+        request: ext_image_copy_capture_session_v1::Request,
+        data: &SessionData,
+        _display: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_image_copy_capture_session_v1::Request::CreateFrame { frame } => {
+                let mut current = data.frame.lock().unwrap();
+                if current.as_ref().is_some_and(Resource::is_alive) {
+                    session.post_error(
+                        ext_image_copy_capture_session_v1::Error::DuplicateFrame,
+                        "session already has a frame",
+                    );
+                    return;
+                }
+                let frame = data_init.init(
+                    frame,
+                    FrameData {
+                        session: session.clone(),
+                        buffer: Mutex::new(None),
+                        captured: AtomicBool::new(false),
+                    },
+                );
+                *current = Some(frame);
+            }
+            ext_image_copy_capture_session_v1::Request::Destroy => {}
+            _ => {}
+        }
+    }
+}
+
+impl<D> Dispatch<ExtImageCopyCaptureFrameV1, FrameData, D> for ImageCopyCaptureState
+where
+    D: ImageCopyCaptureHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        frame: &ExtImageCopyCaptureFrameV1,
+        request: ext_image_copy_capture_frame_v1::Request,
+        data: &FrameData,
+        _display: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            ext_image_copy_capture_frame_v1::Request::AttachBuffer { buffer } => {
+                *data.buffer.lock().unwrap() = Some(buffer);
+            }
+            // Every frame is copied whole, so client damage changes nothing.
+            ext_image_copy_capture_frame_v1::Request::DamageBuffer { .. } => {}
+            ext_image_copy_capture_frame_v1::Request::Capture => {
+                if data.captured.swap(true, Ordering::SeqCst) {
+                    frame.post_error(
+                        ext_image_copy_capture_frame_v1::Error::AlreadyCaptured,
+                        "capture was already requested",
+                    );
+                    return;
+                }
+                let Some(buffer) = data.buffer.lock().unwrap().clone() else {
+                    frame.post_error(
+                        ext_image_copy_capture_frame_v1::Error::NoBuffer,
+                        "no buffer attached",
+                    );
+                    return;
+                };
+                let Some(session_data) = data.session.data::<SessionData>() else {
+                    frame.failed(FailureReason::Unknown);
+                    return;
+                };
+                let Some(source) = session_data.source.clone() else {
+                    frame.failed(FailureReason::Stopped);
+                    return;
+                };
+
+                let size = *session_data.buffer_size.lock().unwrap();
+                let valid = shm::with_buffer_contents(&buffer, |_, shm_len, buffer_data| {
+                    buffer_data.format == Format::Xrgb8888
+                        && buffer_data.width == size.w
+                        && buffer_data.height == size.h
+                        && buffer_data.stride == size.w * 4
+                        && shm_len == buffer_data.stride as usize * buffer_data.height as usize
+                })
+                .unwrap_or(false);
+                if !valid {
+                    frame.failed(FailureReason::BufferConstraints);
+                    return;
+                }
+
+                state.frame(CaptureFrame {
+                    buffer,
+                    frame: frame.clone(),
+                    session: data.session.clone(),
+                    source,
+                    buffer_size: size,
+                    submitted: false,
+                });
+            }
+            ext_image_copy_capture_frame_v1::Request::Destroy => {}
+            _ => {}
+        }
+    }
+}
+
+/// A frame a client asked to be copied into its shm buffer. Dropping it
+/// without `submit` fails the frame.
+pub struct CaptureFrame {
+    pub buffer: WlBuffer,
+    frame: ExtImageCopyCaptureFrameV1,
+    session: ExtImageCopyCaptureSessionV1,
+    source: CaptureSource,
+    buffer_size: Size<i32, Physical>,
+    submitted: bool,
+}
+
+impl Drop for CaptureFrame {
+    fn drop(&mut self) {
+        if !self.submitted {
+            self.frame.failed(FailureReason::Unknown);
+        }
+    }
+}
+
+impl CaptureFrame {
+    pub fn source(&self) -> &CaptureSource {
+        &self.source
+    }
+
+    /// Size of the client's buffer, as announced by the session.
+    pub fn buffer_size(&self) -> Size<i32, Physical> {
+        self.buffer_size
+    }
+
+    /// The source changed size: announce the new constraints and fail this
+    /// frame so the client reallocates.
+    pub fn resized(mut self, size: Size<i32, Physical>) {
+        if let Some(data) = self.session.data::<SessionData>() {
+            *data.buffer_size.lock().unwrap() = size;
+        }
+        send_constraints(&self.session, size);
+        self.frame.failed(FailureReason::BufferConstraints);
+        self.submitted = true;
+    }
+
+    /// The source went away for good; end the session.
+    pub fn stopped(mut self) {
+        self.session.stopped();
+        self.frame.failed(FailureReason::Stopped);
+        self.submitted = true;
+    }
+
+    /// Report the buffer as filled. The whole buffer counts as damaged.
+    pub fn submit(mut self, timestamp: Duration) {
+        self.frame.transform(Transform::Normal);
+        self.frame
+            .damage(0, 0, self.buffer_size.w, self.buffer_size.h);
+
+        let tv_sec_hi = (timestamp.as_secs() >> 32) as u32;
+        let tv_sec_lo = (timestamp.as_secs() & 0xFFFFFFFF) as u32;
+        self.frame
+            .presentation_time(tv_sec_hi, tv_sec_lo, timestamp.subsec_nanos());
+        self.frame.ready();
+
+        self.submitted = true;
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_image_copy_capture {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_copy_capture::v1::server::ext_image_copy_capture_manager_v1::ExtImageCopyCaptureManagerV1: $crate::protocols::image_copy_capture::ImageCopyCaptureGlobalData
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_capture_source::v1::server::ext_output_image_capture_source_manager_v1::ExtOutputImageCaptureSourceManagerV1: $crate::protocols::image_copy_capture::ImageCopyCaptureGlobalData
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_capture_source::v1::server::ext_foreign_toplevel_image_capture_source_manager_v1::ExtForeignToplevelImageCaptureSourceManagerV1: $crate::protocols::image_copy_capture::ImageCopyCaptureGlobalData
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_copy_capture::v1::server::ext_image_copy_capture_manager_v1::ExtImageCopyCaptureManagerV1: ()
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_capture_source::v1::server::ext_output_image_capture_source_manager_v1::ExtOutputImageCaptureSourceManagerV1: ()
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_capture_source::v1::server::ext_foreign_toplevel_image_capture_source_manager_v1::ExtForeignToplevelImageCaptureSourceManagerV1: ()
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_capture_source::v1::server::ext_image_capture_source_v1::ExtImageCaptureSourceV1: Option<$crate::protocols::image_copy_capture::CaptureSource>
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_copy_capture::v1::server::ext_image_copy_capture_session_v1::ExtImageCopyCaptureSessionV1: $crate::protocols::image_copy_capture::SessionData
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_copy_capture::v1::server::ext_image_copy_capture_cursor_session_v1::ExtImageCopyCaptureCursorSessionV1: ()
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::ext::image_copy_capture::v1::server::ext_image_copy_capture_frame_v1::ExtImageCopyCaptureFrameV1: $crate::protocols::image_copy_capture::FrameData
+        ] => $crate::protocols::image_copy_capture::ImageCopyCaptureState);
+    };
+}
@@ -1,3 +1,8 @@
 pub mod ext_workspace;
 pub mod foreign_toplevel;
+pub mod gamma_control;
+pub mod idle_inhibit;
+pub mod image_copy_capture;
+pub mod output_power;
+pub mod tearing_control;
 pub mod wlr_screencopy;
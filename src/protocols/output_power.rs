@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use smithay::output::Output;
+use smithay::reexports::wayland_protocols_wlr::output_power_management::v1::server::{
+    zwlr_output_power_manager_v1::{self, ZwlrOutputPowerManagerV1},
+    zwlr_output_power_v1::{self, Mode, ZwlrOutputPowerV1},
+};
+use smithay::reexports::wayland_server::backend::ClientId;
+use smithay::reexports::wayland_server::{
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource, WEnum,
+};
+
+const VERSION: u32 = 1;
+
+pub trait OutputPowerHandler {
+    fn output_power_state(&mut self) -> &mut OutputPowerManagerState;
+    /// Whether `output` is lit.
+    fn output_powered(&self, output: &Output) -> bool;
+    /// A client asked to turn `output` on or off; `false` fails its request.
+    /// On success call [`OutputPowerManagerState::power_changed`].
+    fn set_output_power(&mut self, output: &Output, on: bool) -> bool;
+}
+
+#[derive(Default)]
+pub struct OutputPowerManagerState {
+    controls: HashMap<ZwlrOutputPowerV1, Output>,
+}
+
+pub struct OutputPowerGlobalData {
+    filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+fn mode(on: bool) -> Mode {
+    if on { Mode::On } else { Mode::Off }
+}
+
+impl OutputPowerManagerState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<ZwlrOutputPowerManagerV1, OutputPowerGlobalData>,
+        D: Dispatch<ZwlrOutputPowerManagerV1, ()>,
+        D: 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+    {
+        let global_data = OutputPowerGlobalData {
+            filter: Box::new(filter),
+        };
+        display.create_global::<D, ZwlrOutputPowerManagerV1, _>(VERSION, global_data);
+
+        Self::default()
+    }
+
+    /// Tell every client watching `output` that it is now on or off.
+    pub fn power_changed(&self, output: &Output, on: bool) {
+        for (control, controlled) in &self.controls {
+            if controlled == output {
+                control.mode(mode(on));
+            }
+        }
+    }
+
+    /// Fail the controls of an output that went away; they are inert from now on.
+    pub fn output_removed(&mut self, output: &Output) {
+        self.controls.retain(|control, controlled| {
+            if controlled != output {
+                return true;
+            }
+            control.failed();
+            false
+        });
+    }
+}
+
+impl<D> GlobalDispatch<ZwlrOutputPowerManagerV1, OutputPowerGlobalData, D>
+    for OutputPowerManagerState
+where
+    D: GlobalDispatch<ZwlrOutputPowerManagerV1, OutputPowerGlobalData>,
+    D: Dispatch<ZwlrOutputPowerManagerV1, ()>,
+    D: 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<ZwlrOutputPowerManagerV1>,
+        _global_data: &OutputPowerGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+
+    fn can_view(client: Client, global_data: &OutputPowerGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<ZwlrOutputPowerManagerV1, (), D> for OutputPowerManagerState
+where
+    D: Dispatch<ZwlrOutputPowerManagerV1, ()>,
+    D: Dispatch<ZwlrOutputPowerV1, ()>,
+    D: OutputPowerHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        _resource: &ZwlrOutputPowerManagerV1,
+        request: <ZwlrOutputPowerManagerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwlr_output_power_manager_v1::Request::GetOutputPower { id, output } => {
+                let control = data_init.init(id, ());
+                let Some(output) = Output::from_resource(&output) else {
+                    control.failed();
+                    return;
+                };
+                control.mode(mode(state.output_powered(&output)));
+                state.output_power_state().controls.insert(control, output);
+            }
+            zwlr_output_power_manager_v1::Request::Destroy => {}
+            _ => {}
+        }
+    }
+}
+
+impl<D> Dispatch<ZwlrOutputPowerV1, (), D> for OutputPowerManagerState
+where
+    D: Dispatch<ZwlrOutputPowerV1, ()>,
+    D: OutputPowerHandler,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &ZwlrOutputPowerV1,
+        request: <ZwlrOutputPowerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            zwlr_output_power_v1::Request::SetMode { mode } => {
+                let Some(output) = state.output_power_state().controls.get(resource).cloned()
+                else {
+                    return;
+                };
+                let on = match mode {
+                    WEnum::Value(Mode::On) => true,
+                    WEnum::Value(Mode::Off) => false,
+                    _ => {
+                        resource.post_error(
+                            zwlr_output_power_v1::Error::InvalidMode,
+                            "unknown power mode",
+                        );
+                        return;
+                    }
+                };
+                if !state.set_output_power(&output, on) {
+                    state.output_power_state().controls.remove(resource);
+                    resource.failed();
+                }
+            }
+            zwlr_output_power_v1::Request::Destroy => {}
+            _ => {}
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, resource: &ZwlrOutputPowerV1, _data: &()) {
+        state.output_power_state().controls.remove(resource);
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_output_power {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols_wlr::output_power_management::v1::server::zwlr_output_power_manager_v1::ZwlrOutputPowerManagerV1: $crate::protocols::output_power::OutputPowerGlobalData
+        ] => $crate::protocols::output_power::OutputPowerManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols_wlr::output_power_management::v1::server::zwlr_output_power_manager_v1::ZwlrOutputPowerManagerV1: ()
+        ] => $crate::protocols::output_power::OutputPowerManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols_wlr::output_power_management::v1::server::zwlr_output_power_v1::ZwlrOutputPowerV1: ()
+        ] => $crate::protocols::output_power::OutputPowerManagerState);
+    };
+}
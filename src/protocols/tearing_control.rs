@@ -0,0 +1,161 @@
+use std::collections::HashMap;
+
+use smithay::reexports::wayland_protocols::wp::tearing_control::v1::server::{
+    wp_tearing_control_manager_v1::{self, WpTearingControlManagerV1},
+    wp_tearing_control_v1::{self, PresentationHint, WpTearingControlV1},
+};
+use smithay::reexports::wayland_server::backend::ClientId;
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+use smithay::reexports::wayland_server::{
+    Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New, Resource, WEnum,
+};
+
+const VERSION: u32 = 1;
+
+pub trait TearingControlHandler {
+    fn tearing_control_state(&mut self) -> &mut TearingControlManagerState;
+}
+
+/// Presentation hints of surfaces with a tearing control; one control per surface.
+/// Frames are always vblank-synced, so the hints are only recorded.
+#[derive(Default)]
+pub struct TearingControlManagerState {
+    controls: HashMap<WpTearingControlV1, (WlSurface, PresentationHint)>,
+}
+
+pub struct TearingControlGlobalData {
+    filter: Box<dyn for<'c> Fn(&'c Client) -> bool + Send + Sync>,
+}
+
+impl TearingControlManagerState {
+    pub fn new<D, F>(display: &DisplayHandle, filter: F) -> Self
+    where
+        D: GlobalDispatch<WpTearingControlManagerV1, TearingControlGlobalData>,
+        D: Dispatch<WpTearingControlManagerV1, ()>,
+        D: 'static,
+        F: for<'c> Fn(&'c Client) -> bool + Send + Sync + 'static,
+    {
+        let global_data = TearingControlGlobalData {
+            filter: Box::new(filter),
+        };
+        display.create_global::<D, WpTearingControlManagerV1, _>(VERSION, global_data);
+
+        Self::default()
+    }
+}
+
+impl<D> GlobalDispatch<WpTearingControlManagerV1, TearingControlGlobalData, D>
+    for TearingControlManagerState
+where
+    D: GlobalDispatch<WpTearingControlManagerV1, TearingControlGlobalData>,
+    D: Dispatch<WpTearingControlManagerV1, ()>,
+    D: 'static,
+{
+    fn bind(
+        _state: &mut D,
+        _handle: &DisplayHandle,
+        _client: &Client,
+        resource: New<WpTearingControlManagerV1>,
+        _global_data: &TearingControlGlobalData,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        data_init.init(resource, ());
+    }
+
+    fn can_view(client: Client, global_data: &TearingControlGlobalData) -> bool {
+        (global_data.filter)(&client)
+    }
+}
+
+impl<D> Dispatch<WpTearingControlManagerV1, (), D> for TearingControlManagerState
+where
+    D: Dispatch<WpTearingControlManagerV1, ()>,
+    D: Dispatch<WpTearingControlV1, ()>,
+    D: TearingControlHandler,
+    D: 'static,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &WpTearingControlManagerV1,
+        request: <WpTearingControlManagerV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            wp_tearing_control_manager_v1::Request::GetTearingControl { id, surface } => {
+                let exists = state
+                    .tearing_control_state()
+                    .controls
+                    .values()
+                    .any(|(controlled, _)| *controlled == surface);
+                if exists {
+                    resource.post_error(
+                        wp_tearing_control_manager_v1::Error::TearingControlExists,
+                        "surface already has a tearing control",
+                    );
+                    return;
+                }
+                let control = data_init.init(id, ());
+                state
+                    .tearing_control_state()
+                    .controls
+                    .insert(control, (surface, PresentationHint::Vsync));
+            }
+            wp_tearing_control_manager_v1::Request::Destroy => {}
+            _ => {}
+        }
+    }
+}
+
+impl<D> Dispatch<WpTearingControlV1, (), D> for TearingControlManagerState
+where
+    D: Dispatch<WpTearingControlV1, ()>,
+    D: TearingControlHandler,
+{
+    fn request(
+        state: &mut D,
+        _client: &Client,
+        resource: &WpTearingControlV1,
+        request: <WpTearingControlV1 as Resource>::Request,
+        _data: &(),
+        _dhandle: &DisplayHandle,
+        _data_init: &mut DataInit<'_, D>,
+    ) {
+        match request {
+            wp_tearing_control_v1::Request::SetPresentationHint { hint } => {
+                // Unknown hints fall back to vsync, the safe choice.
+                let hint = match hint {
+                    WEnum::Value(hint) => hint,
+                    WEnum::Unknown(_) => PresentationHint::Vsync,
+                };
+                if let Some((_, current)) = state.tearing_control_state().controls.get_mut(resource)
+                {
+                    *current = hint;
+                }
+            }
+            wp_tearing_control_v1::Request::Destroy => {}
+            _ => {}
+        }
+    }
+
+    fn destroyed(state: &mut D, _client: ClientId, resource: &WpTearingControlV1, _data: &()) {
+        state.tearing_control_state().controls.remove(resource);
+    }
+}
+
+#[macro_export]
+macro_rules! delegate_tearing_control {
+    ($(@<$( $lt:tt $( : $clt:tt $(+ $dlt:tt )* )? ),+>)? $ty: ty) => {
+        smithay::reexports::wayland_server::delegate_global_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::tearing_control::v1::server::wp_tearing_control_manager_v1::WpTearingControlManagerV1: $crate::protocols::tearing_control::TearingControlGlobalData
+        ] => $crate::protocols::tearing_control::TearingControlManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::tearing_control::v1::server::wp_tearing_control_manager_v1::WpTearingControlManagerV1: ()
+        ] => $crate::protocols::tearing_control::TearingControlManagerState);
+        smithay::reexports::wayland_server::delegate_dispatch!($(@< $( $lt $( : $clt $(+ $dlt )* )? ),+ >)? $ty: [
+            smithay::reexports::wayland_protocols::wp::tearing_control::v1::server::wp_tearing_control_v1::WpTearingControlV1: ()
+        ] => $crate::protocols::tearing_control::TearingControlManagerState);
+    };
+}
@@ -13,7 +13,7 @@ use smithay::reexports::wayland_server::protocol::wl_shm::Format;
 use smithay::reexports::wayland_server::{
     Client, DataInit, Dispatch, DisplayHandle, GlobalDispatch, New,
 };
-use smithay::utils::{Physical, Size};
+use smithay::utils::{Physical, Rectangle, Size};
 use smithay::wayland::shm;
 
 const SCREENCOPY_VERSION: u32 = 3;
@@ -108,7 +108,8 @@ where
             return;
         };
 
-        let buffer_size = mode.size;
+        // Frames are copied the way the output is shown, rotation included.
+        let buffer_size = output.current_transform().transform_size(mode.size);
         let info = ScreencopyFrameInfo {
             output,
             buffer_size,
@@ -178,9 +179,9 @@ where
             return;
         }
 
-        let buffer = match request {
-            zwlr_screencopy_frame_v1::Request::Copy { buffer } => buffer,
-            zwlr_screencopy_frame_v1::Request::CopyWithDamage { buffer } => buffer,
+        let (buffer, with_damage) = match request {
+            zwlr_screencopy_frame_v1::Request::Copy { buffer } => (buffer, false),
+            zwlr_screencopy_frame_v1::Request::CopyWithDamage { buffer } => (buffer, true),
             _ => unreachable!(),
         };
 
@@ -209,6 +210,7 @@ where
             buffer,
             frame: frame.clone(),
             info: info.clone(),
+            with_damage,
             submitted: false,
         });
     }
@@ -218,6 +220,7 @@ pub struct Screencopy {
     pub buffer: WlBuffer,
     frame: ZwlrScreencopyFrameV1,
     info: ScreencopyFrameInfo,
+    with_damage: bool,
     submitted: bool,
 }
 
@@ -238,6 +241,26 @@ impl Screencopy {
         self.info.buffer_size
     }
 
+    /// Whether the client asked for `copy_with_damage`: the frame should only be
+    /// served once something changed, with the changed regions reported.
+    pub fn with_damage(&self) -> bool {
+        self.with_damage
+    }
+
+    /// Report the regions that changed since the client's previous frame, in
+    /// buffer coordinates. Must be called before `submit`.
+    pub fn damage(&self, damage: &[Rectangle<i32, Physical>]) {
+        let bounds = Rectangle::from_size(self.info.buffer_size);
+        for rect in damage.iter().filter_map(|rect| rect.intersection(bounds)) {
+            self.frame.damage(
+                rect.loc.x as u32,
+                rect.loc.y as u32,
+                rect.size.w as u32,
+                rect.size.h as u32,
+            );
+        }
+    }
+
     pub fn submit(mut self, timestamp: Duration) {
         self.frame.flags(Flags::empty());
 
@@ -3,6 +3,13 @@
 //! This module provides rendering utilities that prevent element-specific
 //! black flickers.
 
+pub mod offscreen;
+pub mod rounded;
 pub mod solid_color;
+pub mod text;
 
+pub use offscreen::{OffscreenPool, OffscreenTarget};
+pub use rounded::{
+    RoundedCornerElement, RoundedPrograms, RoundedRectBuffer, RoundedRectElement, RoundedRenderer,
+};
 pub use solid_color::{SolidColorBuffer, SolidColorRenderElement};
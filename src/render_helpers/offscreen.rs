@@ -0,0 +1,120 @@
+//! Pool of reusable offscreen GLES textures.
+//!
+//! Captures, previews and anything else rendered outside an output framebuffer
+//! lease a texture from the pool instead of allocating one per frame. A lease
+//! returns its texture to the pool when dropped; free textures that sit unused
+//! are evicted the next time the pool is asked for one.
+//!
+//! Textures belong to the EGL context of the renderer that created them, so keep
+//! one pool per renderer.
+
+use std::{
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use smithay::backend::allocator::Fourcc;
+use smithay::backend::renderer::{Offscreen, gles::GlesTexture};
+use smithay::utils::{Buffer, Size};
+
+/// Free textures not handed out for this long are dropped.
+const IDLE_EVICTION: Duration = Duration::from_secs(30);
+/// Free textures kept at most; the least recently used are dropped first.
+const MAX_FREE_TARGETS: usize = 4;
+
+struct PooledTarget {
+    texture: GlesTexture,
+    format: Fourcc,
+    size: Size<i32, Buffer>,
+    // Shared with the outstanding lease, if any.
+    lease: Rc<()>,
+    last_used: Instant,
+}
+
+impl PooledTarget {
+    fn leased(&self) -> bool {
+        Rc::strong_count(&self.lease) > 1
+    }
+}
+
+/// A texture leased from an [`OffscreenPool`]. Its previous contents are
+/// undefined, so render with a full clear.
+pub struct OffscreenTarget {
+    texture: GlesTexture,
+    _lease: Rc<()>,
+}
+
+impl OffscreenTarget {
+    pub fn texture(&mut self) -> &mut GlesTexture {
+        &mut self.texture
+    }
+}
+
+#[derive(Default)]
+pub struct OffscreenPool {
+    targets: Vec<PooledTarget>,
+}
+
+impl OffscreenPool {
+    /// Lease a texture of `format` and `size`, reusing a free one when possible.
+    pub fn acquire<R: Offscreen<GlesTexture>>(
+        &mut self,
+        renderer: &mut R,
+        format: Fourcc,
+        size: Size<i32, Buffer>,
+    ) -> Result<OffscreenTarget, R::Error> {
+        self.evict();
+        let now = Instant::now();
+
+        if let Some(target) = self
+            .targets
+            .iter_mut()
+            .find(|target| !target.leased() && target.format == format && target.size == size)
+        {
+            target.last_used = now;
+            return Ok(OffscreenTarget {
+                texture: target.texture.clone(),
+                _lease: Rc::clone(&target.lease),
+            });
+        }
+
+        let texture = renderer.create_buffer(format, size)?;
+        let lease = Rc::new(());
+        tracing::trace!(
+            ?format,
+            ?size,
+            pooled = self.targets.len(),
+            "new offscreen target"
+        );
+        self.targets.push(PooledTarget {
+            texture: texture.clone(),
+            format,
+            size,
+            lease: Rc::clone(&lease),
+            last_used: now,
+        });
+        Ok(OffscreenTarget {
+            texture,
+            _lease: lease,
+        })
+    }
+
+    fn evict(&mut self) {
+        self.targets
+            .retain(|target| target.leased() || target.last_used.elapsed() < IDLE_EVICTION);
+
+        let mut free: Vec<Instant> = self
+            .targets
+            .iter()
+            .filter(|target| !target.leased())
+            .map(|target| target.last_used)
+            .collect();
+        if free.len() <= MAX_FREE_TARGETS {
+            return;
+        }
+        free.sort_unstable();
+        let cutoff = free[free.len() - MAX_FREE_TARGETS];
+        self.targets
+            .retain(|target| target.leased() || target.last_used >= cutoff);
+    }
+}
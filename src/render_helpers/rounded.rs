@@ -0,0 +1,426 @@
+//! Rounded window corners.
+//!
+//! [`RoundedCornerElement`] wraps a window surface element and draws it with a
+//! texture shader that clips it to the window geometry with rounded corners.
+//! [`RoundedRectElement`] draws a rounded rect with a pixel shader, as the
+//! outline behind a rounded window (its border) or filled in front of it (its
+//! dim veil), so neither pokes out past the clipped corners.
+//!
+//! The shaders are compiled once per EGL context. When they fail to compile,
+//! [`RoundedPrograms::get`] returns `None` and windows are drawn square.
+
+use smithay::backend::renderer::element::{Element, Id, Kind, RenderElement, UnderlyingStorage};
+use smithay::backend::renderer::gles::{
+    GlesError, GlesFrame, GlesPixelProgram, GlesRenderer, GlesTexProgram, Uniform, UniformName,
+    UniformType,
+};
+use smithay::backend::renderer::utils::{CommitCounter, DamageSet, OpaqueRegions};
+use smithay::backend::renderer::{Color32F, Renderer};
+use smithay::utils::{Buffer, Logical, Physical, Point, Rectangle, Scale, Size, Transform};
+
+const CORNERS_SHADER: &str = include_str!("shaders/rounded_corners.frag");
+const RECT_SHADER: &str = include_str!("shaders/rounded_rect.frag");
+
+/// Renderers whose frames are GLES frames underneath, so elements can draw
+/// with Raven's shaders.
+pub trait RoundedRenderer: Renderer {
+    fn gles_renderer(&mut self) -> &mut GlesRenderer;
+
+    fn gles_error(err: GlesError) -> Self::Error;
+
+    fn gles_frame<'a, 'frame, 'buffer>(
+        frame: &'a mut Self::Frame<'frame, 'buffer>,
+    ) -> &'a mut GlesFrame<'frame, 'buffer>
+    where
+        Self: 'frame,
+        'buffer: 'frame;
+}
+
+impl RoundedRenderer for GlesRenderer {
+    fn gles_renderer(&mut self) -> &mut GlesRenderer {
+        self
+    }
+
+    fn gles_error(err: GlesError) -> GlesError {
+        err
+    }
+
+    fn gles_frame<'a, 'frame, 'buffer>(
+        frame: &'a mut GlesFrame<'frame, 'buffer>,
+    ) -> &'a mut GlesFrame<'frame, 'buffer>
+    where
+        Self: 'frame,
+        'buffer: 'frame,
+    {
+        frame
+    }
+}
+
+/// The compiled shaders, kept in the EGL context's user data.
+#[derive(Clone)]
+pub struct RoundedPrograms {
+    pub corners: GlesTexProgram,
+    pub rect: GlesPixelProgram,
+}
+
+// What compiling gave, so a broken driver is only tried (and logged) once.
+struct CompiledPrograms(Option<RoundedPrograms>);
+
+impl RoundedPrograms {
+    /// The shaders for `renderer`'s context, compiling them on first use.
+    pub fn get(renderer: &mut GlesRenderer) -> Option<Self> {
+        if renderer
+            .egl_context()
+            .user_data()
+            .get::<CompiledPrograms>()
+            .is_none()
+        {
+            let compiled = Self::compile(renderer)
+                .inspect_err(|err| tracing::warn!("rounded corner shaders failed: {err:?}"))
+                .ok();
+            renderer
+                .egl_context()
+                .user_data()
+                .insert_if_missing(|| CompiledPrograms(compiled));
+        }
+        renderer
+            .egl_context()
+            .user_data()
+            .get::<CompiledPrograms>()
+            .and_then(|compiled| compiled.0.clone())
+    }
+
+    fn compile(renderer: &mut GlesRenderer) -> Result<Self, GlesError> {
+        let corners = renderer.compile_custom_texture_shader(
+            CORNERS_SHADER,
+            &[
+                UniformName::new("geo_size", UniformType::_2f),
+                UniformName::new("corner_radius", UniformType::_1f),
+                UniformName::new("input_scale", UniformType::_2f),
+                UniformName::new("input_offset", UniformType::_2f),
+            ],
+        )?;
+        let rect = renderer.compile_custom_pixel_shader(
+            RECT_SHADER,
+            &[
+                UniformName::new("color", UniformType::_4f),
+                UniformName::new("corner_radius", UniformType::_1f),
+                UniformName::new("border_width", UniformType::_1f),
+            ],
+        )?;
+        Ok(Self { corners, rect })
+    }
+}
+
+/// A window surface element clipped to the window's rounded geometry.
+#[derive(Debug)]
+pub struct RoundedCornerElement<E> {
+    inner: E,
+    program: GlesTexProgram,
+    // Window geometry in the physical space the inner element is placed in.
+    window: Rectangle<i32, Physical>,
+    radius: f32,
+    // Size of the texture the inner element samples.
+    texture_size: Size<i32, Buffer>,
+}
+
+impl<E> RoundedCornerElement<E> {
+    /// Clip `inner`, which samples a texture of `texture_size` without a buffer
+    /// transform, to `window` with corners of `radius` physical pixels.
+    pub fn new(
+        inner: E,
+        program: GlesTexProgram,
+        window: Rectangle<i32, Physical>,
+        radius: f32,
+        texture_size: Size<i32, Buffer>,
+    ) -> Self {
+        Self {
+            inner,
+            program,
+            window,
+            radius,
+            texture_size,
+        }
+    }
+
+    fn uniforms(
+        &self,
+        src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+    ) -> Vec<Uniform<'static>> {
+        // A texture coordinate `v` lands at `dst.loc + (v * texture - src.loc) /
+        // src.size * dst.size`; shift that into the window's own space.
+        let axis = |texture: i32,
+                    src_loc: f64,
+                    src_size: f64,
+                    dst_loc: i32,
+                    dst_size: i32,
+                    window_loc: i32| {
+            let per_src = if src_size > 0.0 {
+                f64::from(dst_size) / src_size
+            } else {
+                0.0
+            };
+            let scale = f64::from(texture) * per_src;
+            let offset = f64::from(dst_loc - window_loc) - src_loc * per_src;
+            (scale as f32, offset as f32)
+        };
+        let (scale_x, offset_x) = axis(
+            self.texture_size.w,
+            src.loc.x,
+            src.size.w,
+            dst.loc.x,
+            dst.size.w,
+            self.window.loc.x,
+        );
+        let (scale_y, offset_y) = axis(
+            self.texture_size.h,
+            src.loc.y,
+            src.size.h,
+            dst.loc.y,
+            dst.size.h,
+            self.window.loc.y,
+        );
+        vec![
+            Uniform::new(
+                "geo_size",
+                [self.window.size.w as f32, self.window.size.h as f32],
+            ),
+            Uniform::new("corner_radius", self.radius),
+            Uniform::new("input_scale", [scale_x, scale_y]),
+            Uniform::new("input_offset", [offset_x, offset_y]),
+        ]
+    }
+}
+
+impl<E: Element> Element for RoundedCornerElement<E> {
+    fn id(&self) -> &Id {
+        self.inner.id()
+    }
+
+    fn current_commit(&self) -> CommitCounter {
+        self.inner.current_commit()
+    }
+
+    fn location(&self, scale: Scale<f64>) -> Point<i32, Physical> {
+        self.inner.location(scale)
+    }
+
+    fn src(&self) -> Rectangle<f64, Buffer> {
+        self.inner.src()
+    }
+
+    fn transform(&self) -> Transform {
+        self.inner.transform()
+    }
+
+    fn geometry(&self, scale: Scale<f64>) -> Rectangle<i32, Physical> {
+        self.inner.geometry(scale)
+    }
+
+    fn damage_since(
+        &self,
+        scale: Scale<f64>,
+        commit: Option<CommitCounter>,
+    ) -> DamageSet<i32, Physical> {
+        self.inner.damage_since(scale, commit)
+    }
+
+    fn opaque_regions(&self, scale: Scale<f64>) -> OpaqueRegions<i32, Physical> {
+        // The clipped corners show what is below.
+        let geometry = self.inner.geometry(scale);
+        let radius = self.radius.ceil() as i32;
+        let window = Rectangle::new(self.window.loc - geometry.loc, self.window.size);
+        let corner = Size::from((radius, radius));
+        let (right, bottom) = (
+            window.loc.x + window.size.w - radius,
+            window.loc.y + window.size.h - radius,
+        );
+        let corners = [
+            Rectangle::new(window.loc, corner),
+            Rectangle::new((right, window.loc.y).into(), corner),
+            Rectangle::new((window.loc.x, bottom).into(), corner),
+            Rectangle::new((right, bottom).into(), corner),
+        ];
+        let opaque = Rectangle::subtract_rects_many(
+            self.inner.opaque_regions(scale).iter().copied(),
+            corners,
+        );
+        OpaqueRegions::from_slice(&opaque)
+    }
+
+    fn alpha(&self) -> f32 {
+        self.inner.alpha()
+    }
+
+    fn kind(&self) -> Kind {
+        self.inner.kind()
+    }
+}
+
+impl<R: RoundedRenderer, E: RenderElement<R>> RenderElement<R> for RoundedCornerElement<E> {
+    fn draw(
+        &self,
+        frame: &mut R::Frame<'_, '_>,
+        src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+        opaque_regions: &[Rectangle<i32, Physical>],
+    ) -> Result<(), R::Error> {
+        R::gles_frame(frame)
+            .override_default_tex_program(self.program.clone(), self.uniforms(src, dst));
+        let result = self.inner.draw(frame, src, dst, damage, opaque_regions);
+        R::gles_frame(frame).clear_tex_program_override();
+        result
+    }
+
+    fn underlying_storage(&self, _renderer: &mut R) -> Option<UnderlyingStorage<'_>> {
+        // Scanning the buffer out directly would skip the clipping.
+        None
+    }
+}
+
+/// A rounded rect kept across frames, like a
+/// [`SolidColorBuffer`](super::SolidColorBuffer): damage follows its commit.
+#[derive(Debug, Clone)]
+pub struct RoundedRectBuffer {
+    id: Id,
+    commit: CommitCounter,
+    size: Size<f64, Logical>,
+    color: Color32F,
+    radius: f32,
+    border_width: f32,
+}
+
+impl Default for RoundedRectBuffer {
+    fn default() -> Self {
+        Self {
+            id: Id::new(),
+            commit: CommitCounter::default(),
+            size: Size::default(),
+            color: Color32F::default(),
+            radius: 0.0,
+            border_width: 0.0,
+        }
+    }
+}
+
+impl RoundedRectBuffer {
+    /// Update the rect. `radius` is that of the inner edge; with a
+    /// `border_width` the rect is an outline that wide around it, otherwise it
+    /// is filled. All in logical pixels.
+    pub fn update(
+        &mut self,
+        size: impl Into<Size<f64, Logical>>,
+        color: impl Into<Color32F>,
+        radius: f32,
+        border_width: f32,
+    ) {
+        let size = size.into();
+        let color = color.into();
+        if size != self.size
+            || color != self.color
+            || radius != self.radius
+            || border_width != self.border_width
+        {
+            self.size = size;
+            self.color = color;
+            self.radius = radius;
+            self.border_width = border_width;
+            self.commit.increment();
+        }
+    }
+}
+
+/// A render element for a [`RoundedRectBuffer`].
+#[derive(Debug, Clone)]
+pub struct RoundedRectElement {
+    buffer: RoundedRectBuffer,
+    program: GlesPixelProgram,
+    location: Point<f64, Logical>,
+    alpha: f32,
+    kind: Kind,
+}
+
+impl RoundedRectElement {
+    pub fn from_buffer(
+        buffer: &RoundedRectBuffer,
+        program: GlesPixelProgram,
+        location: impl Into<Point<f64, Logical>>,
+        alpha: f32,
+        kind: Kind,
+    ) -> Self {
+        Self {
+            buffer: buffer.clone(),
+            program,
+            location: location.into(),
+            alpha,
+            kind,
+        }
+    }
+}
+
+impl Element for RoundedRectElement {
+    fn id(&self) -> &Id {
+        &self.buffer.id
+    }
+
+    fn current_commit(&self) -> CommitCounter {
+        self.buffer.commit
+    }
+
+    fn src(&self) -> Rectangle<f64, Buffer> {
+        Rectangle::from_size(self.buffer.size.to_buffer(1.0, Transform::Normal))
+    }
+
+    fn geometry(&self, scale: Scale<f64>) -> Rectangle<i32, Physical> {
+        Rectangle::new(self.location, self.buffer.size).to_physical_precise_round(scale)
+    }
+
+    fn alpha(&self) -> f32 {
+        self.alpha
+    }
+
+    fn kind(&self) -> Kind {
+        self.kind
+    }
+}
+
+impl<R: RoundedRenderer> RenderElement<R> for RoundedRectElement {
+    fn draw(
+        &self,
+        frame: &mut R::Frame<'_, '_>,
+        src: Rectangle<f64, Buffer>,
+        dst: Rectangle<i32, Physical>,
+        damage: &[Rectangle<i32, Physical>],
+        _opaque_regions: &[Rectangle<i32, Physical>],
+    ) -> Result<(), R::Error> {
+        // The shader works in logical pixels: `size` is the logical size, so
+        // the radius and border width need no scaling.
+        let size = self
+            .buffer
+            .size
+            .to_buffer(1.0, Transform::Normal)
+            .to_i32_round();
+        let color = self.buffer.color;
+        R::gles_frame(frame)
+            .render_pixel_shader_to(
+                &self.program,
+                src,
+                dst,
+                size,
+                Some(damage),
+                self.alpha,
+                &[
+                    Uniform::new("color", [color.r(), color.g(), color.b(), color.a()]),
+                    Uniform::new("corner_radius", self.buffer.radius),
+                    Uniform::new("border_width", self.buffer.border_width),
+                ],
+            )
+            .map_err(R::gles_error)
+    }
+
+    fn underlying_storage(&self, _renderer: &mut R) -> Option<UnderlyingStorage<'_>> {
+        None
+    }
+}
@@ -0,0 +1,69 @@
+#version 100
+
+//_DEFINES_
+
+#if defined(EXTERNAL)
+#extension GL_OES_EGL_image_external : require
+#endif
+
+precision highp float;
+
+#if defined(EXTERNAL)
+uniform samplerExternalOES tex;
+#else
+uniform sampler2D tex;
+#endif
+
+uniform float alpha;
+varying vec2 v_coords;
+
+#if defined(DEBUG_FLAGS)
+uniform float tint;
+#endif
+
+// Window geometry size and corner radius, in physical pixels.
+uniform vec2 geo_size;
+uniform float corner_radius;
+// Maps texture coordinates to pixels relative to the window geometry.
+uniform vec2 input_scale;
+uniform vec2 input_offset;
+
+// Coverage of `coords` by a rect of `size` at the origin with rounded corners.
+float rounded_alpha(vec2 coords, vec2 size, float radius) {
+    if (coords.x < 0.0 || coords.y < 0.0 || coords.x > size.x || coords.y > size.y) {
+        return 0.0;
+    }
+
+    vec2 center;
+    if (coords.x < radius && coords.y < radius) {
+        center = vec2(radius, radius);
+    } else if (coords.x > size.x - radius && coords.y < radius) {
+        center = vec2(size.x - radius, radius);
+    } else if (coords.x > size.x - radius && coords.y > size.y - radius) {
+        center = vec2(size.x - radius, size.y - radius);
+    } else if (coords.x < radius && coords.y > size.y - radius) {
+        center = vec2(radius, size.y - radius);
+    } else {
+        return 1.0;
+    }
+
+    float dist = distance(coords, center);
+    return 1.0 - smoothstep(radius - 0.5, radius + 0.5, dist);
+}
+
+void main() {
+    vec4 color = texture2D(tex, v_coords);
+#if defined(NO_ALPHA)
+    color = vec4(color.rgb, 1.0);
+#endif
+
+    vec2 coords = v_coords * input_scale + input_offset;
+    color = color * alpha * rounded_alpha(coords, geo_size, corner_radius);
+
+#if defined(DEBUG_FLAGS)
+    if (tint == 1.0)
+        color = vec4(0.0, 0.2, 0.0, 0.2) + color * 0.8;
+#endif
+
+    gl_FragColor = color;
+}
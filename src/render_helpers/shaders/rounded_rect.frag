@@ -0,0 +1,59 @@
+#version 100
+
+//_DEFINES_
+
+precision highp float;
+
+uniform float alpha;
+uniform vec2 size;
+varying vec2 v_coords;
+
+#if defined(DEBUG_FLAGS)
+uniform float tint;
+#endif
+
+// Premultiplied RGBA.
+uniform vec4 color;
+// Radius of the inner edge, and width of the outline; 0 fills the rect.
+uniform float corner_radius;
+uniform float border_width;
+
+// Coverage of `coords` by a rect of `size` at the origin with rounded corners.
+float rounded_alpha(vec2 coords, vec2 size, float radius) {
+    if (coords.x < 0.0 || coords.y < 0.0 || coords.x > size.x || coords.y > size.y) {
+        return 0.0;
+    }
+
+    vec2 center;
+    if (coords.x < radius && coords.y < radius) {
+        center = vec2(radius, radius);
+    } else if (coords.x > size.x - radius && coords.y < radius) {
+        center = vec2(size.x - radius, radius);
+    } else if (coords.x > size.x - radius && coords.y > size.y - radius) {
+        center = vec2(size.x - radius, size.y - radius);
+    } else if (coords.x < radius && coords.y > size.y - radius) {
+        center = vec2(radius, size.y - radius);
+    } else {
+        return 1.0;
+    }
+
+    float dist = distance(coords, center);
+    return 1.0 - smoothstep(radius - 0.5, radius + 0.5, dist);
+}
+
+void main() {
+    vec2 coords = v_coords * size;
+    float coverage = rounded_alpha(coords, size, corner_radius + border_width);
+    if (border_width > 0.0) {
+        vec2 inset = vec2(border_width, border_width);
+        coverage *= 1.0 - rounded_alpha(coords - inset, size - 2.0 * inset, corner_radius);
+    }
+    vec4 frag = color * alpha * coverage;
+
+#if defined(DEBUG_FLAGS)
+    if (tint == 1.0)
+        frag = vec4(0.0, 0.2, 0.0, 0.2) + frag * 0.8;
+#endif
+
+    gl_FragColor = frag;
+}
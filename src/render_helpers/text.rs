@@ -0,0 +1,191 @@
+//! Minimal bitmap text rendering built from solid color rectangles.
+//!
+//! This is meant for debug overlays: a fixed 5x7 uppercase font with no
+//! external font dependency. Lowercase input is drawn in uppercase and
+//! unsupported characters are drawn as `?`.
+
+use smithay::backend::renderer::Color32F;
+use smithay::backend::renderer::element::{Id, Kind};
+use smithay::backend::renderer::utils::CommitCounter;
+use smithay::utils::{Logical, Point, Rectangle, Size};
+
+use super::SolidColorRenderElement;
+
+const GLYPH_WIDTH: usize = 5;
+const GLYPH_HEIGHT: usize = 7;
+const GLYPH_ADVANCE: f64 = (GLYPH_WIDTH + 1) as f64;
+const LINE_ADVANCE: f64 = (GLYPH_HEIGHT + 2) as f64;
+
+#[rustfmt::skip]
+fn glyph(ch: char) -> [u8; GLYPH_HEIGHT] {
+    match ch.to_ascii_uppercase() {
+        '0' => [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110],
+        '1' => [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        '2' => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111],
+        '3' => [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110],
+        '4' => [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010],
+        '5' => [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110],
+        '6' => [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110],
+        '7' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000],
+        '8' => [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110],
+        '9' => [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100],
+        'A' => [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'B' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110],
+        'C' => [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110],
+        'D' => [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100],
+        'E' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111],
+        'F' => [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000],
+        'G' => [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111],
+        'H' => [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001],
+        'I' => [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110],
+        'J' => [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100],
+        'K' => [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001],
+        'L' => [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111],
+        'M' => [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001],
+        'N' => [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001],
+        'O' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'P' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000],
+        'Q' => [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101],
+        'R' => [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001],
+        'S' => [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110],
+        'T' => [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100],
+        'U' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110],
+        'V' => [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100],
+        'W' => [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010],
+        'X' => [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001],
+        'Y' => [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100],
+        'Z' => [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111],
+        ' ' => [0; GLYPH_HEIGHT],
+        '#' => [0b01010, 0b01010, 0b11111, 0b01010, 0b11111, 0b01010, 0b01010],
+        '+' => [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000],
+        '-' => [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000],
+        '=' => [0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000],
+        '.' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100],
+        ',' => [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000],
+        ':' => [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000],
+        '_' => [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111],
+        '/' => [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000],
+        '(' => [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010],
+        ')' => [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000],
+        '[' => [0b01110, 0b01000, 0b01000, 0b01000, 0b01000, 0b01000, 0b01110],
+        ']' => [0b01110, 0b00010, 0b00010, 0b00010, 0b00010, 0b00010, 0b01110],
+        '{' => [0b00110, 0b00100, 0b00100, 0b01000, 0b00100, 0b00100, 0b00110],
+        '}' => [0b01100, 0b00100, 0b00100, 0b00010, 0b00100, 0b00100, 0b01100],
+        '<' => [0b00010, 0b00100, 0b01000, 0b10000, 0b01000, 0b00100, 0b00010],
+        '>' => [0b01000, 0b00100, 0b00010, 0b00001, 0b00010, 0b00100, 0b01000],
+        '!' => [0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00000, 0b00100],
+        ';' => [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b00100, 0b01000],
+        '*' => [0b00000, 0b00100, 0b10101, 0b01110, 0b10101, 0b00100, 0b00000],
+        '\'' | '`' => [0b00100, 0b00100, 0b01000, 0b00000, 0b00000, 0b00000, 0b00000],
+        '"' => [0b01010, 0b01010, 0b10100, 0b00000, 0b00000, 0b00000, 0b00000],
+        _ => [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100],
+    }
+}
+
+/// Size of `text` when drawn with square pixels of `pixel_size` logical units.
+///
+/// Lines are split on `\n`; the result covers the widest line.
+pub fn text_size(text: &str, pixel_size: f64) -> Size<f64, Logical> {
+    let lines = text.lines().count().max(1);
+    let columns = text
+        .lines()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let width = (columns as f64 * GLYPH_ADVANCE - 1.0).max(0.0) * pixel_size;
+    let height = (lines as f64 * LINE_ADVANCE - 2.0) * pixel_size;
+    Size::from((width, height))
+}
+
+/// Text drawn frame after frame, such as an overlay label.
+///
+/// The glyph runs keep their element ids and share one commit counter that only
+/// moves when the text, pixel size or color changes, so text that stays put
+/// adds no damage.
+#[derive(Debug, Default)]
+pub struct TextBuffer {
+    drawn: Option<(String, f64, Color32F)>,
+    commit: CommitCounter,
+    ids: Vec<Id>,
+}
+
+impl TextBuffer {
+    /// Render elements drawing `text` with its top-left corner at `location`.
+    ///
+    /// Each horizontal run of lit glyph pixels becomes one solid color element,
+    /// so the element count stays proportional to the text length.
+    pub fn elements(
+        &mut self,
+        text: &str,
+        location: impl Into<Point<f64, Logical>>,
+        pixel_size: f64,
+        color: impl Into<Color32F>,
+    ) -> Vec<SolidColorRenderElement> {
+        let color = color.into();
+        let unchanged = self
+            .drawn
+            .as_ref()
+            .is_some_and(|(drawn, size, drawn_color)| {
+                drawn == text && *size == pixel_size && *drawn_color == color
+            });
+        if !unchanged {
+            self.drawn = Some((text.to_owned(), pixel_size, color));
+            self.commit.increment();
+        }
+
+        let runs = text_runs(text, location.into(), pixel_size);
+        if self.ids.len() < runs.len() {
+            self.ids.resize_with(runs.len(), Id::new);
+        }
+        runs.into_iter()
+            .zip(&self.ids)
+            .map(|(rect, id)| {
+                SolidColorRenderElement::new(
+                    id.clone(),
+                    rect,
+                    self.commit,
+                    color,
+                    Kind::Unspecified,
+                )
+            })
+            .collect()
+    }
+}
+
+/// The horizontal runs of lit glyph pixels of `text` drawn at `origin`.
+fn text_runs(
+    text: &str,
+    origin: Point<f64, Logical>,
+    pixel_size: f64,
+) -> Vec<Rectangle<f64, Logical>> {
+    let mut runs = Vec::new();
+
+    for (line_index, line) in text.lines().enumerate() {
+        let line_y = origin.y + line_index as f64 * LINE_ADVANCE * pixel_size;
+        for (column, ch) in line.chars().enumerate() {
+            let glyph_x = origin.x + column as f64 * GLYPH_ADVANCE * pixel_size;
+            for (row, bits) in glyph(ch).into_iter().enumerate() {
+                let mut col = 0;
+                while col < GLYPH_WIDTH {
+                    if bits & (1 << (GLYPH_WIDTH - 1 - col)) == 0 {
+                        col += 1;
+                        continue;
+                    }
+                    let run_start = col;
+                    while col < GLYPH_WIDTH && bits & (1 << (GLYPH_WIDTH - 1 - col)) != 0 {
+                        col += 1;
+                    }
+                    runs.push(Rectangle::new(
+                        Point::from((
+                            glyph_x + run_start as f64 * pixel_size,
+                            line_y + row as f64 * pixel_size,
+                        )),
+                        Size::from(((col - run_start) as f64 * pixel_size, pixel_size)),
+                    ));
+                }
+            }
+        }
+    }
+
+    runs
+}
@@ -0,0 +1,23 @@
+//! Built-in screencasting: an `org.freedesktop.impl.portal.ScreenCast` backend
+//! that streams outputs and windows to PipeWire as dmabufs, so browsers and
+//! OBS can share the screen through xdg-desktop-portal without
+//! xdg-desktop-portal-wlr.
+//!
+//! Only the DRM backend feeds streams; casts render from the same elements as
+//! the output's frame.
+
+mod portal;
+mod stream;
+
+pub use portal::{
+    CastSource, PortalConnection, PortalRequest, SOURCE_MONITOR, SOURCE_WINDOW, SourceList,
+    StreamInfo,
+};
+pub use stream::{Cast, CastTarget, PipeWire};
+
+/// The portal's bus connection with the casts it started.
+pub struct Screencast {
+    pub portal: PortalConnection,
+    pub pipewire: PipeWire,
+    pub casts: Vec<Cast>,
+}
@@ -0,0 +1,355 @@
+//! The `org.freedesktop.impl.portal.ScreenCast` interface on the session bus.
+//!
+//! xdg-desktop-portal forwards ScreenCast calls here when `raven-portals.conf`
+//! routes them to `raven`. Methods run on zbus's executor thread and reach the
+//! compositor as `PortalRequest`s over a calloop channel.
+
+use std::{
+    collections::HashMap,
+    io::Write,
+    process::{Command, Stdio},
+    sync::{Arc, Mutex},
+};
+
+use smithay::reexports::calloop::channel::Sender;
+use zbus::{
+    interface,
+    object_server::{ObjectServer, SignalEmitter},
+    zvariant::{OwnedObjectPath, OwnedValue, Value},
+};
+
+const BUS_NAME: &str = "org.freedesktop.impl.portal.desktop.raven";
+const OBJECT_PATH: &str = "/org/freedesktop/portal/desktop";
+
+const RESPONSE_SUCCESS: u32 = 0;
+const RESPONSE_CANCELLED: u32 = 1;
+const RESPONSE_OTHER: u32 = 2;
+
+/// `types` bit of a monitor source.
+pub const SOURCE_MONITOR: u32 = 1;
+/// `types` bit of a window source.
+pub const SOURCE_WINDOW: u32 = 2;
+// The pointer is drawn into the frames.
+const CURSOR_EMBEDDED: u32 = 2;
+
+/// Something a cast can show.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CastSource {
+    /// An output, by connector name.
+    Output(String),
+    /// A toplevel, by the id `raven clients` lists.
+    Window(u64),
+}
+
+impl CastSource {
+    /// Parse a line printed by the chooser: `output:<name>` or `window:<id>`,
+    /// optionally followed by the label it was offered with.
+    fn parse(line: &str) -> Option<Self> {
+        let (kind, rest) = line.trim().split_once(':')?;
+        let value = rest.split_whitespace().next()?;
+        match kind {
+            "output" => Some(Self::Output(value.to_owned())),
+            "window" => value.parse().ok().map(Self::Window),
+            _ => None,
+        }
+    }
+
+    pub fn source_type(&self) -> u32 {
+        match self {
+            Self::Output(_) => SOURCE_MONITOR,
+            Self::Window(_) => SOURCE_WINDOW,
+        }
+    }
+}
+
+/// Sources on offer, with the chooser command that picks among them.
+pub struct SourceList {
+    pub chooser: Option<String>,
+    /// Each source with a human-readable label.
+    pub sources: Vec<(CastSource, String)>,
+}
+
+/// A started stream, as reported back to the portal frontend.
+pub struct StreamInfo {
+    pub node_id: u32,
+    pub position: (i32, i32),
+    pub size: (i32, i32),
+    pub source_type: u32,
+}
+
+pub enum PortalRequest {
+    Sources(async_channel::Sender<SourceList>),
+    /// Start casting the `source` the user picked.
+    Start {
+        session: String,
+        source: CastSource,
+        reply: async_channel::Sender<Result<StreamInfo, String>>,
+    },
+    Stop {
+        session: String,
+    },
+}
+
+// Source types each open session selected, by session handle.
+type Sessions = Arc<Mutex<HashMap<String, u32>>>;
+
+/// The bus connection owning the portal name; dropping it releases the name.
+pub struct PortalConnection {
+    connection: zbus::blocking::Connection,
+    sessions: Sessions,
+}
+
+impl PortalConnection {
+    pub fn new(requests: Sender<PortalRequest>) -> zbus::Result<Self> {
+        let sessions = Sessions::default();
+        let screencast = ScreenCast {
+            requests,
+            sessions: sessions.clone(),
+        };
+        let connection = zbus::blocking::connection::Builder::session()?
+            .name(BUS_NAME)?
+            .serve_at(OBJECT_PATH, screencast)?
+            .build()?;
+        Ok(Self {
+            connection,
+            sessions,
+        })
+    }
+
+    /// Tell the frontend that `session` ended on the compositor's side, e.g.
+    /// because its output was unplugged or its window closed. The signal goes
+    /// out on zbus's executor, so the compositor never waits on the bus.
+    pub fn close_session(&self, session: &str) {
+        forget_session(&self.sessions, session);
+        let connection = self.connection.inner().clone();
+        let session = session.to_owned();
+        self.connection
+            .inner()
+            .executor()
+            .spawn(
+                async move {
+                    let server = connection.object_server();
+                    let Ok(iface) = server.interface::<_, Session>(session.as_str()).await else {
+                        return;
+                    };
+                    if let Err(err) = Session::closed(iface.signal_emitter()).await {
+                        tracing::warn!(session, "failed to signal screencast session close: {err}");
+                    }
+                    if let Err(err) = server.remove::<Session, _>(session.as_str()).await {
+                        tracing::warn!(session, "failed to remove screencast session: {err}");
+                    }
+                },
+                "close screencast session",
+            )
+            .detach();
+    }
+}
+
+fn forget_session(sessions: &Sessions, session: &str) {
+    if let Ok(mut sessions) = sessions.lock() {
+        sessions.remove(session);
+    }
+}
+
+fn session_types(sessions: &Sessions, session: &str) -> Option<u32> {
+    sessions.lock().ok()?.get(session).copied()
+}
+
+struct ScreenCast {
+    requests: Sender<PortalRequest>,
+    sessions: Sessions,
+}
+
+impl ScreenCast {
+    /// Ask the chooser which source to cast. Nothing is shared that the user
+    /// did not pick: `Err` when no chooser is configured or the user dismissed
+    /// it.
+    async fn choose(&self, types: u32) -> Result<CastSource, ()> {
+        let (reply, response) = async_channel::bounded(1);
+        if self.requests.send(PortalRequest::Sources(reply)).is_err() {
+            return Err(());
+        }
+        let list = response.recv().await.map_err(|_| ())?;
+        let Some(chooser) = list.chooser else {
+            tracing::warn!("screencast refused: set screencast_chooser to pick what to share");
+            return Err(());
+        };
+
+        let offered: Vec<String> = list
+            .sources
+            .iter()
+            .filter(|(source, _)| types & source.source_type() != 0)
+            .map(|(_, label)| label.clone())
+            .collect();
+
+        // The chooser may wait on the user; keep the bus responsive meanwhile.
+        let (done, chosen) = async_channel::bounded(1);
+        std::thread::spawn(move || {
+            let _ = done.send_blocking(run_chooser(&chooser, &offered));
+        });
+        let line = chosen.recv().await.map_err(|_| ())?.ok_or(())?;
+        CastSource::parse(&line).ok_or_else(|| {
+            tracing::warn!(line, "screencast chooser printed no source");
+        })
+    }
+}
+
+/// Run `command` with one source per stdin line and return the line it prints.
+fn run_chooser(command: &str, offered: &[String]) -> Option<String> {
+    let mut child = Command::new("sh")
+        .args(["-c", command])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|err| tracing::warn!("failed to run screencast chooser: {err}"))
+        .ok()?;
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(offered.join("\n").as_bytes());
+    }
+    let output = child.wait_with_output().ok()?;
+    let line = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    (output.status.success() && !line.is_empty()).then_some(line)
+}
+
+fn stream_results(info: StreamInfo) -> HashMap<String, OwnedValue> {
+    let properties: HashMap<String, Value<'static>> = HashMap::from([
+        ("position".to_owned(), Value::from(info.position)),
+        ("size".to_owned(), Value::from(info.size)),
+        ("source_type".to_owned(), Value::from(info.source_type)),
+    ]);
+    let streams = vec![(info.node_id, properties)];
+    let mut results = HashMap::new();
+    if let Ok(streams) = OwnedValue::try_from(Value::from(streams)) {
+        results.insert("streams".to_owned(), streams);
+    }
+    results
+}
+
+#[interface(name = "org.freedesktop.impl.portal.ScreenCast")]
+impl ScreenCast {
+    async fn create_session(
+        &mut self,
+        _handle: OwnedObjectPath,
+        session_handle: OwnedObjectPath,
+        _app_id: String,
+        _options: HashMap<String, OwnedValue>,
+        #[zbus(object_server)] server: &ObjectServer,
+    ) -> (u32, HashMap<String, OwnedValue>) {
+        let session = Session {
+            id: session_handle.to_string(),
+            requests: self.requests.clone(),
+            sessions: self.sessions.clone(),
+        };
+        if let Err(err) = server.at(&session_handle, session).await {
+            tracing::warn!("failed to export screencast session: {err}");
+            return (RESPONSE_OTHER, HashMap::new());
+        }
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.insert(session_handle.to_string(), SOURCE_MONITOR);
+        }
+        (RESPONSE_SUCCESS, HashMap::new())
+    }
+
+    async fn select_sources(
+        &mut self,
+        _handle: OwnedObjectPath,
+        session_handle: OwnedObjectPath,
+        _app_id: String,
+        options: HashMap<String, OwnedValue>,
+    ) -> (u32, HashMap<String, OwnedValue>) {
+        let types = options
+            .get("types")
+            .and_then(|types| u32::try_from(types).ok())
+            .filter(|types| *types != 0)
+            .unwrap_or(SOURCE_MONITOR);
+        let Ok(mut sessions) = self.sessions.lock() else {
+            return (RESPONSE_OTHER, HashMap::new());
+        };
+        match sessions.get_mut(session_handle.as_str()) {
+            Some(selected) => {
+                *selected = types;
+                (RESPONSE_SUCCESS, HashMap::new())
+            }
+            None => (RESPONSE_OTHER, HashMap::new()),
+        }
+    }
+
+    async fn start(
+        &mut self,
+        _handle: OwnedObjectPath,
+        session_handle: OwnedObjectPath,
+        _app_id: String,
+        _parent_window: String,
+        _options: HashMap<String, OwnedValue>,
+    ) -> (u32, HashMap<String, OwnedValue>) {
+        let Some(types) = session_types(&self.sessions, session_handle.as_str()) else {
+            return (RESPONSE_OTHER, HashMap::new());
+        };
+        let Ok(source) = self.choose(types).await else {
+            return (RESPONSE_CANCELLED, HashMap::new());
+        };
+
+        let (reply, response) = async_channel::bounded(1);
+        let request = PortalRequest::Start {
+            session: session_handle.to_string(),
+            source,
+            reply,
+        };
+        if self.requests.send(request).is_err() {
+            return (RESPONSE_OTHER, HashMap::new());
+        }
+        match response.recv().await {
+            Ok(Ok(info)) => (RESPONSE_SUCCESS, stream_results(info)),
+            Ok(Err(err)) => {
+                tracing::warn!("failed to start screencast: {err}");
+                (RESPONSE_OTHER, HashMap::new())
+            }
+            Err(_) => (RESPONSE_OTHER, HashMap::new()),
+        }
+    }
+
+    #[zbus(property)]
+    fn available_source_types(&self) -> u32 {
+        SOURCE_MONITOR | SOURCE_WINDOW
+    }
+
+    #[zbus(property)]
+    fn available_cursor_modes(&self) -> u32 {
+        CURSOR_EMBEDDED
+    }
+
+    #[zbus(property, name = "version")]
+    fn version(&self) -> u32 {
+        4
+    }
+}
+
+struct Session {
+    id: String,
+    requests: Sender<PortalRequest>,
+    sessions: Sessions,
+}
+
+#[interface(name = "org.freedesktop.impl.portal.Session")]
+impl Session {
+    async fn close(&self, #[zbus(object_server)] server: &ObjectServer) {
+        forget_session(&self.sessions, &self.id);
+        let _ = self.requests.send(PortalRequest::Stop {
+            session: self.id.clone(),
+        });
+        if let Err(err) = server.remove::<Self, _>(self.id.as_str()).await {
+            tracing::warn!(
+                session = self.id,
+                "failed to remove screencast session: {err}"
+            );
+        }
+    }
+
+    #[zbus(signal)]
+    async fn closed(emitter: &SignalEmitter<'_>) -> zbus::Result<()>;
+
+    #[zbus(property, name = "version")]
+    fn version(&self) -> u32 {
+        1
+    }
+}
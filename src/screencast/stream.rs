@@ -0,0 +1,446 @@
+//! PipeWire video streams fed with dmabufs the compositor renders into.
+//!
+//! Raven drives each stream: it allocates linear XRGB8888 buffers on the
+//! primary GPU when PipeWire asks for them, and fills one whenever the cast's
+//! output (or the output showing its window) renders a frame.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    io::Cursor,
+    os::fd::{AsFd, AsRawFd, BorrowedFd},
+    rc::Rc,
+    time::Duration,
+};
+
+use pipewire::{
+    context::Context,
+    core::Core,
+    main_loop::MainLoop,
+    properties::properties,
+    spa::{
+        self,
+        param::{
+            ParamType,
+            format::{FormatProperties, MediaSubtype, MediaType},
+            video::{VideoFormat, VideoInfoRaw},
+        },
+        pod::{
+            self, ChoiceValue, Object, Pod, Property, PropertyFlags, Value,
+            serialize::PodSerializer,
+        },
+        utils::{Choice, ChoiceEnum, ChoiceFlags, Direction, Fraction, SpaTypes},
+    },
+    stream::{Stream, StreamFlags, StreamListener, StreamState},
+};
+use smithay::{
+    backend::{
+        allocator::{
+            Allocator, Fourcc, Modifier,
+            dmabuf::{AsDmabuf, Dmabuf},
+            gbm::{GbmAllocator, GbmBufferFlags, GbmDevice},
+        },
+        drm::DrmDeviceFd,
+    },
+    desktop::Window,
+    output::WeakOutput,
+    reexports::calloop::{
+        Interest, LoopHandle, Mode, PostAction, RegistrationToken, generic::Generic,
+    },
+    utils::{Physical, Size},
+};
+
+use super::portal::StreamInfo;
+use crate::{CompositorError, Raven};
+
+// Frame rate advertised to consumers; frames still only come when the
+// source redraws.
+const MAX_FRAMERATE: u32 = 60;
+
+/// Connection to the PipeWire daemon, dispatched from the compositor's loop.
+pub struct PipeWire {
+    core: Core,
+    _context: Context,
+    _main_loop: MainLoop,
+    loop_handle: LoopHandle<'static, Raven>,
+    token: RegistrationToken,
+}
+
+struct LoopFd(MainLoop);
+
+impl AsFd for LoopFd {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.0.loop_().fd()
+    }
+}
+
+impl PipeWire {
+    pub fn new(loop_handle: &LoopHandle<'static, Raven>) -> Result<Self, CompositorError> {
+        pipewire::init();
+        let main_loop = MainLoop::new(None)
+            .map_err(|err| CompositorError::Backend(format!("pipewire main loop: {err}")))?;
+        let context = Context::new(&main_loop)
+            .map_err(|err| CompositorError::Backend(format!("pipewire context: {err}")))?;
+        let core = context
+            .connect(None)
+            .map_err(|err| CompositorError::Backend(format!("pipewire connect: {err}")))?;
+
+        let dispatch = main_loop.clone();
+        let token = loop_handle
+            .insert_source(
+                Generic::new(LoopFd(main_loop.clone()), Interest::READ, Mode::Level),
+                move |_, _, _| {
+                    dispatch.loop_().iterate(Duration::ZERO);
+                    Ok(PostAction::Continue)
+                },
+            )
+            .map_err(|err| CompositorError::Backend(format!("pipewire loop source: {err}")))?;
+
+        Ok(Self {
+            core,
+            _context: context,
+            _main_loop: main_loop,
+            loop_handle: loop_handle.clone(),
+            token,
+        })
+    }
+}
+
+impl Drop for PipeWire {
+    fn drop(&mut self) {
+        self.loop_handle.remove(self.token);
+    }
+}
+
+/// What a cast shows.
+pub enum CastTarget {
+    Output(WeakOutput),
+    Window(Window),
+}
+
+// State the stream callbacks share with the compositor.
+struct CastShared {
+    gbm: GbmDevice<DrmDeviceFd>,
+    size: Size<i32, Physical>,
+    streaming: bool,
+    failed: bool,
+    // Answer for the portal's `Start`, sent once PipeWire assigned a node id.
+    reply: Option<(
+        async_channel::Sender<Result<StreamInfo, String>>,
+        StreamInfo,
+    )>,
+    // Buffers handed to PipeWire, by the fd in their `spa_data`.
+    dmabufs: HashMap<i64, Dmabuf>,
+}
+
+/// One PipeWire stream of a portal session.
+pub struct Cast {
+    pub session: String,
+    pub target: CastTarget,
+    stream: Stream,
+    _listener: StreamListener<()>,
+    shared: Rc<RefCell<CastShared>>,
+    size: Size<i32, Physical>,
+}
+
+impl Cast {
+    /// Create a stream showing `target` at `size`. `reply` gets `info` with the
+    /// node id filled in once the stream is ready for consumers.
+    pub fn new(
+        pipewire: &PipeWire,
+        gbm: GbmDevice<DrmDeviceFd>,
+        session: String,
+        target: CastTarget,
+        size: Size<i32, Physical>,
+        info: StreamInfo,
+        reply: async_channel::Sender<Result<StreamInfo, String>>,
+    ) -> Result<Self, CompositorError> {
+        let stream = Stream::new(
+            &pipewire.core,
+            "raven-screencast",
+            properties! {
+                *pipewire::keys::MEDIA_CLASS => "Video/Source",
+                *pipewire::keys::NODE_NAME => "raven-screencast",
+            },
+        )
+        .map_err(|err| CompositorError::Backend(format!("pipewire stream: {err}")))?;
+
+        let shared = Rc::new(RefCell::new(CastShared {
+            gbm,
+            size,
+            streaming: false,
+            failed: false,
+            reply: Some((reply, info)),
+            dmabufs: HashMap::new(),
+        }));
+
+        let listener = stream
+            .add_local_listener_with_user_data(())
+            .state_changed({
+                let shared = shared.clone();
+                move |stream, _, _old, new| {
+                    let mut shared = shared.borrow_mut();
+                    match new {
+                        StreamState::Paused => {
+                            if let Some((reply, mut info)) = shared.reply.take() {
+                                info.node_id = stream.node_id();
+                                let _ = reply.try_send(Ok(info));
+                            }
+                            shared.streaming = false;
+                        }
+                        StreamState::Streaming => shared.streaming = true,
+                        StreamState::Error(err) => {
+                            tracing::warn!("screencast stream failed: {err}");
+                            if let Some((reply, _)) = shared.reply.take() {
+                                let _ = reply.try_send(Err(err));
+                            }
+                            shared.failed = true;
+                        }
+                        StreamState::Unconnected => shared.failed = true,
+                        StreamState::Connecting => {}
+                    }
+                }
+            })
+            .param_changed(|stream, _, id, pod| {
+                if id != ParamType::Format.as_raw() {
+                    return;
+                }
+                let Some(pod) = pod else {
+                    return;
+                };
+                let mut format = VideoInfoRaw::new();
+                if let Err(err) = format.parse(pod) {
+                    tracing::warn!("screencast consumer sent a bad format: {err:?}");
+                    return;
+                }
+                tracing::debug!(size = ?format.size(), "screencast format negotiated");
+                let buffers = serialize(buffers_param());
+                let mut params = [Pod::from_bytes(&buffers).expect("serialized pod")];
+                if let Err(err) = stream.update_params(&mut params) {
+                    tracing::warn!("failed to set screencast buffer params: {err}");
+                }
+            })
+            .add_buffer({
+                let shared = shared.clone();
+                move |_, _, buffer| {
+                    let mut shared = shared.borrow_mut();
+                    let dmabuf = match allocate(&shared.gbm, shared.size) {
+                        Ok(dmabuf) => dmabuf,
+                        Err(err) => {
+                            tracing::warn!("failed to allocate screencast buffer: {err}");
+                            shared.failed = true;
+                            return;
+                        }
+                    };
+                    // SAFETY: PipeWire hands us a buffer with one data block, as
+                    // requested by `buffers_param`, valid until `remove_buffer`.
+                    unsafe {
+                        let data = (*(*buffer).buffer).datas;
+                        let Some(handle) = dmabuf.handles().next() else {
+                            return;
+                        };
+                        let stride = dmabuf.strides().next().unwrap_or(0);
+                        let fd = i64::from(handle.as_raw_fd());
+                        (*data).type_ = spa::sys::SPA_DATA_DmaBuf;
+                        (*data).flags = spa::sys::SPA_DATA_FLAG_READWRITE;
+                        (*data).fd = fd;
+                        (*data).mapoffset = 0;
+                        (*data).maxsize = stride * shared.size.h as u32;
+                        (*data).data = std::ptr::null_mut();
+                        let chunk = (*data).chunk;
+                        (*chunk).offset = dmabuf.offsets().next().unwrap_or(0);
+                        (*chunk).stride = stride as i32;
+                        (*chunk).size = (*data).maxsize;
+                        shared.dmabufs.insert(fd, dmabuf);
+                    }
+                }
+            })
+            .remove_buffer({
+                let shared = shared.clone();
+                move |_, _, buffer| {
+                    // SAFETY: see `add_buffer`.
+                    let fd = unsafe { (*(*(*buffer).buffer).datas).fd };
+                    shared.borrow_mut().dmabufs.remove(&fd);
+                }
+            })
+            .register()
+            .map_err(|err| CompositorError::Backend(format!("pipewire listener: {err}")))?;
+
+        let format = serialize(format_param(size));
+        let mut params = [Pod::from_bytes(&format).expect("serialized pod")];
+        stream
+            .connect(
+                Direction::Output,
+                None,
+                StreamFlags::DRIVER | StreamFlags::ALLOC_BUFFERS,
+                &mut params,
+            )
+            .map_err(|err| CompositorError::Backend(format!("pipewire stream connect: {err}")))?;
+
+        Ok(Self {
+            session,
+            target,
+            stream,
+            _listener: listener,
+            shared,
+            size,
+        })
+    }
+
+    /// Whether the stream errored or its consumer went away for good.
+    pub fn failed(&self) -> bool {
+        self.shared.borrow().failed
+    }
+
+    /// Fill the next free buffer with `render` and hand it to the consumer.
+    /// Nothing happens while the consumer is paused or holds every buffer; a
+    /// new `size` renegotiates the format and skips this frame.
+    pub fn render_frame(
+        &mut self,
+        size: Size<i32, Physical>,
+        render: impl FnOnce(&mut Dmabuf) -> Result<(), CompositorError>,
+    ) -> Result<(), CompositorError> {
+        if size != self.size {
+            self.resize(size);
+            return Ok(());
+        }
+        if !self.shared.borrow().streaming {
+            return Ok(());
+        }
+
+        // SAFETY: the buffer goes back to the stream below, whatever happens.
+        let buffer = unsafe { self.stream.dequeue_raw_buffer() };
+        if buffer.is_null() {
+            return Ok(());
+        }
+        let data = unsafe { (*(*buffer).buffer).datas };
+        let fd = unsafe { (*data).fd };
+        let dmabuf = self.shared.borrow().dmabufs.get(&fd).cloned();
+        let result = match dmabuf {
+            Some(mut dmabuf) => render(&mut dmabuf),
+            None => Err(CompositorError::Screencopy(
+                "screencast buffer is not ours".to_owned(),
+            )),
+        };
+        unsafe {
+            let chunk = (*data).chunk;
+            (*chunk).size = if result.is_ok() { (*data).maxsize } else { 0 };
+            self.stream.queue_raw_buffer(buffer);
+        }
+        result
+    }
+
+    fn resize(&mut self, size: Size<i32, Physical>) {
+        tracing::debug!(?size, "screencast source resized");
+        self.size = size;
+        self.shared.borrow_mut().size = size;
+        let format = serialize(format_param(size));
+        let mut params = [Pod::from_bytes(&format).expect("serialized pod")];
+        if let Err(err) = self.stream.update_params(&mut params) {
+            tracing::warn!("failed to renegotiate screencast size: {err}");
+        }
+    }
+}
+
+impl Drop for Cast {
+    fn drop(&mut self) {
+        if let Err(err) = self.stream.disconnect() {
+            tracing::debug!("failed to disconnect screencast stream: {err}");
+        }
+    }
+}
+
+fn allocate(
+    gbm: &GbmDevice<DrmDeviceFd>,
+    size: Size<i32, Physical>,
+) -> Result<Dmabuf, CompositorError> {
+    let mut allocator = GbmAllocator::new(gbm.clone(), GbmBufferFlags::RENDERING);
+    let buffer = allocator
+        .create_buffer(
+            size.w as u32,
+            size.h as u32,
+            Fourcc::Xrgb8888,
+            &[Modifier::Linear],
+        )
+        .map_err(|err| CompositorError::Screencopy(format!("gbm buffer: {err}")))?;
+    buffer
+        .export()
+        .map_err(|err| CompositorError::Screencopy(format!("dmabuf export: {err}")))
+}
+
+fn serialize(object: Object) -> Vec<u8> {
+    PodSerializer::serialize(Cursor::new(Vec::new()), &Value::Object(object))
+        .expect("pod serialization into a Vec cannot fail")
+        .0
+        .into_inner()
+}
+
+/// The one format Raven offers: linear BGRx (DRM XRGB8888) at `size`.
+fn format_param(size: Size<i32, Physical>) -> Object {
+    pod::object!(
+        SpaTypes::ObjectParamFormat,
+        ParamType::EnumFormat,
+        pod::property!(FormatProperties::MediaType, Id, MediaType::Video),
+        pod::property!(FormatProperties::MediaSubtype, Id, MediaSubtype::Raw),
+        pod::property!(FormatProperties::VideoFormat, Id, VideoFormat::BGRx),
+        Property {
+            key: FormatProperties::VideoModifier.as_raw(),
+            flags: PropertyFlags::MANDATORY,
+            value: Value::Long(u64::from(Modifier::Linear) as i64),
+        },
+        pod::property!(
+            FormatProperties::VideoSize,
+            Rectangle,
+            spa::utils::Rectangle {
+                width: size.w as u32,
+                height: size.h as u32,
+            }
+        ),
+        pod::property!(
+            FormatProperties::VideoFramerate,
+            Fraction,
+            Fraction { num: 0, denom: 1 }
+        ),
+        pod::property!(
+            FormatProperties::VideoMaxFramerate,
+            Choice,
+            Range,
+            Fraction,
+            Fraction {
+                num: MAX_FRAMERATE,
+                denom: 1
+            },
+            Fraction { num: 1, denom: 1 },
+            Fraction {
+                num: MAX_FRAMERATE,
+                denom: 1
+            }
+        ),
+    )
+}
+
+/// Buffer requirements: a few single-plane dmabufs.
+fn buffers_param() -> Object {
+    Object {
+        type_: SpaTypes::ObjectParamBuffers.as_raw(),
+        id: ParamType::Buffers.as_raw(),
+        properties: vec![
+            Property::new(
+                spa::sys::SPA_PARAM_BUFFERS_buffers,
+                Value::Choice(ChoiceValue::Int(Choice(
+                    ChoiceFlags::empty(),
+                    ChoiceEnum::Range {
+                        default: 4,
+                        min: 2,
+                        max: 8,
+                    },
+                ))),
+            ),
+            Property::new(spa::sys::SPA_PARAM_BUFFERS_blocks, Value::Int(1)),
+            Property::new(
+                spa::sys::SPA_PARAM_BUFFERS_dataType,
+                Value::Int(1 << spa::sys::SPA_DATA_DmaBuf),
+            ),
+        ],
+    }
+}
@@ -1,8 +1,12 @@
 use smithay::{
+    backend::input::{KeyState, TouchSlot},
     desktop::{PopupManager, Space, Window, layer_map_for_output},
-    input::{Seat, SeatState, pointer::CursorImageStatus},
+    input::{Seat, SeatState, keyboard::Keycode, pointer::CursorImageStatus},
     reexports::{
-        calloop::{Interest, LoopHandle, LoopSignal, Mode, PostAction, generic::Generic},
+        calloop::{
+            Interest, LoopHandle, LoopSignal, Mode, PostAction, channel::Sender, generic::Generic,
+        },
+        wayland_protocols::wp::content_type::v1::server::wp_content_type_v1,
         wayland_protocols::xdg::{
             decoration::zv1::server::zxdg_toplevel_decoration_v1::Mode as XdgDecorationMode,
             shell::server::xdg_toplevel,
@@ -17,9 +21,13 @@ use smithay::{
     utils::{Clock, Logical, Monotonic, Point, Rectangle, Serial, Size},
     wayland::{
         compositor::{CompositorClientState, CompositorState, with_states},
+        content_type::{ContentTypeState, ContentTypeSurfaceCachedState},
+        cursor_shape::CursorShapeManagerState,
         dmabuf::DmabufState,
         drm_syncobj::DrmSyncobjState,
+        foreign_toplevel_list::ForeignToplevelListState,
         fractional_scale::FractionalScaleManagerState,
+        idle_notify::IdleNotifierState,
         output::OutputManagerState,
         pointer_constraints::PointerConstraintsState,
         pointer_gestures::PointerGesturesState,
@@ -31,12 +39,13 @@ use smithay::{
             wlr_layer::WlrLayerShellState,
             xdg::{
                 SurfaceCachedState, XdgShellState, XdgToplevelSurfaceData,
-                decoration::XdgDecorationState,
+                decoration::XdgDecorationState, dialog::XdgDialogState,
             },
         },
         shm::ShmState,
         socket::ListeningSocketSource,
         viewporter::ViewporterState,
+        xdg_activation::{XdgActivationState, XdgActivationTokenData},
     },
 };
 use std::{
@@ -59,24 +68,72 @@ use std::{
 
 use crate::{
     CompositorError,
-    config::{self, RuntimeConfig, WallpaperConfig},
-    layout::{GapConfig, LayoutBox, LayoutType},
+    backend::udev::RedrawCause,
+    config::{self, DpmsMode, RulePosition, RuntimeConfig, WallpaperConfig, WindowType},
+    layout::{GapConfig, LayoutBox, LayoutType, WindowGeometry, WorkspaceLayout, without_gaps},
     protocols::{
         ext_workspace::ExtWorkspaceManagerState,
         foreign_toplevel::ForeignToplevelManagerState,
+        gamma_control::GammaControlManagerState,
+        idle_inhibit::IdleInhibitManagerState,
+        image_copy_capture::ImageCopyCaptureState,
+        output_power::OutputPowerManagerState,
+        tearing_control::TearingControlManagerState,
         wlr_screencopy::{Screencopy, ScreencopyManagerState},
     },
+    render_helpers::SolidColorRenderElement,
 };
 
+mod accessx;
+mod activation;
+mod buffer_budget;
+mod builtin_wallpaper;
+mod config_error;
+mod config_get;
+mod conformance;
+mod debug_overlay;
+mod dnd_edge;
+mod dpms;
+mod events;
+mod find_cursor;
 mod fullscreen;
+mod idle;
+mod idle_inhibit;
+mod image_capture;
 mod ipc;
+mod keymap;
+mod layout_preview;
+mod minimize;
+mod night_light;
+mod pager;
+mod progress;
+mod reserve;
 mod rules;
 mod runtime;
+#[cfg(feature = "screencast")]
+mod screencast;
+mod screenshot;
+mod session;
+mod surface_sweep;
+mod sway_ipc;
+mod touch_hold;
+mod urgency;
+mod wallpaper_slideshow;
+mod window_moves;
+mod window_outputs;
+mod window_switcher;
+mod workspace_transition;
 mod workspaces;
 
+pub use builtin_wallpaper::OutputWallpaper;
 use fullscreen::{FullscreenState, WindowFullscreenMode};
-
-pub const WORKSPACE_COUNT: usize = 10;
+use ipc::ClientsWatcher;
+pub use keymap::KeymapSettings;
+pub use progress::ProgressStatus;
+use screenshot::{NextFrameScreenshot, RegionSelection};
+use session::SessionState;
+use sway_ipc::SwayIpcState;
+pub use workspace_transition::WorkspaceTransitionFrame;
 
 #[derive(Clone, Copy, Debug)]
 pub struct NewWindowRuleDecision {
@@ -84,10 +141,22 @@ pub struct NewWindowRuleDecision {
     pub floating: bool,
     pub fullscreen: bool,
     pub focus: bool,
+    pub sticky: bool,
     pub width: Option<u32>,
     pub height: Option<u32>,
 }
 
+/// How a window is drawn, tiled and paced, from the last matching window rules that
+/// set `opacity`, `border_color`, `no_gaps`, `corner_radius` and `max_fps`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WindowRenderRules {
+    pub opacity: Option<f32>,
+    pub border_color: Option<[f32; 4]>,
+    pub no_gaps: bool,
+    pub corner_radius: Option<u32>,
+    pub max_fps: Option<u32>,
+}
+
 #[derive(Clone, Debug)]
 struct PendingInteractiveMove {
     window: Window,
@@ -115,7 +184,9 @@ pub struct Raven {
 
     pub space: Space<Window>,
     pub seat: Seat<Self>,
-    pub layout: LayoutBox,
+    // How far the column strip of scrolling layouts is scrolled, per output name
+    // and workspace.
+    scroll_offsets: HashMap<(String, usize), i32>,
     pub config: RuntimeConfig,
     pub config_path: PathBuf,
     pub socket_name: OsString,
@@ -126,6 +197,7 @@ pub struct Raven {
     pub xdg_shell_state: XdgShellState,
     pub xdg_decoration_state: XdgDecorationState,
     pub kde_decoration_state: KdeDecorationState,
+    pub xdg_dialog_state: XdgDialogState,
     pub shm_state: ShmState,
     pub output_manager_state: OutputManagerState,
     pub data_device_state: DataDeviceState,
@@ -135,9 +207,19 @@ pub struct Raven {
     pub layer_shell_state: WlrLayerShellState,
     pub ext_workspace_manager_state: ExtWorkspaceManagerState,
     pub foreign_toplevel_manager_state: ForeignToplevelManagerState,
+    pub foreign_toplevel_list_state: ForeignToplevelListState,
     pub screencopy_state: ScreencopyManagerState,
+    pub image_copy_capture_state: ImageCopyCaptureState,
+    pub idle_inhibit_state: IdleInhibitManagerState,
+    pub idle_notifier_state: IdleNotifierState<Raven>,
+    pub output_power_state: OutputPowerManagerState,
+    pub gamma_control_state: GammaControlManagerState,
+    pub tearing_control_state: TearingControlManagerState,
     pub viewporter_state: ViewporterState,
     pub fractional_scale_manager_state: FractionalScaleManagerState,
+    pub content_type_state: ContentTypeState,
+    pub cursor_shape_manager_state: CursorShapeManagerState,
+    pub xdg_activation_state: XdgActivationState,
     pub presentation_state: PresentationState,
     pub pointer_constraints_state: PointerConstraintsState,
     pub pointer_gestures_state: PointerGesturesState,
@@ -146,14 +228,47 @@ pub struct Raven {
     pub pointer_location: Point<f64, Logical>,
     pub pointer_contents: PointContents,
     pub last_pointer_redraw_msec: Option<u32>,
-    pub pending_screencopy: Option<Screencopy>,
+    /// wlr-screencopy frames waiting for their output's next frame.
+    pub pending_screencopies: Vec<Screencopy>,
     pending_interactive_moves: Vec<PendingInteractiveMove>,
     pending_interactive_resizes: Vec<PendingInteractiveResize>,
     pub current_workspace: usize,
-    // Mapped windows that currently participate in workspace rendering/layout.
+    // Mapped windows that currently participate in workspace rendering/layout. The
+    // order of each list is the tiling order; floating windows keep their place in it.
     pub workspaces: Vec<Vec<Window>>,
+    // Layout of each workspace, by workspace index.
+    workspace_layouts: Vec<WorkspaceLayout>,
     // Unmapped toplevels tracked per-workspace until their first real map commit.
     unmapped_workspaces: Vec<Vec<Window>>,
+    // Occupancy seen by the last workspace hook pass.
+    workspace_occupied: Vec<bool>,
+    // Previously focused workspaces, most recent last.
+    workspace_history: Vec<usize>,
+    // Tiled slots from the last layout pass, in layout order.
+    tiled_slots: Vec<(Window, Rectangle<i32, Logical>)>,
+    pub layout_debug_overlay: bool,
+    layout_debug_labels: debug_overlay::DebugLabels,
+    // Message for the on-screen config error banner, until dismissed.
+    config_error: Option<String>,
+    config_error_banners: config_error::BannerBuffers,
+    // Border and veil buffers of windows, kept across frames.
+    pub decoration_buffers: crate::backend::udev::DecorationBuffers,
+    // Compact window ids shown in reports; assigned per toplevel, never reused.
+    window_ids: HashMap<WlSurface, u64>,
+    // Output each toplevel tiles on, by name.
+    window_outputs: HashMap<WlSurface, String>,
+    // MRU focus order and the Main+Tab cycle in progress.
+    window_switcher: window_switcher::WindowSwitcher,
+    next_window_id: u64,
+    // i3/sway-compatible IPC subscribers and event diffing state.
+    sway_ipc: SwayIpcState,
+    // Open `raven clients --watch` connections.
+    clients_watchers: Vec<ClientsWatcher>,
+    // `screenshot --next-frame` requests waiting on their outputs' next frame.
+    next_frame_screenshots: Vec<NextFrameScreenshot>,
+    // A running `screenshot --region` selection.
+    region_selection: Option<RegionSelection>,
+    keymap: KeymapSettings,
     // Fullscreen ownership/transition bookkeeping.
     fullscreen: FullscreenState,
     assigned_rects_by_surface: HashMap<WlSurface, Rectangle<i32, Logical>>,
@@ -161,6 +276,14 @@ pub struct Raven {
     // Track scanout rejection reasons per output to aid debugging/perf tuning.
     scanout_reject_counters: HashMap<String, u64>,
     pub floating_windows: Vec<Window>,
+    // Windows shown on every workspace; they move along on workspace switches.
+    sticky_windows: HashSet<WlSurface>,
+    // Opacity, border and gap settings from window rules, for windows that have any.
+    window_render_rules: HashMap<WlSurface, WindowRenderRules>,
+    // Windows wanting attention, with when they last asked for it.
+    urgent_windows: HashMap<WlSurface, Instant>,
+    // Windows kept in their workspace but out of the space until unminimized.
+    minimized_windows: HashSet<WlSurface>,
     // Per-surface lifecycle sets used during the unmapped -> mapped transition.
     // `pending_initial_configure_ids`: first configure still needs to be sent.
     // `pending_initial_configure_idle_ids`: idle callback already queued for that send.
@@ -172,8 +295,50 @@ pub struct Raven {
     pending_initial_configure_idle_ids: HashSet<WlSurface>,
     pub unmapped_toplevel_ids: HashSet<WlSurface>,
     pending_unmapped_maximized_ids: HashSet<WlSurface>,
+    // Auto-floated toplevels whose first map waits for size hints (bounded by a timer).
+    held_floating_map_ids: HashSet<WlSurface>,
+    // Toplevels the client marked modal through xdg_dialog_v1.
+    modal_dialog_ids: HashSet<WlSurface>,
+    // Sizes and dead entries found by the periodic sweep of the sets above.
+    surface_sweep: surface_sweep::SurfaceSweepStats,
+    // Attached buffer memory per client, against the configured budgets.
+    buffer_budget: buffer_budget::BufferBudget,
     pub autostart_started: bool,
+    // Pid of every autostart command launched so far, keyed by command line.
+    autostart_pids: HashMap<String, u32>,
+    // Autostart entries with a workspace, waiting for their first toplevel.
+    autostart_claims: Vec<runtime::AutostartClaim>,
+    // Toplevels claimed by an autostart entry, and the workspace they were bound to.
+    autostart_workspace_by_surface: HashMap<WlSurface, usize>,
+    // Startup session entries, their progress and the windows they placed.
+    session: SessionState,
+    // The config as loaded while performance mode overrides `config`.
+    performance_mode_base: Option<RuntimeConfig>,
+    // Struts added with `raven reserve`, applied on top of layer-shell zones.
+    reservations: Vec<reserve::Reservation>,
+    // Edge dwell that flips workspaces while a drag-and-drop is in progress.
+    dnd_edge_switch: dnd_edge::DndEdgeSwitch,
+    // Sticky, slow and bounce keys bookkeeping.
+    accessx: accessx::AccessX,
+    // New tiled slots outlined after a reload, until the layout catches up.
+    layout_preview: Option<layout_preview::LayoutPreview>,
+    // When the running `find_cursor` ring started.
+    find_cursor_started: Option<Instant>,
+    // The workspace switch being animated, with the windows it switched away from.
+    workspace_transition: Option<workspace_transition::WorkspaceTransition>,
+    // Touchscreen finger that may become a hold-to-right-click.
+    touch_hold: Option<touch_hold::TouchHold>,
+    // Time since the last input and the `idle` config timer.
+    idle: idle::IdleTracker,
+    // Night light override, schedule timer and the temperature in effect.
+    night_light: night_light::NightLight,
     pub wallpaper_task_inflight: Arc<AtomicBool>,
+    // Set up on the first apply; the wallpaper thread reports its result here.
+    wallpaper_progress: Option<Sender<(String, ProgressStatus)>>,
+    // Slideshow position and timer, when `wallpaper.slideshow_directory` is set.
+    wallpaper_slideshow: wallpaper_slideshow::WallpaperSlideshow,
+    // Images decoded for `wallpaper.builtin`.
+    builtin_wallpaper: builtin_wallpaper::BuiltinWallpaper,
     xwayland_satellite: Option<Child>,
     xwayland_satellite_signature: Option<String>,
     xwayland_satellite_started_at: Option<Instant>,
@@ -182,10 +347,17 @@ pub struct Raven {
 
     // DRM backend fields
     pub cursor_status: CursorImageStatus,
+    /// Set while the last pointer-like input came from a device configured to hide the cursor.
+    pub cursor_hidden_by_device: bool,
     pub clock: Clock<Monotonic>,
     pub dmabuf_state: Option<DmabufState>,
     pub syncobj_state: Option<DrmSyncobjState>,
     pub udev_data: Option<crate::backend::udev::UdevData>,
+    pub backend_kind: crate::backend::BackendKind,
+    /// ScreenCast portal backend; `None` until the DRM backend starts it or
+    /// when there is no session bus or PipeWire.
+    #[cfg(feature = "screencast")]
+    pub screencast: Option<crate::screencast::Screencast>,
 }
 
 impl Raven {
@@ -209,6 +381,7 @@ impl Raven {
                     .map(|data| data.can_view_decoration_globals)
                     .unwrap_or(false)
             });
+        let xdg_dialog_state = XdgDialogState::new::<Self>(&display_handle);
         let kde_decoration_state = KdeDecorationState::new_with_filter::<Self, _>(
             &display_handle,
             KdeDecorationsMode::Server,
@@ -229,10 +402,24 @@ impl Raven {
             ExtWorkspaceManagerState::new::<Self, _>(&display_handle, |_| true);
         let foreign_toplevel_manager_state =
             ForeignToplevelManagerState::new::<Self, _>(&display_handle, |_| true);
+        let foreign_toplevel_list_state = ForeignToplevelListState::new::<Self>(&display_handle);
         let screencopy_state = ScreencopyManagerState::new::<Self, _>(&display_handle, |_| true);
+        let image_copy_capture_state =
+            ImageCopyCaptureState::new::<Self, _>(&display_handle, |_| true);
+        let idle_inhibit_state = IdleInhibitManagerState::new::<Self, _>(&display_handle, |_| true);
+        let idle_notifier_state =
+            IdleNotifierState::<Self>::new(&display_handle, loop_handle.clone());
+        let output_power_state = OutputPowerManagerState::new::<Self, _>(&display_handle, |_| true);
+        let gamma_control_state =
+            GammaControlManagerState::new::<Self, _>(&display_handle, |_| true);
+        let tearing_control_state =
+            TearingControlManagerState::new::<Self, _>(&display_handle, |_| true);
         let viewporter_state = ViewporterState::new::<Self>(&display_handle);
         let fractional_scale_manager_state =
             FractionalScaleManagerState::new::<Self>(&display_handle);
+        let content_type_state = ContentTypeState::new::<Self>(&display_handle);
+        let cursor_shape_manager_state = CursorShapeManagerState::new::<Self>(&display_handle);
+        let xdg_activation_state = XdgActivationState::new::<Self>(&display_handle);
         // CLOCK_MONOTONIC = 1 on Linux; must match Clock<Monotonic>
         let presentation_state = PresentationState::new::<Self>(&display_handle, 1);
         let pointer_constraints_state = PointerConstraintsState::new::<Self>(&display_handle);
@@ -252,11 +439,26 @@ impl Raven {
             Ok(path) => tracing::info!(path = %path.display(), "ipc listener initialized"),
             Err(err) => tracing::warn!("failed to initialize ipc listener: {err}"),
         }
+        match sway_ipc::init_sway_ipc_listener(&loop_handle, &socket_name) {
+            Ok(path) => tracing::info!(path = %path.display(), "sway ipc listener initialized"),
+            Err(err) => tracing::warn!("failed to initialize sway ipc listener: {err}"),
+        }
 
-        // TODO: Get a brain
-        let layout = LayoutType::from_str("tiling").unwrap().new();
-        let loaded_config = config::load_or_create_default()?;
+        // A broken config should not keep the session from starting; fall back to the
+        // built-in defaults (which include the default keybinds) and show a banner.
+        let (loaded_config, startup_config_error) = match config::load_or_create_default() {
+            Ok(loaded_config) => (loaded_config, None),
+            Err(err) => (
+                config::LoadedConfig {
+                    path: config::config_path()?,
+                    config: RuntimeConfig::default(),
+                },
+                Some(err.to_string()),
+            ),
+        };
         config::apply_environment(&loaded_config.config);
+        let layout_type = loaded_config.config.layout_type;
+        let workspace_count = loaded_config.config.workspace_count;
 
         let mut state = Self {
             display_handle,
@@ -264,7 +466,7 @@ impl Raven {
             loop_signal,
 
             space,
-            layout,
+            scroll_offsets: HashMap::new(),
             config: loaded_config.config,
             config_path: loaded_config.path,
             seat,
@@ -275,6 +477,7 @@ impl Raven {
             xdg_shell_state,
             xdg_decoration_state,
             kde_decoration_state,
+            xdg_dialog_state,
             shm_state,
             output_manager_state,
             data_device_state,
@@ -284,9 +487,19 @@ impl Raven {
             layer_shell_state,
             ext_workspace_manager_state,
             foreign_toplevel_manager_state,
+            foreign_toplevel_list_state,
             screencopy_state,
+            image_copy_capture_state,
+            idle_inhibit_state,
+            idle_notifier_state,
+            output_power_state,
+            gamma_control_state,
+            tearing_control_state,
             viewporter_state,
             fractional_scale_manager_state,
+            content_type_state,
+            cursor_shape_manager_state,
+            xdg_activation_state,
             presentation_state,
             pointer_constraints_state,
             pointer_gestures_state,
@@ -295,25 +508,70 @@ impl Raven {
             pointer_location: Point::from((0.0, 0.0)),
             pointer_contents: PointContents::default(),
             last_pointer_redraw_msec: None,
-            pending_screencopy: None,
+            pending_screencopies: Vec::new(),
             pending_interactive_moves: Vec::new(),
             pending_interactive_resizes: Vec::new(),
             current_workspace: 0,
-            workspaces: vec![Vec::new(); WORKSPACE_COUNT],
-            unmapped_workspaces: vec![Vec::new(); WORKSPACE_COUNT],
-            fullscreen: FullscreenState::new(),
+            workspaces: vec![Vec::new(); workspace_count],
+            workspace_layouts: (0..workspace_count)
+                .map(|_| WorkspaceLayout::new(layout_type))
+                .collect(),
+            unmapped_workspaces: vec![Vec::new(); workspace_count],
+            workspace_occupied: vec![false; workspace_count],
+            workspace_history: Vec::new(),
+            tiled_slots: Vec::new(),
+            layout_debug_overlay: false,
+            layout_debug_labels: Default::default(),
+            config_error: None,
+            config_error_banners: Default::default(),
+            decoration_buffers: Default::default(),
+            window_ids: HashMap::new(),
+            window_outputs: HashMap::new(),
+            window_switcher: Default::default(),
+            next_window_id: 1,
+            sway_ipc: SwayIpcState::default(),
+            clients_watchers: Vec::new(),
+            next_frame_screenshots: Vec::new(),
+            region_selection: None,
+            keymap: KeymapSettings::default(),
+            fullscreen: FullscreenState::new(workspace_count),
             assigned_rects_by_surface: HashMap::new(),
             reported_sizes_by_surface: HashMap::new(),
             scanout_reject_counters: HashMap::new(),
             floating_windows: Vec::new(),
+            sticky_windows: HashSet::new(),
+            window_render_rules: HashMap::new(),
+            urgent_windows: HashMap::new(),
+            minimized_windows: HashSet::new(),
             pending_floating_recenter_ids: HashSet::new(),
             pending_window_rule_recheck_ids: HashSet::new(),
             pending_initial_configure_ids: HashSet::new(),
             pending_initial_configure_idle_ids: HashSet::new(),
             unmapped_toplevel_ids: HashSet::new(),
             pending_unmapped_maximized_ids: HashSet::new(),
+            held_floating_map_ids: HashSet::new(),
+            modal_dialog_ids: HashSet::new(),
+            surface_sweep: surface_sweep::SurfaceSweepStats::default(),
+            buffer_budget: buffer_budget::BufferBudget::default(),
             autostart_started: false,
+            autostart_pids: HashMap::new(),
+            autostart_claims: Vec::new(),
+            autostart_workspace_by_surface: HashMap::new(),
+            session: SessionState::default(),
+            performance_mode_base: None,
+            reservations: Vec::new(),
+            dnd_edge_switch: dnd_edge::DndEdgeSwitch::default(),
+            accessx: accessx::AccessX::default(),
+            layout_preview: None,
+            find_cursor_started: None,
+            workspace_transition: None,
+            touch_hold: None,
+            idle: idle::IdleTracker::default(),
+            night_light: night_light::NightLight::default(),
             wallpaper_task_inflight: Arc::new(AtomicBool::new(false)),
+            wallpaper_progress: None,
+            wallpaper_slideshow: wallpaper_slideshow::WallpaperSlideshow::default(),
+            builtin_wallpaper: builtin_wallpaper::BuiltinWallpaper::default(),
             xwayland_satellite: None,
             xwayland_satellite_signature: None,
             xwayland_satellite_started_at: None,
@@ -321,13 +579,20 @@ impl Raven {
             xwayland_satellite_failure_count: 0,
 
             cursor_status: CursorImageStatus::default_named(),
+            cursor_hidden_by_device: false,
             clock: Clock::new(),
             dmabuf_state: None,
             syncobj_state: None,
             udev_data: None,
+            backend_kind: Default::default(),
+            #[cfg(feature = "screencast")]
+            screencast: None,
         };
 
-        Self::ensure_portal_preferences_file();
+        if let Some(err) = startup_config_error {
+            config_error::show_config_error(&mut state, &err, "using built-in defaults");
+        }
+        Self::ensure_portal_preferences_file(false);
         state.ensure_xwayland_display();
         state.sync_activation_environment();
 
@@ -336,6 +601,7 @@ impl Raven {
 
     pub fn apply_layout(&mut self) -> Result<(), CompositorError> {
         self.prune_windows_without_live_client();
+        self.tiled_slots.clear();
 
         // Ensure visible windows tracked on the current workspace are mapped into Space before
         // layout decisions. Without this, a fullscreen owner unmap/destroy can leave sibling
@@ -345,7 +611,7 @@ impl Raven {
             if self.space.element_location(window).is_some() {
                 continue;
             }
-            if self.window_is_unmapped_toplevel(window) {
+            if self.window_is_unmapped_toplevel(window) || self.is_window_minimized(window) {
                 continue;
             }
             if !Self::window_root_surface_has_buffer(window) {
@@ -386,19 +652,40 @@ impl Raven {
             return Ok(());
         }
 
-        let output = self
-            .space
-            .outputs()
-            .next()
-            .cloned()
-            .ok_or_else(|| CompositorError::Backend("no output".into()))?;
+        let outputs: Vec<smithay::output::Output> = self.space.outputs().cloned().collect();
+        if outputs.is_empty() {
+            return Err(CompositorError::Backend("no output".into()));
+        }
+
+        // Each output tiles the windows assigned to it.
+        window_outputs::track_windows(self, &windows);
+        for output in &outputs {
+            let output_windows: Vec<smithay::desktop::Window> = windows
+                .iter()
+                .filter(|window| {
+                    window_outputs::window_output(self, window).as_ref() == Some(output)
+                })
+                .cloned()
+                .collect();
+            self.apply_output_layout(&output_windows, output)?;
+        }
+
+        self.restack_floating_windows_above_tiled();
+
+        Ok(())
+    }
 
+    fn apply_output_layout(
+        &mut self,
+        windows: &[smithay::desktop::Window],
+        output: &smithay::output::Output,
+    ) -> Result<(), CompositorError> {
         let out_geo = self
             .space
-            .output_geometry(&output)
+            .output_geometry(output)
             .ok_or_else(|| CompositorError::Backend("no output geometry".into()))?;
 
-        if self.apply_fullscreen_layout_if_needed(&windows, &output, out_geo)? {
+        if self.apply_fullscreen_layout_if_needed(windows, output, out_geo)? {
             return Ok(());
         }
 
@@ -412,54 +699,37 @@ impl Raven {
             if self.window_is_unmapped_toplevel(window) {
                 continue;
             }
-            let target_rect = self
-                .space
-                .element_geometry(window)
-                .unwrap_or_else(|| self.initial_map_rect_for_window(window));
+            let current_rect = self.space.element_geometry(window);
+            let mut target_rect =
+                current_rect.unwrap_or_else(|| self.initial_map_rect_for_window(window));
+            // Left behind by an unplugged output: center it on this one.
+            if !target_rect.overlaps(out_geo) {
+                let work_area = self.output_work_area(output).unwrap_or(out_geo);
+                target_rect.loc = work_area.loc
+                    + Point::from((
+                        (work_area.size.w - target_rect.size.w) / 2,
+                        (work_area.size.h - target_rect.size.h) / 2,
+                    ));
+            }
             self.record_assigned_rect_for_window(window, target_rect);
-            if self.space.element_location(window).is_none() {
+            if current_rect != Some(target_rect) {
                 self.map_window_to_rect(window, target_rect, false);
             }
         }
 
-        let tiled_windows: Vec<smithay::desktop::Window> = windows
+        let mut tiled_windows: Vec<smithay::desktop::Window> = windows
             .iter()
             .filter(|window| !self.is_window_floating(window))
             .cloned()
             .collect();
+        self.sort_in_workspace_order(self.current_workspace, &mut tiled_windows);
         if tiled_windows.is_empty() {
-            self.restack_floating_windows_above_tiled();
             return Ok(());
         }
 
-        let gaps = GapConfig {
-            outer_horizontal: self.config.gaps_outer_horizontal,
-            outer_vertical: self.config.gaps_outer_vertical,
-            inner_horizontal: self.config.gaps_inner_horizontal,
-            inner_vertical: self.config.gaps_inner_vertical,
-        };
-
-        let master_factor = self.config.master_factor;
-        let num_master = self.config.num_master;
-        let smartgaps_enabled = self.config.smart_gaps;
-        let mut layer_map = layer_map_for_output(&output);
-        layer_map.arrange();
-        let work_geo = layer_map.non_exclusive_zone();
-        let layout_geo = if work_geo.size.w > 0 && work_geo.size.h > 0 {
-            work_geo
-        } else {
-            out_geo
-        };
-
-        let geometries = self.layout.arrange(
-            &tiled_windows,
-            layout_geo.size.w as u32,
-            layout_geo.size.h as u32,
-            &gaps,
-            master_factor,
-            num_master,
-            smartgaps_enabled,
-        );
+        let layout_geo = self.output_work_area(output).unwrap_or(out_geo);
+        let mut geometries = self.arrange_tiled(&tiled_windows, layout_geo.size);
+        self.scroll_geometries(output, &tiled_windows, &mut geometries, layout_geo.size.w);
 
         for (window, geom) in tiled_windows.into_iter().zip(geometries.into_iter()) {
             let loc = Point::<i32, Logical>::from((
@@ -477,6 +747,7 @@ impl Raven {
             let needs_reposition = current_location != Some(loc);
             let target_geometry = Rectangle::new(loc, desired_size);
             self.record_assigned_rect_for_window(&window, target_geometry);
+            self.tiled_slots.push((window.clone(), target_geometry));
 
             if !is_mapped || needs_resize {
                 self.configure_window_for_tiled_layout(&window, target_geometry, layout_geo.size);
@@ -487,11 +758,120 @@ impl Raven {
             }
         }
 
-        self.restack_floating_windows_above_tiled();
-
         Ok(())
     }
 
+    /// Tiles for `windows` in a work area of `size` from the current layout, with
+    /// the windows of `no_gaps` rules grown over their gaps.
+    pub(crate) fn arrange_tiled(
+        &self,
+        windows: &[Window],
+        size: Size<i32, Logical>,
+    ) -> Vec<WindowGeometry> {
+        let gaps = GapConfig {
+            outer_horizontal: self.config.gaps_outer_horizontal,
+            outer_vertical: self.config.gaps_outer_vertical,
+            inner_horizontal: self.config.gaps_inner_horizontal,
+            inner_vertical: self.config.gaps_inner_vertical,
+        };
+        let (width, height) = (size.w as u32, size.h as u32);
+        let mut geometries = self.layout().arrange(
+            windows,
+            width,
+            height,
+            &gaps,
+            self.config.master_factor,
+            self.config.num_master,
+            self.config.smart_gaps,
+        );
+
+        let arranged = geometries.clone();
+        for (index, window) in windows.iter().enumerate().take(arranged.len()) {
+            let no_gaps = Self::window_surface_id(window)
+                .is_some_and(|surface| self.window_render_rules(&surface).no_gaps);
+            if no_gaps {
+                geometries[index] = without_gaps(&arranged, index, width, height);
+            }
+        }
+
+        // Borders are drawn outside the window, so each tile leaves room for its own.
+        let border = self.config.border_size.min(i32::MAX as u32 / 2);
+        for geometry in &mut geometries {
+            geometry.x_coordinate += border as i32;
+            geometry.y_coordinate += border as i32;
+            geometry.width = geometry.width.saturating_sub(2 * border).max(1);
+            geometry.height = geometry.height.saturating_sub(2 * border).max(1);
+        }
+        geometries
+    }
+
+    /// Horizontal scroll of the column strip on `output`'s current workspace; 0
+    /// for layouts that do not scroll.
+    pub(crate) fn scroll_offset(&self, output: &smithay::output::Output) -> i32 {
+        if !self.layout().scrolls() {
+            return 0;
+        }
+        self.scroll_offsets
+            .get(&(output.name(), self.current_workspace))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Scroll the strip on `output` just far enough to show the focused column
+    /// whole, and move `geometries` into view accordingly.
+    fn scroll_geometries(
+        &mut self,
+        output: &smithay::output::Output,
+        windows: &[Window],
+        geometries: &mut [WindowGeometry],
+        view_width: i32,
+    ) {
+        if !self.layout().scrolls() {
+            return;
+        }
+        let gap = self.config.gaps_outer_vertical as i32;
+        let strip_end = geometries
+            .iter()
+            .map(|geom| geom.x_coordinate + geom.width as i32)
+            .max()
+            .unwrap_or(0)
+            + gap;
+
+        let mut offset = self.scroll_offset(output);
+        let focused = self
+            .seat
+            .get_keyboard()
+            .and_then(|keyboard| keyboard.current_focus())
+            .and_then(|surface| self.window_for_surface(&surface))
+            .and_then(|focused| windows.iter().position(|window| *window == focused));
+        if let Some(geom) = focused.map(|index| &geometries[index]) {
+            let left = geom.x_coordinate - gap;
+            let right = geom.x_coordinate + geom.width as i32 + gap;
+            if left < offset {
+                offset = left;
+            } else if right > offset + view_width {
+                offset = right - view_width;
+            }
+        }
+        offset = offset.clamp(0, (strip_end - view_width).max(0));
+        self.scroll_offsets
+            .insert((output.name(), self.current_workspace), offset);
+
+        for geom in geometries {
+            geom.x_coordinate -= offset;
+        }
+    }
+
+    /// Whether `window` belongs to another output's scrolled strip and only
+    /// overhangs `output`; it is neither drawn nor hit there.
+    pub(crate) fn window_scrolled_out(
+        &self,
+        window: &Window,
+        output: &smithay::output::Output,
+    ) -> bool {
+        self.layout().scrolls() && self.window_output(window).is_some_and(|own| own != *output)
+    }
+
     fn restack_floating_windows_above_tiled(&mut self) {
         let windows: Vec<Window> = self.space.elements().cloned().collect();
         if windows.len() < 2 {
@@ -668,17 +1048,17 @@ impl Raven {
     #[inline]
     pub(crate) fn debug_assert_state_invariants(&self, _context: &str) {}
 
-    pub(crate) fn queue_redraw_for_outputs_or_all<I>(&mut self, outputs: I)
+    pub(crate) fn queue_redraw_for_outputs_or_all<I>(&mut self, outputs: I, cause: RedrawCause)
     where
         I: IntoIterator<Item = smithay::output::Output>,
     {
         let mut had_output = false;
         for output in outputs {
             had_output = true;
-            crate::backend::udev::queue_redraw_for_output(self, &output);
+            crate::backend::udev::queue_redraw_for_output(self, &output, cause);
         }
         if !had_output {
-            crate::backend::udev::queue_redraw_all(self);
+            crate::backend::udev::queue_redraw_all(self, cause);
         }
     }
 
@@ -705,6 +1085,21 @@ impl Raven {
         self.add_window_to_workspace(self.current_workspace, window);
     }
 
+    /// Sort `windows` by their position in the workspace list, which is the tiling
+    /// order. Stacking changes on every raise, so it cannot be used for that; windows
+    /// missing from the list go last, in their current order.
+    fn sort_in_workspace_order(&self, workspace_index: usize, windows: &mut [Window]) {
+        let Some(workspace) = self.workspaces.get(workspace_index) else {
+            return;
+        };
+        windows.sort_by_key(|window| {
+            workspace
+                .iter()
+                .position(|candidate| Self::windows_match(candidate, window))
+                .unwrap_or(usize::MAX)
+        });
+    }
+
     fn windows_match(lhs: &Window, rhs: &Window) -> bool {
         match (lhs.toplevel(), rhs.toplevel()) {
             (Some(lhs_toplevel), Some(rhs_toplevel)) => {
@@ -892,13 +1287,29 @@ impl Raven {
         }
     }
 
+    pub fn set_window_sticky(&mut self, window: &Window, sticky: bool) {
+        let Some(surface) = Self::window_surface_id(window) else {
+            return;
+        };
+        if sticky {
+            self.sticky_windows.insert(surface);
+        } else {
+            self.sticky_windows.remove(&surface);
+        }
+    }
+
+    pub fn is_window_sticky(&self, window: &Window) -> bool {
+        Self::window_surface_id(window)
+            .is_some_and(|surface| self.sticky_windows.contains(&surface))
+    }
+
     pub fn is_window_floating(&self, window: &Window) -> bool {
         self.floating_windows
             .iter()
             .any(|candidate| Self::windows_match(candidate, window))
     }
 
-    fn active_output_for_pointer(&self) -> Option<smithay::output::Output> {
+    pub(crate) fn active_output_for_pointer(&self) -> Option<smithay::output::Output> {
         self.space
             .outputs()
             .find(|output| {
@@ -910,19 +1321,44 @@ impl Raven {
             .or_else(|| self.space.outputs().next().cloned())
     }
 
+    /// Output a floating window opens on: the one named by its `output` window
+    /// rule when that output is connected, otherwise the one under the pointer.
+    fn floating_output_for_window(&self, window: &Window) -> Option<smithay::output::Output> {
+        window
+            .toplevel()
+            .and_then(|toplevel| rules::surface_rule_output(self, toplevel.wl_surface()))
+            .and_then(|name| {
+                self.space
+                    .outputs()
+                    .find(|output| output.name() == name)
+                    .cloned()
+            })
+            .or_else(|| self.active_output_for_pointer())
+    }
+
+    /// Part of `output` left for windows once layer-shell exclusive zones and
+    /// `raven reserve` struts are taken out. Falls back to the whole output when
+    /// exclusive zones leave nothing.
+    pub(crate) fn output_work_area(
+        &self,
+        output: &smithay::output::Output,
+    ) -> Option<Rectangle<i32, Logical>> {
+        let out_geo = self.space.output_geometry(output)?;
+        let mut layer_map = layer_map_for_output(output);
+        layer_map.arrange();
+        let work_geo = layer_map.non_exclusive_zone();
+        let work_geo = if work_geo.size.w > 0 && work_geo.size.h > 0 {
+            work_geo
+        } else {
+            out_geo
+        };
+        Some(reserve::shrink_by_reservations(self, output, work_geo))
+    }
+
     fn default_floating_location(&self, window: &Window) -> (i32, i32) {
-        self.active_output_for_pointer()
+        self.floating_output_for_window(window)
             .as_ref()
-            .and_then(|output| {
-                let mut layer_map = layer_map_for_output(output);
-                layer_map.arrange();
-                let work_geo = layer_map.non_exclusive_zone();
-                if work_geo.size.w > 0 && work_geo.size.h > 0 {
-                    Some(work_geo)
-                } else {
-                    self.space.output_geometry(output)
-                }
-            })
+            .and_then(|output| self.output_work_area(output))
             .map(|geometry| {
                 let window_geo = window.geometry();
                 // For fixed-size popups (Steam splash/sign-in, dialogs), use size hints for
@@ -930,12 +1366,26 @@ impl Raven {
                 let hint_size = window
                     .toplevel()
                     .and_then(|toplevel| Self::fixed_hint_size_for_surface(toplevel.wl_surface()));
-                let hinted_or_current_w = hint_size.map(|size| size.w).unwrap_or(window_geo.size.w);
-                let hinted_or_current_h = hint_size.map(|size| size.h).unwrap_or(window_geo.size.h);
+                // Otherwise never center a window smaller than the minimum it asked for.
+                let min_size = window
+                    .toplevel()
+                    .map(|toplevel| Self::surface_min_max_size(toplevel.wl_surface()).0)
+                    .unwrap_or_default();
+                let hinted_or_current_w = hint_size
+                    .map(|size| size.w)
+                    .unwrap_or(window_geo.size.w.max(min_size.w));
+                let hinted_or_current_h = hint_size
+                    .map(|size| size.h)
+                    .unwrap_or(window_geo.size.h.max(min_size.h));
                 let window_width = hinted_or_current_w.clamp(1, geometry.size.w);
                 let window_height = hinted_or_current_h.clamp(1, geometry.size.h);
-                let x = geometry.loc.x + (geometry.size.w - window_width) / 2;
-                let y = geometry.loc.y + (geometry.size.h - window_height) / 2;
+                // Centered unless a window rule places it.
+                let position = window
+                    .toplevel()
+                    .and_then(|toplevel| rules::surface_rule_position(self, toplevel.wl_surface()))
+                    .unwrap_or(RulePosition::CENTER);
+                let x = geometry.loc.x + position.x.offset(geometry.size.w, window_width);
+                let y = geometry.loc.y + position.y.offset(geometry.size.h, window_height);
                 (x, y)
             })
             .unwrap_or((80, 80))
@@ -1072,10 +1522,9 @@ impl Raven {
         }
 
         if self.is_window_floating(window) {
-            return Rectangle::new(
-                self.default_floating_location(window).into(),
-                self.fallback_window_size(window),
-            );
+            let location = session::floating_location_for_window(self, window)
+                .unwrap_or_else(|| self.default_floating_location(window));
+            return Rectangle::new(location.into(), self.fallback_window_size(window));
         }
 
         self.pre_layout_tiled_slot_for_window(window)
@@ -1099,7 +1548,8 @@ impl Raven {
         activate: bool,
     ) -> bool {
         // Workspaces may contain unmapped toplevel entries; those map only via root commit.
-        if self.window_is_unmapped_toplevel(window) {
+        // Minimized windows wait for an unminimize.
+        if self.window_is_unmapped_toplevel(window) || self.is_window_minimized(window) {
             return false;
         }
         self.map_window_to_initial_location(window, activate);
@@ -1110,24 +1560,19 @@ impl Raven {
         &self,
         window: &Window,
     ) -> Option<(Point<i32, Logical>, Size<i32, Logical>, Size<i32, Logical>)> {
-        let output = self.space.outputs().next().cloned()?;
-        let out_geo = self.space.output_geometry(&output)?;
-
-        let mut layer_map = layer_map_for_output(&output);
-        layer_map.arrange();
-        let work_geo = layer_map.non_exclusive_zone();
-        let layout_geo = if work_geo.size.w > 0 && work_geo.size.h > 0 {
-            work_geo
-        } else {
-            out_geo
-        };
+        let output = window_outputs::window_output(self, window)?;
+        let layout_geo = self.output_work_area(&output)?;
 
         // Predict where this window will be after the next layout pass by arranging
-        // the currently mapped tiled set plus this window (if it is not mapped yet).
+        // the currently mapped tiled set on its output plus this window (if it is
+        // not mapped yet).
         let mut tiled_windows: Vec<Window> = self
             .space
             .elements()
             .filter(|candidate| !self.is_window_floating(candidate))
+            .filter(|candidate| {
+                window_outputs::window_output(self, candidate).as_ref() == Some(&output)
+            })
             .filter(|candidate| Self::window_has_live_client(candidate))
             .filter(|candidate| Self::window_root_surface_has_buffer(candidate))
             .cloned()
@@ -1136,6 +1581,10 @@ impl Raven {
         if !self.is_window_mapped(window) && !self.is_window_floating(window) {
             tiled_windows.push(window.clone());
         }
+        let workspace_index = self
+            .workspace_index_for_window(window)
+            .unwrap_or(self.current_workspace);
+        self.sort_in_workspace_order(workspace_index, &mut tiled_windows);
 
         if tiled_windows.is_empty() {
             return None;
@@ -1152,22 +1601,8 @@ impl Raven {
             return None;
         }
 
-        let gaps = GapConfig {
-            outer_horizontal: self.config.gaps_outer_horizontal,
-            outer_vertical: self.config.gaps_outer_vertical,
-            inner_horizontal: self.config.gaps_inner_horizontal,
-            inner_vertical: self.config.gaps_inner_vertical,
-        };
-
-        let geometries = self.layout.arrange(
-            &tiled_windows,
-            layout_geo.size.w as u32,
-            layout_geo.size.h as u32,
-            &gaps,
-            self.config.master_factor,
-            self.config.num_master,
-            self.config.smart_gaps,
-        );
+        let geometries = self.arrange_tiled(&tiled_windows, layout_geo.size);
+        let scroll_offset = self.scroll_offset(&output);
 
         tiled_windows
             .iter()
@@ -1175,7 +1610,7 @@ impl Raven {
             .find_map(|(candidate, geom)| {
                 if Self::windows_match(candidate, window) {
                     let loc = Point::<i32, Logical>::from((
-                        layout_geo.loc.x + geom.x_coordinate,
+                        layout_geo.loc.x + geom.x_coordinate - scroll_offset,
                         layout_geo.loc.y + geom.y_coordinate,
                     ));
                     let size = Size::<i32, Logical>::from((geom.width as i32, geom.height as i32));
@@ -1282,11 +1717,12 @@ impl Raven {
         &self,
         app_id: Option<&str>,
         title: Option<&str>,
+        window_type: WindowType,
     ) -> bool {
-        self.config
-            .window_rules
-            .iter()
-            .any(|rule| rule.floating.is_some() && rule.matches(app_id, title))
+        self.config.window_rules.iter().any(|rule| {
+            (rule.floating.is_some() || rule.sticky == Some(true))
+                && rule.matches(app_id, title, window_type)
+        })
     }
 
     fn surface_min_max_size(surface: &WlSurface) -> (Size<i32, Logical>, Size<i32, Logical>) {
@@ -1299,8 +1735,12 @@ impl Raven {
 
     fn has_window_rule_metadata_gap(&self, app_id: Option<&str>, title: Option<&str>) -> bool {
         self.config.window_rules.iter().any(|rule| {
-            ((rule.class.is_some() || rule.app_id.is_some()) && app_id.is_none())
-                || (rule.title.is_some() && title.is_none())
+            let wants_app_id = rule.class.is_some()
+                || rule.app_id.is_some()
+                || rule.not_class.is_some()
+                || rule.not_app_id.is_some();
+            let wants_title = rule.title.is_some() || rule.not_title.is_some();
+            (wants_app_id && app_id.is_none()) || (wants_title && title.is_none())
         })
     }
 
@@ -1313,6 +1753,10 @@ impl Raven {
             return (false, "exclusive-state");
         }
 
+        if self.modal_dialog_ids.contains(surface) {
+            return (true, "modal");
+        }
+
         if window
             .toplevel()
             .is_some_and(|toplevel| toplevel.parent().is_some())
@@ -1346,8 +1790,12 @@ impl Raven {
         configured_floating: bool,
     ) -> (bool, bool, bool, &'static str) {
         let (app_id, title) = Self::surface_app_id_and_title(surface);
-        let has_explicit_floating_rule =
-            self.has_matching_explicit_floating_rule(app_id.as_deref(), title.as_deref());
+        let has_explicit_floating_rule = session::entry_for_surface(self, surface).is_some()
+            || self.has_matching_explicit_floating_rule(
+                app_id.as_deref(),
+                title.as_deref(),
+                self.surface_window_type(surface),
+            );
         let (auto_floating, auto_reason) = self.compute_auto_floating_for_surface(surface, window);
         let final_floating = if has_explicit_floating_rule {
             configured_floating
@@ -1432,6 +1880,7 @@ impl Raven {
         self.clear_assigned_rect_for_surface(surface);
         self.clear_reported_size_for_surface(surface);
         self.pending_unmapped_maximized_ids.remove(surface);
+        self.held_floating_map_ids.remove(surface);
         self.pending_initial_configure_ids.remove(surface);
         self.pending_initial_configure_idle_ids.remove(surface);
         self.unmapped_toplevel_ids.remove(surface);
@@ -1445,6 +1894,129 @@ impl Raven {
         rules::resolve_window_rules_for_surface(self, surface)
     }
 
+    pub(crate) fn surface_ignores_focus_follows_mouse(&self, surface: &WlSurface) -> bool {
+        rules::surface_ignores_focus_follows_mouse(self, surface)
+    }
+
+    pub fn surface_window_type(&self, surface: &WlSurface) -> WindowType {
+        rules::surface_window_type(self, surface)
+    }
+
+    pub fn set_surface_modal(&mut self, surface: &WlSurface, modal: bool) {
+        if modal {
+            self.modal_dialog_ids.insert(surface.clone());
+        } else {
+            self.modal_dialog_ids.remove(surface);
+        }
+    }
+
+    pub fn clear_surface_modal(&mut self, surface: &WlSurface) {
+        self.modal_dialog_ids.remove(surface);
+    }
+
+    /// Frame rate cap from the last matching `max_fps` window rule, as matched when
+    /// the window's render rules were last refreshed.
+    pub(crate) fn surface_max_fps(&self, surface: &WlSurface) -> Option<u32> {
+        self.window_render_rules
+            .get(surface)
+            .and_then(|rules| rules.max_fps)
+    }
+
+    /// Whether a visible surface holds an idle inhibitor.
+    pub fn idle_inhibited(&self) -> bool {
+        !idle_inhibit::active_inhibitors(self).is_empty()
+    }
+
+    pub(crate) fn idle_inhibitors_changed(&mut self) {
+        idle_inhibit::inhibitors_changed(self);
+        idle::refresh_inhibited(self);
+    }
+
+    /// Reset the idle timers; called for every input event.
+    pub(crate) fn notify_activity(&mut self) {
+        idle::notify_activity(self);
+    }
+
+    /// Keep ext-idle-notify clients in step with visible idle inhibitors.
+    pub fn refresh_idle_inhibit(&mut self) {
+        idle::refresh_inhibited(self);
+    }
+
+    /// Opacity, border and gap settings the window rules give `surface`; an
+    /// urgent window takes `urgent_border_color` instead of its rule's border.
+    pub(crate) fn window_render_rules(&self, surface: &WlSurface) -> WindowRenderRules {
+        let mut rules = self
+            .window_render_rules
+            .get(surface)
+            .copied()
+            .unwrap_or_default();
+        if self.urgent_windows.contains_key(surface) {
+            rules.border_color = self.config.urgent_border_color.or(rules.border_color);
+        }
+        rules
+    }
+
+    /// Border color of `surface`'s window: urgency or its `border_color` rule
+    /// first, then `border_active_color` while it has keyboard focus and
+    /// `border_inactive_color` otherwise.
+    pub(crate) fn window_border_color(&self, surface: &WlSurface) -> [f32; 4] {
+        if let Some(color) = self.window_render_rules(surface).border_color {
+            return color;
+        }
+        let focused = self
+            .seat
+            .get_keyboard()
+            .and_then(|keyboard| keyboard.current_focus());
+        if focused.as_ref() == Some(surface) {
+            self.config.border_active_color
+        } else {
+            self.config.border_inactive_color
+        }
+    }
+
+    /// Corner radius of `surface`'s window in logical pixels: its rule's, else
+    /// `corner_radius`.
+    pub(crate) fn window_corner_radius(&self, surface: &WlSurface) -> u32 {
+        self.window_render_rules(surface)
+            .corner_radius
+            .unwrap_or(self.config.corner_radius)
+    }
+
+    /// Clear the urgency of the window keyboard focus moved to.
+    pub(crate) fn clear_window_urgency(&mut self, surface: Option<&WlSurface>) {
+        urgency::focused(self, surface);
+    }
+
+    pub(crate) fn is_window_urgent(&self, surface: &WlSurface) -> bool {
+        self.urgent_windows.contains_key(surface)
+    }
+
+    pub(crate) fn is_workspace_urgent(&self, index: usize) -> bool {
+        urgency::workspace_urgent(self, index)
+    }
+
+    /// Jump to the window that most recently became urgent.
+    pub(crate) fn focus_urgent(&mut self) {
+        urgency::focus_urgent(self);
+    }
+
+    pub(crate) fn is_window_minimized(&self, window: &Window) -> bool {
+        Self::window_surface_id(window)
+            .is_some_and(|surface| self.minimized_windows.contains(&surface))
+    }
+
+    pub(crate) fn minimize_window(&mut self, window: &Window) {
+        minimize::minimize(self, window);
+    }
+
+    pub(crate) fn unminimize_window(&mut self, window: &Window) {
+        minimize::unminimize(self, window);
+    }
+
+    pub(crate) fn minimize_focused_window(&mut self) {
+        minimize::minimize_focused(self);
+    }
+
     pub fn apply_window_rule_size_to_window(
         &self,
         window: &Window,
@@ -1473,10 +2045,41 @@ impl Raven {
         rules::maybe_apply_deferred_window_rules(self, surface);
     }
 
+    pub fn hold_floating_first_map(&mut self, surface: &WlSurface, window: &Window) -> bool {
+        rules::hold_floating_first_map(self, surface, window)
+    }
+
     pub fn maybe_recenter_floating_window_after_commit(&mut self, surface: &WlSurface) {
         rules::maybe_recenter_floating_window_after_commit(self, surface);
     }
 
+    /// In strict protocol mode, post an error for spec violations in this commit.
+    /// Returns `false` if the client was sent a protocol error.
+    pub fn check_surface_conformance(&self, surface: &WlSurface) -> bool {
+        conformance::check_surface_commit(self, surface)
+    }
+
+    /// Charge the committed buffer to its client's memory budget. Returns `false`
+    /// if the client went over budget and was disconnected.
+    pub fn account_buffer_commit(&mut self, surface: &WlSurface) -> bool {
+        buffer_budget::account_commit(self, surface)
+    }
+
+    /// Whether importing `dmabuf` stays within the global buffer budget.
+    pub fn buffer_import_allowed(
+        &mut self,
+        dmabuf: &smithay::backend::allocator::dmabuf::Dmabuf,
+    ) -> bool {
+        buffer_budget::import_allowed(self, dmabuf)
+    }
+
+    pub fn buffer_destroyed(
+        &mut self,
+        buffer: &smithay::reexports::wayland_server::protocol::wl_buffer::WlBuffer,
+    ) {
+        buffer_budget::buffer_destroyed(self, buffer);
+    }
+
     pub fn handle_ipc_stream(&mut self, mut stream: UnixStream) {
         ipc::handle_ipc_stream(self, &mut stream);
     }
@@ -1493,6 +2096,167 @@ impl Raven {
         workspaces::switch_workspace(self, target_workspace)
     }
 
+    /// The running workspace switch animation as drawn on `output`, if any.
+    pub fn workspace_transition_frame(
+        &self,
+        output: &smithay::output::Output,
+    ) -> Option<WorkspaceTransitionFrame> {
+        workspace_transition::frame(self, output)
+    }
+
+    /// Switch to the first empty workspace other than the current one, adding a
+    /// workspace when none is empty.
+    pub fn switch_to_empty_workspace(&mut self) -> Result<(), CompositorError> {
+        workspaces::switch_to_empty_workspace(self)
+    }
+
+    /// Switch to the next or previous workspace, wrapping around.
+    pub fn switch_workspace_relative(&mut self, forward: bool) -> Result<(), CompositorError> {
+        workspaces::switch_workspace_relative(self, forward)
+    }
+
+    /// Switch back to the workspace that was active before the current one.
+    pub fn workspace_back_and_forth(&mut self) -> Result<(), CompositorError> {
+        workspaces::workspace_back_and_forth(self)
+    }
+
+    /// Drop empty, unfocused workspaces past `workspace_count` from the end.
+    pub fn collect_empty_workspaces(&mut self) {
+        workspaces::collect_empty_workspaces(self);
+    }
+
+    /// Exchange the contents of two workspaces.
+    pub fn swap_workspaces(&mut self, first: usize, second: usize) -> Result<(), CompositorError> {
+        workspaces::swap_workspaces(self, first, second)
+    }
+
+    /// Move a workspace to another index, shifting the ones in between.
+    pub fn reorder_workspace(&mut self, from: usize, to: usize) -> Result<(), CompositorError> {
+        workspaces::reorder_workspace(self, from, to)
+    }
+
+    pub fn set_layout_debug_overlay(&mut self, enabled: bool) {
+        debug_overlay::set_layout_debug_overlay(self, enabled);
+    }
+
+    /// Alpha `window` is drawn with: its `opacity` rule, faded by `dim_inactive`
+    /// while it lacks keyboard focus. Fullscreen windows are drawn opaque, and
+    /// video or games are not faded.
+    pub fn window_alpha(&self, window: &Window) -> f32 {
+        if self.window_effective_fullscreen_state(window) {
+            return 1.0;
+        }
+        let Some(surface) = Self::window_surface_id(window) else {
+            return 1.0;
+        };
+        let opacity = self.window_render_rules(&surface).opacity.unwrap_or(1.0);
+        let dim = self.config.dim_inactive;
+        if dim <= 0.0 || Self::window_shows_video_or_game(window) {
+            return opacity;
+        }
+        let focused = self
+            .seat
+            .get_keyboard()
+            .and_then(|keyboard| keyboard.current_focus());
+        if focused.as_ref() == Some(&surface) {
+            opacity
+        } else {
+            opacity * (1.0 - dim)
+        }
+    }
+
+    /// Opacity of the black veil drawn over `window`, or `None` when it is shown
+    /// as is: dimming is off, the window has keyboard focus or is fullscreen, or
+    /// its content-type hint says it shows video or a game.
+    pub fn unfocused_dim_alpha(&self, window: &Window) -> Option<f32> {
+        let dim = self.config.dim_unfocused;
+        if dim <= 0.0 || self.window_effective_fullscreen_state(window) {
+            return None;
+        }
+        let surface = window.toplevel()?.wl_surface().clone();
+        let focused = self
+            .seat
+            .get_keyboard()
+            .and_then(|keyboard| keyboard.current_focus());
+        if focused.as_ref() == Some(&surface) {
+            return None;
+        }
+        (!Self::window_shows_video_or_game(window)).then_some(dim)
+    }
+
+    /// Whether the content-type hint of `window` says it shows video or a game.
+    pub(crate) fn window_shows_video_or_game(window: &Window) -> bool {
+        let Some(toplevel) = window.toplevel() else {
+            return false;
+        };
+        with_states(toplevel.wl_surface(), |states| {
+            let mut guard = states.cached_state.get::<ContentTypeSurfaceCachedState>();
+            matches!(
+                guard.current().content_type(),
+                wp_content_type_v1::Type::Video | wp_content_type_v1::Type::Game
+            )
+        })
+    }
+
+    /// Compositor-drawn overlays for `output` (config error banner, layout debug
+    /// labels, reload preview, screenshot region selection), relative to the output origin, front to back.
+    pub fn overlay_elements(
+        &self,
+        output: &smithay::output::Output,
+    ) -> Vec<SolidColorRenderElement> {
+        let mut elements = config_error::config_error_banner_elements(self, output);
+        elements.extend(debug_overlay::layout_debug_overlay_elements(self, output));
+        elements.extend(layout_preview::layout_preview_elements(self, output));
+        elements.extend(touch_hold::touch_hold_elements(self, output));
+        elements.extend(find_cursor::find_cursor_elements(self, output));
+        elements.extend(screenshot::region_selection_elements(self, output));
+        elements
+    }
+
+    /// A client started a pointer drag-and-drop; edge dwell may now switch workspaces.
+    pub(crate) fn dnd_drag_started(&mut self) {
+        dnd_edge::drag_started(self);
+    }
+
+    pub(crate) fn update_dnd_edge_switch(&mut self) {
+        dnd_edge::pointer_moved(self);
+    }
+
+    /// Draw a shrinking ring around the pointer for a moment.
+    pub(crate) fn find_cursor(&mut self) {
+        find_cursor::start(self);
+    }
+
+    pub(crate) fn accessx_key(&mut self, key_code: Keycode, key_state: KeyState, time_msec: u32) {
+        accessx::key_event(self, key_code, key_state, time_msec);
+    }
+
+    pub(crate) fn touch_hold_down(&mut self, slot: TouchSlot, location: Point<f64, Logical>) {
+        touch_hold::touch_down(self, slot, location);
+    }
+
+    pub(crate) fn touch_hold_motion(&mut self, slot: TouchSlot, location: Point<f64, Logical>) {
+        touch_hold::touch_motion(self, slot, location);
+    }
+
+    pub(crate) fn touch_hold_up(&mut self, slot: TouchSlot) {
+        touch_hold::touch_up(self, slot);
+    }
+
+    pub(crate) fn touch_hold_cancel(&mut self) {
+        touch_hold::cancel(self);
+    }
+
+    /// Dismiss the config error banner if `location` is on it.
+    pub fn dismiss_config_error_at(&mut self, location: Point<f64, Logical>) -> bool {
+        config_error::dismiss_config_error_at(self, location)
+    }
+
+    /// Apply `last_window_closed` if the focused workspace just lost its last window.
+    pub fn handle_last_window_closed(&mut self) {
+        workspaces::handle_last_window_closed(self);
+    }
+
     pub fn move_focused_window_to_workspace(
         &mut self,
         target_workspace: usize,
@@ -1500,12 +2264,111 @@ impl Raven {
         workspaces::move_focused_window_to_workspace(self, target_workspace)
     }
 
-    pub fn spawn_terminal(&self) {
-        self.spawn_command(&self.config.terminal);
+    pub fn close_window(&mut self, window: &Window, force: bool) -> Result<(), CompositorError> {
+        workspaces::close_window(self, window, force)
+    }
+
+    /// Fire `on_first_window` / `on_workspace_empty` hooks for workspaces whose occupancy
+    /// changed since the last call.
+    pub fn run_workspace_hooks(&mut self) {
+        workspaces::run_workspace_hooks(self);
+    }
+
+    pub fn keymap(&self) -> &KeymapSettings {
+        &self.keymap
+    }
+
+    /// Recompile the seat keyboard's keymap and send it to every bound `wl_keyboard`.
+    pub fn set_keymap(&mut self, settings: KeymapSettings) -> Result<(), CompositorError> {
+        keymap::set_keymap(self, settings)
+    }
+
+    /// Save a PNG of `target` (`all` or an output name); where it was written goes
+    /// to `reply` once the file is saved.
+    pub fn take_screenshot(
+        &mut self,
+        target: &str,
+        path: Option<&std::path::Path>,
+        reply: UnixStream,
+    ) -> Result<(), CompositorError> {
+        screenshot::take_screenshot(self, target, path, reply)
+    }
+
+    /// Save a PNG of the focused window; where it was written goes to `reply` once
+    /// the file is saved.
+    pub fn take_window_screenshot(
+        &mut self,
+        path: Option<&std::path::Path>,
+        reply: UnixStream,
+    ) -> Result<(), CompositorError> {
+        screenshot::take_window_screenshot(self, path, reply)
+    }
+
+    pub(crate) fn region_selection_active(&self) -> bool {
+        self.region_selection.is_some()
+    }
+
+    pub(crate) fn region_selection_pressed(&mut self, location: Point<f64, Logical>) {
+        screenshot::region_pressed(self, location);
+    }
+
+    pub(crate) fn region_selection_moved(&mut self, location: Point<f64, Logical>) {
+        screenshot::region_moved(self, location);
+    }
+
+    pub(crate) fn finish_region_selection(&mut self) {
+        screenshot::finish_region_selection(self);
+    }
+
+    pub(crate) fn cancel_region_selection(&mut self) {
+        screenshot::cancel_region_selection(self);
+    }
+
+    pub(crate) fn next_frame_screenshot_pending(&self, output: &smithay::output::Output) -> bool {
+        screenshot::next_frame_screenshot_pending(self, output)
+    }
+
+    pub(crate) fn next_frame_screenshot_captured(
+        &mut self,
+        output: &smithay::output::Output,
+        capture: Result<crate::backend::capture::OutputCapture, CompositorError>,
+    ) {
+        screenshot::next_frame_captured(self, output, capture);
+    }
+
+    /// Send pending workspace/window events to sway IPC subscribers.
+    pub fn broadcast_sway_ipc_events(&mut self) {
+        sway_ipc::broadcast_events(self);
+    }
+
+    /// A window committed or closed; its sway IPC snapshot is stale.
+    pub(crate) fn note_sway_ipc_window_change(&mut self) {
+        sway_ipc::note_windows_changed(self);
+    }
+
+    /// Report the mode an output was brought up with to sway IPC `output` subscribers.
+    pub fn broadcast_output_mode_event(
+        &mut self,
+        output: &str,
+        mode: smithay::output::Mode,
+        reason: &str,
+    ) {
+        sway_ipc::broadcast_output_mode_event(self, output, mode, reason);
+    }
+
+    /// Re-send the clients report to `raven clients --watch` sessions if it changed.
+    pub fn refresh_clients_watchers(&mut self) {
+        ipc::refresh_clients_watchers(self);
+    }
+
+    pub fn spawn_terminal(&mut self) {
+        let command = self.config.terminal.clone();
+        self.spawn_keybind_command(&command);
     }
 
-    pub fn spawn_launcher(&self) {
-        self.spawn_command(&self.config.launcher);
+    pub fn spawn_launcher(&mut self) {
+        let command = self.config.launcher.clone();
+        self.spawn_keybind_command(&command);
     }
 
     fn infer_command_program(command: &str) -> Option<&str> {
@@ -1827,6 +2690,123 @@ impl Raven {
         runtime::spawn_command(self, command);
     }
 
+    pub fn spawn_command_with_env(&self, command: &str, env: &[(&str, String)]) {
+        runtime::spawn_command_with_env(self, command, env);
+    }
+
+    /// Run the `events.output_added` hook.
+    pub fn output_added_event(&self, output_name: &str) {
+        events::output_added(self, output_name);
+    }
+
+    /// Run the `events.output_removed` hook.
+    pub fn output_removed_event(&self, output_name: &str) {
+        events::output_removed(self, output_name);
+    }
+
+    /// Switch `output_name`, or every mapped output, on or off.
+    pub fn set_dpms(
+        &mut self,
+        output_name: Option<&str>,
+        mode: DpmsMode,
+    ) -> Result<(), CompositorError> {
+        dpms::set_dpms(self, output_name, mode)
+    }
+
+    /// Report a DPMS change to output power management clients.
+    pub fn output_power_changed(&self, output: &smithay::output::Output, on: bool) {
+        self.output_power_state.power_changed(output, on);
+    }
+
+    /// Fail the output power and gamma controls of an unplugged output.
+    pub fn output_power_removed(&mut self, output: &smithay::output::Output) {
+        self.output_power_state.output_removed(output);
+        self.gamma_control_state.output_removed(output);
+    }
+
+    /// Re-tile after an output was plugged or unplugged, so windows assigned to it
+    /// move on or off it.
+    pub fn outputs_changed(&mut self) {
+        night_light::outputs_changed(self);
+        if self.space.outputs().next().is_none() {
+            return;
+        }
+        if let Err(err) = self.apply_layout() {
+            tracing::warn!("failed to apply layout after output change: {err}");
+        }
+    }
+
+    /// Run `events.window_opened` and the matching rule's `on_open` for a toplevel
+    /// that just mapped.
+    pub fn window_opened_event(&self, surface: &WlSurface) {
+        events::window_opened(self, surface);
+    }
+
+    /// Spawn `command` on behalf of a keybind, handing it an activation token that
+    /// marks the launch as user-initiated.
+    pub fn spawn_keybind_command(&mut self, command: &str) {
+        activation::spawn_keybind_command(self, command);
+    }
+
+    pub fn handle_activation_request(
+        &mut self,
+        token_data: &XdgActivationTokenData,
+        surface: &WlSurface,
+    ) {
+        activation::request_activation(self, token_data, surface);
+    }
+
+    /// Whether a window that is just opening may take keyboard focus, given
+    /// `new_window_output_focus`.
+    pub fn may_focus_new_window(&self, window: &Window) -> bool {
+        activation::may_focus_new_window(self, window)
+    }
+
+    /// Id of the toplevel behind `surface`, as listed by `raven clients`.
+    pub fn window_id(&self, surface: &WlSurface) -> Option<u64> {
+        self.window_ids.get(surface).copied()
+    }
+
+    pub(crate) fn assign_window_id(&mut self, surface: &WlSurface) -> u64 {
+        if let Some(id) = self.window_ids.get(surface) {
+            return *id;
+        }
+        let id = self.next_window_id;
+        self.next_window_id += 1;
+        self.window_ids.insert(surface.clone(), id);
+        id
+    }
+
+    pub(crate) fn release_window_id(&mut self, surface: &WlSurface) {
+        self.window_ids.remove(surface);
+    }
+
+    /// Output `window` tiles on; see `window_outputs`.
+    pub(crate) fn window_output(&self, window: &Window) -> Option<smithay::output::Output> {
+        window_outputs::window_output(self, window)
+    }
+
+    pub(crate) fn toplevel_output(&self, window: &Window) -> Option<smithay::output::Output> {
+        window_outputs::toplevel_output(self, window)
+    }
+
+    pub(crate) fn forget_window_output(&mut self, surface: &WlSurface) {
+        window_outputs::forget_window(self, surface);
+    }
+
+    pub fn claim_autostart_workspace(&mut self, surface: &WlSurface) {
+        runtime::claim_autostart_workspace(self, surface);
+    }
+
+    pub fn clear_autostart_workspace(&mut self, surface: &WlSurface) {
+        self.autostart_workspace_by_surface.remove(surface);
+        session::forget_session_window(self, surface);
+    }
+
+    pub fn order_session_windows(&mut self, window: &Window) {
+        session::order_session_windows(self, window);
+    }
+
     pub fn run_startup_tasks(&mut self) {
         runtime::run_startup_tasks(self);
     }
@@ -1839,7 +2819,15 @@ impl Raven {
         runtime::apply_decoration_preferences(self);
     }
 
-    fn ensure_portal_preferences_file() {
+    /// Write Raven's xdg-desktop-portal preferences, or bring a file Raven wrote
+    /// earlier up to date; files the user edited are left alone. ScreenCast is
+    /// routed to Raven only once its portal backend is serving.
+    fn ensure_portal_preferences_file(screencast: bool) {
+        let wanted = if screencast {
+            Self::default_portal_preferences()
+        } else {
+            Self::pre_screencast_portal_preferences()
+        };
         let config_root = std::env::var_os("XDG_CONFIG_HOME")
             .filter(|value| !value.is_empty())
             .map(PathBuf::from)
@@ -1859,16 +2847,25 @@ impl Raven {
 
         if portal_conf.exists() {
             match fs::read_to_string(&portal_conf) {
-                Ok(existing) if existing.trim() == Self::legacy_portal_preferences().trim() => {
-                    if let Err(err) = fs::write(&portal_conf, Self::default_portal_preferences()) {
+                Ok(existing)
+                    if existing.trim() != wanted.trim()
+                        && [
+                            Self::legacy_portal_preferences(),
+                            Self::pre_screencast_portal_preferences(),
+                            Self::default_portal_preferences(),
+                        ]
+                        .iter()
+                        .any(|ours| existing.trim() == ours.trim()) =>
+                {
+                    if let Err(err) = fs::write(&portal_conf, wanted) {
                         tracing::warn!(
                             path = %portal_conf.display(),
-                            "failed to migrate legacy portal preferences: {err}"
+                            "failed to update portal preferences: {err}"
                         );
                     } else {
                         tracing::info!(
                             path = %portal_conf.display(),
-                            "migrated legacy portal preferences"
+                            "updated portal preferences"
                         );
                     }
                 }
@@ -1891,7 +2888,7 @@ impl Raven {
             return;
         }
 
-        if let Err(err) = fs::write(&portal_conf, Self::default_portal_preferences()) {
+        if let Err(err) = fs::write(&portal_conf, wanted) {
             tracing::warn!(
                 path = %portal_conf.display(),
                 "failed to write portal preferences file: {err}"
@@ -1909,6 +2906,18 @@ org.freedesktop.impl.portal.Access=gtk;\n\
 org.freedesktop.impl.portal.Notification=gtk;\n\
 org.freedesktop.impl.portal.FileChooser=gtk;\n\
 org.freedesktop.impl.portal.Settings=gtk;\n\
+org.freedesktop.impl.portal.Secret=gnome-keyring;\n\
+org.freedesktop.impl.portal.ScreenCast=raven;\n"
+    }
+
+    /// Defaults written before Raven served ScreenCast itself.
+    fn pre_screencast_portal_preferences() -> &'static str {
+        "[preferred]\n\
+default=gtk;\n\
+org.freedesktop.impl.portal.Access=gtk;\n\
+org.freedesktop.impl.portal.Notification=gtk;\n\
+org.freedesktop.impl.portal.FileChooser=gtk;\n\
+org.freedesktop.impl.portal.Settings=gtk;\n\
 org.freedesktop.impl.portal.Secret=gnome-keyring;\n"
     }
 
@@ -2401,7 +3410,7 @@ org.freedesktop.impl.portal.Secret=gnome-keyring;\n"
         image_path: PathBuf,
         socket_name: OsString,
         runtime_dir: Option<OsString>,
-    ) {
+    ) -> Result<(), String> {
         let namespace = Self::SWWW_NAMESPACE;
 
         let daemon_log_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
@@ -2484,7 +3493,7 @@ org.freedesktop.impl.portal.Secret=gnome-keyring;\n"
                 namespace,
                 "swww-daemon did not become ready; skipping wallpaper"
             );
-            return;
+            return Err("swww-daemon did not become ready".to_owned());
         }
 
         for attempt in 1..=8 {
@@ -2505,7 +3514,7 @@ org.freedesktop.impl.portal.Secret=gnome-keyring;\n"
             match output {
                 Ok(result) if result.status.success() => {
                     tracing::info!(path = %image_path.display(), "applied wallpaper with swww");
-                    return;
+                    return Ok(());
                 }
                 Ok(result) => {
                     let stderr = String::from_utf8_lossy(&result.stderr).trim().to_owned();
@@ -2515,29 +3524,32 @@ org.freedesktop.impl.portal.Secret=gnome-keyring;\n"
                             stderr,
                             "swww img failed"
                         );
-                        return;
+                        return Err(format!("swww img failed: {stderr}"));
                     }
                 }
                 Err(err) => {
                     if attempt == 8 {
                         tracing::warn!("failed to execute swww img: {err}");
-                        return;
+                        return Err(format!("failed to execute swww img: {err}"));
                     }
                 }
             }
 
             thread::sleep(Duration::from_millis(125));
         }
+        Err("swww img did not succeed".to_owned())
     }
 
-    pub fn apply_wallpaper(&self) {
+    pub fn apply_wallpaper(&mut self) {
+        builtin_wallpaper::apply(self);
         let wallpaper = self.config.wallpaper.clone();
-        if !wallpaper.enabled {
+        if !wallpaper.enabled || wallpaper.builtin {
             return;
         }
 
+        let slideshow_image = wallpaper_slideshow::current_image(self);
         let restore_command = wallpaper.restore_command.trim();
-        if !restore_command.is_empty() {
+        if slideshow_image.is_none() && !restore_command.is_empty() {
             let command = if restore_command == "waypaper --restore" {
                 // Common path: sanitize SWWW_* env and ensure a daemon for empty/default namespace
                 // before waypaper restore.
@@ -2550,23 +3562,42 @@ org.freedesktop.impl.portal.Secret=gnome-keyring;\n"
             return;
         }
 
-        let image = wallpaper.image.trim();
-        if image.is_empty() {
-            tracing::warn!("wallpaper is enabled but no image path is configured");
-            return;
-        }
-
-        let image_path = Self::expand_home_path(image);
+        let image_path = if let Some(image_path) = slideshow_image {
+            image_path
+        } else {
+            let image = wallpaper.image.trim();
+            if image.is_empty() {
+                tracing::warn!("wallpaper is enabled but no image path is configured");
+                self.report_progress(
+                    "wallpaper",
+                    None,
+                    ProgressStatus::Failed("no image path is configured".to_owned()),
+                );
+                return;
+            }
+            Self::expand_home_path(image)
+        };
+        let image = image_path.display().to_string();
         if !image_path.exists() {
-            tracing::warn!(path = %image_path.display(), "wallpaper image not found");
+            tracing::warn!(path = %image, "wallpaper image not found");
+            self.report_progress(
+                "wallpaper",
+                Some(&image),
+                ProgressStatus::Failed("image not found".to_owned()),
+            );
             return;
         }
-        tracing::info!(path = %image_path.display(), "applying wallpaper");
+        tracing::info!(path = %image, "applying wallpaper");
 
         if self.wallpaper_task_inflight.swap(true, Ordering::AcqRel) {
             tracing::info!("wallpaper apply already in progress; skipping duplicate request");
             return;
         }
+        self.report_progress("wallpaper", Some(&image), ProgressStatus::Started);
+        if self.wallpaper_progress.is_none() {
+            self.wallpaper_progress = progress::wallpaper_channel(self);
+        }
+        let progress = self.wallpaper_progress.clone();
 
         let inflight = Arc::clone(&self.wallpaper_task_inflight);
         let socket_name = self.socket_name.clone();
@@ -2580,14 +3611,40 @@ org.freedesktop.impl.portal.Secret=gnome-keyring;\n"
             }
 
             let _reset = ResetInFlight(inflight);
-            Self::apply_wallpaper_blocking(wallpaper, image_path, socket_name, runtime_dir);
+            let result =
+                Self::apply_wallpaper_blocking(wallpaper, image_path, socket_name, runtime_dir);
+            if let Some(progress) = progress {
+                let _ = progress.send((image, ProgressStatus::from(&result)));
+            }
         });
     }
 
+    /// The built-in wallpaper `output` shows, once its image is decoded.
+    pub fn output_wallpaper(&self, output: &smithay::output::Output) -> Option<OutputWallpaper> {
+        builtin_wallpaper::output_wallpaper(self, output)
+    }
+
+    pub fn version_report(&self) -> String {
+        ipc::render_version_report(self)
+    }
+
     pub fn reload_config(&mut self) -> Result<(), CompositorError> {
         runtime::reload_config(self)
     }
 
+    /// Tell `progress` subscribers how a long-running `task` is going.
+    pub fn report_progress(&mut self, task: &str, target: Option<&str>, status: ProgressStatus) {
+        progress::report(self, task, target, status);
+    }
+
+    pub fn performance_mode(&self) -> bool {
+        self.performance_mode_base.is_some()
+    }
+
+    pub fn set_performance_mode(&mut self, enabled: bool) -> Result<(), CompositorError> {
+        runtime::set_performance_mode(self, enabled)
+    }
+
     pub fn toggle_floating_focused_window(&mut self) -> Result<(), CompositorError> {
         let Some(keyboard) = self.seat.get_keyboard() else {
             return Ok(());
@@ -2608,13 +3665,232 @@ org.freedesktop.impl.portal.Secret=gnome-keyring;\n"
         self.apply_layout()
     }
 
+    /// Pin the focused window to every workspace, or unpin it. A tiled window is
+    /// floated when pinned.
+    pub fn toggle_pin_focused_window(&mut self) -> Result<(), CompositorError> {
+        let Some(window) = self
+            .seat
+            .get_keyboard()
+            .and_then(|keyboard| keyboard.current_focus())
+            .and_then(|surface| self.window_for_surface(&surface))
+        else {
+            return Ok(());
+        };
+
+        let sticky = !self.is_window_sticky(&window);
+        self.set_window_sticky(&window, sticky);
+        if sticky
+            && !self.is_window_floating(&window)
+            && !self.window_has_exclusive_layout_state(&window)
+        {
+            self.set_window_floating(&window, true);
+            if self.is_window_mapped(&window) {
+                self.map_window_to_initial_location(&window, true);
+            }
+        }
+        tracing::debug!(sticky, "window pin toggled");
+
+        self.apply_layout()
+    }
+
+    /// Layout of the current workspace.
+    pub fn layout(&self) -> &LayoutBox {
+        &self.workspace_layouts[self.current_workspace].layout
+    }
+
+    pub fn layout_type(&self) -> LayoutType {
+        self.workspace_layouts[self.current_workspace].layout_type
+    }
+
+    /// Switch the current workspace to `layout_type`; other workspaces keep theirs.
+    /// Split ratios of the old layout are dropped.
+    pub fn set_layout(&mut self, layout_type: LayoutType) {
+        if layout_type == self.layout_type() {
+            return;
+        }
+        self.workspace_layouts[self.current_workspace] = WorkspaceLayout::new(layout_type);
+        tracing::info!(
+            workspace = self.current_workspace + 1,
+            layout = layout_type.name(),
+            "layout changed"
+        );
+        if let Err(err) = self.apply_layout() {
+            tracing::warn!("failed to apply layout after layout change: {err}");
+        }
+    }
+
+    /// Move the split next to the focused tiled window by `delta`, in layouts
+    /// that have adjustable splits.
+    pub fn adjust_focused_split(&mut self, delta: f32) {
+        let Some((windows, index)) = self.focused_tiled_position() else {
+            return;
+        };
+        let current = &mut self.workspace_layouts[self.current_workspace];
+        if !current.layout.adjust_split(&windows, index, delta) {
+            tracing::debug!(
+                layout = current.layout_type.name(),
+                "layout has no split to adjust"
+            );
+            return;
+        }
+        if let Err(err) = self.apply_layout() {
+            tracing::warn!("failed to apply layout after split change: {err}");
+        }
+    }
+
+    /// Grow the focused tile by `width` and `height` of its size weights, in
+    /// layouts that size tiles per window.
+    pub fn resize_focused_tile(&mut self, width: f32, height: f32) {
+        let Some((windows, index)) = self.focused_tiled_position() else {
+            return;
+        };
+        let current = &mut self.workspace_layouts[self.current_workspace];
+        if !current.layout.resize_tile(&windows, index, width, height) {
+            tracing::debug!(
+                layout = current.layout_type.name(),
+                "layout cannot resize the focused tile that way"
+            );
+            return;
+        }
+        if let Err(err) = self.apply_layout() {
+            tracing::warn!("failed to apply layout after tile resize: {err}");
+        }
+    }
+
+    /// Change `master_factor` by `delta` until the next reload, clamped to the
+    /// range the config accepts.
+    pub fn adjust_master_factor(&mut self, delta: f32) {
+        let master_factor = (self.config.master_factor + delta).clamp(0.1, 0.9);
+        if master_factor == self.config.master_factor {
+            return;
+        }
+        self.config.master_factor = master_factor;
+        // Survives leaving performance mode, which restores the base config.
+        if let Some(base) = self.performance_mode_base.as_mut() {
+            base.master_factor = master_factor;
+        }
+        tracing::debug!(master_factor, "master factor changed");
+        if let Err(err) = self.apply_layout() {
+            tracing::warn!("failed to apply layout after master factor change: {err}");
+        }
+    }
+
+    /// Swap the focused tiled window with the master, the first tiled window on
+    /// its output; the master itself swaps with the window after it.
+    pub fn swap_focused_with_master(&mut self) {
+        let Some((windows, index)) = self.focused_tiled_position() else {
+            return;
+        };
+        let other = if index == 0 { 1 } else { 0 };
+        let (Some(focused), Some(other)) = (windows.get(index), windows.get(other)) else {
+            return;
+        };
+        if !window_moves::swap_in_workspace(self, focused, other) {
+            return;
+        }
+        if let Err(err) = self.apply_layout() {
+            tracing::warn!("failed to apply layout after swapping master: {err}");
+        }
+    }
+
+    /// Step the Main+Tab window switcher through the current workspace's windows
+    /// in most-recently-used order.
+    pub fn cycle_windows(&mut self, forward: bool) {
+        window_switcher::cycle(self, forward);
+    }
+
+    pub(crate) fn record_window_focus(&mut self, surface: &WlSurface) {
+        window_switcher::record_focus(self, surface);
+    }
+
+    /// Finish a window switcher cycle if the main key has been released.
+    pub(crate) fn window_switcher_key_released(&mut self) {
+        window_switcher::key_released(self);
+    }
+
+    /// Trade places with the nearest tile in `direction`, or move a floating
+    /// window `floating_move_step` pixels that way.
+    pub fn swap_focused_in_direction(&mut self, direction: crate::config::Direction) {
+        window_moves::swap_focused_in_direction(self, direction);
+    }
+
+    /// Focus the tiled window before or after the focused one on its output, in
+    /// layout order; in the scrolling layout, the column to its left or right.
+    pub fn focus_adjacent_tiled(&mut self, forward: bool) {
+        let Some((windows, index)) = self.focused_tiled_position() else {
+            return;
+        };
+        let target = if forward {
+            windows.get(index + 1)
+        } else {
+            index.checked_sub(1).and_then(|index| windows.get(index))
+        };
+        if let Some(toplevel) = target.and_then(|window| window.toplevel()) {
+            let surface = toplevel.wl_surface().clone();
+            self.set_keyboard_focus(Some(surface), SERIAL_COUNTER.next_serial());
+        }
+    }
+
+    /// Tiled windows sharing the focused window's output, in layout order, and
+    /// the focused one's index among them.
+    fn focused_tiled_position(&self) -> Option<(Vec<Window>, usize)> {
+        let window = self
+            .seat
+            .get_keyboard()
+            .and_then(|keyboard| keyboard.current_focus())
+            .and_then(|surface| self.window_for_surface(&surface))?;
+        let output = self.window_output(&window);
+        let windows: Vec<Window> = self
+            .tiled_slots
+            .iter()
+            .map(|(window, _)| window.clone())
+            .filter(|candidate| self.window_output(candidate) == output)
+            .collect();
+        let index = windows.iter().position(|candidate| *candidate == window)?;
+        Some((windows, index))
+    }
+
     pub fn refresh_foreign_toplevel(&mut self) {
         crate::protocols::foreign_toplevel::refresh(self);
     }
 
+    /// Size an ext-image-copy-capture session of `source` captures at.
+    pub(crate) fn image_capture_source_size(
+        &self,
+        source: &crate::protocols::image_copy_capture::CaptureSource,
+    ) -> Option<smithay::utils::Size<i32, smithay::utils::Physical>> {
+        image_capture::source_size(self, source)
+    }
+
+    /// Copy `frame` of an ext-image-copy-capture session into its buffer.
+    pub(crate) fn serve_capture_frame(
+        &mut self,
+        frame: crate::protocols::image_copy_capture::CaptureFrame,
+    ) {
+        image_capture::serve(self, frame);
+    }
+
+    /// Start the ScreenCast portal backend.
+    #[cfg(feature = "screencast")]
+    pub fn init_screencast(&mut self) {
+        screencast::init(self);
+    }
+
+    /// Drop screencasts whose stream failed or whose output or window is gone.
+    pub fn refresh_screencasts(&mut self) {
+        #[cfg(feature = "screencast")]
+        screencast::refresh(self);
+    }
+
     pub fn refresh_ext_workspace(&mut self) {
         crate::protocols::ext_workspace::refresh(self);
     }
+
+    /// Re-broadcast workspace and toplevel state to every bound bar (`raven resync`).
+    pub fn resync_protocol_clients(&mut self) {
+        crate::protocols::ext_workspace::resync(self);
+        crate::protocols::foreign_toplevel::resync(self);
+    }
 }
 
 impl Drop for Raven {
@@ -0,0 +1,178 @@
+//! AccessX-style keyboard aids from the `input` section: sticky modifiers, slow
+//! keys and bounce keys. Every key event passes through here before keybinds or
+//! clients see it.
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::Duration,
+};
+
+use smithay::{
+    backend::input::KeyState,
+    input::keyboard::Keycode,
+    reexports::calloop::{
+        RegistrationToken,
+        timer::{TimeoutAction, Timer},
+    },
+};
+
+use super::Raven;
+
+// xkb keycodes (evdev + 8) of both Shift, Ctrl, Alt and Super keys.
+const MODIFIER_KEYCODES: [u32; 8] = [50, 62, 37, 105, 64, 108, 133, 134];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Sticky {
+    // Held until the next non-modifier key is released.
+    Latched,
+    // Held until the modifier is tapped again.
+    Locked,
+}
+
+#[derive(Default)]
+pub(super) struct AccessX {
+    // Keys whose press was dropped; their release is dropped as well.
+    swallowed: HashSet<Keycode>,
+    // When each key was last released, for bounce keys.
+    released_at: HashMap<Keycode, u32>,
+    // Press held back by slow keys until its delay runs out.
+    slow_pending: Option<(Keycode, RegistrationToken)>,
+    // Modifier pressed with no other key since; releasing it makes it sticky.
+    tap_candidate: Option<Keycode>,
+    // Modifiers kept held for clients after being tapped alone.
+    sticky: HashMap<Keycode, Sticky>,
+    // Sticky modifiers that are physically down again.
+    repressed: HashSet<Keycode>,
+}
+
+fn is_modifier(key_code: Keycode) -> bool {
+    MODIFIER_KEYCODES.contains(&key_code.raw())
+}
+
+pub(super) fn key_event(state: &mut Raven, key_code: Keycode, key_state: KeyState, time_msec: u32) {
+    if !state.config.input.sticky_keys && !state.accessx.sticky.is_empty() {
+        release_sticky(state, time_msec, |_| true);
+    }
+
+    match key_state {
+        KeyState::Pressed => press(state, key_code, time_msec),
+        KeyState::Released => release(state, key_code, time_msec),
+    }
+}
+
+fn press(state: &mut Raven, key_code: Keycode, time_msec: u32) {
+    let bounce_keys_ms = state.config.input.bounce_keys_ms;
+    if bounce_keys_ms > 0
+        && state
+            .accessx
+            .released_at
+            .get(&key_code)
+            .is_some_and(|released_at| time_msec.wrapping_sub(*released_at) < bounce_keys_ms)
+    {
+        tracing::trace!(key = key_code.raw(), "bounce keys: press ignored");
+        state.accessx.swallowed.insert(key_code);
+        return;
+    }
+
+    let slow_keys_ms = state.config.input.slow_keys_ms;
+    if slow_keys_ms > 0 {
+        // Only one press waits at a time; a newer one drops the older.
+        if let Some((pending, timer)) = state.accessx.slow_pending.take() {
+            state.loop_handle.remove(timer);
+            state.accessx.swallowed.insert(pending);
+        }
+        start_slow_key(state, key_code, time_msec, slow_keys_ms);
+        return;
+    }
+
+    accept_press(state, key_code, time_msec);
+}
+
+fn start_slow_key(state: &mut Raven, key_code: Keycode, time_msec: u32, delay: u32) {
+    let timer = Timer::from_duration(Duration::from_millis(u64::from(delay)));
+    match state.loop_handle.insert_source(timer, move |_, _, state| {
+        state.accessx.slow_pending = None;
+        accept_press(state, key_code, time_msec.wrapping_add(delay));
+        TimeoutAction::Drop
+    }) {
+        Ok(token) => state.accessx.slow_pending = Some((key_code, token)),
+        Err(err) => {
+            tracing::warn!("failed to schedule slow keys timer: {err}");
+            accept_press(state, key_code, time_msec);
+        }
+    }
+}
+
+fn accept_press(state: &mut Raven, key_code: Keycode, time_msec: u32) {
+    if !state.config.input.sticky_keys || !is_modifier(key_code) {
+        state.accessx.tap_candidate = None;
+        state.deliver_key(key_code, KeyState::Pressed, time_msec);
+        return;
+    }
+
+    state.accessx.tap_candidate = Some(key_code);
+    if state.accessx.sticky.contains_key(&key_code) {
+        // Clients still see it held from the last tap; the release decides.
+        state.accessx.repressed.insert(key_code);
+        return;
+    }
+    state.deliver_key(key_code, KeyState::Pressed, time_msec);
+}
+
+fn release(state: &mut Raven, key_code: Keycode, time_msec: u32) {
+    state.accessx.released_at.insert(key_code, time_msec);
+    if state.accessx.swallowed.remove(&key_code) {
+        return;
+    }
+    if let Some((pending, timer)) = state.accessx.slow_pending
+        && pending == key_code
+    {
+        // Let go before slow keys accepted it.
+        state.accessx.slow_pending = None;
+        state.loop_handle.remove(timer);
+        return;
+    }
+
+    state.accessx.repressed.remove(&key_code);
+    if !state.config.input.sticky_keys || !is_modifier(key_code) {
+        state.deliver_key(key_code, KeyState::Released, time_msec);
+        if !state.accessx.sticky.is_empty() {
+            release_sticky(state, time_msec, |sticky| sticky == Sticky::Latched);
+        }
+        return;
+    }
+
+    let tapped = state.accessx.tap_candidate.take() == Some(key_code);
+    match (state.accessx.sticky.get(&key_code).copied(), tapped) {
+        (None, true) => {
+            tracing::debug!(key = key_code.raw(), "sticky keys: modifier latched");
+            state.accessx.sticky.insert(key_code, Sticky::Latched);
+        }
+        (Some(Sticky::Latched), true) => {
+            tracing::debug!(key = key_code.raw(), "sticky keys: modifier locked");
+            state.accessx.sticky.insert(key_code, Sticky::Locked);
+        }
+        _ => {
+            state.accessx.sticky.remove(&key_code);
+            state.deliver_key(key_code, KeyState::Released, time_msec);
+        }
+    }
+}
+
+/// Let clients see sticky modifiers matching `filter` released. Ones that are
+/// physically down again just stop being sticky; their own release follows.
+fn release_sticky(state: &mut Raven, time_msec: u32, filter: impl Fn(Sticky) -> bool) {
+    let released: Vec<Keycode> = state
+        .accessx
+        .sticky
+        .iter()
+        .filter(|(_, sticky)| filter(**sticky))
+        .map(|(key_code, _)| *key_code)
+        .collect();
+    for key_code in released {
+        state.accessx.sticky.remove(&key_code);
+        if !state.accessx.repressed.contains(&key_code) {
+            state.deliver_key(key_code, KeyState::Released, time_msec);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! xdg-activation and launch provenance.
+//!
+//! Commands started from a keybind get an activation token of their own through
+//! `XDG_ACTIVATION_TOKEN`/`DESKTOP_STARTUP_ID`. When the new window activates
+//! with that token, Raven knows the user asked for it and may move keyboard focus
+//! (and the pointer, with `warp_pointer`) to whichever output it opened on.
+
+use smithay::{
+    desktop::Window,
+    input::pointer::MotionEvent,
+    output::Output,
+    reexports::wayland_server::protocol::wl_surface::WlSurface,
+    utils::{Logical, Point, SERIAL_COUNTER},
+    wayland::xdg_activation::XdgActivationTokenData,
+};
+use std::time::Duration;
+
+use crate::config::NewWindowOutputFocus;
+
+use super::{Raven, minimize, runtime, urgency};
+
+// Tokens older than this are stale; the launch they came from is long forgotten.
+const ACTIVATION_TOKEN_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Marker stored in the user data of tokens created for keybind launches.
+struct KeybindLaunch;
+
+pub(super) fn spawn_keybind_command(state: &mut Raven, command: &str) {
+    if command.trim().is_empty() {
+        return;
+    }
+
+    let data = XdgActivationTokenData::default();
+    data.user_data.insert_if_missing(|| KeybindLaunch);
+    let (token, _) = state.xdg_activation_state.create_external_token(data);
+    let token = String::from(token.clone());
+    runtime::spawn_command_with_env(
+        state,
+        command,
+        &[
+            ("XDG_ACTIVATION_TOKEN", token.clone()),
+            ("DESKTOP_STARTUP_ID", token),
+        ],
+    );
+}
+
+/// Output the keyboard focus is on: the focused window's, or the pointer's when
+/// nothing is focused.
+fn keyboard_focus_output(state: &Raven) -> Option<Output> {
+    state
+        .seat
+        .get_keyboard()
+        .and_then(|keyboard| keyboard.current_focus())
+        .and_then(|surface| state.window_for_surface(&surface))
+        .and_then(|window| state.space.outputs_for_element(&window).into_iter().next())
+        .or_else(|| state.active_output_for_pointer())
+}
+
+/// Output `window` is on, or will open on when it has not been mapped yet.
+/// Tiled windows always land on the output the layout runs on.
+fn window_output(state: &Raven, window: &Window) -> Option<Output> {
+    if let Some(output) = state.space.outputs_for_element(window).into_iter().next() {
+        return Some(output);
+    }
+    if state.is_window_floating(window) {
+        state.floating_output_for_window(window)
+    } else {
+        state.space.outputs().next().cloned()
+    }
+}
+
+fn opens_on_other_output(state: &Raven, window: &Window) -> bool {
+    match (window_output(state, window), keyboard_focus_output(state)) {
+        (Some(target), Some(focused)) => target != focused,
+        _ => false,
+    }
+}
+
+pub(super) fn may_focus_new_window(state: &Raven, window: &Window) -> bool {
+    state.config.new_window_output_focus == NewWindowOutputFocus::Always
+        || !opens_on_other_output(state, window)
+}
+
+/// Handle `xdg_activation_v1.activate`. Windows on other workspaces are left
+/// alone; across outputs, `new_window_output_focus = "keybind"` only honors
+/// tokens Raven handed out for a keybind launch. Refused windows turn urgent.
+pub(super) fn request_activation(
+    state: &mut Raven,
+    token_data: &XdgActivationTokenData,
+    surface: &WlSurface,
+) {
+    if token_data.timestamp.elapsed() > ACTIVATION_TOKEN_TIMEOUT {
+        tracing::debug!("ignoring activation request with a stale token");
+        urgency::mark(state, surface);
+        return;
+    }
+    let Some(window) = state.window_for_surface(surface) else {
+        return;
+    };
+    if !state.workspace_contains_window(state.current_workspace, &window) {
+        urgency::mark(state, surface);
+        return;
+    }
+
+    let from_keybind = token_data.user_data.get::<KeybindLaunch>().is_some();
+    let crosses_output = opens_on_other_output(state, &window);
+    if crosses_output
+        && !from_keybind
+        && state.config.new_window_output_focus == NewWindowOutputFocus::Keybind
+    {
+        tracing::debug!("activation across outputs without a keybind token; focus kept");
+        urgency::mark(state, surface);
+        return;
+    }
+
+    minimize::unminimize(state, &window);
+    state.set_keyboard_focus(Some(surface.clone()), SERIAL_COUNTER.next_serial());
+    if from_keybind
+        && state.config.warp_pointer
+        && window_output(state, &window) != state.active_output_for_pointer()
+    {
+        warp_pointer_to_window(state, &window);
+    }
+}
+
+fn warp_pointer_to_window(state: &mut Raven, window: &Window) {
+    let Some(geometry) = state.space.element_geometry(window) else {
+        return;
+    };
+    let pointer = state.pointer();
+    if pointer.is_grabbed() {
+        return;
+    }
+
+    state.pointer_location = Point::<i32, Logical>::from((
+        geometry.loc.x + geometry.size.w / 2,
+        geometry.loc.y + geometry.size.h / 2,
+    ))
+    .to_f64();
+    let under = state.contents_under(state.pointer_location);
+    state.pointer_contents.clone_from(&under);
+    pointer.motion(
+        state,
+        under.surface,
+        &MotionEvent {
+            location: state.pointer_location,
+            serial: SERIAL_COUNTER.next_serial(),
+            time: state.start_time.elapsed().as_millis() as u32,
+        },
+    );
+    pointer.frame(state);
+    crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Pointer);
+}
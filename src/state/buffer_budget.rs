@@ -0,0 +1,187 @@
+//! Buffer memory accounting. Every buffer a client attaches is charged to it until
+//! the buffer is destroyed; a client over `client_buffer_budget_mb` is disconnected
+//! with `no_memory`, and dmabuf imports past `buffer_budget_mb` are refused. Usage
+//! is listed in `raven stats`.
+
+use std::collections::HashMap;
+
+use smithay::{
+    backend::{
+        allocator::{Buffer, dmabuf::Dmabuf},
+        renderer::utils::with_renderer_surface_state,
+    },
+    reexports::wayland_server::{
+        Resource,
+        backend::{ClientId, protocol::ProtocolError},
+        protocol::{wl_buffer::WlBuffer, wl_display},
+    },
+    wayland::{dmabuf::get_dmabuf, shm},
+};
+
+use super::{Raven, WlSurface};
+
+const MIB: u64 = 1024 * 1024;
+
+#[derive(Debug)]
+struct ClientUsage {
+    label: String,
+    bytes: u64,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct BufferBudget {
+    buffers: HashMap<WlBuffer, (ClientId, u64)>,
+    clients: HashMap<ClientId, ClientUsage>,
+    total: u64,
+    refused_imports: u64,
+    disconnected_clients: u64,
+}
+
+fn dmabuf_bytes(dmabuf: &Dmabuf) -> u64 {
+    let height = dmabuf.height() as u64;
+    dmabuf.strides().map(|stride| stride as u64 * height).sum()
+}
+
+fn buffer_bytes(buffer: &WlBuffer) -> Option<u64> {
+    shm::with_buffer_contents(buffer, |_, _, data| data.stride as u64 * data.height as u64)
+        .ok()
+        .or_else(|| get_dmabuf(buffer).ok().map(|dmabuf| dmabuf_bytes(&dmabuf)))
+}
+
+fn budget_bytes(megabytes: u32) -> Option<u64> {
+    (megabytes > 0).then_some(megabytes as u64 * MIB)
+}
+
+fn client_label(state: &Raven, surface: &WlSurface) -> String {
+    let (app_id, _) = Raven::surface_app_id_and_title(surface);
+    let pid = surface
+        .client()
+        .and_then(|client| client.get_credentials(&state.display_handle).ok())
+        .map(|credentials| credentials.pid);
+    match (app_id, pid) {
+        (Some(app_id), Some(pid)) => format!("{app_id} (pid {pid})"),
+        (Some(app_id), None) => app_id,
+        (None, Some(pid)) => format!("pid {pid}"),
+        (None, None) => "<unknown>".to_owned(),
+    }
+}
+
+/// Charge the buffer just committed on `surface` to its client. Returns `false`
+/// when that put the client over its budget and it was disconnected.
+pub(super) fn account_commit(state: &mut Raven, surface: &WlSurface) -> bool {
+    let Some(buffer) = with_renderer_surface_state(surface, |renderer_state| {
+        renderer_state
+            .buffer()
+            .map(|buffer| WlBuffer::clone(buffer))
+    })
+    .flatten() else {
+        return true;
+    };
+    if state.buffer_budget.buffers.contains_key(&buffer) {
+        return true;
+    }
+    let (Some(client), Some(bytes)) = (buffer.client(), buffer_bytes(&buffer)) else {
+        return true;
+    };
+
+    let label = client_label(state, surface);
+    let budget = &mut state.buffer_budget;
+    let usage = budget
+        .clients
+        .entry(client.id())
+        .or_insert_with(|| ClientUsage { label, bytes: 0 });
+    usage.bytes += bytes;
+    budget.total += bytes;
+    budget.buffers.insert(buffer, (client.id(), bytes));
+
+    let Some(limit) = budget_bytes(state.config.client_buffer_budget_mb) else {
+        return true;
+    };
+    if usage.bytes <= limit {
+        return true;
+    }
+
+    let message = format!(
+        "client holds {} MiB of buffers, over the {} MiB budget",
+        usage.bytes / MIB,
+        limit / MIB
+    );
+    tracing::warn!(client = %usage.label, "{message}; disconnecting");
+    budget.disconnected_clients += 1;
+    client.kill(
+        &state.display_handle,
+        ProtocolError {
+            code: wl_display::Error::NoMemory as u32,
+            object_id: 1,
+            object_interface: "wl_display".to_owned(),
+            message,
+        },
+    );
+    false
+}
+
+/// Whether a new dmabuf fits in the global budget; refusals are counted.
+pub(super) fn import_allowed(state: &mut Raven, dmabuf: &Dmabuf) -> bool {
+    let Some(limit) = budget_bytes(state.config.buffer_budget_mb) else {
+        return true;
+    };
+    let budget = &mut state.buffer_budget;
+    if budget.total + dmabuf_bytes(dmabuf) <= limit {
+        return true;
+    }
+    budget.refused_imports += 1;
+    tracing::warn!(
+        used_mib = budget.total / MIB,
+        budget_mib = limit / MIB,
+        "refusing dmabuf import over the buffer budget"
+    );
+    false
+}
+
+pub(super) fn buffer_destroyed(state: &mut Raven, buffer: &WlBuffer) {
+    let budget = &mut state.buffer_budget;
+    let Some((client, bytes)) = budget.buffers.remove(buffer) else {
+        return;
+    };
+    budget.total -= bytes;
+    if let Some(usage) = budget.clients.get_mut(&client) {
+        usage.bytes -= bytes;
+        if usage.bytes == 0 {
+            budget.clients.remove(&client);
+        }
+    }
+}
+
+fn format_budget(megabytes: u32) -> String {
+    if megabytes == 0 {
+        "no limit".to_owned()
+    } else {
+        format!("{megabytes} MiB")
+    }
+}
+
+/// The `raven stats` section on buffer memory per client.
+pub(super) fn render_buffer_report(state: &Raven) -> String {
+    let budget = &state.buffer_budget;
+    let mut out = format!(
+        "Buffer memory: {} MiB in {} buffers (budget {}, per client {}):\n",
+        budget.total / MIB,
+        budget.buffers.len(),
+        format_budget(state.config.buffer_budget_mb),
+        format_budget(state.config.client_buffer_budget_mb)
+    );
+    let mut clients: Vec<&ClientUsage> = budget.clients.values().collect();
+    clients.sort_by_key(|usage| std::cmp::Reverse(usage.bytes));
+    for usage in clients {
+        out.push_str(&format!(
+            "  {}: {:.1} MiB\n",
+            usage.label,
+            usage.bytes as f64 / MIB as f64
+        ));
+    }
+    out.push_str(&format!(
+        "  refused imports: {}, disconnected clients: {}\n",
+        budget.refused_imports, budget.disconnected_clients
+    ));
+    out
+}
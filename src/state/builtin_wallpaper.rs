@@ -0,0 +1,204 @@
+//! Built-in wallpaper: with `wallpaper.builtin`, images are decoded on a worker
+//! thread and every backend draws them beneath everything on each output,
+//! cropped to fill it, instead of handing them to swww. A monitor's own
+//! `wallpaper` takes the place of `wallpaper.image` there; outputs without an
+//! image show `background_color`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    thread,
+};
+
+use smithay::{
+    backend::{allocator::Fourcc, renderer::element::memory::MemoryRenderBuffer},
+    output::Output,
+    reexports::calloop::channel::{self, Sender},
+    utils::{Logical, Point, Rectangle, Size, Transform},
+};
+
+use crate::backend::udev::RedrawCause;
+
+use super::{ProgressStatus, Raven};
+
+/// RGBA pixels straight from the decoder, on their way to the main loop.
+struct DecodedImage {
+    size: (u32, u32),
+    pixels: Vec<u8>,
+}
+
+#[derive(Default)]
+pub(super) struct BuiltinWallpaper {
+    // Decoded images by path; outputs showing the same image share one buffer.
+    images: HashMap<PathBuf, (MemoryRenderBuffer, Size<i32, Logical>)>,
+    // Paths being decoded on a worker thread.
+    loading: HashSet<PathBuf>,
+    // Set up on the first load; workers send decoded images back here.
+    sender: Option<Sender<(PathBuf, Result<DecodedImage, String>)>>,
+}
+
+/// What an output shows as its built-in wallpaper.
+pub struct OutputWallpaper {
+    pub buffer: MemoryRenderBuffer,
+    /// The part of the image, in its pixels, that fills the output.
+    pub src: Rectangle<f64, Logical>,
+    /// Output size the image is stretched to.
+    pub size: Size<i32, Logical>,
+}
+
+/// The image outputs without a wallpaper of their own show.
+fn default_image(state: &Raven) -> Option<PathBuf> {
+    super::wallpaper_slideshow::current_image(state).or_else(|| {
+        let image = state.config.wallpaper.image.trim();
+        (!image.is_empty()).then(|| Raven::expand_home_path(image))
+    })
+}
+
+fn image_for_output(state: &Raven, output_name: &str) -> Option<PathBuf> {
+    let monitor_image = state
+        .config
+        .monitors
+        .iter()
+        .find(|monitor| monitor.name == output_name)
+        .and_then(|monitor| monitor.wallpaper.as_deref());
+    match monitor_image {
+        Some(image) => Some(Raven::expand_home_path(image)),
+        None => default_image(state),
+    }
+}
+
+/// Every image the config can put on an output, connected or not, so outputs
+/// plugged in later find theirs decoded.
+fn wanted_images(state: &Raven) -> HashSet<PathBuf> {
+    let wallpaper = &state.config.wallpaper;
+    if !wallpaper.enabled || !wallpaper.builtin {
+        return HashSet::new();
+    }
+    state
+        .config
+        .monitors
+        .iter()
+        .filter_map(|monitor| monitor.wallpaper.as_deref())
+        .map(Raven::expand_home_path)
+        .chain(default_image(state))
+        .collect()
+}
+
+/// Decode the images the config now asks for and drop the ones it no longer does.
+pub(super) fn apply(state: &mut Raven) {
+    let wanted = wanted_images(state);
+    let before = state.builtin_wallpaper.images.len();
+    state
+        .builtin_wallpaper
+        .images
+        .retain(|path, _| wanted.contains(path));
+    if state.builtin_wallpaper.images.len() != before {
+        crate::backend::udev::queue_redraw_all(state, RedrawCause::Ipc);
+    }
+
+    for path in wanted {
+        if !state.builtin_wallpaper.images.contains_key(&path)
+            && !state.builtin_wallpaper.loading.contains(&path)
+        {
+            load(state, path);
+        }
+    }
+}
+
+fn load(state: &mut Raven, path: PathBuf) {
+    if state.builtin_wallpaper.sender.is_none() {
+        state.builtin_wallpaper.sender = decoded_channel(state);
+    }
+    let Some(sender) = state.builtin_wallpaper.sender.clone() else {
+        return;
+    };
+
+    let image = path.display().to_string();
+    tracing::info!(path = %image, "loading built-in wallpaper");
+    state.report_progress("wallpaper", Some(&image), ProgressStatus::Started);
+    state.builtin_wallpaper.loading.insert(path.clone());
+    thread::spawn(move || {
+        let decoded = decode(&path);
+        let _ = sender.send((path, decoded));
+    });
+}
+
+fn decode(path: &Path) -> Result<DecodedImage, String> {
+    let image = image::open(path)
+        .map_err(|err| err.to_string())?
+        .into_rgba8();
+    Ok(DecodedImage {
+        size: image.dimensions(),
+        pixels: image.into_raw(),
+    })
+}
+
+fn decoded_channel(state: &Raven) -> Option<Sender<(PathBuf, Result<DecodedImage, String>)>> {
+    let (sender, receiver) = channel::channel();
+    if let Err(err) = state
+        .loop_handle
+        .insert_source(receiver, |event, _, state| {
+            if let channel::Event::Msg((path, decoded)) = event {
+                loaded(state, path, decoded);
+            }
+        })
+    {
+        tracing::warn!("failed to register built-in wallpaper channel: {err}");
+        return None;
+    }
+    Some(sender)
+}
+
+fn loaded(state: &mut Raven, path: PathBuf, decoded: Result<DecodedImage, String>) {
+    state.builtin_wallpaper.loading.remove(&path);
+    let image = path.display().to_string();
+    let result = decoded.map(|decoded| {
+        // A reload may have moved on while the image was decoding.
+        if !wanted_images(state).contains(&path) {
+            return;
+        }
+        let size = Size::from((decoded.size.0 as i32, decoded.size.1 as i32));
+        let buffer = MemoryRenderBuffer::from_slice(
+            &decoded.pixels,
+            Fourcc::Abgr8888,
+            (size.w, size.h),
+            1,
+            Transform::Normal,
+            None,
+        );
+        state.builtin_wallpaper.images.insert(path, (buffer, size));
+        crate::backend::udev::queue_redraw_all(state, RedrawCause::Ipc);
+    });
+    if let Err(err) = &result {
+        tracing::warn!(path = %image, "failed to load built-in wallpaper: {err}");
+    }
+    state.report_progress("wallpaper", Some(&image), ProgressStatus::from(&result));
+}
+
+pub(super) fn output_wallpaper(state: &Raven, output: &Output) -> Option<OutputWallpaper> {
+    let wallpaper = &state.config.wallpaper;
+    if !wallpaper.enabled || !wallpaper.builtin {
+        return None;
+    }
+    let path = image_for_output(state, &output.name())?;
+    let (buffer, image_size) = state.builtin_wallpaper.images.get(&path)?;
+    let output_size = state.space.output_geometry(output)?.size;
+    if image_size.is_empty() || output_size.is_empty() {
+        return None;
+    }
+
+    // Crop to the output's aspect ratio, keeping the middle of the image.
+    let image_size = image_size.to_f64();
+    let scale =
+        (image_size.w / f64::from(output_size.w)).min(image_size.h / f64::from(output_size.h));
+    let src_size = output_size.to_f64().upscale(scale);
+    let src_loc = Point::from((
+        (image_size.w - src_size.w) / 2.0,
+        (image_size.h - src_size.h) / 2.0,
+    ));
+    Some(OutputWallpaper {
+        buffer: buffer.clone(),
+        src: Rectangle::new(src_loc, src_size),
+        size: output_size,
+    })
+}
@@ -0,0 +1,143 @@
+use std::{cell::RefCell, collections::HashMap};
+
+use smithay::{
+    backend::renderer::element::Kind,
+    output::Output,
+    utils::{Logical, Point, Rectangle},
+};
+
+use crate::render_helpers::{
+    SolidColorBuffer, SolidColorRenderElement,
+    text::{TextBuffer, text_size},
+};
+
+use super::Raven;
+
+const BANNER_PIXEL_SIZE: f64 = 2.0;
+const BANNER_PADDING: f64 = 10.0;
+// Advance of one 5x7 glyph plus spacing, in font pixels.
+const GLYPH_COLUMNS: f64 = 6.0;
+const BANNER_MAX_LINES: usize = 6;
+const BANNER_TEXT_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 1.0];
+const BANNER_BACKGROUND_COLOR: [f32; 4] = [0.6, 0.08, 0.08, 0.92];
+
+/// Text and background of the banner on each output, by output name, so a
+/// banner that stays the same adds no damage.
+#[derive(Default)]
+pub(super) struct BannerBuffers(RefCell<HashMap<String, (TextBuffer, SolidColorBuffer)>>);
+
+/// Greedy word wrap to `columns` characters; overlong words are split.
+fn wrap_text(text: &str, columns: usize) -> Vec<String> {
+    let columns = columns.max(1);
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            let mut word: Vec<char> = word.chars().collect();
+            while word.len() > columns {
+                if !line.is_empty() {
+                    lines.push(std::mem::take(&mut line));
+                }
+                lines.push(word.drain(..columns).collect());
+            }
+            let line_len = line.chars().count();
+            if line_len > 0 && line_len + 1 + word.len() > columns {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.extend(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+pub(super) fn show_config_error(state: &mut Raven, error: &str, fallback: &str) {
+    tracing::error!("config error ({fallback}): {error}");
+    state.config_error = Some(format!(
+        "Config error, {fallback}. Click to dismiss.\n{error}"
+    ));
+    crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Config);
+}
+
+pub(super) fn dismiss_config_error(state: &mut Raven) {
+    if state.config_error.take().is_some() {
+        state.config_error_banners.0.borrow_mut().clear();
+        crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Config);
+    }
+}
+
+/// Banner text lines and height for an output `width` logical pixels wide.
+fn banner_layout(message: &str, width: i32) -> (String, f64) {
+    let usable = f64::from(width) - BANNER_PADDING * 2.0;
+    let columns = (usable / (GLYPH_COLUMNS * BANNER_PIXEL_SIZE)).floor() as usize;
+    let mut lines = wrap_text(message, columns);
+    if lines.len() > BANNER_MAX_LINES {
+        lines.truncate(BANNER_MAX_LINES);
+        if let Some(last) = lines.last_mut() {
+            last.push_str(" ...");
+        }
+    }
+    let text = lines.join("\n");
+    let height = text_size(&text, BANNER_PIXEL_SIZE).h + BANNER_PADDING * 2.0;
+    (text, height)
+}
+
+fn banner_rect(state: &Raven, message: &str, output: &Output) -> Option<Rectangle<i32, Logical>> {
+    let output_geo = state.space.output_geometry(output)?;
+    let (_, height) = banner_layout(message, output_geo.size.w);
+    Some(Rectangle::new(
+        output_geo.loc,
+        (output_geo.size.w, height.ceil() as i32).into(),
+    ))
+}
+
+/// Dismiss the banner if `location` is on it. Returns whether it was hit.
+pub(super) fn dismiss_config_error_at(state: &mut Raven, location: Point<f64, Logical>) -> bool {
+    let Some(message) = state.config_error.as_deref() else {
+        return false;
+    };
+    let hit = state.space.outputs().any(|output| {
+        banner_rect(state, message, output).is_some_and(|rect| rect.to_f64().contains(location))
+    });
+    if hit {
+        dismiss_config_error(state);
+    }
+    hit
+}
+
+/// A banner across the top of `output`, relative to the output origin.
+pub(super) fn config_error_banner_elements(
+    state: &Raven,
+    output: &Output,
+) -> Vec<SolidColorRenderElement> {
+    let Some(message) = state.config_error.as_deref() else {
+        return Vec::new();
+    };
+    let Some(output_geo) = state.space.output_geometry(output) else {
+        return Vec::new();
+    };
+
+    let (text, height) = banner_layout(message, output_geo.size.w);
+    let mut banners = state.config_error_banners.0.borrow_mut();
+    let (text_buffer, background) = banners.entry(output.name()).or_default();
+    let mut elements = text_buffer.elements(
+        &text,
+        (BANNER_PADDING, BANNER_PADDING),
+        BANNER_PIXEL_SIZE,
+        BANNER_TEXT_COLOR,
+    );
+    background.update(
+        (f64::from(output_geo.size.w), height),
+        BANNER_BACKGROUND_COLOR,
+    );
+    elements.push(SolidColorRenderElement::from_buffer(
+        background,
+        (0.0, 0.0),
+        1.0,
+        Kind::Unspecified,
+    ));
+    elements
+}
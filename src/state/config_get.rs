@@ -0,0 +1,651 @@
+//! `raven get <key>`: the effective config as JSON, for scripts.
+//!
+//! Values come from the live config, so performance mode and other runtime
+//! overrides show up as they currently apply. Keys follow the Lua names; nested
+//! sections are reached with dots (`wallpaper.image`) and returned whole when
+//! asked for by name. Workspaces are 1-based, as in the config file.
+
+use crate::{
+    CompositorError,
+    config::{
+        Align, KeybindAction, LastWindowClosed, MainKey, NewWindowOutputFocus, RulePattern,
+        RuleSize, RuntimeConfig, WorkspaceAnimation, WorkspaceHooks,
+    },
+};
+
+use super::sway_ipc::{json_optional_string, json_string};
+
+type Fields = Vec<(&'static str, String)>;
+
+pub(super) fn config_value_json(
+    config: &RuntimeConfig,
+    key: &str,
+) -> Result<String, CompositorError> {
+    let (section, field) = match key.split_once('.') {
+        Some((section, field)) => (section, Some(field)),
+        None => (key, None),
+    };
+    let fields = match section {
+        "wallpaper" => wallpaper_fields(config),
+        "xwayland" => xwayland_fields(config),
+        "input" => input_fields(config),
+        _ if field.is_none() => {
+            return top_level_fields(config)
+                .into_iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| format!("{value}\n"))
+                .ok_or_else(|| unknown_key(key));
+        }
+        _ => return Err(unknown_key(key)),
+    };
+
+    match field {
+        None => Ok(format!("{}\n", json_object(fields))),
+        Some(field) => fields
+            .into_iter()
+            .find(|(name, _)| *name == field)
+            .map(|(_, value)| format!("{value}\n"))
+            .ok_or_else(|| unknown_key(key)),
+    }
+}
+
+/// Every key `config_value_json` answers, for the CLI's usage text.
+pub(super) fn config_keys(config: &RuntimeConfig) -> Vec<String> {
+    let mut keys: Vec<String> = top_level_fields(config)
+        .into_iter()
+        .map(|(name, _)| name.to_owned())
+        .collect();
+    for (section, fields) in [
+        ("wallpaper", wallpaper_fields(config)),
+        ("xwayland", xwayland_fields(config)),
+        ("input", input_fields(config)),
+    ] {
+        keys.push(section.to_owned());
+        keys.extend(
+            fields
+                .into_iter()
+                .map(|(name, _)| format!("{section}.{name}")),
+        );
+    }
+    keys
+}
+
+fn unknown_key(key: &str) -> CompositorError {
+    CompositorError::Backend(format!(
+        "unknown config key `{key}` (see `raven get` for the list)"
+    ))
+}
+
+fn json_object(fields: Fields) -> String {
+    let body: Vec<String> = fields
+        .into_iter()
+        .map(|(name, value)| format!("{}:{value}", json_string(name)))
+        .collect();
+    format!("{{{}}}", body.join(","))
+}
+
+fn json_array(items: impl IntoIterator<Item = String>) -> String {
+    format!("[{}]", items.into_iter().collect::<Vec<_>>().join(","))
+}
+
+fn json_optional(value: Option<impl ToString>) -> String {
+    value.map_or_else(|| "null".to_owned(), |value| value.to_string())
+}
+
+fn json_workspace(index: usize) -> String {
+    (index + 1).to_string()
+}
+
+fn top_level_fields(config: &RuntimeConfig) -> Fields {
+    let (last_window_closed, last_window_closed_command) = match &config.last_window_closed {
+        LastWindowClosed::Stay => ("stay", None),
+        LastWindowClosed::PreviousNonEmpty => ("previous", None),
+        LastWindowClosed::Command(command) => ("command", Some(command.as_str())),
+    };
+
+    vec![
+        (
+            "main_key",
+            json_string(match config.main_key {
+                MainKey::Super => "super",
+                MainKey::Alt => "alt",
+                MainKey::Ctrl => "ctrl",
+            }),
+        ),
+        ("terminal", json_string(&config.terminal)),
+        ("launcher", json_string(&config.launcher)),
+        ("focus_follow_mouse", config.focus_follow_mouse.to_string()),
+        (
+            "new_window_output_focus",
+            json_string(match config.new_window_output_focus {
+                NewWindowOutputFocus::Always => "always",
+                NewWindowOutputFocus::Keybind => "keybind",
+            }),
+        ),
+        (
+            "workspace_animation",
+            json_string(match config.workspace_animation {
+                WorkspaceAnimation::None => "none",
+                WorkspaceAnimation::Slide => "slide",
+                WorkspaceAnimation::Crossfade => "crossfade",
+            }),
+        ),
+        ("warp_pointer", config.warp_pointer.to_string()),
+        (
+            "dnd_edge_switch_delay",
+            config.dnd_edge_switch_delay_ms.to_string(),
+        ),
+        (
+            "reload_layout_preview",
+            config.reload_layout_preview_ms.to_string(),
+        ),
+        (
+            "fullscreen_exclusive_input",
+            config.fullscreen_exclusive_input.to_string(),
+        ),
+        (
+            "fullscreen_exclusive_allow",
+            json_array(
+                config
+                    .fullscreen_exclusive_allow
+                    .iter()
+                    .map(|name| json_string(name)),
+            ),
+        ),
+        (
+            "workspace_grid_columns",
+            config.workspace_grid_columns.to_string(),
+        ),
+        ("workspace_count", config.workspace_count.to_string()),
+        ("floating_move_step", config.floating_move_step.to_string()),
+        ("no_csd", config.no_csd.to_string()),
+        ("strict_protocol", config.strict_protocol.to_string()),
+        (
+            "client_buffer_budget_mb",
+            config.client_buffer_budget_mb.to_string(),
+        ),
+        ("buffer_budget_mb", config.buffer_budget_mb.to_string()),
+        ("border_size", config.border_size.to_string()),
+        (
+            "border_active_color",
+            json_string(&color_hex(config.border_active_color)),
+        ),
+        (
+            "border_inactive_color",
+            json_string(&color_hex(config.border_inactive_color)),
+        ),
+        ("corner_radius", config.corner_radius.to_string()),
+        (
+            "urgent_border_color",
+            json_optional_string(config.urgent_border_color.map(color_hex).as_deref()),
+        ),
+        (
+            "background_color",
+            json_string(&color_hex(config.background_color)),
+        ),
+        (
+            "gaps_outer_horizontal",
+            config.gaps_outer_horizontal.to_string(),
+        ),
+        (
+            "gaps_outer_vertical",
+            config.gaps_outer_vertical.to_string(),
+        ),
+        (
+            "gaps_inner_horizontal",
+            config.gaps_inner_horizontal.to_string(),
+        ),
+        (
+            "gaps_inner_vertical",
+            config.gaps_inner_vertical.to_string(),
+        ),
+        ("layout_type", json_string(config.layout_type.name())),
+        ("master_factor", config.master_factor.to_string()),
+        ("num_master", config.num_master.to_string()),
+        ("smart_gaps", config.smart_gaps.to_string()),
+        ("dim_unfocused", config.dim_unfocused.to_string()),
+        ("dim_inactive", config.dim_inactive.to_string()),
+        (
+            "screencast_chooser",
+            json_optional_string(config.screencast_chooser.as_deref()),
+        ),
+        ("cursor_theme", json_string(&config.cursor_theme)),
+        ("cursor_size", config.cursor_size.to_string()),
+        ("last_window_closed", json_string(last_window_closed)),
+        (
+            "last_window_closed_command",
+            json_optional_string(last_window_closed_command),
+        ),
+        (
+            "on_workspace_empty",
+            json_optional_string(config.workspace_hooks.on_workspace_empty.as_deref()),
+        ),
+        (
+            "on_first_window",
+            json_optional_string(config.workspace_hooks.on_first_window.as_deref()),
+        ),
+        (
+            "workspaces",
+            json_object_by_workspace(
+                config
+                    .workspace_hook_overrides
+                    .iter()
+                    .map(|(index, hooks)| (*index, workspace_hooks_json(hooks))),
+            ),
+        ),
+        (
+            "workspace_names",
+            json_object_by_workspace(
+                config
+                    .workspace_names
+                    .iter()
+                    .map(|(index, name)| (*index, json_string(name))),
+            ),
+        ),
+        (
+            "events",
+            json_object(vec![
+                (
+                    "output_added",
+                    json_optional_string(config.events.output_added.as_deref()),
+                ),
+                (
+                    "output_removed",
+                    json_optional_string(config.events.output_removed.as_deref()),
+                ),
+                (
+                    "workspace_switched",
+                    json_optional_string(config.events.workspace_switched.as_deref()),
+                ),
+                (
+                    "window_opened",
+                    json_optional_string(config.events.window_opened.as_deref()),
+                ),
+            ]),
+        ),
+        (
+            "idle",
+            json_object(vec![
+                ("timeout", config.idle.timeout_secs.to_string()),
+                (
+                    "on_idle",
+                    json_optional_string(config.idle.on_idle.as_deref()),
+                ),
+                (
+                    "on_resume",
+                    json_optional_string(config.idle.on_resume.as_deref()),
+                ),
+            ]),
+        ),
+        (
+            "night_light",
+            json_object(vec![
+                ("enabled", config.night_light.enabled.to_string()),
+                ("temperature", config.night_light.temperature.to_string()),
+                (
+                    "schedule",
+                    config.night_light.schedule.map_or_else(
+                        || "null".to_owned(),
+                        |(start, end)| {
+                            json_string(&format!(
+                                "{:02}:{:02}-{:02}:{:02}",
+                                start / 60,
+                                start % 60,
+                                end / 60,
+                                end % 60
+                            ))
+                        },
+                    ),
+                ),
+            ]),
+        ),
+        ("keybindings", keybinds_json(config)),
+        ("autostart", autostart_json(config)),
+        ("session", session_json(config)),
+        ("monitors", monitors_json(config)),
+        ("window_rules", window_rules_json(config)),
+    ]
+}
+
+fn json_object_by_workspace(entries: impl Iterator<Item = (usize, String)>) -> String {
+    let body: Vec<String> = entries
+        .map(|(index, value)| format!("{}:{value}", json_string(&json_workspace(index))))
+        .collect();
+    format!("{{{}}}", body.join(","))
+}
+
+fn workspace_hooks_json(hooks: &WorkspaceHooks) -> String {
+    json_object(vec![
+        (
+            "on_workspace_empty",
+            json_optional_string(hooks.on_workspace_empty.as_deref()),
+        ),
+        (
+            "on_first_window",
+            json_optional_string(hooks.on_first_window.as_deref()),
+        ),
+    ])
+}
+
+fn keybinds_json(config: &RuntimeConfig) -> String {
+    json_array(config.keybinds.iter().map(|keybind| {
+        let modifiers = [
+            (keybind.modifiers.logo, "super"),
+            (keybind.modifiers.ctrl, "ctrl"),
+            (keybind.modifiers.alt, "alt"),
+            (keybind.modifiers.shift, "shift"),
+        ];
+        let mut fields = vec![
+            (
+                "modifiers",
+                json_array(
+                    modifiers
+                        .into_iter()
+                        .filter(|(held, _)| *held)
+                        .map(|(_, name)| json_string(name)),
+                ),
+            ),
+            ("key", json_string(&keybind.key)),
+        ];
+        fields.extend(keybind_action_fields(&keybind.action));
+        json_object(fields)
+    }))
+}
+
+fn keybind_action_fields(action: &KeybindAction) -> Fields {
+    let simple = |name: &str| vec![("action", json_string(name))];
+    match action {
+        KeybindAction::Exec(command) => vec![
+            ("action", json_string("exec")),
+            ("command", json_string(command)),
+        ],
+        KeybindAction::Terminal => simple("terminal"),
+        KeybindAction::Launcher => simple("launcher"),
+        KeybindAction::CloseFocused => simple("close"),
+        KeybindAction::ForceCloseFocused => simple("force_close"),
+        KeybindAction::ToggleFullscreen => simple("fullscreen"),
+        KeybindAction::ToggleFloating => simple("toggle_floating"),
+        KeybindAction::TogglePin => simple("pin"),
+        KeybindAction::Quit => simple("quit"),
+        KeybindAction::FocusNext => simple("focus_next"),
+        KeybindAction::FocusPrevious => simple("focus_previous"),
+        KeybindAction::ReloadConfig => simple("reload_config"),
+        KeybindAction::SwitchWorkspace(index) => vec![
+            ("action", json_string("workspace")),
+            ("workspace", json_workspace(*index)),
+        ],
+        KeybindAction::SwitchToEmptyWorkspace => vec![
+            ("action", json_string("workspace")),
+            ("workspace", json_string("next_empty")),
+        ],
+        KeybindAction::MoveFocusedToWorkspace(index) => vec![
+            ("action", json_string("movetoworkspace")),
+            ("workspace", json_workspace(*index)),
+        ],
+        KeybindAction::SwapWorkspaces(first, second) => vec![
+            ("action", json_string("workspace_swap")),
+            (
+                "workspaces",
+                json_array([json_workspace(*first), json_workspace(*second)]),
+            ),
+        ],
+        KeybindAction::ReorderWorkspace { from, to } => vec![
+            ("action", json_string("workspace_reorder")),
+            ("from", json_workspace(*from)),
+            ("to", json_workspace(*to)),
+        ],
+        KeybindAction::PreviousWorkspace => simple("workspace_prev"),
+        KeybindAction::NextWorkspace => simple("workspace_next"),
+        KeybindAction::WorkspaceBackAndForth => simple("workspace_back_and_forth"),
+        KeybindAction::SetLayout(layout) => vec![
+            ("action", json_string("layout")),
+            ("layout", json_string(layout.name())),
+        ],
+        KeybindAction::CycleLayout => simple("cycle_layout"),
+        KeybindAction::AdjustSplitRatio(delta) => vec![
+            ("action", json_string("split_ratio")),
+            ("delta", delta.to_string()),
+        ],
+        KeybindAction::FocusColumnLeft => simple("focus_left"),
+        KeybindAction::FocusColumnRight => simple("focus_right"),
+        KeybindAction::ShrinkMaster => simple("resize_left"),
+        KeybindAction::GrowMaster => simple("resize_right"),
+        KeybindAction::SwapMaster => simple("swap_master"),
+        KeybindAction::SwapDirection(_) => simple(action.name()),
+        KeybindAction::CycleWindows => simple("cycle_windows"),
+        KeybindAction::CycleWindowsBack => simple("cycle_windows_back"),
+        KeybindAction::GrowTileWidth => simple("resize_grow_width"),
+        KeybindAction::ShrinkTileWidth => simple("resize_shrink_width"),
+        KeybindAction::GrowTileHeight => simple("resize_grow_height"),
+        KeybindAction::ShrinkTileHeight => simple("resize_shrink_height"),
+        KeybindAction::TogglePerformanceMode => simple("performance_mode"),
+        KeybindAction::FindCursor => simple("find_cursor"),
+        KeybindAction::Dpms(mode) => vec![
+            ("action", json_string("dpms")),
+            ("mode", json_string(mode.name())),
+        ],
+        KeybindAction::FocusUrgent => simple("focus_urgent"),
+        KeybindAction::MinimizeFocused => simple("minimize_focused"),
+    }
+}
+
+fn autostart_json(config: &RuntimeConfig) -> String {
+    json_array(config.autostart.iter().map(|entry| {
+        json_object(vec![
+            ("command", json_string(&entry.command)),
+            (
+                "workspace",
+                json_optional(entry.workspace.map(|index| index + 1)),
+            ),
+        ])
+    }))
+}
+
+fn session_json(config: &RuntimeConfig) -> String {
+    json_array(config.session.iter().map(|entry| {
+        json_object(vec![
+            ("command", json_string(&entry.command)),
+            ("workspace", json_workspace(entry.workspace)),
+            ("floating", entry.floating.to_string()),
+            ("x", json_optional(entry.x)),
+            ("y", json_optional(entry.y)),
+            ("width", json_optional(entry.width)),
+            ("height", json_optional(entry.height)),
+        ])
+    }))
+}
+
+fn monitors_json(config: &RuntimeConfig) -> String {
+    json_array(config.monitors.iter().map(|monitor| {
+        json_object(vec![
+            ("name", json_string(&monitor.name)),
+            ("enabled", monitor.enabled.to_string()),
+            ("width", json_optional(monitor.width)),
+            ("height", json_optional(monitor.height)),
+            ("refresh_hz", json_optional(monitor.refresh_hz)),
+            ("x", json_optional(monitor.x)),
+            ("y", json_optional(monitor.y)),
+            ("scale", json_optional(monitor.scale)),
+            (
+                "transform",
+                json_optional_string(monitor.transform.as_deref()),
+            ),
+            (
+                "fallback_modes",
+                json_array(monitor.fallback_modes.iter().map(|mode| {
+                    json_object(vec![
+                        ("width", mode.width.to_string()),
+                        ("height", mode.height.to_string()),
+                        ("refresh_hz", json_optional(mode.refresh_hz)),
+                    ])
+                })),
+            ),
+            (
+                "wallpaper",
+                json_optional_string(monitor.wallpaper.as_deref()),
+            ),
+        ])
+    }))
+}
+
+fn rule_pattern_json(pattern: &Option<RulePattern>) -> String {
+    json_optional_string(pattern.as_ref().map(RulePattern::as_str))
+}
+
+fn rule_size_json(size: Option<RuleSize>) -> String {
+    match size {
+        Some(RuleSize::Pixels(pixels)) => pixels.to_string(),
+        Some(RuleSize::Percent(percent)) => json_string(&format!("{percent}%")),
+        None => "null".to_owned(),
+    }
+}
+
+fn align_json(align: Align) -> String {
+    match align {
+        Align::Start => json_string("start"),
+        Align::Center => json_string("center"),
+        Align::End => json_string("end"),
+        Align::Offset(offset) => offset.to_string(),
+    }
+}
+
+fn color_hex(color: [f32; 4]) -> String {
+    color.iter().fold("#".to_owned(), |hex, component| {
+        format!("{hex}{:02x}", (component * 255.0).round() as u8)
+    })
+}
+
+fn window_rules_json(config: &RuntimeConfig) -> String {
+    json_array(config.window_rules.iter().map(|rule| {
+        json_object(vec![
+            ("class", rule_pattern_json(&rule.class)),
+            ("app_id", rule_pattern_json(&rule.app_id)),
+            ("title", rule_pattern_json(&rule.title)),
+            ("not_class", rule_pattern_json(&rule.not_class)),
+            ("not_app_id", rule_pattern_json(&rule.not_app_id)),
+            ("not_title", rule_pattern_json(&rule.not_title)),
+            ("match_mode", json_string(rule.match_mode.as_str())),
+            (
+                "type",
+                json_optional_string(rule.window_type.map(|window_type| window_type.as_str())),
+            ),
+            (
+                "workspace",
+                json_optional(rule.workspace.map(|index| index + 1)),
+            ),
+            ("floating", json_optional(rule.floating)),
+            ("sticky", json_optional(rule.sticky)),
+            ("fullscreen", json_optional(rule.fullscreen)),
+            ("focus", json_optional(rule.focus)),
+            (
+                "no_focus_follows_mouse",
+                json_optional(rule.no_focus_follows_mouse),
+            ),
+            ("exclusive_input", json_optional(rule.exclusive_input)),
+            (
+                "close_to_workspace",
+                json_optional(rule.close_to_workspace.map(|index| index + 1)),
+            ),
+            ("hide_on_close", json_optional(rule.hide_on_close)),
+            ("on_open", json_optional_string(rule.on_open.as_deref())),
+            ("output", json_optional_string(rule.output.as_deref())),
+            ("width", rule_size_json(rule.width)),
+            ("height", rule_size_json(rule.height)),
+            (
+                "position",
+                rule.position.map_or_else(
+                    || "null".to_owned(),
+                    |position| {
+                        json_object(vec![
+                            ("x", align_json(position.x)),
+                            ("y", align_json(position.y)),
+                        ])
+                    },
+                ),
+            ),
+            ("max_fps", json_optional(rule.max_fps)),
+            ("opacity", json_optional(rule.opacity)),
+            (
+                "border_color",
+                json_optional_string(rule.border_color.map(color_hex).as_deref()),
+            ),
+            ("no_gaps", json_optional(rule.no_gaps)),
+            ("corner_radius", json_optional(rule.corner_radius)),
+            (
+                "maximize_margins",
+                rule.maximize_margins.map_or_else(
+                    || "null".to_owned(),
+                    |margins| {
+                        json_object(vec![
+                            ("top", margins.top.to_string()),
+                            ("bottom", margins.bottom.to_string()),
+                            ("left", margins.left.to_string()),
+                            ("right", margins.right.to_string()),
+                        ])
+                    },
+                ),
+            ),
+        ])
+    }))
+}
+
+fn wallpaper_fields(config: &RuntimeConfig) -> Fields {
+    let wallpaper = &config.wallpaper;
+    vec![
+        ("enabled", wallpaper.enabled.to_string()),
+        ("restore_command", json_string(&wallpaper.restore_command)),
+        ("image", json_string(&wallpaper.image)),
+        ("resize", json_string(&wallpaper.resize)),
+        ("transition_type", json_string(&wallpaper.transition_type)),
+        (
+            "transition_duration",
+            wallpaper.transition_duration.to_string(),
+        ),
+        (
+            "slideshow_directory",
+            json_string(&wallpaper.slideshow_directory),
+        ),
+        (
+            "slideshow_interval",
+            wallpaper.slideshow_interval_secs.to_string(),
+        ),
+        ("builtin", wallpaper.builtin.to_string()),
+    ]
+}
+
+fn xwayland_fields(config: &RuntimeConfig) -> Fields {
+    let xwayland = &config.xwayland;
+    vec![
+        ("enabled", xwayland.enabled.to_string()),
+        ("path", json_string(&xwayland.path)),
+        ("display", json_string(&xwayland.display)),
+    ]
+}
+
+fn input_fields(config: &RuntimeConfig) -> Fields {
+    let input = &config.input;
+    vec![
+        ("left_handed", input.left_handed.to_string()),
+        (
+            "hide_cursor_on_touch",
+            input.hide_cursor_on_touch.to_string(),
+        ),
+        (
+            "touch_hold_right_click",
+            input.touch_hold_right_click_ms.to_string(),
+        ),
+        ("sticky_keys", input.sticky_keys.to_string()),
+        ("slow_keys", input.slow_keys_ms.to_string()),
+        ("bounce_keys", input.bounce_keys_ms.to_string()),
+        (
+            "devices",
+            json_array(input.devices.iter().map(|device| {
+                json_object(vec![
+                    ("name", json_string(&device.name)),
+                    ("left_handed", json_optional(device.left_handed)),
+                    ("hide_cursor", json_optional(device.hide_cursor)),
+                ])
+            })),
+        ),
+    ]
+}
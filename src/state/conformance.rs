@@ -0,0 +1,112 @@
+use std::sync::OnceLock;
+
+use smithay::{
+    backend::renderer::{buffer_dimensions, utils::with_renderer_surface_state},
+    reexports::{
+        wayland_protocols::xdg::shell::server::xdg_toplevel,
+        wayland_server::{Resource, protocol::wl_surface},
+    },
+};
+
+use super::{Raven, WlSurface};
+
+fn strict_protocol_env() -> Option<bool> {
+    static STRICT_PROTOCOL: OnceLock<Option<bool>> = OnceLock::new();
+    *STRICT_PROTOCOL.get_or_init(|| {
+        std::env::var_os("RAVEN_STRICT_PROTOCOL").map(|value| {
+            let value = value.to_string_lossy().to_ascii_lowercase();
+            matches!(value.as_str(), "1" | "true" | "yes" | "on")
+        })
+    })
+}
+
+fn strict_protocol_enabled(state: &Raven) -> bool {
+    strict_protocol_env().unwrap_or(state.config.strict_protocol)
+}
+
+fn client_label(surface: &WlSurface) -> String {
+    surface
+        .client()
+        .map(|client| format!("{:?}", client.id()))
+        .unwrap_or_else(|| "<dead client>".to_owned())
+}
+
+fn reject(surface: &WlSurface, check: &str, message: String) {
+    tracing::warn!(
+        check,
+        surface = ?surface.id(),
+        client = %client_label(surface),
+        "strict protocol: {message}"
+    );
+    surface.post_error(wl_surface::Error::InvalidSize, message);
+}
+
+/// Validates a freshly committed surface against rules Raven normally tolerates.
+/// Returns `false` once a protocol error was posted and the client is going away.
+pub(super) fn check_surface_commit(state: &Raven, surface: &WlSurface) -> bool {
+    if !strict_protocol_enabled(state) || !surface.is_alive() {
+        return true;
+    }
+
+    let attached = with_renderer_surface_state(surface, |renderer_state| {
+        renderer_state.buffer().and_then(|buffer| {
+            buffer_dimensions(buffer).map(|size| (size, renderer_state.buffer_scale()))
+        })
+    })
+    .flatten();
+
+    if let Some((size, scale)) = attached
+        && scale > 1
+        && (size.w % scale != 0 || size.h % scale != 0)
+    {
+        reject(
+            surface,
+            "buffer_scale",
+            format!(
+                "buffer {}x{} is not a multiple of buffer_scale {scale}",
+                size.w, size.h
+            ),
+        );
+        return false;
+    }
+
+    let Some(window) = state.window_for_surface(surface) else {
+        return true;
+    };
+    let Some(toplevel) = window.toplevel() else {
+        return true;
+    };
+    let Some((configured, constrained)) = toplevel.with_committed_state(|committed| {
+        committed.and_then(|committed| {
+            let constrained = if committed.states.contains(xdg_toplevel::State::Fullscreen) {
+                Some("fullscreen")
+            } else if committed.states.contains(xdg_toplevel::State::Maximized) {
+                Some("maximized")
+            } else {
+                None
+            };
+            committed.size.zip(constrained)
+        })
+    }) else {
+        return true;
+    };
+    if configured.w <= 0 || configured.h <= 0 {
+        return true;
+    }
+
+    // xdg_toplevel: a maximized or fullscreen client must not exceed the acked configure size.
+    let geometry = window.geometry().size;
+    if geometry.w > configured.w || geometry.h > configured.h {
+        reject(
+            surface,
+            "configured_size",
+            format!(
+                "{constrained} window geometry {}x{} exceeds acked configure size {}x{}",
+                geometry.w, geometry.h, configured.w, configured.h
+            ),
+        );
+        return false;
+    }
+
+    true
+}
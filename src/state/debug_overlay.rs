@@ -0,0 +1,111 @@
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
+
+use smithay::{
+    backend::renderer::element::Kind,
+    output::Output,
+    utils::{Logical, Rectangle},
+};
+
+use crate::render_helpers::{
+    SolidColorBuffer, SolidColorRenderElement,
+    text::{TextBuffer, text_size},
+};
+
+use super::Raven;
+
+const LABEL_PIXEL_SIZE: f64 = 2.0;
+const LABEL_PADDING: f64 = 6.0;
+const LABEL_INSET: f64 = 4.0;
+const LABEL_MAX_APP_ID_CHARS: usize = 32;
+const LABEL_TEXT_COLOR: [f32; 4] = [1.0, 0.85, 0.2, 1.0];
+const LABEL_BACKGROUND_COLOR: [f32; 4] = [0.0, 0.0, 0.0, 0.75];
+
+/// Text and background of the labels on screen, by output name and slot, so
+/// labels that stay the same add no damage.
+#[derive(Default)]
+pub(super) struct DebugLabels(RefCell<HashMap<(String, usize), (TextBuffer, SolidColorBuffer)>>);
+
+fn slot_label(index: usize, rect: Rectangle<i32, Logical>, app_id: &str) -> String {
+    let app_id: String = app_id.chars().take(LABEL_MAX_APP_ID_CHARS).collect();
+    format!(
+        "#{} {}x{}+{}+{}\n{app_id}",
+        index + 1,
+        rect.size.w,
+        rect.size.h,
+        rect.loc.x,
+        rect.loc.y
+    )
+}
+
+pub(super) fn set_layout_debug_overlay(state: &mut Raven, enabled: bool) {
+    if state.layout_debug_overlay == enabled {
+        return;
+    }
+    state.layout_debug_overlay = enabled;
+    if !enabled {
+        state.layout_debug_labels.0.borrow_mut().clear();
+    }
+    tracing::info!(enabled, "layout debug overlay toggled");
+    crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Ipc);
+}
+
+/// Labels for the tiled slots of the last layout pass that intersect `output`,
+/// positioned relative to the output origin, in front-to-back order.
+pub(super) fn layout_debug_overlay_elements(
+    state: &Raven,
+    output: &Output,
+) -> Vec<SolidColorRenderElement> {
+    if !state.layout_debug_overlay {
+        return Vec::new();
+    }
+    let Some(output_geo) = state.space.output_geometry(output) else {
+        return Vec::new();
+    };
+
+    let output_name = output.name();
+    let mut labels = state.layout_debug_labels.0.borrow_mut();
+    let mut shown = HashSet::new();
+    let mut elements = Vec::new();
+    for (index, (window, rect)) in state.tiled_slots.iter().enumerate() {
+        if !output_geo.overlaps(*rect) {
+            continue;
+        }
+        let app_id = window
+            .toplevel()
+            .and_then(|toplevel| Raven::surface_app_id_and_title(toplevel.wl_surface()).0)
+            .unwrap_or_else(|| "<unknown>".to_owned());
+        let label = slot_label(index, *rect, &app_id);
+
+        let label_size = text_size(&label, LABEL_PIXEL_SIZE);
+        let origin = (rect.loc - output_geo.loc).to_f64() + (LABEL_INSET, LABEL_INSET).into();
+        let key = (output_name.clone(), index);
+        let (text, background) = labels.entry(key.clone()).or_default();
+        shown.insert(key);
+        elements.extend(text.elements(
+            &label,
+            origin + (LABEL_PADDING, LABEL_PADDING).into(),
+            LABEL_PIXEL_SIZE,
+            LABEL_TEXT_COLOR,
+        ));
+
+        background.update(
+            (
+                label_size.w + LABEL_PADDING * 2.0,
+                label_size.h + LABEL_PADDING * 2.0,
+            ),
+            LABEL_BACKGROUND_COLOR,
+        );
+        elements.push(SolidColorRenderElement::from_buffer(
+            background,
+            origin,
+            1.0,
+            Kind::Unspecified,
+        ));
+    }
+    // Slots gone from this output since the last frame.
+    labels.retain(|key, _| key.0 != output_name || shown.contains(key));
+    elements
+}
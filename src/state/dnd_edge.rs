@@ -0,0 +1,135 @@
+//! Workspace switching while dragging: dwell at the left or right screen edge
+//! during a drag-and-drop for `dnd_edge_switch_delay` ms to flip to the
+//! neighbouring workspace, with the drag still held.
+
+use std::time::Duration;
+
+use smithay::{
+    input::pointer::MotionEvent,
+    reexports::calloop::{
+        RegistrationToken,
+        timer::{TimeoutAction, Timer},
+    },
+    utils::SERIAL_COUNTER,
+};
+
+use super::Raven;
+
+// How close to the edge, in logical pixels, the pointer has to be.
+const EDGE_THRESHOLD: f64 = 2.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Edge {
+    Left,
+    Right,
+}
+
+#[derive(Default)]
+pub(super) struct DndEdgeSwitch {
+    // Set when a client starts a pointer drag; dropped once the grab is gone.
+    active: bool,
+    dwell: Option<(Edge, RegistrationToken)>,
+}
+
+pub(super) fn drag_started(state: &mut Raven) {
+    state.dnd_edge_switch.active = true;
+}
+
+fn edge_under_pointer(state: &Raven) -> Option<Edge> {
+    let output = state.active_output_for_pointer()?;
+    let geometry = state.space.output_geometry(&output)?.to_f64();
+    let x = state.pointer_location.x;
+    if x <= geometry.loc.x + EDGE_THRESHOLD {
+        Some(Edge::Left)
+    } else if x >= geometry.loc.x + geometry.size.w - 1.0 - EDGE_THRESHOLD {
+        Some(Edge::Right)
+    } else {
+        None
+    }
+}
+
+fn stop_dwell(state: &mut Raven) {
+    if let Some((_, timer)) = state.dnd_edge_switch.dwell.take() {
+        state.loop_handle.remove(timer);
+    }
+}
+
+/// Start, keep or drop the edge dwell after the pointer moved.
+pub(super) fn pointer_moved(state: &mut Raven) {
+    if !state.dnd_edge_switch.active {
+        return;
+    }
+    if !state.pointer().is_grabbed() {
+        state.dnd_edge_switch.active = false;
+        stop_dwell(state);
+        return;
+    }
+
+    let edge = edge_under_pointer(state);
+    if state.dnd_edge_switch.dwell.map(|(dwelling, _)| dwelling) == edge {
+        return;
+    }
+    stop_dwell(state);
+    if let Some(edge) = edge {
+        start_dwell(state, edge);
+    }
+}
+
+fn start_dwell(state: &mut Raven, edge: Edge) {
+    let delay = state.config.dnd_edge_switch_delay_ms;
+    if delay == 0 {
+        return;
+    }
+
+    let timer = Timer::from_duration(Duration::from_millis(u64::from(delay)));
+    match state.loop_handle.insert_source(timer, move |_, _, state| {
+        state.dnd_edge_switch.dwell = None;
+        dwell_elapsed(state, edge);
+        TimeoutAction::Drop
+    }) {
+        Ok(token) => state.dnd_edge_switch.dwell = Some((edge, token)),
+        Err(err) => tracing::warn!("failed to schedule dnd edge switch: {err}"),
+    }
+}
+
+fn dwell_elapsed(state: &mut Raven, edge: Edge) {
+    let pointer = state.pointer();
+    if !pointer.is_grabbed() || edge_under_pointer(state) != Some(edge) {
+        state.dnd_edge_switch.active = pointer.is_grabbed();
+        return;
+    }
+
+    let target = match edge {
+        Edge::Left => state.current_workspace.checked_sub(1),
+        Edge::Right => {
+            Some(state.current_workspace + 1).filter(|index| *index < state.workspaces.len())
+        }
+    };
+    if let Some(target) = target {
+        tracing::debug!(
+            workspace = target + 1,
+            "dnd edge dwell: switching workspace"
+        );
+        if let Err(err) = state.switch_workspace(target) {
+            tracing::warn!("failed to switch workspace during drag: {err}");
+            return;
+        }
+
+        // Let the drag grab find its new drop target without waiting for motion.
+        let under = state.contents_under(state.pointer_location);
+        state.pointer_contents.clone_from(&under);
+        pointer.motion(
+            state,
+            under.surface,
+            &MotionEvent {
+                location: state.pointer_location,
+                serial: SERIAL_COUNTER.next_serial(),
+                time: state.start_time.elapsed().as_millis() as u32,
+            },
+        );
+        pointer.frame(state);
+    }
+
+    // Keep flipping while the pointer stays at the edge.
+    start_dwell(state, edge);
+}
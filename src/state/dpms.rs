@@ -0,0 +1,39 @@
+//! The `dpms` action and IPC command. They blank outputs through the same
+//! backend call `zwlr_output_power_manager_v1` clients such as swayidle use, so
+//! every path agrees on which outputs are lit.
+
+use smithay::output::Output;
+
+use crate::{CompositorError, config::DpmsMode};
+
+use super::Raven;
+
+/// Switch `output_name`, or every mapped output, on or off.
+pub(super) fn set_dpms(
+    state: &mut Raven,
+    output_name: Option<&str>,
+    mode: DpmsMode,
+) -> Result<(), CompositorError> {
+    let outputs: Vec<Output> = match output_name {
+        Some(name) => vec![
+            state
+                .space
+                .outputs()
+                .find(|output| output.name() == name)
+                .cloned()
+                .ok_or_else(|| CompositorError::Backend(format!("unknown output `{name}`")))?,
+        ],
+        None => state.space.outputs().cloned().collect(),
+    };
+    let on = match mode {
+        DpmsMode::On => true,
+        DpmsMode::Off => false,
+        DpmsMode::Toggle => !outputs
+            .iter()
+            .any(|output| crate::backend::udev::output_powered(state, output)),
+    };
+    for output in &outputs {
+        crate::backend::udev::set_output_power(state, output, on)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,79 @@
+//! The `events` config table: commands run on compositor events, so users get
+//! hook points without writing an IPC client. Each command is spawned with the
+//! event's details in `RAVEN_*` environment variables.
+
+use smithay::reexports::wayland_server::protocol::wl_surface::WlSurface;
+
+use super::Raven;
+
+fn run_event_hook(state: &Raven, event: &str, command: Option<&str>, env: &[(&str, String)]) {
+    let Some(command) = command else {
+        return;
+    };
+    tracing::info!(event, command = %command, "running event hook");
+    state.spawn_command_with_env(command, env);
+}
+
+pub(super) fn output_added(state: &Raven, output_name: &str) {
+    run_event_hook(
+        state,
+        "output_added",
+        state.config.events.output_added.as_deref(),
+        &[("RAVEN_OUTPUT", output_name.to_owned())],
+    );
+}
+
+pub(super) fn output_removed(state: &Raven, output_name: &str) {
+    run_event_hook(
+        state,
+        "output_removed",
+        state.config.events.output_removed.as_deref(),
+        &[("RAVEN_OUTPUT", output_name.to_owned())],
+    );
+}
+
+pub(super) fn workspace_switched(state: &Raven, previous: usize, current: usize) {
+    run_event_hook(
+        state,
+        "workspace_switched",
+        state.config.events.workspace_switched.as_deref(),
+        &[
+            ("RAVEN_WORKSPACE", (current + 1).to_string()),
+            ("RAVEN_PREVIOUS_WORKSPACE", (previous + 1).to_string()),
+        ],
+    );
+}
+
+/// `events.window_opened`, then the `on_open` command of the last matching window
+/// rule that sets one.
+pub(super) fn window_opened(state: &Raven, surface: &WlSurface) {
+    let (app_id, title) = Raven::surface_app_id_and_title(surface);
+    let workspace = state
+        .window_for_surface(surface)
+        .and_then(|window| state.workspace_index_for_window(&window))
+        .unwrap_or(state.current_workspace);
+    let env = [
+        ("RAVEN_APP_ID", app_id.unwrap_or_default()),
+        ("RAVEN_TITLE", title.unwrap_or_default()),
+        (
+            "RAVEN_WINDOW_ID",
+            state
+                .window_id(surface)
+                .map_or_else(String::new, |id| id.to_string()),
+        ),
+        ("RAVEN_WORKSPACE", (workspace + 1).to_string()),
+    ];
+
+    run_event_hook(
+        state,
+        "window_opened",
+        state.config.events.window_opened.as_deref(),
+        &env,
+    );
+    run_event_hook(
+        state,
+        "window_rule.on_open",
+        super::rules::surface_on_open(state, surface),
+        &env,
+    );
+}
@@ -0,0 +1,95 @@
+//! The `find_cursor` action: a ring that closes in on the pointer and fades,
+//! for spotting a small cursor across large or many monitors.
+
+use std::{
+    f64::consts::TAU,
+    time::{Duration, Instant},
+};
+
+use smithay::{
+    backend::renderer::element::Kind,
+    output::Output,
+    reexports::calloop::timer::{TimeoutAction, Timer},
+    utils::Point,
+};
+
+use crate::render_helpers::{SolidColorBuffer, SolidColorRenderElement};
+
+use super::Raven;
+
+const DURATION: Duration = Duration::from_millis(900);
+// Roughly one animation step per frame at 60 Hz.
+const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+const START_RADIUS: f64 = 160.0;
+const END_RADIUS: f64 = 24.0;
+const DOT_COUNT: usize = 48;
+const DOT_SIZE: f64 = 5.0;
+const RING_COLOR: [f32; 3] = [1.0, 0.85, 0.2];
+
+pub(super) fn start(state: &mut Raven) {
+    // Pressing the key again restarts the ring instead of stacking a second one.
+    if state.find_cursor_started.is_some() {
+        state.find_cursor_started = Some(Instant::now());
+        return;
+    }
+
+    let timer = Timer::from_duration(FRAME_INTERVAL);
+    match state.loop_handle.insert_source(timer, |_, _, state| {
+        let finished = state
+            .find_cursor_started
+            .is_none_or(|started| started.elapsed() >= DURATION);
+        if finished {
+            state.find_cursor_started = None;
+        }
+        crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Animation);
+        if finished {
+            TimeoutAction::Drop
+        } else {
+            TimeoutAction::ToDuration(FRAME_INTERVAL)
+        }
+    }) {
+        Ok(_) => {
+            state.find_cursor_started = Some(Instant::now());
+            crate::backend::udev::queue_redraw_all(
+                state,
+                crate::backend::udev::RedrawCause::Animation,
+            );
+        }
+        Err(err) => tracing::warn!("failed to schedule find-cursor animation: {err}"),
+    }
+}
+
+/// The ring around the pointer on `output`, relative to the output origin.
+pub(super) fn find_cursor_elements(state: &Raven, output: &Output) -> Vec<SolidColorRenderElement> {
+    let Some(started) = state.find_cursor_started else {
+        return Vec::new();
+    };
+    let Some(output_geo) = state.space.output_geometry(output) else {
+        return Vec::new();
+    };
+
+    let progress = (started.elapsed().as_secs_f64() / DURATION.as_secs_f64()).min(1.0);
+    // Ease out so the ring slows down as it reaches the cursor.
+    let eased = 1.0 - (1.0 - progress).powi(3);
+    let radius = START_RADIUS + (END_RADIUS - START_RADIUS) * eased;
+    let alpha = (1.0 - progress * progress) as f32;
+    let center = state.pointer_location - output_geo.loc.to_f64();
+    let output_rect = output_geo.to_f64();
+
+    let [r, g, b] = RING_COLOR;
+    (0..DOT_COUNT)
+        .map(|index| {
+            let angle = TAU * index as f64 / DOT_COUNT as f64;
+            center
+                + Point::from((
+                    radius * angle.cos() - DOT_SIZE / 2.0,
+                    radius * angle.sin() - DOT_SIZE / 2.0,
+                ))
+        })
+        .filter(|location| output_rect.contains(*location + output_geo.loc.to_f64()))
+        .map(|location| {
+            let buffer = SolidColorBuffer::new((DOT_SIZE, DOT_SIZE), [r, g, b, alpha]);
+            SolidColorRenderElement::from_buffer(&buffer, location, 1.0, Kind::Unspecified)
+        })
+        .collect()
+}
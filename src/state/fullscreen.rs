@@ -43,9 +43,9 @@ pub(super) struct FullscreenState {
 }
 
 impl FullscreenState {
-    pub(super) fn new() -> Self {
+    pub(super) fn new(workspace_count: usize) -> Self {
         Self {
-            owner_surfaces_by_workspace: vec![None; WORKSPACE_COUNT],
+            owner_surfaces_by_workspace: vec![None; workspace_count],
             maximized_surfaces: HashSet::new(),
             pending_unmapped_ids: HashSet::new(),
             pending_transition_by_surface: HashMap::new(),
@@ -148,14 +148,24 @@ impl Raven {
         window: &Window,
         requested_output: Option<Output>,
     ) -> Option<Output> {
+        let requested_output = requested_output.filter(|output| {
+            let active = self.output_is_active(output);
+            if !active {
+                tracing::debug!(
+                    output = %output.name(),
+                    "fullscreen requested on inactive output; falling back to window output"
+                );
+            }
+            active
+        });
         requested_output
-            .filter(|output| self.output_is_active(output))
             .or_else(|| self.fullscreen_slot_output_for_window(window))
             .or_else(|| self.fallback_output_for_window(window))
     }
 
     fn fullscreen_target_output_for_window(&self, window: &Window) -> Option<Output> {
         self.fullscreen_slot_output_for_window(window)
+            .or_else(|| self.window_output(window))
             .or_else(|| self.space.outputs_for_element(window).into_iter().next())
             .or_else(|| self.space.outputs().next().cloned())
     }
@@ -354,15 +364,29 @@ impl Raven {
 
     fn work_area_rect_for_window(&self, window: &Window) -> Option<Rectangle<i32, Logical>> {
         let output = self.preferred_output_for_window(window)?;
-        let output_rect = self.space.output_geometry(&output)?;
-        let mut layer_map = layer_map_for_output(&output);
-        layer_map.arrange();
-        let work_geo = layer_map.non_exclusive_zone();
-        if work_geo.size.w > 0 && work_geo.size.h > 0 {
-            Some(work_geo)
-        } else {
-            Some(output_rect)
-        }
+        let work_area = self.output_work_area(&output)?;
+        let Some(margins) = window
+            .toplevel()
+            .and_then(|toplevel| rules::surface_maximize_margins(self, toplevel.wl_surface()))
+        else {
+            return Some(work_area);
+        };
+
+        // Never squeeze the window below one pixel, whatever the rule asks for.
+        let horizontal = i32::try_from(margins.left.saturating_add(margins.right))
+            .unwrap_or(i32::MAX)
+            .min((work_area.size.w - 1).max(0));
+        let vertical = i32::try_from(margins.top.saturating_add(margins.bottom))
+            .unwrap_or(i32::MAX)
+            .min((work_area.size.h - 1).max(0));
+        let left = i32::try_from(margins.left)
+            .unwrap_or(i32::MAX)
+            .min(horizontal);
+        let top = i32::try_from(margins.top).unwrap_or(i32::MAX).min(vertical);
+        Some(Rectangle::new(
+            (work_area.loc.x + left, work_area.loc.y + top).into(),
+            (work_area.size.w - horizontal, work_area.size.h - vertical).into(),
+        ))
     }
 
     pub(crate) fn window_exclusive_target_rect(
@@ -724,6 +748,23 @@ impl Raven {
         self.fullscreen_window_for_output(output).is_some()
     }
 
+    /// Whether keys bypass keybinds for the focused fullscreen window. A window
+    /// rule's `exclusive_input` takes precedence over the global flag.
+    pub(crate) fn fullscreen_input_is_exclusive(&self) -> bool {
+        let Some(slot) = self.slot_for_workspace(self.current_workspace) else {
+            return false;
+        };
+        let focused = self
+            .seat
+            .get_keyboard()
+            .and_then(|keyboard| keyboard.current_focus());
+        if focused.as_ref() != Some(&slot.surface) {
+            return false;
+        }
+        rules::surface_exclusive_input(self, &slot.surface)
+            .unwrap_or(self.config.fullscreen_exclusive_input)
+    }
+
     pub(crate) fn fullscreen_window_for_output(&self, output: &Output) -> Option<Window> {
         let workspace_index = self.current_workspace;
         let window = self.workspace_fullscreen_owner_window(workspace_index)?;
@@ -751,15 +792,7 @@ impl Raven {
             .workspace_index_for_window(window)
             .unwrap_or(self.current_workspace);
         let output = self.preferred_output_for_window(window)?;
-        let out_geo = self.space.output_geometry(&output)?;
-        let mut layer_map = layer_map_for_output(&output);
-        layer_map.arrange();
-        let work_geo = layer_map.non_exclusive_zone();
-        let layout_geo = if work_geo.size.w > 0 && work_geo.size.h > 0 {
-            work_geo
-        } else {
-            out_geo
-        };
+        let layout_geo = self.output_work_area(&output)?;
 
         let workspace_windows = self.workspaces[workspace_index].clone();
         let tiled_windows: Vec<Window> = workspace_windows
@@ -773,22 +806,8 @@ impl Raven {
             return None;
         }
 
-        let gaps = GapConfig {
-            outer_horizontal: self.config.gaps_outer_horizontal,
-            outer_vertical: self.config.gaps_outer_vertical,
-            inner_horizontal: self.config.gaps_inner_horizontal,
-            inner_vertical: self.config.gaps_inner_vertical,
-        };
-
-        let geometries = self.layout.arrange(
-            &tiled_windows,
-            layout_geo.size.w as u32,
-            layout_geo.size.h as u32,
-            &gaps,
-            self.config.master_factor,
-            self.config.num_master,
-            self.config.smart_gaps,
-        );
+        let geometries = self.arrange_tiled(&tiled_windows, layout_geo.size);
+        let scroll_offset = self.scroll_offset(&output);
 
         tiled_windows
             .into_iter()
@@ -798,7 +817,7 @@ impl Raven {
                 (
                     Rectangle::new(
                         Point::<i32, Logical>::from((
-                            layout_geo.loc.x + geom.x_coordinate,
+                            layout_geo.loc.x + geom.x_coordinate - scroll_offset,
                             layout_geo.loc.y + geom.y_coordinate,
                         )),
                         Size::<i32, Logical>::from((geom.width as i32, geom.height as i32)),
@@ -955,6 +974,22 @@ impl Raven {
         false
     }
 
+    /// Switch to the workspace holding `window` so a client-requested fullscreen output shows
+    /// the window instead of an unrelated workspace. Returns false if the switch failed.
+    pub(crate) fn reveal_workspace_for_fullscreen_request(&mut self, window: &Window) -> bool {
+        let Some(target_workspace) = self.workspace_index_for_window(window) else {
+            return true;
+        };
+        if target_workspace == self.current_workspace {
+            return true;
+        }
+        if let Err(err) = self.switch_workspace(target_workspace) {
+            tracing::warn!("failed to switch workspace for fullscreen request: {err}");
+            return false;
+        }
+        true
+    }
+
     pub(crate) fn request_fullscreen_exit(&mut self, window: &Window) -> bool {
         let Some(surface_id) = Self::window_surface_id(window) else {
             return false;
@@ -0,0 +1,102 @@
+//! Idle tracking. Every input event resets the `ext-idle-notify-v1` timers that
+//! swayidle and hypridle listen on, and the timer of the `idle` config table:
+//! after `idle.timeout` seconds without input Raven runs `idle.on_idle`, and
+//! `idle.on_resume` with the next input. Visible idle inhibitors hold both.
+
+use std::time::{Duration, Instant};
+
+use smithay::reexports::calloop::{
+    RegistrationToken,
+    timer::{TimeoutAction, Timer},
+};
+
+use super::Raven;
+
+pub(super) struct IdleTracker {
+    last_activity: Instant,
+    // Set once `on_idle` ran, until the next input.
+    idle: bool,
+    // Timeout the timer runs for; a reload re-arms it on change.
+    armed: Option<(Duration, RegistrationToken)>,
+}
+
+impl Default for IdleTracker {
+    fn default() -> Self {
+        Self {
+            last_activity: Instant::now(),
+            idle: false,
+            armed: None,
+        }
+    }
+}
+
+pub(super) fn notify_activity(state: &mut Raven) {
+    let seat = state.seat.clone();
+    state.idle_notifier_state.notify_activity(&seat);
+    state.idle.last_activity = Instant::now();
+    if !std::mem::take(&mut state.idle.idle) {
+        return;
+    }
+    tracing::debug!("input after idle timeout");
+    if let Some(command) = state.config.idle.on_resume.clone() {
+        state.spawn_command(&command);
+    }
+}
+
+/// Pass the inhibitor state on to `ext-idle-notify-v1` clients. Inhibitors count
+/// only while their surface is visible, so this follows workspace switches too.
+pub(super) fn refresh_inhibited(state: &mut Raven) {
+    let inhibited = state.idle_inhibited();
+    if state.idle_notifier_state.is_inhibited() != inhibited {
+        state.idle_notifier_state.set_is_inhibited(inhibited);
+    }
+}
+
+/// Start, re-arm or stop the idle timer to match `idle.timeout`.
+pub(super) fn sync(state: &mut Raven) {
+    let wanted = (state.config.idle.timeout_secs > 0)
+        .then(|| Duration::from_secs(u64::from(state.config.idle.timeout_secs)));
+    let armed = state.idle.armed.as_ref().map(|(timeout, _)| *timeout);
+    if armed == wanted {
+        return;
+    }
+    if let Some((_, timer)) = state.idle.armed.take() {
+        state.loop_handle.remove(timer);
+    }
+    let Some(timeout) = wanted else {
+        return;
+    };
+
+    let timer = Timer::from_duration(timeout);
+    match state.loop_handle.insert_source(timer, move |_, _, state| {
+        TimeoutAction::ToDuration(check(state, timeout))
+    }) {
+        Ok(timer) => {
+            tracing::info!(timeout_secs = timeout.as_secs(), "idle timer armed");
+            state.idle.armed = Some((timeout, timer));
+        }
+        Err(err) => tracing::warn!("failed to schedule idle timer: {err}"),
+    }
+}
+
+/// Run `on_idle` once the timeout passed without input; returns when to check again.
+fn check(state: &mut Raven, timeout: Duration) -> Duration {
+    let elapsed = state.idle.last_activity.elapsed();
+    if elapsed < timeout {
+        return timeout - elapsed;
+    }
+    if state.idle.idle {
+        return timeout;
+    }
+    // An inhibitor restarts the countdown, as it does for ext-idle-notify clients.
+    if state.idle_inhibited() {
+        state.idle.last_activity = Instant::now();
+        return timeout;
+    }
+    tracing::debug!(timeout_secs = timeout.as_secs(), "idle timeout reached");
+    state.idle.idle = true;
+    if let Some(command) = state.config.idle.on_idle.clone() {
+        state.spawn_command(&command);
+    }
+    timeout
+}
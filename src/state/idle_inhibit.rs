@@ -0,0 +1,88 @@
+//! Idle inhibitors from `zwp_idle_inhibit_manager_v1`, as video players create
+//! while playing. An inhibitor only counts while its surface is visible: a
+//! window on the shown workspace, or a layer surface. `raven idle` reports the
+//! state for bar modules.
+
+use smithay::{
+    desktop::{WindowSurfaceType, layer_map_for_output},
+    reexports::wayland_server::protocol::wl_surface::WlSurface,
+    wayland::compositor::get_parent,
+};
+
+use super::{Raven, sway_ipc::json_optional_string};
+
+fn root_surface(surface: &WlSurface) -> WlSurface {
+    let mut root = surface.clone();
+    while let Some(parent) = get_parent(&root) {
+        root = parent;
+    }
+    root
+}
+
+fn surface_visible(state: &Raven, surface: &WlSurface) -> bool {
+    let root = root_surface(surface);
+    if let Some(window) = state.window_for_surface(&root) {
+        return state.space.element_location(&window).is_some();
+    }
+    state.space.outputs().any(|output| {
+        layer_map_for_output(output)
+            .layer_for_surface(&root, WindowSurfaceType::TOPLEVEL)
+            .is_some()
+    })
+}
+
+/// Surfaces whose inhibitors are in effect right now.
+pub(super) fn active_inhibitors(state: &Raven) -> Vec<WlSurface> {
+    state
+        .idle_inhibit_state
+        .surfaces()
+        .into_iter()
+        .filter(|surface| surface_visible(state, surface))
+        .collect()
+}
+
+pub(super) fn inhibitors_changed(state: &mut Raven) {
+    tracing::debug!(
+        inhibitors = state.idle_inhibit_state.surfaces().len(),
+        inhibited = !active_inhibitors(state).is_empty(),
+        "idle inhibitors changed"
+    );
+}
+
+fn inhibitor_label(surface: &WlSurface) -> Option<String> {
+    Raven::surface_app_id_and_title(&root_surface(surface)).0
+}
+
+/// The `raven idle` report.
+pub(super) fn render_idle_report(state: &Raven) -> String {
+    let surfaces = active_inhibitors(state);
+    if surfaces.is_empty() {
+        return "Idle: not inhibited\n".to_owned();
+    }
+    let mut out = format!("Idle: inhibited by {} surface(s)\n", surfaces.len());
+    for surface in &surfaces {
+        let label = inhibitor_label(surface).unwrap_or_else(|| "<unknown>".to_owned());
+        out.push_str(&format!("  {label}\n"));
+    }
+    out
+}
+
+/// The `raven idle --json` report.
+pub(super) fn render_idle_json(state: &Raven) -> String {
+    let surfaces = active_inhibitors(state);
+    let inhibitors: Vec<String> = surfaces
+        .iter()
+        .map(|surface| {
+            format!(
+                r#"{{"app_id":{}}}"#,
+                json_optional_string(inhibitor_label(surface).as_deref())
+            )
+        })
+        .collect();
+    let report = format!(
+        r#"{{"idle_inhibited":{},"inhibitors":[{}]}}"#,
+        !surfaces.is_empty(),
+        inhibitors.join(",")
+    );
+    format!("{report}\n")
+}
@@ -0,0 +1,80 @@
+//! Frames for ext-image-copy-capture sessions. An output source is rendered
+//! the way `raven screenshot` captures it; a toplevel source renders just that
+//! window's surface tree offscreen, so it can be shared while covered or on
+//! another workspace.
+
+use smithay::{
+    desktop::Window,
+    output::Output,
+    utils::{Physical, Size},
+};
+
+use super::Raven;
+use crate::protocols::image_copy_capture::{CaptureFrame, CaptureSource};
+
+enum Source {
+    Output(Output),
+    Window(Window),
+}
+
+fn resolve(state: &Raven, source: &CaptureSource) -> Option<Source> {
+    match source {
+        CaptureSource::Output(output) => output.upgrade().map(Source::Output),
+        CaptureSource::Toplevel(handle) => state
+            .foreign_toplevel_manager_state
+            .surface_for_list_handle(handle)
+            .and_then(|surface| state.window_for_surface(&surface))
+            .map(Source::Window),
+    }
+}
+
+fn size_of(state: &Raven, source: &Source) -> Option<Size<i32, Physical>> {
+    let size = match source {
+        Source::Output(output) => {
+            let mode = output.current_mode()?;
+            output.current_transform().transform_size(mode.size)
+        }
+        Source::Window(window) => {
+            let scale = state
+                .toplevel_output(window)
+                .map_or(1.0, |output| output.current_scale().fractional_scale());
+            window.geometry().size.to_physical_precise_round(scale)
+        }
+    };
+    (size.w > 0 && size.h > 0).then_some(size)
+}
+
+pub(super) fn source_size(state: &Raven, source: &CaptureSource) -> Option<Size<i32, Physical>> {
+    size_of(state, &resolve(state, source)?)
+}
+
+pub(super) fn serve(state: &mut Raven, frame: CaptureFrame) {
+    let Some(source) = resolve(state, frame.source()) else {
+        frame.stopped();
+        return;
+    };
+    let Some(size) = size_of(state, &source) else {
+        frame.stopped();
+        return;
+    };
+    if size != frame.buffer_size() {
+        frame.resized(size);
+        return;
+    }
+
+    let pixels = match &source {
+        Source::Output(output) => {
+            crate::backend::udev::capture_output(state, output).map(|capture| capture.pixels)
+        }
+        Source::Window(window) => {
+            crate::backend::udev::capture_window(state, window).map(|(_, pixels)| pixels)
+        }
+    };
+    let result = pixels
+        .and_then(|pixels| crate::backend::capture::copy_rgba_to_shm(&frame.buffer, size, &pixels));
+    match result {
+        Ok(()) => frame.submit(state.start_time.elapsed()),
+        // Dropping the frame reports the failure to the client.
+        Err(err) => tracing::warn!("image copy capture failed: {err}"),
+    }
+}
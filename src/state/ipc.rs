@@ -2,14 +2,38 @@ use std::{
     collections::HashSet,
     io::{Read, Write},
     os::unix::net::UnixStream,
+    path::Path,
+    time::Duration,
 };
 
 use smithay::{
-    reexports::wayland_server::Resource,
+    output::Output,
+    utils::{Logical, Rectangle},
     wayland::{compositor::with_states, shell::xdg::XdgToplevelSurfaceData},
 };
 
-use super::Raven;
+use crate::{CompositorError, backend::commit_timing::BUCKET_BOUNDS};
+
+use super::{
+    KeymapSettings, ProgressStatus, Raven,
+    reserve::ReserveEdge,
+    sway_ipc::{json_optional_string, json_string, listed_workspaces, rect_json},
+};
+
+// `clients --watch` frames end with this byte so the CLI knows when to redraw.
+const WATCH_FRAME_END: u8 = 0;
+const WATCH_WRITE_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// A CLI connection kept open by `clients --watch`.
+pub(super) struct ClientsWatcher {
+    stream: UnixStream,
+    last_report: String,
+}
+
+fn write_watch_frame(stream: &mut UnixStream, report: &str) -> std::io::Result<()> {
+    stream.write_all(report.as_bytes())?;
+    stream.write_all(&[WATCH_FRAME_END])
+}
 
 fn write_ipc_response(stream: &mut UnixStream, message: &str) {
     if let Err(err) = stream.write_all(message.as_bytes()) {
@@ -17,6 +41,18 @@ fn write_ipc_response(stream: &mut UnixStream, message: &str) {
     }
 }
 
+/// Split screenshot arguments into the target (`all`, an output name,
+/// `--region` or `--window`) and an optional path. `--output NAME` names the
+/// output explicitly.
+fn screenshot_args(args: &str) -> (&str, Option<&Path>) {
+    let args = args.strip_prefix("--output ").map_or(args, str::trim_start);
+    match args.split_once(char::is_whitespace) {
+        Some((target, path)) => (target, Some(Path::new(path.trim()))),
+        None if args.is_empty() => ("all", None),
+        None => (args, None),
+    }
+}
+
 pub(super) fn handle_ipc_stream(state: &mut Raven, stream: &mut UnixStream) {
     let mut request = String::new();
     if let Err(err) = stream.read_to_string(&mut request) {
@@ -29,10 +65,395 @@ pub(super) fn handle_ipc_stream(state: &mut Raven, stream: &mut UnixStream) {
             let output = render_clients_report(state);
             write_ipc_response(stream, &output);
         }
+        "clients --json" => {
+            let output = render_clients_json(state);
+            write_ipc_response(stream, &output);
+        }
+        "clients --watch" => {
+            let report = render_clients_report(state);
+            let stream = stream.try_clone().and_then(|mut stream| {
+                stream.set_write_timeout(Some(WATCH_WRITE_TIMEOUT))?;
+                write_watch_frame(&mut stream, &report)?;
+                Ok(stream)
+            });
+            match stream {
+                Ok(stream) => state.clients_watchers.push(ClientsWatcher {
+                    stream,
+                    last_report: report,
+                }),
+                Err(err) => tracing::warn!("failed to start clients watch: {err}"),
+            }
+        }
         "monitors" => {
             let output = render_monitors_report(state);
             write_ipc_response(stream, &output);
         }
+        "monitors --json" => {
+            let output = render_monitors_json(state);
+            write_ipc_response(stream, &output);
+        }
+        "workspaces" => {
+            let output = render_workspaces_report(state);
+            write_ipc_response(stream, &output);
+        }
+        "workspaces --json" => {
+            let output = render_workspaces_json(state);
+            write_ipc_response(stream, &output);
+        }
+        "focused" => {
+            let output = render_focused_report(state);
+            write_ipc_response(stream, &output);
+        }
+        "focused --json" => {
+            let output = render_focused_json(state);
+            write_ipc_response(stream, &output);
+        }
+        "pager" => {
+            let output = super::pager::render_pager_report(state);
+            write_ipc_response(stream, &output);
+        }
+        "pager --json" => {
+            let output = super::pager::render_pager_json(state);
+            write_ipc_response(stream, &output);
+        }
+        "stats" => {
+            let output = render_stats_report(state);
+            write_ipc_response(stream, &output);
+        }
+        "stats --clients" => {
+            let output = render_client_latency_report(state);
+            write_ipc_response(stream, &output);
+        }
+        "idle" => {
+            let output = super::idle_inhibit::render_idle_report(state);
+            write_ipc_response(stream, &output);
+        }
+        "idle --json" => {
+            let output = super::idle_inhibit::render_idle_json(state);
+            write_ipc_response(stream, &output);
+        }
+        "night-light" => {
+            let output = super::night_light::render_night_light_report(state);
+            write_ipc_response(stream, &output);
+        }
+        "night-light on" | "night-light off" | "night-light toggle" => {
+            let on = match request {
+                "night-light on" => Some(true),
+                "night-light off" => Some(false),
+                _ => None,
+            };
+            super::night_light::set(state, on);
+            let output = super::night_light::render_night_light_report(state);
+            write_ipc_response(stream, &output);
+        }
+        "session" => {
+            let output = super::session::render_session_report(state);
+            write_ipc_response(stream, &output);
+        }
+        "actions" => write_ipc_response(stream, &render_actions_report()),
+        "version" => {
+            let output = render_version_report(state);
+            write_ipc_response(stream, &output);
+        }
+        "get" => {
+            let keys = super::config_get::config_keys(&state.config);
+            write_ipc_response(
+                stream,
+                &format!("error: usage: get <key> (keys: {})\n", keys.join(", ")),
+            );
+        }
+        request if request.split_whitespace().next() == Some("get") => {
+            let key = request.trim_start_matches("get").trim();
+            match super::config_get::config_value_json(&state.config, key) {
+                Ok(value) => write_ipc_response(stream, &value),
+                Err(err) => write_ipc_response(stream, &format!("error: {err}\n")),
+            }
+        }
+        request if request.split_whitespace().next() == Some("layout-overlay") => {
+            let argument = request.split_whitespace().nth(1).unwrap_or("toggle");
+            let enabled = match argument {
+                "on" => true,
+                "off" => false,
+                "toggle" => !state.layout_debug_overlay,
+                other => {
+                    write_ipc_response(
+                        stream,
+                        &format!(
+                            "error: invalid layout-overlay argument `{other}` (expected on, off, or toggle)\n"
+                        ),
+                    );
+                    return;
+                }
+            };
+            state.set_layout_debug_overlay(enabled);
+            let status = if enabled { "on" } else { "off" };
+            write_ipc_response(stream, &format!("layout overlay: {status}\n"));
+        }
+        request if request.split_whitespace().next() == Some("performance-mode") => {
+            let argument = request.split_whitespace().nth(1).unwrap_or("toggle");
+            let enabled = match argument {
+                "on" => true,
+                "off" => false,
+                "toggle" => !state.performance_mode(),
+                other => {
+                    write_ipc_response(
+                        stream,
+                        &format!(
+                            "error: invalid performance-mode argument `{other}` (expected on, off, or toggle)\n"
+                        ),
+                    );
+                    return;
+                }
+            };
+            match state.set_performance_mode(enabled) {
+                Ok(()) => {
+                    let status = if enabled { "on" } else { "off" };
+                    write_ipc_response(stream, &format!("performance mode: {status}\n"));
+                }
+                Err(err) => write_ipc_response(stream, &format!("error: {err}\n")),
+            }
+        }
+        "reserve" => {
+            let output = super::reserve::render_reservations_report(state);
+            write_ipc_response(stream, &output);
+        }
+        request if request.split_whitespace().next() == Some("reserve") => {
+            let args: Vec<&str> = request.split_whitespace().skip(1).collect();
+            let [name, edge, size] = args[..] else {
+                write_ipc_response(
+                    stream,
+                    "error: usage: reserve <output> <top|bottom|left|right> <px>\n",
+                );
+                return;
+            };
+            let Some(edge) = ReserveEdge::parse(edge) else {
+                write_ipc_response(
+                    stream,
+                    &format!(
+                        "error: invalid edge `{edge}` (expected top, bottom, left, or right)\n"
+                    ),
+                );
+                return;
+            };
+            let Ok(size) = size.parse::<u32>() else {
+                write_ipc_response(stream, &format!("error: invalid size `{size}`\n"));
+                return;
+            };
+            match super::reserve::set_reservation(state, name, edge, size) {
+                Ok(()) => write_ipc_response(stream, "ok\n"),
+                Err(err) => write_ipc_response(stream, &format!("error: {err}\n")),
+            }
+        }
+        request if request.split_whitespace().next() == Some("unreserve") => {
+            let args: Vec<&str> = request.split_whitespace().skip(1).collect();
+            let (name, edge) = match args[..] {
+                [name] => (name, None),
+                [name, edge] => match ReserveEdge::parse(edge) {
+                    Some(edge) => (name, Some(edge)),
+                    None => {
+                        write_ipc_response(
+                            stream,
+                            &format!(
+                                "error: invalid edge `{edge}` (expected top, bottom, left, or right)\n"
+                            ),
+                        );
+                        return;
+                    }
+                },
+                _ => {
+                    write_ipc_response(
+                        stream,
+                        "error: usage: unreserve <output> [top|bottom|left|right]\n",
+                    );
+                    return;
+                }
+            };
+            match super::reserve::remove_reservations(state, name, edge) {
+                Ok(0) => {
+                    write_ipc_response(stream, &format!("error: nothing reserved on {name}\n"))
+                }
+                Ok(_) => write_ipc_response(stream, "ok\n"),
+                Err(err) => write_ipc_response(stream, &format!("error: {err}\n")),
+            }
+        }
+        "keymap" => write_ipc_response(stream, &state.keymap().describe()),
+        request if request.split_whitespace().next() == Some("keymap") => {
+            let args = request.trim_start_matches("keymap").trim();
+            match KeymapSettings::parse(args).and_then(|settings| state.set_keymap(settings)) {
+                Ok(()) => write_ipc_response(stream, "ok\n"),
+                Err(err) => write_ipc_response(stream, &format!("error: {err}\n")),
+            }
+        }
+        request if request.starts_with("screenshot --next-frame") => {
+            let args = request.trim_start_matches("screenshot --next-frame").trim();
+            let (target, path) = screenshot_args(args);
+            if matches!(target, "--region" | "--window") {
+                write_ipc_response(stream, "error: --next-frame only captures outputs\n");
+                return;
+            }
+            let armed = stream
+                .try_clone()
+                .map_err(CompositorError::from)
+                .and_then(|reply| {
+                    super::screenshot::arm_next_frame_screenshot(state, reply, target, path)
+                });
+            if let Err(err) = armed {
+                write_ipc_response(stream, &format!("error: {err}\n"));
+            }
+        }
+        request if request.split_whitespace().next() == Some("screenshot") => {
+            let args = request.trim_start_matches("screenshot").trim();
+            let (target, path) = screenshot_args(args);
+            if target == "--region" {
+                // Answered once the user finishes dragging out the region.
+                let started = stream
+                    .try_clone()
+                    .map_err(CompositorError::from)
+                    .and_then(|reply| {
+                        super::screenshot::start_region_selection(state, reply, path)
+                    });
+                if let Err(err) = started {
+                    write_ipc_response(stream, &format!("error: {err}\n"));
+                }
+                return;
+            }
+            // Answered from the thread that writes the file.
+            let saved = stream
+                .try_clone()
+                .map_err(CompositorError::from)
+                .and_then(|reply| {
+                    if target == "--window" {
+                        state.take_window_screenshot(path, reply)
+                    } else {
+                        state.take_screenshot(target, path, reply)
+                    }
+                });
+            if let Err(err) = saved {
+                write_ipc_response(stream, &format!("error: {err}\n"));
+            }
+        }
+        request if request.split_whitespace().next() == Some("output") => {
+            let mut words = request.split_whitespace().skip(1);
+            let (Some(name), Some(action), None) = (words.next(), words.next(), words.next())
+            else {
+                write_ipc_response(stream, "error: usage: output <name> pause|resume\n");
+                return;
+            };
+            let paused = match action {
+                "pause" => true,
+                "resume" => false,
+                other => {
+                    write_ipc_response(
+                        stream,
+                        &format!(
+                            "error: invalid output action `{other}` (expected pause or resume)\n"
+                        ),
+                    );
+                    return;
+                }
+            };
+            let result = crate::backend::udev::set_output_paused(state, name, paused);
+            state.report_progress("output", Some(name), ProgressStatus::from(&result));
+            match result {
+                Ok(()) => write_ipc_response(stream, "ok\n"),
+                Err(err) => write_ipc_response(stream, &format!("error: {err}\n")),
+            }
+        }
+        request if request.split_whitespace().next() == Some("dpms") => {
+            let mut words = request.split_whitespace().skip(1);
+            let (Some(mode), output, None) = (words.next(), words.next(), words.next()) else {
+                write_ipc_response(stream, "error: usage: dpms on|off|toggle [output]\n");
+                return;
+            };
+            let Some(mode) = crate::config::DpmsMode::parse(mode) else {
+                write_ipc_response(
+                    stream,
+                    &format!("error: invalid dpms mode `{mode}` (expected on, off or toggle)\n"),
+                );
+                return;
+            };
+            match state.set_dpms(output, mode) {
+                Ok(()) => write_ipc_response(stream, "ok\n"),
+                Err(err) => write_ipc_response(stream, &format!("error: {err}\n")),
+            }
+        }
+        request
+            if matches!(
+                request.split_whitespace().next(),
+                Some("workspace-swap" | "workspace-reorder")
+            ) =>
+        {
+            let (command, args) = request
+                .split_once(char::is_whitespace)
+                .unwrap_or((request, ""));
+            let result = crate::config::parse_workspace_pair(
+                args,
+                request,
+                command,
+                &state.config.workspace_names,
+            )
+            .and_then(|(first, second)| {
+                if command == "workspace-swap" {
+                    state.swap_workspaces(first, second)
+                } else {
+                    state.reorder_workspace(first, second)
+                }
+            });
+            match result {
+                Ok(()) => write_ipc_response(stream, "ok\n"),
+                Err(err) => write_ipc_response(stream, &format!("error: {err}\n")),
+            }
+        }
+        request if request.split_whitespace().next() == Some("workspace") => {
+            let target = request.trim_start_matches("workspace").trim();
+            let (name, args) = match target {
+                "prev" => ("workspace_prev", ""),
+                "next" => ("workspace_next", ""),
+                "back-and-forth" | "back_and_forth" => ("workspace_back_and_forth", ""),
+                _ => ("workspace", target),
+            };
+            match crate::config::parse_keybind_action(
+                name,
+                args,
+                request,
+                &state.config.workspace_names,
+            ) {
+                Ok(action) => {
+                    state.dispatch_action(action);
+                    write_ipc_response(stream, "ok\n");
+                }
+                Err(err) => write_ipc_response(stream, &format!("error: {err}\n")),
+            }
+        }
+        request if request.split_whitespace().next() == Some("dispatch") => {
+            let command = request.trim_start_matches("dispatch").trim();
+            let (name, args) = command
+                .split_once(char::is_whitespace)
+                .unwrap_or((command, ""));
+            if name.is_empty() {
+                write_ipc_response(
+                    stream,
+                    "error: usage: dispatch <action> [args] (run `raven actions` for the list)\n",
+                );
+                return;
+            }
+            match crate::config::parse_keybind_action(
+                name,
+                args,
+                command,
+                &state.config.workspace_names,
+            ) {
+                Ok(action) => {
+                    state.dispatch_action(action);
+                    write_ipc_response(stream, "ok\n");
+                }
+                Err(err) => write_ipc_response(stream, &format!("error: {err}\n")),
+            }
+        }
+        "resync" => {
+            state.resync_protocol_clients();
+            write_ipc_response(stream, "ok\n");
+        }
         "reload" => match state.reload_config() {
             Ok(()) => write_ipc_response(stream, "ok\n"),
             Err(err) => write_ipc_response(stream, &format!("error: {err}\n")),
@@ -40,48 +461,72 @@ pub(super) fn handle_ipc_stream(state: &mut Raven, stream: &mut UnixStream) {
         "" => {
             write_ipc_response(
                 stream,
-                "error: empty command (supported: clients, monitors, reload)\n",
+                "error: empty command (supported: clients, monitors, workspaces, focused, pager, stats, idle, night-light, session, version, actions, get, layout-overlay, performance-mode, keymap, screenshot, output, dpms, reserve, unreserve, workspace, workspace-swap, workspace-reorder, dispatch, resync, reload)\n",
             );
         }
         other => {
             write_ipc_response(
                 stream,
                 &format!(
-                    "error: unsupported command `{other}` (supported: clients, monitors, reload)\n"
+                    "error: unsupported command `{other}` (supported: clients, monitors, workspaces, focused, pager, stats, idle, night-light, session, version, actions, get, layout-overlay, performance-mode, keymap, screenshot, output, dpms, reserve, unreserve, workspace, workspace-swap, workspace-reorder, dispatch, resync, reload)\n"
                 ),
             );
         }
     }
 }
 
-pub(super) fn render_clients_report(state: &Raven) -> String {
+/// Push a fresh clients report to `clients --watch` connections whose view is stale.
+/// Watchers that can no longer be written to are dropped.
+pub(super) fn refresh_clients_watchers(state: &mut Raven) {
+    if state.clients_watchers.is_empty() {
+        return;
+    }
+
+    let report = render_clients_report(state);
+    state.clients_watchers.retain_mut(|watcher| {
+        if watcher.last_report == report {
+            return true;
+        }
+        if let Err(err) = write_watch_frame(&mut watcher.stream, &report) {
+            tracing::debug!("dropping clients watcher: {err}");
+            return false;
+        }
+        watcher.last_report.clone_from(&report);
+        true
+    });
+}
+
+/// One row of `raven clients`: every toplevel, mapped or not, in workspace order.
+struct ClientEntry {
+    id: Option<u64>,
+    app_id: Option<String>,
+    title: Option<String>,
+    // 1-based.
+    workspace: usize,
+    rect: Option<Rectangle<i32, Logical>>,
+    mapped: bool,
+    floating: bool,
+    fullscreen: bool,
+    focused: bool,
+    urgent: bool,
+}
+
+fn client_entries(state: &Raven) -> Vec<ClientEntry> {
     let focused_surface = state
         .seat
         .get_keyboard()
         .and_then(|keyboard| keyboard.current_focus());
 
     let mut seen_surfaces = HashSet::new();
-    let mut windows = Vec::new();
+    let mut entries = Vec::new();
     for window in state.workspace_windows().chain(state.space.elements()) {
-        let Some(toplevel) = window.toplevel() else {
-            continue;
-        };
-        let surface = toplevel.wl_surface();
-        if seen_surfaces.insert(surface.clone()) {
-            windows.push(window.clone());
-        }
-    }
-
-    if windows.is_empty() {
-        return "No clients.\n".to_owned();
-    }
-
-    let mut out = String::new();
-    for (index, window) in windows.iter().enumerate() {
         let Some(toplevel) = window.toplevel() else {
             continue;
         };
         let wl_surface = toplevel.wl_surface().clone();
+        if !seen_surfaces.insert(wl_surface.clone()) {
+            continue;
+        }
 
         let (app_id, title) = with_states(&wl_surface, |states| {
             let role = states
@@ -93,40 +538,269 @@ pub(super) fn render_clients_report(state: &Raven) -> String {
             (role.app_id.clone(), role.title.clone())
         });
 
-        let workspace = state
-            .workspace_index_for_window(window)
-            .map(|idx| idx + 1)
-            .unwrap_or(state.current_workspace + 1);
-
-        let class = app_id.as_deref().unwrap_or("<unknown>");
-        let title = title.as_deref().unwrap_or("<untitled>");
-        let focused = focused_surface.as_ref() == Some(&wl_surface);
         let mapped = state.is_window_mapped(window);
-        let floating = state.is_window_floating(window);
-        let fullscreen = state.window_effective_fullscreen_state(window);
-        let surface_id = format!("{:?}", wl_surface.id());
+        entries.push(ClientEntry {
+            id: state.window_id(&wl_surface),
+            app_id,
+            title,
+            workspace: state
+                .workspace_index_for_window(window)
+                .map(|idx| idx + 1)
+                .unwrap_or(state.current_workspace + 1),
+            rect: mapped
+                .then(|| state.space.element_geometry(window))
+                .flatten(),
+            mapped,
+            floating: state.is_window_floating(window),
+            fullscreen: state.window_effective_fullscreen_state(window),
+            focused: focused_surface.as_ref() == Some(&wl_surface),
+            urgent: state.is_window_urgent(&wl_surface),
+        });
+    }
+    entries
+}
 
+fn format_client_entry(out: &mut String, entry: &ClientEntry) {
+    let id = entry
+        .id
+        .map_or_else(|| "<none>".to_owned(), |id| id.to_string());
+    out.push_str(&format!("  id: {id}\n"));
+    out.push_str(&format!(
+        "  class: {}\n",
+        entry.app_id.as_deref().unwrap_or("<unknown>")
+    ));
+    out.push_str(&format!(
+        "  title: {}\n",
+        entry.title.as_deref().unwrap_or("<untitled>")
+    ));
+    out.push_str(&format!("  workspace: {}\n", entry.workspace));
+    out.push_str(&format!("  mapped: {}\n", entry.mapped));
+    out.push_str(&format!("  floating: {}\n", entry.floating));
+    out.push_str(&format!("  fullscreen: {}\n", entry.fullscreen));
+    out.push_str(&format!("  focused: {}\n", entry.focused));
+    out.push_str(&format!("  urgent: {}\n", entry.urgent));
+}
+
+fn client_entry_json(entry: &ClientEntry) -> String {
+    format!(
+        r#"{{"id":{},"app_id":{},"title":{},"workspace":{},"rect":{},"mapped":{},"floating":{},"fullscreen":{},"focused":{},"urgent":{}}}"#,
+        entry
+            .id
+            .map_or_else(|| "null".to_owned(), |id| id.to_string()),
+        json_optional_string(entry.app_id.as_deref()),
+        json_optional_string(entry.title.as_deref()),
+        entry.workspace,
+        entry.rect.map_or_else(|| "null".to_owned(), rect_json),
+        entry.mapped,
+        entry.floating,
+        entry.fullscreen,
+        entry.focused,
+        entry.urgent
+    )
+}
+
+pub(super) fn render_clients_report(state: &Raven) -> String {
+    let entries = client_entries(state);
+    if entries.is_empty() {
+        return "No clients.\n".to_owned();
+    }
+
+    let mut out = String::new();
+    for (index, entry) in entries.iter().enumerate() {
         out.push_str(&format!("Client {}:\n", index + 1));
-        out.push_str(&format!("  surface: {surface_id}\n"));
-        out.push_str(&format!("  class: {class}\n"));
-        out.push_str(&format!("  title: {title}\n"));
-        out.push_str(&format!("  workspace: {workspace}\n"));
-        out.push_str(&format!("  mapped: {mapped}\n"));
-        out.push_str(&format!("  floating: {floating}\n"));
-        out.push_str(&format!("  fullscreen: {fullscreen}\n"));
-        out.push_str(&format!("  focused: {focused}\n"));
+        format_client_entry(&mut out, entry);
         out.push('\n');
     }
 
+    out.push_str(&super::surface_sweep::render_sweep_report(state));
     out
 }
 
-pub(super) fn render_monitors_report(state: &Raven) -> String {
-    let mut outputs: Vec<_> = state.space.outputs().cloned().collect();
-    if outputs.is_empty() {
-        return "No monitors.\n".to_owned();
+/// `clients --json`: the same fields as `render_clients_report`, for bars.
+pub(super) fn render_clients_json(state: &Raven) -> String {
+    let clients: Vec<String> = client_entries(state)
+        .iter()
+        .map(client_entry_json)
+        .collect();
+    format!("[{}]\n", clients.join(","))
+}
+
+pub(super) fn render_focused_report(state: &Raven) -> String {
+    match client_entries(state).iter().find(|entry| entry.focused) {
+        Some(entry) => {
+            let mut out = "Focused client:\n".to_owned();
+            format_client_entry(&mut out, entry);
+            out
+        }
+        None => "No focused client.\n".to_owned(),
+    }
+}
+
+/// `focused --json`: the focused client as in `clients --json`, or `null`.
+pub(super) fn render_focused_json(state: &Raven) -> String {
+    let focused = client_entries(state)
+        .iter()
+        .find(|entry| entry.focused)
+        .map_or_else(|| "null".to_owned(), client_entry_json);
+    format!("{focused}\n")
+}
+
+/// One row of `raven workspaces`: the current workspace and every occupied one.
+struct WorkspaceEntry {
+    // 1-based.
+    num: usize,
+    name: Option<String>,
+    active: bool,
+    windows: usize,
+    focused_window: Option<u64>,
+    layout: &'static str,
+}
+
+fn workspace_entries(state: &Raven) -> Vec<WorkspaceEntry> {
+    let focused = client_entries(state)
+        .into_iter()
+        .find(|entry| entry.focused);
+    listed_workspaces(state)
+        .into_iter()
+        .map(|index| WorkspaceEntry {
+            num: index + 1,
+            name: state.config.workspace_names.get(&index).cloned(),
+            active: index == state.current_workspace,
+            windows: state.workspaces[index].len(),
+            focused_window: focused
+                .as_ref()
+                .filter(|entry| entry.workspace == index + 1)
+                .and_then(|entry| entry.id),
+            layout: state.workspace_layouts[index].layout_type.name(),
+        })
+        .collect()
+}
+
+pub(super) fn render_workspaces_report(state: &Raven) -> String {
+    let mut out = String::new();
+    for entry in workspace_entries(state) {
+        out.push_str(&format!(
+            "Workspace {}{}{}: {} window{}, {}",
+            entry.num,
+            entry
+                .name
+                .as_ref()
+                .map(|name| format!(" \"{name}\""))
+                .unwrap_or_default(),
+            if entry.active { " (active)" } else { "" },
+            entry.windows,
+            if entry.windows == 1 { "" } else { "s" },
+            entry.layout
+        ));
+        if let Some(id) = entry.focused_window {
+            out.push_str(&format!(", focused {id}"));
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// `workspaces --json`: the same fields as `render_workspaces_report`.
+pub(super) fn render_workspaces_json(state: &Raven) -> String {
+    let workspaces: Vec<String> = workspace_entries(state)
+        .iter()
+        .map(|entry| {
+            format!(
+                r#"{{"num":{},"name":{},"active":{},"windows":{},"focused_window":{},"layout":{}}}"#,
+                entry.num,
+                json_optional_string(entry.name.as_deref()),
+                entry.active,
+                entry.windows,
+                entry
+                    .focused_window
+                    .map_or_else(|| "null".to_owned(), |id| id.to_string()),
+                json_string(entry.layout)
+            )
+        })
+        .collect();
+    format!("[{}]\n", workspaces.join(","))
+}
+
+pub(super) fn render_client_latency_report(state: &Raven) -> String {
+    let mut histograms = crate::backend::udev::commit_latency_histograms(state);
+    if histograms.is_empty() {
+        return "No commit timing recorded.\n".to_owned();
+    }
+    histograms.sort_by_key(|(_, histogram)| std::cmp::Reverse(histogram.average()));
+
+    let mut out = String::new();
+    for (index, (surface, histogram)) in histograms.iter().enumerate() {
+        let (app_id, title) = with_states(surface, |states| {
+            states
+                .data_map
+                .get::<XdgToplevelSurfaceData>()
+                .and_then(|data| data.lock().ok())
+                .map(|role| (role.app_id.clone(), role.title.clone()))
+                .unwrap_or_default()
+        });
+
+        out.push_str(&format!("Client {}:\n", index + 1));
+        out.push_str(&format!(
+            "  id: {}\n",
+            state
+                .window_id(surface)
+                .map_or_else(|| "<none>".to_owned(), |id| id.to_string())
+        ));
+        out.push_str(&format!(
+            "  class: {}\n",
+            app_id.as_deref().unwrap_or("<unknown>")
+        ));
+        out.push_str(&format!(
+            "  title: {}\n",
+            title.as_deref().unwrap_or("<untitled>")
+        ));
+        out.push_str(&format!("  frames: {}\n", histogram.count));
+        out.push_str(&format!(
+            "  commit to present: avg {:.1}ms, max {:.1}ms\n",
+            histogram.average().as_secs_f64() * 1000.0,
+            histogram.max.as_secs_f64() * 1000.0
+        ));
+        for (bound, count) in BUCKET_BOUNDS.iter().zip(&histogram.buckets) {
+            out.push_str(&format!("  <={}ms: {count}\n", bound.as_millis()));
+        }
+        out.push_str(&format!(
+            "  >{}ms: {}\n",
+            BUCKET_BOUNDS[BUCKET_BOUNDS.len() - 1].as_millis(),
+            histogram.buckets[BUCKET_BOUNDS.len()]
+        ));
+        out.push('\n');
     }
 
+    out
+}
+
+/// One row of `raven monitors`: mapped outputs in position order, then paused ones.
+struct MonitorEntry {
+    output: Output,
+    geometry: Option<Rectangle<i32, Logical>>,
+    paused: bool,
+    // Blanked by DPMS while still in the layout.
+    powered: bool,
+    // Adaptive sync, on while fullscreen video or games are shown.
+    vrr: bool,
+    // 0-based; only the primary output shows workspaces.
+    active_workspace: Option<usize>,
+    workspaces: Vec<usize>,
+    scanout: Option<String>,
+    // Last error while rendering is disabled after repeated failures.
+    render_error: Option<String>,
+}
+
+fn monitor_entries(state: &Raven) -> Vec<MonitorEntry> {
+    let primary = state.space.outputs().next().cloned();
+    let render_failures = crate::backend::udev::render_failures(state);
+    let render_error = |output: &Output| {
+        render_failures
+            .iter()
+            .find(|(failed, _)| failed == output)
+            .map(|(_, error)| error.clone())
+    };
+    let mut outputs: Vec<_> = state.space.outputs().cloned().collect();
     outputs.sort_by_key(|output| {
         state
             .space
@@ -135,8 +809,54 @@ pub(super) fn render_monitors_report(state: &Raven) -> String {
             .unwrap_or((i32::MAX, i32::MAX))
     });
 
+    let mut entries: Vec<MonitorEntry> = outputs
+        .into_iter()
+        .map(|output| {
+            let is_primary = primary.as_ref() == Some(&output);
+            MonitorEntry {
+                geometry: state.space.output_geometry(&output),
+                paused: false,
+                powered: crate::backend::udev::output_powered(state, &output),
+                vrr: crate::backend::udev::output_vrr(state, &output),
+                active_workspace: is_primary.then_some(state.current_workspace),
+                workspaces: if is_primary {
+                    listed_workspaces(state)
+                } else {
+                    Vec::new()
+                },
+                scanout: crate::backend::udev::scanout_status(state, &output),
+                render_error: render_error(&output),
+                output,
+            }
+        })
+        .collect();
+    entries.extend(
+        crate::backend::udev::paused_outputs(state)
+            .into_iter()
+            .map(|output| MonitorEntry {
+                output,
+                geometry: None,
+                paused: true,
+                powered: false,
+                vrr: false,
+                active_workspace: None,
+                workspaces: Vec::new(),
+                scanout: None,
+                render_error: None,
+            }),
+    );
+    entries
+}
+
+pub(super) fn render_monitors_report(state: &Raven) -> String {
+    let entries = monitor_entries(state);
+    if entries.is_empty() {
+        return "No monitors.\n".to_owned();
+    }
+
     let mut out = String::new();
-    for (index, output) in outputs.iter().enumerate() {
+    for (index, entry) in entries.iter().enumerate() {
+        let output = &entry.output;
         out.push_str(&format!("Monitor {}:\n", index + 1));
         out.push_str(&format!("  name: {}\n", output.name()));
 
@@ -155,7 +875,7 @@ pub(super) fn render_monitors_report(state: &Raven) -> String {
             out.push_str("  mode: <unknown>\n");
         }
 
-        if let Some(geo) = state.space.output_geometry(output) {
+        if let Some(geo) = entry.geometry {
             out.push_str(&format!("  position: {}, {}\n", geo.loc.x, geo.loc.y));
             out.push_str(&format!("  logical_size: {}x{}\n", geo.size.w, geo.size.h));
         } else {
@@ -167,8 +887,167 @@ pub(super) fn render_monitors_report(state: &Raven) -> String {
             "  scale: {:.3}\n",
             output.current_scale().fractional_scale()
         ));
+
+        match entry.active_workspace {
+            Some(workspace) => out.push_str(&format!("  active_workspace: {}\n", workspace + 1)),
+            None => out.push_str("  active_workspace: none\n"),
+        }
+        let workspaces: Vec<String> = entry
+            .workspaces
+            .iter()
+            .map(|workspace| (workspace + 1).to_string())
+            .collect();
+        if workspaces.is_empty() {
+            out.push_str("  workspaces: none\n");
+        } else {
+            out.push_str(&format!("  workspaces: {}\n", workspaces.join(", ")));
+        }
+
+        out.push_str(&format!(
+            "  dpms: {}\n",
+            if entry.powered { "on" } else { "off" }
+        ));
+        out.push_str(&format!(
+            "  vrr: {}\n",
+            if entry.vrr { "on" } else { "off" }
+        ));
+        out.push_str(&format!(
+            "  scanout: {}\n",
+            entry.scanout.as_deref().unwrap_or("n/a")
+        ));
+        match &entry.render_error {
+            Some(error) => out.push_str(&format!("  render: disabled ({error})\n")),
+            None => out.push_str("  render: ok\n"),
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+/// `monitors --json`: the same fields as `render_monitors_report`, for bars.
+pub(super) fn render_monitors_json(state: &Raven) -> String {
+    let monitors: Vec<String> = monitor_entries(state)
+        .iter()
+        .map(|entry| {
+            let output = &entry.output;
+            let mode = output.current_mode().map_or_else(
+                || "null".to_owned(),
+                |mode| {
+                    format!(
+                        r#"{{"width":{},"height":{},"refresh":{:.3}}}"#,
+                        mode.size.w,
+                        mode.size.h,
+                        mode.refresh as f64 / 1000.0
+                    )
+                },
+            );
+            let (position, logical_size) = entry.geometry.map_or_else(
+                || ("null".to_owned(), "null".to_owned()),
+                |geo| {
+                    (
+                        format!(r#"{{"x":{},"y":{}}}"#, geo.loc.x, geo.loc.y),
+                        format!(r#"{{"width":{},"height":{}}}"#, geo.size.w, geo.size.h),
+                    )
+                },
+            );
+            let active_workspace = entry
+                .active_workspace
+                .map_or_else(|| "null".to_owned(), |workspace| (workspace + 1).to_string());
+            let workspaces: Vec<String> = entry
+                .workspaces
+                .iter()
+                .map(|workspace| (workspace + 1).to_string())
+                .collect();
+            let scanout = entry
+                .scanout
+                .as_deref()
+                .map_or_else(|| "null".to_owned(), json_string);
+            let render_error = entry
+                .render_error
+                .as_deref()
+                .map_or_else(|| "null".to_owned(), json_string);
+            format!(
+                r#"{{"name":{},"mode":{mode},"position":{position},"logical_size":{logical_size},"scale":{:.3},"active_workspace":{active_workspace},"workspaces":[{}],"dpms":{},"vrr":{},"scanout":{scanout},"render_error":{render_error}}}"#,
+                json_string(&output.name()),
+                output.current_scale().fractional_scale(),
+                workspaces.join(","),
+                entry.powered,
+                entry.vrr,
+            )
+        })
+        .collect();
+    format!("[{}]\n", monitors.join(","))
+}
+
+/// Every keybind action with its arguments and aliases.
+pub(super) fn render_actions_report() -> String {
+    let width = crate::config::KEYBIND_ACTIONS
+        .iter()
+        .map(|action| action.name.len() + action.args.len() + 1)
+        .max()
+        .unwrap_or(0);
+
+    let mut out = String::new();
+    for action in crate::config::KEYBIND_ACTIONS {
+        let usage = format!("{} {}", action.name, action.args);
+        if action.aliases.is_empty() {
+            out.push_str(&format!("{}\n", usage.trim_end()));
+        } else {
+            out.push_str(&format!(
+                "{usage:<width$}  (aliases: {})\n",
+                action.aliases.join(", ")
+            ));
+        }
+    }
+    out
+}
+
+/// What is running, for bug reports; also logged once the backend is up.
+pub(super) fn render_version_report(state: &Raven) -> String {
+    // Every feature in Cargo.toml, default ones included.
+    let features: Vec<&str> = [
+        ("screencast", cfg!(feature = "screencast")),
+        ("xwayland", cfg!(feature = "xwayland")),
+        ("test-capture", cfg!(feature = "test-capture")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect();
+    let backend = state.backend_kind.as_str();
+
+    let features = if features.is_empty() {
+        "none".to_owned()
+    } else {
+        features.join(", ")
+    };
+    let primary_gpu =
+        crate::backend::udev::primary_gpu_description(state).unwrap_or_else(|| "none".to_owned());
+
+    let mut out = format!("Raven {}\n", env!("CARGO_PKG_VERSION"));
+    out.push_str(&format!("  features: {features}\n"));
+    out.push_str(&format!("  backend: {backend}\n"));
+    out.push_str(&format!("  primary gpu: {primary_gpu}\n"));
+    out
+}
+
+pub(super) fn render_stats_report(state: &Raven) -> String {
+    let counts = crate::backend::udev::redraw_cause_counts(state);
+    let mut out = String::new();
+    if counts.is_empty() {
+        out.push_str("No redraw stats.\n\n");
+    }
+    for group in counts.chunk_by(|left, right| left.0 == right.0) {
+        let total: u64 = group.iter().map(|(_, _, count)| count).sum();
+
+        out.push_str(&format!("Output {}:\n", group[0].0));
+        out.push_str(&format!("  redraws: {total}\n"));
+        for (_, cause, count) in group {
+            out.push_str(&format!("  {}: {count}\n", cause.as_str()));
+        }
         out.push('\n');
     }
 
+    out.push_str(&super::buffer_budget::render_buffer_report(state));
     out
 }
@@ -0,0 +1,113 @@
+use smithay::input::keyboard::XkbConfig;
+
+use crate::CompositorError;
+
+use super::{Raven, sway_ipc};
+
+/// The xkb settings last applied to the seat keyboard. Empty fields fall back to
+/// libxkbcommon's defaults (`XKB_DEFAULT_*`).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KeymapSettings {
+    pub layout: String,
+    pub variant: String,
+    pub model: String,
+    pub options: Option<String>,
+}
+
+impl KeymapSettings {
+    /// Parse `<layout> [variant=<v>] [model=<m>] [options=<o>]`.
+    pub fn parse(args: &str) -> Result<Self, CompositorError> {
+        let mut words = args.split_whitespace();
+        let Some(layout) = words.next() else {
+            return Err(CompositorError::Backend(
+                "keymap requires a layout (e.g. `keymap us,de options=grp:alt_shift_toggle`)"
+                    .to_owned(),
+            ));
+        };
+
+        let mut settings = Self {
+            layout: layout.to_owned(),
+            ..Self::default()
+        };
+        for word in words {
+            match word.split_once('=') {
+                Some(("variant", value)) => settings.variant = value.to_owned(),
+                Some(("model", value)) => settings.model = value.to_owned(),
+                Some(("options", value)) => {
+                    settings.options = (!value.is_empty()).then(|| value.to_owned());
+                }
+                _ => {
+                    return Err(CompositorError::Backend(format!(
+                        "invalid keymap argument `{word}` (expected variant=, model=, or options=)"
+                    )));
+                }
+            }
+        }
+        Ok(settings)
+    }
+
+    /// Layout names in group order, as listed in `layout`.
+    pub fn layout_names(&self) -> Vec<&str> {
+        self.layout
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .collect()
+    }
+
+    pub fn describe(&self) -> String {
+        let mut out = format!(
+            "layout: {}\n",
+            if self.layout.is_empty() {
+                "<default>"
+            } else {
+                &self.layout
+            }
+        );
+        if !self.variant.is_empty() {
+            out.push_str(&format!("variant: {}\n", self.variant));
+        }
+        if !self.model.is_empty() {
+            out.push_str(&format!("model: {}\n", self.model));
+        }
+        if let Some(options) = self.options.as_deref() {
+            out.push_str(&format!("options: {options}\n"));
+        }
+        out
+    }
+}
+
+pub(super) fn set_keymap(
+    state: &mut Raven,
+    settings: KeymapSettings,
+) -> Result<(), CompositorError> {
+    let keyboard = state
+        .seat
+        .get_keyboard()
+        .ok_or_else(|| CompositorError::Backend("seat has no keyboard".to_owned()))?;
+
+    let xkb_config = XkbConfig {
+        layout: &settings.layout,
+        variant: &settings.variant,
+        model: &settings.model,
+        options: settings.options.clone(),
+        ..XkbConfig::default()
+    };
+    keyboard.set_xkb_config(state, xkb_config).map_err(|err| {
+        CompositorError::Backend(format!(
+            "failed to compile keymap `{}`: {err:?}",
+            settings.layout
+        ))
+    })?;
+
+    tracing::info!(
+        layout = %settings.layout,
+        variant = %settings.variant,
+        model = %settings.model,
+        options = settings.options.as_deref().unwrap_or(""),
+        "keymap switched"
+    );
+    state.keymap = settings;
+    sway_ipc::broadcast_input_event(state, "xkb_keymap");
+    Ok(())
+}
@@ -0,0 +1,178 @@
+//! Layout preview on reload: when a reloaded config changes gaps or the
+//! master area, outline the tiled slots the new settings produce for
+//! `reload_layout_preview` ms before windows move into them.
+
+use std::time::Duration;
+
+use smithay::{
+    backend::renderer::element::Kind,
+    desktop::Window,
+    output::Output,
+    reexports::calloop::{
+        RegistrationToken,
+        timer::{TimeoutAction, Timer},
+    },
+    utils::{Logical, Point, Rectangle},
+};
+
+use crate::{
+    config::RuntimeConfig,
+    layout::LayoutType,
+    render_helpers::{SolidColorBuffer, SolidColorRenderElement},
+};
+
+use super::Raven;
+
+const OUTLINE_THICKNESS: i32 = 3;
+const OUTLINE_COLOR: [f32; 4] = [0.2, 0.7, 1.0, 0.9];
+
+/// The config values that decide where tiled windows go.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct LayoutSettings {
+    layout_type: LayoutType,
+    gaps: [u32; 4],
+    master_factor: f32,
+    num_master: i32,
+    smart_gaps: bool,
+}
+
+impl LayoutSettings {
+    pub(super) fn of(config: &RuntimeConfig) -> Self {
+        Self {
+            layout_type: config.layout_type,
+            gaps: [
+                config.gaps_outer_horizontal,
+                config.gaps_outer_vertical,
+                config.gaps_inner_horizontal,
+                config.gaps_inner_vertical,
+            ],
+            master_factor: config.master_factor,
+            num_master: config.num_master,
+            smart_gaps: config.smart_gaps,
+        }
+    }
+}
+
+pub(super) struct LayoutPreview {
+    slots: Vec<Rectangle<i32, Logical>>,
+    timer: RegistrationToken,
+}
+
+/// Tiled slots of the current workspace under the current config.
+fn preview_slots(state: &Raven) -> Vec<Rectangle<i32, Logical>> {
+    let Some(output) = state.space.outputs().next().cloned() else {
+        return Vec::new();
+    };
+    let Some(layout_geo) = state.output_work_area(&output) else {
+        return Vec::new();
+    };
+
+    let tiled_windows: Vec<Window> = state.workspaces[state.current_workspace]
+        .iter()
+        .filter(|window| !state.is_window_floating(window))
+        .filter(|window| !state.window_is_unmapped_toplevel(window))
+        .filter(|window| Raven::window_has_live_client(window))
+        .cloned()
+        .collect();
+    if tiled_windows.is_empty() {
+        return Vec::new();
+    }
+
+    state
+        .arrange_tiled(&tiled_windows, layout_geo.size)
+        .into_iter()
+        .map(|geom| {
+            Rectangle::new(
+                (
+                    layout_geo.loc.x + geom.x_coordinate,
+                    layout_geo.loc.y + geom.y_coordinate,
+                )
+                    .into(),
+                (geom.width as i32, geom.height as i32).into(),
+            )
+        })
+        .collect()
+}
+
+/// Show the new slots and hold the layout back until the preview ends. Returns
+/// false when there is nothing to preview and the layout should be applied now.
+pub(super) fn start(state: &mut Raven) -> bool {
+    cancel(state);
+
+    let delay = state.config.reload_layout_preview_ms;
+    if delay == 0 {
+        return false;
+    }
+    let slots = preview_slots(state);
+    if slots.is_empty() {
+        return false;
+    }
+
+    let timer = Timer::from_duration(Duration::from_millis(u64::from(delay)));
+    let timer = match state.loop_handle.insert_source(timer, |_, _, state| {
+        state.layout_preview = None;
+        if let Err(err) = state.apply_layout() {
+            tracing::warn!("failed to apply layout after reload preview: {err}");
+        }
+        crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Animation);
+        TimeoutAction::Drop
+    }) {
+        Ok(token) => token,
+        Err(err) => {
+            tracing::warn!("failed to schedule reload layout preview: {err}");
+            return false;
+        }
+    };
+
+    tracing::debug!(slots = slots.len(), "previewing reloaded layout");
+    state.layout_preview = Some(LayoutPreview { slots, timer });
+    crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Config);
+    true
+}
+
+fn cancel(state: &mut Raven) {
+    if let Some(preview) = state.layout_preview.take() {
+        state.loop_handle.remove(preview.timer);
+    }
+}
+
+/// Outlines of the previewed slots on `output`, relative to the output origin.
+pub(super) fn layout_preview_elements(
+    state: &Raven,
+    output: &Output,
+) -> Vec<SolidColorRenderElement> {
+    let Some(preview) = state.layout_preview.as_ref() else {
+        return Vec::new();
+    };
+    let Some(output_geo) = state.space.output_geometry(output) else {
+        return Vec::new();
+    };
+
+    let mut elements = Vec::new();
+    for rect in preview
+        .slots
+        .iter()
+        .filter(|rect| output_geo.overlaps(**rect))
+    {
+        let origin = rect.loc - output_geo.loc;
+        let thickness = OUTLINE_THICKNESS.min(rect.size.w).min(rect.size.h);
+        let far_x = rect.size.w - thickness;
+        let far_y = rect.size.h - thickness;
+        let edges: [(Point<i32, Logical>, (i32, i32)); 4] = [
+            (origin, (rect.size.w, thickness)),
+            (origin + Point::from((0, far_y)), (rect.size.w, thickness)),
+            (origin, (thickness, rect.size.h)),
+            (origin + Point::from((far_x, 0)), (thickness, rect.size.h)),
+        ];
+        for (location, (w, h)) in edges {
+            let buffer = SolidColorBuffer::new((f64::from(w), f64::from(h)), OUTLINE_COLOR);
+            elements.push(SolidColorRenderElement::from_buffer(
+                &buffer,
+                location.to_f64(),
+                1.0,
+                Kind::Unspecified,
+            ));
+        }
+    }
+    elements
+}
@@ -0,0 +1,57 @@
+//! Minimized windows. A minimized window stays in its workspace but is left
+//! out of the space, so it takes no tile and gets no focus until a taskbar
+//! (wlr-foreign-toplevel) activates or unminimizes it.
+
+use smithay::desktop::Window;
+
+use super::Raven;
+
+/// Hide `window` from the layout, keeping its place in the workspace.
+pub(super) fn minimize(state: &mut Raven, window: &Window) {
+    let Some(surface) = Raven::window_surface_id(window) else {
+        return;
+    };
+    if !state.minimized_windows.insert(surface.clone()) {
+        return;
+    }
+    tracing::debug!(window = ?state.window_id(&surface), "window minimized");
+
+    if state.is_window_mapped(window) {
+        state.unmap_window(window);
+        if let Err(err) = state.apply_layout() {
+            tracing::warn!("failed to apply layout after minimize: {err}");
+        }
+        state.refocus_visible_window();
+    }
+}
+
+/// Put a minimized `window` back into the layout of its workspace.
+pub(super) fn unminimize(state: &mut Raven, window: &Window) {
+    let Some(surface) = Raven::window_surface_id(window) else {
+        return;
+    };
+    if !state.minimized_windows.remove(&surface) {
+        return;
+    }
+    tracing::debug!(window = ?state.window_id(&surface), "window unminimized");
+
+    if state.workspace_contains_window(state.current_workspace, window)
+        && state.map_window_to_initial_location_if_mappable(window, false)
+        && let Err(err) = state.apply_layout()
+    {
+        tracing::warn!("failed to apply layout after unminimize: {err}");
+    }
+}
+
+/// The `minimize_focused` action.
+pub(super) fn minimize_focused(state: &mut Raven) {
+    let Some(window) = state
+        .seat
+        .get_keyboard()
+        .and_then(|keyboard| keyboard.current_focus())
+        .and_then(|surface| state.window_for_surface(&surface))
+    else {
+        return;
+    };
+    minimize(state, &window);
+}
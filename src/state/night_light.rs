@@ -0,0 +1,179 @@
+//! Built-in night light. The `night_light` table warms every output by scaling
+//! its gamma ramp toward the color of a `temperature` kelvin blackbody, always or
+//! inside `schedule`. `raven night-light on|off|toggle` overrides the config
+//! until the schedule next flips. Gamma control clients such as gammastep win
+//! over it on the outputs they control.
+
+use std::time::Duration;
+
+use smithay::{
+    output::Output,
+    reexports::calloop::{
+        RegistrationToken,
+        timer::{TimeoutAction, Timer},
+    },
+};
+
+use super::Raven;
+
+// How often a scheduled night light checks the clock.
+const SCHEDULE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+pub(super) struct NightLight {
+    // Set by `raven night-light`; cleared when the schedule flips.
+    forced: Option<bool>,
+    // What the config and schedule asked for at the last check.
+    scheduled: bool,
+    // Temperature the outputs are warmed to.
+    applied: Option<u32>,
+    schedule_timer: Option<RegistrationToken>,
+}
+
+/// Red, green and blue of a blackbody at `kelvin`, from 0 to 1. Tanner
+/// Helland's fit; 6500 K comes out close to white.
+fn whitepoint(kelvin: u32) -> [f64; 3] {
+    let temperature = f64::from(kelvin.clamp(1000, 10000)) / 100.0;
+    let red = if temperature <= 66.0 {
+        255.0
+    } else {
+        329.698_727_446 * (temperature - 60.0).powf(-0.133_204_759_2)
+    };
+    let green = if temperature <= 66.0 {
+        99.470_802_586_1 * temperature.ln() - 161.119_568_166_1
+    } else {
+        288.122_169_528_3 * (temperature - 60.0).powf(-0.075_514_849_2)
+    };
+    let blue = if temperature >= 66.0 {
+        255.0
+    } else if temperature <= 19.0 {
+        0.0
+    } else {
+        138.517_731_223_1 * (temperature - 10.0).ln() - 305.044_792_730_7
+    };
+    [red, green, blue].map(|channel| (channel / 255.0).clamp(0.0, 1.0))
+}
+
+fn minutes_since_midnight() -> Option<u32> {
+    // SAFETY: `localtime_r` only writes the `tm` it is handed.
+    let tm = unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&now, &mut tm).is_null() {
+            return None;
+        }
+        tm
+    };
+    u32::try_from(tm.tm_hour * 60 + tm.tm_min).ok()
+}
+
+fn in_schedule((start, end): (u32, u32), now: u32) -> bool {
+    if start <= end {
+        (start..end).contains(&now)
+    } else {
+        now >= start || now < end
+    }
+}
+
+fn scheduled(state: &Raven) -> bool {
+    let config = state.config.night_light;
+    config.enabled
+        && config.schedule.is_none_or(|schedule| {
+            minutes_since_midnight().is_some_and(|now| in_schedule(schedule, now))
+        })
+}
+
+/// Whether the night light is on right now.
+pub(super) fn active(state: &Raven) -> bool {
+    state
+        .night_light
+        .forced
+        .unwrap_or(state.night_light.scheduled)
+}
+
+/// Load the night light onto `outputs`, or take it off them.
+fn apply(state: &mut Raven, outputs: &[Output], temperature: Option<u32>) {
+    for output in outputs {
+        if let Err(err) =
+            crate::backend::udev::set_night_light(state, output, temperature.map(whitepoint))
+        {
+            tracing::warn!(output = %output.name(), "failed to apply night light: {err}");
+        }
+    }
+}
+
+/// Load the night light as it should be now onto every output that is not.
+/// Call on startup, reload and from the schedule timer.
+pub(super) fn refresh(state: &mut Raven) {
+    let scheduled = scheduled(state);
+    if scheduled != state.night_light.scheduled {
+        state.night_light.scheduled = scheduled;
+        state.night_light.forced = None;
+    }
+    let wanted = active(state).then_some(state.config.night_light.temperature);
+    if wanted != state.night_light.applied {
+        tracing::info!(temperature = ?wanted, "night light changed");
+        let outputs: Vec<Output> = state.space.outputs().cloned().collect();
+        apply(state, &outputs, wanted);
+        state.night_light.applied = wanted;
+    }
+    sync_schedule_timer(state);
+}
+
+/// Warm newly connected outputs too.
+pub(super) fn outputs_changed(state: &mut Raven) {
+    if let Some(temperature) = state.night_light.applied {
+        let outputs: Vec<Output> = state.space.outputs().cloned().collect();
+        apply(state, &outputs, Some(temperature));
+    }
+}
+
+/// Turn the night light on or off until the schedule next flips; `None` toggles.
+pub(super) fn set(state: &mut Raven, on: Option<bool>) {
+    let on = on.unwrap_or_else(|| !active(state));
+    state.night_light.forced = Some(on);
+    refresh(state);
+}
+
+fn sync_schedule_timer(state: &mut Raven) {
+    let config = state.config.night_light;
+    let wanted = config.enabled && config.schedule.is_some();
+    if wanted == state.night_light.schedule_timer.is_some() {
+        return;
+    }
+    if let Some(timer) = state.night_light.schedule_timer.take() {
+        state.loop_handle.remove(timer);
+        return;
+    }
+
+    let timer = Timer::from_duration(SCHEDULE_CHECK_INTERVAL);
+    match state.loop_handle.insert_source(timer, |_, _, state| {
+        refresh(state);
+        TimeoutAction::ToDuration(SCHEDULE_CHECK_INTERVAL)
+    }) {
+        Ok(timer) => state.night_light.schedule_timer = Some(timer),
+        Err(err) => tracing::warn!("failed to schedule night light timer: {err}"),
+    }
+}
+
+/// The `raven night-light` report.
+pub(super) fn render_night_light_report(state: &Raven) -> String {
+    let config = state.config.night_light;
+    let mut out = match state.night_light.applied {
+        Some(temperature) => format!("Night light: on ({temperature}K)\n"),
+        None => "Night light: off\n".to_owned(),
+    };
+    if let Some((start, end)) = config.schedule.filter(|_| config.enabled) {
+        out.push_str(&format!(
+            "  schedule: {:02}:{:02}-{:02}:{:02}\n",
+            start / 60,
+            start % 60,
+            end / 60,
+            end % 60
+        ));
+    }
+    if state.night_light.forced.is_some() {
+        out.push_str("  overridden until the schedule flips\n");
+    }
+    out
+}
@@ -0,0 +1,166 @@
+//! `raven pager`: a snapshot of every workspace with the rectangles of its
+//! windows, for pager applets that want to draw miniature layouts. ext-workspace
+//! only carries grid coordinates, so this is the fallback for window geometry.
+
+use smithay::{
+    desktop::Window,
+    utils::{Logical, Rectangle},
+};
+
+use super::{
+    Raven,
+    sway_ipc::{json_optional_string, json_string, rect_json},
+};
+
+struct PagerWindow {
+    id: Option<u64>,
+    app_id: Option<String>,
+    floating: bool,
+    focused: bool,
+    // Relative to the output origin; windows that never had a slot have none.
+    rect: Option<Rectangle<i32, Logical>>,
+}
+
+struct PagerWorkspace {
+    index: usize,
+    coordinates: [u32; 2],
+    active: bool,
+    windows: Vec<PagerWindow>,
+}
+
+/// Where `window` is drawn: its place in the space on the current workspace, its
+/// last assigned slot on the others.
+fn window_rect(
+    state: &Raven,
+    window: &Window,
+    origin: Rectangle<i32, Logical>,
+) -> Option<Rectangle<i32, Logical>> {
+    let mut rect = state
+        .space
+        .element_geometry(window)
+        .or_else(|| state.assigned_rect_for_window(window))?;
+    rect.loc -= origin.loc;
+    Some(rect)
+}
+
+fn pager_workspaces(state: &Raven, origin: Rectangle<i32, Logical>) -> Vec<PagerWorkspace> {
+    let focused_surface = state
+        .seat
+        .get_keyboard()
+        .and_then(|keyboard| keyboard.current_focus());
+    let columns = state.config.workspace_grid_columns;
+
+    (0..state.workspaces.len())
+        .map(|index| PagerWorkspace {
+            index,
+            coordinates: crate::protocols::ext_workspace::workspace_coordinates(index, columns),
+            active: index == state.current_workspace,
+            windows: state.workspaces[index]
+                .iter()
+                .filter_map(|window| {
+                    let surface = window.toplevel()?.wl_surface().clone();
+                    Some(PagerWindow {
+                        id: state.window_id(&surface),
+                        app_id: Raven::surface_app_id_and_title(&surface).0,
+                        floating: state.is_window_floating(window),
+                        focused: focused_surface.as_ref() == Some(&surface),
+                        rect: window_rect(state, window, origin),
+                    })
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn primary_output_geometry(state: &Raven) -> Option<(String, Rectangle<i32, Logical>)> {
+    let output = state.space.outputs().next()?;
+    Some((output.name(), state.space.output_geometry(output)?))
+}
+
+pub(super) fn render_pager_report(state: &Raven) -> String {
+    let Some((output_name, origin)) = primary_output_geometry(state) else {
+        return "No monitors.\n".to_owned();
+    };
+
+    let mut out = format!(
+        "Output {output_name}: {}x{}\n",
+        origin.size.w, origin.size.h
+    );
+    for workspace in pager_workspaces(state, origin) {
+        let [column, row] = workspace.coordinates;
+        out.push_str(&format!(
+            "\nWorkspace {} (column {column}, row {row}){}:\n",
+            workspace.index + 1,
+            if workspace.active { ", active" } else { "" }
+        ));
+        if workspace.windows.is_empty() {
+            out.push_str("  no windows\n");
+        }
+        for window in &workspace.windows {
+            let id = window
+                .id
+                .map_or_else(|| "<none>".to_owned(), |id| id.to_string());
+            let rect = window.rect.map_or_else(
+                || "<unplaced>".to_owned(),
+                |rect| {
+                    format!(
+                        "{}x{}+{}+{}",
+                        rect.size.w, rect.size.h, rect.loc.x, rect.loc.y
+                    )
+                },
+            );
+            out.push_str(&format!(
+                "  {id} {}: {rect}{}{}\n",
+                window.app_id.as_deref().unwrap_or("<unknown>"),
+                if window.floating { " floating" } else { "" },
+                if window.focused { " focused" } else { "" }
+            ));
+        }
+    }
+    out
+}
+
+/// `pager --json`: the same snapshot as `render_pager_report`.
+pub(super) fn render_pager_json(state: &Raven) -> String {
+    let Some((output_name, origin)) = primary_output_geometry(state) else {
+        return "null\n".to_owned();
+    };
+
+    let workspaces: Vec<String> = pager_workspaces(state, origin)
+        .iter()
+        .map(|workspace| {
+            let windows: Vec<String> = workspace
+                .windows
+                .iter()
+                .map(|window| {
+                    format!(
+                        r#"{{"id":{},"app_id":{},"floating":{},"focused":{},"rect":{}}}"#,
+                        window
+                            .id
+                            .map_or_else(|| "null".to_owned(), |id| id.to_string()),
+                        json_optional_string(window.app_id.as_deref()),
+                        window.floating,
+                        window.focused,
+                        window.rect.map_or_else(|| "null".to_owned(), rect_json)
+                    )
+                })
+                .collect();
+            format!(
+                r#"{{"num":{},"coordinates":[{},{}],"active":{},"windows":[{}]}}"#,
+                workspace.index + 1,
+                workspace.coordinates[0],
+                workspace.coordinates[1],
+                workspace.active,
+                windows.join(",")
+            )
+        })
+        .collect();
+    let snapshot = format!(
+        r#"{{"output":{},"size":{{"width":{},"height":{}}},"workspaces":[{}]}}"#,
+        json_string(&output_name),
+        origin.size.w,
+        origin.size.h,
+        workspaces.join(",")
+    );
+    format!("{snapshot}\n")
+}
@@ -0,0 +1,73 @@
+//! Progress events for operations that can take a while: config reloads, output
+//! changes and wallpaper applies. They go to sway IPC clients subscribed to the
+//! Raven-specific `progress` event, so frontends see success or failure as soon
+//! as it is known instead of polling.
+
+use smithay::reexports::calloop::channel::{self, Sender};
+
+use super::{
+    Raven,
+    sway_ipc::{self, json_optional_string, json_string},
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProgressStatus {
+    Started,
+    Succeeded,
+    Failed(String),
+}
+
+impl ProgressStatus {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Started => "started",
+            Self::Succeeded => "succeeded",
+            Self::Failed(_) => "failed",
+        }
+    }
+}
+
+impl<E: std::fmt::Display> From<&Result<(), E>> for ProgressStatus {
+    fn from(result: &Result<(), E>) -> Self {
+        match result {
+            Ok(()) => Self::Succeeded,
+            Err(err) => Self::Failed(err.to_string()),
+        }
+    }
+}
+
+/// `target` names what the operation acts on (a config path, an output name, an
+/// image), when there is one.
+pub(super) fn report(state: &mut Raven, task: &str, target: Option<&str>, status: ProgressStatus) {
+    tracing::debug!(task, target, status = status.as_str(), "progress");
+    let error = match &status {
+        ProgressStatus::Failed(error) => Some(error.as_str()),
+        _ => None,
+    };
+    let event = format!(
+        r#"{{"task":{},"target":{},"status":{},"error":{}}}"#,
+        json_string(task),
+        json_optional_string(target),
+        json_string(status.as_str()),
+        json_optional_string(error)
+    );
+    sway_ipc::broadcast_progress_event(state, &event);
+}
+
+/// Channel the wallpaper thread reports its result on; results are turned into
+/// `wallpaper` progress events on the main loop.
+pub(super) fn wallpaper_channel(state: &Raven) -> Option<Sender<(String, ProgressStatus)>> {
+    let (sender, receiver) = channel::channel();
+    if let Err(err) = state
+        .loop_handle
+        .insert_source(receiver, |event, _, state| {
+            if let channel::Event::Msg((image, status)) = event {
+                report(state, "wallpaper", Some(&image), status);
+            }
+        })
+    {
+        tracing::warn!("failed to register wallpaper progress channel: {err}");
+        return None;
+    }
+    Some(sender)
+}
@@ -0,0 +1,166 @@
+//! Manual struts (`raven reserve`) for bars and docks that do not use
+//! layer-shell exclusive zones.
+
+use smithay::{
+    output::Output,
+    utils::{Logical, Rectangle},
+};
+
+use crate::CompositorError;
+
+use super::Raven;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum ReserveEdge {
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+impl ReserveEdge {
+    pub fn parse(raw: &str) -> Option<Self> {
+        match raw.trim().to_ascii_lowercase().as_str() {
+            "top" => Some(Self::Top),
+            "bottom" => Some(Self::Bottom),
+            "left" => Some(Self::Left),
+            "right" => Some(Self::Right),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Top => "top",
+            Self::Bottom => "bottom",
+            Self::Left => "left",
+            Self::Right => "right",
+        }
+    }
+}
+
+/// Space kept free along one edge of an output, keyed by output name so it
+/// survives the output being unplugged and plugged back in.
+pub(super) struct Reservation {
+    output: String,
+    edge: ReserveEdge,
+    size: u32,
+}
+
+/// Add or replace the reservation on `edge` of `output_name`. A size of 0 removes it.
+pub(super) fn set_reservation(
+    state: &mut Raven,
+    output_name: &str,
+    edge: ReserveEdge,
+    size: u32,
+) -> Result<(), CompositorError> {
+    if !state
+        .space
+        .outputs()
+        .any(|output| output.name() == output_name)
+    {
+        return Err(CompositorError::Backend(format!(
+            "unknown output `{output_name}`"
+        )));
+    }
+
+    state
+        .reservations
+        .retain(|reservation| reservation.output != output_name || reservation.edge != edge);
+    if size > 0 {
+        state.reservations.push(Reservation {
+            output: output_name.to_owned(),
+            edge,
+            size,
+        });
+    }
+    tracing::info!(
+        output = output_name,
+        edge = edge.as_str(),
+        size,
+        "screen space reserved"
+    );
+
+    state.apply_layout()?;
+    crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Ipc);
+    Ok(())
+}
+
+/// Drop the reservations on `output_name` (only `edge` when given); returns how many went.
+pub(super) fn remove_reservations(
+    state: &mut Raven,
+    output_name: &str,
+    edge: Option<ReserveEdge>,
+) -> Result<usize, CompositorError> {
+    let before = state.reservations.len();
+    state.reservations.retain(|reservation| {
+        reservation.output != output_name || edge.is_some_and(|edge| reservation.edge != edge)
+    });
+    let removed = before - state.reservations.len();
+    if removed > 0 {
+        tracing::info!(
+            output = output_name,
+            removed,
+            "screen space reservations removed"
+        );
+        state.apply_layout()?;
+        crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Ipc);
+    }
+    Ok(removed)
+}
+
+/// Shrink `area` (an output's work area) by that output's reservations. Each edge
+/// keeps at least one pixel of the area so the layout never sees an empty rect.
+pub(super) fn shrink_by_reservations(
+    state: &Raven,
+    output: &Output,
+    mut area: Rectangle<i32, Logical>,
+) -> Rectangle<i32, Logical> {
+    let name = output.name();
+    for reservation in state
+        .reservations
+        .iter()
+        .filter(|reservation| reservation.output == name)
+    {
+        let size = i32::try_from(reservation.size).unwrap_or(i32::MAX);
+        match reservation.edge {
+            ReserveEdge::Top | ReserveEdge::Bottom => {
+                let size = size.min(area.size.h - 1).max(0);
+                if reservation.edge == ReserveEdge::Top {
+                    area.loc.y += size;
+                }
+                area.size.h -= size;
+            }
+            ReserveEdge::Left | ReserveEdge::Right => {
+                let size = size.min(area.size.w - 1).max(0);
+                if reservation.edge == ReserveEdge::Left {
+                    area.loc.x += size;
+                }
+                area.size.w -= size;
+            }
+        }
+    }
+    area
+}
+
+pub(super) fn render_reservations_report(state: &Raven) -> String {
+    if state.reservations.is_empty() {
+        return "No reserved screen space.\n".to_owned();
+    }
+
+    let mut out = String::new();
+    for reservation in &state.reservations {
+        let connected = state
+            .space
+            .outputs()
+            .any(|output| output.name() == reservation.output);
+        out.push_str(&format!(
+            "{} {} {}px{}\n",
+            reservation.output,
+            reservation.edge.as_str(),
+            reservation.size,
+            if connected { "" } else { " (disconnected)" }
+        ));
+    }
+    out
+}
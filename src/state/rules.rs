@@ -1,21 +1,31 @@
+use std::time::Duration;
+
 use smithay::{
     backend::renderer::utils::RendererSurfaceStateUserData,
     desktop::Window,
     reexports::{
+        calloop::timer::{TimeoutAction, Timer},
         wayland_protocols::xdg::{
             decoration::zv1::server::zxdg_toplevel_decoration_v1::Mode as XdgDecorationMode,
             shell::server::xdg_toplevel,
         },
         wayland_server::{Resource, protocol::wl_surface::WlSurface},
     },
-    utils::SERIAL_COUNTER,
-    wayland::compositor::with_states,
+    utils::{Logical, SERIAL_COUNTER, Size},
+    wayland::{compositor::with_states, shell::xdg::XdgToplevelSurfaceData},
 };
 
-use crate::{config::WindowRule, state::NewWindowRuleDecision};
+use crate::{
+    config::{Margins, RulePosition, WindowRule, WindowType},
+    state::{NewWindowRuleDecision, WindowRenderRules},
+};
 
 use super::Raven;
 
+// How long an auto-floated toplevel may wait for size hints or a real-size buffer
+// before it is mapped anyway.
+const FLOATING_FIRST_MAP_HOLD: Duration = Duration::from_millis(150);
+
 pub(super) fn queue_window_rule_recheck_for_surface(state: &mut Raven, surface: &WlSurface) {
     if should_defer_window_rules_for_surface(state, surface) {
         state
@@ -73,13 +83,90 @@ pub(super) fn queue_initial_configure_idle_for_surface(state: &mut Raven, surfac
     });
 }
 
+fn floating_first_map_is_placeable(surface: &WlSurface, window: &Window) -> bool {
+    let (min_size, _) = Raven::surface_min_max_size(surface);
+    let has_size_hints = min_size.w > 0 && min_size.h > 0;
+    let size = window.geometry().size;
+    has_size_hints || (size.w > 1 && size.h > 1)
+}
+
+/// Whether the first map of `window` should wait for another commit.
+///
+/// Auto-floated dialogs are centered from their size at map time. Clients that
+/// attach a placeholder buffer first and only then commit size hints would
+/// otherwise appear at the wrong spot and jump once recentered. The hold is
+/// bounded by `FLOATING_FIRST_MAP_HOLD`; the timer maps the window regardless.
+pub(super) fn hold_floating_first_map(
+    state: &mut Raven,
+    surface: &WlSurface,
+    window: &Window,
+) -> bool {
+    let (_, has_explicit_floating_rule, auto_floating, _) =
+        state.resolve_effective_floating_for_surface(surface, window, false);
+    if has_explicit_floating_rule
+        || !auto_floating
+        || floating_first_map_is_placeable(surface, window)
+    {
+        state.held_floating_map_ids.remove(surface);
+        return false;
+    }
+    if !state.held_floating_map_ids.insert(surface.clone()) {
+        return true;
+    }
+
+    let surface_id = surface.clone();
+    let timer = Timer::from_duration(FLOATING_FIRST_MAP_HOLD);
+    if let Err(err) = state.loop_handle.insert_source(timer, move |_, _, state| {
+        release_held_floating_map(state, &surface_id);
+        TimeoutAction::Drop
+    }) {
+        tracing::warn!("failed to schedule floating map hold timeout: {err}");
+        state.held_floating_map_ids.remove(surface);
+        return false;
+    }
+    tracing::debug!(window = ?state.window_id(surface), "holding first map of auto-floated window");
+    true
+}
+
+fn release_held_floating_map(state: &mut Raven, surface: &WlSurface) {
+    if !state.held_floating_map_ids.remove(surface) || !surface.is_alive() {
+        return;
+    }
+    if !state.unmapped_toplevel_ids.contains(surface) {
+        return;
+    }
+    let Some(window) = state.window_for_surface(surface) else {
+        return;
+    };
+    tracing::debug!(window = ?state.window_id(surface), "floating map hold timed out; mapping anyway");
+
+    // Same transition the root commit path performs once a real buffer is attached.
+    state.clear_surface_unmapped_toplevel(surface);
+    state.clear_initial_configure_for_surface(surface);
+    state.promote_window_to_mapped_workspace(&window);
+    maybe_apply_deferred_window_rules(state, surface);
+    if state.workspace_contains_window(state.current_workspace, &window) {
+        state.map_window_to_initial_location(&window, false);
+        if let Err(err) = state.apply_layout() {
+            tracing::warn!("failed to apply layout after floating map hold: {err}");
+        }
+    }
+    maybe_recenter_floating_window_after_commit(state, surface);
+    state.debug_assert_state_invariants("floating_map_hold_timeout");
+    crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Commit);
+}
+
 pub(super) fn should_defer_window_rules_for_surface(state: &Raven, surface: &WlSurface) -> bool {
     let (app_id, title) = Raven::surface_app_id_and_title(surface);
     if state.has_window_rule_metadata_gap(app_id.as_deref(), title.as_deref()) {
         return true;
     }
 
-    !state.has_matching_explicit_floating_rule(app_id.as_deref(), title.as_deref())
+    !state.has_matching_explicit_floating_rule(
+        app_id.as_deref(),
+        title.as_deref(),
+        surface_window_type(state, surface),
+    )
 }
 
 pub(super) fn resolve_window_rules_for_surface(
@@ -87,33 +174,278 @@ pub(super) fn resolve_window_rules_for_surface(
     surface: &WlSurface,
 ) -> NewWindowRuleDecision {
     let (app_id, title) = Raven::surface_app_id_and_title(surface);
+    let window_type = surface_window_type(state, surface);
 
     let mut decision = NewWindowRuleDecision {
         workspace_index: state.current_workspace,
         floating: false,
         fullscreen: false,
         focus: true,
+        sticky: false,
         width: None,
         height: None,
     };
+    // Percentage sizes are taken of the work area the window opens on.
+    let work_area = state
+        .window_for_surface(surface)
+        .and_then(|window| state.floating_output_for_window(&window))
+        .and_then(|output| state.output_work_area(&output))
+        .map(|work_area| work_area.size);
 
     for rule in &state.config.window_rules {
-        if !rule.matches(app_id.as_deref(), title.as_deref()) {
+        if !rule.matches(app_id.as_deref(), title.as_deref(), window_type) {
             continue;
         }
-        apply_window_rule_to_decision(rule, &mut decision);
+        apply_window_rule_to_decision(rule, &mut decision, work_area);
+    }
+    if let Some(&workspace_index) = state.autostart_workspace_by_surface.get(surface) {
+        decision.workspace_index = workspace_index;
+    }
+    if let Some(entry) = super::session::entry_for_surface(state, surface) {
+        decision.floating = entry.floating;
+        if entry.floating {
+            decision.width = entry.width.or(decision.width);
+            decision.height = entry.height.or(decision.height);
+        }
     }
 
     decision
 }
 
-fn apply_window_rule_to_decision(rule: &WindowRule, decision: &mut NewWindowRuleDecision) {
+pub(super) fn surface_window_type(state: &Raven, surface: &WlSurface) -> WindowType {
+    if state.modal_dialog_ids.contains(surface) {
+        return WindowType::Modal;
+    }
+    #[cfg(feature = "xwayland")]
+    if let Some(window_type) = x11_window_type(state, surface) {
+        return window_type;
+    }
+    let has_parent = with_states(surface, |states| {
+        states
+            .data_map
+            .get::<XdgToplevelSurfaceData>()
+            .and_then(|data| data.lock().ok().map(|role| role.parent.is_some()))
+            .unwrap_or(false)
+    });
+    // xdg-shell has no hint for palettes or toolboxes, so every parented
+    // toplevel is a dialog, resizable or not.
+    if has_parent {
+        return WindowType::Dialog;
+    }
+    if Raven::fixed_hint_size_for_surface(surface).is_some() {
+        WindowType::Splash
+    } else {
+        WindowType::Normal
+    }
+}
+
+/// The type an X11 window declares in `_NET_WM_WINDOW_TYPE`. Windows without
+/// one are dialogs when transient for another window.
+#[cfg(feature = "xwayland")]
+fn x11_window_type(state: &Raven, surface: &WlSurface) -> Option<WindowType> {
+    use smithay::xwayland::xwm::WmWindowType;
+
+    let x11 = state.workspace_windows().find_map(|window| {
+        window
+            .x11_surface()
+            .filter(|x11| x11.wl_surface().as_ref() == Some(surface))
+            .cloned()
+    })?;
+    let transient = x11.is_transient_for().is_some();
+    Some(match x11.window_type() {
+        Some(WmWindowType::Dialog) => WindowType::Dialog,
+        Some(WmWindowType::Splash) => WindowType::Splash,
+        Some(
+            WmWindowType::Utility
+            | WmWindowType::Toolbar
+            | WmWindowType::Menu
+            | WmWindowType::DropdownMenu
+            | WmWindowType::PopupMenu
+            | WmWindowType::Tooltip
+            | WmWindowType::Notification,
+        ) => WindowType::Utility,
+        Some(WmWindowType::Normal) | None if transient => WindowType::Dialog,
+        Some(WmWindowType::Normal) | None => WindowType::Normal,
+    })
+}
+
+pub(super) fn surface_ignores_focus_follows_mouse(state: &Raven, surface: &WlSurface) -> bool {
+    let (app_id, title) = Raven::surface_app_id_and_title(surface);
+    let window_type = surface_window_type(state, surface);
+
+    state
+        .config
+        .window_rules
+        .iter()
+        .filter(|rule| rule.matches(app_id.as_deref(), title.as_deref(), window_type))
+        .filter_map(|rule| rule.no_focus_follows_mouse)
+        .last()
+        .unwrap_or(false)
+}
+
+/// `exclusive_input` of the last matching window rule that sets it.
+pub(super) fn surface_exclusive_input(state: &Raven, surface: &WlSurface) -> Option<bool> {
+    let (app_id, title) = Raven::surface_app_id_and_title(surface);
+    let window_type = surface_window_type(state, surface);
+
+    state
+        .config
+        .window_rules
+        .iter()
+        .filter(|rule| rule.matches(app_id.as_deref(), title.as_deref(), window_type))
+        .filter_map(|rule| rule.exclusive_input)
+        .last()
+}
+
+/// Workspace a close request moves the window to, from the last matching rule that
+/// sets `close_to_workspace` or `hide_on_close`. `hide_on_close` alone parks the
+/// window on the last workspace.
+pub(super) fn surface_close_target(state: &Raven, surface: &WlSurface) -> Option<usize> {
+    let (app_id, title) = Raven::surface_app_id_and_title(surface);
+    let window_type = surface_window_type(state, surface);
+
+    state
+        .config
+        .window_rules
+        .iter()
+        .filter(|rule| rule.matches(app_id.as_deref(), title.as_deref(), window_type))
+        .fold(None, |target, rule| {
+            match (rule.close_to_workspace, rule.hide_on_close) {
+                (Some(workspace), _) => Some(workspace),
+                (None, Some(true)) => target.or(Some(state.config.workspace_count - 1)),
+                (None, Some(false)) => None,
+                (None, None) => target,
+            }
+        })
+}
+
+/// `on_open` of the last matching window rule that sets it.
+pub(super) fn surface_on_open<'a>(state: &'a Raven, surface: &WlSurface) -> Option<&'a str> {
+    let (app_id, title) = Raven::surface_app_id_and_title(surface);
+    let window_type = surface_window_type(state, surface);
+
+    state
+        .config
+        .window_rules
+        .iter()
+        .filter(|rule| rule.matches(app_id.as_deref(), title.as_deref(), window_type))
+        .filter_map(|rule| rule.on_open.as_deref())
+        .last()
+}
+
+/// Output named by the last matching window rule with an `output`.
+pub(super) fn surface_rule_output(state: &Raven, surface: &WlSurface) -> Option<String> {
+    let (app_id, title) = Raven::surface_app_id_and_title(surface);
+    let window_type = surface_window_type(state, surface);
+
+    state
+        .config
+        .window_rules
+        .iter()
+        .filter(|rule| rule.matches(app_id.as_deref(), title.as_deref(), window_type))
+        .filter_map(|rule| rule.output.clone())
+        .last()
+}
+
+/// Placement of the last matching window rule with a position.
+pub(super) fn surface_rule_position(state: &Raven, surface: &WlSurface) -> Option<RulePosition> {
+    let (app_id, title) = Raven::surface_app_id_and_title(surface);
+    let window_type = surface_window_type(state, surface);
+
+    state
+        .config
+        .window_rules
+        .iter()
+        .filter(|rule| rule.matches(app_id.as_deref(), title.as_deref(), window_type))
+        .filter_map(|rule| rule.position)
+        .last()
+}
+
+/// Maximize margins of the last matching window rule that sets them.
+pub(super) fn surface_maximize_margins(state: &Raven, surface: &WlSurface) -> Option<Margins> {
+    let (app_id, title) = Raven::surface_app_id_and_title(surface);
+    let window_type = surface_window_type(state, surface);
+
+    state
+        .config
+        .window_rules
+        .iter()
+        .filter(|rule| rule.matches(app_id.as_deref(), title.as_deref(), window_type))
+        .filter_map(|rule| rule.maximize_margins)
+        .last()
+}
+
+fn surface_render_rules(state: &Raven, surface: &WlSurface) -> WindowRenderRules {
+    let (app_id, title) = Raven::surface_app_id_and_title(surface);
+    let window_type = surface_window_type(state, surface);
+
+    let mut render_rules = WindowRenderRules::default();
+    for rule in state
+        .config
+        .window_rules
+        .iter()
+        .filter(|rule| rule.matches(app_id.as_deref(), title.as_deref(), window_type))
+    {
+        if let Some(opacity) = rule.opacity {
+            render_rules.opacity = Some(opacity);
+        }
+        if let Some(border_color) = rule.border_color {
+            render_rules.border_color = Some(border_color);
+        }
+        if let Some(no_gaps) = rule.no_gaps {
+            render_rules.no_gaps = no_gaps;
+        }
+        if let Some(corner_radius) = rule.corner_radius {
+            render_rules.corner_radius = Some(corner_radius);
+        }
+        if let Some(max_fps) = rule.max_fps {
+            render_rules.max_fps = Some(max_fps);
+        }
+    }
+    render_rules
+}
+
+/// Re-match the opacity, border, gap, corner and frame rate rules for `surface`;
+/// only windows some rule applies to keep an entry.
+pub(super) fn refresh_render_rules(state: &mut Raven, surface: &WlSurface) {
+    let render_rules = surface_render_rules(state, surface);
+    if render_rules == WindowRenderRules::default() {
+        state.window_render_rules.remove(surface);
+    } else {
+        state
+            .window_render_rules
+            .insert(surface.clone(), render_rules);
+    }
+}
+
+/// Re-match render rules for every window, after the rules changed.
+pub(super) fn refresh_all_render_rules(state: &mut Raven) {
+    let surfaces: Vec<WlSurface> = state
+        .workspace_windows()
+        .filter_map(Raven::window_surface_id)
+        .collect();
+    state.window_render_rules.clear();
+    for surface in surfaces {
+        refresh_render_rules(state, &surface);
+    }
+}
+
+fn apply_window_rule_to_decision(
+    rule: &WindowRule,
+    decision: &mut NewWindowRuleDecision,
+    work_area: Option<Size<i32, Logical>>,
+) {
     if let Some(workspace_index) = rule.workspace {
         decision.workspace_index = workspace_index;
     }
     if let Some(floating) = rule.floating {
         decision.floating = floating;
     }
+    // Sticky windows float on top of whatever workspace is shown.
+    if let Some(sticky) = rule.sticky {
+        decision.sticky = sticky;
+        decision.floating |= sticky;
+    }
     if let Some(fullscreen) = rule.fullscreen {
         decision.fullscreen = fullscreen;
     }
@@ -121,10 +453,14 @@ fn apply_window_rule_to_decision(rule: &WindowRule, decision: &mut NewWindowRule
         decision.focus = focus;
     }
     if let Some(width) = rule.width {
-        decision.width = Some(width);
+        decision.width = width
+            .resolve(work_area.map(|size| size.w))
+            .or(decision.width);
     }
     if let Some(height) = rule.height {
-        decision.height = Some(height);
+        decision.height = height
+            .resolve(work_area.map(|size| size.h))
+            .or(decision.height);
     }
 }
 
@@ -168,6 +504,10 @@ pub(super) fn send_initial_configure_for_surface(state: &mut Raven, surface: &Wl
     }
 
     state.set_window_floating(&window, decision.floating && !window_has_exclusive_state);
+    if decision.sticky {
+        state.set_window_sticky(&window, true);
+    }
+    refresh_render_rules(state, surface);
 
     let Some(toplevel) = window.toplevel() else {
         return;
@@ -315,6 +655,10 @@ pub(super) fn maybe_apply_deferred_window_rules(state: &mut Raven, surface: &WlS
 
     let was_floating = state.is_window_floating(&window);
     state.set_window_floating(&window, decision.floating && !window_has_exclusive_state);
+    if decision.sticky {
+        state.set_window_sticky(&window, true);
+    }
+    refresh_render_rules(state, surface);
     let on_current_workspace = state.workspace_contains_window(state.current_workspace, &window);
     let tiled_slot = if on_current_workspace
         && !decision.floating
@@ -358,7 +702,10 @@ pub(super) fn maybe_apply_deferred_window_rules(state: &mut Raven, surface: &WlS
         tracing::warn!("failed to apply layout after deferred rule resolution: {err}");
     }
 
-    if decision.focus && decision.workspace_index == state.current_workspace {
+    if decision.focus
+        && decision.workspace_index == state.current_workspace
+        && state.may_focus_new_window(&window)
+    {
         state.set_keyboard_focus(Some(surface.clone()), SERIAL_COUNTER.next_serial());
     }
 
@@ -1,14 +1,52 @@
-use std::process::Command;
+use std::{
+    collections::HashSet,
+    process::Command,
+    time::{Duration, Instant},
+};
 
-use smithay::reexports::wayland_protocols::xdg::decoration::zv1::server::zxdg_toplevel_decoration_v1::Mode as XdgDecorationMode;
+use smithay::reexports::{
+    wayland_protocols::xdg::decoration::zv1::server::zxdg_toplevel_decoration_v1::Mode as XdgDecorationMode,
+    wayland_server::{Resource, protocol::wl_surface::WlSurface},
+};
 
-use crate::{CompositorError, config};
+use crate::{
+    CompositorError,
+    backend::udev::RedrawCause,
+    config::{self, AutostartEntry},
+    layout::WorkspaceLayout,
+};
 
-use super::Raven;
+use super::{
+    ProgressStatus, Raven,
+    layout_preview::{self, LayoutSettings},
+};
 
 pub(super) fn spawn_command(state: &Raven, command: &str) {
+    spawn_command_with_env(state, command, &[]);
+}
+
+// How long an autostart command may take to open its first window before its
+// workspace binding is dropped.
+pub(super) const AUTOSTART_CLAIM_TIMEOUT: Duration = Duration::from_secs(60);
+// Launchers and wrapper scripts rarely nest deeper than this.
+const AUTOSTART_MAX_ANCESTRY_DEPTH: usize = 8;
+
+/// A spawned autostart command still waiting for its first toplevel.
+pub(super) struct AutostartClaim {
+    pid: u32,
+    workspace: usize,
+    spawned_at: Instant,
+    // Index into the session entries when the command came from `session`.
+    session_entry: Option<usize>,
+}
+
+pub(super) fn spawn_command_with_env(state: &Raven, command: &str, env: &[(&str, String)]) {
+    spawn_child(state, command, env);
+}
+
+pub(super) fn spawn_child(state: &Raven, command: &str, env: &[(&str, String)]) -> Option<u32> {
     if command.trim().is_empty() {
-        return;
+        return None;
     }
 
     let command = state.apply_no_csd_spawn_overrides(command);
@@ -16,9 +54,14 @@ pub(super) fn spawn_command(state: &Raven, command: &str) {
     let mut cmd = Command::new("sh");
     cmd.arg("-c").arg(&command);
     state.apply_wayland_child_env(&mut cmd);
+    cmd.envs(env.iter().map(|(key, value)| (*key, value)));
 
-    if let Err(err) = cmd.spawn() {
-        tracing::warn!(command = %command, "failed to spawn command: {err}");
+    match cmd.spawn() {
+        Ok(child) => Some(child.id()),
+        Err(err) => {
+            tracing::warn!(command = %command, "failed to spawn command: {err}");
+            None
+        }
     }
 }
 
@@ -35,9 +78,14 @@ pub(super) fn run_startup_tasks(state: &mut Raven) {
     state.maintain_xwayland_satellite();
     state.kick_portal_services_async();
     run_autostart_commands(state);
+    super::session::start_session(state);
+    super::surface_sweep::start(state);
     state.ensure_waypaper_swww_daemon();
+    super::idle::sync(state);
+    super::night_light::refresh(state);
+    super::wallpaper_slideshow::sync(state);
     state.apply_wallpaper();
-    crate::backend::udev::queue_redraw_all(state);
+    crate::backend::udev::queue_redraw_all(state, RedrawCause::Startup);
 }
 
 pub(super) fn preferred_decoration_mode(state: &Raven) -> XdgDecorationMode {
@@ -71,16 +119,218 @@ pub(super) fn run_autostart_commands(state: &mut Raven) {
     }
     state.autostart_started = true;
 
-    for command in &state.config.autostart {
-        tracing::info!(command, "starting autostart command");
-        state.spawn_command(command);
+    let entries = state.config.autostart.clone();
+    let mut seen = HashSet::new();
+    for entry in entries {
+        if !seen.insert(entry.command.trim().to_owned()) {
+            tracing::warn!(command = %entry.command, "duplicate autostart command skipped");
+            continue;
+        }
+        start_autostart_entry(state, &entry);
+    }
+}
+
+fn start_autostart_entry(state: &mut Raven, entry: &AutostartEntry) {
+    tracing::info!(
+        command = %entry.command,
+        workspace = entry.workspace.map(|index| index + 1),
+        "starting autostart command"
+    );
+    let pid = spawn_child(state, &entry.command, &[]);
+    if let Some(pid) = pid {
+        state
+            .autostart_pids
+            .insert(entry.command.trim().to_owned(), pid);
+    }
+    if let (Some(pid), Some(workspace)) = (pid, entry.workspace) {
+        track_first_window(state, pid, workspace, None);
+    }
+}
+
+fn process_alive(pid: u32) -> bool {
+    // Exited children linger as zombies until reaped; those count as gone.
+    std::fs::read_to_string(format!("/proc/{pid}/stat"))
+        .ok()
+        .and_then(|stat| {
+            let (_, fields) = stat.rsplit_once(')')?;
+            fields.split_whitespace().next().map(|state| state != "Z")
+        })
+        .unwrap_or(false)
+}
+
+/// Start the autostart entries a reload added. Commands that are still running
+/// or were already in the previous config are skipped, so a reload never
+/// launches a second copy of anything.
+fn start_new_autostart_entries(state: &mut Raven, previous: &[AutostartEntry]) {
+    if !state.autostart_started {
+        // Startup tasks have not run yet; they will pick up the new list.
+        return;
+    }
+
+    let entries = state.config.autostart.clone();
+    let mut seen = HashSet::new();
+    for entry in entries {
+        let command = entry.command.trim();
+        if !seen.insert(command.to_owned()) {
+            tracing::warn!(command, "duplicate autostart command skipped");
+            continue;
+        }
+        if let Some(pid) = state.autostart_pids.get(command).copied()
+            && process_alive(pid)
+        {
+            tracing::info!(command, pid, "autostart command already running; skipped");
+            continue;
+        }
+        if previous
+            .iter()
+            .any(|previous| previous.command.trim() == command)
+        {
+            tracing::info!(command, "autostart command unchanged; skipped");
+            continue;
+        }
+        start_autostart_entry(state, &entry);
     }
 }
 
+/// Wait for the first toplevel opened by `pid` (or its descendants) and bind it
+/// to `workspace`.
+pub(super) fn track_first_window(
+    state: &mut Raven,
+    pid: u32,
+    workspace: usize,
+    session_entry: Option<usize>,
+) {
+    state.autostart_claims.push(AutostartClaim {
+        pid,
+        workspace,
+        spawned_at: Instant::now(),
+        session_entry,
+    });
+}
+
+fn parent_pid(pid: u32) -> Option<u32> {
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    // The command name may contain spaces and parentheses; fields resume after the last `)`.
+    let (_, fields) = stat.rsplit_once(')')?;
+    fields
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()
+        .filter(|ppid| *ppid > 1)
+}
+
+/// Bind `surface` to the workspace of the autostart entry whose process tree owns its
+/// client. Each entry claims only the first toplevel it opens.
+pub(super) fn claim_autostart_workspace(state: &mut Raven, surface: &WlSurface) {
+    state
+        .autostart_claims
+        .retain(|claim| claim.spawned_at.elapsed() < AUTOSTART_CLAIM_TIMEOUT);
+    if state.autostart_claims.is_empty() {
+        return;
+    }
+    let Some(client_pid) = surface
+        .client()
+        .and_then(|client| client.get_credentials(&state.display_handle).ok())
+        .and_then(|credentials| u32::try_from(credentials.pid).ok())
+    else {
+        return;
+    };
+
+    let mut pid = Some(client_pid);
+    for _ in 0..AUTOSTART_MAX_ANCESTRY_DEPTH {
+        let Some(current) = pid else {
+            return;
+        };
+        if let Some(position) = state
+            .autostart_claims
+            .iter()
+            .position(|claim| claim.pid == current)
+        {
+            let claim = state.autostart_claims.remove(position);
+            tracing::info!(
+                client_pid,
+                autostart_pid = claim.pid,
+                workspace = claim.workspace + 1,
+                "binding first autostart window to workspace"
+            );
+            state
+                .autostart_workspace_by_surface
+                .insert(surface.clone(), claim.workspace);
+            if let Some(index) = claim.session_entry {
+                super::session::mark_entry_placed(state, index, surface);
+            }
+            return;
+        }
+        pid = parent_pid(current);
+    }
+}
+
+/// Layer the performance overrides onto the loaded config, or drop them again.
+/// The loaded config is kept aside meanwhile so a reload only replaces the base.
+pub(super) fn set_performance_mode(
+    state: &mut Raven,
+    enabled: bool,
+) -> Result<(), CompositorError> {
+    if state.performance_mode_base.is_some() == enabled {
+        return Ok(());
+    }
+    if enabled {
+        let base = state.config.clone();
+        state.config = base.with_performance_overrides();
+        state.performance_mode_base = Some(base);
+    } else if let Some(base) = state.performance_mode_base.take() {
+        state.config = base;
+    }
+    tracing::info!(enabled, "performance mode toggled");
+
+    state.apply_layout()?;
+    crate::backend::udev::queue_redraw_all(state, RedrawCause::Ipc);
+    Ok(())
+}
+
 pub(super) fn reload_config(state: &mut Raven) -> Result<(), CompositorError> {
-    let config = config::load_from_path(&state.config_path)?;
+    let path = state.config_path.display().to_string();
+    state.report_progress("reload", Some(&path), ProgressStatus::Started);
+    let result = load_and_apply_config(state);
+    state.report_progress("reload", Some(&path), ProgressStatus::from(&result));
+    result
+}
+
+fn load_and_apply_config(state: &mut Raven) -> Result<(), CompositorError> {
+    let config = match config::load_from_path(&state.config_path) {
+        Ok(config) => config,
+        Err(err) => {
+            super::config_error::show_config_error(
+                state,
+                &err.to_string(),
+                "keeping the previous config",
+            );
+            return Err(err);
+        }
+    };
+    super::config_error::dismiss_config_error(state);
     config::apply_environment(&config);
-    state.config = config;
+    let previous_layout = LayoutSettings::of(&state.config);
+    let previous_layout_type = state.config.layout_type;
+    let previous_autostart = state.config.autostart.clone();
+    if let Some(base) = state.performance_mode_base.as_mut() {
+        state.config = config.with_performance_overrides();
+        *base = config;
+    } else {
+        state.config = config;
+    }
+    // A changed `layout.type` replaces the layouts picked at runtime on every
+    // workspace; otherwise those are kept.
+    if state.config.layout_type != previous_layout_type {
+        for workspace_layout in &mut state.workspace_layouts {
+            *workspace_layout = WorkspaceLayout::new(state.config.layout_type);
+        }
+    }
+    // A larger `workspace_count` adds workspaces now; a smaller one lets the
+    // extra ones go once they are empty.
+    super::workspaces::ensure_workspace(state, state.config.workspace_count - 1)?;
+    state.collect_empty_workspaces();
     state.ensure_xwayland_display();
     state.sync_activation_environment();
     state.log_xwayland_satellite_context("reload");
@@ -89,10 +339,23 @@ pub(super) fn reload_config(state: &mut Raven) -> Result<(), CompositorError> {
 
     if state.udev_data.is_some() {
         crate::backend::udev::reload_cursor_theme(state);
+        crate::backend::udev::apply_input_config(state);
+        crate::backend::udev::apply_monitor_config(state);
     }
 
-    state.apply_layout()?;
+    super::rules::refresh_all_render_rules(state);
+
+    // With a preview running, the layout is applied once it ends.
+    if previous_layout == LayoutSettings::of(&state.config) || !layout_preview::start(state) {
+        state.apply_layout()?;
+    }
+    super::idle::sync(state);
+    super::night_light::refresh(state);
+    super::wallpaper_slideshow::sync(state);
     state.apply_wallpaper();
+    // Picks up changed workspace names and `workspace_grid_columns`.
+    state.refresh_ext_workspace();
+    start_new_autostart_entries(state, &previous_autostart);
     tracing::info!(path = %state.config_path.display(), "reloaded config.lua");
     Ok(())
 }
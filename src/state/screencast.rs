@@ -0,0 +1,236 @@
+//! Compositor side of the ScreenCast portal backend: answers the portal's
+//! requests from the event loop and keeps the casts it started. Frames are fed
+//! by the DRM backend as outputs render.
+
+use smithay::{
+    desktop::Window,
+    output::Output,
+    reexports::calloop::channel::{self, Event},
+    utils::{IsAlive, Physical, Size},
+};
+
+use super::Raven;
+use crate::{
+    CompositorError,
+    screencast::{
+        Cast, CastSource, CastTarget, PipeWire, PortalConnection, PortalRequest, SOURCE_MONITOR,
+        SOURCE_WINDOW, Screencast, SourceList, StreamInfo,
+    },
+};
+
+/// Claim the portal bus name and connect to PipeWire. Without a session bus or
+/// a PipeWire daemon Raven runs on, just without screencasting.
+pub(super) fn init(state: &mut Raven) {
+    let (sender, requests) = channel::channel();
+    let token = match state
+        .loop_handle
+        .insert_source(requests, |event, _, state| {
+            if let Event::Msg(request) = event {
+                handle_request(state, request);
+            }
+        }) {
+        Ok(token) => token,
+        Err(err) => {
+            tracing::warn!("failed to insert screencast request source: {err}");
+            return;
+        }
+    };
+
+    let screencast = PortalConnection::new(sender)
+        .map_err(|err| CompositorError::Backend(format!("screencast portal: {err}")))
+        .and_then(|portal| {
+            Ok(Screencast {
+                portal,
+                pipewire: PipeWire::new(&state.loop_handle)?,
+                casts: Vec::new(),
+            })
+        });
+    match screencast {
+        Ok(screencast) => {
+            tracing::info!("screencast portal backend ready");
+            state.screencast = Some(screencast);
+            Raven::ensure_portal_preferences_file(true);
+        }
+        Err(err) => {
+            tracing::warn!("screencasting unavailable: {err}");
+            state.loop_handle.remove(token);
+        }
+    }
+}
+
+fn handle_request(state: &mut Raven, request: PortalRequest) {
+    match request {
+        PortalRequest::Sources(reply) => {
+            let _ = reply.try_send(SourceList {
+                chooser: state.config.screencast_chooser.clone(),
+                sources: sources(state),
+            });
+        }
+        PortalRequest::Start {
+            session,
+            source,
+            reply,
+        } => {
+            if let Err(err) = start(state, session, source, reply.clone()) {
+                tracing::warn!("failed to start screencast: {err}");
+                let _ = reply.try_send(Err(err.to_string()));
+            }
+        }
+        PortalRequest::Stop { session } => {
+            if let Some(screencast) = state.screencast.as_mut() {
+                screencast.casts.retain(|cast| cast.session != session);
+                tracing::debug!(session, "screencast session closed");
+            }
+        }
+    }
+}
+
+/// Outputs and windows on offer, labelled the way the chooser prints them back.
+fn sources(state: &Raven) -> Vec<(CastSource, String)> {
+    let mut sources: Vec<(CastSource, String)> = state
+        .space
+        .outputs()
+        .map(|output| {
+            let name = output.name();
+            let label = format!("output:{name} {}", output.description());
+            (CastSource::Output(name), label)
+        })
+        .collect();
+    for window in state.workspace_windows() {
+        let Some(surface) = Raven::window_surface_id(window) else {
+            continue;
+        };
+        let Some(id) = state.window_id(&surface) else {
+            continue;
+        };
+        let (app_id, title) = Raven::surface_app_id_and_title(&surface);
+        let label = format!(
+            "window:{id} {} — {}",
+            app_id.as_deref().unwrap_or("unknown"),
+            title.as_deref().unwrap_or("untitled")
+        );
+        sources.push((CastSource::Window(id), label));
+    }
+    sources
+}
+
+fn window_by_id(state: &Raven, id: u64) -> Option<Window> {
+    state
+        .workspace_windows()
+        .find(|window| {
+            Raven::window_surface_id(window)
+                .is_some_and(|surface| state.window_id(&surface) == Some(id))
+        })
+        .cloned()
+}
+
+/// Size `output` renders at, in the orientation it is shown.
+fn output_size(output: &Output) -> Option<Size<i32, Physical>> {
+    let mode = output.current_mode()?;
+    Some(output.current_transform().transform_size(mode.size))
+}
+
+/// Size a cast of `window` renders at: its geometry at its output's scale.
+fn window_size(state: &Raven, window: &Window) -> Size<i32, Physical> {
+    let scale = state
+        .toplevel_output(window)
+        .map_or(1.0, |output| output.current_scale().fractional_scale());
+    window.geometry().size.to_physical_precise_round(scale)
+}
+
+fn start(
+    state: &mut Raven,
+    session: String,
+    source: CastSource,
+    reply: async_channel::Sender<Result<StreamInfo, String>>,
+) -> Result<(), CompositorError> {
+    let (target, size, info) = match &source {
+        CastSource::Output(name) => {
+            let output = state
+                .space
+                .outputs()
+                .find(|output| output.name() == *name)
+                .cloned()
+                .ok_or_else(|| CompositorError::Screencopy(format!("no output named {name}")))?;
+            let geometry = state.space.output_geometry(&output).ok_or_else(|| {
+                CompositorError::Screencopy(format!("output {name} is not mapped"))
+            })?;
+            let size = output_size(&output)
+                .ok_or_else(|| CompositorError::Screencopy(format!("output {name} has no mode")))?;
+            let info = StreamInfo {
+                node_id: 0,
+                position: (geometry.loc.x, geometry.loc.y),
+                size: (geometry.size.w, geometry.size.h),
+                source_type: SOURCE_MONITOR,
+            };
+            (CastTarget::Output(output.downgrade()), size, info)
+        }
+        CastSource::Window(id) => {
+            let window = window_by_id(state, *id)
+                .ok_or_else(|| CompositorError::Screencopy(format!("no window with id {id}")))?;
+            let geometry = window.geometry();
+            let info = StreamInfo {
+                node_id: 0,
+                position: (0, 0),
+                size: (geometry.size.w, geometry.size.h),
+                source_type: SOURCE_WINDOW,
+            };
+            (
+                CastTarget::Window(window.clone()),
+                window_size(state, &window),
+                info,
+            )
+        }
+    };
+    if size.w <= 0 || size.h <= 0 {
+        return Err(CompositorError::Screencopy(
+            "cast source has no size yet".to_owned(),
+        ));
+    }
+
+    let gbm = crate::backend::udev::primary_gbm(state).ok_or_else(|| {
+        CompositorError::Screencopy("screencasting needs the DRM backend".to_owned())
+    })?;
+    let screencast = state
+        .screencast
+        .as_mut()
+        .ok_or_else(|| CompositorError::Screencopy("screencasting is not running".to_owned()))?;
+    let cast = Cast::new(
+        &screencast.pipewire,
+        gbm,
+        session.clone(),
+        target,
+        size,
+        info,
+        reply,
+    )?;
+    screencast.casts.push(cast);
+    tracing::info!(session, ?source, "screencast started");
+
+    crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Ipc);
+    Ok(())
+}
+
+/// Drop casts whose stream failed or whose source is gone, and tell the portal
+/// their sessions ended.
+pub(super) fn refresh(state: &mut Raven) {
+    let Some(screencast) = state.screencast.as_mut() else {
+        return;
+    };
+    let mut closed = Vec::new();
+    screencast.casts.retain(|cast| {
+        let alive = !cast.failed()
+            && match &cast.target {
+                CastTarget::Output(output) => output.upgrade().is_some(),
+                CastTarget::Window(window) => window.alive(),
+            };
+        if !alive {
+            closed.push(cast.session.clone());
+        }
+        alive
+    });
+    for session in closed {
+        tracing::info!(session, "screencast source went away");
+        screencast.portal.close_session(&session);
+    }
+}
@@ -0,0 +1,459 @@
+use std::{
+    fs,
+    io::Write,
+    os::unix::net::UnixStream,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use smithay::{
+    backend::renderer::element::Kind,
+    input::pointer::{Focus, GrabStartData as PointerGrabStartData},
+    output::Output,
+    reexports::calloop::{
+        RegistrationToken,
+        timer::{TimeoutAction, Timer},
+    },
+    utils::{Logical, Point, Rectangle, SERIAL_COUNTER},
+};
+
+use crate::{
+    CompositorError,
+    backend::capture::{OutputCapture, StitchedImage, encode_png, stitch_captures, stitch_region},
+    grabs::region_select_grab::RegionSelectGrab,
+    render_helpers::{SolidColorBuffer, SolidColorRenderElement},
+};
+
+use super::Raven;
+
+// Armed outputs get a forced redraw, so a frame this late means the output
+// is not presenting at all.
+const NEXT_FRAME_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// A `screenshot --next-frame` request waiting for its outputs to present.
+pub(super) struct NextFrameScreenshot {
+    stream: UnixStream,
+    path: Option<PathBuf>,
+    waiting: Vec<Output>,
+    captures: Vec<OutputCapture>,
+    deadline: Instant,
+    timeout: RegistrationToken,
+}
+
+const SELECTION_SHADE: [f32; 4] = [0.0, 0.0, 0.0, 0.4];
+const SELECTION_BORDER: f64 = 2.0;
+const SELECTION_BORDER_COLOR: [f32; 4] = [1.0, 0.85, 0.2, 1.0];
+
+/// A `screenshot --region` request while the region is being dragged out.
+pub(super) struct RegionSelection {
+    stream: UnixStream,
+    path: Option<PathBuf>,
+    anchor: Option<Point<f64, Logical>>,
+    cursor: Point<f64, Logical>,
+}
+
+impl RegionSelection {
+    /// The dragged rectangle in the global logical space, once a drag started.
+    fn region(&self) -> Option<Rectangle<i32, Logical>> {
+        let anchor = self.anchor?;
+        let (x1, x2) = (anchor.x.min(self.cursor.x), anchor.x.max(self.cursor.x));
+        let (y1, y2) = (anchor.y.min(self.cursor.y), anchor.y.max(self.cursor.y));
+        let (x1, y1) = (x1.floor() as i32, y1.floor() as i32);
+        let (x2, y2) = (x2.ceil() as i32, y2.ceil() as i32);
+        Some(Rectangle::new((x1, y1).into(), (x2 - x1, y2 - y1).into()))
+    }
+}
+
+fn default_screenshot_path() -> PathBuf {
+    let dir = std::env::var_os("XDG_PICTURES_DIR")
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join("Pictures")))
+        .unwrap_or_else(std::env::temp_dir);
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or(0);
+    dir.join(format!("raven-screenshot-{stamp}.png"))
+}
+
+/// Capture `target` (`all` or an output name) and write it as PNG; the path goes
+/// to `reply` once the file is written.
+///
+/// With `all`, every mapped output is captured in the same pass and stitched
+/// together by its position in the layout, so the image reflects one moment in
+/// time even across monitors with different scales.
+pub(super) fn take_screenshot(
+    state: &mut Raven,
+    target: &str,
+    path: Option<&Path>,
+    reply: UnixStream,
+) -> Result<(), CompositorError> {
+    let outputs = target_outputs(state, target)?;
+    let mut captures = Vec::with_capacity(outputs.len());
+    for output in &outputs {
+        captures.push(crate::backend::udev::capture_output(state, output)?);
+    }
+    save_in_background(stitch(&captures)?, path, reply);
+    Ok(())
+}
+
+fn target_outputs(state: &Raven, target: &str) -> Result<Vec<Output>, CompositorError> {
+    let outputs: Vec<_> = state
+        .space
+        .outputs()
+        .filter(|output| target == "all" || output.name() == target)
+        .cloned()
+        .collect();
+    if outputs.is_empty() {
+        return Err(CompositorError::Screencopy(if target == "all" {
+            "no outputs to capture".to_owned()
+        } else {
+            format!("unknown output `{target}`")
+        }));
+    }
+    Ok(outputs)
+}
+
+fn stitch(captures: &[OutputCapture]) -> Result<StitchedImage, CompositorError> {
+    stitch_captures(captures)
+        .ok_or_else(|| CompositorError::Screencopy("captured outputs are empty".to_owned()))
+}
+
+/// Encode and write `image` on a worker thread, so large captures don't stall the
+/// event loop; the path or the error is written to `reply` from there.
+fn save_in_background(image: StitchedImage, path: Option<&Path>, mut reply: UnixStream) {
+    let path = path
+        .map(Path::to_path_buf)
+        .unwrap_or_else(default_screenshot_path);
+    thread::spawn(move || {
+        let message = match save_image(&image, &path) {
+            Ok(()) => format!("{}\n", path.display()),
+            Err(err) => format!("error: {err}\n"),
+        };
+        if let Err(err) = reply.write_all(message.as_bytes()) {
+            tracing::warn!("failed to write ipc response: {err}");
+        }
+    });
+}
+
+fn save_image(image: &StitchedImage, path: &Path) -> Result<(), CompositorError> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent).map_err(|err| {
+            CompositorError::Screencopy(format!("failed to create {}: {err}", parent.display()))
+        })?;
+    }
+    fs::write(path, encode_png(image)?).map_err(|err| {
+        CompositorError::Screencopy(format!("failed to write {}: {err}", path.display()))
+    })?;
+
+    tracing::info!(
+        path = %path.display(),
+        width = image.width,
+        height = image.height,
+        "screenshot saved"
+    );
+    Ok(())
+}
+
+/// Capture the focused window on its own, uncovered, and write it as PNG; the path
+/// goes to `reply` once the file is written.
+pub(super) fn take_window_screenshot(
+    state: &mut Raven,
+    path: Option<&Path>,
+    reply: UnixStream,
+) -> Result<(), CompositorError> {
+    let window = state
+        .focused_window()
+        .ok_or_else(|| CompositorError::Screencopy("no focused window to capture".to_owned()))?;
+    let (size, pixels) = crate::backend::udev::capture_window(state, &window)?;
+    let image = StitchedImage {
+        width: size.w as u32,
+        height: size.h as u32,
+        pixels,
+    };
+    save_in_background(image, path, reply);
+    Ok(())
+}
+
+/// Let the user drag out a region with the pointer; the reply goes to `stream`
+/// once the region is captured or the selection is cancelled.
+pub(super) fn start_region_selection(
+    state: &mut Raven,
+    stream: UnixStream,
+    path: Option<&Path>,
+) -> Result<(), CompositorError> {
+    if state.udev_data.is_none() {
+        return Err(CompositorError::Screencopy(
+            "screenshots need the DRM backend".to_owned(),
+        ));
+    }
+    if state.region_selection.is_some() {
+        return Err(CompositorError::Screencopy(
+            "a region selection is already running".to_owned(),
+        ));
+    }
+    let pointer = state.pointer();
+    if pointer.is_grabbed() {
+        return Err(CompositorError::Screencopy(
+            "the pointer is busy".to_owned(),
+        ));
+    }
+
+    state.region_selection = Some(RegionSelection {
+        stream,
+        path: path.map(Path::to_path_buf),
+        anchor: None,
+        cursor: state.pointer_location,
+    });
+    let grab = RegionSelectGrab {
+        start_data: PointerGrabStartData {
+            focus: None,
+            button: 0,
+            location: state.pointer_location,
+        },
+    };
+    pointer.set_grab(state, grab, SERIAL_COUNTER.next_serial(), Focus::Clear);
+    crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Ipc);
+    Ok(())
+}
+
+pub(super) fn region_pressed(state: &mut Raven, location: Point<f64, Logical>) {
+    if let Some(selection) = state.region_selection.as_mut() {
+        selection.anchor = Some(location);
+        selection.cursor = location;
+    }
+}
+
+pub(super) fn region_moved(state: &mut Raven, location: Point<f64, Logical>) {
+    let Some(selection) = state.region_selection.as_mut() else {
+        return;
+    };
+    selection.cursor = location;
+    if selection.anchor.is_some() {
+        crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Pointer);
+    }
+}
+
+/// Capture the dragged region and answer the request.
+pub(super) fn finish_region_selection(state: &mut Raven) {
+    let Some(selection) = state.region_selection.take() else {
+        return;
+    };
+    crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Ipc);
+    match capture_region(state, &selection) {
+        Ok(image) => save_in_background(image, selection.path.as_deref(), selection.stream),
+        Err(err) => reply_region(selection, &format!("error: {err}\n")),
+    }
+}
+
+fn capture_region(
+    state: &mut Raven,
+    selection: &RegionSelection,
+) -> Result<StitchedImage, CompositorError> {
+    let region = selection
+        .region()
+        .filter(|region| region.size.w > 0 && region.size.h > 0)
+        .ok_or_else(|| CompositorError::Screencopy("selected region is empty".to_owned()))?;
+    let outputs: Vec<Output> = state
+        .space
+        .outputs()
+        .filter(|output| {
+            state
+                .space
+                .output_geometry(output)
+                .is_some_and(|geometry| geometry.overlaps(region))
+        })
+        .cloned()
+        .collect();
+    let mut captures = Vec::with_capacity(outputs.len());
+    for output in &outputs {
+        captures.push(crate::backend::udev::capture_output(state, output)?);
+    }
+    stitch_region(&captures, region).ok_or_else(|| {
+        CompositorError::Screencopy("selected region is outside every output".to_owned())
+    })
+}
+
+pub(super) fn cancel_region_selection(state: &mut Raven) {
+    let Some(selection) = state.region_selection.take() else {
+        return;
+    };
+    crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Ipc);
+    reply_region(selection, "error: region selection cancelled\n");
+}
+
+fn reply_region(mut selection: RegionSelection, reply: &str) {
+    if let Err(err) = selection.stream.write_all(reply.as_bytes()) {
+        tracing::warn!("failed to write ipc response: {err}");
+    }
+}
+
+fn solid_rect(rect: Rectangle<f64, Logical>, color: [f32; 4]) -> SolidColorRenderElement {
+    let buffer = SolidColorBuffer::new((rect.size.w, rect.size.h), color);
+    SolidColorRenderElement::from_buffer(&buffer, rect.loc, 1.0, Kind::Unspecified)
+}
+
+/// The selection overlay on `output`, relative to the output origin: the
+/// screen is shaded except for the dragged region, which gets a border.
+pub(super) fn region_selection_elements(
+    state: &Raven,
+    output: &Output,
+) -> Vec<SolidColorRenderElement> {
+    let Some(selection) = state.region_selection.as_ref() else {
+        return Vec::new();
+    };
+    let Some(output_geo) = state.space.output_geometry(output) else {
+        return Vec::new();
+    };
+    let bounds = Rectangle::from_size(output_geo.size.to_f64());
+    let Some(region) = selection.region() else {
+        return vec![solid_rect(bounds, SELECTION_SHADE)];
+    };
+
+    let loc = region.loc.to_f64() - output_geo.loc.to_f64();
+    let (x, y) = (loc.x, loc.y);
+    let (w, h) = (f64::from(region.size.w), f64::from(region.size.h));
+    let (out_w, out_h) = (bounds.size.w, bounds.size.h);
+    let b = SELECTION_BORDER;
+
+    // Front to back: the border above the shade.
+    let border = [
+        (x - b, y - b, w + 2.0 * b, b, SELECTION_BORDER_COLOR),
+        (x - b, y + h, w + 2.0 * b, b, SELECTION_BORDER_COLOR),
+        (x - b, y, b, h, SELECTION_BORDER_COLOR),
+        (x + w, y, b, h, SELECTION_BORDER_COLOR),
+    ];
+    let shade = [
+        (0.0, 0.0, out_w, y, SELECTION_SHADE),
+        (0.0, y + h, out_w, out_h - (y + h), SELECTION_SHADE),
+        (0.0, y, x, h, SELECTION_SHADE),
+        (x + w, y, out_w - (x + w), h, SELECTION_SHADE),
+    ];
+    border
+        .into_iter()
+        .chain(shade)
+        .filter(|(_, _, w, h, _)| *w > 0.0 && *h > 0.0)
+        .filter_map(|(x, y, w, h, color)| {
+            Rectangle::new((x, y).into(), (w, h).into())
+                .intersection(bounds)
+                .map(|rect| solid_rect(rect, color))
+        })
+        .collect()
+}
+
+/// Arm a capture that the DRM backend fulfils with the next frame each target
+/// output queues for scanout, so the image holds exactly what was presented.
+/// The reply goes to `stream` once every output has delivered its frame.
+pub(super) fn arm_next_frame_screenshot(
+    state: &mut Raven,
+    stream: UnixStream,
+    target: &str,
+    path: Option<&Path>,
+) -> Result<(), CompositorError> {
+    if state.udev_data.is_none() {
+        return Err(CompositorError::Screencopy(
+            "screenshots need the DRM backend".to_owned(),
+        ));
+    }
+    let waiting = target_outputs(state, target)?;
+
+    let timer = Timer::from_duration(NEXT_FRAME_TIMEOUT);
+    let timeout = state
+        .loop_handle
+        .insert_source(timer, |_, _, state| {
+            expire_next_frame_screenshots(state);
+            TimeoutAction::Drop
+        })
+        .map_err(|err| {
+            CompositorError::Screencopy(format!("failed to schedule capture timeout: {err}"))
+        })?;
+
+    for output in &waiting {
+        crate::backend::udev::queue_redraw_for_output(
+            state,
+            output,
+            crate::backend::udev::RedrawCause::Ipc,
+        );
+    }
+    state.next_frame_screenshots.push(NextFrameScreenshot {
+        stream,
+        path: path.map(Path::to_path_buf),
+        waiting,
+        captures: Vec::new(),
+        deadline: Instant::now() + NEXT_FRAME_TIMEOUT,
+        timeout,
+    });
+    Ok(())
+}
+
+pub(super) fn next_frame_screenshot_pending(state: &Raven, output: &Output) -> bool {
+    state
+        .next_frame_screenshots
+        .iter()
+        .any(|request| request.waiting.contains(output))
+}
+
+/// Hand the frame just queued on `output` to every request waiting for it.
+pub(super) fn next_frame_captured(
+    state: &mut Raven,
+    output: &Output,
+    capture: Result<OutputCapture, CompositorError>,
+) {
+    let mut index = 0;
+    while index < state.next_frame_screenshots.len() {
+        let request = &mut state.next_frame_screenshots[index];
+        let Some(position) = request.waiting.iter().position(|waiting| waiting == output) else {
+            index += 1;
+            continue;
+        };
+        request.waiting.remove(position);
+
+        let image = match &capture {
+            Ok(capture) => {
+                request.captures.push(capture.clone());
+                if !request.waiting.is_empty() {
+                    index += 1;
+                    continue;
+                }
+                stitch(&request.captures).map_err(|err| err.to_string())
+            }
+            Err(err) => Err(err.to_string()),
+        };
+        let request = state.next_frame_screenshots.remove(index);
+        state.loop_handle.remove(request.timeout);
+        match image {
+            Ok(image) => save_in_background(image, request.path.as_deref(), request.stream),
+            Err(err) => reply_next_frame(request, &format!("error: {err}\n")),
+        }
+    }
+}
+
+fn expire_next_frame_screenshots(state: &mut Raven) {
+    let now = Instant::now();
+    let (expired, waiting): (Vec<_>, Vec<_>) = state
+        .next_frame_screenshots
+        .drain(..)
+        .partition(|request| request.deadline <= now);
+    state.next_frame_screenshots = waiting;
+
+    for request in expired {
+        let outputs: Vec<String> = request.waiting.iter().map(Output::name).collect();
+        tracing::warn!(?outputs, "next-frame screenshot timed out");
+        let reply = format!(
+            "error: no frame presented on {} within {}s\n",
+            outputs.join(", "),
+            NEXT_FRAME_TIMEOUT.as_secs()
+        );
+        reply_next_frame(request, &reply);
+    }
+}
+
+fn reply_next_frame(mut request: NextFrameScreenshot, reply: &str) {
+    if let Err(err) = request.stream.write_all(reply.as_bytes()) {
+        tracing::warn!("failed to write ipc response: {err}");
+    }
+}
@@ -0,0 +1,204 @@
+//! The `session` config table: an initial layout launched once at startup.
+//!
+//! Each entry is spawned like an autostart command with a workspace. Its first
+//! toplevel is bound to that workspace, then placed from the entry (tile order or
+//! floating geometry). `raven session` reports how far the session has come.
+
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use smithay::{desktop::Window, reexports::wayland_server::protocol::wl_surface::WlSurface};
+
+use crate::config::SessionEntry;
+
+use super::{Raven, runtime};
+
+/// Session entries and the windows they produced.
+#[derive(Default)]
+pub(super) struct SessionState {
+    started_at: Option<Instant>,
+    slots: Vec<SessionSlot>,
+    // First toplevel of each placed entry, with the entry index.
+    windows: HashMap<WlSurface, usize>,
+}
+
+struct SessionSlot {
+    entry: SessionEntry,
+    status: SlotStatus,
+}
+
+enum SlotStatus {
+    SpawnFailed,
+    Waiting { pid: u32 },
+    Placed { pid: u32, after: Duration },
+}
+
+/// Spawn every session entry. Runs once per compositor lifetime; reloading the
+/// config does not relaunch the session.
+pub(super) fn start_session(state: &mut Raven) {
+    if state.session.started_at.is_some() || state.config.session.is_empty() {
+        return;
+    }
+    state.session.started_at = Some(Instant::now());
+
+    let entries = state.config.session.clone();
+    for (index, entry) in entries.into_iter().enumerate() {
+        tracing::info!(
+            command = %entry.command,
+            workspace = entry.workspace + 1,
+            floating = entry.floating,
+            "starting session entry"
+        );
+        let status = match runtime::spawn_child(state, &entry.command, &[]) {
+            Some(pid) => {
+                runtime::track_first_window(state, pid, entry.workspace, Some(index));
+                SlotStatus::Waiting { pid }
+            }
+            None => SlotStatus::SpawnFailed,
+        };
+        state.session.slots.push(SessionSlot { entry, status });
+    }
+}
+
+pub(super) fn mark_entry_placed(state: &mut Raven, index: usize, surface: &WlSurface) {
+    let Some(started_at) = state.session.started_at else {
+        return;
+    };
+    let Some(slot) = state.session.slots.get_mut(index) else {
+        return;
+    };
+    let SlotStatus::Waiting { pid } = slot.status else {
+        return;
+    };
+    slot.status = SlotStatus::Placed {
+        pid,
+        after: started_at.elapsed(),
+    };
+    state.session.windows.insert(surface.clone(), index);
+}
+
+pub(super) fn forget_session_window(state: &mut Raven, surface: &WlSurface) {
+    state.session.windows.remove(surface);
+}
+
+pub(super) fn entry_for_surface<'a>(
+    state: &'a Raven,
+    surface: &WlSurface,
+) -> Option<&'a SessionEntry> {
+    let index = *state.session.windows.get(surface)?;
+    state.session.slots.get(index).map(|slot| &slot.entry)
+}
+
+fn entry_index_for_window(state: &Raven, window: &Window) -> Option<usize> {
+    let toplevel = window.toplevel()?;
+    state.session.windows.get(toplevel.wl_surface()).copied()
+}
+
+/// Where a floating session window goes when the entry sets `x` and/or `y`.
+/// Unset coordinates keep the default (centered) placement.
+pub(super) fn floating_location_for_window(state: &Raven, window: &Window) -> Option<(i32, i32)> {
+    let toplevel = window.toplevel()?;
+    let entry = entry_for_surface(state, toplevel.wl_surface())?;
+    if !entry.floating || (entry.x.is_none() && entry.y.is_none()) {
+        return None;
+    }
+
+    let origin = state
+        .active_output_for_pointer()
+        .and_then(|output| state.space.output_geometry(&output))
+        .map(|geometry| geometry.loc)
+        .unwrap_or_default();
+    let (default_x, default_y) = state.default_floating_location(window);
+    Some((
+        entry.x.map_or(default_x, |x| origin.x + x),
+        entry.y.map_or(default_y, |y| origin.y + y),
+    ))
+}
+
+/// Keep session windows in their listed order in the workspace list, which the
+/// tiling layout follows.
+pub(super) fn order_session_windows(state: &mut Raven, window: &Window) {
+    if entry_index_for_window(state, window).is_none() {
+        return;
+    }
+    let Some(workspace_index) = state.workspace_index_for_window(window) else {
+        return;
+    };
+
+    let positions: Vec<usize> = state.workspaces[workspace_index]
+        .iter()
+        .enumerate()
+        .filter(|(_, candidate)| entry_index_for_window(state, candidate).is_some())
+        .map(|(position, _)| position)
+        .collect();
+    let mut ordered: Vec<Window> = positions
+        .iter()
+        .map(|position| state.workspaces[workspace_index][*position].clone())
+        .collect();
+    ordered.sort_by_key(|candidate| entry_index_for_window(state, candidate));
+    for (position, candidate) in positions.iter().zip(&ordered) {
+        state.workspaces[workspace_index][*position] = candidate.clone();
+    }
+}
+
+fn or_auto(value: Option<impl ToString>) -> String {
+    value.map_or_else(|| "auto".to_owned(), |value| value.to_string())
+}
+
+fn describe_placement(entry: &SessionEntry) -> String {
+    if !entry.floating {
+        return "tiled".to_owned();
+    }
+    format!(
+        "floating at {},{} size {}x{}",
+        or_auto(entry.x),
+        or_auto(entry.y),
+        or_auto(entry.width),
+        or_auto(entry.height)
+    )
+}
+
+pub(super) fn render_session_report(state: &Raven) -> String {
+    let Some(started_at) = state.session.started_at else {
+        return "No session configured.\n".to_owned();
+    };
+
+    let elapsed = started_at.elapsed();
+    let placed = state
+        .session
+        .slots
+        .iter()
+        .filter(|slot| matches!(slot.status, SlotStatus::Placed { .. }))
+        .count();
+    let mut out = format!(
+        "Session: {placed}/{} placed, started {:.1}s ago\n\n",
+        state.session.slots.len(),
+        elapsed.as_secs_f64()
+    );
+
+    for (index, slot) in state.session.slots.iter().enumerate() {
+        let status = match slot.status {
+            SlotStatus::SpawnFailed => "failed to spawn".to_owned(),
+            SlotStatus::Waiting { pid } if elapsed >= runtime::AUTOSTART_CLAIM_TIMEOUT => {
+                format!("gave up waiting for a window (pid {pid})")
+            }
+            SlotStatus::Waiting { pid } => format!("waiting for a window (pid {pid})"),
+            SlotStatus::Placed { pid, after } => {
+                format!("placed after {:.1}s (pid {pid})", after.as_secs_f64())
+            }
+        };
+        out.push_str(&format!("Entry {}:\n", index + 1));
+        out.push_str(&format!("  command: {}\n", slot.entry.command));
+        out.push_str(&format!("  workspace: {}\n", slot.entry.workspace + 1));
+        out.push_str(&format!(
+            "  placement: {}\n",
+            describe_placement(&slot.entry)
+        ));
+        out.push_str(&format!("  status: {status}\n"));
+        out.push('\n');
+    }
+
+    out
+}
@@ -0,0 +1,174 @@
+//! Periodic sweep of the per-surface bookkeeping sets. Entries are meant to go
+//! away in `toplevel_destroyed`; a client dying mid-transition can skip that
+//! path, so dead surfaces are dropped here and counted for `raven stats`.
+
+use std::{
+    collections::{HashMap, HashSet},
+    time::{Duration, Instant},
+};
+
+use smithay::reexports::{
+    calloop::timer::{TimeoutAction, Timer},
+    wayland_server::{Resource, protocol::wl_surface::WlSurface},
+};
+
+use super::Raven;
+
+const SWEEP_INTERVAL: Duration = Duration::from_secs(30);
+
+#[derive(Debug)]
+struct SetStats {
+    name: &'static str,
+    // Entries left after the last sweep.
+    len: usize,
+    // Dead entries dropped over the whole session.
+    dropped: u64,
+}
+
+#[derive(Debug, Default)]
+pub(super) struct SurfaceSweepStats {
+    sweeps: u64,
+    last_sweep: Option<Instant>,
+    sets: Vec<SetStats>,
+}
+
+trait SurfaceKeyed {
+    fn len(&self) -> usize;
+    fn retain_alive(&mut self);
+}
+
+impl SurfaceKeyed for HashSet<WlSurface> {
+    fn len(&self) -> usize {
+        HashSet::len(self)
+    }
+
+    fn retain_alive(&mut self) {
+        self.retain(|surface| surface.is_alive());
+    }
+}
+
+impl<V> SurfaceKeyed for HashMap<WlSurface, V> {
+    fn len(&self) -> usize {
+        HashMap::len(self)
+    }
+
+    fn retain_alive(&mut self) {
+        self.retain(|surface, _| surface.is_alive());
+    }
+}
+
+pub(super) fn start(state: &mut Raven) {
+    let timer = Timer::from_duration(SWEEP_INTERVAL);
+    if let Err(err) = state.loop_handle.insert_source(timer, |_, _, state| {
+        sweep(state);
+        TimeoutAction::ToDuration(SWEEP_INTERVAL)
+    }) {
+        tracing::warn!("failed to schedule surface bookkeeping sweep: {err}");
+    }
+}
+
+fn sweep(state: &mut Raven) {
+    // (name, entries left, entries dropped)
+    let mut swept: Vec<(&'static str, usize, usize)> = Vec::new();
+    let mut sweep_set = |name, set: &mut dyn SurfaceKeyed| {
+        let before = set.len();
+        set.retain_alive();
+        swept.push((name, set.len(), before - set.len()));
+    };
+    sweep_set(
+        "pending_floating_recenter",
+        &mut state.pending_floating_recenter_ids,
+    );
+    sweep_set(
+        "pending_window_rule_recheck",
+        &mut state.pending_window_rule_recheck_ids,
+    );
+    sweep_set(
+        "pending_initial_configure",
+        &mut state.pending_initial_configure_ids,
+    );
+    sweep_set(
+        "pending_initial_configure_idle",
+        &mut state.pending_initial_configure_idle_ids,
+    );
+    sweep_set("unmapped_toplevel", &mut state.unmapped_toplevel_ids);
+    sweep_set(
+        "pending_unmapped_maximized",
+        &mut state.pending_unmapped_maximized_ids,
+    );
+    sweep_set("held_floating_map", &mut state.held_floating_map_ids);
+    sweep_set("modal_dialog", &mut state.modal_dialog_ids);
+    sweep_set("window_ids", &mut state.window_ids);
+    sweep_set("window_outputs", &mut state.window_outputs);
+    sweep_set("sticky_windows", &mut state.sticky_windows);
+    sweep_set("window_render_rules", &mut state.window_render_rules);
+    sweep_set("urgent_windows", &mut state.urgent_windows);
+    sweep_set("minimized_windows", &mut state.minimized_windows);
+    sweep_set("assigned_rects", &mut state.assigned_rects_by_surface);
+    sweep_set("reported_sizes", &mut state.reported_sizes_by_surface);
+    sweep_set(
+        "autostart_workspace",
+        &mut state.autostart_workspace_by_surface,
+    );
+    sweep_set(
+        "fullscreen_maximized",
+        &mut state.fullscreen.maximized_surfaces,
+    );
+    sweep_set(
+        "fullscreen_pending_unmapped",
+        &mut state.fullscreen.pending_unmapped_ids,
+    );
+    sweep_set(
+        "fullscreen_pending_transition",
+        &mut state.fullscreen.pending_transition_by_surface,
+    );
+    sweep_set(
+        "fullscreen_restore_state",
+        &mut state.fullscreen.restore_state_by_surface,
+    );
+
+    let stats = &mut state.surface_sweep;
+    stats.sweeps += 1;
+    stats.last_sweep = Some(Instant::now());
+    for (name, len, dropped) in swept {
+        if dropped > 0 {
+            tracing::warn!(
+                set = name,
+                dropped,
+                "dropped dead surfaces from bookkeeping"
+            );
+        }
+        match stats.sets.iter_mut().find(|set| set.name == name) {
+            Some(set) => {
+                set.len = len;
+                set.dropped += dropped as u64;
+            }
+            None => stats.sets.push(SetStats {
+                name,
+                len,
+                dropped: dropped as u64,
+            }),
+        }
+    }
+}
+
+/// The `raven stats` section on bookkeeping sizes and sweep results.
+pub(super) fn render_sweep_report(state: &Raven) -> String {
+    let stats = &state.surface_sweep;
+    let Some(last_sweep) = stats.last_sweep else {
+        return "Surface bookkeeping: not swept yet.\n".to_owned();
+    };
+
+    let mut out = format!(
+        "Surface bookkeeping ({} sweeps, last {}s ago):\n",
+        stats.sweeps,
+        last_sweep.elapsed().as_secs()
+    );
+    for set in &stats.sets {
+        out.push_str(&format!(
+            "  {}: {} entries, {} dead dropped\n",
+            set.name, set.len, set.dropped
+        ));
+    }
+    out
+}
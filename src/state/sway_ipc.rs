@@ -0,0 +1,959 @@
+//! Subset of the i3/sway IPC protocol on a second socket, so tools written for sway
+//! (waybar's sway modules, i3-msg/swaymsg scripts) work against Raven unmodified.
+//!
+//! Supported messages: RUN_COMMAND, GET_WORKSPACES, SUBSCRIBE (workspace, output, window
+//! and input events), GET_TREE and GET_INPUTS. Anything else gets a `success: false` reply.
+//! SUBSCRIBE also takes `progress`, a Raven extension reporting reload, output and
+//! wallpaper results.
+
+use std::{
+    collections::{HashMap, HashSet},
+    ffi::OsString,
+    fmt::Write as _,
+    io::{Read, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::PathBuf,
+    time::Duration,
+};
+
+use smithay::{
+    desktop::Window,
+    output::Output,
+    reexports::{
+        calloop::{Interest, LoopHandle, Mode, PostAction, RegistrationToken, generic::Generic},
+        wayland_server::protocol::wl_surface::WlSurface,
+    },
+    utils::{Logical, Rectangle},
+};
+
+use crate::{CompositorError, config_import};
+
+use super::Raven;
+
+const MAGIC: &[u8; 6] = b"i3-ipc";
+const HEADER_LEN: usize = MAGIC.len() + 8;
+// Commands and subscriptions are tiny; anything near this is not a sway client.
+const MAX_PAYLOAD_LEN: usize = 1024 * 1024;
+const WRITE_TIMEOUT: Duration = Duration::from_millis(250);
+// Events a subscriber has not read yet; a client this far behind has stopped reading.
+const MAX_QUEUED_LEN: usize = 4 * 1024 * 1024;
+
+const RUN_COMMAND: u32 = 0;
+const GET_WORKSPACES: u32 = 1;
+const SUBSCRIBE: u32 = 2;
+const GET_TREE: u32 = 4;
+const GET_INPUTS: u32 = 100;
+
+const EVENT_WORKSPACE: u32 = 0x8000_0000;
+const EVENT_OUTPUT: u32 = 0x8000_0001;
+const EVENT_WINDOW: u32 = 0x8000_0003;
+const EVENT_INPUT: u32 = 0x8000_0015;
+// Not a sway event; picked well clear of sway's range.
+const EVENT_PROGRESS: u32 = 0x8000_0100;
+
+const KEYBOARD_IDENTIFIER: &str = "0:0:Raven_Keyboard";
+
+const ROOT_NODE_ID: u64 = 1;
+const OUTPUT_NODE_ID_BASE: u64 = 2;
+const WORKSPACE_NODE_ID_BASE: u64 = 64;
+const FIRST_WINDOW_NODE_ID: u64 = 128;
+
+struct Subscriber {
+    // Id of the connection the subscription came in on.
+    connection: u64,
+    stream: UnixStream,
+    // Encoded messages not yet written, and the writable source that flushes them.
+    outbox: Vec<u8>,
+    flush: Option<RegistrationToken>,
+    workspace: bool,
+    output: bool,
+    window: bool,
+    input: bool,
+    progress: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct WindowSnapshot {
+    node_id: u64,
+    app_id: Option<String>,
+    title: Option<String>,
+    urgent: bool,
+}
+
+/// Per-compositor sway IPC bookkeeping: subscribers and the last state events
+/// were generated from.
+#[derive(Default)]
+pub(super) struct SwayIpcState {
+    subscribers: Vec<Subscriber>,
+    next_connection: u64,
+    last_workspace: Option<usize>,
+    last_focus: Option<WlSurface>,
+    last_layout: usize,
+    windows: HashMap<WlSurface, WindowSnapshot>,
+    // Set when a window committed, closed or changed urgency since the last snapshot.
+    windows_changed: bool,
+}
+
+/// Window con ids follow Raven's own window ids, so `raven clients` and sway
+/// clients agree on which window is which.
+fn node_id(state: &mut Raven, surface: &WlSurface) -> u64 {
+    FIRST_WINDOW_NODE_ID + state.assign_window_id(surface)
+}
+
+pub(super) fn init_sway_ipc_listener(
+    loop_handle: &LoopHandle<'static, Raven>,
+    socket_name: &OsString,
+) -> Result<PathBuf, CompositorError> {
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR").ok_or_else(|| {
+        CompositorError::Backend(
+            "XDG_RUNTIME_DIR is not set; cannot create sway ipc socket".to_owned(),
+        )
+    })?;
+    let socket_path = PathBuf::from(runtime_dir).join(format!(
+        "raven-{}.sway.sock",
+        socket_name.to_string_lossy().trim()
+    ));
+
+    if socket_path.exists()
+        && let Err(err) = std::fs::remove_file(&socket_path)
+    {
+        return Err(CompositorError::Backend(format!(
+            "failed to remove stale sway ipc socket {}: {err}",
+            socket_path.display()
+        )));
+    }
+
+    let listener = UnixListener::bind(&socket_path).map_err(|err| {
+        CompositorError::Backend(format!(
+            "failed to bind sway ipc socket {}: {err}",
+            socket_path.display()
+        ))
+    })?;
+    listener.set_nonblocking(true).map_err(|err| {
+        CompositorError::Backend(format!(
+            "failed to set sway ipc socket nonblocking {}: {err}",
+            socket_path.display()
+        ))
+    })?;
+
+    loop_handle
+        .insert_source(
+            Generic::new(listener, Interest::READ, Mode::Level),
+            move |_, listener, state| {
+                loop {
+                    match listener.accept() {
+                        Ok((stream, _)) => register_connection(state, stream),
+                        Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => break,
+                        Err(err) => {
+                            tracing::warn!("sway ipc accept failed: {err}");
+                            break;
+                        }
+                    }
+                }
+                Ok(PostAction::Continue)
+            },
+        )
+        .map_err(|err| {
+            CompositorError::EventLoop(format!("failed to init sway ipc listener: {err}"))
+        })?;
+
+    // SAFETY: called once from Raven::new on the main thread, before any threads are spawned.
+    unsafe {
+        std::env::set_var("SWAYSOCK", &socket_path);
+        std::env::set_var("I3SOCK", &socket_path);
+    }
+
+    Ok(socket_path)
+}
+
+fn register_connection(state: &mut Raven, stream: UnixStream) {
+    if let Err(err) = stream
+        .set_nonblocking(true)
+        .and_then(|()| stream.set_write_timeout(Some(WRITE_TIMEOUT)))
+    {
+        tracing::warn!("failed to configure sway ipc connection: {err}");
+        return;
+    }
+
+    let connection = state.sway_ipc.next_connection;
+    state.sway_ipc.next_connection += 1;
+    let mut buffer = Vec::new();
+    let result = state.loop_handle.insert_source(
+        Generic::new(stream, Interest::READ, Mode::Level),
+        move |_, stream, state| {
+            let stream: &UnixStream = stream;
+            let mut closed = false;
+            let mut chunk = [0u8; 4096];
+            loop {
+                match (&*stream).read(&mut chunk) {
+                    Ok(0) => {
+                        closed = true;
+                        break;
+                    }
+                    Ok(read) => {
+                        buffer.extend_from_slice(&chunk[..read]);
+                        // Parse what is there before reading on, so a client that
+                        // keeps writing can't grow the buffer past one message.
+                        if buffer.len() > HEADER_LEN + MAX_PAYLOAD_LEN {
+                            break;
+                        }
+                    }
+                    Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => break,
+                    Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+                    Err(err) => {
+                        tracing::debug!("sway ipc read failed: {err}");
+                        return Ok(PostAction::Remove);
+                    }
+                }
+            }
+
+            loop {
+                match take_message(&mut buffer) {
+                    Ok(Some((message_type, payload))) => {
+                        let reply =
+                            handle_message(state, stream, connection, message_type, &payload);
+                        if let Err(err) =
+                            send_reply(state, stream, connection, message_type, &reply)
+                        {
+                            tracing::debug!("sway ipc write failed: {err}");
+                            return Ok(PostAction::Remove);
+                        }
+                    }
+                    Ok(None) => break,
+                    Err(err) => {
+                        tracing::debug!("dropping sway ipc connection: {err}");
+                        return Ok(PostAction::Remove);
+                    }
+                }
+            }
+
+            Ok(if closed {
+                PostAction::Remove
+            } else {
+                PostAction::Continue
+            })
+        },
+    );
+    if let Err(err) = result {
+        tracing::warn!("failed to register sway ipc connection: {err}");
+    }
+}
+
+fn take_message(buffer: &mut Vec<u8>) -> Result<Option<(u32, String)>, String> {
+    if buffer.len() < HEADER_LEN {
+        return Ok(None);
+    }
+    if &buffer[..MAGIC.len()] != MAGIC {
+        return Err("bad magic".to_owned());
+    }
+    let word = |offset: usize| {
+        u32::from_ne_bytes(buffer[offset..offset + 4].try_into().expect("4-byte slice"))
+    };
+    let length = word(MAGIC.len()) as usize;
+    let message_type = word(MAGIC.len() + 4);
+    if length > MAX_PAYLOAD_LEN {
+        return Err(format!("payload of {length} bytes is too large"));
+    }
+    if buffer.len() < HEADER_LEN + length {
+        return Ok(None);
+    }
+
+    let payload = String::from_utf8_lossy(&buffer[HEADER_LEN..HEADER_LEN + length]).into_owned();
+    buffer.drain(..HEADER_LEN + length);
+    Ok(Some((message_type, payload)))
+}
+
+fn encode_message(message_type: u32, payload: &str) -> Vec<u8> {
+    let mut message = Vec::with_capacity(HEADER_LEN + payload.len());
+    message.extend_from_slice(MAGIC);
+    message.extend_from_slice(&(payload.len() as u32).to_ne_bytes());
+    message.extend_from_slice(&message_type.to_ne_bytes());
+    message.extend_from_slice(payload.as_bytes());
+    message
+}
+
+fn write_message(stream: &UnixStream, message_type: u32, payload: &str) -> std::io::Result<()> {
+    let message = encode_message(message_type, payload);
+    // Replies are small and clients read them right away; the write timeout keeps a
+    // stuck client from stalling the compositor.
+    stream.set_nonblocking(false)?;
+    let result = (&*stream).write_all(&message);
+    stream.set_nonblocking(true)?;
+    result
+}
+
+/// Reply on a subscribed connection through its outbox, so the reply can't land in
+/// the middle of a half-written event.
+fn send_reply(
+    state: &mut Raven,
+    stream: &UnixStream,
+    connection: u64,
+    message_type: u32,
+    payload: &str,
+) -> std::io::Result<()> {
+    let loop_handle = &state.loop_handle;
+    match state
+        .sway_ipc
+        .subscribers
+        .iter_mut()
+        .find(|subscriber| subscriber.connection == connection)
+    {
+        Some(subscriber) => queue_message(loop_handle, subscriber, message_type, payload),
+        None => write_message(stream, message_type, payload),
+    }
+}
+
+/// Append a message to the subscriber's outbox and write what the socket takes without
+/// blocking; the rest goes out from a writable source once the client reads again.
+fn queue_message(
+    loop_handle: &LoopHandle<'static, Raven>,
+    subscriber: &mut Subscriber,
+    message_type: u32,
+    payload: &str,
+) -> std::io::Result<()> {
+    if subscriber.outbox.len() > MAX_QUEUED_LEN {
+        return Err(std::io::Error::other("client stopped reading events"));
+    }
+    subscriber
+        .outbox
+        .extend_from_slice(&encode_message(message_type, payload));
+    flush_outbox(subscriber)?;
+    if !subscriber.outbox.is_empty() && subscriber.flush.is_none() {
+        let connection = subscriber.connection;
+        let token = loop_handle
+            .insert_source(
+                Generic::new(subscriber.stream.try_clone()?, Interest::WRITE, Mode::Level),
+                move |_, _, state| Ok(flush_subscriber(state, connection)),
+            )
+            .map_err(|err| std::io::Error::other(err.to_string()))?;
+        subscriber.flush = Some(token);
+    }
+    Ok(())
+}
+
+fn flush_outbox(subscriber: &mut Subscriber) -> std::io::Result<()> {
+    while !subscriber.outbox.is_empty() {
+        match (&subscriber.stream).write(&subscriber.outbox) {
+            Ok(0) => return Err(std::io::ErrorKind::WriteZero.into()),
+            Ok(written) => {
+                subscriber.outbox.drain(..written);
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => break,
+            Err(err) if err.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(())
+}
+
+fn flush_subscriber(state: &mut Raven, connection: u64) -> PostAction {
+    let subscribers = &mut state.sway_ipc.subscribers;
+    let Some(index) = subscribers
+        .iter()
+        .position(|subscriber| subscriber.connection == connection)
+    else {
+        return PostAction::Remove;
+    };
+    if let Err(err) = flush_outbox(&mut subscribers[index]) {
+        tracing::debug!("dropping sway ipc subscriber: {err}");
+        subscribers.remove(index);
+        return PostAction::Remove;
+    }
+    if subscribers[index].outbox.is_empty() {
+        subscribers[index].flush = None;
+        PostAction::Remove
+    } else {
+        PostAction::Continue
+    }
+}
+
+/// Queue an event for `subscriber`; false when it has to be dropped.
+fn send_event(
+    loop_handle: &LoopHandle<'static, Raven>,
+    subscriber: &mut Subscriber,
+    event_type: u32,
+    event: &str,
+) -> bool {
+    match queue_message(loop_handle, subscriber, event_type, event) {
+        Ok(()) => true,
+        Err(err) => {
+            tracing::debug!("dropping sway ipc subscriber: {err}");
+            if let Some(token) = subscriber.flush.take() {
+                loop_handle.remove(token);
+            }
+            false
+        }
+    }
+}
+
+fn handle_message(
+    state: &mut Raven,
+    stream: &UnixStream,
+    connection: u64,
+    message_type: u32,
+    payload: &str,
+) -> String {
+    match message_type {
+        RUN_COMMAND => run_commands(state, payload),
+        GET_WORKSPACES => workspaces_json(state),
+        SUBSCRIBE => subscribe(state, stream, connection, payload),
+        GET_TREE => tree_json(state),
+        GET_INPUTS => format!("[{}]", keyboard_input_json(state)),
+        other => {
+            tracing::debug!(message_type = other, "unsupported sway ipc message");
+            format!(
+                r#"{{"success":false,"error":{}}}"#,
+                json_string(&format!("unsupported message type {other}"))
+            )
+        }
+    }
+}
+
+fn run_commands(state: &mut Raven, payload: &str) -> String {
+    let results = split_commands(payload)
+        .into_iter()
+        .map(|command| {
+            match config_import::sway_command_action(&command, &state.config.workspace_names) {
+                Some(action) => {
+                    state.dispatch_action(action);
+                    r#"{"success":true}"#.to_owned()
+                }
+                None => format!(
+                    r#"{{"success":false,"parse_error":true,"error":{}}}"#,
+                    json_string(&format!("unsupported command `{command}`"))
+                ),
+            }
+        })
+        .collect::<Vec<_>>();
+    format!("[{}]", results.join(","))
+}
+
+/// Split on `;` and `,` outside quotes, the way sway separates chained commands.
+fn split_commands(payload: &str) -> Vec<String> {
+    let mut commands = Vec::new();
+    let mut current = String::new();
+    let mut quote = None;
+    for ch in payload.chars() {
+        match (quote, ch) {
+            (Some(open), ch) if ch == open => {
+                quote = None;
+                current.push(ch);
+            }
+            (None, '"' | '\'') => {
+                quote = Some(ch);
+                current.push(ch);
+            }
+            (None, ';' | ',') => commands.push(std::mem::take(&mut current)),
+            (_, ch) => current.push(ch),
+        }
+    }
+    commands.push(current);
+    commands
+        .into_iter()
+        .map(|command| command.trim().to_owned())
+        .filter(|command| !command.is_empty())
+        .collect()
+}
+
+fn subscribe(state: &mut Raven, stream: &UnixStream, connection: u64, payload: &str) -> String {
+    let events = quoted_strings(payload);
+    let workspace = events.iter().any(|event| event == "workspace");
+    let output = events.iter().any(|event| event == "output");
+    let window = events.iter().any(|event| event == "window");
+    let input = events.iter().any(|event| event == "input");
+    let progress = events.iter().any(|event| event == "progress");
+    if events.iter().any(|event| {
+        !matches!(
+            event.as_str(),
+            "workspace" | "output" | "window" | "input" | "progress"
+        )
+    }) {
+        tracing::debug!(?events, "ignoring unsupported sway ipc event subscriptions");
+    }
+
+    // A second SUBSCRIBE on the same connection adds to the first.
+    if let Some(subscriber) = state
+        .sway_ipc
+        .subscribers
+        .iter_mut()
+        .find(|subscriber| subscriber.connection == connection)
+    {
+        subscriber.workspace |= workspace;
+        subscriber.output |= output;
+        subscriber.window |= window;
+        subscriber.input |= input;
+        subscriber.progress |= progress;
+        return r#"{"success":true}"#.to_owned();
+    }
+
+    let stream = match stream.try_clone() {
+        Ok(stream) => stream,
+        Err(err) => {
+            tracing::warn!("failed to clone sway ipc subscriber stream: {err}");
+            return r#"{"success":false}"#.to_owned();
+        }
+    };
+    if state.sway_ipc.subscribers.is_empty() {
+        // Start diffing from the current state so a new subscriber isn't flooded.
+        record_snapshot(state);
+    }
+    state.sway_ipc.subscribers.push(Subscriber {
+        connection,
+        stream,
+        outbox: Vec::new(),
+        flush: None,
+        workspace,
+        output,
+        window,
+        input,
+        progress,
+    });
+    r#"{"success":true}"#.to_owned()
+}
+
+fn quoted_strings(payload: &str) -> Vec<String> {
+    payload
+        .split('"')
+        .skip(1)
+        .step_by(2)
+        .map(str::to_owned)
+        .collect()
+}
+
+/// Output workspace `index` is on, as sway puts each workspace on one output:
+/// the one most of its windows tile on, or the primary output while it is empty.
+fn workspace_output(state: &Raven, index: usize) -> Option<(String, Rectangle<i32, Logical>)> {
+    let mut counts: Vec<(Output, usize)> = Vec::new();
+    for window in state.workspaces.get(index).into_iter().flatten() {
+        let Some(output) = state.window_output(window) else {
+            continue;
+        };
+        match counts
+            .iter_mut()
+            .find(|(candidate, _)| *candidate == output)
+        {
+            Some((_, count)) => *count += 1,
+            None => counts.push((output, 1)),
+        }
+    }
+    // Stable, so ties go to the output seen first.
+    counts.sort_by(|a, b| b.1.cmp(&a.1));
+    let output = counts
+        .into_iter()
+        .next()
+        .map(|(output, _)| output)
+        .or_else(|| state.space.outputs().next().cloned())?;
+    let geometry = state.space.output_geometry(&output)?;
+    Some((output.name(), geometry))
+}
+
+fn focused_surface(state: &Raven) -> Option<WlSurface> {
+    state
+        .seat
+        .get_keyboard()
+        .and_then(|keyboard| keyboard.current_focus())
+}
+
+pub(super) fn listed_workspaces(state: &Raven) -> Vec<usize> {
+    (0..state.workspaces.len())
+        .filter(|&index| index == state.current_workspace || !state.workspaces[index].is_empty())
+        .collect()
+}
+
+pub(super) fn rect_json(rect: Rectangle<i32, Logical>) -> String {
+    format!(
+        r#"{{"x":{},"y":{},"width":{},"height":{}}}"#,
+        rect.loc.x, rect.loc.y, rect.size.w, rect.size.h
+    )
+}
+
+pub(super) fn json_string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            ch if (ch as u32) < 0x20 => {
+                let _ = write!(out, "\\u{:04x}", ch as u32);
+            }
+            ch => out.push(ch),
+        }
+    }
+    out.push('"');
+    out
+}
+
+pub(super) fn json_optional_string(value: Option<&str>) -> String {
+    value.map_or_else(|| "null".to_owned(), json_string)
+}
+
+fn workspace_fields(state: &Raven, index: usize) -> String {
+    let (output_name, rect) = workspace_output(state, index).unwrap_or_default();
+    let focused = index == state.current_workspace;
+    let urgent = state.is_workspace_urgent(index);
+    format!(
+        r#""id":{},"type":"workspace","num":{},"name":{},"visible":{focused},"focused":{focused},"urgent":{urgent},"output":{},"rect":{}"#,
+        WORKSPACE_NODE_ID_BASE + index as u64,
+        index + 1,
+        json_string(&state.config.workspace_label(index)),
+        json_string(&output_name),
+        rect_json(rect)
+    )
+}
+
+fn workspace_json(state: &Raven, index: usize) -> String {
+    format!("{{{}}}", workspace_fields(state, index))
+}
+
+fn workspaces_json(state: &Raven) -> String {
+    let workspaces = listed_workspaces(state)
+        .into_iter()
+        .map(|index| workspace_json(state, index))
+        .collect::<Vec<_>>();
+    format!("[{}]", workspaces.join(","))
+}
+
+fn window_json(state: &mut Raven, window: &Window) -> Option<String> {
+    let surface = window.toplevel()?.wl_surface().clone();
+    let node_id = node_id(state, &surface);
+    let (app_id, title) = Raven::surface_app_id_and_title(&surface);
+    let pid = surface
+        .client()
+        .and_then(|client| client.get_credentials(&state.display_handle).ok())
+        .map_or_else(
+            || "null".to_owned(),
+            |credentials| credentials.pid.to_string(),
+        );
+    let floating = state.is_window_floating(window);
+    let rect = state
+        .space
+        .element_geometry(window)
+        .unwrap_or_else(|| window.geometry());
+    let focused = focused_surface(state).as_ref() == Some(&surface);
+    let visible = state.is_window_mapped(window);
+    let urgent = state.is_window_urgent(&surface);
+    let fullscreen_mode = u8::from(state.window_effective_fullscreen_state(window));
+
+    Some(format!(
+        r#"{{"id":{node_id},"type":{},"name":{},"app_id":{},"pid":{pid},"focused":{focused},"visible":{visible},"urgent":{urgent},"fullscreen_mode":{fullscreen_mode},"rect":{},"nodes":[],"floating_nodes":[]}}"#,
+        json_string(if floating { "floating_con" } else { "con" }),
+        json_optional_string(title.as_deref()),
+        json_optional_string(app_id.as_deref()),
+        rect_json(rect)
+    ))
+}
+
+fn tree_json(state: &mut Raven) -> String {
+    let outputs = state
+        .space
+        .outputs()
+        .filter_map(|output| {
+            state
+                .space
+                .output_geometry(output)
+                .map(|geometry| (output.name(), geometry))
+        })
+        .collect::<Vec<_>>();
+    let root_rect = outputs
+        .iter()
+        .map(|(_, geometry)| *geometry)
+        .reduce(|acc, geometry| acc.merge(geometry))
+        .unwrap_or_default();
+
+    // Each workspace goes under the output it is on; see `workspace_output`.
+    let mut workspace_nodes = vec![Vec::new(); outputs.len()];
+    let mut current_output = None;
+    for index in listed_workspaces(state) {
+        let output_index = workspace_output(state, index)
+            .and_then(|(output_name, _)| outputs.iter().position(|(name, _)| *name == output_name))
+            .unwrap_or(0);
+        if index == state.current_workspace {
+            current_output = Some(output_index);
+        }
+
+        let windows = state.workspaces[index].clone();
+        let mut tiled = Vec::new();
+        let mut floating = Vec::new();
+        for window in &windows {
+            let Some(node) = window_json(state, window) else {
+                continue;
+            };
+            if state.is_window_floating(window) {
+                floating.push(node);
+            } else {
+                tiled.push(node);
+            }
+        }
+        if let Some(nodes) = workspace_nodes.get_mut(output_index) {
+            nodes.push(format!(
+                r#"{{{},"nodes":[{}],"floating_nodes":[{}]}}"#,
+                workspace_fields(state, index),
+                tiled.join(","),
+                floating.join(",")
+            ));
+        }
+    }
+
+    let mut output_nodes = Vec::new();
+    for (output_index, ((name, geometry), workspace_nodes)) in
+        outputs.iter().zip(workspace_nodes).enumerate()
+    {
+        let focused = current_output == Some(output_index);
+        let current_workspace = if focused {
+            json_string(&state.config.workspace_label(state.current_workspace))
+        } else {
+            "null".to_owned()
+        };
+        output_nodes.push(format!(
+            r#"{{"id":{},"type":"output","name":{},"active":true,"focused":{focused},"current_workspace":{current_workspace},"rect":{},"nodes":[{}],"floating_nodes":[]}}"#,
+            OUTPUT_NODE_ID_BASE + output_index as u64,
+            json_string(name),
+            rect_json(*geometry),
+            workspace_nodes.join(",")
+        ));
+    }
+
+    format!(
+        r#"{{"id":{ROOT_NODE_ID},"type":"root","name":"root","focused":false,"rect":{},"nodes":[{}],"floating_nodes":[]}}"#,
+        rect_json(root_rect),
+        output_nodes.join(",")
+    )
+}
+
+/// Index of the seat keyboard's active xkb group.
+fn active_layout(state: &Raven) -> usize {
+    state.seat.get_keyboard().map_or(0, |keyboard| {
+        keyboard.modifier_state().serialized.layout_effective as usize
+    })
+}
+
+fn keyboard_input_json(state: &Raven) -> String {
+    let layouts = state.keymap.layout_names();
+    let active_index = active_layout(state);
+    let active = layouts.get(active_index).copied();
+    let names = layouts
+        .iter()
+        .map(|name| json_string(name))
+        .collect::<Vec<_>>();
+    format!(
+        r#"{{"identifier":{},"name":"Raven keyboard","type":"keyboard","xkb_layout_names":[{}],"xkb_active_layout_index":{active_index},"xkb_active_layout_name":{}}}"#,
+        json_string(KEYBOARD_IDENTIFIER),
+        names.join(","),
+        json_optional_string(active)
+    )
+}
+
+/// Notify `input` subscribers that the seat keyboard's keymap (`xkb_keymap`) or
+/// active group (`xkb_layout`) changed.
+pub(super) fn broadcast_input_event(state: &mut Raven, change: &str) {
+    state.sway_ipc.last_layout = active_layout(state);
+    if !state
+        .sway_ipc
+        .subscribers
+        .iter()
+        .any(|subscriber| subscriber.input)
+    {
+        return;
+    }
+
+    let event = format!(
+        r#"{{"change":{},"input":{}}}"#,
+        json_string(change),
+        keyboard_input_json(state)
+    );
+    let loop_handle = &state.loop_handle;
+    state.sway_ipc.subscribers.retain_mut(|subscriber| {
+        !subscriber.input || send_event(loop_handle, subscriber, EVENT_INPUT, &event)
+    });
+}
+
+/// Tell `output` subscribers which mode an output came up with. `reason` says whether
+/// the configured mode, a fallback, or the connector's preferred mode was used.
+pub(super) fn broadcast_output_mode_event(
+    state: &mut Raven,
+    output: &str,
+    mode: smithay::output::Mode,
+    reason: &str,
+) {
+    let event = format!(
+        r#"{{"change":"mode","output":{},"mode":{{"width":{},"height":{},"refresh":{}}},"reason":{}}}"#,
+        json_string(output),
+        mode.size.w,
+        mode.size.h,
+        mode.refresh,
+        json_string(reason)
+    );
+    let loop_handle = &state.loop_handle;
+    state.sway_ipc.subscribers.retain_mut(|subscriber| {
+        !subscriber.output || send_event(loop_handle, subscriber, EVENT_OUTPUT, &event)
+    });
+}
+
+/// Send a `progress` event (see `progress::report`) to its subscribers.
+pub(super) fn broadcast_progress_event(state: &mut Raven, event: &str) {
+    let loop_handle = &state.loop_handle;
+    state.sway_ipc.subscribers.retain_mut(|subscriber| {
+        !subscriber.progress || send_event(loop_handle, subscriber, EVENT_PROGRESS, event)
+    });
+}
+
+fn snapshot_windows(state: &mut Raven) -> HashMap<WlSurface, WindowSnapshot> {
+    let surfaces = state
+        .workspaces
+        .iter()
+        .flatten()
+        .filter_map(|window| {
+            window
+                .toplevel()
+                .map(|toplevel| toplevel.wl_surface().clone())
+        })
+        .collect::<HashSet<_>>();
+
+    surfaces
+        .into_iter()
+        .map(|surface| {
+            let (app_id, title) = Raven::surface_app_id_and_title(&surface);
+            let snapshot = WindowSnapshot {
+                node_id: node_id(state, &surface),
+                app_id,
+                title,
+                urgent: state.is_window_urgent(&surface),
+            };
+            (surface, snapshot)
+        })
+        .collect()
+}
+
+fn record_snapshot(state: &mut Raven) {
+    state.sway_ipc.windows = snapshot_windows(state);
+    state.sway_ipc.windows_changed = false;
+    state.sway_ipc.last_workspace = Some(state.current_workspace);
+    state.sway_ipc.last_focus = focused_surface(state);
+    state.sway_ipc.last_layout = active_layout(state);
+}
+
+fn closed_window_json(snapshot: &WindowSnapshot) -> String {
+    format!(
+        r#"{{"id":{},"type":"con","name":{},"app_id":{},"focused":false,"visible":false,"nodes":[],"floating_nodes":[]}}"#,
+        snapshot.node_id,
+        json_optional_string(snapshot.title.as_deref()),
+        json_optional_string(snapshot.app_id.as_deref())
+    )
+}
+
+fn window_event(state: &mut Raven, change: &str, surface: &WlSurface) -> Option<String> {
+    let window = state.window_for_surface(surface)?;
+    let container = window_json(state, &window)?;
+    Some(format!(
+        r#"{{"change":{},"container":{container}}}"#,
+        json_string(change)
+    ))
+}
+
+/// Have the next `broadcast_events` pass re-snapshot the windows.
+pub(super) fn note_windows_changed(state: &mut Raven) {
+    state.sway_ipc.windows_changed = true;
+}
+
+/// Diff the current workspace/window/keyboard group state against the last pass and
+/// send `workspace`, `window` and `xkb_layout` input events to subscribers.
+pub(super) fn broadcast_events(state: &mut Raven) {
+    if state.sway_ipc.subscribers.is_empty() {
+        return;
+    }
+
+    // Group switches happen inside xkb (grp: options, layout keys), so they are
+    // noticed here rather than where the key came in.
+    if active_layout(state) != state.sway_ipc.last_layout {
+        broadcast_input_event(state, "xkb_layout");
+    }
+
+    let mut workspace_events = Vec::new();
+    let mut window_events = Vec::new();
+
+    let previous_workspace = state.sway_ipc.last_workspace;
+    if previous_workspace != Some(state.current_workspace) {
+        let old = previous_workspace
+            .filter(|&index| index < state.workspaces.len())
+            .map_or_else(|| "null".to_owned(), |index| workspace_json(state, index));
+        workspace_events.push(format!(
+            r#"{{"change":"focus","current":{},"old":{old}}}"#,
+            workspace_json(state, state.current_workspace)
+        ));
+    }
+
+    // Titles, app ids and the window set only change on commits, closes and urgency
+    // changes, so the windows aren't snapshotted on every loop iteration.
+    if std::mem::take(&mut state.sway_ipc.windows_changed) {
+        let windows = snapshot_windows(state);
+        let previous_windows = std::mem::take(&mut state.sway_ipc.windows);
+        for (surface, snapshot) in &previous_windows {
+            if !windows.contains_key(surface) {
+                window_events.push(format!(
+                    r#"{{"change":"close","container":{}}}"#,
+                    closed_window_json(snapshot)
+                ));
+            }
+        }
+        for (surface, snapshot) in &windows {
+            let change = match previous_windows.get(surface) {
+                None => "new",
+                Some(previous) if previous.title != snapshot.title => "title",
+                Some(previous) if previous.urgent != snapshot.urgent => "urgent",
+                Some(_) => continue,
+            };
+            // Bars mark the workspace of an urgent window from workspace events.
+            if change == "urgent"
+                && let Some(index) = state
+                    .window_for_surface(surface)
+                    .and_then(|window| state.workspace_index_for_window(&window))
+            {
+                workspace_events.push(format!(
+                    r#"{{"change":"urgent","current":{}}}"#,
+                    workspace_json(state, index)
+                ));
+            }
+            if let Some(event) = window_event(state, change, surface) {
+                window_events.push(event);
+            }
+        }
+        state.sway_ipc.windows = windows;
+    }
+
+    let focus = focused_surface(state);
+    if focus != state.sway_ipc.last_focus
+        && let Some(surface) = focus.as_ref()
+        && let Some(event) = window_event(state, "focus", surface)
+    {
+        window_events.push(event);
+    }
+
+    state.sway_ipc.last_workspace = Some(state.current_workspace);
+    state.sway_ipc.last_focus = focus;
+
+    if workspace_events.is_empty() && window_events.is_empty() {
+        return;
+    }
+    let loop_handle = &state.loop_handle;
+    state.sway_ipc.subscribers.retain_mut(|subscriber| {
+        let workspace = subscriber
+            .workspace
+            .then_some(&workspace_events)
+            .into_iter()
+            .flatten()
+            .map(|event| (EVENT_WORKSPACE, event));
+        let window = subscriber
+            .window
+            .then_some(&window_events)
+            .into_iter()
+            .flatten()
+            .map(|event| (EVENT_WINDOW, event));
+        workspace
+            .chain(window)
+            .all(|(event_type, event)| send_event(loop_handle, subscriber, event_type, event))
+    });
+}
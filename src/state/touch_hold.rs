@@ -0,0 +1,181 @@
+//! Hold-to-right-click for touchscreens without a pointer of their own.
+//!
+//! Raven does not forward touch to clients yet, so a finger resting still for
+//! `input.touch_hold_right_click` ms is the only way to reach context menus on
+//! a touch-only device. While the finger is down a square marks the spot; it
+//! fills in when the click fires.
+
+use std::time::Duration;
+
+use smithay::{
+    backend::{
+        input::{ButtonState, TouchSlot},
+        renderer::element::Kind,
+    },
+    input::pointer::{ButtonEvent, MotionEvent},
+    output::Output,
+    reexports::calloop::{
+        RegistrationToken,
+        timer::{TimeoutAction, Timer},
+    },
+    utils::{Logical, Point, SERIAL_COUNTER},
+};
+
+use crate::render_helpers::{SolidColorBuffer, SolidColorRenderElement};
+
+use super::Raven;
+
+// linux/input-event-codes.h
+const BTN_RIGHT: u32 = 0x111;
+// Fingers wobble; moving further than this turns the hold into a drag.
+const HOLD_SLOP: f64 = 12.0;
+const MARKER_SIZE: f64 = 36.0;
+const MARKER_THICKNESS: f64 = 3.0;
+const MARKER_COLOR: [f32; 4] = [1.0, 1.0, 1.0, 0.85];
+
+/// A finger that may still turn into a right-click.
+pub(super) struct TouchHold {
+    slot: TouchSlot,
+    origin: Point<f64, Logical>,
+    timer: Option<RegistrationToken>,
+    fired: bool,
+}
+
+pub(super) fn touch_down(state: &mut Raven, slot: TouchSlot, location: Point<f64, Logical>) {
+    let delay = state.config.input.touch_hold_right_click_ms;
+    if delay == 0 {
+        return;
+    }
+    // Only the first finger counts; a second one makes it a multi-finger gesture.
+    if state.touch_hold.is_some() {
+        cancel(state);
+        return;
+    }
+
+    let timer = Timer::from_duration(Duration::from_millis(u64::from(delay)));
+    let timer = match state.loop_handle.insert_source(timer, |_, _, state| {
+        fire(state);
+        TimeoutAction::Drop
+    }) {
+        Ok(token) => Some(token),
+        Err(err) => {
+            tracing::warn!("failed to schedule touch hold timer: {err}");
+            return;
+        }
+    };
+    state.touch_hold = Some(TouchHold {
+        slot,
+        origin: location,
+        timer,
+        fired: false,
+    });
+    crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Input);
+}
+
+pub(super) fn touch_motion(state: &mut Raven, slot: TouchSlot, location: Point<f64, Logical>) {
+    let Some(hold) = state.touch_hold.as_ref() else {
+        return;
+    };
+    if hold.slot != slot || hold.fired {
+        return;
+    }
+    let delta = location - hold.origin;
+    if delta.x.hypot(delta.y) > HOLD_SLOP {
+        cancel(state);
+    }
+}
+
+pub(super) fn touch_up(state: &mut Raven, slot: TouchSlot) {
+    if state
+        .touch_hold
+        .as_ref()
+        .is_some_and(|hold| hold.slot == slot)
+    {
+        cancel(state);
+    }
+}
+
+pub(super) fn cancel(state: &mut Raven) {
+    let Some(hold) = state.touch_hold.take() else {
+        return;
+    };
+    if let Some(timer) = hold.timer {
+        state.loop_handle.remove(timer);
+    }
+    crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Input);
+}
+
+fn fire(state: &mut Raven) {
+    let Some(hold) = state.touch_hold.as_mut() else {
+        return;
+    };
+    hold.timer = None;
+    hold.fired = true;
+    let location = hold.origin;
+    tracing::debug!(x = location.x, y = location.y, "touch hold: right-click");
+
+    let pointer = state.pointer();
+    if !pointer.is_grabbed() {
+        state.pointer_location = location;
+        let under = state.contents_under(location);
+        state.pointer_contents.clone_from(&under);
+        let time = state.start_time.elapsed().as_millis() as u32;
+        pointer.motion(
+            state,
+            under.surface,
+            &MotionEvent {
+                location,
+                serial: SERIAL_COUNTER.next_serial(),
+                time,
+            },
+        );
+        for button_state in [ButtonState::Pressed, ButtonState::Released] {
+            pointer.button(
+                state,
+                &ButtonEvent {
+                    button: BTN_RIGHT,
+                    state: button_state,
+                    serial: SERIAL_COUNTER.next_serial(),
+                    time,
+                },
+            );
+        }
+        pointer.frame(state);
+    }
+    crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Animation);
+}
+
+/// The hold marker on `output`: an outline while waiting, filled once fired.
+pub(super) fn touch_hold_elements(state: &Raven, output: &Output) -> Vec<SolidColorRenderElement> {
+    let Some(hold) = state.touch_hold.as_ref() else {
+        return Vec::new();
+    };
+    let Some(output_geo) = state.space.output_geometry(output) else {
+        return Vec::new();
+    };
+    if !output_geo.to_f64().contains(hold.origin) {
+        return Vec::new();
+    }
+
+    let origin =
+        hold.origin - output_geo.loc.to_f64() - Point::from((MARKER_SIZE / 2.0, MARKER_SIZE / 2.0));
+    let far = MARKER_SIZE - MARKER_THICKNESS;
+    let pieces: Vec<(Point<f64, Logical>, (f64, f64))> = if hold.fired {
+        vec![(origin, (MARKER_SIZE, MARKER_SIZE))]
+    } else {
+        vec![
+            (origin, (MARKER_SIZE, MARKER_THICKNESS)),
+            (origin + (0.0, far).into(), (MARKER_SIZE, MARKER_THICKNESS)),
+            (origin, (MARKER_THICKNESS, MARKER_SIZE)),
+            (origin + (far, 0.0).into(), (MARKER_THICKNESS, MARKER_SIZE)),
+        ]
+    };
+
+    pieces
+        .into_iter()
+        .map(|(location, size)| {
+            let buffer = SolidColorBuffer::new(size, MARKER_COLOR);
+            SolidColorRenderElement::from_buffer(&buffer, location, 1.0, Kind::Unspecified)
+        })
+        .collect()
+}
@@ -0,0 +1,82 @@
+//! Urgent windows. A window that asks for activation and is refused focus (the
+//! token is stale, it sits on another workspace, or it opens on another output
+//! without a keybind launch) wants attention instead. X11 clients run through
+//! xwayland-satellite, so their urgency hint arrives as such a request too.
+//!
+//! Urgency shows in `raven clients`, as the urgent state of the window's
+//! ext-workspace and sway IPC workspace, and as an `urgent_border_color` border.
+//! It clears once the window gets keyboard focus; `focus_urgent` goes there.
+
+use std::time::Instant;
+
+use smithay::{reexports::wayland_server::protocol::wl_surface::WlSurface, utils::SERIAL_COUNTER};
+
+use super::{Raven, sway_ipc};
+
+fn queue_redraw(state: &mut Raven) {
+    if state.config.urgent_border_color.is_some() {
+        crate::backend::udev::queue_redraw_all(state, crate::backend::udev::RedrawCause::Ipc);
+    }
+}
+
+/// Mark `surface` urgent, unless it already has keyboard focus.
+pub(super) fn mark(state: &mut Raven, surface: &WlSurface) {
+    let focused = state
+        .seat
+        .get_keyboard()
+        .and_then(|keyboard| keyboard.current_focus());
+    if focused.as_ref() == Some(surface) || state.window_for_surface(surface).is_none() {
+        return;
+    }
+    tracing::debug!(window = ?state.window_id(surface), "window marked urgent");
+    state.urgent_windows.insert(surface.clone(), Instant::now());
+    sway_ipc::note_windows_changed(state);
+    queue_redraw(state);
+}
+
+/// Drop the urgency of a window that got keyboard focus.
+pub(super) fn focused(state: &mut Raven, surface: Option<&WlSurface>) {
+    if surface.is_some_and(|surface| state.urgent_windows.remove(surface).is_some()) {
+        sway_ipc::note_windows_changed(state);
+        queue_redraw(state);
+    }
+}
+
+/// Whether a window on workspace `index` is urgent.
+pub(super) fn workspace_urgent(state: &Raven, index: usize) -> bool {
+    state.workspaces.get(index).is_some_and(|windows| {
+        windows.iter().any(|window| {
+            window
+                .toplevel()
+                .is_some_and(|toplevel| state.urgent_windows.contains_key(toplevel.wl_surface()))
+        })
+    })
+}
+
+/// The `focus_urgent` action: switch to the workspace of the window that most
+/// recently became urgent and focus it.
+pub(super) fn focus_urgent(state: &mut Raven) {
+    let Some((surface, window)) = state
+        .urgent_windows
+        .iter()
+        .filter_map(|(surface, since)| {
+            let window = state.window_for_surface(surface)?;
+            Some((since, surface.clone(), window))
+        })
+        .max_by_key(|(since, ..)| **since)
+        .map(|(_, surface, window)| (surface, window))
+    else {
+        tracing::debug!("focus_urgent: no urgent window");
+        return;
+    };
+
+    if let Some(workspace) = state.workspace_index_for_window(&window)
+        && workspace != state.current_workspace
+        && let Err(err) = state.switch_workspace(workspace)
+    {
+        tracing::warn!("failed to switch to the urgent window's workspace: {err}");
+        return;
+    }
+    state.raise_window_preserving_layer(&window);
+    state.set_keyboard_focus(Some(surface), SERIAL_COUNTER.next_serial());
+}
@@ -0,0 +1,142 @@
+//! Wallpaper slideshow: with `wallpaper.slideshow_directory` set, the swww path
+//! shows the images of that directory in turn, one every `slideshow_interval`
+//! seconds. A fullscreen window on the current workspace holds the slideshow,
+//! so games and videos are not disturbed by transitions behind them.
+
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use smithay::reexports::calloop::{
+    RegistrationToken,
+    timer::{TimeoutAction, Timer},
+};
+
+use super::{Raven, WindowFullscreenMode};
+
+const IMAGE_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "webp", "gif", "bmp", "tiff"];
+
+#[derive(Default)]
+pub(super) struct WallpaperSlideshow {
+    // Directory and interval the timer runs for; a reload re-arms it on change.
+    armed: Option<(PathBuf, Duration, RegistrationToken)>,
+    current: Option<PathBuf>,
+}
+
+fn slideshow_images(directory: &Path) -> Vec<PathBuf> {
+    let entries = match std::fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(err) => {
+            tracing::warn!(
+                path = %directory.display(),
+                "failed to read wallpaper slideshow directory: {err}"
+            );
+            return Vec::new();
+        }
+    };
+    let mut images: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .is_some_and(|extension| {
+                    IMAGE_EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+                })
+        })
+        .collect();
+    images.sort();
+    images
+}
+
+/// Image the slideshow is on, when one is configured.
+pub(super) fn current_image(state: &Raven) -> Option<PathBuf> {
+    state
+        .wallpaper_slideshow
+        .armed
+        .as_ref()
+        .and(state.wallpaper_slideshow.current.clone())
+}
+
+/// Start, re-arm or stop the slideshow to match the config. Call before
+/// `apply_wallpaper` so it picks up the current image.
+pub(super) fn sync(state: &mut Raven) {
+    let wallpaper = &state.config.wallpaper;
+    let directory = wallpaper.slideshow_directory.trim();
+    let wanted = (wallpaper.enabled && !directory.is_empty()).then(|| {
+        (
+            Raven::expand_home_path(directory),
+            Duration::from_secs(u64::from(wallpaper.slideshow_interval_secs)),
+        )
+    });
+
+    let armed = state
+        .wallpaper_slideshow
+        .armed
+        .as_ref()
+        .map(|(directory, interval, _)| (directory.clone(), *interval));
+    if armed == wanted {
+        return;
+    }
+    if let Some((_, _, timer)) = state.wallpaper_slideshow.armed.take() {
+        state.loop_handle.remove(timer);
+    }
+    let Some((directory, interval)) = wanted else {
+        state.wallpaper_slideshow.current = None;
+        return;
+    };
+
+    let timer = Timer::from_duration(interval);
+    match state.loop_handle.insert_source(timer, move |_, _, state| {
+        advance(state);
+        TimeoutAction::ToDuration(interval)
+    }) {
+        Ok(timer) => {
+            let keep_current = state
+                .wallpaper_slideshow
+                .current
+                .as_ref()
+                .is_some_and(|current| current.parent() == Some(directory.as_path()));
+            if !keep_current {
+                state.wallpaper_slideshow.current = slideshow_images(&directory).into_iter().next();
+            }
+            tracing::info!(
+                path = %directory.display(),
+                interval_secs = interval.as_secs(),
+                "wallpaper slideshow armed"
+            );
+            state.wallpaper_slideshow.armed = Some((directory, interval, timer));
+        }
+        Err(err) => tracing::warn!("failed to schedule wallpaper slideshow: {err}"),
+    }
+}
+
+fn advance(state: &mut Raven) {
+    if state
+        .workspace_effective_exclusive_mode(state.current_workspace)
+        .contains(WindowFullscreenMode::FULLSCREEN)
+    {
+        tracing::debug!("wallpaper slideshow held while a window is fullscreen");
+        return;
+    }
+    let Some((directory, _, _)) = state.wallpaper_slideshow.armed.as_ref() else {
+        return;
+    };
+
+    // Re-read the directory each time so added and removed images are noticed.
+    let images = slideshow_images(directory);
+    let next = state
+        .wallpaper_slideshow
+        .current
+        .as_ref()
+        .and_then(|current| images.iter().position(|image| image > current))
+        .unwrap_or(0);
+    let Some(image) = images.into_iter().nth(next) else {
+        return;
+    };
+    if state.wallpaper_slideshow.current.as_ref() == Some(&image) {
+        return;
+    }
+    state.wallpaper_slideshow.current = Some(image);
+    state.apply_wallpaper();
+}
@@ -0,0 +1,119 @@
+//! Directional moves of the focused window: a tiled window trades places in the
+//! tiling order with the nearest tile in that direction, and a floating window
+//! moves `floating_move_step` pixels.
+
+use smithay::{
+    desktop::Window,
+    utils::{Logical, Point, Rectangle},
+};
+
+use super::Raven;
+use crate::config::Direction;
+
+/// Distance from `from` to `to` when `to` lies wholly in `direction` of it:
+/// the gap along that axis plus the offset of their centers across it.
+fn distance_in_direction(
+    from: Rectangle<i32, Logical>,
+    to: Rectangle<i32, Logical>,
+    direction: Direction,
+) -> Option<i32> {
+    let center = |rect: Rectangle<i32, Logical>| rect.loc + rect.size.downscale(2).to_point();
+    let (from_center, to_center) = (center(from), center(to));
+    let (gap, across) = match direction {
+        Direction::Left => (
+            from.loc.x - (to.loc.x + to.size.w),
+            from_center.y - to_center.y,
+        ),
+        Direction::Right => (
+            to.loc.x - (from.loc.x + from.size.w),
+            from_center.y - to_center.y,
+        ),
+        Direction::Up => (
+            from.loc.y - (to.loc.y + to.size.h),
+            from_center.x - to_center.x,
+        ),
+        Direction::Down => (
+            to.loc.y - (from.loc.y + from.size.h),
+            from_center.x - to_center.x,
+        ),
+    };
+    (gap >= 0).then_some(gap + across.abs())
+}
+
+/// The tile nearest `window`'s in `direction`, among tiles on the same output.
+fn tiled_neighbor(state: &Raven, window: &Window, direction: Direction) -> Option<Window> {
+    let (_, from) = state
+        .tiled_slots
+        .iter()
+        .find(|(candidate, _)| Raven::windows_match(candidate, window))?;
+    let output = state.window_output(window);
+    state
+        .tiled_slots
+        .iter()
+        .filter(|(candidate, _)| {
+            !Raven::windows_match(candidate, window) && state.window_output(candidate) == output
+        })
+        .filter_map(|(candidate, rect)| {
+            distance_in_direction(*from, *rect, direction).map(|distance| (distance, candidate))
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate.clone())
+}
+
+/// Swap two windows of the same workspace in its tiling order.
+pub(super) fn swap_in_workspace(state: &mut Raven, first: &Window, second: &Window) -> bool {
+    let Some(workspace) = state
+        .workspace_index_for_window(first)
+        .and_then(|index| state.workspaces.get_mut(index))
+    else {
+        return false;
+    };
+    let position = |window: &Window| {
+        workspace
+            .iter()
+            .position(|candidate| Raven::windows_match(candidate, window))
+    };
+    let (Some(first), Some(second)) = (position(first), position(second)) else {
+        return false;
+    };
+    workspace.swap(first, second);
+    true
+}
+
+fn move_floating(state: &mut Raven, window: &Window, direction: Direction) {
+    let Some(mut rect) = state.space.element_geometry(window) else {
+        return;
+    };
+    let step = state.config.floating_move_step as i32;
+    rect.loc += match direction {
+        Direction::Left => Point::from((-step, 0)),
+        Direction::Right => Point::from((step, 0)),
+        Direction::Up => Point::from((0, -step)),
+        Direction::Down => Point::from((0, step)),
+    };
+    state.map_window_to_rect(window, rect, false);
+}
+
+pub(super) fn swap_focused_in_direction(state: &mut Raven, direction: Direction) {
+    let Some(window) = state
+        .seat
+        .get_keyboard()
+        .and_then(|keyboard| keyboard.current_focus())
+        .and_then(|surface| state.window_for_surface(&surface))
+    else {
+        return;
+    };
+    if state.is_window_floating(&window) {
+        move_floating(state, &window, direction);
+    } else {
+        let Some(neighbor) = tiled_neighbor(state, &window, direction) else {
+            return;
+        };
+        if !swap_in_workspace(state, &window, &neighbor) {
+            return;
+        }
+    }
+    if let Err(err) = state.apply_layout() {
+        tracing::warn!("failed to apply layout after moving window: {err}");
+    }
+}
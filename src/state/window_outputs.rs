@@ -0,0 +1,89 @@
+//! Which output each toplevel tiles on. A window is assigned the output it opens
+//! on (its `output` window rule, else the one under the pointer) and keeps it by
+//! name, so windows on an unplugged monitor tile on the primary output until it
+//! comes back. Floating windows follow the output they are dragged onto.
+
+use smithay::{
+    desktop::Window,
+    output::Output,
+    utils::{Logical, Point},
+};
+
+use super::{Raven, WlSurface};
+
+fn output_named(state: &Raven, name: &str) -> Option<Output> {
+    state
+        .space
+        .outputs()
+        .find(|output| output.name() == name)
+        .cloned()
+}
+
+/// Output `window` is laid out on: its assigned output while that is connected,
+/// the primary output while it is not, and the output it would open on when it
+/// has no assignment yet.
+pub(super) fn window_output(state: &Raven, window: &Window) -> Option<Output> {
+    let surface = window.toplevel()?.wl_surface();
+    match state.window_outputs.get(surface) {
+        Some(name) => output_named(state, name).or_else(|| state.space.outputs().next().cloned()),
+        None => state.floating_output_for_window(window),
+    }
+}
+
+/// Output a taskbar lists `window` on: the output it tiles on, once it has
+/// been mapped or assigned one. Windows on hidden workspaces and minimized
+/// windows keep theirs.
+pub(super) fn toplevel_output(state: &Raven, window: &Window) -> Option<Output> {
+    let surface = window.toplevel()?.wl_surface();
+    let placed = state.window_outputs.contains_key(surface)
+        || state.space.element_location(window).is_some();
+    placed.then(|| window_output(state, window)).flatten()
+}
+
+fn output_at(state: &Raven, point: Point<i32, Logical>) -> Option<Output> {
+    state
+        .space
+        .outputs()
+        .find(|output| {
+            state
+                .space
+                .output_geometry(output)
+                .is_some_and(|geo| geo.contains(point))
+        })
+        .cloned()
+}
+
+/// Assign outputs to newly laid out windows, and move floating windows dragged
+/// onto another output over to it.
+pub(super) fn track_windows(state: &mut Raven, windows: &[Window]) {
+    for window in windows {
+        let Some(surface) = window
+            .toplevel()
+            .map(|toplevel| toplevel.wl_surface().clone())
+        else {
+            continue;
+        };
+        let assigned = state
+            .window_outputs
+            .get(&surface)
+            .and_then(|name| output_named(state, name));
+        let target = match assigned {
+            None if state.window_outputs.contains_key(&surface) => continue,
+            None => state.floating_output_for_window(window),
+            Some(assigned) if state.is_window_floating(window) => state
+                .space
+                .element_geometry(window)
+                .and_then(|geo| output_at(state, geo.loc + geo.size.downscale(2).to_point()))
+                .filter(|output| *output != assigned),
+            Some(_) => continue,
+        };
+        if let Some(output) = target {
+            tracing::debug!(output = %output.name(), "window assigned to output");
+            state.window_outputs.insert(surface, output.name());
+        }
+    }
+}
+
+pub(super) fn forget_window(state: &mut Raven, surface: &WlSurface) {
+    state.window_outputs.remove(surface);
+}
@@ -0,0 +1,95 @@
+//! Main+Tab window switching in most-recently-used order. Every focus change
+//! moves the window to the front of an MRU stack; `cycle_windows` steps through
+//! the current workspace's windows in that order, raising each candidate, and
+//! focuses the one picked when the main key is released.
+
+use smithay::{desktop::Window, reexports::wayland_server::Resource, utils::SERIAL_COUNTER};
+
+use super::{Raven, WlSurface};
+use crate::backend::udev::RedrawCause;
+
+#[derive(Debug, Default)]
+pub(super) struct WindowSwitcher {
+    // Focused toplevels, most recent first.
+    recent: Vec<WlSurface>,
+    // Candidates of the cycle in progress and the index of the selected one.
+    cycle: Option<(Vec<Window>, usize)>,
+}
+
+pub(super) fn record_focus(state: &mut Raven, surface: &WlSurface) {
+    let recent = &mut state.window_switcher.recent;
+    recent.retain(|candidate| candidate != surface && candidate.is_alive());
+    recent.insert(0, surface.clone());
+}
+
+/// Windows of the current workspace, most recently focused first; windows never
+/// focused follow in workspace order.
+fn candidates(state: &Raven) -> Vec<Window> {
+    let workspace = &state.workspaces[state.current_workspace];
+    let mut windows: Vec<Window> = state
+        .window_switcher
+        .recent
+        .iter()
+        .filter_map(|surface| state.window_for_surface(surface))
+        .filter(|window| Raven::workspace_contains_window_entry(workspace, window))
+        .collect();
+    for window in workspace {
+        if !Raven::workspace_contains_window_entry(&windows, window) {
+            windows.push(window.clone());
+        }
+    }
+    windows
+}
+
+fn main_key_held(state: &Raven) -> bool {
+    state
+        .seat
+        .get_keyboard()
+        .is_some_and(|keyboard| state.config.main_key.matches(&keyboard.modifier_state()))
+}
+
+pub(super) fn cycle(state: &mut Raven, forward: bool) {
+    let (windows, index) = state
+        .window_switcher
+        .cycle
+        .take()
+        .unwrap_or_else(|| (candidates(state), 0));
+    if windows.len() < 2 {
+        return;
+    }
+    let step = if forward { 1 } else { windows.len() - 1 };
+    let index = (index + step) % windows.len();
+    state.raise_window_preserving_layer(&windows[index]);
+    state.window_switcher.cycle = Some((windows, index));
+    crate::backend::udev::queue_redraw_all(state, RedrawCause::Keyboard);
+
+    // Dispatched over IPC, or the main key already let go: nothing to wait for.
+    if !main_key_held(state) {
+        finish(state);
+    }
+}
+
+/// Focus the selected window once the main key is up.
+pub(super) fn key_released(state: &mut Raven) {
+    if state.window_switcher.cycle.is_some() && !main_key_held(state) {
+        finish(state);
+    }
+}
+
+fn finish(state: &mut Raven) {
+    let Some((windows, index)) = state.window_switcher.cycle.take() else {
+        return;
+    };
+    let window = &windows[index];
+    let Some(surface) = window
+        .toplevel()
+        .map(|toplevel| toplevel.wl_surface().clone())
+        .filter(|surface| surface.is_alive())
+    else {
+        return;
+    };
+    state.raise_window_preserving_layer(window);
+    state.set_keyboard_focus(Some(surface), SERIAL_COUNTER.next_serial());
+    // Candidates raised along the way were activated; only the picked one stays so.
+    state.sync_window_activation(Some(window));
+}
@@ -0,0 +1,115 @@
+//! Workspace switch animation. The workspace switched away from is unmapped at
+//! once, so the transition keeps its windows and where they were; the udev
+//! backend draws them sliding (or fading) out while the new workspace comes in.
+
+use std::time::{Duration, Instant};
+
+use smithay::{
+    desktop::Window,
+    output::Output,
+    reexports::calloop::timer::{TimeoutAction, Timer},
+    utils::{IsAlive, Logical, Point},
+};
+
+use crate::{backend::udev::RedrawCause, config::WorkspaceAnimation};
+
+use super::Raven;
+
+const DURATION: Duration = Duration::from_millis(220);
+// Roughly one animation step per frame at 60 Hz.
+const FRAME_INTERVAL: Duration = Duration::from_millis(16);
+
+pub(super) struct WorkspaceTransition {
+    started: Instant,
+    // 1.0 when the new workspace comes in from the right, -1.0 from the left.
+    direction: f64,
+    // The outgoing workspace's windows and their locations, bottom to top.
+    outgoing: Vec<(Window, Point<i32, Logical>)>,
+}
+
+/// The running workspace switch animation as drawn on one output.
+pub struct WorkspaceTransitionFrame {
+    /// Added to the location of the incoming workspace's windows.
+    pub incoming_offset: Point<i32, Logical>,
+    pub incoming_alpha: f32,
+    /// The outgoing workspace's windows at their location for this frame,
+    /// bottom to top.
+    pub outgoing: Vec<(Window, Point<i32, Logical>)>,
+    pub outgoing_alpha: f32,
+}
+
+/// Animate the switch from `previous` to `target`; `outgoing` are the windows
+/// just unmapped with the locations they had.
+pub(super) fn start(
+    state: &mut Raven,
+    previous: usize,
+    target: usize,
+    outgoing: Vec<(Window, Point<i32, Logical>)>,
+) {
+    if state.config.workspace_animation == WorkspaceAnimation::None {
+        return;
+    }
+
+    let direction = if target > previous { 1.0 } else { -1.0 };
+    // Switching again mid-animation starts over from the workspace left last;
+    // the running timer carries on with it.
+    let running = state.workspace_transition.is_some();
+    state.workspace_transition = Some(WorkspaceTransition {
+        started: Instant::now(),
+        direction,
+        outgoing,
+    });
+    if running {
+        return;
+    }
+
+    let timer = Timer::from_duration(FRAME_INTERVAL);
+    if let Err(err) = state.loop_handle.insert_source(timer, |_, _, state| {
+        let finished = state
+            .workspace_transition
+            .as_ref()
+            .is_none_or(|transition| transition.started.elapsed() >= DURATION);
+        if finished {
+            state.workspace_transition = None;
+        }
+        crate::backend::udev::queue_redraw_all(state, RedrawCause::Animation);
+        if finished {
+            TimeoutAction::Drop
+        } else {
+            TimeoutAction::ToDuration(FRAME_INTERVAL)
+        }
+    }) {
+        tracing::warn!("failed to schedule workspace animation: {err}");
+        state.workspace_transition = None;
+    }
+}
+
+pub(super) fn frame(state: &Raven, output: &Output) -> Option<WorkspaceTransitionFrame> {
+    let transition = state.workspace_transition.as_ref()?;
+    let output_geo = state.space.output_geometry(output)?;
+
+    let progress = (transition.started.elapsed().as_secs_f64() / DURATION.as_secs_f64()).min(1.0);
+    // Ease out so the new workspace settles into place.
+    let eased = 1.0 - (1.0 - progress).powi(3);
+    let (incoming_x, outgoing_x, incoming_alpha, outgoing_alpha) =
+        match state.config.workspace_animation {
+            WorkspaceAnimation::Crossfade => (0.0, 0.0, eased as f32, 1.0 - eased as f32),
+            WorkspaceAnimation::Slide | WorkspaceAnimation::None => {
+                let width = f64::from(output_geo.size.w) * transition.direction;
+                ((1.0 - eased) * width, -eased * width, 1.0, 1.0)
+            }
+        };
+
+    let outgoing_offset = Point::from((outgoing_x.round() as i32, 0));
+    Some(WorkspaceTransitionFrame {
+        incoming_offset: Point::from((incoming_x.round() as i32, 0)),
+        incoming_alpha,
+        outgoing: transition
+            .outgoing
+            .iter()
+            .filter(|(window, _)| window.alive())
+            .map(|(window, location)| (window.clone(), *location + outgoing_offset))
+            .collect(),
+        outgoing_alpha,
+    })
+}
@@ -1,6 +1,11 @@
 use smithay::desktop::Window;
 
-use crate::CompositorError;
+use crate::{
+    CompositorError,
+    backend::udev::RedrawCause,
+    config::{LastWindowClosed, MAX_WORKSPACES},
+    layout::WorkspaceLayout,
+};
 
 use super::Raven;
 
@@ -10,16 +15,63 @@ pub(super) fn workspace_index_for_window(state: &Raven, window: &Window) -> Opti
         .or_else(|| state.workspace_index_for_unmapped_window(window))
 }
 
+fn workspace_is_empty(state: &Raven, index: usize) -> bool {
+    state.workspaces[index].is_empty() && state.unmapped_workspaces[index].is_empty()
+}
+
+/// Add empty workspaces until `index` exists, for switching or moving windows
+/// past the current count.
+pub(super) fn ensure_workspace(state: &mut Raven, index: usize) -> Result<(), CompositorError> {
+    if index >= MAX_WORKSPACES {
+        return Err(CompositorError::Backend(format!(
+            "invalid workspace index {index} (at most {MAX_WORKSPACES} workspaces)"
+        )));
+    }
+    let layout_type = state.config.layout_type;
+    while state.workspaces.len() <= index {
+        state.workspaces.push(Vec::new());
+        state.unmapped_workspaces.push(Vec::new());
+        state.workspace_occupied.push(false);
+        state
+            .workspace_layouts
+            .push(WorkspaceLayout::new(layout_type));
+        state.fullscreen.owner_surfaces_by_workspace.push(None);
+    }
+    Ok(())
+}
+
+/// Drop trailing workspaces past `workspace_count` once they are empty and not
+/// focused. ext-workspace clients are told on the next refresh.
+pub(super) fn collect_empty_workspaces(state: &mut Raven) {
+    let mut count = state.workspaces.len();
+    while count > state.config.workspace_count
+        && count - 1 != state.current_workspace
+        && workspace_is_empty(state, count - 1)
+    {
+        count -= 1;
+    }
+    if count == state.workspaces.len() {
+        return;
+    }
+
+    state.workspaces.truncate(count);
+    state.unmapped_workspaces.truncate(count);
+    state.workspace_occupied.truncate(count);
+    state.workspace_layouts.truncate(count);
+    state.fullscreen.owner_surfaces_by_workspace.truncate(count);
+    state.workspace_history.retain(|&index| index < count);
+    state.scroll_offsets.retain(|(_, index), _| *index < count);
+    tracing::debug!(workspaces = count, "removed empty workspaces");
+    state.refresh_ext_workspace();
+    state.debug_assert_state_invariants("collect_empty_workspaces");
+}
+
 pub(super) fn move_window_to_workspace_internal(
     state: &mut Raven,
     window: &Window,
     target_workspace: usize,
 ) -> Result<(), CompositorError> {
-    if target_workspace >= state.workspaces.len() {
-        return Err(CompositorError::Backend(format!(
-            "invalid workspace index {target_workspace}"
-        )));
-    }
+    ensure_workspace(state, target_workspace)?;
 
     let source_mapped_workspace = state.workspace_index_for_mapped_window(window);
     let source_unmapped_workspace = state.workspace_index_for_unmapped_window(window);
@@ -100,11 +152,7 @@ pub(super) fn switch_workspace(
     state: &mut Raven,
     target_workspace: usize,
 ) -> Result<(), CompositorError> {
-    if target_workspace >= state.workspaces.len() {
-        return Err(CompositorError::Backend(format!(
-            "invalid workspace index {target_workspace}"
-        )));
-    }
+    ensure_workspace(state, target_workspace)?;
 
     if target_workspace == state.current_workspace {
         return Ok(());
@@ -112,14 +160,40 @@ pub(super) fn switch_workspace(
 
     state.prune_windows_without_live_client();
 
+    // Sticky windows stay mapped and move along to the new workspace.
     let current_windows = state.workspaces[state.current_workspace].clone();
+    let (sticky_windows, current_windows): (Vec<Window>, Vec<Window>) = current_windows
+        .into_iter()
+        .partition(|window| state.is_window_sticky(window));
+    let outgoing = state
+        .space
+        .elements()
+        .filter(|window| current_windows.contains(window))
+        .filter_map(|window| Some((window.clone(), state.space.element_location(window)?)))
+        .collect();
     for window in &current_windows {
         state.unmap_window(window);
     }
 
+    let previous_workspace = state.current_workspace;
+    state
+        .workspace_history
+        .retain(|&index| index != previous_workspace && index != target_workspace);
+    state.workspace_history.push(previous_workspace);
     state.current_workspace = target_workspace;
 
     let target_windows = state.workspaces[target_workspace].clone();
+    for window in &sticky_windows {
+        Raven::remove_window_from_workspace_list(&mut state.workspaces, window);
+        Raven::add_window_to_workspace_list(
+            &mut state.workspaces,
+            target_workspace,
+            window.clone(),
+        )?;
+        if let Some(surface_id) = Raven::window_surface_id(window) {
+            state.move_workspace_fullscreen_owner_surface(&surface_id, target_workspace);
+        }
+    }
     for window in target_windows {
         let mapped_now = state.map_window_to_initial_location_if_mappable(&window, false);
         if mapped_now
@@ -136,20 +210,49 @@ pub(super) fn switch_workspace(
     state.apply_layout()?;
     state.refocus_visible_window();
     state.refresh_ext_workspace();
-    crate::backend::udev::queue_redraw_all(state);
+    super::workspace_transition::start(state, previous_workspace, target_workspace, outgoing);
+    crate::backend::udev::queue_redraw_all(state, RedrawCause::Workspace);
+    super::events::workspace_switched(state, previous_workspace, target_workspace);
     state.debug_assert_state_invariants("switch_workspace");
     Ok(())
 }
 
+pub(super) fn switch_workspace_relative(
+    state: &mut Raven,
+    forward: bool,
+) -> Result<(), CompositorError> {
+    let count = state.workspaces.len();
+    let target = if forward {
+        (state.current_workspace + 1) % count
+    } else {
+        (state.current_workspace + count - 1) % count
+    };
+    switch_workspace(state, target)
+}
+
+/// The last entry of `workspace_history` is the workspace switched away from
+/// most recently, so repeating this toggles between two workspaces.
+pub(super) fn workspace_back_and_forth(state: &mut Raven) -> Result<(), CompositorError> {
+    let Some(&previous) = state.workspace_history.last() else {
+        return Ok(());
+    };
+    switch_workspace(state, previous)
+}
+
+/// `workspace next_empty`: the first empty workspace besides the current one,
+/// or a new one after the last.
+pub(super) fn switch_to_empty_workspace(state: &mut Raven) -> Result<(), CompositorError> {
+    let target = (0..state.workspaces.len())
+        .find(|&index| index != state.current_workspace && workspace_is_empty(state, index))
+        .unwrap_or(state.workspaces.len());
+    switch_workspace(state, target)
+}
+
 pub(super) fn move_focused_window_to_workspace(
     state: &mut Raven,
     target_workspace: usize,
 ) -> Result<(), CompositorError> {
-    if target_workspace >= state.workspaces.len() {
-        return Err(CompositorError::Backend(format!(
-            "invalid workspace index {target_workspace}"
-        )));
-    }
+    ensure_workspace(state, target_workspace)?;
 
     let Some(keyboard) = state.seat.get_keyboard() else {
         return Ok(());
@@ -184,3 +287,198 @@ pub(super) fn move_focused_window_to_workspace(
     state.refresh_ext_workspace();
     Ok(())
 }
+
+/// Close `window`, unless a `close_to_workspace` / `hide_on_close` rule matches it
+/// and `force` is unset: then it is moved to that workspace instead. A window that
+/// is already there closes for real, so a second close still gets rid of it.
+pub(super) fn close_window(
+    state: &mut Raven,
+    window: &Window,
+    force: bool,
+) -> Result<(), CompositorError> {
+    let hide_workspace = if force {
+        None
+    } else {
+        window
+            .toplevel()
+            .and_then(|toplevel| super::rules::surface_close_target(state, toplevel.wl_surface()))
+    };
+    let source_workspace = state
+        .workspace_index_for_window(window)
+        .unwrap_or(state.current_workspace);
+
+    let Some(target_workspace) = hide_workspace.filter(|target| *target != source_workspace) else {
+        tracing::info!(force, "Closing window");
+        if let Some(toplevel) = window.toplevel() {
+            toplevel.send_close();
+        } else {
+            #[cfg(feature = "xwayland")]
+            if let Some(x11) = window.x11_surface() {
+                let _ = x11.close();
+            }
+        }
+        return Ok(());
+    };
+
+    tracing::info!(
+        workspace = target_workspace + 1,
+        "Hiding window on close instead of closing it"
+    );
+    move_window_to_workspace_internal(state, window, target_workspace)?;
+    if source_workspace == state.current_workspace {
+        state.apply_layout()?;
+        state.refocus_visible_window();
+    }
+    state.refresh_ext_workspace();
+    Ok(())
+}
+
+fn permuted<T: Default>(mut items: Vec<T>, order: &[usize]) -> Vec<T> {
+    order
+        .iter()
+        .map(|&index| std::mem::take(&mut items[index]))
+        .collect()
+}
+
+/// Rearrange workspace contents so that index `i` holds what was at `order[i]`.
+///
+/// Windows stay mapped: the focused workspace takes its new index instead of
+/// changing what is on screen, so only indices reported to bars change.
+fn permute_workspaces(state: &mut Raven, order: &[usize]) {
+    let new_index = |old: usize| order.iter().position(|&index| index == old).unwrap_or(old);
+
+    state.workspaces = permuted(std::mem::take(&mut state.workspaces), order);
+    state.unmapped_workspaces = permuted(std::mem::take(&mut state.unmapped_workspaces), order);
+    // Occupancy moves with the windows so workspace hooks do not fire for a reorder.
+    state.workspace_occupied = permuted(std::mem::take(&mut state.workspace_occupied), order);
+    state.workspace_layouts = permuted(std::mem::take(&mut state.workspace_layouts), order);
+    state.fullscreen.owner_surfaces_by_workspace = permuted(
+        std::mem::take(&mut state.fullscreen.owner_surfaces_by_workspace),
+        order,
+    );
+    state.current_workspace = new_index(state.current_workspace);
+    for index in &mut state.workspace_history {
+        *index = new_index(*index);
+    }
+    state.scroll_offsets = std::mem::take(&mut state.scroll_offsets)
+        .into_iter()
+        .map(|((output, index), offset)| ((output, new_index(index)), offset))
+        .collect();
+    for index in state.autostart_workspace_by_surface.values_mut() {
+        *index = new_index(*index);
+    }
+
+    state.refresh_ext_workspace();
+    crate::backend::udev::queue_redraw_all(state, RedrawCause::Workspace);
+    state.debug_assert_state_invariants("permute_workspaces");
+}
+
+fn check_workspace_index(state: &Raven, index: usize) -> Result<(), CompositorError> {
+    if index >= state.workspaces.len() {
+        return Err(CompositorError::Backend(format!(
+            "invalid workspace index {index}"
+        )));
+    }
+    Ok(())
+}
+
+pub(super) fn swap_workspaces(
+    state: &mut Raven,
+    first: usize,
+    second: usize,
+) -> Result<(), CompositorError> {
+    check_workspace_index(state, first)?;
+    check_workspace_index(state, second)?;
+    if first == second {
+        return Ok(());
+    }
+
+    let mut order: Vec<usize> = (0..state.workspaces.len()).collect();
+    order.swap(first, second);
+    permute_workspaces(state, &order);
+    tracing::info!(first = first + 1, second = second + 1, "workspaces swapped");
+    Ok(())
+}
+
+/// Move workspace `from` to index `to`, shifting the workspaces in between by one.
+pub(super) fn reorder_workspace(
+    state: &mut Raven,
+    from: usize,
+    to: usize,
+) -> Result<(), CompositorError> {
+    check_workspace_index(state, from)?;
+    check_workspace_index(state, to)?;
+    if from == to {
+        return Ok(());
+    }
+
+    let mut order: Vec<usize> = (0..state.workspaces.len()).collect();
+    let moved = order.remove(from);
+    order.insert(to, moved);
+    permute_workspaces(state, &order);
+    tracing::info!(from = from + 1, to = to + 1, "workspace reordered");
+    Ok(())
+}
+
+pub(super) fn handle_last_window_closed(state: &mut Raven) {
+    let current = state.current_workspace;
+    if !state.workspaces[current].is_empty() {
+        return;
+    }
+
+    match state.config.last_window_closed.clone() {
+        LastWindowClosed::Stay => {}
+        LastWindowClosed::PreviousNonEmpty => {
+            let Some(target) = state
+                .workspace_history
+                .iter()
+                .rev()
+                .copied()
+                .find(|&index| index != current && !state.workspaces[index].is_empty())
+            else {
+                return;
+            };
+            if let Err(err) = switch_workspace(state, target) {
+                tracing::warn!("failed to leave emptied workspace {}: {err}", current + 1);
+            }
+        }
+        LastWindowClosed::Command(command) => {
+            tracing::info!(
+                workspace = current + 1,
+                command = %command,
+                "running last_window_closed command"
+            );
+            state.spawn_command_with_env(
+                &command,
+                &[("RAVEN_WORKSPACE", (current + 1).to_string())],
+            );
+        }
+    }
+}
+
+pub(super) fn run_workspace_hooks(state: &mut Raven) {
+    for index in 0..state.workspaces.len() {
+        let occupied = !state.workspaces[index].is_empty();
+        if state.workspace_occupied[index] == occupied {
+            continue;
+        }
+        state.workspace_occupied[index] = occupied;
+
+        let command = if occupied {
+            state.config.on_first_window_command(index)
+        } else {
+            state.config.on_workspace_empty_command(index)
+        };
+        let Some(command) = command.map(str::to_owned) else {
+            continue;
+        };
+
+        tracing::info!(
+            workspace = index + 1,
+            occupied,
+            command = %command,
+            "running workspace hook"
+        );
+        state.spawn_command_with_env(&command, &[("RAVEN_WORKSPACE", (index + 1).to_string())]);
+    }
+}
@@ -0,0 +1,125 @@
+//! Golden-image tests of the headless backend. Frames go through the same
+//! element pipeline as the udev backend, so these cover what a DRM output
+//! would show.
+//!
+//! Run with `cargo test --features test-capture`. Set `RAVEN_UPDATE_GOLDENS=1`
+//! to rewrite the images under `tests/golden` from the current output.
+
+#![cfg(feature = "test-capture")]
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use raven::{
+    Raven,
+    backend::{capture::OutputCapture, headless::HeadlessBackend},
+};
+use smithay::reexports::{calloop::EventLoop, wayland_server::Display};
+
+// Per channel, to absorb rounding differences between GL drivers.
+const TOLERANCE: u8 = 2;
+
+fn golden_path(name: &str) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("golden")
+        .join(format!("{name}.png"))
+}
+
+fn assert_matches_golden(capture: &OutputCapture, name: &str) {
+    let path = golden_path(name);
+    let (width, height) = (capture.size.w as u32, capture.size.h as u32);
+    if std::env::var_os("RAVEN_UPDATE_GOLDENS").is_some() {
+        image::RgbaImage::from_raw(width, height, capture.pixels.clone())
+            .expect("capture has the size it reports")
+            .save(&path)
+            .unwrap_or_else(|err| panic!("failed to write {}: {err}", path.display()));
+        return;
+    }
+
+    let golden = image::open(&path)
+        .unwrap_or_else(|err| panic!("failed to read {}: {err}", path.display()))
+        .into_rgba8();
+    assert_eq!(
+        golden.dimensions(),
+        (width, height),
+        "{name}: capture size differs from the golden image"
+    );
+    let mismatch = golden
+        .as_raw()
+        .chunks_exact(4)
+        .zip(capture.pixels.chunks_exact(4))
+        .position(|(expected, actual)| {
+            expected
+                .iter()
+                .zip(actual)
+                .any(|(expected, actual)| expected.abs_diff(*actual) > TOLERANCE)
+        });
+    if let Some(index) = mismatch {
+        let pixel = &capture.pixels[index * 4..index * 4 + 4];
+        let expected = &golden.as_raw()[index * 4..index * 4 + 4];
+        panic!(
+            "{name}: pixel ({}, {}) is {pixel:?}, golden image has {expected:?}",
+            index as u32 % width,
+            index as u32 / width,
+        );
+    }
+}
+
+// One test, so the environment set up for the compositor is not shared
+// between threads.
+#[test]
+fn headless_frames_match_goldens() {
+    let scratch = std::env::temp_dir().join(format!("raven-golden-{}", std::process::id()));
+    let runtime_dir = scratch.join("runtime");
+    let config_dir = scratch.join("config");
+    std::fs::create_dir_all(&runtime_dir).unwrap();
+    std::fs::create_dir_all(&config_dir).unwrap();
+    // SAFETY: no other thread of this test binary reads the environment.
+    unsafe {
+        std::env::set_var("XDG_RUNTIME_DIR", &runtime_dir);
+        std::env::set_var("XDG_CONFIG_HOME", &config_dir);
+    }
+
+    let mut backend = match HeadlessBackend::new() {
+        Ok(backend) => backend,
+        Err(err) => {
+            eprintln!("skipping headless golden tests: {err}");
+            return;
+        }
+    };
+    let mut event_loop: EventLoop<Raven> = EventLoop::try_new().unwrap();
+    let display = Display::new().unwrap();
+    let mut state = Raven::new(display, event_loop.handle(), event_loop.get_signal()).unwrap();
+    let output = backend
+        .add_output(&mut state, "HEADLESS-1", (64, 48), 1.0, (0, 0))
+        .unwrap();
+
+    // Nothing mapped: only the backdrop in `background_color`.
+    let capture = backend.render_output(&mut state, &output).unwrap();
+    assert_matches_golden(&capture, "background");
+
+    // The built-in wallpaper is stretched over the whole output.
+    let wallpaper = scratch.join("wallpaper.png");
+    image::RgbaImage::from_pixel(8, 8, image::Rgba([255, 0, 0, 255]))
+        .save(&wallpaper)
+        .unwrap();
+    state.config.wallpaper.enabled = true;
+    state.config.wallpaper.builtin = true;
+    state.config.wallpaper.image = wallpaper.display().to_string();
+    state.apply_wallpaper();
+    let deadline = Instant::now() + Duration::from_secs(5);
+    while state.output_wallpaper(&output).is_none() {
+        assert!(
+            Instant::now() < deadline,
+            "built-in wallpaper was never decoded"
+        );
+        event_loop
+            .dispatch(Some(Duration::from_millis(10)), &mut state)
+            .unwrap();
+    }
+    let capture = backend.render_output(&mut state, &output).unwrap();
+    assert_matches_golden(&capture, "wallpaper");
+
+    let _ = std::fs::remove_dir_all(&scratch);
+}